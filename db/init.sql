@@ -71,3 +71,1240 @@ CREATE TRIGGER update_users_updated_at
 --     FOR EACH ROW 
 --     WHEN (OLD.active IS DISTINCT FROM NEW.active AND NEW.active = TRUE)
 --     EXECUTE FUNCTION update_activated_at_column();
+
+-- Create lists table to group todos
+CREATE TABLE IF NOT EXISTS lists (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_lists_user_id ON lists(user_id);
+
+-- Create trigger to automatically update updated_at
+CREATE TRIGGER update_lists_updated_at
+    BEFORE UPDATE ON lists
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+-- Todos belong to a user and optionally to one of that user's lists
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS list_id INTEGER;
+ALTER TABLE todos ADD CONSTRAINT fk_todos_list_same_owner
+    FOREIGN KEY (list_id, user_id) REFERENCES lists(id, user_id)
+    ON UPDATE CASCADE ON DELETE SET NULL (list_id);
+
+CREATE INDEX IF NOT EXISTS idx_todos_user_id ON todos(user_id);
+CREATE INDEX IF NOT EXISTS idx_todos_list_id ON todos(list_id);
+
+-- Create tags table, tag names are unique per user
+CREATE TABLE IF NOT EXISTS tags (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(64) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (user_id, name)
+);
+
+-- Create join table between todos and tags
+CREATE TABLE IF NOT EXISTS todo_tags (
+    todo_id INTEGER NOT NULL REFERENCES todos(id) ON DELETE CASCADE,
+    tag_id INTEGER NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+    PRIMARY KEY (todo_id, tag_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_todo_tags_tag_id ON todo_tags(tag_id);
+
+-- Create list ownership transfers, accepted by the recipient
+CREATE TABLE IF NOT EXISTS list_transfers (
+    id SERIAL PRIMARY KEY,
+    list_id INTEGER NOT NULL REFERENCES lists(id) ON DELETE CASCADE,
+    from_user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    to_user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    status VARCHAR(16) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'accepted', 'declined')),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    resolved_at TIMESTAMP WITH TIME ZONE DEFAULT NULL
+);
+
+-- Only one pending transfer per list
+CREATE UNIQUE INDEX IF NOT EXISTS idx_list_transfers_pending
+    ON list_transfers(list_id) WHERE status = 'pending';
+CREATE INDEX IF NOT EXISTS idx_list_transfers_to_user_id ON list_transfers(to_user_id);
+
+-- Guest accounts are limited, expiring users invited to a single list
+ALTER TABLE users ADD COLUMN IF NOT EXISTS is_guest BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS guest_expires_at TIMESTAMP WITH TIME ZONE DEFAULT NULL;
+
+-- Create guest invitations, the token is stored hashed
+CREATE TABLE IF NOT EXISTS guest_invitations (
+    id SERIAL PRIMARY KEY,
+    list_id INTEGER NOT NULL REFERENCES lists(id) ON DELETE CASCADE,
+    invited_by INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    guest_user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    email VARCHAR(255) NOT NULL,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    accepted_at TIMESTAMP WITH TIME ZONE DEFAULT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_guest_invitations_guest_user_id ON guest_invitations(guest_user_id);
+
+-- Todos can have a due date and repeat following an iCalendar RRULE
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS due_at TIMESTAMP WITH TIME ZONE DEFAULT NULL;
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS recurrence TEXT DEFAULT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_todos_due_at ON todos(due_at);
+
+-- Create checklist items under a todo, ordered by position
+CREATE TABLE IF NOT EXISTS todo_items (
+    id SERIAL PRIMARY KEY,
+    todo_id INTEGER NOT NULL REFERENCES todos(id) ON DELETE CASCADE,
+    title VARCHAR(255) NOT NULL,
+    completed BOOLEAN NOT NULL DEFAULT FALSE,
+    position INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_todo_items_todo_id ON todo_items(todo_id, position);
+
+-- Create trigger to automatically update updated_at
+CREATE TRIGGER update_todo_items_updated_at
+    BEFORE UPDATE ON todo_items
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+-- Deleted todos stay in the trash until restored or purged
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP WITH TIME ZONE DEFAULT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_todos_deleted_at ON todos(deleted_at) WHERE deleted_at IS NOT NULL;
+
+-- Archived todos and lists are hidden from the default listings
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS archived BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE lists ADD COLUMN IF NOT EXISTS archived BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Todos move through a status workflow instead of a completed flag
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS status VARCHAR(16) NOT NULL DEFAULT 'backlog'
+    CHECK (status IN ('backlog', 'in_progress', 'blocked', 'done', 'cancelled'));
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS status_changed_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP;
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS completed_at TIMESTAMP WITH TIME ZONE DEFAULT NULL;
+UPDATE todos SET status = 'done', completed_at = updated_at WHERE completed;
+DROP INDEX IF EXISTS idx_todos_completed;
+ALTER TABLE todos DROP COLUMN IF EXISTS completed;
+
+CREATE INDEX IF NOT EXISTS idx_todos_status ON todos(status);
+
+-- Offline sync: every todo write bumps the todo version and moves it to the end of the
+-- sync sequence, trashed todos are returned as tombstones
+CREATE SEQUENCE IF NOT EXISTS todo_sync_seq;
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS version BIGINT NOT NULL DEFAULT 1;
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS sync_seq BIGINT NOT NULL DEFAULT nextval('todo_sync_seq');
+
+CREATE INDEX IF NOT EXISTS idx_todos_user_sync_seq ON todos(user_id, sync_seq);
+
+CREATE OR REPLACE FUNCTION bump_todo_sync_version()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.version = OLD.version + 1;
+    NEW.sync_seq = nextval('todo_sync_seq');
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER bump_todos_sync_version
+    BEFORE UPDATE ON todos
+    FOR EACH ROW
+    EXECUTE FUNCTION bump_todo_sync_version();
+
+-- Files attached to todos, the content lives in the configured storage backend
+CREATE TABLE IF NOT EXISTS todo_attachments (
+    id SERIAL PRIMARY KEY,
+    todo_id INTEGER NOT NULL REFERENCES todos(id) ON DELETE CASCADE,
+    file_name VARCHAR(255) NOT NULL,
+    content_type VARCHAR(255) NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    storage_key TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_todo_attachments_todo_id ON todo_attachments(todo_id);
+
+-- Change events of todos, lists and tags, polled by clients through GET /changes.
+-- No foreign key on user_id: deleting a user cascades into deletion events of its rows.
+CREATE TABLE IF NOT EXISTS change_events (
+    seq BIGSERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    entity VARCHAR(8) NOT NULL CHECK (entity IN ('todo', 'list', 'tag')),
+    entity_id INTEGER NOT NULL,
+    action VARCHAR(8) NOT NULL CHECK (action IN ('created', 'updated', 'deleted')),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_change_events_user_seq ON change_events(user_id, seq);
+
+-- Records a change of the row, the entity name is the trigger argument. Moving a row to
+-- another user is a deletion for the previous owner, trashing a todo is a deletion too.
+CREATE OR REPLACE FUNCTION record_change_event()
+RETURNS TRIGGER AS $$
+DECLARE
+    entity_name VARCHAR(8) := TG_ARGV[0];
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        INSERT INTO change_events (user_id, entity, entity_id, action)
+        VALUES (NEW.user_id, entity_name, NEW.id, 'created');
+    ELSIF TG_OP = 'DELETE' THEN
+        INSERT INTO change_events (user_id, entity, entity_id, action)
+        VALUES (OLD.user_id, entity_name, OLD.id, 'deleted');
+    ELSIF OLD.user_id <> NEW.user_id THEN
+        INSERT INTO change_events (user_id, entity, entity_id, action)
+        VALUES (OLD.user_id, entity_name, OLD.id, 'deleted'),
+               (NEW.user_id, entity_name, NEW.id, 'created');
+    ELSIF entity_name = 'todo' AND OLD.deleted_at IS NULL AND NEW.deleted_at IS NOT NULL THEN
+        INSERT INTO change_events (user_id, entity, entity_id, action)
+        VALUES (NEW.user_id, entity_name, NEW.id, 'deleted');
+    ELSIF entity_name = 'todo' AND OLD.deleted_at IS NOT NULL AND NEW.deleted_at IS NULL THEN
+        INSERT INTO change_events (user_id, entity, entity_id, action)
+        VALUES (NEW.user_id, entity_name, NEW.id, 'created');
+    ELSE
+        INSERT INTO change_events (user_id, entity, entity_id, action)
+        VALUES (NEW.user_id, entity_name, NEW.id, 'updated');
+    END IF;
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER record_todos_insert_delete
+    AFTER INSERT OR DELETE ON todos
+    FOR EACH ROW
+    EXECUTE FUNCTION record_change_event('todo');
+
+CREATE TRIGGER record_todos_update
+    AFTER UPDATE ON todos
+    FOR EACH ROW
+    WHEN (OLD.* IS DISTINCT FROM NEW.*)
+    EXECUTE FUNCTION record_change_event('todo');
+
+CREATE TRIGGER record_lists_insert_delete
+    AFTER INSERT OR DELETE ON lists
+    FOR EACH ROW
+    EXECUTE FUNCTION record_change_event('list');
+
+CREATE TRIGGER record_lists_update
+    AFTER UPDATE ON lists
+    FOR EACH ROW
+    WHEN (OLD.* IS DISTINCT FROM NEW.*)
+    EXECUTE FUNCTION record_change_event('list');
+
+-- Re-creating an existing tag name is an upsert that doesn't change the row
+CREATE TRIGGER record_tags_insert_delete
+    AFTER INSERT OR DELETE ON tags
+    FOR EACH ROW
+    EXECUTE FUNCTION record_change_event('tag');
+
+CREATE TRIGGER record_tags_update
+    AFTER UPDATE ON tags
+    FOR EACH ROW
+    WHEN (OLD.* IS DISTINCT FROM NEW.*)
+    EXECUTE FUNCTION record_change_event('tag');
+
+-- Users a list is shared with, editors may change its todos while viewers only read them
+CREATE TABLE IF NOT EXISTS list_members (
+    list_id INTEGER NOT NULL REFERENCES lists(id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role VARCHAR(8) NOT NULL CHECK (role IN ('viewer', 'editor')),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (list_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_list_members_user_id ON list_members(user_id);
+
+-- Tombstones of hard-deleted todos, lists and tags, keyed by the seq of their deletion
+-- event. Kept for TOMBSTONE_RETENTION_DAYS, pruned with the change events by the purge job.
+CREATE TABLE IF NOT EXISTS tombstones (
+    seq BIGINT PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    entity VARCHAR(8) NOT NULL CHECK (entity IN ('todo', 'list', 'tag')),
+    entity_id INTEGER NOT NULL,
+    deleted_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_tombstones_user_seq ON tombstones(user_id, seq);
+CREATE INDEX IF NOT EXISTS idx_tombstones_deleted_at ON tombstones(deleted_at);
+CREATE INDEX IF NOT EXISTS idx_change_events_created_at ON change_events(created_at);
+
+-- Hard deletions now also leave a tombstone
+CREATE OR REPLACE FUNCTION record_change_event()
+RETURNS TRIGGER AS $$
+DECLARE
+    entity_name VARCHAR(8) := TG_ARGV[0];
+    event_seq BIGINT;
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        INSERT INTO change_events (user_id, entity, entity_id, action)
+        VALUES (NEW.user_id, entity_name, NEW.id, 'created');
+    ELSIF TG_OP = 'DELETE' THEN
+        INSERT INTO change_events (user_id, entity, entity_id, action)
+        VALUES (OLD.user_id, entity_name, OLD.id, 'deleted')
+        RETURNING seq INTO event_seq;
+        INSERT INTO tombstones (seq, user_id, entity, entity_id)
+        VALUES (event_seq, OLD.user_id, entity_name, OLD.id);
+    ELSIF OLD.user_id <> NEW.user_id THEN
+        INSERT INTO change_events (user_id, entity, entity_id, action)
+        VALUES (OLD.user_id, entity_name, OLD.id, 'deleted'),
+               (NEW.user_id, entity_name, NEW.id, 'created');
+    ELSIF entity_name = 'todo' AND OLD.deleted_at IS NULL AND NEW.deleted_at IS NOT NULL THEN
+        INSERT INTO change_events (user_id, entity, entity_id, action)
+        VALUES (NEW.user_id, entity_name, NEW.id, 'deleted');
+    ELSIF entity_name = 'todo' AND OLD.deleted_at IS NOT NULL AND NEW.deleted_at IS NULL THEN
+        INSERT INTO change_events (user_id, entity, entity_id, action)
+        VALUES (NEW.user_id, entity_name, NEW.id, 'created');
+    ELSE
+        INSERT INTO change_events (user_id, entity, entity_id, action)
+        VALUES (NEW.user_id, entity_name, NEW.id, 'updated');
+    END IF;
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+-- Workspaces group the lists and todos of a team, every user has a personal one
+CREATE TABLE IF NOT EXISTS workspaces (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    -- Set on personal workspaces, deleted with their user
+    personal_user_id INTEGER UNIQUE REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS workspace_members (
+    workspace_id INTEGER NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role VARCHAR(8) NOT NULL CHECK (role IN ('owner', 'member')),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (workspace_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_workspace_members_user_id ON workspace_members(user_id);
+
+-- Invitations to join a workspace, accepted by the user owning the email.
+-- The token is stored hashed.
+CREATE TABLE IF NOT EXISTS workspace_invitations (
+    id SERIAL PRIMARY KEY,
+    workspace_id INTEGER NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    invited_by INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    email VARCHAR(255) NOT NULL,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    accepted_at TIMESTAMP WITH TIME ZONE DEFAULT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_workspace_invitations_workspace_id
+    ON workspace_invitations(workspace_id);
+
+-- New users start with their personal workspace
+CREATE OR REPLACE FUNCTION create_personal_workspace()
+RETURNS TRIGGER AS $$
+DECLARE
+    new_workspace_id INTEGER;
+BEGIN
+    INSERT INTO workspaces (name, personal_user_id)
+    VALUES ('Personal', NEW.id)
+    RETURNING id INTO new_workspace_id;
+    INSERT INTO workspace_members (workspace_id, user_id, role)
+    VALUES (new_workspace_id, NEW.id, 'owner');
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER create_users_personal_workspace
+    AFTER INSERT ON users
+    FOR EACH ROW
+    EXECUTE FUNCTION create_personal_workspace();
+
+-- Existing users get their personal workspace, holding their lists and todos
+INSERT INTO workspaces (name, personal_user_id)
+SELECT 'Personal', u.id FROM users u
+WHERE NOT EXISTS(SELECT 1 FROM workspaces w WHERE w.personal_user_id = u.id);
+
+INSERT INTO workspace_members (workspace_id, user_id, role)
+SELECT id, personal_user_id, 'owner' FROM workspaces WHERE personal_user_id IS NOT NULL
+ON CONFLICT (workspace_id, user_id) DO NOTHING;
+
+ALTER TABLE lists ADD COLUMN IF NOT EXISTS workspace_id INTEGER REFERENCES workspaces(id) ON DELETE CASCADE;
+UPDATE lists l SET workspace_id = w.id
+FROM workspaces w WHERE w.personal_user_id = l.user_id AND l.workspace_id IS NULL;
+ALTER TABLE lists ALTER COLUMN workspace_id SET NOT NULL;
+ALTER TABLE lists ADD CONSTRAINT uq_lists_id_workspace UNIQUE (id, workspace_id);
+
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS workspace_id INTEGER REFERENCES workspaces(id) ON DELETE CASCADE;
+UPDATE todos t SET workspace_id = w.id
+FROM workspaces w WHERE w.personal_user_id = t.user_id AND t.workspace_id IS NULL;
+ALTER TABLE todos ALTER COLUMN workspace_id SET NOT NULL;
+
+-- A todo stays in the workspace of its list
+ALTER TABLE todos ADD CONSTRAINT fk_todos_list_same_workspace
+    FOREIGN KEY (list_id, workspace_id) REFERENCES lists(id, workspace_id)
+    ON DELETE SET NULL (list_id);
+
+CREATE INDEX IF NOT EXISTS idx_lists_workspace_id ON lists(workspace_id);
+CREATE INDEX IF NOT EXISTS idx_todos_workspace_id ON todos(workspace_id);
+
+-- Outbox of the domain events, written by triggers in the transaction changing the data
+-- so events are never lost nor published for rolled back changes. The relay worker
+-- delivers them in order and marks them published, or failed after too many attempts.
+CREATE TABLE IF NOT EXISTS outbox_events (
+    id BIGSERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    workspace_id INTEGER NOT NULL,
+    event_type VARCHAR(32) NOT NULL CHECK (event_type IN ('todo.created', 'todo.completed')),
+    payload JSONB NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT DEFAULT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    published_at TIMESTAMP WITH TIME ZONE DEFAULT NULL,
+    failed_at TIMESTAMP WITH TIME ZONE DEFAULT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_outbox_events_pending
+    ON outbox_events(id) WHERE published_at IS NULL AND failed_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_outbox_events_published_at ON outbox_events(published_at);
+
+CREATE OR REPLACE FUNCTION record_outbox_event()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO outbox_events (user_id, workspace_id, event_type, payload)
+    VALUES (
+        NEW.user_id,
+        NEW.workspace_id,
+        TG_ARGV[0],
+        jsonb_build_object(
+            'id', NEW.id,
+            'list_id', NEW.list_id,
+            'title', NEW.title,
+            'status', NEW.status,
+            'due_at', NEW.due_at,
+            'completed_at', NEW.completed_at
+        )
+    );
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER record_todos_created_outbox
+    AFTER INSERT ON todos
+    FOR EACH ROW
+    EXECUTE FUNCTION record_outbox_event('todo.created');
+
+CREATE TRIGGER record_todos_completed_outbox
+    AFTER UPDATE OF status ON todos
+    FOR EACH ROW
+    WHEN (OLD.status <> 'done' AND NEW.status = 'done')
+    EXECUTE FUNCTION record_outbox_event('todo.completed');
+
+-- Edits of the todo title, description and due date, one row per changed field.
+-- The editor is read from the `app.actor_id` setting of the transaction, NULL when the
+-- change doesn't come from a user. Due dates are stored as RFC 3339 UTC text.
+CREATE TABLE IF NOT EXISTS todo_history (
+    id BIGSERIAL PRIMARY KEY,
+    todo_id INTEGER NOT NULL REFERENCES todos(id) ON DELETE CASCADE,
+    version BIGINT NOT NULL,
+    changed_by INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    field VARCHAR(16) NOT NULL CHECK (field IN ('title', 'description', 'due_at')),
+    old_value TEXT,
+    new_value TEXT,
+    changed_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_todo_history_todo_version ON todo_history(todo_id, version);
+
+CREATE OR REPLACE FUNCTION record_todo_history()
+RETURNS TRIGGER AS $$
+DECLARE
+    actor_id INTEGER := NULLIF(current_setting('app.actor_id', true), '')::INTEGER;
+BEGIN
+    IF OLD.title IS DISTINCT FROM NEW.title THEN
+        INSERT INTO todo_history (todo_id, version, changed_by, field, old_value, new_value)
+        VALUES (NEW.id, NEW.version, actor_id, 'title', OLD.title, NEW.title);
+    END IF;
+    IF OLD.description IS DISTINCT FROM NEW.description THEN
+        INSERT INTO todo_history (todo_id, version, changed_by, field, old_value, new_value)
+        VALUES (NEW.id, NEW.version, actor_id, 'description', OLD.description, NEW.description);
+    END IF;
+    IF OLD.due_at IS DISTINCT FROM NEW.due_at THEN
+        INSERT INTO todo_history (todo_id, version, changed_by, field, old_value, new_value)
+        VALUES (
+            NEW.id, NEW.version, actor_id, 'due_at',
+            to_char(OLD.due_at AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"'),
+            to_char(NEW.due_at AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"')
+        );
+    END IF;
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER record_todos_history
+    AFTER UPDATE OF title, description, due_at ON todos
+    FOR EACH ROW
+    EXECUTE FUNCTION record_todo_history();
+
+-- Reminders of todos, dispatched by the reminder worker once `remind_at` is reached.
+-- Reminders of todos closed or deleted before then are cancelled instead.
+CREATE TABLE IF NOT EXISTS reminders (
+    id SERIAL PRIMARY KEY,
+    todo_id INTEGER NOT NULL REFERENCES todos(id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    remind_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'sent', 'cancelled', 'failed')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    sent_at TIMESTAMP WITH TIME ZONE DEFAULT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_reminders_todo_id ON reminders(todo_id);
+CREATE INDEX IF NOT EXISTS idx_reminders_pending ON reminders(remind_at) WHERE status = 'pending';
+
+-- Trigram similarity of todo titles, used to suggest duplicates before creating a todo
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX IF NOT EXISTS idx_todos_title_trgm ON todos USING GIN (title gin_trgm_ops);
+
+-- Email verification, users created before it are considered verified
+ALTER TABLE users ADD COLUMN IF NOT EXISTS email_verified_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP;
+ALTER TABLE users ALTER COLUMN email_verified_at SET DEFAULT NULL;
+
+-- Pending email verifications, the token is stored hashed
+CREATE TABLE IF NOT EXISTS email_verifications (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_email_verifications_user_id ON email_verifications(user_id);
+
+-- Open todos are reported stale from the time they last changed
+CREATE INDEX IF NOT EXISTS idx_todos_user_updated_at ON todos(user_id, updated_at) WHERE deleted_at IS NULL;
+
+-- Users emailed a monthly review of their stale todos, a todo being stale after `days`
+CREATE TABLE IF NOT EXISTS stale_report_subscriptions (
+    user_id INTEGER PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    days INTEGER NOT NULL CHECK (days > 0),
+    -- Start of the current month, the next review is sent a month after it
+    last_notified_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Open todos entering (+1) and leaving (-1) each list, the daily burndown of a list is
+-- rebuilt from them. A todo is open in its list while its status is open and it is not
+-- deleted. No foreign key on list_id: moving the todos out of a deleted list records
+-- their leave while the list is gone.
+CREATE TABLE IF NOT EXISTS todo_list_events (
+    id BIGSERIAL PRIMARY KEY,
+    list_id INTEGER NOT NULL,
+    todo_id INTEGER NOT NULL,
+    delta SMALLINT NOT NULL CHECK (delta IN (-1, 1)),
+    occurred_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_todo_list_events_list ON todo_list_events(list_id, occurred_at);
+
+-- Seed the events of the todos created before the burndown, as if each todo had always
+-- been in its current list
+INSERT INTO todo_list_events (list_id, todo_id, delta, occurred_at)
+SELECT list_id, id, delta, occurred_at FROM (
+    SELECT list_id, id, 1 AS delta, COALESCE(created_at, NOW()) AS occurred_at
+    FROM todos WHERE list_id IS NOT NULL
+    UNION ALL
+    SELECT list_id, id, -1, COALESCE(deleted_at, completed_at, status_changed_at, NOW())
+    FROM todos
+    WHERE list_id IS NOT NULL
+        AND (deleted_at IS NOT NULL OR status NOT IN ('backlog', 'in_progress', 'blocked'))
+) seed
+WHERE NOT EXISTS (SELECT 1 FROM todo_list_events);
+
+CREATE OR REPLACE FUNCTION record_todo_list_event()
+RETURNS TRIGGER AS $$
+DECLARE
+    old_list INTEGER;
+    new_list INTEGER;
+BEGIN
+    IF TG_OP = 'UPDATE' THEN
+        IF OLD.deleted_at IS NULL AND OLD.status IN ('backlog', 'in_progress', 'blocked') THEN
+            old_list := OLD.list_id;
+        END IF;
+    END IF;
+    IF NEW.deleted_at IS NULL AND NEW.status IN ('backlog', 'in_progress', 'blocked') THEN
+        new_list := NEW.list_id;
+    END IF;
+
+    IF old_list IS DISTINCT FROM new_list THEN
+        IF old_list IS NOT NULL THEN
+            INSERT INTO todo_list_events (list_id, todo_id, delta) VALUES (old_list, NEW.id, -1);
+        END IF;
+        IF new_list IS NOT NULL THEN
+            INSERT INTO todo_list_events (list_id, todo_id, delta) VALUES (new_list, NEW.id, 1);
+        END IF;
+    END IF;
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER record_todos_list_events
+    AFTER INSERT OR UPDATE OF status, list_id, deleted_at ON todos
+    FOR EACH ROW
+    EXECUTE FUNCTION record_todo_list_event();
+
+-- Reminders relative to the todo due date, `offset_minutes` before it. They follow the
+-- due date and are resolved by the reminder worker at dispatch time.
+ALTER TABLE reminders ADD COLUMN IF NOT EXISTS offset_minutes INTEGER DEFAULT NULL
+    CHECK (offset_minutes >= 0);
+ALTER TABLE reminders ALTER COLUMN remind_at DROP NOT NULL;
+ALTER TABLE reminders ADD CONSTRAINT chk_reminders_time
+    CHECK ((remind_at IS NULL) <> (offset_minutes IS NULL));
+
+-- Moving the due date of a todo re-arms its relative reminders already sent or failed
+CREATE OR REPLACE FUNCTION rearm_relative_reminders()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.due_at IS DISTINCT FROM OLD.due_at THEN
+        UPDATE reminders
+        SET status = 'pending', attempts = 0, last_error = NULL, sent_at = NULL
+        WHERE todo_id = NEW.id AND offset_minutes IS NOT NULL
+          AND status IN ('sent', 'failed');
+    END IF;
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER rearm_todos_relative_reminders
+    AFTER UPDATE OF due_at ON todos
+    FOR EACH ROW
+    EXECUTE FUNCTION rearm_relative_reminders();
+
+-- Sessions of the users, one per login. Their tokens carry the session id and are
+-- rejected once the session is deleted. Expired sessions are dropped on the next login.
+CREATE TABLE IF NOT EXISTS sessions (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    user_agent TEXT,
+    ip_address VARCHAR(45),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    last_used_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_sessions_user_id ON sessions(user_id);
+
+-- Preferences of the users. Non-critical reminders due during the quiet hours, read in
+-- the user timezone, are held until the window ends.
+CREATE TABLE IF NOT EXISTS user_preferences (
+    user_id INTEGER PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    timezone VARCHAR(64) NOT NULL DEFAULT 'UTC',
+    quiet_start TIME DEFAULT NULL,
+    quiet_end TIME DEFAULT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    CHECK ((quiet_start IS NULL) = (quiet_end IS NULL))
+);
+
+ALTER TABLE reminders ADD COLUMN IF NOT EXISTS critical BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Formatting of the dates and numbers written for the user. `week_start` and
+-- `time_format` are NULL to follow the locale.
+ALTER TABLE user_preferences ADD COLUMN IF NOT EXISTS locale VARCHAR(16) NOT NULL DEFAULT 'en-US';
+ALTER TABLE user_preferences ADD COLUMN IF NOT EXISTS week_start VARCHAR(16) DEFAULT NULL
+    CHECK (week_start IN ('saturday', 'sunday', 'monday'));
+ALTER TABLE user_preferences ADD COLUMN IF NOT EXISTS time_format VARCHAR(8) DEFAULT NULL
+    CHECK (time_format IN ('12h', '24h'));
+
+-- TOTP second factor of the users, enabled once the setup is confirmed with a code.
+-- The secret is kept as is since it is needed to check the codes. `last_used_step`
+-- refuses a code used twice.
+CREATE TABLE IF NOT EXISTS user_two_factor (
+    user_id INTEGER PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    secret VARCHAR(64) NOT NULL,
+    enabled_at TIMESTAMP WITH TIME ZONE DEFAULT NULL,
+    last_used_step BIGINT DEFAULT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Single use recovery codes replacing the authenticator app, stored hashed
+CREATE TABLE IF NOT EXISTS two_factor_recovery_codes (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    code_hash VARCHAR(64) NOT NULL,
+    UNIQUE (user_id, code_hash)
+);
+
+-- Pre-auth tokens of the logins waiting for their second factor, stored hashed
+CREATE TABLE IF NOT EXISTS two_factor_challenges (
+    token_hash VARCHAR(64) PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+-- Todos delegated by their owner to another member of the workspace. The assignment
+-- waits in the review queue of the assignee until accepted or declined, a todo has at
+-- most one pending or accepted assignment. Accepted assignees can edit the todo.
+CREATE TABLE IF NOT EXISTS todo_assignments (
+    id SERIAL PRIMARY KEY,
+    todo_id INTEGER NOT NULL REFERENCES todos(id) ON DELETE CASCADE,
+    assigner_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    assignee_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    status VARCHAR(10) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'accepted', 'declined')),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    responded_at TIMESTAMP WITH TIME ZONE DEFAULT NULL,
+    CHECK (assigner_id <> assignee_id)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_todo_assignments_open
+    ON todo_assignments(todo_id) WHERE status IN ('pending', 'accepted');
+CREATE INDEX IF NOT EXISTS idx_todo_assignments_assignee
+    ON todo_assignments(assignee_id, status);
+
+-- Method of the first factor of the logins waiting for their second factor, RFC 8176
+ALTER TABLE two_factor_challenges
+    ADD COLUMN IF NOT EXISTS first_factor VARCHAR(8) NOT NULL DEFAULT 'pwd';
+
+-- Pending OAuth logins, the state sent to the provider and checked on the callback,
+-- stored hashed. Expired states are dropped when a login starts.
+CREATE TABLE IF NOT EXISTS oauth_states (
+    state_hash VARCHAR(64) PRIMARY KEY,
+    provider VARCHAR(16) NOT NULL,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+-- Accounts of the users at the identity providers, found by the provider user id.
+-- Accounts are linked on their first login to the user with the same verified email.
+CREATE TABLE IF NOT EXISTS oauth_accounts (
+    provider VARCHAR(16) NOT NULL,
+    subject VARCHAR(255) NOT NULL,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (provider, subject)
+);
+
+CREATE INDEX IF NOT EXISTS idx_oauth_accounts_user_id ON oauth_accounts(user_id);
+
+-- Work in progress limits of the status columns of the lists. Todos can't be created,
+-- moved or attached to a list into a column holding `max_todos` unarchived todos.
+CREATE TABLE IF NOT EXISTS list_wip_limits (
+    list_id INTEGER NOT NULL REFERENCES lists(id) ON DELETE CASCADE,
+    status VARCHAR(16) NOT NULL
+        CHECK (status IN ('backlog', 'in_progress', 'blocked', 'done', 'cancelled')),
+    max_todos INTEGER NOT NULL CHECK (max_todos > 0),
+    PRIMARY KEY (list_id, status)
+);
+
+CREATE INDEX IF NOT EXISTS idx_todos_list_status ON todos(list_id, status);
+
+-- Administrators of the instance, granted in database:
+-- UPDATE users SET is_admin = TRUE WHERE username = '...';
+ALTER TABLE users ADD COLUMN IF NOT EXISTS is_admin BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Terms refused in the list names, matched as whole words regardless of case
+CREATE TABLE IF NOT EXISTS banned_terms (
+    id SERIAL PRIMARY KEY,
+    term VARCHAR(100) NOT NULL,
+    created_by INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_banned_terms_term ON banned_terms(LOWER(term));
+
+-- Content flagged by the external moderation API, kept until an administrator reviews
+-- it. Removed content is replaced in place.
+CREATE TABLE IF NOT EXISTS moderation_flags (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    content_type VARCHAR(16) NOT NULL CHECK (content_type IN ('list_name')),
+    content_id INTEGER NOT NULL,
+    content TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'dismissed', 'removed')),
+    reviewed_by INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    reviewed_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_moderation_flags_status ON moderation_flags(status, created_at);
+
+-- Audit log of the sensitive actions. `user_id` is the account concerned and `actor_id`
+-- the user who acted, without foreign keys so the entries outlive deleted accounts.
+CREATE TABLE IF NOT EXISTS audit_log (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER,
+    actor_id INTEGER,
+    action VARCHAR(32) NOT NULL,
+    ip_address VARCHAR(45) NOT NULL,
+    user_agent TEXT,
+    details TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_user_id ON audit_log(user_id, id);
+
+-- Account-level webhooks receiving the security events of their user, read from the
+-- audit log. `last_audit_id` is the last entry delivered, a new webhook starts after the
+-- existing entries.
+CREATE TABLE IF NOT EXISTS security_webhooks (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    secret VARCHAR(64) NOT NULL,
+    last_audit_id INTEGER NOT NULL DEFAULT 0,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_security_webhooks_user_id ON security_webhooks(user_id);
+
+-- Todos deferred to a later date, hidden from the active views until the scheduler
+-- activates them by clearing the date
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS scheduled_for TIMESTAMP WITH TIME ZONE DEFAULT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_todos_scheduled_for ON todos(scheduled_for)
+    WHERE scheduled_for IS NOT NULL;
+
+-- Devices the accounts signed in from, as a hash of the user agent and address network.
+-- A login from a device not listed is emailed to the user.
+CREATE TABLE IF NOT EXISTS known_devices (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    fingerprint VARCHAR(64) NOT NULL,
+    first_seen_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    last_seen_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (user_id, fingerprint)
+);
+
+-- Email addresses of the lists, `alias@INBOUND_EMAIL_DOMAIN`. Emails sent to them by the
+-- list owner or an allowed sender (an address or `@domain`) become todos of the list.
+CREATE TABLE IF NOT EXISTS list_inbound_addresses (
+    list_id INTEGER PRIMARY KEY REFERENCES lists(id) ON DELETE CASCADE,
+    alias VARCHAR(64) NOT NULL UNIQUE,
+    allowed_senders TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Invite codes required to sign up when `SIGNUP_MODE` is `invite`, stored by their hash.
+-- A code allows `max_uses` signups until it expires.
+CREATE TABLE IF NOT EXISTS invite_codes (
+    id SERIAL PRIMARY KEY,
+    code_hash VARCHAR(64) NOT NULL UNIQUE,
+    max_uses INTEGER NOT NULL,
+    uses INTEGER NOT NULL DEFAULT 0,
+    expires_at TIMESTAMP WITH TIME ZONE,
+    created_by INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Accounts whose stored password hash can't be verified, such as the values left by the
+-- old signup. They must reset their password before logging in again.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS password_reset_required BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Pending password resets, the token is stored hashed. One reset per user at a time.
+CREATE TABLE IF NOT EXISTS password_resets (
+    user_id INTEGER PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Counts of the periodic scan of the stored password hashes, the latest is shown to the
+-- administrators
+CREATE TABLE IF NOT EXISTS password_hash_audits (
+    id SERIAL PRIMARY KEY,
+    total INTEGER NOT NULL,
+    current INTEGER NOT NULL,
+    outdated INTEGER NOT NULL,
+    malformed INTEGER NOT NULL,
+    scanned_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Named permission sets of a shared workspace, assigned by its owners to members. Owners
+-- and members without a template have every permission. A template can't be deleted
+-- while members hold it.
+CREATE TABLE IF NOT EXISTS workspace_permission_templates (
+    id SERIAL PRIMARY KEY,
+    workspace_id INTEGER NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    name VARCHAR(64) NOT NULL,
+    permissions TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (workspace_id, name)
+);
+
+ALTER TABLE workspace_members ADD COLUMN IF NOT EXISTS template_id INTEGER
+    REFERENCES workspace_permission_templates(id);
+
+-- Template given to the members joining the workspace
+ALTER TABLE workspaces ADD COLUMN IF NOT EXISTS default_template_id INTEGER
+    REFERENCES workspace_permission_templates(id) ON DELETE SET NULL;
+
+-- Keys of the server-side integrations, which sign their requests with HMAC-SHA256
+-- instead of holding a JWT. The secret is kept as is since it is needed to check the
+-- signatures. Revoked keys are kept so their key id is never reused.
+CREATE TABLE IF NOT EXISTS api_keys (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    key_id VARCHAR(32) NOT NULL UNIQUE,
+    secret VARCHAR(64) NOT NULL,
+    name VARCHAR(64) NOT NULL,
+    last_used_at TIMESTAMP WITH TIME ZONE DEFAULT NULL,
+    revoked_at TIMESTAMP WITH TIME ZONE DEFAULT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_keys_user_id ON api_keys(user_id);
+
+-- Signatures of the accepted signed requests, a signature is only accepted once. They
+-- are kept until their timestamp is too old to be accepted anyway.
+CREATE TABLE IF NOT EXISTS api_key_signatures (
+    signature VARCHAR(64) PRIMARY KEY,
+    api_key_id INTEGER NOT NULL REFERENCES api_keys(id) ON DELETE CASCADE,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_key_signatures_expires_at ON api_key_signatures(expires_at);
+
+-- Unread counters of the badges of each user, kept up to date by the triggers below in
+-- the transaction of each write so reading them is a single row lookup. Notifications
+-- are the sent reminders and the answers to the assignments of the user, assigned todos
+-- the pending assignments of live todos delegated to the user.
+CREATE TABLE IF NOT EXISTS user_badges (
+    user_id INTEGER PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    unread_notifications INTEGER NOT NULL DEFAULT 0 CHECK (unread_notifications >= 0),
+    assigned_todos INTEGER NOT NULL DEFAULT 0 CHECK (assigned_todos >= 0),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO user_badges (user_id, assigned_todos)
+SELECT a.assignee_id, COUNT(*)
+FROM todo_assignments a
+JOIN todos t ON t.id = a.todo_id
+WHERE a.status = 'pending' AND t.deleted_at IS NULL
+GROUP BY a.assignee_id
+ON CONFLICT (user_id) DO NOTHING;
+
+-- Add to the counters of a user, the row is locked by the update so concurrent writes
+-- add up
+CREATE OR REPLACE FUNCTION add_user_badges(
+    badge_user_id INTEGER, notifications INTEGER, assigned INTEGER
+)
+RETURNS VOID AS $$
+BEGIN
+    INSERT INTO user_badges (user_id, unread_notifications, assigned_todos)
+    VALUES (badge_user_id, GREATEST(notifications, 0), GREATEST(assigned, 0))
+    ON CONFLICT (user_id) DO UPDATE SET
+        unread_notifications = GREATEST(user_badges.unread_notifications + notifications, 0),
+        assigned_todos = GREATEST(user_badges.assigned_todos + assigned, 0),
+        updated_at = NOW();
+END;
+$$ language 'plpgsql';
+
+CREATE OR REPLACE FUNCTION count_assignment_badges()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        IF NEW.status = 'pending' THEN
+            PERFORM add_user_badges(NEW.assignee_id, 0, 1);
+        END IF;
+    ELSIF TG_OP = 'UPDATE' THEN
+        -- Answered, the assigner is notified
+        IF OLD.status = 'pending' AND NEW.status <> 'pending' THEN
+            PERFORM add_user_badges(NEW.assignee_id, 0, -1);
+            PERFORM add_user_badges(NEW.assigner_id, 1, 0);
+        END IF;
+    -- Deleted with its todo, counted out when the todo was trashed or deleted
+    ELSIF OLD.status = 'pending'
+        AND EXISTS (SELECT 1 FROM todos WHERE id = OLD.todo_id AND deleted_at IS NULL) THEN
+        PERFORM add_user_badges(OLD.assignee_id, 0, -1);
+    END IF;
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER count_todo_assignments_badges
+    AFTER INSERT OR UPDATE OF status OR DELETE ON todo_assignments
+    FOR EACH ROW
+    EXECUTE FUNCTION count_assignment_badges();
+
+-- Pending assignments of a todo leave the count while it is in the trash
+CREATE OR REPLACE FUNCTION count_trashed_todo_badges()
+RETURNS TRIGGER AS $$
+DECLARE
+    assigned INTEGER;
+    assignee INTEGER;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        IF OLD.deleted_at IS NOT NULL THEN
+            RETURN OLD;
+        END IF;
+        assigned := -1;
+    ELSIF (OLD.deleted_at IS NULL) = (NEW.deleted_at IS NULL) THEN
+        RETURN NEW;
+    ELSE
+        assigned := CASE WHEN NEW.deleted_at IS NULL THEN 1 ELSE -1 END;
+    END IF;
+
+    FOR assignee IN
+        SELECT assignee_id FROM todo_assignments
+        WHERE todo_id = OLD.id AND status = 'pending'
+    LOOP
+        PERFORM add_user_badges(assignee, 0, assigned);
+    END LOOP;
+
+    IF TG_OP = 'DELETE' THEN
+        RETURN OLD;
+    END IF;
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER count_todos_trashed_badges
+    AFTER UPDATE OF deleted_at ON todos
+    FOR EACH ROW
+    EXECUTE FUNCTION count_trashed_todo_badges();
+
+CREATE TRIGGER count_todos_deleted_badges
+    BEFORE DELETE ON todos
+    FOR EACH ROW
+    EXECUTE FUNCTION count_trashed_todo_badges();
+
+CREATE OR REPLACE FUNCTION count_reminder_badges()
+RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM add_user_badges(NEW.user_id, 1, 0);
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER count_reminders_sent_badges
+    AFTER UPDATE OF status ON reminders
+    FOR EACH ROW
+    WHEN (OLD.status <> 'sent' AND NEW.status = 'sent')
+    EXECUTE FUNCTION count_reminder_badges();
+
+-- Full text search of the todo titles and descriptions, with the language agnostic
+-- `simple` configuration
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS search_vector TSVECTOR
+    GENERATED ALWAYS AS (
+        to_tsvector('simple', title || ' ' || COALESCE(description, ''))
+    ) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_todos_search_vector ON todos USING GIN (search_vector);
+
+-- Edits, trashing and restoring of the todos are published too, so external search
+-- indexes follow the todos. The payload carries the fields they index.
+ALTER TABLE outbox_events DROP CONSTRAINT IF EXISTS outbox_events_event_type_check;
+ALTER TABLE outbox_events ADD CONSTRAINT outbox_events_event_type_check
+    CHECK (event_type IN ('todo.created', 'todo.completed', 'todo.updated'));
+
+CREATE OR REPLACE FUNCTION record_outbox_event()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO outbox_events (user_id, workspace_id, event_type, payload)
+    VALUES (
+        NEW.user_id,
+        NEW.workspace_id,
+        TG_ARGV[0],
+        jsonb_build_object(
+            'id', NEW.id,
+            'list_id', NEW.list_id,
+            'title', NEW.title,
+            'description', NEW.description,
+            'status', NEW.status,
+            'due_at', NEW.due_at,
+            'completed_at', NEW.completed_at,
+            'deleted_at', NEW.deleted_at
+        )
+    );
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+-- Completions have their own event
+CREATE TRIGGER record_todos_updated_outbox
+    AFTER UPDATE OF list_id, title, description, status, due_at, deleted_at ON todos
+    FOR EACH ROW
+    WHEN (
+        (OLD.list_id, OLD.title, OLD.description, OLD.status, OLD.due_at, OLD.deleted_at)
+            IS DISTINCT FROM
+        (NEW.list_id, NEW.title, NEW.description, NEW.status, NEW.due_at, NEW.deleted_at)
+        AND NOT (OLD.status <> 'done' AND NEW.status = 'done')
+    )
+    EXECUTE FUNCTION record_outbox_event('todo.updated');
+
+-- Throwaway users of the demo sessions, deleted with their data once expired
+ALTER TABLE users ADD COLUMN IF NOT EXISTS demo_expires_at TIMESTAMP WITH TIME ZONE DEFAULT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_users_demo_expires_at
+    ON users(demo_expires_at) WHERE demo_expires_at IS NOT NULL;
+
+-- Signups are published too, with the personal workspace of the user. Triggers of the
+-- same event fire by name, the workspace is created first.
+ALTER TABLE outbox_events DROP CONSTRAINT IF EXISTS outbox_events_event_type_check;
+ALTER TABLE outbox_events ADD CONSTRAINT outbox_events_event_type_check
+    CHECK (event_type IN ('user.created', 'todo.created', 'todo.completed', 'todo.updated'));
+
+CREATE OR REPLACE FUNCTION record_user_outbox_event()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO outbox_events (user_id, workspace_id, event_type, payload)
+    SELECT
+        NEW.id,
+        w.id,
+        TG_ARGV[0],
+        jsonb_build_object(
+            'id', NEW.id,
+            'username', NEW.username,
+            'demo', NEW.demo_expires_at IS NOT NULL,
+            'created_at', NEW.created_at
+        )
+    FROM workspaces w
+    WHERE w.personal_user_id = NEW.id;
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER record_users_created_outbox
+    AFTER INSERT ON users
+    FOR EACH ROW
+    EXECUTE FUNCTION record_user_outbox_event('user.created');
+
+-- Data exports of the accounts too large to download at once, built in the background
+-- and kept in the blob storage until they expire. No foreign key on user_id: the files
+-- of deleted users are removed with their expiry.
+CREATE TABLE IF NOT EXISTS data_exports (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    format VARCHAR(10) NOT NULL CHECK (format IN ('json', 'csv')),
+    status VARCHAR(10) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'ready', 'failed')),
+    storage_key TEXT UNIQUE,
+    size_bytes BIGINT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    completed_at TIMESTAMP WITH TIME ZONE,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_data_exports_user_id ON data_exports(user_id);
+CREATE INDEX IF NOT EXISTS idx_data_exports_expires_at ON data_exports(expires_at);
+
+-- Change events and tombstones belong to the workspace of their todo or list, so the feed
+-- of a workspace only returns its own rows. Tags are shared by the workspaces of their
+-- user and keep no workspace.
+ALTER TABLE change_events ADD COLUMN IF NOT EXISTS workspace_id INTEGER;
+ALTER TABLE tombstones ADD COLUMN IF NOT EXISTS workspace_id INTEGER;
+
+-- Rows recorded before workspaces belong to the personal workspace of their user
+UPDATE change_events e SET workspace_id = w.id
+FROM workspaces w
+WHERE w.personal_user_id = e.user_id AND e.entity <> 'tag' AND e.workspace_id IS NULL;
+UPDATE tombstones t SET workspace_id = w.id
+FROM workspaces w
+WHERE w.personal_user_id = t.user_id AND t.entity <> 'tag' AND t.workspace_id IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_change_events_workspace_seq ON change_events(workspace_id, seq);
+CREATE INDEX IF NOT EXISTS idx_tombstones_workspace_seq ON tombstones(workspace_id, seq);
+
+-- Moving a row to another workspace is a deletion for the previous one. The workspace is
+-- read through JSON since the tags have none.
+CREATE OR REPLACE FUNCTION record_change_event()
+RETURNS TRIGGER AS $$
+DECLARE
+    entity_name VARCHAR(8) := TG_ARGV[0];
+    event_seq BIGINT;
+    old_workspace_id INTEGER;
+    new_workspace_id INTEGER;
+BEGIN
+    IF TG_OP <> 'INSERT' THEN
+        old_workspace_id := (to_jsonb(OLD) ->> 'workspace_id')::INTEGER;
+    END IF;
+    IF TG_OP <> 'DELETE' THEN
+        new_workspace_id := (to_jsonb(NEW) ->> 'workspace_id')::INTEGER;
+    END IF;
+
+    IF TG_OP = 'INSERT' THEN
+        INSERT INTO change_events (user_id, workspace_id, entity, entity_id, action)
+        VALUES (NEW.user_id, new_workspace_id, entity_name, NEW.id, 'created');
+    ELSIF TG_OP = 'DELETE' THEN
+        INSERT INTO change_events (user_id, workspace_id, entity, entity_id, action)
+        VALUES (OLD.user_id, old_workspace_id, entity_name, OLD.id, 'deleted')
+        RETURNING seq INTO event_seq;
+        INSERT INTO tombstones (seq, user_id, workspace_id, entity, entity_id)
+        VALUES (event_seq, OLD.user_id, old_workspace_id, entity_name, OLD.id);
+    ELSIF OLD.user_id <> NEW.user_id OR old_workspace_id IS DISTINCT FROM new_workspace_id THEN
+        INSERT INTO change_events (user_id, workspace_id, entity, entity_id, action)
+        VALUES (OLD.user_id, old_workspace_id, entity_name, OLD.id, 'deleted'),
+               (NEW.user_id, new_workspace_id, entity_name, NEW.id, 'created');
+    ELSIF entity_name = 'todo' AND OLD.deleted_at IS NULL AND NEW.deleted_at IS NOT NULL THEN
+        INSERT INTO change_events (user_id, workspace_id, entity, entity_id, action)
+        VALUES (NEW.user_id, new_workspace_id, entity_name, NEW.id, 'deleted');
+    ELSIF entity_name = 'todo' AND OLD.deleted_at IS NOT NULL AND NEW.deleted_at IS NULL THEN
+        INSERT INTO change_events (user_id, workspace_id, entity, entity_id, action)
+        VALUES (NEW.user_id, new_workspace_id, entity_name, NEW.id, 'created');
+    ELSE
+        INSERT INTO change_events (user_id, workspace_id, entity, entity_id, action)
+        VALUES (NEW.user_id, new_workspace_id, entity_name, NEW.id, 'updated');
+    END IF;
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+-- Offline sync shares the change sequence of the changes feed, every todo write still
+-- bumps the version but the todo sync sequence is gone
+CREATE OR REPLACE FUNCTION bump_todo_sync_version()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.version = OLD.version + 1;
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+DROP INDEX IF EXISTS idx_todos_user_sync_seq;
+ALTER TABLE todos DROP COLUMN IF EXISTS sync_seq;
+DROP SEQUENCE IF EXISTS todo_sync_seq;
+
+-- Client ids of the todos created by offline sync, a repeated push of a create returns
+-- the todo of its first push. The id is claimed before the todo is created.
+CREATE TABLE IF NOT EXISTS sync_client_todos (
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    client_id TEXT NOT NULL,
+    todo_id INTEGER REFERENCES todos(id) ON DELETE CASCADE,
+    claimed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_sync_client_todos_user_client
+    ON sync_client_todos (user_id, client_id);
+
+-- Workspace ownership transfers, accepted by the recipient. The previous owner stays a
+-- member of the workspace.
+CREATE TABLE IF NOT EXISTS workspace_transfers (
+    id SERIAL PRIMARY KEY,
+    workspace_id INTEGER NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    from_user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    to_user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    status VARCHAR(16) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'accepted', 'declined')),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    resolved_at TIMESTAMP WITH TIME ZONE DEFAULT NULL
+);
+
+-- Only one pending transfer per workspace
+CREATE UNIQUE INDEX IF NOT EXISTS idx_workspace_transfers_pending
+    ON workspace_transfers(workspace_id) WHERE status = 'pending';
+
+-- Secrets of the api keys are derived from the server secret and their key id, the
+-- database holds none. Keys created with a stored secret are revoked.
+UPDATE api_keys SET revoked_at = NOW() WHERE revoked_at IS NULL;
+ALTER TABLE api_keys DROP COLUMN IF EXISTS secret;
@@ -1,14 +1,31 @@
-use crate::{modules::common::ErrorResponse, AppState}; // Import AppState from the crate root
+pub mod cookie;
+pub mod oauth;
+
+use self::cookie::{cookie_session, CookieSession};
+use crate::modules::session::repository::SessionRepository;
+use crate::modules::user::repository::UserRepository;
+use crate::telemetry::record_token_issued;
+use crate::{modules::common::AppError, AppState}; // Import AppState from the crate root
 use axum::{
-    extract::FromRequestParts,
-    http::{request::Parts, StatusCode},
-    RequestPartsExt,
+    extract::{FromRequestParts, Request, State},
+    http::{header::SEC_WEBSOCKET_PROTOCOL, request::Parts, HeaderMap, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
 };
-use axum_extra::{extract::TypedHeader, headers::authorization::Bearer, headers::Authorization};
+use axum_extra::headers::{authorization::Bearer, Authorization, HeaderMapExt};
 use chrono::{Duration, Utc};
 use jsonwebtoken::{decode, Algorithm, Validation};
 use jsonwebtoken::{encode, EncodingKey, Header};
 use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+// Authentication methods of a session, RFC 8176. `oauth` is not registered, it marks the
+// sessions opened through an identity provider.
+pub const AMR_PASSWORD: &str = "pwd";
+pub const AMR_OTP: &str = "otp";
+pub const AMR_RECOVERY_CODE: &str = "kba";
+pub const AMR_MULTI_FACTOR: &str = "mfa";
+pub const AMR_OAUTH: &str = "oauth";
 
 // Make the Claims struct public
 #[derive(Debug, Serialize, Deserialize)]
@@ -16,6 +33,115 @@ pub struct Claims {
     pub iat: i64,
     pub exp: i64,
     pub user_id: i64,
+    // Set on guest tokens, which only grant access to this list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guest_list_id: Option<i64>,
+    // Active workspace, the personal workspace of the user when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace_id: Option<i64>,
+    // Session of the member tokens, they are rejected once it is revoked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<i64>,
+    // Methods the user authenticated with, `mfa` once a second factor was checked
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub amr: Vec<String>,
+}
+
+// Claims of a guest token, limited to a single shared list
+#[derive(Debug)]
+pub struct GuestClaims {
+    pub user_id: i64,
+    pub list_id: i64,
+}
+
+// Claims of a member token of an administrator of the instance
+#[derive(Debug)]
+pub struct AdminClaims {
+    pub user_id: i64,
+}
+
+// WebSocket protocol announcing the token in the handshake, as the protocol following
+// it. The scripts of a browser can't set the headers of a socket.
+pub const BEARER_PROTOCOL: &str = "bearer";
+
+// Token of a WebSocket handshake, `Sec-WebSocket-Protocol: bearer, <token>`
+fn websocket_token(headers: &HeaderMap) -> Option<String> {
+    let protocols = headers.get(SEC_WEBSOCKET_PROTOCOL)?.to_str().ok()?;
+    let mut protocols = protocols.split(',').map(str::trim);
+    protocols.find(|protocol| *protocol == BEARER_PROTOCOL)?;
+    protocols
+        .next()
+        .filter(|token| !token.is_empty())
+        .map(str::to_string)
+}
+
+// Token of a request, from its bearer header, the protocols of a WebSocket handshake or
+// else from its session cookie
+pub fn request_token(
+    method: &Method,
+    headers: &HeaderMap,
+    state: &AppState,
+) -> Result<String, StatusCode> {
+    if let Some(Authorization(bearer)) = headers.typed_get::<Authorization<Bearer>>() {
+        return Ok(bearer.token().to_string());
+    }
+    if let Some(token) = websocket_token(headers) {
+        return Ok(token);
+    }
+    match cookie_session(method, headers, &state.config.jwt.secret) {
+        CookieSession::Token(token) => Ok(token),
+        // Refused apart from a missing token, the session itself may be valid
+        CookieSession::CsrfMismatch => Err(StatusCode::FORBIDDEN),
+        CookieSession::Missing => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+// Extract and decode the token of the request
+async fn decode_token(parts: &Parts, state: &AppState) -> Result<Claims, StatusCode> {
+    let token = request_token(&parts.method, &parts.headers, state).map_err(|status| {
+        if status == StatusCode::FORBIDDEN {
+            tracing::warn!("Session cookie used without its CSRF token");
+        } else {
+            tracing::warn!("Request without bearer header or session cookie");
+        }
+        status
+    })?;
+
+    // Decode the user data
+    let token_data = match decode::<Claims>(
+        &token,
+        &state.decoding_key,
+        &Validation::new(Algorithm::HS256),
+    ) {
+        Ok(data) => data,
+        Err(e) => {
+            tracing::warn!("Failed to decode token: {0}", &e);
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+    };
+    let claims = token_data.claims;
+
+    // Reject the tokens of revoked sessions
+    if let Some(session_id) = claims.session_id {
+        let expires_at = OffsetDateTime::from_unix_timestamp(claims.exp)
+            .map_err(|_| StatusCode::UNAUTHORIZED)?;
+        match SessionRepository::new(state.db_pool.clone())
+            .touch_session(session_id, claims.user_id, expires_at)
+            .await
+        {
+            Ok(true) => {}
+            Ok(false) => {
+                tracing::warn!("Token of revoked session {} used", session_id);
+                return Err(StatusCode::UNAUTHORIZED);
+            }
+            Err(e) => {
+                tracing::warn!("Error checking session: {}", e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        }
+    }
+
+    Ok(claims)
 }
 
 impl FromRequestParts<AppState> for Claims {
@@ -25,53 +151,188 @@ impl FromRequestParts<AppState> for Claims {
         parts: &mut Parts,
         state: &AppState,
     ) -> Result<Self, Self::Rejection> {
-        // Extract the token from the authorization header
-        let TypedHeader(Authorization(bearer)) =
-            match parts.extract::<TypedHeader<Authorization<Bearer>>>().await {
-                Ok(extracted) => extracted,
-                Err(e) => {
-                    tracing::warn!("Failed to extract bearer header: {0}", &e);
-                    return Err(StatusCode::UNAUTHORIZED);
-                }
-            };
-
-        // Decode the user data
-        let token_data = match decode::<Self>(
-            bearer.token(),
-            &state.decoding_key,
-            &Validation::new(Algorithm::HS256),
-        ) {
-            Ok(data) => data,
+        let claims = decode_token(parts, state).await?;
+
+        // Guest tokens are only accepted by guest endpoints
+        if claims.guest_list_id.is_some() {
+            tracing::warn!("Guest token used on a member endpoint");
+            return Err(StatusCode::FORBIDDEN);
+        }
+
+        Ok(claims)
+    }
+}
+
+impl FromRequestParts<AppState> for GuestClaims {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let claims = decode_token(parts, state).await?;
+
+        let Some(list_id) = claims.guest_list_id else {
+            tracing::warn!("Member token used on a guest endpoint");
+            return Err(StatusCode::FORBIDDEN);
+        };
+
+        Ok(Self {
+            user_id: claims.user_id,
+            list_id,
+        })
+    }
+}
+
+impl FromRequestParts<AppState> for AdminClaims {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let claims = Claims::from_request_parts(parts, state).await?;
+
+        match UserRepository::new(state.db_pool.clone())
+            .is_admin(claims.user_id)
+            .await
+        {
+            Ok(true) => Ok(Self {
+                user_id: claims.user_id,
+            }),
+            Ok(false) => {
+                tracing::warn!("User {} is not an administrator", claims.user_id);
+                Err(StatusCode::FORBIDDEN)
+            }
             Err(e) => {
-                tracing::warn!("Failed to decode token: {0}", &e);
-                return Err(StatusCode::UNAUTHORIZED);
+                tracing::warn!("Error checking administrator: {}", e);
+                Err(StatusCode::INTERNAL_SERVER_ERROR)
             }
-        };
+        }
+    }
+}
 
-        Ok(token_data.claims)
+// Whether a request needs a verified email. Reads, the account endpoints, which
+// verify the email, and the authentication endpoints are allowed to unverified users.
+fn requires_verified_email(method: &Method, path: &str) -> bool {
+    let is_read = matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS);
+    let is_account = path == "/user" || path.starts_with("/user/") || path.starts_with("/auth/");
+    !is_read && !is_account
+}
+
+// Middleware rejecting writes of users whose email is not verified, with a 403 telling
+// them apart from other authorization failures. Requests without a valid member token
+// are left to the endpoints.
+pub async fn require_verified_email(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !requires_verified_email(request.method(), request.uri().path()) {
+        return next.run(request).await;
+    }
+
+    let claims = request_token(request.method(), request.headers(), &state)
+        .ok()
+        .and_then(|token| {
+            decode::<Claims>(
+                &token,
+                &state.decoding_key,
+                &Validation::new(Algorithm::HS256),
+            )
+            .ok()
+        })
+        .filter(|token_data| token_data.claims.guest_list_id.is_none());
+    let Some(token_data) = claims else {
+        return next.run(request).await;
+    };
+
+    let user_id = token_data.claims.user_id;
+    match UserRepository::new(state.db_pool.clone())
+        .is_email_verified(user_id)
+        .await
+    {
+        Ok(true) => next.run(request).await,
+        Ok(false) => {
+            tracing::warn!("Write rejected, email of user {} not verified", user_id);
+            AppError::Forbidden("Email address not verified".to_string()).into_response()
+        }
+        Err(e) => AppError::Database(e).into_response(),
     }
 }
 
 pub fn generate_token(
     session_duration: i64,
     user_id: i64,
+    session_id: i64,
+    amr: Vec<String>,
+    enconding_key: &EncodingKey,
+) -> Result<String, AppError> {
+    generate_workspace_token(
+        session_duration,
+        user_id,
+        Some(session_id),
+        amr,
+        None,
+        enconding_key,
+    )
+}
+
+// Generate a session token working in `workspace_id`, or the personal workspace with `None`
+pub fn generate_workspace_token(
+    session_duration: i64,
+    user_id: i64,
+    session_id: Option<i64>,
+    amr: Vec<String>,
+    workspace_id: Option<i64>,
     enconding_key: &EncodingKey,
-) -> Result<String, ErrorResponse> {
+) -> Result<String, AppError> {
     let now = Utc::now();
     let exp = now + Duration::minutes(session_duration);
     let claims = Claims {
         user_id,
         iat: now.timestamp(),
         exp: exp.timestamp(),
+        guest_list_id: None,
+        workspace_id,
+        session_id,
+        amr,
     };
 
-    let token = match encode(&Header::default(), &claims, enconding_key) {
+    let token = encode_claims(&claims, enconding_key)?;
+    record_token_issued("session");
+    Ok(token)
+}
+
+// Generate a token for a guest user, valid until `expires_at` for a single list
+pub fn generate_guest_token(
+    expires_at: i64,
+    user_id: i64,
+    list_id: i64,
+    enconding_key: &EncodingKey,
+) -> Result<String, AppError> {
+    let claims = Claims {
+        user_id,
+        iat: Utc::now().timestamp(),
+        exp: expires_at,
+        guest_list_id: Some(list_id),
+        workspace_id: None,
+        session_id: None,
+        amr: Vec::new(),
+    };
+
+    let token = encode_claims(&claims, enconding_key)?;
+    record_token_issued("guest");
+    Ok(token)
+}
+
+fn encode_claims(claims: &Claims, enconding_key: &EncodingKey) -> Result<String, AppError> {
+    let token = match encode(&Header::default(), claims, enconding_key) {
         Ok(token) => token,
         Err(e) => {
-            tracing::warn!("Error generating JWT token: {0}", e);
-            return Err(ErrorResponse {
-                message: "Failed to generate JWT token.".to_string(),
-            });
+            return Err(AppError::Internal(format!(
+                "Failed to generate JWT token: {e}"
+            )));
         }
     };
 
@@ -91,7 +352,13 @@ mod tests {
         let user_id = 123;
         let session_duration = 60;
 
-        let result = generate_token(session_duration, user_id, &encoding_key);
+        let result = generate_token(
+            session_duration,
+            user_id,
+            7,
+            vec![AMR_PASSWORD.to_string()],
+            &encoding_key,
+        );
         assert!(result.is_ok());
 
         let token = result.unwrap();
@@ -105,6 +372,8 @@ mod tests {
 
         let claims = decoded.unwrap().claims;
         assert_eq!(claims.user_id, user_id);
+        assert_eq!(claims.session_id, Some(7));
+        assert_eq!(claims.amr, [AMR_PASSWORD]);
     }
 
     #[test]
@@ -113,6 +382,10 @@ mod tests {
             iat: 1234567890,
             exp: 1234567950,
             user_id: 42,
+            guest_list_id: None,
+            workspace_id: None,
+            session_id: None,
+            amr: Vec::new(),
         };
 
         assert_eq!(claims.iat, 1234567890);
@@ -126,8 +399,8 @@ mod tests {
         let encoding_key = EncodingKey::from_secret(secret.as_ref());
         let session_duration = 60;
 
-        let token1 = generate_token(session_duration, 1, &encoding_key).unwrap();
-        let token2 = generate_token(session_duration, 2, &encoding_key).unwrap();
+        let token1 = generate_token(session_duration, 1, 7, Vec::new(), &encoding_key).unwrap();
+        let token2 = generate_token(session_duration, 2, 8, Vec::new(), &encoding_key).unwrap();
 
         assert_ne!(token1, token2);
 
@@ -162,6 +435,10 @@ mod tests {
             user_id: 1,
             iat: chrono::Utc::now().timestamp(),
             exp: (chrono::Utc::now() - chrono::Duration::hours(1)).timestamp(),
+            guest_list_id: None,
+            workspace_id: None,
+            session_id: None,
+            amr: Vec::new(),
         };
 
         let encoding_key = EncodingKey::from_secret("secret".as_ref());
@@ -173,14 +450,107 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_generate_guest_token_carries_list() {
+        let secret = "test_secret";
+        let encoding_key = EncodingKey::from_secret(secret.as_ref());
+        let expires_at = (Utc::now() + Duration::minutes(30)).timestamp();
+
+        let token = generate_guest_token(expires_at, 5, 9, &encoding_key).unwrap();
+
+        let decoding_key = DecodingKey::from_secret(secret.as_ref());
+        let validation = Validation::new(Algorithm::HS256);
+        let claims = decode::<Claims>(&token, &decoding_key, &validation)
+            .unwrap()
+            .claims;
+        assert_eq!(claims.user_id, 5);
+        assert_eq!(claims.exp, expires_at);
+        assert_eq!(claims.guest_list_id, Some(9));
+        assert_eq!(claims.session_id, None);
+    }
+
+    #[test]
+    fn test_member_token_has_no_guest_list() {
+        let encoding_key = EncodingKey::from_secret("secret".as_ref());
+        let token = generate_token(60, 1, 7, Vec::new(), &encoding_key).unwrap();
+
+        let decoding_key = DecodingKey::from_secret("secret".as_ref());
+        let validation = Validation::new(Algorithm::HS256);
+        let claims = decode::<Claims>(&token, &decoding_key, &validation)
+            .unwrap()
+            .claims;
+        assert_eq!(claims.guest_list_id, None);
+        assert_eq!(claims.workspace_id, None);
+        assert!(claims.amr.is_empty());
+    }
+
+    #[test]
+    fn test_workspace_token_carries_workspace() {
+        let encoding_key = EncodingKey::from_secret("secret".as_ref());
+        let token = generate_workspace_token(
+            60,
+            1,
+            Some(7),
+            vec![AMR_MULTI_FACTOR.to_string()],
+            Some(12),
+            &encoding_key,
+        )
+        .unwrap();
+
+        let decoding_key = DecodingKey::from_secret("secret".as_ref());
+        let validation = Validation::new(Algorithm::HS256);
+        let claims = decode::<Claims>(&token, &decoding_key, &validation)
+            .unwrap()
+            .claims;
+        assert_eq!(claims.user_id, 1);
+        assert_eq!(claims.workspace_id, Some(12));
+        assert_eq!(claims.session_id, Some(7));
+        assert_eq!(claims.amr, [AMR_MULTI_FACTOR]);
+    }
+
+    #[test]
+    fn test_requires_verified_email_for_writes() {
+        assert!(requires_verified_email(&Method::POST, "/todos"));
+        assert!(requires_verified_email(&Method::DELETE, "/lists/3"));
+        assert!(requires_verified_email(&Method::PATCH, "/users-export"));
+        assert!(!requires_verified_email(&Method::GET, "/todos"));
+        assert!(!requires_verified_email(
+            &Method::POST,
+            "/user/verify/resend"
+        ));
+        assert!(!requires_verified_email(&Method::DELETE, "/user"));
+        assert!(!requires_verified_email(
+            &Method::DELETE,
+            "/auth/sessions/4"
+        ));
+    }
+
     #[test]
     fn test_wrong_secret() {
         let encoding_key = EncodingKey::from_secret("secret".as_ref());
-        let token = generate_token(60, 1, &encoding_key).unwrap();
+        let token = generate_token(60, 1, 7, Vec::new(), &encoding_key).unwrap();
         let wrong_key = DecodingKey::from_secret("wrong_secret".as_ref());
         let validation = Validation::default();
 
         let result = decode::<Claims>(&token, &wrong_key, &validation);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_websocket_token() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(websocket_token(&headers), None);
+
+        headers.insert(SEC_WEBSOCKET_PROTOCOL, "bearer, abc.def".parse().unwrap());
+        assert_eq!(websocket_token(&headers), Some("abc.def".to_string()));
+
+        headers.insert(
+            SEC_WEBSOCKET_PROTOCOL,
+            "graphql-ws, abc.def".parse().unwrap(),
+        );
+        assert_eq!(websocket_token(&headers), None);
+
+        headers.insert(SEC_WEBSOCKET_PROTOCOL, "bearer".parse().unwrap());
+        assert_eq!(websocket_token(&headers), None);
+    }
 }
@@ -0,0 +1,214 @@
+//! # Cookie Sessions
+//! Sessions of the browser frontends, kept in an `HttpOnly` cookie instead of a header
+//! the scripts of the page handle. Cookies are sent by the browser on their own, so the
+//! requests changing state must also repeat the CSRF token of the session in the
+//! `X-CSRF-Token` header. The token is an HMAC of the session token, readable by the page
+//! in its own cookie: another site can't read it nor forge it.
+
+use axum::http::{HeaderMap, HeaderName, Method};
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use time::Duration;
+
+use crate::modules::user::interfaces::LoginUserResponse;
+use crate::AppState;
+
+// Cookie holding the session token, out of reach of the scripts
+pub const SESSION_COOKIE: &str = "session";
+
+// Cookie holding the CSRF token, read by the page to fill the header
+pub const CSRF_COOKIE: &str = "csrf_token";
+
+pub const CSRF_HEADER: HeaderName = HeaderName::from_static("x-csrf-token");
+
+// Token of a cookie session, or why the request is refused
+#[derive(Debug, PartialEq, Eq)]
+pub enum CookieSession {
+    // No session cookie
+    Missing,
+    // Session cookie without the matching CSRF header on a state changing request
+    CsrfMismatch,
+    Token(String),
+}
+
+// CSRF token of a session, the hex HMAC of its token
+pub fn csrf_token(secret: &str, session_token: &str) -> String {
+    // HMAC accepts keys of any length
+    Hmac::<Sha256>::new_from_slice(secret.as_bytes()).map_or_else(
+        |_| String::new(),
+        |mut mac| {
+            mac.update(session_token.as_bytes());
+            hex::encode(mac.finalize().into_bytes())
+        },
+    )
+}
+
+// Check a CSRF token in constant time
+fn verify_csrf_token(secret: &str, session_token: &str, csrf_token: &str) -> bool {
+    let Ok(csrf_token) = hex::decode(csrf_token) else {
+        return false;
+    };
+    Hmac::<Sha256>::new_from_slice(secret.as_bytes()).is_ok_and(|mut mac| {
+        mac.update(session_token.as_bytes());
+        mac.verify_slice(&csrf_token).is_ok()
+    })
+}
+
+// Session token of the cookie of a request. Reads pass without the CSRF header, a link
+// from another site can't see their answer.
+pub fn cookie_session(method: &Method, headers: &HeaderMap, secret: &str) -> CookieSession {
+    let jar = CookieJar::from_headers(headers);
+    let Some(token) = jar.get(SESSION_COOKIE).map(Cookie::value) else {
+        return CookieSession::Missing;
+    };
+    let is_read = matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS);
+    let csrf_header = headers
+        .get(&CSRF_HEADER)
+        .and_then(|value| value.to_str().ok());
+    if is_read || csrf_header.is_some_and(|csrf| verify_csrf_token(secret, token, csrf)) {
+        CookieSession::Token(token.to_string())
+    } else {
+        CookieSession::CsrfMismatch
+    }
+}
+
+fn session_cookie(
+    name: &'static str,
+    value: String,
+    http_only: bool,
+    secure: bool,
+) -> Cookie<'static> {
+    Cookie::build((name, value))
+        .path("/")
+        .http_only(http_only)
+        .secure(secure)
+        .same_site(SameSite::Strict)
+        .build()
+}
+
+// Start a cookie session with a session token, returns the cookies to set and the CSRF
+// token of the session. Both cookies expire along with the token.
+pub fn start_cookie_session(
+    jar: CookieJar,
+    token: &str,
+    secret: &str,
+    duration_minutes: i64,
+    secure: bool,
+) -> (CookieJar, String) {
+    let csrf = csrf_token(secret, token);
+    let max_age = Duration::minutes(duration_minutes);
+    let mut session = session_cookie(SESSION_COOKIE, token.to_string(), true, secure);
+    session.set_max_age(max_age);
+    let mut csrf_cookie = session_cookie(CSRF_COOKIE, csrf.clone(), false, secure);
+    csrf_cookie.set_max_age(max_age);
+    (jar.add(session).add(csrf_cookie), csrf)
+}
+
+// Move the session of a login to the cookies, the body keeps the CSRF token instead of
+// the session token. A pre-auth token stays in the body until the second factor is
+// checked.
+pub fn cookie_login(
+    jar: CookieJar,
+    mut response: LoginUserResponse,
+    state: &AppState,
+) -> (CookieJar, LoginUserResponse) {
+    if response.two_factor_required {
+        return (jar, response);
+    }
+    let token = std::mem::take(&mut response.token);
+    let (jar, csrf) = start_cookie_session(
+        jar,
+        &token,
+        &state.config.jwt.secret,
+        state.config.session.duration_minutes,
+        state.config.session.cookie_secure,
+    );
+    response.csrf_token = Some(csrf);
+    (jar, response)
+}
+
+// Remove the cookies of a session
+pub fn end_cookie_session(jar: CookieJar) -> CookieJar {
+    jar.remove(Cookie::build(SESSION_COOKIE).path("/"))
+        .remove(Cookie::build(CSRF_COOKIE).path("/"))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use axum::http::{header, HeaderValue};
+
+    const SECRET: &str = "secret";
+
+    fn headers(cookie: &str, csrf: Option<&str>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::COOKIE, HeaderValue::from_str(cookie).unwrap());
+        if let Some(csrf) = csrf {
+            headers.insert(CSRF_HEADER, HeaderValue::from_str(csrf).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn test_csrf_token_bound_to_session() {
+        let csrf = csrf_token(SECRET, "token");
+        assert_eq!(csrf.len(), 64);
+        assert!(verify_csrf_token(SECRET, "token", &csrf));
+        assert!(!verify_csrf_token(SECRET, "other", &csrf));
+        assert!(!verify_csrf_token("other", "token", &csrf));
+        assert!(!verify_csrf_token(SECRET, "token", "not hex"));
+    }
+
+    #[test]
+    fn test_cookie_session() {
+        let csrf = csrf_token(SECRET, "token");
+        assert_eq!(
+            cookie_session(&Method::GET, &headers("session=token", None), SECRET),
+            CookieSession::Token("token".to_string())
+        );
+        assert_eq!(
+            cookie_session(
+                &Method::POST,
+                &headers("session=token", Some(&csrf)),
+                SECRET
+            ),
+            CookieSession::Token("token".to_string())
+        );
+        assert_eq!(
+            cookie_session(&Method::POST, &headers("session=token", None), SECRET),
+            CookieSession::CsrfMismatch
+        );
+        assert_eq!(
+            cookie_session(
+                &Method::DELETE,
+                &headers("session=other", Some(&csrf)),
+                SECRET
+            ),
+            CookieSession::CsrfMismatch
+        );
+        assert_eq!(
+            cookie_session(&Method::POST, &headers("theme=dark", Some(&csrf)), SECRET),
+            CookieSession::Missing
+        );
+    }
+
+    #[test]
+    fn test_start_and_end_cookie_session() {
+        let (jar, csrf) = start_cookie_session(CookieJar::new(), "token", SECRET, 60, true);
+        let session = jar.get(SESSION_COOKIE).unwrap();
+        assert_eq!(session.value(), "token");
+        assert_eq!(session.http_only(), Some(true));
+        assert_eq!(session.secure(), Some(true));
+        assert_eq!(session.same_site(), Some(SameSite::Strict));
+        assert_eq!(session.max_age(), Some(Duration::minutes(60)));
+        let csrf_cookie = jar.get(CSRF_COOKIE).unwrap();
+        assert_eq!(csrf_cookie.value(), csrf);
+        assert_eq!(csrf_cookie.http_only(), Some(false));
+
+        let jar = end_cookie_session(jar);
+        assert!(jar.get(SESSION_COOKIE).is_none());
+        assert!(jar.get(CSRF_COOKIE).is_none());
+    }
+}
@@ -0,0 +1,25 @@
+//! # `OAuth` Interfaces
+//! This module defines the data structures from the `OAuth` login module
+
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+// Query parameters the provider redirects the user back with
+#[derive(Deserialize, IntoParams, Clone, Debug, Default)]
+#[into_params(parameter_in = Query)]
+pub struct OAuthCallbackQuery {
+    /// Authorization code to exchange for the identity of the user
+    pub code: Option<String>,
+    /// State sent by `/auth/oauth/{provider}/start`
+    pub state: Option<String>,
+    /// Set by the provider instead of the code when the user denies the access
+    pub error: Option<String>,
+}
+
+// User found by email for an account linking
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct EmailUserRow {
+    pub id: i32,
+    // Only verified emails are linked to a provider account
+    pub email_verified: bool,
+}
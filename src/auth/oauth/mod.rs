@@ -0,0 +1,11 @@
+//! # `OAuth` Mod
+//! `OAuth` imports for the logins through Google and GitHub, with the authorization-code
+//! flow
+
+pub mod interfaces;
+pub mod provider;
+pub mod repository;
+pub mod routes;
+pub mod service;
+
+pub use routes::oauth_routes;
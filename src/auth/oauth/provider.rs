@@ -0,0 +1,333 @@
+//! # `OAuth` Providers
+//! This module runs the authorization-code flow against the identity providers,
+//! configured with the `OAUTH_*` variables: the authorize URL the user is sent to, the
+//! code exchange and the identity of the user.
+
+use std::{str::FromStr, sync::Arc, time::Duration};
+
+use reqwest::{header::ACCEPT, header::USER_AGENT, Client, RequestBuilder, Url};
+use serde::{de::DeserializeOwned, Deserialize};
+
+const DEFAULT_OAUTH_TIMEOUT_SECONDS: u64 = 10;
+
+// GitHub refuses API calls without a user agent
+const CLIENT_USER_AGENT: &str = "rust_todo_app";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OAuthProvider {
+    Google,
+    GitHub,
+}
+
+impl OAuthProvider {
+    pub const ALL: [Self; 2] = [Self::Google, Self::GitHub];
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Google => "google",
+            Self::GitHub => "github",
+        }
+    }
+
+    const fn authorize_url(self) -> &'static str {
+        match self {
+            Self::Google => "https://accounts.google.com/o/oauth2/v2/auth",
+            Self::GitHub => "https://github.com/login/oauth/authorize",
+        }
+    }
+
+    const fn token_url(self) -> &'static str {
+        match self {
+            Self::Google => "https://oauth2.googleapis.com/token",
+            Self::GitHub => "https://github.com/login/oauth/access_token",
+        }
+    }
+
+    // Scopes granting the id, verified email and name of the user
+    const fn scope(self) -> &'static str {
+        match self {
+            Self::Google => "openid email profile",
+            Self::GitHub => "read:user user:email",
+        }
+    }
+}
+
+impl FromStr for OAuthProvider {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "google" => Ok(Self::Google),
+            "github" => Ok(Self::GitHub),
+            _ => Err(format!("Unknown OAuth provider: {s}")),
+        }
+    }
+}
+
+// User as known by the provider
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OAuthIdentity {
+    // Id of the user at the provider, stable across email and login changes
+    pub subject: String,
+    // Email of the user, only set when verified by the provider
+    pub email: Option<String>,
+    // Login or email name the username of a new user is derived from
+    pub login: String,
+    pub name: Option<String>,
+}
+
+// Credentials of the application registered at a provider
+#[derive(Clone, Debug)]
+struct OAuthCredentials {
+    client_id: String,
+    client_secret: String,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+#[derive(Deserialize)]
+struct GoogleUser {
+    sub: String,
+    email: Option<String>,
+    #[serde(default)]
+    email_verified: bool,
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GitHubUser {
+    id: i64,
+    login: String,
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GitHubEmail {
+    email: String,
+    primary: bool,
+    verified: bool,
+}
+
+// Providers the application is registered at, a provider without credentials is disabled
+#[derive(Clone)]
+pub struct OAuthClients {
+    client: Client,
+    google: Option<Arc<OAuthCredentials>>,
+    github: Option<Arc<OAuthCredentials>>,
+    // Base URL of the callbacks, registered at the providers
+    public_url: String,
+}
+
+// Credentials of a provider from `OAUTH_<PROVIDER>_CLIENT_ID` and `_CLIENT_SECRET`
+fn credentials_from_env(prefix: &str) -> Option<Arc<OAuthCredentials>> {
+    let var = |name: &str| {
+        std::env::var(format!("{prefix}_{name}"))
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    Some(Arc::new(OAuthCredentials {
+        client_id: var("CLIENT_ID")?,
+        client_secret: var("CLIENT_SECRET")?,
+    }))
+}
+
+impl OAuthClients {
+    // Providers configured in the environment, the callbacks are served under `public_url`
+    pub fn from_env(public_url: &str) -> Result<Self, String> {
+        let timeout = std::env::var("OAUTH_TIMEOUT_SECONDS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_OAUTH_TIMEOUT_SECONDS);
+        let client = Client::builder()
+            .timeout(Duration::from_secs(timeout))
+            .build()
+            .map_err(|e| format!("Failed to create OAuth client: {e}"))?;
+
+        Ok(Self {
+            client,
+            google: credentials_from_env("OAUTH_GOOGLE"),
+            github: credentials_from_env("OAUTH_GITHUB"),
+            public_url: public_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    fn credentials(&self, provider: OAuthProvider) -> Option<&OAuthCredentials> {
+        match provider {
+            OAuthProvider::Google => self.google.as_deref(),
+            OAuthProvider::GitHub => self.github.as_deref(),
+        }
+    }
+
+    pub fn is_enabled(&self, provider: OAuthProvider) -> bool {
+        self.credentials(provider).is_some()
+    }
+
+    // Providers with credentials, the users can log in with them
+    pub fn enabled_providers(&self) -> Vec<OAuthProvider> {
+        OAuthProvider::ALL
+            .into_iter()
+            .filter(|provider| self.is_enabled(*provider))
+            .collect()
+    }
+
+    fn redirect_uri(&self, provider: OAuthProvider) -> String {
+        format!(
+            "{}/auth/oauth/{}/callback",
+            self.public_url,
+            provider.as_str()
+        )
+    }
+
+    // URL of the provider consent page, `None` when the provider is disabled
+    pub fn authorize_url(&self, provider: OAuthProvider, state: &str) -> Option<Url> {
+        let credentials = self.credentials(provider)?;
+        let mut url = Url::parse(provider.authorize_url()).ok()?;
+        url.query_pairs_mut()
+            .append_pair("client_id", &credentials.client_id)
+            .append_pair("redirect_uri", &self.redirect_uri(provider))
+            .append_pair("response_type", "code")
+            .append_pair("scope", provider.scope())
+            .append_pair("state", state);
+        Some(url)
+    }
+
+    // Exchange the code of the callback for the identity of the user
+    pub async fn identity(
+        &self,
+        provider: OAuthProvider,
+        code: &str,
+    ) -> Result<OAuthIdentity, String> {
+        let credentials = self
+            .credentials(provider)
+            .ok_or_else(|| format!("OAuth provider {} is disabled", provider.as_str()))?;
+        let redirect_uri = self.redirect_uri(provider);
+        let token: TokenResponse = fetch_json(self.client.post(provider.token_url()).form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("client_id", credentials.client_id.as_str()),
+            ("client_secret", credentials.client_secret.as_str()),
+            ("redirect_uri", redirect_uri.as_str()),
+        ]))
+        .await?;
+
+        match provider {
+            OAuthProvider::Google => self.google_identity(&token.access_token).await,
+            OAuthProvider::GitHub => self.github_identity(&token.access_token).await,
+        }
+    }
+
+    async fn google_identity(&self, access_token: &str) -> Result<OAuthIdentity, String> {
+        let user: GoogleUser = fetch_json(
+            self.client
+                .get("https://openidconnect.googleapis.com/v1/userinfo")
+                .bearer_auth(access_token),
+        )
+        .await?;
+
+        let email = user.email.filter(|_| user.email_verified);
+        let login = email
+            .as_deref()
+            .and_then(|email| email.split('@').next())
+            .unwrap_or_default()
+            .to_string();
+        Ok(OAuthIdentity {
+            subject: user.sub,
+            email,
+            login,
+            name: user.name,
+        })
+    }
+
+    async fn github_identity(&self, access_token: &str) -> Result<OAuthIdentity, String> {
+        let user: GitHubUser = fetch_json(
+            self.client
+                .get("https://api.github.com/user")
+                .bearer_auth(access_token),
+        )
+        .await?;
+        // The profile email may be unverified or hidden, the primary one is used instead
+        let emails: Vec<GitHubEmail> = fetch_json(
+            self.client
+                .get("https://api.github.com/user/emails")
+                .bearer_auth(access_token),
+        )
+        .await?;
+
+        Ok(OAuthIdentity {
+            subject: user.id.to_string(),
+            email: emails
+                .into_iter()
+                .find(|email| email.primary && email.verified)
+                .map(|email| email.email),
+            login: user.login,
+            name: user.name,
+        })
+    }
+}
+
+// Send a request to a provider and read its JSON answer, any non 2xx answer is an error
+async fn fetch_json<T: DeserializeOwned>(request: RequestBuilder) -> Result<T, String> {
+    request
+        .header(ACCEPT, "application/json")
+        .header(USER_AGENT, CLIENT_USER_AGENT)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|e| e.to_string())?
+        .json::<T>()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn clients() -> OAuthClients {
+        OAuthClients {
+            client: Client::new(),
+            google: Some(Arc::new(OAuthCredentials {
+                client_id: "app id".to_string(),
+                client_secret: "secret".to_string(),
+            })),
+            github: None,
+            public_url: "http://localhost:8000".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_provider() {
+        assert_eq!("google".parse(), Ok(OAuthProvider::Google));
+        assert_eq!("github".parse(), Ok(OAuthProvider::GitHub));
+        assert!("gitlab".parse::<OAuthProvider>().is_err());
+    }
+
+    #[test]
+    fn test_enabled_providers() {
+        assert_eq!(clients().enabled_providers(), vec![OAuthProvider::Google]);
+    }
+
+    #[test]
+    fn test_authorize_url() {
+        let url = clients()
+            .authorize_url(OAuthProvider::Google, "abc")
+            .unwrap();
+        let query: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+
+        assert_eq!(url.host_str(), Some("accounts.google.com"));
+        assert!(query.contains(&("client_id".to_string(), "app id".to_string())));
+        assert!(query.contains(&(
+            "redirect_uri".to_string(),
+            "http://localhost:8000/auth/oauth/google/callback".to_string()
+        )));
+        assert!(query.contains(&("state".to_string(), "abc".to_string())));
+        assert!(clients()
+            .authorize_url(OAuthProvider::GitHub, "abc")
+            .is_none());
+    }
+}
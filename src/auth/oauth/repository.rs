@@ -0,0 +1,170 @@
+//! # `OAuth` Repository
+//! This module defines the repository of the `OAuth` states and linked accounts.
+
+use sqlx::{Error, Pool, Postgres};
+use time::OffsetDateTime;
+
+use crate::telemetry::observe_query;
+use crate::{auth::oauth::interfaces::EmailUserRow, modules::common::to_db_id};
+
+pub struct OAuthRepository {
+    pool: Pool<Postgres>,
+}
+
+impl OAuthRepository {
+    // Constructor
+    pub const fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Store the state of a login starting, dropping the expired ones
+    pub async fn create_state(
+        &self,
+        state_hash: &str,
+        provider: &str,
+        expires_at: OffsetDateTime,
+    ) -> Result<(), Error> {
+        observe_query("oauth.create_state", async move {
+            let mut tx = self.pool.begin().await?;
+            sqlx::query("DELETE FROM oauth_states WHERE expires_at <= NOW()")
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query(
+                "INSERT INTO oauth_states (state_hash, provider, expires_at) VALUES ($1, $2, $3)",
+            )
+            .bind(state_hash)
+            .bind(provider)
+            .bind(expires_at)
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await
+        })
+        .await
+    }
+
+    // Use the state of a login, returns whether it was issued for the provider and is
+    // unexpired. A state is only used once.
+    pub async fn consume_state(&self, state_hash: &str, provider: &str) -> Result<bool, Error> {
+        observe_query("oauth.consume_state", async move {
+            let result = sqlx::query(
+                "DELETE FROM oauth_states
+                 WHERE state_hash = $1 AND provider = $2 AND expires_at > NOW()",
+            )
+            .bind(state_hash)
+            .bind(provider)
+            .execute(&self.pool)
+            .await?;
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    // User the provider account is linked to
+    pub async fn find_linked_user(
+        &self,
+        provider: &str,
+        subject: &str,
+    ) -> Result<Option<i64>, Error> {
+        observe_query("oauth.find_linked_user", async move {
+            let user_id = sqlx::query_scalar::<_, i32>(
+                "SELECT user_id FROM oauth_accounts WHERE provider = $1 AND subject = $2",
+            )
+            .bind(provider)
+            .bind(subject)
+            .fetch_optional(&self.pool)
+            .await?;
+            Ok(user_id.map(i64::from))
+        })
+        .await
+    }
+
+    // Registered user with the email, guests excluded
+    pub async fn find_user_by_email(&self, email: &str) -> Result<Option<EmailUserRow>, Error> {
+        observe_query("oauth.find_user_by_email", async move {
+            sqlx::query_as::<_, EmailUserRow>(
+                "SELECT id, email_verified_at IS NOT NULL AS email_verified FROM users
+                 WHERE LOWER(email) = LOWER($1) AND NOT is_guest",
+            )
+            .bind(email)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Check if a username is already taken
+    pub async fn is_username_taken(&self, username: &str) -> Result<bool, Error> {
+        observe_query("oauth.is_username_taken", async move {
+            sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM users WHERE username = $1)")
+                .bind(username)
+                .fetch_one(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Link the provider account to the user
+    pub async fn link_account(
+        &self,
+        user_id: i64,
+        provider: &str,
+        subject: &str,
+    ) -> Result<(), Error> {
+        observe_query("oauth.link_account", async move {
+            sqlx::query(
+                "INSERT INTO oauth_accounts (provider, subject, user_id) VALUES ($1, $2, $3)
+                 ON CONFLICT (provider, subject) DO NOTHING",
+            )
+            .bind(provider)
+            .bind(subject)
+            .bind(to_db_id(user_id)?)
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    // Create an active user with the verified email of the provider account and link the
+    // account, converting a guest account with the same email. The password is random,
+    // the user signs in through the provider.
+    pub async fn create_linked_user(
+        &self,
+        username: &str,
+        email: &str,
+        name: Option<&str>,
+        password_hash: &str,
+        provider: &str,
+        subject: &str,
+    ) -> Result<i64, Error> {
+        observe_query("oauth.create_linked_user", async move {
+            let mut tx = self.pool.begin().await?;
+            let user_id = sqlx::query_scalar::<_, i32>(
+                "INSERT INTO users (username, email, password, name, active, email_verified_at)
+                 VALUES ($1, $2, $3, $4, true, NOW())
+                 ON CONFLICT (email) DO UPDATE SET username = EXCLUDED.username,
+                    password = EXCLUDED.password, name = EXCLUDED.name, active = true,
+                    email_verified_at = NOW(), is_guest = false, guest_expires_at = NULL
+                 WHERE users.is_guest
+                 RETURNING id",
+            )
+            .bind(username)
+            .bind(email)
+            .bind(password_hash)
+            .bind(name)
+            .fetch_one(&mut *tx)
+            .await?;
+            sqlx::query(
+                "INSERT INTO oauth_accounts (provider, subject, user_id) VALUES ($1, $2, $3)",
+            )
+            .bind(provider)
+            .bind(subject)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+            Ok(i64::from(user_id))
+        })
+        .await
+    }
+}
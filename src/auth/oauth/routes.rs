@@ -0,0 +1,139 @@
+//! #`OAuth` Routes
+//! This module defines the HTTP routes of the logins through an identity provider.
+
+use axum::extract::{Path, Query};
+use axum::response::Redirect;
+use axum::routing::get;
+use axum::{
+    extract::State,
+    http::{header::USER_AGENT, HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json, Router,
+};
+
+use crate::auth::oauth::interfaces::OAuthCallbackQuery;
+use crate::auth::oauth::repository::OAuthRepository;
+use crate::auth::oauth::service::OAuthService;
+use crate::modules::audit::repository::AuditRepository;
+use crate::modules::audit::service::AuditService;
+use crate::modules::common::ErrorResponse;
+use crate::modules::session::repository::SessionRepository;
+use crate::modules::session::service::{session_device, SessionService};
+use crate::modules::two_factor::repository::TwoFactorRepository;
+use crate::modules::two_factor::service::TwoFactorService;
+use crate::modules::user::interfaces::LoginUserResponse;
+use crate::utils::client_ip::ClientIp;
+use crate::AppState;
+
+// Creates and returns the OAuth routes
+pub fn oauth_routes() -> Router<AppState> {
+    Router::new()
+        .route("/auth/oauth/{provider}/start", get(oauth_start_route))
+        .route("/auth/oauth/{provider}/callback", get(oauth_callback_route))
+}
+
+fn oauth_service(app_state: &AppState) -> OAuthService {
+    OAuthService::new(
+        OAuthRepository::new(app_state.db_pool.clone()),
+        app_state.oauth_clients.clone(),
+        SessionService::new(
+            SessionRepository::new(app_state.db_pool.clone()),
+            AuditService::new(AuditRepository::new(app_state.db_pool.clone())),
+            app_state.login_alerts.clone(),
+        ),
+        TwoFactorService::new(
+            TwoFactorRepository::new(app_state.db_pool.clone()),
+            SessionService::new(
+                SessionRepository::new(app_state.db_pool.clone()),
+                AuditService::new(AuditRepository::new(app_state.db_pool.clone())),
+                app_state.login_alerts.clone(),
+            ),
+        ),
+        app_state.signup_mode,
+    )
+}
+
+// OAuth Start Route
+#[utoipa::path(
+    get,
+    path = "/auth/oauth/{provider}/start",
+    tag = "OAuth",
+    params(
+        ("provider" = String, Path, description = "Identity provider, `google` or `github`")
+    ),
+    responses(
+        (status = 303, description = "Redirect to the consent page of the provider"),
+        (status = 404, description = "Unknown or disabled provider", body = ErrorResponse)
+    )
+)]
+pub async fn oauth_start_route(
+    State(app_state): State<AppState>,
+    Path(provider): Path<String>,
+) -> impl IntoResponse {
+    match oauth_service(&app_state).start(&provider).await {
+        Ok(url) => Redirect::to(url.as_str()).into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+// OAuth Callback Route
+#[utoipa::path(
+    get,
+    path = "/auth/oauth/{provider}/callback",
+    tag = "OAuth",
+    params(
+        ("provider" = String, Path, description = "Identity provider, `google` or `github`"),
+        OAuthCallbackQuery
+    ),
+    responses(
+        (status = 200, description = "User logged in, or pre-auth token when a two-factor code is required", body = LoginUserResponse),
+        (status = 400, description = "Missing code or state, or the OAuth account has no verified email", body = ErrorResponse),
+        (status = 401, description = "Access denied or invalid state", body = ErrorResponse),
+        (status = 403, description = "Account not linkable until its email is verified, or signup disabled", body = ErrorResponse),
+        (status = 404, description = "Unknown or disabled OAuth provider", body = ErrorResponse),
+        (status = 500, description = "Failed to log in", body = ErrorResponse)
+    )
+)]
+pub async fn oauth_callback_route(
+    State(app_state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    headers: HeaderMap,
+    Path(provider): Path<String>,
+    Query(query): Query<OAuthCallbackQuery>,
+) -> impl IntoResponse {
+    let device = session_device(
+        headers
+            .get(USER_AGENT)
+            .and_then(|value| value.to_str().ok()),
+        client_ip,
+    );
+
+    match oauth_service(&app_state)
+        .callback(
+            &provider,
+            query,
+            &device,
+            &app_state.encoding_key,
+            app_state.config.session.duration_minutes,
+        )
+        .await
+    {
+        Ok(response) => (StatusCode::OK, Json(response)).into_response(),
+        Err(error) => {
+            tracing::warn!("OAuth login failed from {}", client_ip);
+            error.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oauth_routes_creation() {
+        let _routes = oauth_routes();
+        assert!(true);
+    }
+}
@@ -0,0 +1,313 @@
+//! # `OAuth` Service
+//!
+//! This module contains the bussiness logic for the logins through an identity provider.
+//! Provider accounts are linked to the user with the same verified email, or to a new
+//! user, and get the same session token as a password login.
+
+use jsonwebtoken::EncodingKey;
+use reqwest::Url;
+use time::{Duration, OffsetDateTime};
+
+use crate::auth::{
+    oauth::{
+        interfaces::OAuthCallbackQuery,
+        provider::{OAuthClients, OAuthIdentity, OAuthProvider},
+        repository::OAuthRepository,
+    },
+    AMR_OAUTH,
+};
+use crate::telemetry::{record_login, LoginOutcome};
+use crate::{
+    modules::{
+        common::AppError,
+        session::{interfaces::SessionDevice, service::SessionService},
+        two_factor::service::TwoFactorService,
+        user::{interfaces::LoginUserResponse, signup::SignupMode},
+    },
+    utils::{
+        password::hash_password,
+        token::{generate_random_token, hash_token},
+    },
+};
+
+// Lifetime of the state of a login, the time the user has to consent at the provider
+const STATE_MINUTES: i64 = 10;
+// Longest username derived from a provider login
+const MAX_USERNAME_CHARS: usize = 32;
+// Usernames tried for a new user before giving up
+const USERNAME_ATTEMPTS: usize = 3;
+
+// Username of a new user from its provider login, keeping letters, digits and `_-.`
+fn username_base(login: &str) -> String {
+    let username: String = login
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        .take(MAX_USERNAME_CHARS)
+        .collect::<String>()
+        .to_lowercase();
+    if username.is_empty() {
+        "user".to_string()
+    } else {
+        username
+    }
+}
+
+fn parse_provider(provider: &str, clients: &OAuthClients) -> Result<OAuthProvider, AppError> {
+    provider
+        .parse::<OAuthProvider>()
+        .ok()
+        .filter(|provider| clients.is_enabled(*provider))
+        .ok_or_else(|| AppError::NotFound("Unknown or disabled OAuth provider".to_string()))
+}
+
+pub struct OAuthService {
+    oauth_repository: OAuthRepository,
+    clients: OAuthClients,
+    session_service: SessionService,
+    two_factor_service: TwoFactorService,
+    signup_mode: SignupMode,
+}
+
+impl OAuthService {
+    pub const fn new(
+        oauth_repository: OAuthRepository,
+        clients: OAuthClients,
+        session_service: SessionService,
+        two_factor_service: TwoFactorService,
+        signup_mode: SignupMode,
+    ) -> Self {
+        Self {
+            oauth_repository,
+            clients,
+            session_service,
+            two_factor_service,
+            signup_mode,
+        }
+    }
+
+    // Start a login, returns the URL of the provider consent page
+    pub async fn start(&self, provider: &str) -> Result<Url, AppError> {
+        let provider = parse_provider(provider, &self.clients)?;
+
+        let state = generate_random_token();
+        let expires_at = OffsetDateTime::now_utc() + Duration::minutes(STATE_MINUTES);
+        if let Err(e) = self
+            .oauth_repository
+            .create_state(&hash_token(&state), provider.as_str(), expires_at)
+            .await
+        {
+            tracing::warn!("Error storing OAuth state: {}", e);
+            return Err(AppError::Internal(
+                "Failed to start OAuth login".to_string(),
+            ));
+        }
+
+        self.clients
+            .authorize_url(provider, &state)
+            .ok_or_else(|| AppError::NotFound("Unknown or disabled OAuth provider".to_string()))
+    }
+
+    // Finish a login with the code the provider redirected the user back with
+    pub async fn callback(
+        &self,
+        provider: &str,
+        query: OAuthCallbackQuery,
+        device: &SessionDevice,
+        encoding_key: &EncodingKey,
+        session_duration: i64,
+    ) -> Result<LoginUserResponse, AppError> {
+        let provider = parse_provider(provider, &self.clients)?;
+        let invalid = |message: &str| {
+            record_login(LoginOutcome::InvalidOAuth);
+            AppError::Unauthorized(message.to_string())
+        };
+
+        if let Some(error) = query.error {
+            tracing::warn!("OAuth login refused by {}: {}", provider.as_str(), error);
+            return Err(invalid("Access denied by the OAuth provider"));
+        }
+        let (Some(code), Some(state)) = (query.code, query.state) else {
+            record_login(LoginOutcome::MissingFields);
+            return Err(AppError::Validation(
+                "Missing required fields: code, state".to_string(),
+            ));
+        };
+
+        // The state proves the login was started here, against login CSRF
+        match self
+            .oauth_repository
+            .consume_state(&hash_token(&state), provider.as_str())
+            .await
+        {
+            Ok(true) => {}
+            Ok(false) => return Err(invalid("Invalid or expired OAuth state")),
+            Err(e) => {
+                tracing::warn!("Error checking OAuth state: {}", e);
+                record_login(LoginOutcome::Error);
+                return Err(AppError::Database(e));
+            }
+        }
+
+        let identity = match self.clients.identity(provider, &code).await {
+            Ok(identity) => identity,
+            Err(e) => {
+                tracing::warn!("Error fetching {} identity: {}", provider.as_str(), e);
+                return Err(invalid("Failed to log in with the OAuth provider"));
+            }
+        };
+
+        let user_id = self
+            .linked_user(provider, identity)
+            .await
+            .map_err(|error| {
+                record_login(LoginOutcome::InvalidOAuth);
+                error
+            })?;
+
+        // Users with a second factor first get a pre-auth token to exchange with a code
+        match self
+            .two_factor_service
+            .start_challenge(user_id, AMR_OAUTH)
+            .await
+        {
+            Ok(None) => {}
+            Ok(Some(token)) => {
+                record_login(LoginOutcome::TwoFactorRequired);
+                return Ok(LoginUserResponse {
+                    token,
+                    message: "Two-factor code required".to_string(),
+                    two_factor_required: true,
+                    csrf_token: None,
+                });
+            }
+            Err(error) => {
+                record_login(LoginOutcome::Error);
+                return Err(error);
+            }
+        }
+
+        let token = self
+            .session_service
+            .open_session(
+                user_id,
+                device,
+                vec![AMR_OAUTH.to_string()],
+                encoding_key,
+                session_duration,
+            )
+            .await
+            .map_err(|error| {
+                record_login(LoginOutcome::Error);
+                error
+            })?;
+
+        record_login(LoginOutcome::Success);
+        Ok(LoginUserResponse {
+            token,
+            message: "User logged in".to_string(),
+            two_factor_required: false,
+            csrf_token: None,
+        })
+    }
+
+    // User of the provider account, linking it on its first login to the user with the
+    // same email or to a new user when the signup is open
+    async fn linked_user(
+        &self,
+        provider: OAuthProvider,
+        identity: OAuthIdentity,
+    ) -> Result<i64, AppError> {
+        let failed = |e: sqlx::Error| {
+            tracing::warn!("Error linking {} account: {}", provider.as_str(), e);
+            AppError::Internal("Failed to log in".to_string())
+        };
+
+        if let Some(user_id) = self
+            .oauth_repository
+            .find_linked_user(provider.as_str(), &identity.subject)
+            .await
+            .map_err(failed)?
+        {
+            return Ok(user_id);
+        }
+
+        let Some(email) = identity.email else {
+            return Err(AppError::Validation(
+                "The OAuth account has no verified email".to_string(),
+            ));
+        };
+
+        match self
+            .oauth_repository
+            .find_user_by_email(&email)
+            .await
+            .map_err(failed)?
+        {
+            // An unverified email may have been registered by someone else, linking it
+            // would hand them the account
+            Some(user) if !user.email_verified => Err(AppError::Forbidden(
+                "Verify the email of your account before logging in with this provider".to_string(),
+            )),
+            Some(user) => {
+                let user_id = i64::from(user.id);
+                self.oauth_repository
+                    .link_account(user_id, provider.as_str(), &identity.subject)
+                    .await
+                    .map_err(failed)?;
+                Ok(user_id)
+            }
+            None if self.signup_mode != SignupMode::Open => Err(AppError::Forbidden(
+                "Signup is disabled on this server, ask an administrator for an account"
+                    .to_string(),
+            )),
+            None => {
+                let username = self.available_username(&identity.login).await?;
+                let password = hash_password(&generate_random_token()).map_err(|e| {
+                    tracing::warn!("Error hashing password: {}", e);
+                    AppError::Internal("Failed to log in".to_string())
+                })?;
+                self.oauth_repository
+                    .create_linked_user(
+                        &username,
+                        &email,
+                        identity.name.as_deref(),
+                        &password,
+                        provider.as_str(),
+                        &identity.subject,
+                    )
+                    .await
+                    .map_err(failed)
+            }
+        }
+    }
+
+    // Username for a new user, its provider login or the login with a random suffix
+    async fn available_username(&self, login: &str) -> Result<String, AppError> {
+        let base = username_base(login);
+        let mut username = base.clone();
+        for _ in 0..USERNAME_ATTEMPTS {
+            match self.oauth_repository.is_username_taken(&username).await {
+                Ok(false) => return Ok(username),
+                Ok(true) => username = format!("{base}-{}", &generate_random_token()[..6]),
+                Err(e) => {
+                    tracing::warn!("Error checking username: {}", e);
+                    break;
+                }
+            }
+        }
+        Err(AppError::Internal("Failed to log in".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_username_base() {
+        assert_eq!(username_base("Ana.Silva"), "ana.silva");
+        assert_eq!(username_base("ana silva+todo"), "anasilvatodo");
+        assert_eq!(username_base("çã"), "user");
+        assert_eq!(username_base(&"a".repeat(40)).len(), MAX_USERNAME_CHARS);
+    }
+}
@@ -0,0 +1,911 @@
+//! # Configuration
+//!
+//! Settings read once at startup. Each setting has a default, replaced by the TOML
+//! file named in `CONFIG_FILE` when set, then by its environment variable. The whole
+//! configuration is validated before the server starts. Settings that can change while
+//! the server runs are in `settings`.
+
+use std::{fmt::Display, str::FromStr};
+
+use serde::Deserialize;
+
+// Secret used in development when none is configured
+const DEFAULT_JWT_SECRET: &str = "my_secret_key";
+
+const DEFAULT_DATABASE_URL: &str = "postgresql://localhost/rust_todo_app";
+
+// Shortest JWT secret accepted in production
+const MIN_JWT_SECRET_BYTES: usize = 32;
+
+// Whether the server runs for development or in production. Production refuses to
+// start without a database URL and a strong JWT secret.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AppEnv {
+    #[default]
+    Development,
+    Production,
+}
+
+impl FromStr for AppEnv {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "development" => Ok(Self::Development),
+            "production" => Ok(Self::Production),
+            other => Err(format!("unknown environment {other}")),
+        }
+    }
+}
+
+// Settings of the application
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub environment: AppEnv,
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub jwt: JwtConfig,
+    pub session: SessionConfig,
+    pub health: HealthConfig,
+    pub todo: TodoConfig,
+    pub smtp: SmtpConfig,
+    pub rate_limit: RateLimitConfig,
+    pub search: SearchConfig,
+    pub demo: DemoConfig,
+}
+
+// Address the server listens on and how it's reached
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub address: String,
+    pub port: u16,
+    // Public base URL of the API, used in the links of the emails.
+    // `http://localhost:<port>` when empty.
+    pub public_url: String,
+    pub instance_name: String,
+    // Longest wait for the background tasks at shutdown
+    pub shutdown_timeout_seconds: u64,
+    // Longest time to answer a request before a 408, uploads included
+    pub request_timeout_seconds: u64,
+    // Largest request body, except the attachments which have their own limit
+    pub body_limit_bytes: usize,
+    // Gzip or brotli responses for the clients accepting them
+    pub compression: bool,
+    // Log every response with its status and latency, not only with `RUST_LOG=debug`
+    pub access_log: bool,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            address: "127.0.0.1".to_string(),
+            port: 8000,
+            public_url: String::new(),
+            instance_name: "Todo App".to_string(),
+            shutdown_timeout_seconds: 30,
+            request_timeout_seconds: 30,
+            body_limit_bytes: 2 * 1024 * 1024,
+            compression: true,
+            access_log: false,
+        }
+    }
+}
+
+impl ServerConfig {
+    // Socket address the listener binds to
+    pub fn bind_address(&self) -> String {
+        format!("{}:{}", self.address, self.port)
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    // Required in production
+    pub url: String,
+    pub max_connections: u32,
+    // Connections kept open even when idle
+    pub min_connections: u32,
+    // Longest wait for a free connection before a query fails
+    pub acquire_timeout_seconds: u64,
+    // Idle connections above `min_connections` are closed after it, 0 keeps them
+    pub idle_timeout_seconds: u64,
+    // Statements running longer are cancelled by Postgres, 0 disables it
+    pub statement_timeout_ms: u64,
+    // Tries to connect at startup, the delay between them doubles from
+    // `connect_backoff_ms` up to `connect_max_backoff_ms`
+    pub connect_attempts: u32,
+    pub connect_backoff_ms: u64,
+    pub connect_max_backoff_ms: u64,
+    // Start without connecting, the first queries open the connections
+    pub lazy_connect: bool,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            max_connections: 5,
+            min_connections: 0,
+            acquire_timeout_seconds: 30,
+            idle_timeout_seconds: 600,
+            statement_timeout_ms: 0,
+            connect_attempts: 10,
+            connect_backoff_ms: 500,
+            connect_max_backoff_ms: 10_000,
+            lazy_connect: false,
+        }
+    }
+}
+
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JwtConfig {
+    // Required in production, at least `MIN_JWT_SECRET_BYTES` long
+    pub secret: String,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SessionConfig {
+    pub duration_minutes: i64,
+    // Session cookies only sent over HTTPS, browsers still send them to localhost
+    pub cookie_secure: bool,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            duration_minutes: 60,
+            cookie_secure: true,
+        }
+    }
+}
+
+// Checks of the database read by `/ping`
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HealthConfig {
+    pub sample_seconds: u64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self { sample_seconds: 5 }
+    }
+}
+
+// Caches, retention and polling of the todos
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TodoConfig {
+    pub view_cache_ttl_seconds: u64,
+    // 0 disables the deduplication of the creations
+    pub dedup_window_seconds: u64,
+    pub trash_retention_days: i64,
+    pub tombstone_retention_days: i64,
+    pub reminder_poll_seconds: u64,
+}
+
+impl Default for TodoConfig {
+    fn default() -> Self {
+        Self {
+            view_cache_ttl_seconds: 30,
+            dedup_window_seconds: 0,
+            trash_retention_days: 30,
+            tombstone_retention_days: 90,
+            reminder_poll_seconds: 30,
+        }
+    }
+}
+
+// Server the emails are sent through, they are only logged without a host
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SmtpConfig {
+    pub host: Option<String>,
+    pub port: u16,
+    // `starttls`, `tls` or `none`
+    pub tls: String,
+    pub username: Option<String>,
+    pub password: String,
+    pub from: String,
+    pub timeout_seconds: u64,
+    pub retry_attempts: u32,
+    // Domains verified with the email provider
+    pub sender_domains: Vec<String>,
+}
+
+impl Default for SmtpConfig {
+    fn default() -> Self {
+        Self {
+            host: None,
+            port: 587,
+            tls: "starttls".to_string(),
+            username: None,
+            password: String::new(),
+            from: "Todo App <no-reply@localhost>".to_string(),
+            timeout_seconds: 10,
+            retry_attempts: 3,
+            sender_domains: Vec::new(),
+        }
+    }
+}
+
+// Where the rate limit counters are kept
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitBackend {
+    Off,
+    Memory,
+    Redis,
+}
+
+impl FromStr for RateLimitBackend {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim() {
+            "off" => Ok(Self::Off),
+            "memory" => Ok(Self::Memory),
+            "redis" => Ok(Self::Redis),
+            other => Err(format!("unknown backend {other}")),
+        }
+    }
+}
+
+// Limits of the authentication requests, a max of 0 disables a limit
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    pub backend: RateLimitBackend,
+    pub redis_url: String,
+    pub ip_max: u64,
+    pub ip_window_seconds: u64,
+    pub username_max: u64,
+    pub username_window_seconds: u64,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            backend: RateLimitBackend::Memory,
+            redis_url: "redis://localhost:6379".to_string(),
+            ip_max: 20,
+            ip_window_seconds: 60,
+            username_max: 5,
+            username_window_seconds: 300,
+        }
+    }
+}
+
+// Index the todo searches run on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchBackend {
+    Postgres,
+    Meilisearch,
+}
+
+impl FromStr for SearchBackend {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim() {
+            "postgres" => Ok(Self::Postgres),
+            "meilisearch" => Ok(Self::Meilisearch),
+            other => Err(format!("unknown backend {other}")),
+        }
+    }
+}
+
+// Search of the todos, the full text search of Postgres or a Meilisearch index kept in
+// sync with the outbox events
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SearchConfig {
+    pub backend: SearchBackend,
+    pub meilisearch_url: String,
+    pub meilisearch_api_key: Option<String>,
+    pub meilisearch_index: String,
+    pub timeout_seconds: u64,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            backend: SearchBackend::Postgres,
+            meilisearch_url: "http://localhost:7700".to_string(),
+            meilisearch_api_key: None,
+            meilisearch_index: "todos".to_string(),
+            timeout_seconds: 5,
+        }
+    }
+}
+
+// Sandbox sessions of `/demo/session`, off unless enabled
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DemoConfig {
+    pub enabled: bool,
+    // Lifetime of the demo users and of their token
+    pub session_minutes: i64,
+}
+
+impl Default for DemoConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            session_minutes: 30,
+        }
+    }
+}
+
+// Replace `setting` with the parsed variable, when set
+fn override_with<T>(setting: &mut T, name: &str, value: Option<String>) -> Result<(), String>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    if let Some(value) = value {
+        *setting = value.parse().map_err(|e| format!("Invalid {name}: {e}"))?;
+    }
+    Ok(())
+}
+
+impl Config {
+    // Configuration of the TOML file in `CONFIG_FILE` and the environment, validated
+    pub fn load() -> Result<Self, String> {
+        let mut config = match std::env::var("CONFIG_FILE")
+            .ok()
+            .filter(|path| !path.trim().is_empty())
+        {
+            Some(path) => {
+                let text = std::fs::read_to_string(path.trim())
+                    .map_err(|e| format!("Could not read CONFIG_FILE {path}: {e}"))?;
+                Self::from_toml(&text)?
+            }
+            None => Self::default(),
+        };
+        config.apply_env(|name| std::env::var(name).ok())?;
+        if config.environment == AppEnv::Development {
+            config.use_development_defaults();
+        }
+        config.normalize();
+        config.validate()?;
+        Ok(config)
+    }
+
+    // Fill the database URL and JWT secret left unset, only done in development
+    fn use_development_defaults(&mut self) {
+        if self.database.url.is_empty() {
+            tracing::warn!("DATABASE_URL not set, using default PostgreSQL connection");
+            self.database.url = DEFAULT_DATABASE_URL.to_string();
+        }
+        if self.jwt.secret.is_empty() {
+            tracing::warn!("JWT_SECRET not set, using default secret");
+            self.jwt.secret = DEFAULT_JWT_SECRET.to_string();
+        }
+    }
+
+    // Settings of a configuration file, unset ones keep their default
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        toml::from_str(text).map_err(|e| format!("Invalid CONFIG_FILE: {e}"))
+    }
+
+    // Override the settings with the environment variables set to a non empty value
+    #[allow(clippy::too_many_lines)]
+    fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<(), String> {
+        let var = |name: &str| var(name).filter(|value| !value.trim().is_empty());
+
+        override_with(&mut self.environment, "APP_ENV", var("APP_ENV"))?;
+
+        override_with(&mut self.server.address, "ADDRESS", var("ADDRESS"))?;
+        override_with(&mut self.server.port, "PORT", var("PORT"))?;
+        override_with(&mut self.server.public_url, "PUBLIC_URL", var("PUBLIC_URL"))?;
+        override_with(
+            &mut self.server.instance_name,
+            "INSTANCE_NAME",
+            var("INSTANCE_NAME"),
+        )?;
+        override_with(
+            &mut self.server.shutdown_timeout_seconds,
+            "SHUTDOWN_TIMEOUT_SECONDS",
+            var("SHUTDOWN_TIMEOUT_SECONDS"),
+        )?;
+        override_with(
+            &mut self.server.request_timeout_seconds,
+            "REQUEST_TIMEOUT_SECONDS",
+            var("REQUEST_TIMEOUT_SECONDS"),
+        )?;
+        override_with(
+            &mut self.server.body_limit_bytes,
+            "BODY_LIMIT_BYTES",
+            var("BODY_LIMIT_BYTES"),
+        )?;
+        override_with(
+            &mut self.server.compression,
+            "COMPRESSION",
+            var("COMPRESSION"),
+        )?;
+        override_with(&mut self.server.access_log, "ACCESS_LOG", var("ACCESS_LOG"))?;
+
+        override_with(&mut self.database.url, "DATABASE_URL", var("DATABASE_URL"))?;
+        override_with(
+            &mut self.database.max_connections,
+            "DATABASE_MAX_CONNECTIONS",
+            var("DATABASE_MAX_CONNECTIONS"),
+        )?;
+        override_with(
+            &mut self.database.min_connections,
+            "DATABASE_MIN_CONNECTIONS",
+            var("DATABASE_MIN_CONNECTIONS"),
+        )?;
+        override_with(
+            &mut self.database.acquire_timeout_seconds,
+            "DATABASE_ACQUIRE_TIMEOUT_SECONDS",
+            var("DATABASE_ACQUIRE_TIMEOUT_SECONDS"),
+        )?;
+        override_with(
+            &mut self.database.idle_timeout_seconds,
+            "DATABASE_IDLE_TIMEOUT_SECONDS",
+            var("DATABASE_IDLE_TIMEOUT_SECONDS"),
+        )?;
+        override_with(
+            &mut self.database.statement_timeout_ms,
+            "DATABASE_STATEMENT_TIMEOUT_MS",
+            var("DATABASE_STATEMENT_TIMEOUT_MS"),
+        )?;
+        override_with(
+            &mut self.database.connect_attempts,
+            "DATABASE_CONNECT_ATTEMPTS",
+            var("DATABASE_CONNECT_ATTEMPTS"),
+        )?;
+        override_with(
+            &mut self.database.connect_backoff_ms,
+            "DATABASE_CONNECT_BACKOFF_MS",
+            var("DATABASE_CONNECT_BACKOFF_MS"),
+        )?;
+        override_with(
+            &mut self.database.connect_max_backoff_ms,
+            "DATABASE_CONNECT_MAX_BACKOFF_MS",
+            var("DATABASE_CONNECT_MAX_BACKOFF_MS"),
+        )?;
+        override_with(
+            &mut self.database.lazy_connect,
+            "DATABASE_LAZY_CONNECT",
+            var("DATABASE_LAZY_CONNECT"),
+        )?;
+
+        override_with(&mut self.jwt.secret, "JWT_SECRET", var("JWT_SECRET"))?;
+        override_with(
+            &mut self.session.duration_minutes,
+            "SESSION_DURATION_MINUTES",
+            var("SESSION_DURATION_MINUTES"),
+        )?;
+        override_with(
+            &mut self.session.cookie_secure,
+            "SESSION_COOKIE_SECURE",
+            var("SESSION_COOKIE_SECURE"),
+        )?;
+
+        override_with(
+            &mut self.health.sample_seconds,
+            "HEALTH_SAMPLE_SECONDS",
+            var("HEALTH_SAMPLE_SECONDS"),
+        )?;
+
+        override_with(
+            &mut self.todo.view_cache_ttl_seconds,
+            "VIEW_CACHE_TTL_SECONDS",
+            var("VIEW_CACHE_TTL_SECONDS"),
+        )?;
+        override_with(
+            &mut self.todo.dedup_window_seconds,
+            "TODO_DEDUP_WINDOW_SECONDS",
+            var("TODO_DEDUP_WINDOW_SECONDS"),
+        )?;
+        override_with(
+            &mut self.todo.trash_retention_days,
+            "TRASH_RETENTION_DAYS",
+            var("TRASH_RETENTION_DAYS"),
+        )?;
+        override_with(
+            &mut self.todo.tombstone_retention_days,
+            "TOMBSTONE_RETENTION_DAYS",
+            var("TOMBSTONE_RETENTION_DAYS"),
+        )?;
+        override_with(
+            &mut self.todo.reminder_poll_seconds,
+            "REMINDER_POLL_SECONDS",
+            var("REMINDER_POLL_SECONDS"),
+        )?;
+
+        if let Some(host) = var("SMTP_HOST") {
+            self.smtp.host = Some(host);
+        }
+        override_with(&mut self.smtp.port, "SMTP_PORT", var("SMTP_PORT"))?;
+        override_with(&mut self.smtp.tls, "SMTP_TLS", var("SMTP_TLS"))?;
+        if let Some(username) = var("SMTP_USERNAME") {
+            self.smtp.username = Some(username);
+        }
+        override_with(
+            &mut self.smtp.password,
+            "SMTP_PASSWORD",
+            var("SMTP_PASSWORD"),
+        )?;
+        override_with(&mut self.smtp.from, "EMAIL_FROM", var("EMAIL_FROM"))?;
+        override_with(
+            &mut self.smtp.timeout_seconds,
+            "EMAIL_TIMEOUT_SECONDS",
+            var("EMAIL_TIMEOUT_SECONDS"),
+        )?;
+        override_with(
+            &mut self.smtp.retry_attempts,
+            "EMAIL_RETRY_ATTEMPTS",
+            var("EMAIL_RETRY_ATTEMPTS"),
+        )?;
+        if let Some(domains) = var("EMAIL_SENDER_DOMAINS") {
+            self.smtp.sender_domains = domains.split(',').map(str::to_string).collect();
+        }
+
+        override_with(
+            &mut self.rate_limit.backend,
+            "RATE_LIMIT_BACKEND",
+            var("RATE_LIMIT_BACKEND"),
+        )?;
+        override_with(
+            &mut self.rate_limit.redis_url,
+            "REDIS_URL",
+            var("REDIS_URL"),
+        )?;
+        override_with(
+            &mut self.rate_limit.ip_max,
+            "RATE_LIMIT_IP_MAX",
+            var("RATE_LIMIT_IP_MAX"),
+        )?;
+        override_with(
+            &mut self.rate_limit.ip_window_seconds,
+            "RATE_LIMIT_IP_WINDOW_SECONDS",
+            var("RATE_LIMIT_IP_WINDOW_SECONDS"),
+        )?;
+        override_with(
+            &mut self.rate_limit.username_max,
+            "RATE_LIMIT_USERNAME_MAX",
+            var("RATE_LIMIT_USERNAME_MAX"),
+        )?;
+        override_with(
+            &mut self.rate_limit.username_window_seconds,
+            "RATE_LIMIT_USERNAME_WINDOW_SECONDS",
+            var("RATE_LIMIT_USERNAME_WINDOW_SECONDS"),
+        )?;
+
+        override_with(
+            &mut self.search.backend,
+            "SEARCH_BACKEND",
+            var("SEARCH_BACKEND"),
+        )?;
+        override_with(
+            &mut self.search.meilisearch_url,
+            "MEILISEARCH_URL",
+            var("MEILISEARCH_URL"),
+        )?;
+        if let Some(api_key) = var("MEILISEARCH_API_KEY") {
+            self.search.meilisearch_api_key = Some(api_key);
+        }
+        override_with(
+            &mut self.search.meilisearch_index,
+            "MEILISEARCH_INDEX",
+            var("MEILISEARCH_INDEX"),
+        )?;
+        override_with(
+            &mut self.search.timeout_seconds,
+            "SEARCH_TIMEOUT_SECONDS",
+            var("SEARCH_TIMEOUT_SECONDS"),
+        )?;
+
+        override_with(&mut self.demo.enabled, "DEMO_ENABLED", var("DEMO_ENABLED"))?;
+        override_with(
+            &mut self.demo.session_minutes,
+            "DEMO_SESSION_MINUTES",
+            var("DEMO_SESSION_MINUTES"),
+        )
+    }
+
+    // Trim the values and fill the settings derived from others
+    fn normalize(&mut self) {
+        if self.server.public_url.trim().is_empty() {
+            self.server.public_url = format!("http://localhost:{}", self.server.port);
+        }
+        self.server.public_url = self.server.public_url.trim().to_string();
+        self.smtp.host = self
+            .smtp
+            .host
+            .as_deref()
+            .map(str::trim)
+            .filter(|host| !host.is_empty())
+            .map(str::to_string);
+        self.smtp.username = self
+            .smtp
+            .username
+            .take()
+            .filter(|username| !username.is_empty());
+        self.smtp.sender_domains = self
+            .smtp
+            .sender_domains
+            .iter()
+            .map(|domain| domain.trim().to_ascii_lowercase())
+            .filter(|domain| !domain.is_empty())
+            .collect();
+    }
+
+    // Reject the settings the server can't run with
+    fn validate(&self) -> Result<(), String> {
+        let checks = [
+            (self.server.port > 0, "server.port must be positive"),
+            (
+                self.server.request_timeout_seconds > 0,
+                "server.request_timeout_seconds must be positive",
+            ),
+            (
+                self.server.body_limit_bytes > 0,
+                "server.body_limit_bytes must be positive",
+            ),
+            (
+                self.database.max_connections > 0,
+                "database.max_connections must be positive",
+            ),
+            (
+                self.database.min_connections <= self.database.max_connections,
+                "database.min_connections must not exceed database.max_connections",
+            ),
+            (
+                self.database.acquire_timeout_seconds > 0,
+                "database.acquire_timeout_seconds must be positive",
+            ),
+            (
+                self.database.connect_attempts > 0,
+                "database.connect_attempts must be positive",
+            ),
+            (
+                self.database.connect_max_backoff_ms >= self.database.connect_backoff_ms,
+                "database.connect_max_backoff_ms must not be below database.connect_backoff_ms",
+            ),
+            (
+                !self.database.url.is_empty(),
+                "database.url must be set, with DATABASE_URL or the configuration file",
+            ),
+            (
+                !self.jwt.secret.is_empty(),
+                "jwt.secret must be set, with JWT_SECRET or the configuration file",
+            ),
+            (
+                self.environment == AppEnv::Development
+                    || self.jwt.secret.len() >= MIN_JWT_SECRET_BYTES,
+                "jwt.secret must be at least 32 bytes long in production",
+            ),
+            (
+                self.session.duration_minutes > 0,
+                "session.duration_minutes must be positive",
+            ),
+            (
+                self.health.sample_seconds > 0,
+                "health.sample_seconds must be positive",
+            ),
+            (
+                self.todo.trash_retention_days >= 0,
+                "todo.trash_retention_days must not be negative",
+            ),
+            (
+                self.todo.tombstone_retention_days >= 0,
+                "todo.tombstone_retention_days must not be negative",
+            ),
+            (
+                self.todo.reminder_poll_seconds > 0,
+                "todo.reminder_poll_seconds must be positive",
+            ),
+            (
+                self.smtp.timeout_seconds > 0,
+                "smtp.timeout_seconds must be positive",
+            ),
+            (
+                self.smtp.retry_attempts > 0,
+                "smtp.retry_attempts must be positive",
+            ),
+            (
+                self.rate_limit.ip_window_seconds > 0,
+                "rate_limit.ip_window_seconds must be positive",
+            ),
+            (
+                self.rate_limit.username_window_seconds > 0,
+                "rate_limit.username_window_seconds must be positive",
+            ),
+            (
+                self.search.timeout_seconds > 0,
+                "search.timeout_seconds must be positive",
+            ),
+            (
+                self.search.backend == SearchBackend::Postgres
+                    || !self.search.meilisearch_index.trim().is_empty(),
+                "search.meilisearch_index must be set to search with Meilisearch",
+            ),
+            (
+                self.demo.session_minutes > 0,
+                "demo.session_minutes must be positive",
+            ),
+        ];
+        match checks.iter().find(|(valid, _)| !valid) {
+            Some((_, message)) => Err(format!("Invalid configuration: {message}")),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn with_env(config: &mut Config, vars: &[(&str, &str)]) -> Result<(), String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| ((*name).to_string(), (*value).to_string()))
+            .collect();
+        config.apply_env(|name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn test_defaults() {
+        let mut config = Config::default();
+        config.use_development_defaults();
+        config.normalize();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.server.bind_address(), "127.0.0.1:8000");
+        assert_eq!(config.server.public_url, "http://localhost:8000");
+        assert_eq!(config.session.duration_minutes, 60);
+        assert_eq!(config.rate_limit.backend, RateLimitBackend::Memory);
+        assert_eq!(config.search.backend, SearchBackend::Postgres);
+        assert!(config.smtp.host.is_none());
+    }
+
+    #[test]
+    fn test_from_toml() {
+        let config = Config::from_toml(
+            r#"
+            [server]
+            port = 3000
+
+            [smtp]
+            host = "smtp.example.com"
+            sender_domains = ["Example.com"]
+
+            [rate_limit]
+            backend = "off"
+
+            [search]
+            backend = "meilisearch"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.server.port, 3000);
+        assert_eq!(config.server.address, "127.0.0.1");
+        assert_eq!(config.smtp.host.as_deref(), Some("smtp.example.com"));
+        assert_eq!(config.smtp.port, 587);
+        assert_eq!(config.rate_limit.backend, RateLimitBackend::Off);
+        assert_eq!(config.search.backend, SearchBackend::Meilisearch);
+        assert_eq!(config.search.meilisearch_index, "todos");
+
+        assert!(Config::from_toml("[server]\nprot = 3000").is_err());
+        assert!(Config::from_toml("[rate_limit]\nbackend = \"disk\"").is_err());
+    }
+
+    #[test]
+    fn test_env_overrides_file() {
+        let mut config = Config::from_toml("[server]\nport = 3000\naddress = \"0.0.0.0\"").unwrap();
+        with_env(
+            &mut config,
+            &[
+                ("PORT", "4000"),
+                ("ADDRESS", ""),
+                ("RATE_LIMIT_BACKEND", "redis"),
+                ("DATABASE_LAZY_CONNECT", "true"),
+                ("DEMO_ENABLED", "true"),
+                ("COMPRESSION", "false"),
+                ("EMAIL_SENDER_DOMAINS", " Example.com, ,mail.example.org"),
+            ],
+        )
+        .unwrap();
+        config.normalize();
+
+        assert_eq!(config.server.bind_address(), "0.0.0.0:4000");
+        assert_eq!(config.server.public_url, "http://localhost:4000");
+        assert_eq!(config.rate_limit.backend, RateLimitBackend::Redis);
+        assert!(config.database.lazy_connect);
+        assert!(config.demo.enabled);
+        assert!(!config.server.compression);
+        assert_eq!(
+            config.smtp.sender_domains,
+            vec!["example.com", "mail.example.org"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_env() {
+        let mut config = Config::default();
+        let error = with_env(&mut config, &[("PORT", "eighty")]).unwrap_err();
+        assert!(error.starts_with("Invalid PORT"));
+        assert!(with_env(&mut config, &[("RATE_LIMIT_BACKEND", "disk")]).is_err());
+        assert!(with_env(&mut config, &[("SEARCH_BACKEND", "typesense")]).is_err());
+    }
+
+    // Defaults of a development server
+    fn development() -> Config {
+        let mut config = Config::default();
+        config.use_development_defaults();
+        config
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(development().validate().is_ok());
+
+        let mut config = development();
+        config.session.duration_minutes = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = development();
+        config.todo.reminder_poll_seconds = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = development();
+        config.rate_limit.ip_window_seconds = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = development();
+        config.search.backend = SearchBackend::Meilisearch;
+        config.search.meilisearch_index = " ".to_string();
+        assert!(config.validate().is_err());
+
+        let mut config = development();
+        config.server.request_timeout_seconds = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = development();
+        config.demo.session_minutes = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = development();
+        config.database.min_connections = 10;
+        assert!(config.validate().is_err());
+
+        let mut config = development();
+        config.database.connect_attempts = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = development();
+        config.database.connect_max_backoff_ms = 100;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_production_requires_secrets() {
+        let mut config = Config::default();
+        with_env(&mut config, &[("APP_ENV", "Production")]).unwrap();
+        assert_eq!(config.environment, AppEnv::Production);
+        assert!(config.validate().is_err());
+
+        config.database.url = "postgresql://db/todo_app".to_string();
+        config.jwt.secret = DEFAULT_JWT_SECRET.to_string();
+        assert!(config.validate().is_err());
+
+        config.jwt.secret = "a".repeat(MIN_JWT_SECRET_BYTES);
+        assert!(config.validate().is_ok());
+
+        assert!(with_env(&mut config, &[("APP_ENV", "staging")]).is_err());
+    }
+}
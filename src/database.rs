@@ -0,0 +1,296 @@
+//! # Database Connection
+//!
+//! The pool is opened at startup. A database still starting, like Postgres in
+//! docker-compose, is retried with an exponential backoff instead of stopping the
+//! process. With `lazy_connect` the server starts right away and the connections are
+//! opened by the first queries, the workers wait for the database on their own.
+//!
+//! The size and timeouts of the pool come from the configuration. A monitor exports the
+//! connections of the pool as metrics and logs when every connection is busy.
+//!
+//! Queries failing with a transient error, a serialization failure, a deadlock or a lost
+//! connection, can be retried a few times with `retry_transient`. Errors that persist
+//! while the database is unreachable are answered with 503, see `is_unavailable`.
+
+use std::{future::Future, str::FromStr, time::Duration};
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use sqlx::{
+    postgres::{PgConnectOptions, PgPoolOptions},
+    Pool, Postgres,
+};
+
+use crate::{
+    config::DatabaseConfig,
+    telemetry::{observe_query, record_pool, record_query_retry},
+};
+
+// Delay between two samples of the pool connections
+const POOL_SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+
+// Runs of a query failing with a transient error, the first one included
+const TRANSIENT_ATTEMPTS: u32 = 3;
+
+// Delay before the first retry of a query, doubled for the next one
+const TRANSIENT_BACKOFF: Duration = Duration::from_millis(20);
+
+// Longest delay between two runs of a query
+const TRANSIENT_MAX_BACKOFF: Duration = Duration::from_millis(200);
+
+// Delay before the attempt following `attempt`, doubled from `initial` up to `max`.
+// Half of it is random so instances restarted together don't retry in step.
+fn backoff_delay(attempt: u32, initial: Duration, max: Duration, random: u64) -> Duration {
+    let delay = initial
+        .saturating_mul(2_u32.saturating_pow(attempt.saturating_sub(1)))
+        .min(max);
+    let half_ms = u64::try_from(delay.as_millis() / 2).unwrap_or(u64::MAX);
+    let jitter_ms = random.checked_rem(half_ms.saturating_add(1)).unwrap_or(0);
+    Duration::from_millis(half_ms.saturating_add(jitter_ms))
+}
+
+// Size and timeouts of the pool
+fn pool_options(config: &DatabaseConfig) -> PgPoolOptions {
+    let idle_timeout =
+        (config.idle_timeout_seconds > 0).then(|| Duration::from_secs(config.idle_timeout_seconds));
+    PgPoolOptions::new()
+        .max_connections(config.max_connections)
+        .min_connections(config.min_connections)
+        .acquire_timeout(Duration::from_secs(config.acquire_timeout_seconds))
+        .idle_timeout(idle_timeout)
+}
+
+// Connection settings of the URL, with the statement timeout of each session
+fn connect_options(config: &DatabaseConfig) -> Result<PgConnectOptions, sqlx::Error> {
+    let options = PgConnectOptions::from_str(&config.url)?;
+    if config.statement_timeout_ms == 0 {
+        return Ok(options);
+    }
+    Ok(options.options([("statement_timeout", config.statement_timeout_ms.to_string())]))
+}
+
+// Pool of the configured database, connected with `connect_attempts` tries unless lazy
+pub async fn connect_pool(config: &DatabaseConfig) -> Result<Pool<Postgres>, sqlx::Error> {
+    let options = pool_options(config);
+    let connect_options = connect_options(config)?;
+    if config.lazy_connect {
+        tracing::info!("Database connections are opened on first use");
+        return Ok(options.connect_lazy_with(connect_options));
+    }
+
+    let initial = Duration::from_millis(config.connect_backoff_ms);
+    let max = Duration::from_millis(config.connect_max_backoff_ms);
+    let mut attempt = 1;
+    loop {
+        match options.clone().connect_with(connect_options.clone()).await {
+            Ok(pool) => return Ok(pool),
+            Err(e) if attempt >= config.connect_attempts => return Err(e),
+            Err(e) => {
+                let delay = backoff_delay(attempt, initial, max, OsRng.next_u64());
+                tracing::warn!(
+                    "Database connection attempt {}/{} failed, retrying in {}ms: {}",
+                    attempt,
+                    config.connect_attempts,
+                    delay.as_millis(),
+                    e
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+// Whether a SQLSTATE is worth running the query again: serialization failures and
+// deadlocks roll the transaction back, the connection errors (class 08) and the
+// shutdowns of the server (57P01 to 57P03) drop the connection
+fn is_transient_code(code: &str) -> bool {
+    matches!(code, "40001" | "40P01" | "57P01" | "57P02" | "57P03") || code.starts_with("08")
+}
+
+// Whether the error may not happen again when the query is run again
+pub fn is_transient(error: &sqlx::Error) -> bool {
+    match error {
+        sqlx::Error::Io(_) => true,
+        sqlx::Error::Database(e) => e.code().is_some_and(|code| is_transient_code(&code)),
+        _ => false,
+    }
+}
+
+// Whether the error comes from a database unreachable or overloaded, rather than from
+// the query itself. The requests failing with it are answered with 503.
+pub fn is_unavailable(error: &sqlx::Error) -> bool {
+    match error {
+        sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed | sqlx::Error::WorkerCrashed => true,
+        sqlx::Error::Database(e) => e.code().as_deref() == Some("53300") || is_transient(error),
+        _ => is_transient(error),
+    }
+}
+
+// Run a repository query, observed as `name`, again while it fails with a transient
+// error, up to `TRANSIENT_ATTEMPTS` runs. Only for reads and for transactions that can
+// run twice: a connection lost while committing leaves the first run unknown.
+pub async fn retry_transient<T, F, Fut>(name: &'static str, mut query: F) -> Result<T, sqlx::Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, sqlx::Error>>,
+{
+    let mut attempt = 1;
+    loop {
+        match observe_query(name, query()).await {
+            Err(e) if attempt < TRANSIENT_ATTEMPTS && is_transient(&e) => {
+                let delay = backoff_delay(
+                    attempt,
+                    TRANSIENT_BACKOFF,
+                    TRANSIENT_MAX_BACKOFF,
+                    OsRng.next_u64(),
+                );
+                tracing::warn!(
+                    "Query {} failed with a transient error, retrying in {}ms: {}",
+                    name,
+                    delay.as_millis(),
+                    e
+                );
+                record_query_retry(name);
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+// Whether every connection of the pool is open and busy
+const fn is_saturated(size: u32, idle: usize, max: u32) -> bool {
+    size >= max && idle == 0
+}
+
+// Export the connections of the pool every `POOL_SAMPLE_INTERVAL`. Saturation is logged
+// when it starts and when it ends, not on every sample.
+pub fn start_pool_monitor(pool: Pool<Postgres>) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(POOL_SAMPLE_INTERVAL);
+        let mut was_saturated = false;
+        loop {
+            ticker.tick().await;
+            let size = pool.size();
+            let idle = pool.num_idle();
+            let max = pool.options().get_max_connections();
+            record_pool(size, idle, max);
+
+            let saturated = is_saturated(size, idle, max);
+            if saturated && !was_saturated {
+                tracing::warn!(
+                    "Database pool saturated, all {} connections are busy. Raise database.max_connections if queries time out.",
+                    max
+                );
+            } else if !saturated && was_saturated {
+                tracing::info!("Database pool has idle connections again");
+            }
+            was_saturated = saturated;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_delay_doubles_up_to_max() {
+        let initial = Duration::from_millis(500);
+        let max = Duration::from_secs(10);
+        assert_eq!(
+            backoff_delay(1, initial, max, 0),
+            Duration::from_millis(250)
+        );
+        assert_eq!(backoff_delay(3, initial, max, 0), Duration::from_secs(1));
+        assert_eq!(backoff_delay(30, initial, max, 0), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_transient_errors() {
+        for code in ["40001", "40P01", "08006", "08001", "57P01"] {
+            assert!(is_transient_code(code), "{code}");
+        }
+        for code in ["23505", "42P01", "53300", "57014"] {
+            assert!(!is_transient_code(code), "{code}");
+        }
+
+        let reset = sqlx::Error::Io(std::io::Error::from(std::io::ErrorKind::ConnectionReset));
+        assert!(is_transient(&reset));
+        assert!(is_unavailable(&reset));
+        assert!(!is_transient(&sqlx::Error::PoolTimedOut));
+        assert!(is_unavailable(&sqlx::Error::PoolTimedOut));
+        assert!(!is_transient(&sqlx::Error::RowNotFound));
+        assert!(!is_unavailable(&sqlx::Error::RowNotFound));
+    }
+
+    #[tokio::test]
+    async fn test_retry_transient() {
+        let mut runs = 0;
+        let result = retry_transient("test.retry", || {
+            runs += 1;
+            let run = runs;
+            async move {
+                if run < 3 {
+                    Err(sqlx::Error::Io(std::io::Error::from(
+                        std::io::ErrorKind::ConnectionReset,
+                    )))
+                } else {
+                    Ok(run)
+                }
+            }
+        })
+        .await;
+        assert_eq!(result.ok(), Some(3));
+
+        let mut runs = 0;
+        let result: Result<(), _> = retry_transient("test.retry", || {
+            runs += 1;
+            async { Err(sqlx::Error::RowNotFound) }
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(runs, 1);
+
+        let mut runs = 0;
+        let result: Result<(), _> = retry_transient("test.retry", || {
+            runs += 1;
+            async { Err(sqlx::Error::Io(std::io::ErrorKind::BrokenPipe.into())) }
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(runs, TRANSIENT_ATTEMPTS);
+    }
+
+    #[test]
+    fn test_is_saturated() {
+        assert!(is_saturated(5, 0, 5));
+        assert!(!is_saturated(5, 1, 5));
+        assert!(!is_saturated(3, 0, 5));
+    }
+
+    #[test]
+    fn test_connect_options_reject_invalid_url() {
+        let config = DatabaseConfig {
+            url: "not a url".to_string(),
+            ..DatabaseConfig::default()
+        };
+        assert!(connect_options(&config).is_err());
+    }
+
+    #[test]
+    fn test_backoff_delay_jitter_stays_in_range() {
+        let initial = Duration::from_millis(500);
+        let max = Duration::from_secs(10);
+        for random in [1, 249, 250, 251, u64::MAX] {
+            let delay = backoff_delay(1, initial, max, random);
+            assert!(delay >= Duration::from_millis(250));
+            assert!(delay <= initial);
+        }
+        assert_eq!(
+            backoff_delay(1, Duration::ZERO, max, u64::MAX),
+            Duration::ZERO
+        );
+    }
+}
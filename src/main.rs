@@ -4,25 +4,116 @@
 //! This application provides a REST API for managing todo items with
 //! comprehensive health checks and Swagger documentation.
 
-use axum::Router;
+use auth::oauth::{oauth_routes, provider::OAuthClients};
+use auth::require_verified_email;
+use axum::{extract::DefaultBodyLimit, http::StatusCode, middleware, Router};
+use config::Config;
+use database::{connect_pool, start_pool_monitor};
 use dotenvy::dotenv;
 use jsonwebtoken::{DecodingKey, EncodingKey};
-use sqlx::postgres::PgPoolOptions;
 use sqlx::{Pool, Postgres};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::watch;
+use tower_http::{
+    compression::CompressionLayer, limit::RequestBodyLimitLayer, timeout::TimeoutLayer,
+    trace::TraceLayer,
+};
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
 mod swagger {
     pub mod doc_config;
+    pub mod postman;
 }
 
 mod auth;
+mod config;
+mod database;
 mod modules;
+mod otel;
+mod settings;
+mod storage;
+mod tasks;
+mod telemetry;
 mod utils;
-
+mod workers;
+
+use modules::admin::admin_routes;
+use modules::api_key::api_key_routes;
+use modules::assignment::assignment_routes;
+use modules::attachment::attachment_routes;
+use modules::attachment::storage::AttachmentStorage;
+use modules::audit::audit_routes;
+use modules::badge::badge_routes;
+use modules::changes::changes_routes;
+use modules::changes::repository::ChangesRepository;
+use modules::demo::demo_routes;
+use modules::demo::{purge::demo_purge_worker, repository::DemoRepository};
+use modules::email::mailer::{mailer_from_config, Mailer};
+use modules::export::export_routes;
+use modules::export::{purge::export_purge_worker, repository::ExportRepository};
 use modules::health::health_routes;
+use modules::health::sampler::{start_db_health_sampler, DbHealth};
+use modules::import::import_routes;
+use modules::inbound::config::{inbound_email_from_env, InboundEmailConfig};
+use modules::inbound::inbound_routes;
+use modules::invitation::invitation_routes;
+use modules::item::item_routes;
+use modules::list::list_routes;
+use modules::meta::{
+    meta_routes,
+    service::{build_info, instance_meta},
+};
+use modules::moderation::moderation_routes;
+use modules::moderation::moderator::{moderator_from_env, Moderator};
+use modules::outbox::{
+    broker::BroadcastSink, relay::relay_worker, repository::OutboxRepository, sink::sinks_from_env,
+};
+use modules::preference::preference_routes;
+use modules::presence::hub::PresenceHub;
+use modules::presence::presence_routes;
+use modules::reminder::{
+    dispatcher::reminder_worker, notifier::notifiers_from_env, reminder_routes,
+    repository::ReminderRepository,
+};
+use modules::report::{report_routes, repository::ReportRepository, review::review_worker};
+use modules::search::{
+    index::{search_index_from_config, SearchIndex},
+    search_routes,
+};
+use modules::security_webhook::{
+    dispatcher::{security_webhook_worker, webhook_client_from_env},
+    repository::SecurityWebhookRepository,
+    security_webhook_routes,
+};
+use modules::session::alerts::{geoip_from_env, LoginAlerts};
+use modules::session::session_routes;
+use modules::sync::{hub::SyncHub, sync_routes};
+use modules::tag::tag_routes;
+use modules::todo::cache::ViewCache;
+use modules::todo::dedup::CreateDedup;
+use modules::todo::repository::TodoRepository;
+use modules::todo::scheduler::activation_worker;
+use modules::todo::todo_routes;
+use modules::todo::trash::purge_worker;
+use modules::two_factor::two_factor_routes;
+use modules::user::hash_audit::password_audit_worker;
+use modules::user::repository::UserRepository;
+use modules::user::signup::{signup_mode_from_env, SignupMode};
 use modules::user::user_routes;
+use modules::workspace::workspace_routes;
+use otel::{request_span, RecordResponse};
+use settings::{init_tracing, reload_on_sighup, RuntimeSettings};
+use storage::{blob_storage_from_env, BlobStorage};
 use swagger::doc_config::ApiDoc;
+use swagger::postman::{postman_collection, postman_environment};
+use tasks::BackgroundTasks;
+use telemetry::{install_recorder, metrics_routes, track_auth_rejections};
+use utils::rate_limit::{rate_limit_auth, rate_limiter_from_config, RateLimiter};
+use utils::request_id::assign_request_id;
+use utils::static_json::{static_json_route, StaticJson};
+use workers::{start_workers, WorkerRegistry};
 
 /// Application state containing shared resources
 #[derive(Clone)]
@@ -33,65 +124,378 @@ pub struct AppState {
     pub encoding_key: EncodingKey,
     /// JWT decoding key
     pub decoding_key: DecodingKey,
-    /// Session duration in minutes
-    pub session_duration_minutes: i64,
+    /// Settings read at startup
+    pub config: Arc<Config>,
+    /// Work spawned by the handlers, awaited at shutdown
+    pub tasks: BackgroundTasks,
+    /// Last check of the database, read by the ping endpoints
+    pub db_health: DbHealth,
+    /// Per user cache of the todo smart views
+    pub view_cache: ViewCache,
+    /// Deduplication of double-submitted todo creations
+    pub create_dedup: CreateDedup,
+    /// Settings reloaded on `SIGHUP`
+    pub settings: watch::Receiver<RuntimeSettings>,
+    /// Background workers, surfaced by the readiness endpoint
+    pub workers: WorkerRegistry,
+    /// Users viewing each list, shared by the live sockets
+    pub presence: PresenceHub,
+    /// Sync sockets of each user, woken by the todo events
+    pub sync_hub: SyncHub,
+    /// Storage backend of the todo attachments
+    pub attachment_storage: AttachmentStorage,
+    /// Blob storage of the account exports built in the background
+    pub blob_storage: Arc<dyn BlobStorage>,
+    /// Outgoing emails, retried on failure
+    pub mailer: Arc<dyn Mailer>,
+    /// Identity providers of the OAuth logins
+    pub oauth_clients: OAuthClients,
+    /// Moderation API flagging the user content, when configured
+    pub moderator: Option<Arc<dyn Moderator>>,
+    /// Limits of the authentication requests, unless disabled
+    pub rate_limiter: Option<RateLimiter>,
+    /// Emails of the logins from new devices
+    pub login_alerts: LoginAlerts,
+    /// Domain and webhook secret of the list email addresses, when configured
+    pub inbound_email: Option<Arc<InboundEmailConfig>>,
+    /// Whether anyone, only invited users or nobody can sign up
+    pub signup_mode: SignupMode,
+    /// Index the todo searches run on
+    pub search_index: Arc<dyn SearchIndex>,
 }
 
 /// Main application entry point
 #[tokio::main]
+#[allow(clippy::too_many_lines)]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Load environment variables from .env file
     dotenv().ok();
 
-    // Initialize tracing subscriber for logging
-    tracing_subscriber::fmt::init();
+    // Initialize tracing subscriber for logging, its filter can be reloaded
+    let (log_handle, trace_export) = init_tracing()?;
 
-    // Get database URL from environment
-    let database_url = std::env::var("DATABASE_URL").unwrap_or_else(|_| {
-        tracing::warn!("DATABASE_URL not set, using default PostgreSQL connection");
-        "postgresql://localhost/rust_todo_app".to_string()
-    });
+    // Install the Prometheus recorder before any metric is emitted
+    let metrics_handle = install_recorder().map_err(|e| {
+        tracing::error!("Failed to install metrics recorder: {}", e);
+        e
+    })?;
+
+    // Read and validate the configuration
+    let config = Arc::new(Config::load().map_err(|e| {
+        tracing::error!("{}", e);
+        e
+    })?);
 
     // Create database connection pool
-    let pool = PgPoolOptions::new()
-        .max_connections(5)
-        .connect(&database_url)
+    let pool = connect_pool(&config.database).await.map_err(|e| {
+        tracing::error!("Failed to create database connection pool: {}", e);
+        e
+    })?;
+    start_pool_monitor(pool.clone());
+
+    let addr = config.server.bind_address();
+    let encoding_key = EncodingKey::from_secret(config.jwt.secret.as_bytes());
+    let decoding_key = DecodingKey::from_secret(config.jwt.secret.as_bytes());
+    let blob_storage = blob_storage_from_env().map_err(|e| {
+        tracing::error!("{}", e);
+        e
+    })?;
+    let attachment_storage = AttachmentStorage::from_env(blob_storage.clone());
+    let attachment_max_bytes = attachment_storage.max_bytes();
+    let mailer = mailer_from_config(&config.smtp).map_err(|e| {
+        tracing::error!("{}", e);
+        e
+    })?;
+    let oauth_clients = OAuthClients::from_env(&config.server.public_url).map_err(|e| {
+        tracing::error!("{}", e);
+        e
+    })?;
+    let moderator = moderator_from_env().map_err(|e| {
+        tracing::error!("{}", e);
+        e
+    })?;
+    let reminder_notifiers = notifiers_from_env(mailer.clone()).map_err(|e| {
+        tracing::error!("{}", e);
+        e
+    })?;
+    let security_webhook_client = webhook_client_from_env().map_err(|e| {
+        tracing::error!("{}", e);
+        e
+    })?;
+    let rate_limiter = rate_limiter_from_config(&config.rate_limit)
         .await
         .map_err(|e| {
-            tracing::error!("Failed to create database connection pool: {}", e);
+            tracing::error!("{}", e);
             e
         })?;
+    let geoip = geoip_from_env().map_err(|e| {
+        tracing::error!("{}", e);
+        e
+    })?;
+    let inbound_email = inbound_email_from_env().map_err(|e| {
+        tracing::error!("{}", e);
+        e
+    })?;
+    let signup_mode = signup_mode_from_env().map_err(|e| {
+        tracing::error!("{}", e);
+        e
+    })?;
+    let event_broadcast = BroadcastSink::default();
+    let mut outbox_sinks = sinks_from_env(&event_broadcast).await.map_err(|e| {
+        tracing::error!("{}", e);
+        e
+    })?;
+    let (search_index, search_sink) = search_index_from_config(&config.search, &pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{}", e);
+            e
+        })?;
+    // External search indexes follow the todos through the outbox events
+    outbox_sinks.extend(search_sink);
+    tracing::info!("Searching todos with {}", search_index.name());
+    let runtime_settings = RuntimeSettings::from_env().map_err(|e| {
+        tracing::error!("{}", e);
+        e
+    })?;
 
-    // Get server address and port from environment
-    let address = std::env::var("ADDRESS").unwrap_or_else(|_| "127.0.0.1".to_string());
-    let port = std::env::var("PORT").unwrap_or_else(|_| "8000".to_string());
-    let addr = format!("{address}:{port}");
-
-    // Get JWT secret from environment
-    let jwt_secret = std::env::var("JWT_SECRET").unwrap_or_else(|_| {
-        tracing::warn!("JWT_SECRET not set, using default secret");
-        "my_secret_key".to_string()
+    // Reload the runtime settings on SIGHUP
+    let (settings_sender, settings) = watch::channel(runtime_settings);
+    #[cfg(unix)]
+    tokio::spawn(async move {
+        if let Err(e) = reload_on_sighup(log_handle, settings_sender).await {
+            tracing::error!("Failed to listen for SIGHUP: {}", e);
+        }
     });
-    let encoding_key = EncodingKey::from_secret(jwt_secret.as_bytes());
-    let decoding_key = DecodingKey::from_secret(jwt_secret.as_bytes());
-    let session_duration_minutes = std::env::var("SESSION_DURATION_MINUTES")
-        .ok()
-        .and_then(|v| v.parse::<i64>().ok())
-        .unwrap_or(60); // default to 60 minutes
+
+    // Shared with the todo activation, which refreshes the views of the activated todos
+    let view_cache = ViewCache::new(std::time::Duration::from_secs(
+        config.todo.view_cache_ttl_seconds,
+    ));
+
+    // Start the background workers once the database is ready
+    let worker_registry = WorkerRegistry::default();
+    tokio::spawn(start_workers(
+        pool.clone(),
+        worker_registry.clone(),
+        vec![
+            (
+                "trash_purge",
+                purge_worker(
+                    pool.clone(),
+                    TodoRepository::new(pool.clone()),
+                    ChangesRepository::new(pool.clone()),
+                    time::Duration::days(config.todo.trash_retention_days),
+                    time::Duration::days(config.todo.tombstone_retention_days),
+                ),
+            ),
+            (
+                "demo_purge",
+                demo_purge_worker(pool.clone(), DemoRepository::new(pool.clone())),
+            ),
+            (
+                "export_purge",
+                export_purge_worker(
+                    pool.clone(),
+                    ExportRepository::new(pool.clone()),
+                    blob_storage.clone(),
+                ),
+            ),
+            (
+                "todo_activation",
+                activation_worker(
+                    pool.clone(),
+                    TodoRepository::new(pool.clone()),
+                    view_cache.clone(),
+                ),
+            ),
+            (
+                "outbox_relay",
+                relay_worker(
+                    pool.clone(),
+                    OutboxRepository::new(pool.clone()),
+                    outbox_sinks,
+                ),
+            ),
+            (
+                "reminder_dispatch",
+                reminder_worker(
+                    pool.clone(),
+                    ReminderRepository::new(pool.clone()),
+                    reminder_notifiers,
+                    std::time::Duration::from_secs(config.todo.reminder_poll_seconds),
+                ),
+            ),
+            (
+                "stale_review",
+                review_worker(
+                    pool.clone(),
+                    ReportRepository::new(pool.clone()),
+                    mailer.clone(),
+                ),
+            ),
+            (
+                "security_webhook_dispatch",
+                security_webhook_worker(
+                    pool.clone(),
+                    SecurityWebhookRepository::new(pool.clone()),
+                    security_webhook_client,
+                ),
+            ),
+            (
+                "password_hash_audit",
+                password_audit_worker(pool.clone(), UserRepository::new(pool.clone())),
+            ),
+        ],
+    ));
+
+    // Push the in-process domain events to the live list sockets
+    let presence = PresenceHub::default();
+    tokio::spawn(presence.clone().forward_events(event_broadcast.subscribe()));
+    let sync_hub = SyncHub::default();
+    tokio::spawn(sync_hub.clone().forward_events(event_broadcast.subscribe()));
+
+    // Check the database in the background for the ping endpoints
+    let db_health = start_db_health_sampler(
+        pool.clone(),
+        std::time::Duration::from_secs(config.health.sample_seconds),
+    );
 
     // Create application state
+    let tasks = BackgroundTasks::default();
     let app_state = AppState {
         db_pool: pool,
         encoding_key,
         decoding_key,
-        session_duration_minutes,
+        view_cache,
+        create_dedup: CreateDedup::new(std::time::Duration::from_secs(
+            config.todo.dedup_window_seconds,
+        )),
+        settings,
+        workers: worker_registry,
+        presence,
+        sync_hub,
+        attachment_storage,
+        blob_storage,
+        login_alerts: LoginAlerts::new(mailer.clone(), geoip, tasks.clone()),
+        mailer,
+        oauth_clients,
+        moderator,
+        rate_limiter,
+        inbound_email,
+        signup_mode,
+        search_index,
+        config: config.clone(),
+        tasks: tasks.clone(),
+        db_health,
     };
 
+    // Serialize the OpenAPI document once, it's served with an ETag afterwards
+    let api_doc = ApiDoc::openapi();
+    let openapi = StaticJson::new(&api_doc).map_err(|e| {
+        tracing::error!("Failed to serialize OpenAPI document: {}", e);
+        e
+    })?;
+
+    // Postman collection and environment derived from the OpenAPI document
+    let postman = postman_collection(&api_doc)
+        .and_then(|collection| StaticJson::new(&collection))
+        .map_err(|e| {
+            tracing::error!("Failed to build Postman collection: {}", e);
+            e
+        })?;
+    let postman_env = StaticJson::new(&postman_environment(
+        &api_doc.info.title,
+        &config.server.public_url,
+    ))?;
+
+    // Description of the deployment served to the frontends
+    let meta = StaticJson::new(&instance_meta(
+        &app_state,
+        &config.server.instance_name,
+        &api_doc.info.version,
+    ))?;
+    let version = StaticJson::new(&build_info())?;
+
     // Build the application router
     let app = Router::new()
-        .merge(SwaggerUi::new("/swagger-ui").url("/api-doc/openapi.json", ApiDoc::openapi()))
+        .merge(SwaggerUi::new("/swagger-ui").config(utoipa_swagger_ui::Config::from(
+            "/api-doc/openapi.json",
+        )))
+        .route("/api-doc/openapi.json", static_json_route(openapi))
+        .route("/api-doc/postman.json", static_json_route(postman))
+        .route(
+            "/api-doc/postman_environment.json",
+            static_json_route(postman_env),
+        )
         .merge(health_routes())
         .merge(user_routes())
+        .merge(session_routes())
+        .merge(two_factor_routes())
+        .merge(oauth_routes())
+        .merge(preference_routes())
+        .merge(badge_routes())
+        .merge(demo_routes())
+        .merge(search_routes())
+        .merge(list_routes())
+        .merge(todo_routes())
+        .merge(tag_routes())
+        .merge(item_routes())
+        .merge(reminder_routes())
+        .merge(assignment_routes())
+        .merge(report_routes())
+        .merge(invitation_routes())
+        .merge(presence_routes())
+        .merge(sync_routes())
+        .merge(changes_routes())
+        .merge(workspace_routes())
+        .merge(meta_routes(meta, version))
+        .merge(admin_routes())
+        .merge(moderation_routes())
+        .merge(audit_routes())
+        .merge(security_webhook_routes())
+        .merge(api_key_routes())
+        .merge(metrics_routes(metrics_handle))
+        // Limit the request bodies, the routes merged below raise the limit to the size
+        // of the attachments they receive
+        .layer(DefaultBodyLimit::disable())
+        .layer(RequestBodyLimitLayer::new(config.server.body_limit_bytes))
+        .merge(attachment_routes(attachment_max_bytes))
+        .merge(export_routes())
+        .merge(import_routes())
+        .merge(inbound_routes(attachment_max_bytes))
+        // Reject writes of users with an unverified email
+        .layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            require_verified_email,
+        ))
+        // Throttle the login, signup and password attempts
+        .layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            rate_limit_auth,
+        ))
+        // Count 401 and 403 responses per route
+        .layer(middleware::from_fn(track_auth_rejections))
+        // Answer with a 408 past the deadline
+        .layer(TimeoutLayer::with_status_code(
+            StatusCode::REQUEST_TIMEOUT,
+            std::time::Duration::from_secs(config.server.request_timeout_seconds),
+        ))
+        // Span of each request, exported with the database queries it ran
+        .layer(
+            TraceLayer::new_for_http()
+                .make_span_with(request_span)
+                .on_request(())
+                .on_response(RecordResponse::new(config.server.access_log)),
+        )
+        // Compress the responses for the clients accepting it
+        .layer(
+            CompressionLayer::new()
+                .gzip(config.server.compression)
+                .br(config.server.compression),
+        )
+        // Id of each request, in its logs and its error responses
+        .layer(middleware::from_fn(assign_request_id))
         .with_state(app_state);
 
     // Create TCP listener
@@ -102,15 +506,61 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     tracing::info!("Server listening on {}", listener.local_addr()?);
 
-    // Start the server
-    axum::serve(listener, app).await.map_err(|e| {
+    // Start the server, until SIGTERM or Ctrl-C
+    // Peer addresses are needed to resolve client IPs
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await
+    .map_err(|e| {
         tracing::error!("Server error: {}", e);
         e
     })?;
 
+    // Let the work spawned by the handlers finish
+    let timeout = std::time::Duration::from_secs(config.server.shutdown_timeout_seconds);
+    let unfinished = tasks.shutdown(timeout).await;
+    if unfinished > 0 {
+        tracing::warn!("{} background tasks dropped at shutdown", unfinished);
+    }
+    trace_export.shutdown();
+
     Ok(())
 }
 
+// Resolves on Ctrl-C or, on Unix, on SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => {},
+        () = terminate => {},
+    }
+    tracing::info!("Shutting down, waiting for the open requests");
+}
+
 #[cfg(test)]
 #[allow(
     clippy::assertions_on_constants,
@@ -0,0 +1,192 @@
+//! # `Admin` Interfaces
+//! This module defines the data structures from Admin module
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::modules::email::mailer::EmailSettings;
+use crate::utils::dates::rfc3339_option;
+
+// Filters of the user accounts listed to the administrators
+#[derive(Deserialize, IntoParams, Clone, Debug, Default)]
+#[into_params(parameter_in = Query)]
+pub struct AdminUserFilter {
+    /// Only return enabled (`true`) or disabled (`false`) accounts
+    pub active: Option<bool>,
+    /// Only return accounts with a verified (`true`) or unverified (`false`) email
+    pub verified: Option<bool>,
+}
+
+// User row as seen by the administrators
+#[derive(sqlx::FromRow, Clone, Debug)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct AdminUserRow {
+    pub id: i32,
+    pub username: String,
+    pub email: String,
+    pub active: bool,
+    pub email_verified: bool,
+    pub is_admin: bool,
+    pub is_guest: bool,
+    pub created_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct AdminUserResponse {
+    pub id: i64,
+    pub username: String,
+    pub email: String,
+    // Disabled accounts can't log in
+    pub active: bool,
+    pub email_verified: bool,
+    pub is_admin: bool,
+    pub is_guest: bool,
+    #[serde(with = "rfc3339_option")]
+    pub created_at: Option<OffsetDateTime>,
+}
+
+impl From<AdminUserRow> for AdminUserResponse {
+    fn from(row: AdminUserRow) -> Self {
+        Self {
+            id: i64::from(row.id),
+            username: row.username,
+            email: row.email,
+            active: row.active,
+            email_verified: row.email_verified,
+            is_admin: row.is_admin,
+            is_guest: row.is_guest,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct AdminUserPageResponse {
+    pub users: Vec<AdminUserResponse>,
+    // Cursor of the next page, `null` on the last page
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct AdminMessageResponse {
+    pub message: String,
+}
+
+// Email configuration of the instance, checked for the administrators
+#[derive(Serialize, ToSchema, Clone, Debug)]
+pub struct EmailDiagnosticsResponse {
+    pub settings: EmailSettings,
+    // Whether the SMTP server accepted a connection
+    pub connected: bool,
+    pub connection_error: Option<String>,
+    // Problems of the configuration and checks left to the administrator
+    pub hints: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, Default)]
+pub struct TestEmailRequest {
+    // Recipient of the test email, the email of the administrator when omitted
+    pub to: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct TestEmailResponse {
+    pub to: String,
+    // Whether the SMTP server accepted the email, it can still be lost or flagged later
+    pub sent: bool,
+    // Answer of the SMTP server when the email was not accepted
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, Default)]
+pub struct CreateInviteCodeRequest {
+    // Signups the code allows, 1 when omitted
+    pub max_uses: Option<i64>,
+    // Days before the code expires, it never expires when omitted
+    pub expires_in_days: Option<i64>,
+}
+
+// Invite code row as stored in database, without its hash
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct InviteCodeRow {
+    pub id: i32,
+    pub max_uses: i32,
+    pub uses: i32,
+    pub expires_at: Option<OffsetDateTime>,
+    pub created_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct InviteCodeResponse {
+    pub id: i64,
+    pub max_uses: i64,
+    // Signups done with the code
+    pub uses: i64,
+    #[serde(with = "rfc3339_option")]
+    pub expires_at: Option<OffsetDateTime>,
+    #[serde(with = "rfc3339_option")]
+    pub created_at: Option<OffsetDateTime>,
+}
+
+impl From<InviteCodeRow> for InviteCodeResponse {
+    fn from(row: InviteCodeRow) -> Self {
+        Self {
+            id: i64::from(row.id),
+            max_uses: i64::from(row.max_uses),
+            uses: i64::from(row.uses),
+            expires_at: row.expires_at,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct InviteCodeCreatedResponse {
+    #[serde(flatten)]
+    pub invite_code: InviteCodeResponse,
+    // Code to give to the invited users, only its hash is stored so it is shown once
+    pub code: String,
+}
+
+// Latest password hash scan with the accounts waiting for a reset
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct PasswordHashStatsRow {
+    pub scanned_at: Option<OffsetDateTime>,
+    pub total: Option<i32>,
+    pub current: Option<i32>,
+    pub outdated: Option<i32>,
+    pub malformed: Option<i32>,
+    pub reset_required: i64,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct PasswordHashStatsResponse {
+    // Date of the latest scan, `null` before the first one
+    #[serde(with = "rfc3339_option")]
+    pub scanned_at: Option<OffsetDateTime>,
+    // Stored hashes at the latest scan
+    pub total: i64,
+    // Argon2id hashes with the current parameters
+    pub current: i64,
+    // Hashes still verifying but replaced on the next login of their user
+    pub outdated: i64,
+    // Hashes no password verifies against
+    pub malformed: i64,
+    // Accounts that must reset their password before logging in, right now
+    pub reset_required: i64,
+}
+
+impl From<PasswordHashStatsRow> for PasswordHashStatsResponse {
+    fn from(row: PasswordHashStatsRow) -> Self {
+        Self {
+            scanned_at: row.scanned_at,
+            total: row.total.map_or(0, i64::from),
+            current: row.current.map_or(0, i64::from),
+            outdated: row.outdated.map_or(0, i64::from),
+            malformed: row.malformed.map_or(0, i64::from),
+            reset_required: row.reset_required,
+        }
+    }
+}
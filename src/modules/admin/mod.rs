@@ -0,0 +1,9 @@
+//! # `Admin` Mod
+//! Admin imports for the administrators managing the user accounts
+
+pub mod interfaces;
+pub mod repository;
+pub mod routes;
+pub mod service;
+
+pub use routes::admin_routes;
@@ -0,0 +1,177 @@
+//! # `Admin` Repository
+//! This module defines the admin repository for the admin endpoints.
+
+use sqlx::{Error, Pool, Postgres};
+use time::OffsetDateTime;
+
+use crate::modules::{
+    admin::interfaces::{AdminUserFilter, AdminUserRow, InviteCodeRow, PasswordHashStatsRow},
+    common::to_db_id,
+};
+use crate::telemetry::observe_query;
+
+// Columns of an `AdminUserRow` from the users table
+const ADMIN_USER_COLUMNS: &str = "id, username, email, active,
+    email_verified_at IS NOT NULL AS email_verified, is_admin, is_guest, created_at";
+
+// Columns of an `InviteCodeRow` from the invite_codes table
+const INVITE_CODE_COLUMNS: &str = "id, max_uses, uses, expires_at, created_at";
+
+pub struct AdminRepository {
+    pool: Pool<Postgres>,
+}
+
+impl AdminRepository {
+    // Constructor
+    pub const fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // List a page of the users matching the filter, the newest first
+    pub async fn list_users(
+        &self,
+        filter: &AdminUserFilter,
+        before_id: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<AdminUserRow>, Error> {
+        observe_query("admin.list_users", async move {
+            let query = format!(
+                "SELECT {ADMIN_USER_COLUMNS} FROM users
+                 WHERE ($1::BOOLEAN IS NULL OR active = $1)
+                   AND ($2::BOOLEAN IS NULL OR (email_verified_at IS NOT NULL) = $2)
+                   AND ($3::INTEGER IS NULL OR id < $3)
+                 ORDER BY id DESC
+                 LIMIT $4"
+            );
+
+            sqlx::query_as::<_, AdminUserRow>(&query)
+                .bind(filter.active)
+                .bind(filter.verified)
+                .bind(before_id.map(to_db_id).transpose()?)
+                .bind(limit)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Email of a user, `None` when the user doesn't exist
+    pub async fn fetch_email(&self, id: i64) -> Result<Option<String>, Error> {
+        observe_query("admin.fetch_email", async move {
+            sqlx::query_scalar::<_, String>("SELECT email FROM users WHERE id = $1")
+                .bind(to_db_id(id)?)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Enable or disable an account, the sessions of a disabled account are revoked.
+    // Returns `None` when the user doesn't exist.
+    pub async fn set_active(&self, id: i64, active: bool) -> Result<Option<AdminUserRow>, Error> {
+        observe_query("admin.set_active", async move {
+            let id = to_db_id(id)?;
+            let mut tx = self.pool.begin().await?;
+
+            let query = format!(
+                "UPDATE users SET active = $2 WHERE id = $1
+                 RETURNING {ADMIN_USER_COLUMNS}"
+            );
+            let user = sqlx::query_as::<_, AdminUserRow>(&query)
+                .bind(id)
+                .bind(active)
+                .fetch_optional(&mut *tx)
+                .await?;
+            if user.is_some() && !active {
+                sqlx::query("DELETE FROM sessions WHERE user_id = $1")
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+
+            tx.commit().await?;
+            Ok(user)
+        })
+        .await
+    }
+
+    // Delete an account with its data, returns whether it existed
+    pub async fn delete_user(&self, id: i64) -> Result<bool, Error> {
+        observe_query("admin.delete_user", async move {
+            let result = sqlx::query("DELETE FROM users WHERE id = $1")
+                .bind(to_db_id(id)?)
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    // Counts of the latest password hash scan and the accounts waiting for a reset
+    pub async fn password_hash_stats(&self) -> Result<PasswordHashStatsRow, Error> {
+        observe_query("admin.password_hash_stats", async move {
+            sqlx::query_as::<_, PasswordHashStatsRow>(
+                "SELECT a.scanned_at, a.total, a.current, a.outdated, a.malformed,
+                        (SELECT COUNT(*) FROM users WHERE password_reset_required)
+                            AS reset_required
+                 FROM (SELECT 1) AS s
+                 LEFT JOIN LATERAL (
+                     SELECT * FROM password_hash_audits ORDER BY id DESC LIMIT 1
+                 ) AS a ON TRUE",
+            )
+            .fetch_one(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Store a new invite code by its hash
+    pub async fn create_invite_code(
+        &self,
+        code_hash: &str,
+        max_uses: i32,
+        expires_at: Option<OffsetDateTime>,
+        created_by: i64,
+    ) -> Result<InviteCodeRow, Error> {
+        observe_query("admin.create_invite_code", async move {
+            let query = format!(
+                "INSERT INTO invite_codes (code_hash, max_uses, expires_at, created_by)
+                 VALUES ($1, $2, $3, $4)
+                 RETURNING {INVITE_CODE_COLUMNS}"
+            );
+
+            sqlx::query_as::<_, InviteCodeRow>(&query)
+                .bind(code_hash)
+                .bind(max_uses)
+                .bind(expires_at)
+                .bind(to_db_id(created_by)?)
+                .fetch_one(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // List the invite codes, the newest first
+    pub async fn list_invite_codes(&self) -> Result<Vec<InviteCodeRow>, Error> {
+        observe_query("admin.list_invite_codes", async move {
+            let query = format!("SELECT {INVITE_CODE_COLUMNS} FROM invite_codes ORDER BY id DESC");
+
+            sqlx::query_as::<_, InviteCodeRow>(&query)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Delete an invite code, returns whether it existed
+    pub async fn delete_invite_code(&self, id: i64) -> Result<bool, Error> {
+        observe_query("admin.delete_invite_code", async move {
+            let result = sqlx::query("DELETE FROM invite_codes WHERE id = $1")
+                .bind(to_db_id(id)?)
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+}
@@ -0,0 +1,347 @@
+//! #`Admin` Routes
+//! This module defines the HTTP routes for the administrators managing the user accounts
+//! and the invite codes.
+
+use axum::extract::{Path, Query};
+use axum::routing::{delete, get, post};
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Router};
+
+use crate::auth::AdminClaims;
+use crate::modules::admin::interfaces::{
+    AdminMessageResponse, AdminUserFilter, AdminUserPageResponse, AdminUserResponse,
+    CreateInviteCodeRequest, EmailDiagnosticsResponse, InviteCodeCreatedResponse,
+    InviteCodeResponse, PasswordHashStatsResponse, TestEmailRequest, TestEmailResponse,
+};
+use crate::modules::admin::repository::AdminRepository;
+use crate::modules::admin::service::AdminService;
+use crate::modules::audit::repository::AuditRepository;
+use crate::modules::audit::service::AuditService;
+use crate::modules::common::{respond, ErrorResponse, Pagination};
+use crate::modules::session::interfaces::SessionDevice;
+use crate::utils::json::Json;
+use crate::AppState;
+
+// Creates and returns the admin routes
+pub fn admin_routes() -> Router<AppState> {
+    Router::new()
+        .route("/admin/users", get(list_users_route))
+        .route("/admin/users/{id}", delete(delete_user_route))
+        .route("/admin/users/{id}/disable", post(disable_user_route))
+        .route("/admin/users/{id}/enable", post(enable_user_route))
+        .route("/admin/email", get(email_diagnostics_route))
+        .route("/admin/email/test", post(test_email_route))
+        .route(
+            "/admin/invite-codes",
+            get(list_invite_codes_route).post(create_invite_code_route),
+        )
+        .route("/admin/invite-codes/{id}", delete(delete_invite_code_route))
+        .route(
+            "/admin/stats/password-hashes",
+            get(password_hash_stats_route),
+        )
+}
+
+fn admin_service(app_state: &AppState) -> AdminService {
+    AdminService::new(
+        AdminRepository::new(app_state.db_pool.clone()),
+        AuditService::new(AuditRepository::new(app_state.db_pool.clone())),
+        app_state.mailer.clone(),
+    )
+}
+
+// List Users Route
+#[utoipa::path(
+    get,
+    path = "/admin/users",
+    tag = "Admin",
+    params(AdminUserFilter, Pagination),
+    responses(
+        (status = 200, description = "User accounts, the newest first", body = AdminUserPageResponse),
+        (status = 400, description = "Invalid cursor", body = ErrorResponse),
+        (status = 403, description = "Not an administrator"),
+        (status = 500, description = "Failed to list users", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn list_users_route(
+    State(app_state): State<AppState>,
+    _admin: AdminClaims,
+    Query(filter): Query<AdminUserFilter>,
+    Query(pagination): Query<Pagination>,
+) -> impl IntoResponse {
+    let before_id = match pagination.decode_cursor::<i64>() {
+        Ok(before_id) => before_id,
+        Err(error) => return error.into_response(),
+    };
+
+    respond(
+        StatusCode::OK,
+        admin_service(&app_state)
+            .list_users(&filter, before_id, pagination.limit())
+            .await,
+    )
+}
+
+// Disable User Route
+#[utoipa::path(
+    post,
+    path = "/admin/users/{id}/disable",
+    tag = "Admin",
+    params(
+        ("id" = i64, Path, description = "User id")
+    ),
+    responses(
+        (status = 200, description = "Account disabled and its sessions revoked", body = AdminUserResponse),
+        (status = 403, description = "Not an administrator, or own account"),
+        (status = 404, description = "User not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn disable_user_route(
+    State(app_state): State<AppState>,
+    admin: AdminClaims,
+    device: SessionDevice,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        admin_service(&app_state)
+            .set_active(admin.user_id, id, false, &device)
+            .await,
+    )
+}
+
+// Enable User Route
+#[utoipa::path(
+    post,
+    path = "/admin/users/{id}/enable",
+    tag = "Admin",
+    params(
+        ("id" = i64, Path, description = "User id")
+    ),
+    responses(
+        (status = 200, description = "Account enabled, the user can log in again", body = AdminUserResponse),
+        (status = 403, description = "Not an administrator, or own account"),
+        (status = 404, description = "User not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn enable_user_route(
+    State(app_state): State<AppState>,
+    admin: AdminClaims,
+    device: SessionDevice,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        admin_service(&app_state)
+            .set_active(admin.user_id, id, true, &device)
+            .await,
+    )
+}
+
+// Delete User Route
+#[utoipa::path(
+    delete,
+    path = "/admin/users/{id}",
+    tag = "Admin",
+    params(
+        ("id" = i64, Path, description = "User id")
+    ),
+    responses(
+        (status = 200, description = "Account deleted with its data", body = AdminMessageResponse),
+        (status = 403, description = "Not an administrator, or own account"),
+        (status = 404, description = "User not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn delete_user_route(
+    State(app_state): State<AppState>,
+    admin: AdminClaims,
+    device: SessionDevice,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        admin_service(&app_state)
+            .delete_user(admin.user_id, id, &device)
+            .await,
+    )
+}
+
+// Email Diagnostics Route
+#[utoipa::path(
+    get,
+    path = "/admin/email",
+    tag = "Admin",
+    responses(
+        (status = 200, description = "Email settings of the instance without the secrets, the SMTP connection check and hints about the sender configuration (From domain, DKIM, SPF, DMARC)", body = EmailDiagnosticsResponse),
+        (status = 403, description = "Not an administrator")
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn email_diagnostics_route(
+    State(app_state): State<AppState>,
+    _admin: AdminClaims,
+) -> impl IntoResponse {
+    let diagnostics = admin_service(&app_state).email_diagnostics().await;
+    (StatusCode::OK, Json(diagnostics)).into_response()
+}
+
+// Test Email Route
+#[utoipa::path(
+    post,
+    path = "/admin/email/test",
+    tag = "Admin",
+    request_body = TestEmailRequest,
+    responses(
+        (status = 200, description = "Test email sent, or the error of the SMTP server when it was refused", body = TestEmailResponse),
+        (status = 400, description = "Invalid recipient", body = ErrorResponse),
+        (status = 403, description = "Not an administrator")
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn test_email_route(
+    State(app_state): State<AppState>,
+    admin: AdminClaims,
+    Json(request): Json<TestEmailRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        admin_service(&app_state)
+            .send_test_email(admin.user_id, request)
+            .await,
+    )
+}
+
+// Create Invite Code Route
+#[utoipa::path(
+    post,
+    path = "/admin/invite-codes",
+    tag = "Admin",
+    request_body = CreateInviteCodeRequest,
+    responses(
+        (status = 201, description = "Invite code created, the code is only returned in this response", body = InviteCodeCreatedResponse),
+        (status = 400, description = "Invalid uses or expiration", body = ErrorResponse),
+        (status = 403, description = "Not an administrator")
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn create_invite_code_route(
+    State(app_state): State<AppState>,
+    admin: AdminClaims,
+    device: SessionDevice,
+    Json(request): Json<CreateInviteCodeRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::CREATED,
+        admin_service(&app_state)
+            .create_invite_code(admin.user_id, request, &device)
+            .await,
+    )
+}
+
+// List Invite Codes Route
+#[utoipa::path(
+    get,
+    path = "/admin/invite-codes",
+    tag = "Admin",
+    responses(
+        (status = 200, description = "Invite codes with their uses, the newest first", body = Vec<InviteCodeResponse>),
+        (status = 403, description = "Not an administrator"),
+        (status = 500, description = "Failed to list invite codes", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn list_invite_codes_route(
+    State(app_state): State<AppState>,
+    _admin: AdminClaims,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        admin_service(&app_state).list_invite_codes().await,
+    )
+}
+
+// Delete Invite Code Route
+#[utoipa::path(
+    delete,
+    path = "/admin/invite-codes/{id}",
+    tag = "Admin",
+    params(
+        ("id" = i64, Path, description = "Invite code id")
+    ),
+    responses(
+        (status = 200, description = "Invite code deleted, it can't be used to sign up anymore", body = AdminMessageResponse),
+        (status = 403, description = "Not an administrator"),
+        (status = 404, description = "Invite code not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn delete_invite_code_route(
+    State(app_state): State<AppState>,
+    admin: AdminClaims,
+    device: SessionDevice,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        admin_service(&app_state)
+            .delete_invite_code(admin.user_id, id, &device)
+            .await,
+    )
+}
+
+// Password Hash Stats Route
+#[utoipa::path(
+    get,
+    path = "/admin/stats/password-hashes",
+    tag = "Admin",
+    responses(
+        (status = 200, description = "Stored password hashes by state at the latest daily scan, and the accounts that must reset their password", body = PasswordHashStatsResponse),
+        (status = 403, description = "Not an administrator"),
+        (status = 500, description = "Failed to fetch password hash stats", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn password_hash_stats_route(
+    State(app_state): State<AppState>,
+    _admin: AdminClaims,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        admin_service(&app_state).password_hash_stats().await,
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_admin_routes_creation() {
+        let _routes = admin_routes();
+        assert!(true);
+    }
+}
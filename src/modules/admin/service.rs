@@ -0,0 +1,394 @@
+//! # `Admin` Service
+//!
+//! This module contains the bussiness logic for the administrators managing the user
+//! accounts. Disabled accounts keep their data but can't log in anymore. The email
+//! configuration of the instance is checked from here too, and the invite codes required
+//! to sign up on invite-only instances are generated here.
+
+use std::sync::Arc;
+
+use email_address::EmailAddress;
+use time::{Duration, OffsetDateTime};
+
+use crate::modules::{
+    admin::{
+        interfaces::{
+            AdminMessageResponse, AdminUserFilter, AdminUserPageResponse, AdminUserResponse,
+            CreateInviteCodeRequest, EmailDiagnosticsResponse, InviteCodeCreatedResponse,
+            InviteCodeResponse, PasswordHashStatsResponse, TestEmailRequest, TestEmailResponse,
+        },
+        repository::AdminRepository,
+    },
+    audit::{
+        interfaces::{AuditAction, NewAuditEntry},
+        service::AuditService,
+    },
+    common::{encode_cursor, AppError},
+    email::{
+        diagnostics::email_hints,
+        mailer::{EmailMessage, Mailer},
+    },
+    session::interfaces::SessionDevice,
+};
+use crate::utils::token::{generate_random_token, hash_token};
+
+// Most signups a single invite code allows
+const MAX_INVITE_USES: i64 = 1000;
+
+// Longest validity of an invite code
+const MAX_INVITE_DAYS: i64 = 365;
+
+// Administrators can't disable nor delete their own account, which could leave the
+// instance without administrator
+fn ensure_other_user(admin_id: i64, id: i64) -> Result<(), AppError> {
+    if admin_id == id {
+        return Err(AppError::Forbidden(
+            "Administrators can't disable or delete their own account".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// Uses and validity in days of a new invite code, one use without expiration by default
+fn invite_code_limits(request: &CreateInviteCodeRequest) -> Result<(i32, Option<i64>), AppError> {
+    let max_uses = request.max_uses.unwrap_or(1);
+    if !(1..=MAX_INVITE_USES).contains(&max_uses) {
+        return Err(AppError::Validation(format!(
+            "max_uses must be between 1 and {MAX_INVITE_USES}"
+        )));
+    }
+    if let Some(days) = request.expires_in_days {
+        if !(1..=MAX_INVITE_DAYS).contains(&days) {
+            return Err(AppError::Validation(format!(
+                "expires_in_days must be between 1 and {MAX_INVITE_DAYS}"
+            )));
+        }
+    }
+    let max_uses = i32::try_from(max_uses).unwrap_or(1);
+    Ok((max_uses, request.expires_in_days))
+}
+
+// Test email sent by the administrators
+fn test_email(to: String) -> EmailMessage {
+    EmailMessage {
+        to,
+        subject: "Todo App test email".to_string(),
+        text_body: "This test email was sent from the administration of your Todo App \
+                    instance. If you read it, the emails of the instance are delivered.\n"
+            .to_string(),
+        html_body: None,
+    }
+}
+
+pub struct AdminService {
+    admin_repository: AdminRepository,
+    audit_service: AuditService,
+    mailer: Arc<dyn Mailer>,
+}
+
+impl AdminService {
+    pub const fn new(
+        admin_repository: AdminRepository,
+        audit_service: AuditService,
+        mailer: Arc<dyn Mailer>,
+    ) -> Self {
+        Self {
+            admin_repository,
+            audit_service,
+            mailer,
+        }
+    }
+
+    // Email configuration of the instance, with a connection to the SMTP server
+    pub async fn email_diagnostics(&self) -> EmailDiagnosticsResponse {
+        let settings = self.mailer.settings();
+        let connection_error = self.mailer.check().await.err();
+        EmailDiagnosticsResponse {
+            hints: email_hints(&settings),
+            settings,
+            connected: connection_error.is_none(),
+            connection_error,
+        }
+    }
+
+    // Send a test email, to the administrator unless another recipient is given. The
+    // email is sent right away so the answer of the SMTP server is returned.
+    pub async fn send_test_email(
+        &self,
+        admin_id: i64,
+        request: TestEmailRequest,
+    ) -> Result<TestEmailResponse, AppError> {
+        let to = match request.to.map(|to| to.trim().to_string()) {
+            Some(to) if !to.is_empty() => to,
+            _ => match self.admin_repository.fetch_email(admin_id).await {
+                Ok(Some(email)) => email,
+                Ok(None) => return Err(AppError::NotFound("User not found".to_string())),
+                Err(e) => {
+                    tracing::warn!("Error fetching email of user {}: {}", admin_id, e);
+                    return Err(AppError::Database(e));
+                }
+            },
+        };
+        if !EmailAddress::is_valid(&to) {
+            return Err(AppError::Validation("Email is not valid".to_string()));
+        }
+
+        let error = self.mailer.send(&test_email(to.clone())).await.err();
+        if let Some(ref e) = error {
+            tracing::warn!("Test email to {} not sent: {}", to, e);
+        } else {
+            tracing::info!("Test email sent to {} by administrator {}", to, admin_id);
+        }
+        Ok(TestEmailResponse {
+            to,
+            sent: error.is_none(),
+            error,
+        })
+    }
+
+    // List a page of the user accounts, `before_id` comes from the page cursor
+    pub async fn list_users(
+        &self,
+        filter: &AdminUserFilter,
+        before_id: Option<i64>,
+        limit: i64,
+    ) -> Result<AdminUserPageResponse, AppError> {
+        // One extra row tells whether there is a next page
+        match self
+            .admin_repository
+            .list_users(filter, before_id, limit + 1)
+            .await
+        {
+            Ok(mut users) => {
+                let page_size = usize::try_from(limit).unwrap_or(1);
+                let next_cursor = if users.len() > page_size {
+                    users.truncate(page_size);
+                    users
+                        .last()
+                        .and_then(|user| encode_cursor(&i64::from(user.id)))
+                } else {
+                    None
+                };
+
+                Ok(AdminUserPageResponse {
+                    users: users.into_iter().map(AdminUserResponse::from).collect(),
+                    next_cursor,
+                })
+            }
+            Err(e) => {
+                tracing::warn!("Error listing users: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Enable or disable the account of another user
+    pub async fn set_active(
+        &self,
+        admin_id: i64,
+        id: i64,
+        active: bool,
+        device: &SessionDevice,
+    ) -> Result<AdminUserResponse, AppError> {
+        ensure_other_user(admin_id, id)?;
+
+        match self.admin_repository.set_active(id, active).await {
+            Ok(Some(user)) => {
+                tracing::info!(
+                    "User {} {} by administrator {}",
+                    id,
+                    if active { "enabled" } else { "disabled" },
+                    admin_id
+                );
+                self.audit_service
+                    .record(NewAuditEntry {
+                        user_id: Some(id),
+                        actor_id: Some(admin_id),
+                        action: if active {
+                            AuditAction::UserEnabled
+                        } else {
+                            AuditAction::UserDisabled
+                        },
+                        device,
+                        details: None,
+                    })
+                    .await;
+                Ok(AdminUserResponse::from(user))
+            }
+            Ok(None) => Err(AppError::NotFound("User not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error updating user {}: {}", id, e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Delete the account of another user
+    pub async fn delete_user(
+        &self,
+        admin_id: i64,
+        id: i64,
+        device: &SessionDevice,
+    ) -> Result<AdminMessageResponse, AppError> {
+        ensure_other_user(admin_id, id)?;
+
+        match self.admin_repository.delete_user(id).await {
+            Ok(true) => {
+                tracing::info!("User {} deleted by administrator {}", id, admin_id);
+                self.audit_service
+                    .record(NewAuditEntry {
+                        user_id: Some(id),
+                        actor_id: Some(admin_id),
+                        action: AuditAction::UserDeleted,
+                        device,
+                        details: None,
+                    })
+                    .await;
+                Ok(AdminMessageResponse {
+                    message: "User deleted".to_string(),
+                })
+            }
+            Ok(false) => Err(AppError::NotFound("User not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error deleting user {}: {}", id, e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Counts of the stored password hashes by state, from the latest daily scan
+    pub async fn password_hash_stats(&self) -> Result<PasswordHashStatsResponse, AppError> {
+        match self.admin_repository.password_hash_stats().await {
+            Ok(row) => Ok(PasswordHashStatsResponse::from(row)),
+            Err(e) => {
+                tracing::warn!("Error fetching password hash stats: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Generate an invite code, only its hash is stored so the code is returned once
+    pub async fn create_invite_code(
+        &self,
+        admin_id: i64,
+        request: CreateInviteCodeRequest,
+        device: &SessionDevice,
+    ) -> Result<InviteCodeCreatedResponse, AppError> {
+        let (max_uses, expires_in_days) = invite_code_limits(&request)?;
+        let expires_at =
+            expires_in_days.map(|days| OffsetDateTime::now_utc() + Duration::days(days));
+        let code = generate_random_token();
+
+        match self
+            .admin_repository
+            .create_invite_code(&hash_token(&code), max_uses, expires_at, admin_id)
+            .await
+        {
+            Ok(invite_code) => {
+                tracing::info!(
+                    "Invite code {} created by administrator {}",
+                    invite_code.id,
+                    admin_id
+                );
+                self.audit_service
+                    .record(NewAuditEntry {
+                        user_id: None,
+                        actor_id: Some(admin_id),
+                        action: AuditAction::InviteCodeCreated,
+                        device,
+                        details: Some(format!("invite code {}", invite_code.id)),
+                    })
+                    .await;
+                Ok(InviteCodeCreatedResponse {
+                    invite_code: InviteCodeResponse::from(invite_code),
+                    code,
+                })
+            }
+            Err(e) => {
+                tracing::warn!("Error creating invite code: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // List the invite codes with their uses, the codes themselves can't be shown again
+    pub async fn list_invite_codes(&self) -> Result<Vec<InviteCodeResponse>, AppError> {
+        match self.admin_repository.list_invite_codes().await {
+            Ok(invite_codes) => Ok(invite_codes
+                .into_iter()
+                .map(InviteCodeResponse::from)
+                .collect()),
+            Err(e) => {
+                tracing::warn!("Error listing invite codes: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Delete an invite code, it can't be used to sign up anymore
+    pub async fn delete_invite_code(
+        &self,
+        admin_id: i64,
+        id: i64,
+        device: &SessionDevice,
+    ) -> Result<AdminMessageResponse, AppError> {
+        match self.admin_repository.delete_invite_code(id).await {
+            Ok(true) => {
+                tracing::info!("Invite code {} deleted by administrator {}", id, admin_id);
+                self.audit_service
+                    .record(NewAuditEntry {
+                        user_id: None,
+                        actor_id: Some(admin_id),
+                        action: AuditAction::InviteCodeDeleted,
+                        device,
+                        details: Some(format!("invite code {id}")),
+                    })
+                    .await;
+                Ok(AdminMessageResponse {
+                    message: "Invite code deleted".to_string(),
+                })
+            }
+            Ok(false) => Err(AppError::NotFound("Invite code not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error deleting invite code {}: {}", id, e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_test_email() {
+        let message = test_email("ana@example.com".to_string());
+        assert_eq!(message.to, "ana@example.com");
+        assert!(!message.subject.is_empty());
+        assert!(message.text_body.contains("delivered"));
+    }
+
+    #[test]
+    fn test_ensure_other_user() {
+        assert!(ensure_other_user(1, 2).is_ok());
+        assert!(ensure_other_user(1, 1).is_err());
+    }
+
+    #[test]
+    fn test_invite_code_limits() {
+        let limits = |max_uses, expires_in_days| {
+            invite_code_limits(&CreateInviteCodeRequest {
+                max_uses,
+                expires_in_days,
+            })
+            .ok()
+        };
+        assert_eq!(limits(None, None), Some((1, None)));
+        assert_eq!(limits(Some(5), Some(7)), Some((5, Some(7))));
+        assert_eq!(limits(Some(0), None), None);
+        assert_eq!(limits(Some(MAX_INVITE_USES + 1), None), None);
+        assert_eq!(limits(None, Some(0)), None);
+        assert_eq!(limits(None, Some(MAX_INVITE_DAYS + 1)), None);
+    }
+}
@@ -0,0 +1,110 @@
+//! # `Api Key` Interfaces
+//! This module defines the data structures from Api Key module
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+use crate::utils::dates::rfc3339_option;
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct CreateApiKeyRequest {
+    // Name telling the integrations apart, e.g. `billing-sync`
+    pub name: Option<String>,
+}
+
+// Api key row as stored in database
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct ApiKeyRow {
+    pub id: i32,
+    pub user_id: i32,
+    pub key_id: String,
+    pub name: String,
+    pub last_used_at: Option<OffsetDateTime>,
+    pub revoked_at: Option<OffsetDateTime>,
+    pub created_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ApiKeyResponse {
+    pub id: i64,
+    // Sent in the `X-Api-Key` header of the signed requests
+    pub key_id: String,
+    pub name: String,
+    #[serde(with = "rfc3339_option")]
+    pub last_used_at: Option<OffsetDateTime>,
+    // Set once revoked, the key is then rejected
+    #[serde(with = "rfc3339_option")]
+    pub revoked_at: Option<OffsetDateTime>,
+    #[serde(with = "rfc3339_option")]
+    pub created_at: Option<OffsetDateTime>,
+}
+
+impl From<ApiKeyRow> for ApiKeyResponse {
+    fn from(row: ApiKeyRow) -> Self {
+        Self {
+            id: i64::from(row.id),
+            key_id: row.key_id,
+            name: row.name,
+            last_used_at: row.last_used_at,
+            revoked_at: row.revoked_at,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ApiKeyCreatedResponse {
+    pub id: i64,
+    pub key_id: String,
+    // Key of the request signatures, only returned once
+    pub secret: String,
+    pub name: String,
+    #[serde(with = "rfc3339_option")]
+    pub created_at: Option<OffsetDateTime>,
+}
+
+impl ApiKeyCreatedResponse {
+    // Created key with its secret, which is not stored
+    pub fn new(row: ApiKeyRow, secret: String) -> Self {
+        Self {
+            id: i64::from(row.id),
+            key_id: row.key_id,
+            secret,
+            name: row.name,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ApiKeyMessageResponse {
+    pub message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_key_response_hides_secret() {
+        let row = ApiKeyRow {
+            id: 4,
+            user_id: 2,
+            key_id: "key_0123456789abcdef".to_string(),
+            name: "billing-sync".to_string(),
+            last_used_at: None,
+            revoked_at: None,
+            created_at: None,
+        };
+        let created = serde_json::to_string(&ApiKeyCreatedResponse::new(
+            row.clone(),
+            "s3cr3t".to_string(),
+        ))
+        .unwrap_or_default();
+        assert!(created.contains("s3cr3t"));
+        let response = serde_json::to_string(&ApiKeyResponse::from(row)).unwrap_or_default();
+        assert!(response.contains("key_0123456789abcdef"));
+        assert!(!response.contains("s3cr3t"));
+    }
+}
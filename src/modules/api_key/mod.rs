@@ -0,0 +1,11 @@
+//! # `Api Key` Mod
+//! Api key imports for the server-side integrations signing their requests with HMAC
+//! instead of holding a JWT
+
+pub mod interfaces;
+pub mod repository;
+pub mod routes;
+pub mod service;
+pub mod signature;
+
+pub use routes::api_key_routes;
@@ -0,0 +1,141 @@
+//! # `Api Key` Repository
+//! This module defines the api key repository for the key endpoints and the signed
+//! requests.
+
+use sqlx::{Error, Pool, Postgres};
+use time::OffsetDateTime;
+
+use crate::modules::{api_key::interfaces::ApiKeyRow, common::to_db_id};
+use crate::telemetry::observe_query;
+
+const API_KEY_COLUMNS: &str = "id, user_id, key_id, name, last_used_at, revoked_at, created_at";
+
+pub struct ApiKeyRepository {
+    pool: Pool<Postgres>,
+}
+
+impl ApiKeyRepository {
+    // Constructor
+    pub const fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Create a key, `None` when the user already has `max_keys` unrevoked keys
+    pub async fn create_key(
+        &self,
+        user_id: i64,
+        key_id: &str,
+        name: &str,
+        max_keys: i64,
+    ) -> Result<Option<ApiKeyRow>, Error> {
+        observe_query("api_key.create_key", async move {
+            let user_id = to_db_id(user_id)?;
+            sqlx::query_as::<_, ApiKeyRow>(&format!(
+                "INSERT INTO api_keys (user_id, key_id, name)
+                 SELECT $1, $2, $3
+                 WHERE (SELECT COUNT(*) FROM api_keys
+                        WHERE user_id = $1 AND revoked_at IS NULL) < $4
+                 RETURNING {API_KEY_COLUMNS}"
+            ))
+            .bind(user_id)
+            .bind(key_id)
+            .bind(name)
+            .bind(max_keys)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Keys of a user, the oldest first
+    pub async fn list_keys(&self, user_id: i64) -> Result<Vec<ApiKeyRow>, Error> {
+        observe_query("api_key.list_keys", async move {
+            sqlx::query_as::<_, ApiKeyRow>(&format!(
+                "SELECT {API_KEY_COLUMNS} FROM api_keys WHERE user_id = $1 ORDER BY id"
+            ))
+            .bind(to_db_id(user_id)?)
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Revoke a key of the user, returns whether it was active
+    pub async fn revoke_key(&self, user_id: i64, id: i64) -> Result<bool, Error> {
+        observe_query("api_key.revoke_key", async move {
+            let result = sqlx::query(
+                "UPDATE api_keys SET revoked_at = NOW()
+                 WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL",
+            )
+            .bind(to_db_id(id)?)
+            .bind(to_db_id(user_id)?)
+            .execute(&self.pool)
+            .await?;
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    // Unrevoked key with the key id, the keys of disabled accounts are not found
+    pub async fn find_active_key(&self, key_id: &str) -> Result<Option<ApiKeyRow>, Error> {
+        observe_query("api_key.find_active_key", async move {
+            sqlx::query_as::<_, ApiKeyRow>(&format!(
+                "SELECT {API_KEY_COLUMNS} FROM api_keys k
+                 WHERE key_id = $1 AND revoked_at IS NULL
+                    AND EXISTS(SELECT 1 FROM users u WHERE u.id = k.user_id AND u.active)"
+            ))
+            .bind(key_id)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Record the signature of an accepted request, dropping the expired ones. Returns
+    // false when it was already used, the request is then a replay.
+    pub async fn record_signature(
+        &self,
+        api_key_id: i32,
+        signature: &str,
+        expires_at: OffsetDateTime,
+    ) -> Result<bool, Error> {
+        observe_query("api_key.record_signature", async move {
+            let mut tx = self.pool.begin().await?;
+            sqlx::query("DELETE FROM api_key_signatures WHERE expires_at <= NOW()")
+                .execute(&mut *tx)
+                .await?;
+            let inserted = sqlx::query(
+                "INSERT INTO api_key_signatures (signature, api_key_id, expires_at)
+                 VALUES ($1, $2, $3)
+                 ON CONFLICT (signature) DO NOTHING",
+            )
+            .bind(signature)
+            .bind(api_key_id)
+            .bind(expires_at)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected()
+                > 0;
+            if inserted {
+                sqlx::query("UPDATE api_keys SET last_used_at = NOW() WHERE id = $1")
+                    .bind(api_key_id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            tx.commit().await?;
+            Ok(inserted)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_key_columns_exclude_secret() {
+        assert!(!API_KEY_COLUMNS.contains("secret"));
+        assert!(API_KEY_COLUMNS.contains("revoked_at"));
+    }
+}
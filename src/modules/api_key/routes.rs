@@ -0,0 +1,170 @@
+//! #`Api Key` Routes
+//! This module defines the HTTP routes of the api keys, and the endpoints of the
+//! integrations signing their requests with them.
+
+use axum::extract::Path;
+use axum::routing::{delete, get, post};
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Router};
+
+use crate::auth::Claims;
+use crate::modules::api_key::interfaces::{
+    ApiKeyCreatedResponse, ApiKeyMessageResponse, ApiKeyResponse, CreateApiKeyRequest,
+};
+use crate::modules::api_key::repository::ApiKeyRepository;
+use crate::modules::api_key::service::ApiKeyService;
+use crate::modules::api_key::signature::SignedRequest;
+use crate::modules::common::{respond, ErrorResponse};
+use crate::modules::todo::interfaces::{CreateTodoRequest, TodoResponse};
+use crate::modules::todo::repository::TodoRepository;
+use crate::modules::todo::service::TodoService;
+use crate::utils::json::Json;
+use crate::AppState;
+
+// Creates and returns the api key routes
+pub fn api_key_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/api-keys",
+            get(list_api_keys_route).post(create_api_key_route),
+        )
+        .route("/api-keys/{id}", delete(revoke_api_key_route))
+        .route("/integrations/todos", post(create_signed_todo_route))
+}
+
+fn api_key_service(app_state: &AppState) -> ApiKeyService {
+    ApiKeyService::new(
+        ApiKeyRepository::new(app_state.db_pool.clone()),
+        app_state.config.jwt.secret.clone(),
+    )
+}
+
+// Create Api Key Route
+#[utoipa::path(
+    post,
+    path = "/api-keys",
+    tag = "Api Keys",
+    request_body = CreateApiKeyRequest,
+    responses(
+        (status = 201, description = "Key created, with the secret of its signatures", body = ApiKeyCreatedResponse),
+        (status = 400, description = "Invalid name", body = ErrorResponse),
+        (status = 409, description = "Too many active keys", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn create_api_key_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Json(create_request): Json<CreateApiKeyRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::CREATED,
+        api_key_service(&app_state)
+            .create_key(claims.user_id, create_request)
+            .await,
+    )
+}
+
+// List Api Keys Route
+#[utoipa::path(
+    get,
+    path = "/api-keys",
+    tag = "Api Keys",
+    responses(
+        (status = 200, description = "Keys of the user, the oldest first", body = [ApiKeyResponse]),
+        (status = 500, description = "Failed to list api keys", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn list_api_keys_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        api_key_service(&app_state).list_keys(claims.user_id).await,
+    )
+}
+
+// Revoke Api Key Route
+#[utoipa::path(
+    delete,
+    path = "/api-keys/{id}",
+    tag = "Api Keys",
+    params(
+        ("id" = i64, Path, description = "Api key id")
+    ),
+    responses(
+        (status = 200, description = "Key revoked", body = ApiKeyMessageResponse),
+        (status = 404, description = "Api key not found or already revoked", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn revoke_api_key_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        api_key_service(&app_state)
+            .revoke_key(claims.user_id, id)
+            .await,
+    )
+}
+
+// Create Signed Todo Route
+#[utoipa::path(
+    post,
+    path = "/integrations/todos",
+    tag = "Api Keys",
+    request_body = CreateTodoRequest,
+    responses(
+        (status = 201, description = "Todo created in the personal workspace of the key owner", body = TodoResponse),
+        (status = 200, description = "Duplicate submission, the original todo is returned", body = TodoResponse),
+        (status = 400, description = "Invalid todo data", body = ErrorResponse),
+        (status = 401, description = "Unknown or revoked key, disabled account, invalid, stale or replayed signature", body = ErrorResponse)
+    ),
+    security(
+        ("api_key_signature" = [])
+    )
+)]
+pub async fn create_signed_todo_route(
+    State(app_state): State<AppState>,
+    signed: SignedRequest,
+) -> impl IntoResponse {
+    let create_request = match signed.json::<CreateTodoRequest>() {
+        Ok(create_request) => create_request,
+        Err(error) => return error.into_response(),
+    };
+
+    match TodoService::new(
+        TodoRepository::new(app_state.db_pool.clone()),
+        app_state.view_cache.clone(),
+        app_state.create_dedup.clone(),
+    )
+    .create_todo(signed.user_id, signed.workspace_id, create_request)
+    .await
+    {
+        Ok((todo, true)) => (StatusCode::CREATED, Json(todo)).into_response(),
+        Ok((todo, false)) => (StatusCode::OK, Json(todo)).into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_key_routes_creation() {
+        let _routes = api_key_routes();
+        assert!(true);
+    }
+}
@@ -0,0 +1,146 @@
+//! # `Api Key` Service
+//!
+//! This module contains the bussiness logic for the api keys. A key lets a server-side
+//! integration act as its user by signing its requests with the secret returned at
+//! creation, see `signature`. Secrets are derived from the server secret, they are never
+//! stored.
+
+use crate::modules::{
+    api_key::{
+        interfaces::{
+            ApiKeyCreatedResponse, ApiKeyMessageResponse, ApiKeyResponse, CreateApiKeyRequest,
+        },
+        repository::ApiKeyRepository,
+        signature::key_secret,
+    },
+    common::AppError,
+};
+use crate::utils::token::generate_random_token;
+
+// Unrevoked keys a user can hold
+const MAX_KEYS_PER_USER: i64 = 10;
+
+// Longest key name
+const MAX_NAME_CHARS: usize = 64;
+
+// Prefix of the key ids, telling them apart from the other tokens
+const KEY_ID_PREFIX: &str = "key_";
+
+// Random hex characters of a key id
+const KEY_ID_CHARS: usize = 24;
+
+// Validate the name of a key
+fn key_name(name: Option<&str>) -> Result<String, AppError> {
+    let Some(name) = name.map(str::trim).filter(|name| !name.is_empty()) else {
+        return Err(AppError::Validation(
+            "Missing required fields: name".to_string(),
+        ));
+    };
+    if name.chars().count() > MAX_NAME_CHARS {
+        return Err(AppError::Validation(format!(
+            "Name must be at most {MAX_NAME_CHARS} characters"
+        )));
+    }
+    Ok(name.to_string())
+}
+
+// Public id of a new key
+fn generate_key_id() -> String {
+    let mut random = generate_random_token();
+    random.truncate(KEY_ID_CHARS);
+    format!("{KEY_ID_PREFIX}{random}")
+}
+
+pub struct ApiKeyService {
+    api_key_repository: ApiKeyRepository,
+    // Secret the key secrets are derived from
+    server_secret: String,
+}
+
+impl ApiKeyService {
+    pub const fn new(api_key_repository: ApiKeyRepository, server_secret: String) -> Self {
+        Self {
+            api_key_repository,
+            server_secret,
+        }
+    }
+
+    // Create a key, its secret is only returned here
+    pub async fn create_key(
+        &self,
+        user_id: i64,
+        create_request: CreateApiKeyRequest,
+    ) -> Result<ApiKeyCreatedResponse, AppError> {
+        let name = key_name(create_request.name.as_deref())?;
+        let key_id = generate_key_id();
+        let secret = key_secret(&self.server_secret, &key_id)?;
+
+        match self
+            .api_key_repository
+            .create_key(user_id, &key_id, &name, MAX_KEYS_PER_USER)
+            .await
+        {
+            Ok(Some(key)) => Ok(ApiKeyCreatedResponse::new(key, secret)),
+            Ok(None) => Err(AppError::Conflict(format!(
+                "At most {MAX_KEYS_PER_USER} api keys can be active"
+            ))),
+            Err(e) => {
+                tracing::warn!("Error creating api key: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Keys of the user, revoked ones included
+    pub async fn list_keys(&self, user_id: i64) -> Result<Vec<ApiKeyResponse>, AppError> {
+        match self.api_key_repository.list_keys(user_id).await {
+            Ok(keys) => Ok(keys.into_iter().map(ApiKeyResponse::from).collect()),
+            Err(e) => {
+                tracing::warn!("Error listing api keys: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Revoke a key, its signed requests are rejected from now on
+    pub async fn revoke_key(
+        &self,
+        user_id: i64,
+        id: i64,
+    ) -> Result<ApiKeyMessageResponse, AppError> {
+        match self.api_key_repository.revoke_key(user_id, id).await {
+            Ok(true) => Ok(ApiKeyMessageResponse {
+                message: "Api key revoked".to_string(),
+            }),
+            Ok(false) => Err(AppError::NotFound("Api key not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error revoking api key: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_name() {
+        assert_eq!(
+            key_name(Some("  billing-sync ")).ok().as_deref(),
+            Some("billing-sync")
+        );
+        assert!(key_name(None).is_err());
+        assert!(key_name(Some("   ")).is_err());
+        assert!(key_name(Some(&"a".repeat(MAX_NAME_CHARS + 1))).is_err());
+    }
+
+    #[test]
+    fn test_generate_key_id() {
+        let key_id = generate_key_id();
+        assert!(key_id.starts_with(KEY_ID_PREFIX));
+        assert_eq!(key_id.len(), KEY_ID_PREFIX.len() + KEY_ID_CHARS);
+        assert_ne!(key_id, generate_key_id());
+    }
+}
@@ -0,0 +1,301 @@
+//! # Signed Requests
+//!
+//! Server-side integrations that can't safely hold a JWT sign each request with the
+//! secret of an api key instead. A signed request carries three headers:
+//!
+//! - `X-Api-Key`: the key id
+//! - `X-Api-Timestamp`: the unix time of the request, in seconds
+//! - `X-Api-Signature`: the hex HMAC-SHA256, keyed with the secret, of
+//!   `<timestamp>\n<METHOD>\n<path and query>\n<hex SHA-256 of the body>`
+//!
+//! Requests whose timestamp is more than 5 minutes away from the server clock are
+//! rejected, and a signature is only accepted once, so captured requests can't be
+//! replayed.
+//!
+//! The secret of a key is derived from the server secret and the key id, so a leaked
+//! database holds no secret. Changing the JWT secret changes the secrets of every key.
+
+use axum::{
+    body::{to_bytes, Bytes},
+    extract::{FromRequest, Request},
+    http::{uri::PathAndQuery, Method},
+};
+use hmac::{Hmac, Mac};
+use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
+use time::OffsetDateTime;
+
+use crate::{
+    modules::{
+        api_key::repository::ApiKeyRepository, common::AppError,
+        workspace::repository::WorkspaceRepository,
+    },
+    utils::json,
+    AppState,
+};
+
+pub const API_KEY_HEADER: &str = "X-Api-Key";
+pub const TIMESTAMP_HEADER: &str = "X-Api-Timestamp";
+pub const SIGNATURE_HEADER: &str = "X-Api-Signature";
+
+// Largest gap between the timestamp of a request and the server clock
+const MAX_CLOCK_SKEW_SECONDS: i64 = 300;
+
+// Largest body of a signed request
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+// Text signed by a request
+fn string_to_sign(timestamp: i64, method: &Method, path: &str, body: &[u8]) -> String {
+    format!(
+        "{timestamp}\n{}\n{path}\n{}",
+        method.as_str(),
+        hex::encode(Sha256::digest(body))
+    )
+}
+
+// Hex secret of the key `key_id`, the HMAC-SHA256 of the key id keyed with the server
+// secret
+pub fn key_secret(server_secret: &str, key_id: &str) -> Result<String, AppError> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(server_secret.as_bytes())
+        .map_err(|e| AppError::Internal(format!("Api key secret derivation error: {e}")))?;
+    mac.update(b"api-key\n");
+    mac.update(key_id.as_bytes());
+    Ok(hex::encode(mac.finalize().into_bytes()))
+}
+
+// Check a hex signature in constant time
+fn verify_signature(secret: &str, message: &str, signature: &str) -> bool {
+    let Ok(signature) = hex::decode(signature) else {
+        return false;
+    };
+    Hmac::<Sha256>::new_from_slice(secret.as_bytes()).is_ok_and(|mut mac| {
+        mac.update(message.as_bytes());
+        mac.verify_slice(&signature).is_ok()
+    })
+}
+
+// Whether a request timestamp is close enough to the server clock
+const fn is_fresh(timestamp: i64, now: i64) -> bool {
+    now.abs_diff(timestamp) <= MAX_CLOCK_SKEW_SECONDS.unsigned_abs()
+}
+
+// Request signed with an api key. The integration acts as the owner of the key, in its
+// personal workspace.
+#[derive(Debug)]
+pub struct SignedRequest {
+    pub user_id: i64,
+    pub workspace_id: i64,
+    pub body: Bytes,
+}
+
+impl SignedRequest {
+    // Body of the request as JSON, invalid bodies are validation errors
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, AppError> {
+        json::from_slice(&self.body)
+    }
+}
+
+// Rejection of a request without a valid signature, the cause is only logged
+fn unauthorized() -> AppError {
+    AppError::Unauthorized("Invalid request signature".to_string())
+}
+
+impl FromRequest<AppState> for SignedRequest {
+    type Rejection = AppError;
+
+    async fn from_request(request: Request, state: &AppState) -> Result<Self, Self::Rejection> {
+        let (parts, body) = request.into_parts();
+        let header = |name: &str| {
+            parts
+                .headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::trim)
+        };
+        let (Some(key_id), Some(timestamp), Some(signature)) = (
+            header(API_KEY_HEADER),
+            header(TIMESTAMP_HEADER),
+            header(SIGNATURE_HEADER),
+        ) else {
+            tracing::warn!("Signed request without its key, timestamp or signature");
+            return Err(AppError::Unauthorized(format!(
+                "Missing {API_KEY_HEADER}, {TIMESTAMP_HEADER} or {SIGNATURE_HEADER} header"
+            )));
+        };
+        let Ok(timestamp) = timestamp.parse::<i64>() else {
+            tracing::warn!("Signed request with an invalid timestamp");
+            return Err(unauthorized());
+        };
+        if !is_fresh(timestamp, OffsetDateTime::now_utc().unix_timestamp()) {
+            tracing::warn!("Signed request of key {} with a stale timestamp", key_id);
+            return Err(unauthorized());
+        }
+        let body = to_bytes(body, MAX_BODY_BYTES).await.map_err(|_| {
+            AppError::Validation(format!(
+                "Request body must be at most {MAX_BODY_BYTES} bytes"
+            ))
+        })?;
+
+        let repository = ApiKeyRepository::new(state.db_pool.clone());
+        let key = match repository.find_active_key(key_id).await {
+            Ok(Some(key)) => key,
+            Ok(None) => {
+                tracing::warn!(
+                    "Signed request of unknown, revoked or disabled key {}",
+                    key_id
+                );
+                return Err(unauthorized());
+            }
+            Err(e) => {
+                tracing::warn!("Error fetching api key: {}", e);
+                return Err(AppError::Database(e));
+            }
+        };
+        let path = parts
+            .uri
+            .path_and_query()
+            .map_or_else(|| parts.uri.path(), PathAndQuery::as_str);
+        let message = string_to_sign(timestamp, &parts.method, path, &body);
+        let secret = key_secret(&state.config.jwt.secret, &key.key_id)?;
+        if !verify_signature(&secret, &message, signature) {
+            tracing::warn!("Signed request of key {} with an invalid signature", key_id);
+            return Err(unauthorized());
+        }
+
+        // Kept until the timestamp is too old to pass the freshness check anyway
+        let expires_at =
+            OffsetDateTime::from_unix_timestamp(timestamp.saturating_add(MAX_CLOCK_SKEW_SECONDS))
+                .map_err(|_| unauthorized())?;
+        match repository
+            .record_signature(key.id, &signature.to_ascii_lowercase(), expires_at)
+            .await
+        {
+            Ok(true) => {}
+            Ok(false) => {
+                tracing::warn!("Replayed signed request of key {}", key_id);
+                return Err(unauthorized());
+            }
+            Err(e) => {
+                tracing::warn!("Error recording request signature: {}", e);
+                return Err(AppError::Database(e));
+            }
+        }
+
+        let user_id = i64::from(key.user_id);
+        match WorkspaceRepository::new(state.db_pool.clone())
+            .active_workspace(user_id, None)
+            .await
+        {
+            Ok(Some(workspace)) => Ok(Self {
+                user_id,
+                workspace_id: i64::from(workspace.id),
+                body,
+            }),
+            Ok(None) => {
+                tracing::warn!(
+                    "User {} of key {} has no personal workspace",
+                    user_id,
+                    key_id
+                );
+                Err(AppError::Forbidden(
+                    "Api key owner has no personal workspace".to_string(),
+                ))
+            }
+            Err(e) => {
+                tracing::warn!("Error resolving personal workspace: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    // Hex signature of a request, as computed by the integrations
+    fn sign_request(
+        secret: &str,
+        timestamp: i64,
+        method: &Method,
+        path: &str,
+        body: &[u8],
+    ) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(string_to_sign(timestamp, method, path, body).as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    #[test]
+    fn test_signature_round_trip() {
+        let body = br#"{"title":"Renew domain"}"#;
+        let signature = sign_request(
+            "secret",
+            1_700_000_000,
+            &Method::POST,
+            "/integrations/todos",
+            body,
+        );
+        let message = string_to_sign(1_700_000_000, &Method::POST, "/integrations/todos", body);
+
+        assert_eq!(signature.len(), 64);
+        assert!(verify_signature("secret", &message, &signature));
+        assert!(verify_signature(
+            "secret",
+            &message,
+            &signature.to_ascii_uppercase()
+        ));
+        assert!(!verify_signature("other", &message, &signature));
+        assert!(!verify_signature("secret", &message, "not hex"));
+
+        let tampered = string_to_sign(1_700_000_000, &Method::POST, "/integrations/todos", b"{}");
+        assert!(!verify_signature("secret", &tampered, &signature));
+    }
+
+    #[test]
+    fn test_key_secret() {
+        let secret = key_secret("server secret", "key_0123456789abcdef").unwrap();
+        assert_eq!(secret.len(), 64);
+        assert_eq!(
+            key_secret("server secret", "key_0123456789abcdef").unwrap(),
+            secret
+        );
+        assert_ne!(
+            key_secret("server secret", "key_fedcba9876543210").unwrap(),
+            secret
+        );
+        assert_ne!(
+            key_secret("other secret", "key_0123456789abcdef").unwrap(),
+            secret
+        );
+    }
+
+    #[test]
+    fn test_rejections_are_error_responses() {
+        let response = unauthorized().to_error_response();
+        assert_eq!(
+            unauthorized().status(),
+            axum::http::StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(response.code.as_deref(), Some("unauthorized"));
+    }
+
+    #[test]
+    fn test_string_to_sign() {
+        assert_eq!(
+            string_to_sign(42, &Method::GET, "/todos?page=2", b""),
+            "42\nGET\n/todos?page=2\ne3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn test_is_fresh() {
+        let now = 1_700_000_000;
+        assert!(is_fresh(now, now));
+        assert!(is_fresh(now - MAX_CLOCK_SKEW_SECONDS, now));
+        assert!(is_fresh(now + MAX_CLOCK_SKEW_SECONDS, now));
+        assert!(!is_fresh(now - MAX_CLOCK_SKEW_SECONDS - 1, now));
+        assert!(!is_fresh(i64::MIN, now));
+    }
+}
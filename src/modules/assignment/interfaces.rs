@@ -0,0 +1,93 @@
+//! # `Assignment` Interfaces
+//! This module defines the data structures from Assignments module
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::utils::dates::rfc3339_option;
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct AssignTodoRequest {
+    // Username of the workspace member the todo is delegated to
+    pub assignee: Option<String>,
+}
+
+// Query parameters of the review queue
+#[derive(Deserialize, IntoParams, Clone, Debug, Default)]
+#[into_params(parameter_in = Query)]
+pub struct AssignmentQuery {
+    /// One of `pending`, `accepted` or `declined`, defaults to `pending`
+    pub status: Option<String>,
+}
+
+// Answer of the assignee to an assignment
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssignmentDecision {
+    Accept,
+    Decline,
+}
+
+impl AssignmentDecision {
+    // Status of the assignment once answered
+    pub const fn status(self) -> &'static str {
+        match self {
+            Self::Accept => "accepted",
+            Self::Decline => "declined",
+        }
+    }
+}
+
+// Assignment row as stored in database, with the todo title and the usernames
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct AssignmentRow {
+    pub id: i32,
+    pub todo_id: i32,
+    pub title: String,
+    pub assigner: String,
+    pub assignee: String,
+    pub status: String,
+    pub created_at: Option<OffsetDateTime>,
+    pub responded_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct AssignmentResponse {
+    pub id: i64,
+    pub todo_id: i64,
+    pub title: String,
+    // Username of the todo owner who delegated it
+    pub assigner: String,
+    // Username of the member the todo is delegated to
+    pub assignee: String,
+    // One of `pending`, `accepted` or `declined`
+    pub status: String,
+    #[serde(with = "rfc3339_option")]
+    pub created_at: Option<OffsetDateTime>,
+    #[serde(with = "rfc3339_option")]
+    pub responded_at: Option<OffsetDateTime>,
+}
+
+impl From<AssignmentRow> for AssignmentResponse {
+    fn from(row: AssignmentRow) -> Self {
+        Self {
+            id: i64::from(row.id),
+            todo_id: i64::from(row.todo_id),
+            title: row.title,
+            assigner: row.assigner,
+            assignee: row.assignee,
+            status: row.status,
+            created_at: row.created_at,
+            responded_at: row.responded_at,
+        }
+    }
+}
+
+// Assignment with the email of the user to notify about it, the assignee of a new
+// assignment or the assigner of an answered one
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct NotifiedAssignmentRow {
+    #[sqlx(flatten)]
+    pub assignment: AssignmentRow,
+    pub notify_email: String,
+}
@@ -0,0 +1,9 @@
+//! # `Assignment` Mod
+//! Assignment imports for the todos delegated to other members of a workspace
+
+pub mod interfaces;
+pub mod repository;
+pub mod routes;
+pub mod service;
+
+pub use routes::assignment_routes;
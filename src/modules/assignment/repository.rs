@@ -0,0 +1,164 @@
+//! # `Assignment` Repository
+//! This module defines the assignment repository for the assignment endpoints.
+
+use sqlx::{Error, Pool, Postgres};
+
+use crate::modules::{
+    assignment::interfaces::{AssignmentDecision, AssignmentRow, NotifiedAssignmentRow},
+    common::to_db_id,
+};
+use crate::telemetry::observe_query;
+
+// Columns of an `AssignmentRow` from the assignment `a`, its todo `t` and the assigner
+// `ur` and assignee `ue` users
+const ASSIGNMENT_COLUMNS: &str = "a.id, a.todo_id, t.title, ur.username AS assigner,
+    ue.username AS assignee, a.status, a.created_at, a.responded_at";
+
+pub struct AssignmentRepository {
+    pool: Pool<Postgres>,
+}
+
+impl AssignmentRepository {
+    // Constructor
+    pub const fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Check if a todo of the workspace is owned by the user
+    pub async fn is_todo_owner(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+    ) -> Result<bool, Error> {
+        observe_query("assignment.is_todo_owner", async move {
+            sqlx::query_scalar::<_, bool>(
+                "SELECT EXISTS(
+                    SELECT 1 FROM todos
+                    WHERE id = $1 AND user_id = $2 AND workspace_id = $3 AND deleted_at IS NULL)",
+            )
+            .bind(to_db_id(todo_id)?)
+            .bind(to_db_id(user_id)?)
+            .bind(to_db_id(workspace_id)?)
+            .fetch_one(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Id of the workspace member with the username, `None` when not a member
+    pub async fn find_member(
+        &self,
+        workspace_id: i64,
+        username: &str,
+    ) -> Result<Option<i32>, Error> {
+        observe_query("assignment.find_member", async move {
+            sqlx::query_scalar::<_, i32>(
+                "SELECT u.id FROM users u
+                 JOIN workspace_members wm ON wm.user_id = u.id
+                 WHERE wm.workspace_id = $1 AND u.username = $2",
+            )
+            .bind(to_db_id(workspace_id)?)
+            .bind(username)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Delegate a todo of the user to the assignee, notifying the assignee. Returns `None`
+    // when the todo already has a pending or accepted assignment.
+    pub async fn create_assignment(
+        &self,
+        user_id: i64,
+        todo_id: i64,
+        assignee_id: i32,
+    ) -> Result<Option<NotifiedAssignmentRow>, Error> {
+        observe_query("assignment.create_assignment", async move {
+            let query = format!(
+                "WITH a AS (
+                    INSERT INTO todo_assignments (todo_id, assigner_id, assignee_id)
+                    VALUES ($1, $2, $3)
+                    ON CONFLICT (todo_id) WHERE status IN ('pending', 'accepted') DO NOTHING
+                    RETURNING *
+                 )
+                 SELECT {ASSIGNMENT_COLUMNS}, ue.email AS notify_email
+                 FROM a
+                 JOIN todos t ON t.id = a.todo_id
+                 JOIN users ur ON ur.id = a.assigner_id
+                 JOIN users ue ON ue.id = a.assignee_id"
+            );
+            sqlx::query_as::<_, NotifiedAssignmentRow>(&query)
+                .bind(to_db_id(todo_id)?)
+                .bind(to_db_id(user_id)?)
+                .bind(assignee_id)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Assignments of the workspace todos delegated to the user with the status, the
+    // oldest first
+    pub async fn list_assigned(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        status: &str,
+    ) -> Result<Vec<AssignmentRow>, Error> {
+        observe_query("assignment.list_assigned", async move {
+            let query = format!(
+                "SELECT {ASSIGNMENT_COLUMNS}
+                 FROM todo_assignments a
+                 JOIN todos t ON t.id = a.todo_id
+                 JOIN users ur ON ur.id = a.assigner_id
+                 JOIN users ue ON ue.id = a.assignee_id
+                 WHERE a.assignee_id = $1 AND t.workspace_id = $2 AND a.status = $3
+                   AND t.deleted_at IS NULL
+                 ORDER BY a.created_at, a.id"
+            );
+            sqlx::query_as::<_, AssignmentRow>(&query)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .bind(status)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Answer the pending assignment of a workspace todo delegated to the user, notifying
+    // the assigner. Returns `None` without such an assignment.
+    pub async fn respond(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+        decision: AssignmentDecision,
+    ) -> Result<Option<NotifiedAssignmentRow>, Error> {
+        observe_query("assignment.respond", async move {
+            let query = format!(
+                "WITH a AS (
+                    UPDATE todo_assignments a SET status = $4, responded_at = NOW()
+                    FROM todos t
+                    WHERE a.todo_id = $1 AND a.assignee_id = $2 AND a.status = 'pending'
+                      AND t.id = a.todo_id AND t.workspace_id = $3 AND t.deleted_at IS NULL
+                    RETURNING a.*
+                 )
+                 SELECT {ASSIGNMENT_COLUMNS}, ur.email AS notify_email
+                 FROM a
+                 JOIN todos t ON t.id = a.todo_id
+                 JOIN users ur ON ur.id = a.assigner_id
+                 JOIN users ue ON ue.id = a.assignee_id"
+            );
+            sqlx::query_as::<_, NotifiedAssignmentRow>(&query)
+                .bind(to_db_id(todo_id)?)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .bind(decision.status())
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+}
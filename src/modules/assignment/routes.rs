@@ -0,0 +1,178 @@
+//! #`Assignment` Routes
+//! This module defines the HTTP routes for the todos delegated to workspace members.
+
+use axum::extract::{Path, Query};
+use axum::routing::{get, post};
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Router};
+
+use crate::modules::assignment::interfaces::{
+    AssignTodoRequest, AssignmentDecision, AssignmentQuery, AssignmentResponse,
+};
+use crate::modules::assignment::repository::AssignmentRepository;
+use crate::modules::assignment::service::AssignmentService;
+use crate::modules::common::{respond, ErrorResponse};
+use crate::modules::email::mailer::BackgroundMailer;
+use crate::modules::workspace::context::WorkspaceContext;
+use crate::utils::json::Json;
+use crate::AppState;
+
+// Creates and returns the assignment routes
+pub fn assignment_routes() -> Router<AppState> {
+    Router::new()
+        .route("/todos/assigned", get(list_assigned_route))
+        .route("/todos/{id}/assign", post(assign_todo_route))
+        .route("/todos/{id}/accept", post(accept_assignment_route))
+        .route("/todos/{id}/decline", post(decline_assignment_route))
+}
+
+fn assignment_service(app_state: &AppState) -> AssignmentService {
+    AssignmentService::new(
+        AssignmentRepository::new(app_state.db_pool.clone()),
+        BackgroundMailer::new(app_state.mailer.clone(), app_state.tasks.clone()),
+    )
+}
+
+// Assign Todo Route
+#[utoipa::path(
+    post,
+    path = "/todos/{id}/assign",
+    tag = "Assignments",
+    params(
+        ("id" = i64, Path, description = "Todo id")
+    ),
+    request_body = AssignTodoRequest,
+    responses(
+        (status = 201, description = "Todo waiting for the assignee to accept it", body = AssignmentResponse),
+        (status = 400, description = "Invalid assignee", body = ErrorResponse),
+        (status = 404, description = "Todo not found", body = ErrorResponse),
+        (status = 409, description = "Todo already assigned", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn assign_todo_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path(id): Path<i64>,
+    Json(assign_request): Json<AssignTodoRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::CREATED,
+        assignment_service(&app_state)
+            .assign(
+                workspace.user_id,
+                workspace.workspace_id,
+                id,
+                assign_request,
+            )
+            .await,
+    )
+}
+
+// List Assigned Route
+#[utoipa::path(
+    get,
+    path = "/todos/assigned",
+    tag = "Assignments",
+    params(AssignmentQuery),
+    responses(
+        (status = 200, description = "Todos delegated to the user, the oldest first", body = [AssignmentResponse]),
+        (status = 400, description = "Invalid status", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn list_assigned_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Query(query): Query<AssignmentQuery>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        assignment_service(&app_state)
+            .list_assigned(workspace.user_id, workspace.workspace_id, &query)
+            .await,
+    )
+}
+
+// Accept Assignment Route
+#[utoipa::path(
+    post,
+    path = "/todos/{id}/accept",
+    tag = "Assignments",
+    params(
+        ("id" = i64, Path, description = "Todo id")
+    ),
+    responses(
+        (status = 200, description = "Assignment accepted, the assigner is notified", body = AssignmentResponse),
+        (status = 404, description = "Pending assignment not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn accept_assignment_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        assignment_service(&app_state)
+            .respond(
+                workspace.user_id,
+                workspace.workspace_id,
+                id,
+                AssignmentDecision::Accept,
+            )
+            .await,
+    )
+}
+
+// Decline Assignment Route
+#[utoipa::path(
+    post,
+    path = "/todos/{id}/decline",
+    tag = "Assignments",
+    params(
+        ("id" = i64, Path, description = "Todo id")
+    ),
+    responses(
+        (status = 200, description = "Assignment declined, the assigner is notified", body = AssignmentResponse),
+        (status = 404, description = "Pending assignment not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn decline_assignment_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        assignment_service(&app_state)
+            .respond(
+                workspace.user_id,
+                workspace.workspace_id,
+                id,
+                AssignmentDecision::Decline,
+            )
+            .await,
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assignment_routes_creation() {
+        let _routes = assignment_routes();
+        assert!(true);
+    }
+}
@@ -0,0 +1,212 @@
+//! # `Assignment` Service
+//!
+//! This module contains the bussiness logic for the todos delegated to other members of a
+//! workspace. Delegated todos wait in the review queue of the assignee, who accepts or
+//! declines them, instead of landing silently in their list.
+
+use crate::modules::{
+    assignment::{
+        interfaces::{
+            AssignTodoRequest, AssignmentDecision, AssignmentQuery, AssignmentResponse,
+            NotifiedAssignmentRow,
+        },
+        repository::AssignmentRepository,
+    },
+    common::AppError,
+    email::{
+        mailer::BackgroundMailer,
+        templates::{ASSIGNMENT_REQUEST, ASSIGNMENT_RESPONSE},
+    },
+};
+
+// Status of the assignments listed by the review queue, `pending` by default
+fn parse_status(status: Option<&str>) -> Result<&'static str, AppError> {
+    match status.map(str::trim) {
+        None | Some("pending") => Ok("pending"),
+        Some("accepted") => Ok("accepted"),
+        Some("declined") => Ok("declined"),
+        Some(_) => Err(AppError::Validation(
+            "Status must be one of pending, accepted or declined".to_string(),
+        )),
+    }
+}
+
+pub struct AssignmentService {
+    assignment_repository: AssignmentRepository,
+    mailer: BackgroundMailer,
+}
+
+impl AssignmentService {
+    pub const fn new(
+        assignment_repository: AssignmentRepository,
+        mailer: BackgroundMailer,
+    ) -> Self {
+        Self {
+            assignment_repository,
+            mailer,
+        }
+    }
+
+    // Delegate a todo of the user to another member of the workspace, the todo waits
+    // in the review queue of the assignee until answered
+    pub async fn assign(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+        assign_request: AssignTodoRequest,
+    ) -> Result<AssignmentResponse, AppError> {
+        let Some(assignee) = assign_request
+            .assignee
+            .as_deref()
+            .map(str::trim)
+            .filter(|assignee| !assignee.is_empty())
+        else {
+            return Err(AppError::Validation(
+                "Missing required field: assignee".to_string(),
+            ));
+        };
+
+        match self
+            .assignment_repository
+            .is_todo_owner(user_id, workspace_id, todo_id)
+            .await
+        {
+            Ok(true) => {}
+            Ok(false) => return Err(AppError::NotFound("Todo not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error checking todo ownership: {}", e);
+                return Err(AppError::Database(e));
+            }
+        }
+
+        let assignee_id = match self
+            .assignment_repository
+            .find_member(workspace_id, assignee)
+            .await
+        {
+            Ok(Some(assignee_id)) if i64::from(assignee_id) == user_id => {
+                return Err(AppError::Validation(
+                    "Todos cannot be assigned to yourself".to_string(),
+                ))
+            }
+            Ok(Some(assignee_id)) => assignee_id,
+            Ok(None) => {
+                return Err(AppError::Validation(
+                    "Assignee is not a member of the workspace".to_string(),
+                ))
+            }
+            Err(e) => {
+                tracing::warn!("Error finding assignee: {}", e);
+                return Err(AppError::Database(e));
+            }
+        };
+
+        match self
+            .assignment_repository
+            .create_assignment(user_id, todo_id, assignee_id)
+            .await
+        {
+            Ok(Some(row)) => {
+                let NotifiedAssignmentRow {
+                    assignment,
+                    notify_email,
+                } = row;
+                self.mailer.send(ASSIGNMENT_REQUEST.render(
+                    notify_email,
+                    &[
+                        ("username", assignment.assignee.as_str()),
+                        ("assigner", assignment.assigner.as_str()),
+                        ("title", assignment.title.as_str()),
+                    ],
+                ));
+                Ok(AssignmentResponse::from(assignment))
+            }
+            Ok(None) => Err(AppError::Conflict(
+                "Todo is already assigned, wait for the assignee to decline it".to_string(),
+            )),
+            Err(e) => {
+                tracing::warn!("Error assigning todo: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Review queue of the user, the todos of the workspace delegated to them
+    pub async fn list_assigned(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        query: &AssignmentQuery,
+    ) -> Result<Vec<AssignmentResponse>, AppError> {
+        let status = parse_status(query.status.as_deref())?;
+
+        match self
+            .assignment_repository
+            .list_assigned(user_id, workspace_id, status)
+            .await
+        {
+            Ok(assignments) => Ok(assignments
+                .into_iter()
+                .map(AssignmentResponse::from)
+                .collect()),
+            Err(e) => {
+                tracing::warn!("Error listing assignments: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Accept or decline the pending assignment of a todo, the assigner is notified of
+    // the answer. Declined todos can be assigned again.
+    pub async fn respond(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+        decision: AssignmentDecision,
+    ) -> Result<AssignmentResponse, AppError> {
+        match self
+            .assignment_repository
+            .respond(user_id, workspace_id, todo_id, decision)
+            .await
+        {
+            Ok(Some(row)) => {
+                let NotifiedAssignmentRow {
+                    assignment,
+                    notify_email,
+                } = row;
+                self.mailer.send(ASSIGNMENT_RESPONSE.render(
+                    notify_email,
+                    &[
+                        ("username", assignment.assigner.as_str()),
+                        ("assignee", assignment.assignee.as_str()),
+                        ("title", assignment.title.as_str()),
+                        ("decision", decision.status()),
+                    ],
+                ));
+                Ok(AssignmentResponse::from(assignment))
+            }
+            Ok(None) => Err(AppError::NotFound(
+                "Pending assignment not found".to_string(),
+            )),
+            Err(e) => {
+                tracing::warn!("Error answering assignment: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_status() {
+        assert_eq!(parse_status(None).ok(), Some("pending"));
+        assert_eq!(parse_status(Some(" accepted ")).ok(), Some("accepted"));
+        assert_eq!(parse_status(Some("declined")).ok(), Some("declined"));
+        assert!(parse_status(Some("cancelled")).is_err());
+    }
+}
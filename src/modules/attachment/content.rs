@@ -0,0 +1,332 @@
+//! # Attachment Content Checks
+//! Uploaded files are checked against their declared content type with their magic
+//! bytes, and executables are rejected whatever type they declare. The metadata of
+//! JPEG, PNG and WebP images (EXIF with the GPS position, XMP, text chunks) is stripped
+//! before they are stored. The EXIF orientation goes with it.
+
+use axum::body::Bytes;
+
+const JPEG_MAGIC: &[u8] = &[0xFF, 0xD8, 0xFF];
+const PNG_MAGIC: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+// Types recognized by their magic bytes, a file declaring one of them must match it
+const SNIFFED_TYPES: &[&str] = &[
+    "image/jpeg",
+    "image/png",
+    "image/gif",
+    "image/webp",
+    "application/pdf",
+];
+
+// Magic bytes of Windows, Linux and macOS executables, and of scripts
+const EXECUTABLE_MAGICS: &[&[u8]] = &[
+    b"MZ",
+    b"\x7fELF",
+    &[0xFE, 0xED, 0xFA, 0xCE],
+    &[0xFE, 0xED, 0xFA, 0xCF],
+    &[0xCE, 0xFA, 0xED, 0xFE],
+    &[0xCF, 0xFA, 0xED, 0xFE],
+    &[0xCA, 0xFE, 0xBA, 0xBE],
+    b"#!",
+];
+
+// PNG chunks holding metadata
+const PNG_METADATA_CHUNKS: &[&[u8]] = &[b"eXIf", b"tEXt", b"zTXt", b"iTXt", b"tIME"];
+
+// JPEG segments holding metadata: APP1 (EXIF and XMP) and APP13 (Photoshop IPTC)
+const JPEG_APP1: u8 = 0xE1;
+const JPEG_APP13: u8 = 0xED;
+const JPEG_START_OF_SCAN: u8 = 0xDA;
+const JPEG_END_OF_IMAGE: u8 = 0xD9;
+
+// Flags of the WebP VP8X chunk announcing EXIF and XMP chunks
+const WEBP_EXIF_FLAG: u8 = 0x08;
+const WEBP_XMP_FLAG: u8 = 0x04;
+
+// Content type of a file told by its magic bytes, `None` when not recognized
+pub fn sniff_content_type(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(JPEG_MAGIC) {
+        Some("image/jpeg")
+    } else if data.starts_with(PNG_MAGIC) {
+        Some("image/png")
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WEBP".as_slice()) {
+        Some("image/webp")
+    } else if data.starts_with(b"%PDF-") {
+        Some("application/pdf")
+    } else {
+        None
+    }
+}
+
+// Check the content of a file against its declared content type
+pub fn validate_content(content_type: &str, data: &[u8]) -> Result<(), String> {
+    if EXECUTABLE_MAGICS
+        .iter()
+        .any(|magic| data.starts_with(magic))
+    {
+        return Err("Executable files are not allowed".to_string());
+    }
+
+    match sniff_content_type(data) {
+        Some(detected) if detected != content_type => {
+            Err(format!("File content is {detected}, not {content_type}"))
+        }
+        None if content_type.starts_with("text/") => {
+            if !data.contains(&0) && std::str::from_utf8(data).is_ok() {
+                Ok(())
+            } else {
+                Err("File content is not text".to_string())
+            }
+        }
+        None if SNIFFED_TYPES.contains(&content_type) => {
+            Err(format!("File content is not {content_type}"))
+        }
+        Some(_) | None => Ok(()),
+    }
+}
+
+// Remove the metadata of an image, other files are returned as they are
+pub fn strip_metadata(content_type: &str, data: Bytes) -> Result<Bytes, String> {
+    match content_type {
+        "image/jpeg" => strip_jpeg(&data).map(Bytes::from),
+        "image/png" => strip_png(&data).map(Bytes::from),
+        "image/webp" => strip_webp(&data).map(Bytes::from),
+        _ => Ok(data),
+    }
+}
+
+fn malformed(kind: &str) -> String {
+    format!("Malformed {kind} image")
+}
+
+// Copy the segments up to the image data, without the metadata ones
+fn strip_jpeg(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut stripped = Vec::with_capacity(data.len());
+    stripped.extend_from_slice(data.get(..2).ok_or_else(|| malformed("JPEG"))?);
+
+    let mut pos = 2;
+    loop {
+        let Some(&[0xFF, marker]) = data.get(pos..pos + 2) else {
+            return Err(malformed("JPEG"));
+        };
+        match marker {
+            // Fill byte before a marker
+            0xFF => {
+                pos += 1;
+                continue;
+            }
+            // The entropy coded image data follows, kept as it is
+            JPEG_START_OF_SCAN | JPEG_END_OF_IMAGE => {
+                stripped.extend_from_slice(&data[pos..]);
+                return Ok(stripped);
+            }
+            // Markers without a payload
+            0x01 | 0xD0..=0xD7 => {
+                stripped.extend_from_slice(&data[pos..pos + 2]);
+                pos += 2;
+                continue;
+            }
+            _ => {}
+        }
+
+        let length = data
+            .get(pos + 2..pos + 4)
+            .map(|length| usize::from(u16::from_be_bytes([length[0], length[1]])))
+            .filter(|length| *length >= 2)
+            .ok_or_else(|| malformed("JPEG"))?;
+        let end = pos + 2 + length;
+        let segment = data.get(pos..end).ok_or_else(|| malformed("JPEG"))?;
+        if marker != JPEG_APP1 && marker != JPEG_APP13 {
+            stripped.extend_from_slice(segment);
+        }
+        pos = end;
+    }
+}
+
+// Copy the chunks of the image, without the metadata ones
+fn strip_png(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut stripped = Vec::with_capacity(data.len());
+    stripped.extend_from_slice(PNG_MAGIC);
+
+    let mut pos = PNG_MAGIC.len();
+    while pos < data.len() {
+        // Length, type, data and CRC
+        let end = data
+            .get(pos..pos + 4)
+            .and_then(|length| <[u8; 4]>::try_from(length).ok())
+            .and_then(|length| usize::try_from(u32::from_be_bytes(length)).ok())
+            .and_then(|length| length.checked_add(pos + 12))
+            .ok_or_else(|| malformed("PNG"))?;
+        let chunk = data.get(pos..end).ok_or_else(|| malformed("PNG"))?;
+        if !PNG_METADATA_CHUNKS.contains(&&chunk[4..8]) {
+            stripped.extend_from_slice(chunk);
+        }
+        pos = end;
+    }
+    Ok(stripped)
+}
+
+// Rebuild the RIFF container without the EXIF and XMP chunks
+fn strip_webp(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut chunks = Vec::with_capacity(data.len());
+
+    let mut pos = 12;
+    while pos < data.len() {
+        // FourCC, size and data padded to an even size
+        let size = data
+            .get(pos + 4..pos + 8)
+            .and_then(|size| <[u8; 4]>::try_from(size).ok())
+            .and_then(|size| usize::try_from(u32::from_le_bytes(size)).ok())
+            .ok_or_else(|| malformed("WebP"))?;
+        let end = size
+            .checked_add(size % 2 + pos + 8)
+            .ok_or_else(|| malformed("WebP"))?;
+        // Some encoders leave out the padding of the last chunk
+        let chunk = data
+            .get(pos..end.min(data.len()))
+            .filter(|chunk| end - pos - chunk.len() <= 1)
+            .ok_or_else(|| malformed("WebP"))?;
+
+        match &chunk[..4] {
+            b"EXIF" | b"XMP " => {}
+            b"VP8X" => {
+                let mut header = chunk.to_vec();
+                if let Some(flags) = header.get_mut(8) {
+                    *flags &= !(WEBP_EXIF_FLAG | WEBP_XMP_FLAG);
+                }
+                chunks.extend_from_slice(&header);
+            }
+            _ => chunks.extend_from_slice(chunk),
+        }
+        pos = end;
+    }
+
+    let riff_size = u32::try_from(chunks.len() + 4).map_err(|_| malformed("WebP"))?;
+    let mut stripped = Vec::with_capacity(chunks.len() + 12);
+    stripped.extend_from_slice(b"RIFF");
+    stripped.extend_from_slice(&riff_size.to_le_bytes());
+    stripped.extend_from_slice(b"WEBP");
+    stripped.extend_from_slice(&chunks);
+    Ok(stripped)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn jpeg() -> Vec<u8> {
+        let mut data = vec![0xFF, 0xD8];
+        // APP0 JFIF
+        data.extend_from_slice(&[0xFF, 0xE0, 0x00, 0x06, b'J', b'F', b'I', b'F']);
+        // APP1 EXIF
+        data.extend_from_slice(&[0xFF, 0xE1, 0x00, 0x08, b'E', b'x', b'i', b'f', 0, 0]);
+        // Start of scan and image data
+        data.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x02, 0x12, 0x34, 0xFF, 0xD9]);
+        data
+    }
+
+    fn png_chunk(kind: &[u8], payload: &[u8]) -> Vec<u8> {
+        let mut chunk = u32::try_from(payload.len()).unwrap().to_be_bytes().to_vec();
+        chunk.extend_from_slice(kind);
+        chunk.extend_from_slice(payload);
+        chunk.extend_from_slice(&[0, 0, 0, 0]);
+        chunk
+    }
+
+    fn webp_chunk(kind: &[u8], payload: &[u8]) -> Vec<u8> {
+        let mut chunk = kind.to_vec();
+        chunk.extend_from_slice(&u32::try_from(payload.len()).unwrap().to_le_bytes());
+        chunk.extend_from_slice(payload);
+        if payload.len() % 2 == 1 {
+            chunk.push(0);
+        }
+        chunk
+    }
+
+    fn webp(chunks: &[Vec<u8>]) -> Vec<u8> {
+        let body = chunks.concat();
+        let mut data = b"RIFF".to_vec();
+        data.extend_from_slice(&u32::try_from(body.len() + 4).unwrap().to_le_bytes());
+        data.extend_from_slice(b"WEBP");
+        data.extend_from_slice(&body);
+        data
+    }
+
+    #[test]
+    fn test_sniff_content_type() {
+        assert_eq!(sniff_content_type(&jpeg()), Some("image/jpeg"));
+        assert_eq!(sniff_content_type(b"GIF89a..."), Some("image/gif"));
+        assert_eq!(sniff_content_type(b"%PDF-1.7"), Some("application/pdf"));
+        assert_eq!(sniff_content_type(&webp(&[])), Some("image/webp"));
+        assert_eq!(sniff_content_type(b"hello"), None);
+    }
+
+    #[test]
+    fn test_validate_content() {
+        assert!(validate_content("image/jpeg", &jpeg()).is_ok());
+        assert!(validate_content("text/plain", b"hello").is_ok());
+        assert!(validate_content("image/png", &jpeg()).is_err());
+        assert!(validate_content("image/png", b"hello").is_err());
+        assert!(validate_content("text/plain", &jpeg()).is_err());
+        assert!(validate_content("text/plain", b"a\0b").is_err());
+        assert!(validate_content("application/zip", b"PK\x03\x04").is_ok());
+    }
+
+    #[test]
+    fn test_validate_content_rejects_executables() {
+        assert!(validate_content("application/pdf", b"MZ\x90\x00").is_err());
+        assert!(validate_content("text/plain", b"#!/bin/sh\nrm -rf /").is_err());
+        assert!(validate_content("application/octet-stream", b"\x7fELF\x02").is_err());
+    }
+
+    #[test]
+    fn test_strip_jpeg_drops_exif() {
+        let stripped = strip_metadata("image/jpeg", Bytes::from(jpeg())).unwrap();
+        assert!(!stripped.windows(4).any(|window| window == b"Exif"));
+        assert!(stripped.starts_with(&[0xFF, 0xD8, 0xFF, 0xE0]));
+        assert!(stripped.ends_with(&[0xFF, 0xDA, 0x00, 0x02, 0x12, 0x34, 0xFF, 0xD9]));
+    }
+
+    #[test]
+    fn test_strip_png_drops_text_chunks() {
+        let mut data = PNG_MAGIC.to_vec();
+        data.extend(png_chunk(b"IHDR", &[1; 13]));
+        data.extend(png_chunk(b"tEXt", b"GPS\x0048.85,2.35"));
+        data.extend(png_chunk(b"IEND", &[]));
+
+        let stripped = strip_metadata("image/png", Bytes::from(data)).unwrap();
+        let mut expected = PNG_MAGIC.to_vec();
+        expected.extend(png_chunk(b"IHDR", &[1; 13]));
+        expected.extend(png_chunk(b"IEND", &[]));
+        assert_eq!(stripped, expected);
+    }
+
+    #[test]
+    fn test_strip_webp_drops_exif_and_flag() {
+        let data = webp(&[
+            webp_chunk(b"VP8X", &[WEBP_EXIF_FLAG, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
+            webp_chunk(b"VP8 ", &[1, 2, 3]),
+            webp_chunk(b"EXIF", b"Exif\0\0"),
+        ]);
+
+        let stripped = strip_metadata("image/webp", Bytes::from(data)).unwrap();
+        assert_eq!(
+            stripped,
+            webp(&[
+                webp_chunk(b"VP8X", &[0; 10]),
+                webp_chunk(b"VP8 ", &[1, 2, 3]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_strip_metadata_rejects_truncated_images() {
+        let mut data = jpeg();
+        data.truncate(12);
+        assert!(strip_metadata("image/jpeg", Bytes::from(data)).is_err());
+        assert!(strip_metadata("text/plain", Bytes::from_static(b"hi")).is_ok());
+    }
+}
@@ -0,0 +1,127 @@
+//! # `Attachments` Interfaces
+//! This module defines the data structures from todo Attachments module
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+use crate::utils::dates::rfc3339_option;
+
+// Multipart body of an upload, only documents the `file` field
+#[allow(dead_code)]
+#[derive(ToSchema)]
+pub struct UploadAttachmentRequest {
+    #[schema(value_type = String, format = Binary)]
+    pub file: Vec<u8>,
+}
+
+// File read from an upload, before it is stored
+pub struct UploadedFile {
+    pub file_name: String,
+    pub content_type: String,
+    pub data: axum::body::Bytes,
+}
+
+// Attachment row as stored in database
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct AttachmentRow {
+    pub id: i32,
+    pub todo_id: i32,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub storage_key: String,
+    pub created_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct AttachmentResponse {
+    pub id: i64,
+    pub todo_id: i64,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    #[serde(with = "rfc3339_option")]
+    pub created_at: Option<OffsetDateTime>,
+}
+
+impl From<AttachmentRow> for AttachmentResponse {
+    fn from(row: AttachmentRow) -> Self {
+        Self {
+            id: i64::from(row.id),
+            todo_id: i64::from(row.todo_id),
+            file_name: row.file_name,
+            content_type: row.content_type,
+            size_bytes: row.size_bytes,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct AttachmentMessageResponse {
+    pub message: String,
+}
+
+// Attachment bytes of a user in one workspace, as summed in database
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct WorkspaceStorageRow {
+    pub workspace_id: i32,
+    pub workspace_name: String,
+    pub attachments: i64,
+    pub used_bytes: i64,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct WorkspaceStorageUsage {
+    pub workspace_id: i64,
+    pub workspace_name: String,
+    pub attachments: i64,
+    pub used_bytes: i64,
+}
+
+impl From<WorkspaceStorageRow> for WorkspaceStorageUsage {
+    fn from(row: WorkspaceStorageRow) -> Self {
+        Self {
+            workspace_id: i64::from(row.workspace_id),
+            workspace_name: row.workspace_name,
+            attachments: row.attachments,
+            used_bytes: row.used_bytes,
+        }
+    }
+}
+
+// Attachment storage of the user, trashed todos keep counting until they are purged
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct StorageUsageResponse {
+    pub attachments: i64,
+    pub used_bytes: i64,
+    // Bytes the user may store, null when unlimited
+    pub quota_bytes: Option<i64>,
+    // Bytes left before uploads are refused, null when unlimited
+    pub remaining_bytes: Option<i64>,
+    // Usage of each workspace the user has attachments in, largest first
+    pub workspaces: Vec<WorkspaceStorageUsage>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attachment_response_hides_storage_key() {
+        let row = AttachmentRow {
+            id: 3,
+            todo_id: 7,
+            file_name: "receipt.pdf".to_string(),
+            content_type: "application/pdf".to_string(),
+            size_bytes: 2048,
+            storage_key: "todos/7/abc".to_string(),
+            created_at: None,
+        };
+        let response = AttachmentResponse::from(row);
+        assert_eq!(response.id, 3);
+        assert_eq!(response.todo_id, 7);
+        assert_eq!(response.size_bytes, 2048);
+    }
+}
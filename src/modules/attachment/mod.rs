@@ -0,0 +1,11 @@
+//! # `Attachment` Mod
+//! Attachment imports for the todo attachments module
+
+pub mod content;
+pub mod interfaces;
+pub mod repository;
+pub mod routes;
+pub mod service;
+pub mod storage;
+
+pub use routes::attachment_routes;
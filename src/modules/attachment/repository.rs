@@ -0,0 +1,217 @@
+//! # `Attachment` Repository
+//! This module defines the attachment repository for todo attachment metadata.
+
+use sqlx::{Error, Pool, Postgres};
+
+use crate::modules::{
+    attachment::interfaces::{AttachmentRow, WorkspaceStorageRow},
+    common::to_db_id,
+};
+use crate::telemetry::observe_query;
+
+const ATTACHMENT_COLUMNS: &str =
+    "id, todo_id, file_name, content_type, size_bytes, storage_key, created_at";
+
+pub struct AttachmentRepository {
+    pool: Pool<Postgres>,
+}
+
+impl AttachmentRepository {
+    // Constructor
+    pub const fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Check if a todo of the workspace is owned by the user
+    pub async fn is_todo_owner(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+    ) -> Result<bool, Error> {
+        observe_query("attachment.is_todo_owner", async move {
+            sqlx::query_scalar::<_, bool>(
+                "SELECT EXISTS(
+                    SELECT 1 FROM todos
+                    WHERE id = $1 AND user_id = $2 AND workspace_id = $3 AND deleted_at IS NULL)",
+            )
+            .bind(to_db_id(todo_id)?)
+            .bind(to_db_id(user_id)?)
+            .bind(to_db_id(workspace_id)?)
+            .fetch_one(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Total bytes of the attachments on the todos of the user, across workspaces
+    pub async fn used_bytes(&self, user_id: i64) -> Result<i64, Error> {
+        observe_query("attachment.used_bytes", async move {
+            sqlx::query_scalar::<_, i64>(
+                "SELECT COALESCE(SUM(a.size_bytes), 0)::BIGINT
+                 FROM todo_attachments a
+                 JOIN todos t ON t.id = a.todo_id
+                 WHERE t.user_id = $1",
+            )
+            .bind(to_db_id(user_id)?)
+            .fetch_one(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Attachment bytes of the user per workspace, largest first
+    pub async fn storage_usage(&self, user_id: i64) -> Result<Vec<WorkspaceStorageRow>, Error> {
+        observe_query("attachment.storage_usage", async move {
+            sqlx::query_as::<_, WorkspaceStorageRow>(
+                "SELECT w.id AS workspace_id, w.name AS workspace_name,
+                        COUNT(a.id) AS attachments,
+                        COALESCE(SUM(a.size_bytes), 0)::BIGINT AS used_bytes
+                 FROM todo_attachments a
+                 JOIN todos t ON t.id = a.todo_id
+                 JOIN workspaces w ON w.id = t.workspace_id
+                 WHERE t.user_id = $1
+                 GROUP BY w.id, w.name
+                 ORDER BY used_bytes DESC, w.id",
+            )
+            .bind(to_db_id(user_id)?)
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Record a stored attachment, returns `None` when the todo is not owned
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_attachment(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+        file_name: &str,
+        content_type: &str,
+        size_bytes: i64,
+        storage_key: &str,
+    ) -> Result<Option<AttachmentRow>, Error> {
+        observe_query("attachment.create_attachment", async move {
+            let query = format!(
+                "INSERT INTO todo_attachments (todo_id, file_name, content_type, size_bytes, storage_key)
+                 SELECT t.id, $3, $4, $5, $6
+                 FROM todos t
+                 WHERE t.id = $1 AND t.user_id = $2 AND t.workspace_id = $7 AND t.deleted_at IS NULL
+                 RETURNING {ATTACHMENT_COLUMNS}"
+            );
+
+            sqlx::query_as::<_, AttachmentRow>(&query)
+                .bind(to_db_id(todo_id)?)
+                .bind(to_db_id(user_id)?)
+                .bind(file_name)
+                .bind(content_type)
+                .bind(size_bytes)
+                .bind(storage_key)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // List the attachments of a todo, oldest first
+    pub async fn list_attachments(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+    ) -> Result<Vec<AttachmentRow>, Error> {
+        observe_query("attachment.list_attachments", async move {
+            let query = format!(
+                "SELECT {ATTACHMENT_COLUMNS} FROM todo_attachments
+                 WHERE todo_id = $1
+                   AND EXISTS(
+                        SELECT 1 FROM todos
+                        WHERE id = $1 AND user_id = $2 AND workspace_id = $3 AND deleted_at IS NULL)
+                 ORDER BY created_at, id"
+            );
+
+            sqlx::query_as::<_, AttachmentRow>(&query)
+                .bind(to_db_id(todo_id)?)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Fetch an attachment of a todo owned by the user in the workspace
+    pub async fn fetch_attachment(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+        id: i64,
+    ) -> Result<Option<AttachmentRow>, Error> {
+        observe_query("attachment.fetch_attachment", async move {
+            let query = format!(
+                "SELECT {ATTACHMENT_COLUMNS} FROM todo_attachments
+                 WHERE id = $1 AND todo_id = $2
+                   AND EXISTS(
+                        SELECT 1 FROM todos
+                        WHERE id = $2 AND user_id = $3 AND workspace_id = $4 AND deleted_at IS NULL)"
+            );
+
+            sqlx::query_as::<_, AttachmentRow>(&query)
+                .bind(to_db_id(id)?)
+                .bind(to_db_id(todo_id)?)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Delete an attachment, returns the deleted row so its content can be removed
+    pub async fn delete_attachment(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+        id: i64,
+    ) -> Result<Option<AttachmentRow>, Error> {
+        observe_query("attachment.delete_attachment", async move {
+            let query = format!(
+                "DELETE FROM todo_attachments
+                 WHERE id = $1 AND todo_id = $2
+                   AND EXISTS(
+                        SELECT 1 FROM todos
+                        WHERE id = $2 AND user_id = $3 AND workspace_id = $4 AND deleted_at IS NULL)
+                 RETURNING {ATTACHMENT_COLUMNS}"
+            );
+
+            sqlx::query_as::<_, AttachmentRow>(&query)
+                .bind(to_db_id(id)?)
+                .bind(to_db_id(todo_id)?)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attachment_columns_include_storage_key() {
+        assert!(ATTACHMENT_COLUMNS.contains("storage_key"));
+    }
+
+    #[test]
+    fn test_invalid_id_is_rejected() {
+        assert!(to_db_id(i64::MAX).is_err());
+    }
+}
@@ -0,0 +1,248 @@
+//! #`Attachment` Routes
+//! This module defines the HTTP routes for todo attachments.
+
+use axum::extract::{DefaultBodyLimit, Multipart, Path};
+use axum::http::header::{CONTENT_DISPOSITION, CONTENT_LENGTH, CONTENT_TYPE};
+use axum::response::Redirect;
+use axum::routing::get;
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Router};
+
+use crate::auth::Claims;
+use crate::modules::attachment::interfaces::{
+    AttachmentMessageResponse, AttachmentResponse, StorageUsageResponse, UploadAttachmentRequest,
+};
+use crate::modules::attachment::repository::AttachmentRepository;
+use crate::modules::attachment::service::AttachmentService;
+use crate::modules::common::{respond, ErrorResponse};
+use crate::modules::workspace::context::WorkspaceContext;
+use crate::storage::Download;
+use crate::AppState;
+
+// Room left for the multipart boundaries and headers around the file
+const MULTIPART_OVERHEAD_BYTES: usize = 64 * 1024;
+
+// Creates and returns the attachment routes, uploads are capped to `max_bytes`
+pub fn attachment_routes(max_bytes: usize) -> Router<AppState> {
+    Router::new()
+        .route(
+            "/todos/{id}/attachments",
+            get(list_attachments_route)
+                .post(upload_attachment_route)
+                .layer(DefaultBodyLimit::max(
+                    max_bytes.saturating_add(MULTIPART_OVERHEAD_BYTES),
+                )),
+        )
+        .route(
+            "/todos/{id}/attachments/{attachment_id}",
+            get(download_attachment_route).delete(delete_attachment_route),
+        )
+        .route("/user/storage", get(storage_usage_route))
+}
+
+fn attachment_service(app_state: &AppState) -> AttachmentService {
+    AttachmentService::new(
+        AttachmentRepository::new(app_state.db_pool.clone()),
+        app_state.attachment_storage.clone(),
+    )
+}
+
+// Header offering the file name to save the download as, non ASCII characters are replaced
+fn content_disposition(file_name: &str) -> String {
+    let ascii_name: String = file_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii() && !c.is_ascii_control() {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("attachment; filename=\"{ascii_name}\"")
+}
+
+// Upload Attachment Route
+#[utoipa::path(
+    post,
+    path = "/todos/{id}/attachments",
+    tag = "Attachments",
+    params(
+        ("id" = i64, Path, description = "Todo id")
+    ),
+    request_body(content = UploadAttachmentRequest, content_type = "multipart/form-data"),
+    responses(
+        (status = 201, description = "Attachment uploaded successfully", body = AttachmentResponse),
+        (status = 400, description = "Invalid file, content not matching its type or file too large", body = ErrorResponse),
+        (status = 404, description = "Todo not found", body = ErrorResponse),
+        (status = 409, description = "Storage quota exceeded", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn upload_attachment_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path(id): Path<i64>,
+    multipart: Multipart,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::CREATED,
+        attachment_service(&app_state)
+            .upload_attachment(workspace.user_id, workspace.workspace_id, id, multipart)
+            .await,
+    )
+}
+
+// List Attachments Route
+#[utoipa::path(
+    get,
+    path = "/todos/{id}/attachments",
+    tag = "Attachments",
+    params(
+        ("id" = i64, Path, description = "Todo id")
+    ),
+    responses(
+        (status = 200, description = "Attachments fetched successfully", body = Vec<AttachmentResponse>),
+        (status = 500, description = "Failed to list attachments", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn list_attachments_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        attachment_service(&app_state)
+            .list_attachments(workspace.user_id, workspace.workspace_id, id)
+            .await,
+    )
+}
+
+// Download Attachment Route
+#[utoipa::path(
+    get,
+    path = "/todos/{id}/attachments/{attachment_id}",
+    tag = "Attachments",
+    params(
+        ("id" = i64, Path, description = "Todo id"),
+        ("attachment_id" = i64, Path, description = "Attachment id")
+    ),
+    responses(
+        (status = 200, description = "Attachment content, streamed by the local storage", content_type = "application/octet-stream"),
+        (status = 307, description = "Redirect to a presigned URL of the S3 or GCS storage"),
+        (status = 404, description = "Attachment not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn download_attachment_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path((id, attachment_id)): Path<(i64, i64)>,
+) -> impl IntoResponse {
+    match attachment_service(&app_state)
+        .download_attachment(workspace.user_id, workspace.workspace_id, id, attachment_id)
+        .await
+    {
+        Ok((_, Download::Redirect(url))) => Redirect::temporary(&url).into_response(),
+        Ok((attachment, Download::Stream { body, size })) => (
+            StatusCode::OK,
+            [
+                (CONTENT_TYPE, attachment.content_type),
+                (CONTENT_LENGTH, size.to_string()),
+                (
+                    CONTENT_DISPOSITION,
+                    content_disposition(&attachment.file_name),
+                ),
+            ],
+            body,
+        )
+            .into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+// Delete Attachment Route
+#[utoipa::path(
+    delete,
+    path = "/todos/{id}/attachments/{attachment_id}",
+    tag = "Attachments",
+    params(
+        ("id" = i64, Path, description = "Todo id"),
+        ("attachment_id" = i64, Path, description = "Attachment id")
+    ),
+    responses(
+        (status = 200, description = "Attachment deleted successfully", body = AttachmentMessageResponse),
+        (status = 404, description = "Attachment not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn delete_attachment_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path((id, attachment_id)): Path<(i64, i64)>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        attachment_service(&app_state)
+            .delete_attachment(workspace.user_id, workspace.workspace_id, id, attachment_id)
+            .await,
+    )
+}
+
+// Storage Usage Route
+#[utoipa::path(
+    get,
+    path = "/user/storage",
+    tag = "Attachments",
+    responses(
+        (status = 200, description = "Attachment storage used by the user and the quota", body = StorageUsageResponse),
+        (status = 500, description = "Failed to fetch storage usage", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn storage_usage_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        attachment_service(&app_state)
+            .storage_usage(claims.user_id)
+            .await,
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attachment_routes_creation() {
+        let _routes = attachment_routes(1024);
+        assert!(true);
+    }
+
+    #[test]
+    fn test_content_disposition() {
+        assert_eq!(
+            content_disposition("report.pdf"),
+            "attachment; filename=\"report.pdf\""
+        );
+        assert_eq!(
+            content_disposition("café.txt"),
+            "attachment; filename=\"caf_.txt\""
+        );
+    }
+}
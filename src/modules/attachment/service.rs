@@ -0,0 +1,369 @@
+//! # `Attachment` Service
+//!
+//! This module contains the bussiness logic for todo attachments. Metadata lives in
+//! database, the content in the blob storage.
+
+use axum::{body::Bytes, extract::Multipart};
+
+use crate::{
+    modules::{
+        attachment::{
+            content::{strip_metadata, validate_content},
+            interfaces::{
+                AttachmentMessageResponse, AttachmentResponse, AttachmentRow, StorageUsageResponse,
+                UploadedFile, WorkspaceStorageUsage,
+            },
+            repository::AttachmentRepository,
+            storage::AttachmentStorage,
+        },
+        common::AppError,
+    },
+    storage::Download,
+    utils::token::generate_random_token,
+};
+
+// Maximum length of a stored file name
+const MAX_FILE_NAME_LENGTH: usize = 255;
+const DEFAULT_FILE_NAME: &str = "attachment";
+
+// Keep the last path segment of a client file name, without quotes or control characters
+pub fn sanitize_file_name(file_name: &str) -> String {
+    let base = file_name
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .trim();
+    let sanitized: String = base
+        .chars()
+        .map(|c| if c.is_control() || c == '"' { '_' } else { c })
+        .take(MAX_FILE_NAME_LENGTH)
+        .collect();
+    if sanitized.is_empty() || sanitized == "." || sanitized == ".." {
+        DEFAULT_FILE_NAME.to_string()
+    } else {
+        sanitized
+    }
+}
+
+// Lowercase media type without its parameters, e.g. `text/plain; charset=utf-8` is `text/plain`
+pub fn normalize_content_type(content_type: &str) -> String {
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+fn invalid_upload(error: impl std::fmt::Display) -> AppError {
+    AppError::Validation(format!("Invalid upload: {error}"))
+}
+
+// Read the `file` field of a multipart body, stops as soon as it exceeds the size limit
+pub async fn read_file(
+    multipart: &mut Multipart,
+    max_bytes: usize,
+) -> Result<UploadedFile, AppError> {
+    while let Some(mut field) = multipart.next_field().await.map_err(invalid_upload)? {
+        if field.name() != Some("file") {
+            continue;
+        }
+        let file_name = sanitize_file_name(field.file_name().unwrap_or_default());
+        let content_type = field.content_type().map_or_else(
+            || "application/octet-stream".to_string(),
+            normalize_content_type,
+        );
+
+        let mut data = Vec::new();
+        while let Some(chunk) = field.chunk().await.map_err(invalid_upload)? {
+            if data.len() + chunk.len() > max_bytes {
+                return Err(AppError::Validation(format!(
+                    "File must be at most {max_bytes} bytes"
+                )));
+            }
+            data.extend_from_slice(&chunk);
+        }
+
+        return Ok(UploadedFile {
+            file_name,
+            content_type,
+            data: Bytes::from(data),
+        });
+    }
+    Err(AppError::Validation(
+        "Missing required fields: file".to_string(),
+    ))
+}
+
+pub struct AttachmentService {
+    attachment_repository: AttachmentRepository,
+    storage: AttachmentStorage,
+}
+
+impl AttachmentService {
+    pub const fn new(
+        attachment_repository: AttachmentRepository,
+        storage: AttachmentStorage,
+    ) -> Self {
+        Self {
+            attachment_repository,
+            storage,
+        }
+    }
+
+    // Store the uploaded file and attach it to a todo
+    pub async fn upload_attachment(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+        mut multipart: Multipart,
+    ) -> Result<AttachmentResponse, AppError> {
+        // Checked first so files for other todos are never read
+        match self
+            .attachment_repository
+            .is_todo_owner(user_id, workspace_id, todo_id)
+            .await
+        {
+            Ok(true) => {}
+            Ok(false) => return Err(AppError::NotFound("Todo not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error checking todo owner: {}", e);
+                return Err(AppError::Database(e));
+            }
+        }
+
+        let file = read_file(&mut multipart, self.storage.max_bytes()).await?;
+        self.attach_file(user_id, workspace_id, todo_id, file).await
+    }
+
+    // Store a file read from an upload or an email and attach it to a todo of the user
+    pub async fn attach_file(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+        file: UploadedFile,
+    ) -> Result<AttachmentResponse, AppError> {
+        if file.data.len() > self.storage.max_bytes() {
+            return Err(AppError::Validation(format!(
+                "File must be at most {} bytes",
+                self.storage.max_bytes()
+            )));
+        }
+        if !self.storage.is_allowed(&file.content_type) {
+            return Err(AppError::Validation(format!(
+                "File type {} is not allowed",
+                file.content_type
+            )));
+        }
+        validate_content(&file.content_type, &file.data).map_err(AppError::Validation)?;
+        let data = strip_metadata(&file.content_type, file.data).map_err(AppError::Validation)?;
+        let size_bytes = i64::try_from(data.len()).unwrap_or(i64::MAX);
+        // Parallel uploads may each pass the check, overshooting the quota by a file
+        self.check_quota(user_id, size_bytes).await?;
+
+        let storage_key = format!("todos/{todo_id}/{}", generate_random_token());
+        if let Err(e) = self
+            .storage
+            .backend()
+            .put(&storage_key, data, &file.content_type)
+            .await
+        {
+            tracing::warn!("Error storing attachment: {}", e);
+            return Err(AppError::Internal(
+                "Failed to upload attachment".to_string(),
+            ));
+        }
+
+        let result = self
+            .attachment_repository
+            .create_attachment(
+                user_id,
+                workspace_id,
+                todo_id,
+                &file.file_name,
+                &file.content_type,
+                size_bytes,
+                &storage_key,
+            )
+            .await;
+        match result {
+            Ok(Some(attachment)) => Ok(AttachmentResponse::from(attachment)),
+            Ok(None) => {
+                self.remove_content(&storage_key).await;
+                Err(AppError::NotFound("Todo not found".to_string()))
+            }
+            Err(e) => {
+                tracing::warn!("Error creating attachment: {}", e);
+                self.remove_content(&storage_key).await;
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Refuse a file the storage quota of the user has no room for
+    async fn check_quota(&self, user_id: i64, size_bytes: i64) -> Result<(), AppError> {
+        let Some(quota_bytes) = self.storage.quota_bytes() else {
+            return Ok(());
+        };
+        let used_bytes = match self.attachment_repository.used_bytes(user_id).await {
+            Ok(used_bytes) => used_bytes,
+            Err(e) => {
+                tracing::warn!("Error fetching storage usage: {}", e);
+                return Err(AppError::Database(e));
+            }
+        };
+        if self.storage.has_room(used_bytes, size_bytes) {
+            Ok(())
+        } else {
+            Err(AppError::Conflict(format!(
+                "Storage quota exceeded: {used_bytes} of {quota_bytes} bytes used, the file needs {size_bytes} bytes"
+            )))
+        }
+    }
+
+    // Attachment storage used by the user, with the quota
+    pub async fn storage_usage(&self, user_id: i64) -> Result<StorageUsageResponse, AppError> {
+        let workspaces: Vec<WorkspaceStorageUsage> =
+            match self.attachment_repository.storage_usage(user_id).await {
+                Ok(rows) => rows.into_iter().map(WorkspaceStorageUsage::from).collect(),
+                Err(e) => {
+                    tracing::warn!("Error fetching storage usage: {}", e);
+                    return Err(AppError::Database(e));
+                }
+            };
+        let attachments = workspaces.iter().map(|usage| usage.attachments).sum();
+        let used_bytes = workspaces
+            .iter()
+            .fold(0_i64, |total, usage| total.saturating_add(usage.used_bytes));
+        let quota_bytes = self.storage.quota_bytes();
+
+        Ok(StorageUsageResponse {
+            attachments,
+            used_bytes,
+            quota_bytes,
+            remaining_bytes: quota_bytes.map(|quota| quota.saturating_sub(used_bytes).max(0)),
+            workspaces,
+        })
+    }
+
+    // List the attachments of a todo
+    pub async fn list_attachments(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+    ) -> Result<Vec<AttachmentResponse>, AppError> {
+        match self
+            .attachment_repository
+            .list_attachments(user_id, workspace_id, todo_id)
+            .await
+        {
+            Ok(attachments) => Ok(attachments
+                .into_iter()
+                .map(AttachmentResponse::from)
+                .collect()),
+            Err(e) => {
+                tracing::warn!("Error listing attachments: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Resolve how the content of an attachment is downloaded
+    pub async fn download_attachment(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+        id: i64,
+    ) -> Result<(AttachmentRow, Download), AppError> {
+        let attachment = match self
+            .attachment_repository
+            .fetch_attachment(user_id, workspace_id, todo_id, id)
+            .await
+        {
+            Ok(Some(attachment)) => attachment,
+            Ok(None) => return Err(AppError::NotFound("Attachment not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error fetching attachment: {}", e);
+                return Err(AppError::Database(e));
+            }
+        };
+
+        match self
+            .storage
+            .backend()
+            .download(&attachment.storage_key)
+            .await
+        {
+            Ok(download) => Ok((attachment, download)),
+            Err(e) => Err(AppError::Internal(format!(
+                "Failed to read attachment content: {e}"
+            ))),
+        }
+    }
+
+    // Delete an attachment and its content
+    pub async fn delete_attachment(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+        id: i64,
+    ) -> Result<AttachmentMessageResponse, AppError> {
+        match self
+            .attachment_repository
+            .delete_attachment(user_id, workspace_id, todo_id, id)
+            .await
+        {
+            Ok(Some(attachment)) => {
+                self.remove_content(&attachment.storage_key).await;
+                Ok(AttachmentMessageResponse {
+                    message: "Attachment deleted successfully".to_string(),
+                })
+            }
+            Ok(None) => Err(AppError::NotFound("Attachment not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error deleting attachment: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Best effort removal, an orphaned object only costs storage
+    async fn remove_content(&self, storage_key: &str) {
+        if let Err(e) = self.storage.backend().delete(storage_key).await {
+            tracing::warn!("Error removing attachment content {}: {}", storage_key, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_file_name() {
+        assert_eq!(sanitize_file_name("report.pdf"), "report.pdf");
+        assert_eq!(sanitize_file_name("C:\\Users\\me\\photo.png"), "photo.png");
+        assert_eq!(sanitize_file_name("../../etc/passwd"), "passwd");
+        assert_eq!(sanitize_file_name("say \"hi\".txt"), "say _hi_.txt");
+        assert_eq!(sanitize_file_name(""), DEFAULT_FILE_NAME);
+        assert_eq!(sanitize_file_name("dir/.."), DEFAULT_FILE_NAME);
+        assert_eq!(
+            sanitize_file_name(&"a".repeat(300)).chars().count(),
+            MAX_FILE_NAME_LENGTH
+        );
+    }
+
+    #[test]
+    fn test_normalize_content_type() {
+        assert_eq!(
+            normalize_content_type("Text/Plain; charset=utf-8"),
+            "text/plain"
+        );
+        assert_eq!(normalize_content_type("image/png"), "image/png");
+    }
+}
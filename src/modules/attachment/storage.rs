@@ -0,0 +1,159 @@
+//! # Attachment Storage
+//! This module defines the upload limits and the storage quota of attachments, their
+//! content is kept in the blob storage.
+
+use std::sync::Arc;
+
+use crate::storage::BlobStorage;
+
+// Default maximum size of an attachment, 10 MiB
+const DEFAULT_MAX_BYTES: usize = 10 * 1024 * 1024;
+// Default attachment bytes a user may store, 500 MiB
+const DEFAULT_QUOTA_BYTES: i64 = 500 * 1024 * 1024;
+const DEFAULT_ALLOWED_TYPES: &str =
+    "image/png,image/jpeg,image/gif,image/webp,application/pdf,text/plain";
+
+// Blob storage of the attachments with the upload limits
+#[derive(Clone)]
+pub struct AttachmentStorage {
+    backend: Arc<dyn BlobStorage>,
+    max_bytes: usize,
+    allowed_types: Arc<[String]>,
+    quota_bytes: Option<i64>,
+}
+
+impl AttachmentStorage {
+    pub fn new(
+        backend: Arc<dyn BlobStorage>,
+        max_bytes: usize,
+        allowed_types: Vec<String>,
+        quota_bytes: Option<i64>,
+    ) -> Self {
+        Self {
+            backend,
+            max_bytes,
+            allowed_types: allowed_types.into(),
+            quota_bytes,
+        }
+    }
+
+    // Read the upload limits from the environment, a quota of 0 leaves the storage unlimited
+    pub fn from_env(backend: Arc<dyn BlobStorage>) -> Self {
+        let max_bytes = std::env::var("ATTACHMENT_MAX_BYTES")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(DEFAULT_MAX_BYTES);
+        let allowed_types = parse_allowed_types(
+            &std::env::var("ATTACHMENT_ALLOWED_TYPES")
+                .unwrap_or_else(|_| DEFAULT_ALLOWED_TYPES.to_string()),
+        );
+
+        let quota_bytes = parse_quota(
+            std::env::var("ATTACHMENT_QUOTA_BYTES")
+                .ok()
+                .and_then(|v| v.parse::<i64>().ok())
+                .unwrap_or(DEFAULT_QUOTA_BYTES),
+        );
+
+        Self::new(backend, max_bytes, allowed_types, quota_bytes)
+    }
+
+    pub fn backend(&self) -> &dyn BlobStorage {
+        self.backend.as_ref()
+    }
+
+    pub const fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    // Attachment bytes a user may store, `None` when unlimited
+    pub const fn quota_bytes(&self) -> Option<i64> {
+        self.quota_bytes
+    }
+
+    // Check whether a user storing `used_bytes` has room for `size_bytes` more
+    pub const fn has_room(&self, used_bytes: i64, size_bytes: i64) -> bool {
+        match self.quota_bytes {
+            Some(quota_bytes) => used_bytes.saturating_add(size_bytes) <= quota_bytes,
+            None => true,
+        }
+    }
+
+    // Check a content type against the allow list, `type/*` allows a whole family
+    pub fn is_allowed(&self, content_type: &str) -> bool {
+        let family = content_type.split('/').next().unwrap_or_default();
+        self.allowed_types.iter().any(|allowed| {
+            allowed == content_type
+                || allowed
+                    .strip_suffix("/*")
+                    .is_some_and(|prefix| prefix == family)
+        })
+    }
+}
+
+// Parse a comma separated list of content types
+fn parse_allowed_types(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|content_type| content_type.trim().to_ascii_lowercase())
+        .filter(|content_type| !content_type.is_empty())
+        .collect()
+}
+
+// A quota of 0 or less disables it
+const fn parse_quota(quota_bytes: i64) -> Option<i64> {
+    if quota_bytes > 0 {
+        Some(quota_bytes)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::LocalStorage;
+
+    #[test]
+    fn test_parse_allowed_types() {
+        assert_eq!(
+            parse_allowed_types(" image/PNG, ,application/pdf"),
+            vec!["image/png".to_string(), "application/pdf".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_is_allowed() {
+        let storage = AttachmentStorage::new(
+            Arc::new(LocalStorage::new(std::env::temp_dir())),
+            1024,
+            parse_allowed_types("image/*,application/pdf"),
+            None,
+        );
+        assert!(storage.is_allowed("image/png"));
+        assert!(storage.is_allowed("application/pdf"));
+        assert!(!storage.is_allowed("application/zip"));
+        assert!(!storage.is_allowed("imagery/png"));
+    }
+
+    #[test]
+    fn test_has_room() {
+        let storage = AttachmentStorage::new(
+            Arc::new(LocalStorage::new(std::env::temp_dir())),
+            1024,
+            Vec::new(),
+            parse_quota(2048),
+        );
+        assert!(storage.has_room(1024, 1024));
+        assert!(!storage.has_room(1024, 1025));
+        assert!(!storage.has_room(i64::MAX, 1));
+        assert_eq!(parse_quota(0), None);
+        assert!(AttachmentStorage::new(
+            Arc::new(LocalStorage::new(std::env::temp_dir())),
+            1024,
+            Vec::new(),
+            None,
+        )
+        .has_room(i64::MAX, i64::MAX));
+    }
+}
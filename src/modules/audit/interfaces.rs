@@ -0,0 +1,153 @@
+//! # `Audit` Interfaces
+//! This module defines the data structures from Audit module
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::modules::session::interfaces::SessionDevice;
+use crate::utils::dates::rfc3339_option;
+
+// Sensitive action recorded in the audit log
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuditAction {
+    Login,
+    LoginFailed,
+    PasswordChanged,
+    AccountDeleted,
+    UserDisabled,
+    UserEnabled,
+    UserDeleted,
+    BannedTermAdded,
+    BannedTermDeleted,
+    FlagReviewed,
+    TagBulkAttached,
+    TagBulkDetached,
+    InviteCodeCreated,
+    InviteCodeDeleted,
+}
+
+impl AuditAction {
+    pub const ALL: [Self; 14] = [
+        Self::Login,
+        Self::LoginFailed,
+        Self::PasswordChanged,
+        Self::AccountDeleted,
+        Self::UserDisabled,
+        Self::UserEnabled,
+        Self::UserDeleted,
+        Self::BannedTermAdded,
+        Self::BannedTermDeleted,
+        Self::FlagReviewed,
+        Self::TagBulkAttached,
+        Self::TagBulkDetached,
+        Self::InviteCodeCreated,
+        Self::InviteCodeDeleted,
+    ];
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Login => "login",
+            Self::LoginFailed => "login_failed",
+            Self::PasswordChanged => "password_changed",
+            Self::AccountDeleted => "account_deleted",
+            Self::UserDisabled => "admin_user_disabled",
+            Self::UserEnabled => "admin_user_enabled",
+            Self::UserDeleted => "admin_user_deleted",
+            Self::BannedTermAdded => "admin_banned_term_added",
+            Self::BannedTermDeleted => "admin_banned_term_deleted",
+            Self::FlagReviewed => "admin_flag_reviewed",
+            Self::TagBulkAttached => "tag_bulk_attached",
+            Self::TagBulkDetached => "tag_bulk_detached",
+            Self::InviteCodeCreated => "admin_invite_code_created",
+            Self::InviteCodeDeleted => "admin_invite_code_deleted",
+        }
+    }
+}
+
+impl FromStr for AuditAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|action| action.as_str() == s)
+            .ok_or_else(|| format!("Unknown audit action: {s}"))
+    }
+}
+
+// Entry to record, `user_id` is the account concerned and `actor_id` the user who acted
+#[derive(Clone, Debug)]
+pub struct NewAuditEntry<'a> {
+    pub user_id: Option<i64>,
+    pub actor_id: Option<i64>,
+    pub action: AuditAction,
+    pub device: &'a SessionDevice,
+    pub details: Option<String>,
+}
+
+// Filters of the audit log listed to the administrators
+#[derive(Deserialize, IntoParams, Clone, Debug, Default)]
+#[into_params(parameter_in = Query)]
+pub struct AuditFilter {
+    /// Only return the entries concerning this user
+    pub user_id: Option<i64>,
+    /// Only return the entries of this action, such as `login_failed`
+    pub action: Option<String>,
+}
+
+// Audit entry row as stored in database, with the username of the actor
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct AuditEntryRow {
+    pub id: i32,
+    pub user_id: Option<i32>,
+    pub actor_id: Option<i32>,
+    pub actor: Option<String>,
+    pub action: String,
+    pub ip_address: String,
+    pub user_agent: Option<String>,
+    pub details: Option<String>,
+    pub created_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct AuditEntryResponse {
+    pub id: i64,
+    // Account concerned by the action
+    pub user_id: Option<i64>,
+    // User who acted, `null` for the failed logins of unknown users
+    pub actor_id: Option<i64>,
+    // Username of the actor, `null` once the account is deleted
+    pub actor: Option<String>,
+    pub action: String,
+    pub ip_address: String,
+    pub user_agent: Option<String>,
+    pub details: Option<String>,
+    #[serde(with = "rfc3339_option")]
+    pub created_at: Option<OffsetDateTime>,
+}
+
+impl From<AuditEntryRow> for AuditEntryResponse {
+    fn from(row: AuditEntryRow) -> Self {
+        Self {
+            id: i64::from(row.id),
+            user_id: row.user_id.map(i64::from),
+            actor_id: row.actor_id.map(i64::from),
+            actor: row.actor,
+            action: row.action,
+            ip_address: row.ip_address,
+            user_agent: row.user_agent,
+            details: row.details,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct AuditPageResponse {
+    pub entries: Vec<AuditEntryResponse>,
+    // Cursor of the next page, `null` on the last page
+    pub next_cursor: Option<String>,
+}
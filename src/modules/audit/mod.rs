@@ -0,0 +1,9 @@
+//! # `Audit` Mod
+//! Audit imports for the log of the sensitive actions on the accounts
+
+pub mod interfaces;
+pub mod repository;
+pub mod routes;
+pub mod service;
+
+pub use routes::audit_routes;
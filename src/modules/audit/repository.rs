@@ -0,0 +1,71 @@
+//! # `Audit` Repository
+//! This module defines the audit repository for the audit log.
+
+use sqlx::{Error, Pool, Postgres};
+
+use crate::modules::{
+    audit::interfaces::{AuditEntryRow, NewAuditEntry},
+    common::to_db_id,
+};
+use crate::telemetry::observe_query;
+
+pub struct AuditRepository {
+    pool: Pool<Postgres>,
+}
+
+impl AuditRepository {
+    // Constructor
+    pub const fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Record an entry
+    pub async fn record(&self, entry: &NewAuditEntry<'_>) -> Result<(), Error> {
+        observe_query("audit.record", async move {
+            sqlx::query(
+                "INSERT INTO audit_log (user_id, actor_id, action, ip_address, user_agent, details)
+                 VALUES ($1, $2, $3, $4, $5, $6)",
+            )
+            .bind(entry.user_id.map(to_db_id).transpose()?)
+            .bind(entry.actor_id.map(to_db_id).transpose()?)
+            .bind(entry.action.as_str())
+            .bind(&entry.device.ip_address)
+            .bind(&entry.device.user_agent)
+            .bind(&entry.details)
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    // List a page of the entries, of a single user or action when set, the newest first
+    pub async fn list_entries(
+        &self,
+        user_id: Option<i64>,
+        action: Option<&str>,
+        before_id: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<AuditEntryRow>, Error> {
+        observe_query("audit.list_entries", async move {
+            sqlx::query_as::<_, AuditEntryRow>(
+                "SELECT a.id, a.user_id, a.actor_id, u.username AS actor, a.action, a.ip_address,
+                    a.user_agent, a.details, a.created_at
+                 FROM audit_log a
+                 LEFT JOIN users u ON u.id = a.actor_id
+                 WHERE ($1::INTEGER IS NULL OR a.user_id = $1)
+                   AND ($2::TEXT IS NULL OR a.action = $2)
+                   AND ($3::INTEGER IS NULL OR a.id < $3)
+                 ORDER BY a.id DESC
+                 LIMIT $4",
+            )
+            .bind(user_id.map(to_db_id).transpose()?)
+            .bind(action)
+            .bind(before_id.map(to_db_id).transpose()?)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+}
@@ -0,0 +1,103 @@
+//! #`Audit` Routes
+//! This module defines the HTTP routes for the audit log of the sensitive actions.
+
+use axum::extract::Query;
+use axum::routing::get;
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Router};
+
+use crate::auth::{AdminClaims, Claims};
+use crate::modules::audit::interfaces::{AuditFilter, AuditPageResponse};
+use crate::modules::audit::repository::AuditRepository;
+use crate::modules::audit::service::AuditService;
+use crate::modules::common::{respond, ErrorResponse, Pagination};
+use crate::AppState;
+
+// Creates and returns the audit routes
+pub fn audit_routes() -> Router<AppState> {
+    Router::new()
+        .route("/user/audit", get(list_user_audit_route))
+        .route("/admin/audit", get(list_admin_audit_route))
+}
+
+fn audit_service(app_state: &AppState) -> AuditService {
+    AuditService::new(AuditRepository::new(app_state.db_pool.clone()))
+}
+
+// List User Audit Route
+#[utoipa::path(
+    get,
+    path = "/user/audit",
+    tag = "Audit",
+    params(Pagination),
+    responses(
+        (status = 200, description = "Sensitive actions on the account of the user, the newest first", body = AuditPageResponse),
+        (status = 400, description = "Invalid cursor", body = ErrorResponse),
+        (status = 500, description = "Failed to list audit entries", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn list_user_audit_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Query(pagination): Query<Pagination>,
+) -> impl IntoResponse {
+    let before_id = match pagination.decode_cursor::<i64>() {
+        Ok(before_id) => before_id,
+        Err(error) => return error.into_response(),
+    };
+
+    respond(
+        StatusCode::OK,
+        audit_service(&app_state)
+            .list_user_entries(claims.user_id, before_id, pagination.limit())
+            .await,
+    )
+}
+
+// List Admin Audit Route
+#[utoipa::path(
+    get,
+    path = "/admin/audit",
+    tag = "Audit",
+    params(AuditFilter, Pagination),
+    responses(
+        (status = 200, description = "Sensitive actions on every account, the newest first", body = AuditPageResponse),
+        (status = 400, description = "Invalid cursor or action", body = ErrorResponse),
+        (status = 403, description = "Not an administrator")
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn list_admin_audit_route(
+    State(app_state): State<AppState>,
+    _admin: AdminClaims,
+    Query(filter): Query<AuditFilter>,
+    Query(pagination): Query<Pagination>,
+) -> impl IntoResponse {
+    let before_id = match pagination.decode_cursor::<i64>() {
+        Ok(before_id) => before_id,
+        Err(error) => return error.into_response(),
+    };
+
+    respond(
+        StatusCode::OK,
+        audit_service(&app_state)
+            .list_all_entries(&filter, before_id, pagination.limit())
+            .await,
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_routes_creation() {
+        let _routes = audit_routes();
+        assert!(true);
+    }
+}
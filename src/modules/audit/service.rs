@@ -0,0 +1,121 @@
+//! # `Audit` Service
+//!
+//! This module contains the bussiness logic for the audit log: the logins, failed logins,
+//! password changes, account deletions and administrator actions, with the address and
+//! user agent they came from. Users see the entries of their account, administrators
+//! see every entry.
+
+use crate::modules::{
+    audit::{
+        interfaces::{
+            AuditAction, AuditEntryResponse, AuditFilter, AuditPageResponse, NewAuditEntry,
+        },
+        repository::AuditRepository,
+    },
+    common::{encode_cursor, AppError},
+};
+
+pub struct AuditService {
+    audit_repository: AuditRepository,
+}
+
+impl AuditService {
+    pub const fn new(audit_repository: AuditRepository) -> Self {
+        Self { audit_repository }
+    }
+
+    // Record an entry, a failure is logged without failing the audited action
+    pub async fn record(&self, entry: NewAuditEntry<'_>) {
+        if let Err(e) = self.audit_repository.record(&entry).await {
+            tracing::warn!(
+                "Error recording audit entry {}: {}",
+                entry.action.as_str(),
+                e
+            );
+        }
+    }
+
+    // List a page of the entries, `before_id` comes from the page cursor
+    async fn list_entries(
+        &self,
+        user_id: Option<i64>,
+        action: Option<AuditAction>,
+        before_id: Option<i64>,
+        limit: i64,
+    ) -> Result<AuditPageResponse, AppError> {
+        // One extra row tells whether there is a next page
+        match self
+            .audit_repository
+            .list_entries(
+                user_id,
+                action.map(AuditAction::as_str),
+                before_id,
+                limit + 1,
+            )
+            .await
+        {
+            Ok(mut entries) => {
+                let page_size = usize::try_from(limit).unwrap_or(1);
+                let next_cursor = if entries.len() > page_size {
+                    entries.truncate(page_size);
+                    entries
+                        .last()
+                        .and_then(|entry| encode_cursor(&i64::from(entry.id)))
+                } else {
+                    None
+                };
+
+                Ok(AuditPageResponse {
+                    entries: entries.into_iter().map(AuditEntryResponse::from).collect(),
+                    next_cursor,
+                })
+            }
+            Err(e) => {
+                tracing::warn!("Error listing audit entries: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Entries concerning the account of the user
+    pub async fn list_user_entries(
+        &self,
+        user_id: i64,
+        before_id: Option<i64>,
+        limit: i64,
+    ) -> Result<AuditPageResponse, AppError> {
+        self.list_entries(Some(user_id), None, before_id, limit)
+            .await
+    }
+
+    // Entries of every account, for the administrators
+    pub async fn list_all_entries(
+        &self,
+        filter: &AuditFilter,
+        before_id: Option<i64>,
+        limit: i64,
+    ) -> Result<AuditPageResponse, AppError> {
+        let action = filter
+            .action
+            .as_deref()
+            .map(|action| action.trim().parse::<AuditAction>())
+            .transpose()
+            .map_err(AppError::Validation)?;
+
+        self.list_entries(filter.user_id, action, before_id, limit)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_audit_action() {
+        for action in AuditAction::ALL {
+            assert_eq!(action.as_str().parse(), Ok(action));
+        }
+        assert!("logout".parse::<AuditAction>().is_err());
+    }
+}
@@ -0,0 +1,29 @@
+//! # `Badge` Interfaces
+//! This module defines the data structures from Badges module
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+// Counters row as stored in database
+#[derive(sqlx::FromRow, Clone, Copy, Debug, Default)]
+pub struct BadgesRow {
+    pub unread_notifications: i32,
+    pub assigned_todos: i32,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BadgesResponse {
+    // Reminders sent and answers to the assignments of the user since last read
+    pub unread_notifications: i64,
+    // Todos delegated to the user waiting for an answer
+    pub assigned_todos: i64,
+}
+
+impl From<BadgesRow> for BadgesResponse {
+    fn from(row: BadgesRow) -> Self {
+        Self {
+            unread_notifications: i64::from(row.unread_notifications),
+            assigned_todos: i64::from(row.assigned_todos),
+        }
+    }
+}
@@ -0,0 +1,9 @@
+//! # `Badge` Mod
+//! Badge imports for the unread counters clients show without fetching the collections
+
+pub mod interfaces;
+pub mod repository;
+pub mod routes;
+pub mod service;
+
+pub use routes::badge_routes;
@@ -0,0 +1,47 @@
+//! # `Badge` Repository
+//! This module defines the badge repository for the badge endpoints. The counters are
+//! written by the database triggers of the notified and assigned rows.
+
+use sqlx::{Error, Pool, Postgres};
+
+use crate::modules::{badge::interfaces::BadgesRow, common::to_db_id};
+use crate::telemetry::observe_query;
+
+pub struct BadgeRepository {
+    pool: Pool<Postgres>,
+}
+
+impl BadgeRepository {
+    // Constructor
+    pub const fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Counters of the user, `None` before anything was counted for them
+    pub async fn fetch_badges(&self, user_id: i64) -> Result<Option<BadgesRow>, Error> {
+        observe_query("badge.fetch_badges", async move {
+            sqlx::query_as::<_, BadgesRow>(
+                "SELECT unread_notifications, assigned_todos FROM user_badges WHERE user_id = $1",
+            )
+            .bind(to_db_id(user_id)?)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Reset the unread notifications of the user, returns the counters once reset
+    pub async fn mark_notifications_read(&self, user_id: i64) -> Result<Option<BadgesRow>, Error> {
+        observe_query("badge.mark_notifications_read", async move {
+            sqlx::query_as::<_, BadgesRow>(
+                "UPDATE user_badges SET unread_notifications = 0, updated_at = NOW()
+                 WHERE user_id = $1
+                 RETURNING unread_notifications, assigned_todos",
+            )
+            .bind(to_db_id(user_id)?)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+}
@@ -0,0 +1,34 @@
+//! # `Badge` Service
+//!
+//! This module contains the bussiness logic for the unread counters of the users.
+
+use crate::modules::{
+    badge::{interfaces::BadgesResponse, repository::BadgeRepository},
+    common::AppError,
+};
+
+pub struct BadgeService {
+    badge_repository: BadgeRepository,
+}
+
+impl BadgeService {
+    // Constructor
+    pub const fn new(badge_repository: BadgeRepository) -> Self {
+        Self { badge_repository }
+    }
+
+    // Counters of the user, zero before anything was counted
+    pub async fn fetch_badges(&self, user_id: i64) -> Result<BadgesResponse, AppError> {
+        let row = self.badge_repository.fetch_badges(user_id).await?;
+        Ok(row.unwrap_or_default().into())
+    }
+
+    // Mark the notifications of the user read
+    pub async fn mark_notifications_read(&self, user_id: i64) -> Result<BadgesResponse, AppError> {
+        let row = self
+            .badge_repository
+            .mark_notifications_read(user_id)
+            .await?;
+        Ok(row.unwrap_or_default().into())
+    }
+}
@@ -0,0 +1,165 @@
+//! # `Changes` Interfaces
+//! This module defines the data structures of the changes feed
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::modules::{
+    common::{encode_cursor, AppError, Pagination},
+    list::interfaces::ListResponse,
+    tag::interfaces::TagResponse,
+    todo::interfaces::TodoResponse,
+};
+use crate::utils::dates::rfc3339;
+
+#[derive(Deserialize, IntoParams, Clone, Debug, Default)]
+#[into_params(parameter_in = Query)]
+pub struct ChangesQuery {
+    /// Cursor returned by the previous poll. Omitted, only the current cursor is returned.
+    pub since: Option<String>,
+}
+
+// Position in the change sequence, the cursor of the changes feed and of the offline
+// sync. Cursors built from any other position, like a page of todos, don't decode.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ChangeCursor {
+    // Sequence of the last change event returned, 0 before the first event
+    pub change_seq: i64,
+}
+
+impl ChangeCursor {
+    // Decode the opaque cursor sent by a client, `None` without cursor
+    pub fn decode(cursor: Option<String>) -> Result<Option<Self>, AppError> {
+        Pagination {
+            limit: None,
+            cursor,
+        }
+        .decode_cursor()
+    }
+
+    pub fn encode(self) -> String {
+        encode_cursor(&self).unwrap_or_default()
+    }
+}
+
+// Change event row as stored in database
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct ChangeEventRow {
+    pub seq: i64,
+    pub entity: String,
+    pub entity_id: i32,
+    pub action: String,
+}
+
+// Tombstone of a hard-deleted row as stored in database
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct TombstoneRow {
+    pub entity: String,
+    pub entity_id: i32,
+    pub deleted_at: OffsetDateTime,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct TombstoneResponse {
+    // One of `todo`, `list` or `tag`
+    pub entity: String,
+    pub id: i64,
+    #[serde(with = "rfc3339")]
+    pub deleted_at: OffsetDateTime,
+}
+
+impl From<TombstoneRow> for TombstoneResponse {
+    fn from(row: TombstoneRow) -> Self {
+        Self {
+            entity: row.entity,
+            id: i64::from(row.entity_id),
+            deleted_at: row.deleted_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, Default)]
+pub struct ChangedTodos {
+    pub created: Vec<TodoResponse>,
+    pub updated: Vec<TodoResponse>,
+    // Ids of the todos deleted or moved to the trash
+    pub deleted: Vec<i64>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, Default)]
+pub struct ChangedLists {
+    pub created: Vec<ListResponse>,
+    pub updated: Vec<ListResponse>,
+    // Ids of the lists deleted or transferred to another user
+    pub deleted: Vec<i64>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, Default)]
+pub struct ChangedTags {
+    pub created: Vec<TagResponse>,
+    pub updated: Vec<TagResponse>,
+    pub deleted: Vec<i64>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ChangesResponse {
+    // Cursor to send as `since` on the next poll
+    pub cursor: String,
+    // More changes are waiting, poll again with the new cursor
+    pub has_more: bool,
+    pub todos: ChangedTodos,
+    pub lists: ChangedLists,
+    pub tags: ChangedTags,
+    // Rows permanently deleted since the cursor, their ids are also listed as deleted
+    pub tombstones: Vec<TombstoneResponse>,
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changes_response_serialization() {
+        let response = ChangesResponse {
+            cursor: "31".to_string(),
+            has_more: false,
+            todos: ChangedTodos::default(),
+            lists: ChangedLists::default(),
+            tags: ChangedTags {
+                deleted: vec![4],
+                ..ChangedTags::default()
+            },
+            tombstones: vec![TombstoneResponse::from(TombstoneRow {
+                entity: "tag".to_string(),
+                entity_id: 4,
+                deleted_at: time::macros::datetime!(2025-03-10 12:00 UTC),
+            })],
+        };
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["tags"]["deleted"][0], 4);
+        assert_eq!(json["tombstones"][0]["entity"], "tag");
+        assert_eq!(json["tombstones"][0]["id"], 4);
+        assert!(json["todos"]["created"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_change_cursor_roundtrip() {
+        let cursor = ChangeCursor { change_seq: 31 };
+        assert_eq!(
+            ChangeCursor::decode(Some(cursor.encode())).unwrap(),
+            Some(cursor)
+        );
+        assert_eq!(ChangeCursor::decode(None).unwrap(), None);
+    }
+
+    #[test]
+    fn test_foreign_cursors_are_rejected() {
+        // A bare sequence number, and the keyset cursor of a page of todos
+        assert!(ChangeCursor::decode(encode_cursor(&31)).is_err());
+        assert!(ChangeCursor::decode(encode_cursor(&(7, "a"))).is_err());
+        assert!(ChangeCursor::decode(encode_cursor(&serde_json::json!({"seq": 31}))).is_err());
+    }
+}
@@ -0,0 +1,9 @@
+//! # `Changes` Mod
+//! Changes imports for the changes feed module
+
+pub mod interfaces;
+pub mod repository;
+pub mod routes;
+pub mod service;
+
+pub use routes::changes_routes;
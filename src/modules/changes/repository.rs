@@ -0,0 +1,217 @@
+//! # `Changes` Repository
+//! This module defines the changes repository, reading the change events and changed rows.
+
+use sqlx::{Error, Pool, Postgres};
+use time::OffsetDateTime;
+
+use crate::modules::{
+    changes::interfaces::{ChangeEventRow, TombstoneRow},
+    common::to_db_id,
+    list::{interfaces::ListRow, repository::LIST_COLUMNS},
+    tag::interfaces::TagRow,
+    todo::{interfaces::TodoRow, repository::TODO_COLUMNS},
+};
+use crate::telemetry::observe_query;
+
+pub struct ChangesRepository {
+    pool: Pool<Postgres>,
+}
+
+impl ChangesRepository {
+    // Constructor
+    pub const fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Sequence of the last change event of the user in the workspace, 0 without events
+    pub async fn latest_seq(&self, user_id: i64, workspace_id: i64) -> Result<i64, Error> {
+        observe_query("changes.latest_seq", async move {
+            sqlx::query_scalar::<_, i64>(
+                "SELECT COALESCE(MAX(seq), 0) FROM change_events
+                 WHERE user_id = $1 AND (workspace_id = $2 OR workspace_id IS NULL)",
+            )
+            .bind(to_db_id(user_id)?)
+            .bind(to_db_id(workspace_id)?)
+            .fetch_one(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Server time of the event at `seq` of the user in the workspace, `None` once purged
+    pub async fn event_time(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        seq: i64,
+    ) -> Result<Option<OffsetDateTime>, Error> {
+        observe_query("changes.event_time", async move {
+            sqlx::query_scalar::<_, Option<OffsetDateTime>>(
+                "SELECT created_at FROM change_events
+                 WHERE seq = $1 AND user_id = $2
+                    AND (workspace_id = $3 OR workspace_id IS NULL)",
+            )
+            .bind(seq)
+            .bind(to_db_id(user_id)?)
+            .bind(to_db_id(workspace_id)?)
+            .fetch_optional(&self.pool)
+            .await
+            .map(Option::flatten)
+        })
+        .await
+    }
+
+    // List the change events of the user in the workspace after `after_seq`, in order.
+    // Tag events have no workspace and are listed in every one.
+    pub async fn list_events(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        after_seq: i64,
+        limit: i64,
+    ) -> Result<Vec<ChangeEventRow>, Error> {
+        observe_query("changes.list_events", async move {
+            sqlx::query_as::<_, ChangeEventRow>(
+                "SELECT seq, entity, entity_id, action FROM change_events
+                 WHERE user_id = $1 AND (workspace_id = $2 OR workspace_id IS NULL)
+                    AND seq > $3
+                 ORDER BY seq ASC
+                 LIMIT $4",
+            )
+            .bind(to_db_id(user_id)?)
+            .bind(to_db_id(workspace_id)?)
+            .bind(after_seq)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // List the tombstones of the user in the workspace recorded in `(after_seq, until_seq]`
+    pub async fn list_tombstones(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        after_seq: i64,
+        until_seq: i64,
+    ) -> Result<Vec<TombstoneRow>, Error> {
+        observe_query("changes.list_tombstones", async move {
+            sqlx::query_as::<_, TombstoneRow>(
+                "SELECT entity, entity_id, deleted_at FROM tombstones
+                 WHERE user_id = $1 AND (workspace_id = $2 OR workspace_id IS NULL)
+                    AND seq > $3 AND seq <= $4
+                 ORDER BY seq ASC",
+            )
+            .bind(to_db_id(user_id)?)
+            .bind(to_db_id(workspace_id)?)
+            .bind(after_seq)
+            .bind(until_seq)
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Delete the tombstones and change events older than `before`, returns how many
+    // tombstones were deleted
+    pub async fn purge_tombstones(&self, before: OffsetDateTime) -> Result<u64, Error> {
+        observe_query("changes.purge_tombstones", async move {
+            let mut tx = self.pool.begin().await?;
+
+            let purged = sqlx::query("DELETE FROM tombstones WHERE deleted_at < $1")
+                .bind(before)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query("DELETE FROM change_events WHERE created_at < $1")
+                .bind(before)
+                .execute(&mut *tx)
+                .await?;
+
+            tx.commit().await?;
+            Ok(purged.rows_affected())
+        })
+        .await
+    }
+
+    // Fetch the todos of the user in the workspace among `ids`, trashed ones excluded
+    pub async fn fetch_todos(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        ids: &[i32],
+    ) -> Result<Vec<TodoRow>, Error> {
+        observe_query("changes.fetch_todos", async move {
+            let query = format!(
+                "SELECT {TODO_COLUMNS} FROM todos
+                 WHERE user_id = $1 AND workspace_id = $2 AND id = ANY($3)
+                    AND deleted_at IS NULL
+                 ORDER BY id"
+            );
+
+            sqlx::query_as::<_, TodoRow>(&query)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .bind(ids)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Fetch the lists of the user in the workspace among `ids`
+    pub async fn fetch_lists(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        ids: &[i32],
+    ) -> Result<Vec<ListRow>, Error> {
+        observe_query("changes.fetch_lists", async move {
+            let query = format!(
+                "SELECT {LIST_COLUMNS} FROM lists
+                 WHERE user_id = $1 AND workspace_id = $2 AND id = ANY($3)
+                 ORDER BY id"
+            );
+
+            sqlx::query_as::<_, ListRow>(&query)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .bind(ids)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Fetch the tags of the user among `ids`, tags are shared by the workspaces
+    pub async fn fetch_tags(&self, user_id: i64, ids: &[i32]) -> Result<Vec<TagRow>, Error> {
+        observe_query("changes.fetch_tags", async move {
+            sqlx::query_as::<_, TagRow>(
+                "SELECT id, name, created_at FROM tags
+                 WHERE user_id = $1 AND id = ANY($2)
+                 ORDER BY id",
+            )
+            .bind(to_db_id(user_id)?)
+            .bind(ids)
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_columns_are_shared() {
+        assert!(LIST_COLUMNS.contains("archived"));
+        assert!(TODO_COLUMNS.contains("deleted_at"));
+    }
+
+    #[test]
+    fn test_invalid_id_is_rejected() {
+        assert!(to_db_id(i64::MAX).is_err());
+    }
+}
@@ -0,0 +1,58 @@
+//! #`Changes` Routes
+//! This module defines the HTTP route of the changes feed.
+
+use axum::extract::Query;
+use axum::routing::get;
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Router};
+
+use crate::modules::changes::interfaces::{ChangesQuery, ChangesResponse};
+use crate::modules::changes::repository::ChangesRepository;
+use crate::modules::changes::service::ChangesService;
+use crate::modules::common::{respond, ErrorResponse};
+use crate::modules::workspace::context::WorkspaceContext;
+use crate::AppState;
+
+// Creates and returns the changes routes
+pub fn changes_routes() -> Router<AppState> {
+    Router::new().route("/changes", get(list_changes_route))
+}
+
+// List Changes Route
+#[utoipa::path(
+    get,
+    path = "/changes",
+    tag = "Sync",
+    params(ChangesQuery),
+    responses(
+        (status = 200, description = "Todos and lists of the workspace, and tags, changed since the cursor", body = ChangesResponse),
+        (status = 400, description = "Invalid or expired cursor", body = ErrorResponse),
+        (status = 500, description = "Failed to list changes", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn list_changes_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Query(query): Query<ChangesQuery>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        ChangesService::new(ChangesRepository::new(app_state.db_pool.clone()))
+            .list_changes(workspace.user_id, workspace.workspace_id, query)
+            .await,
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changes_routes_creation() {
+        let _routes = changes_routes();
+        assert!(true);
+    }
+}
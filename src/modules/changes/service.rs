@@ -0,0 +1,282 @@
+//! # `Changes` Service
+//!
+//! This module contains the changes feed. The change events after the cursor are collapsed
+//! per row, so a row created then updated in the same window is only reported as created,
+//! and the current state of the remaining rows is read back. Permanently deleted rows
+//! come with their tombstone. The feed covers the todos and lists of the active
+//! workspace, and the tags, which all the workspaces of the user share.
+//!
+//! Cursors are positions in the change sequence, the offline sync shares them. Events and
+//! tombstones are purged after their retention window, older cursors expire.
+
+use std::collections::BTreeMap;
+
+use time::OffsetDateTime;
+
+use crate::modules::{
+    changes::{
+        interfaces::{
+            ChangeCursor, ChangeEventRow, ChangedLists, ChangedTags, ChangedTodos, ChangesQuery,
+            ChangesResponse, TombstoneResponse,
+        },
+        repository::ChangesRepository,
+    },
+    common::AppError,
+};
+
+// Events read per poll, clients poll again while `has_more` is set
+const CHANGES_LIMIT: i64 = 500;
+
+// Ids of the rows of one entity changed in a window of events
+#[derive(Debug, Default, PartialEq, Eq)]
+struct EntityDelta {
+    created: Vec<i32>,
+    updated: Vec<i32>,
+    deleted: Vec<i32>,
+}
+
+impl EntityDelta {
+    // Rows whose current state is returned
+    fn changed_ids(&self) -> Vec<i32> {
+        self.created.iter().chain(&self.updated).copied().collect()
+    }
+
+    fn deleted_ids(&self) -> Vec<i64> {
+        self.deleted.iter().copied().map(i64::from).collect()
+    }
+}
+
+// Collapse the events of one entity by row, the last deletion or creation wins
+fn collapse(events: &[ChangeEventRow], entity: &str) -> EntityDelta {
+    let mut actions: BTreeMap<i32, (&str, &str)> = BTreeMap::new();
+    for event in events.iter().filter(|event| event.entity == entity) {
+        let action = event.action.as_str();
+        actions
+            .entry(event.entity_id)
+            .and_modify(|(_, last)| *last = action)
+            .or_insert((action, action));
+    }
+
+    let mut delta = EntityDelta::default();
+    for (id, actions) in actions {
+        match actions {
+            (_, "deleted") => delta.deleted.push(id),
+            ("created", _) | (_, "created") => delta.created.push(id),
+            _ => delta.updated.push(id),
+        }
+    }
+    delta
+}
+
+// Split fetched rows into created and updated ones
+fn split_created<R, T: From<R>>(
+    rows: Vec<R>,
+    id: impl Fn(&R) -> i32,
+    delta: &EntityDelta,
+) -> (Vec<T>, Vec<T>) {
+    let (created, updated): (Vec<R>, Vec<R>) = rows
+        .into_iter()
+        .partition(|row| delta.created.contains(&id(row)));
+    (
+        created.into_iter().map(T::from).collect(),
+        updated.into_iter().map(T::from).collect(),
+    )
+}
+
+fn failed(error: &sqlx::Error) -> AppError {
+    tracing::warn!("Error listing changes: {}", error);
+    AppError::Internal("Failed to list changes".to_string())
+}
+
+pub struct ChangesService {
+    changes_repository: ChangesRepository,
+}
+
+impl ChangesService {
+    pub const fn new(changes_repository: ChangesRepository) -> Self {
+        Self { changes_repository }
+    }
+
+    // Server time of the event a cursor points at, the start of the sequence has none.
+    // Cursors of purged events, or of another user or workspace, are refused.
+    pub async fn cursor_time(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        cursor: ChangeCursor,
+    ) -> Result<Option<OffsetDateTime>, AppError> {
+        if cursor.change_seq == 0 {
+            return Ok(None);
+        }
+
+        self.changes_repository
+            .event_time(user_id, workspace_id, cursor.change_seq)
+            .await
+            .map_err(|e| failed(&e))?
+            .map(Some)
+            .ok_or_else(|| {
+                AppError::Validation(
+                    "Cursor expired, download everything again and poll without a cursor"
+                        .to_string(),
+                )
+            })
+    }
+
+    // List the todos and lists of the workspace, and the tags, changed since the cursor
+    pub async fn list_changes(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        query: ChangesQuery,
+    ) -> Result<ChangesResponse, AppError> {
+        let Some(cursor) = ChangeCursor::decode(query.since)? else {
+            // Clients start polling from the current position after a full download
+            let latest_seq = self
+                .changes_repository
+                .latest_seq(user_id, workspace_id)
+                .await
+                .map_err(|e| failed(&e))?;
+            return Ok(ChangesResponse {
+                cursor: ChangeCursor {
+                    change_seq: latest_seq,
+                }
+                .encode(),
+                has_more: false,
+                todos: ChangedTodos::default(),
+                lists: ChangedLists::default(),
+                tags: ChangedTags::default(),
+                tombstones: Vec::new(),
+            });
+        };
+
+        // Events of a cursor past the retention window are purged, changes may be missing
+        self.cursor_time(user_id, workspace_id, cursor).await?;
+        let after_seq = cursor.change_seq;
+
+        let mut events = self
+            .changes_repository
+            .list_events(user_id, workspace_id, after_seq, CHANGES_LIMIT + 1)
+            .await
+            .map_err(|e| failed(&e))?;
+
+        // One extra event tells whether more changes are waiting
+        let page_size = usize::try_from(CHANGES_LIMIT).unwrap_or(1);
+        let has_more = events.len() > page_size;
+        events.truncate(page_size);
+        let last_seq = events.last().map_or(after_seq, |event| event.seq);
+
+        let todos = collapse(&events, "todo");
+        let lists = collapse(&events, "list");
+        let tags = collapse(&events, "tag");
+
+        let todo_rows = match todos.changed_ids() {
+            ids if ids.is_empty() => Vec::new(),
+            ids => self
+                .changes_repository
+                .fetch_todos(user_id, workspace_id, &ids)
+                .await
+                .map_err(|e| failed(&e))?,
+        };
+        let list_rows = match lists.changed_ids() {
+            ids if ids.is_empty() => Vec::new(),
+            ids => self
+                .changes_repository
+                .fetch_lists(user_id, workspace_id, &ids)
+                .await
+                .map_err(|e| failed(&e))?,
+        };
+        let tag_rows = match tags.changed_ids() {
+            ids if ids.is_empty() => Vec::new(),
+            ids => self
+                .changes_repository
+                .fetch_tags(user_id, &ids)
+                .await
+                .map_err(|e| failed(&e))?,
+        };
+
+        let (created_todos, updated_todos) = split_created(todo_rows, |row| row.id, &todos);
+        let (created_lists, updated_lists) = split_created(list_rows, |row| row.id, &lists);
+        let (created_tags, updated_tags) = split_created(tag_rows, |row| row.id, &tags);
+        let tombstones = self
+            .changes_repository
+            .list_tombstones(user_id, workspace_id, after_seq, last_seq)
+            .await
+            .map_err(|e| failed(&e))?;
+
+        Ok(ChangesResponse {
+            cursor: ChangeCursor {
+                change_seq: last_seq,
+            }
+            .encode(),
+            has_more,
+            todos: ChangedTodos {
+                created: created_todos,
+                updated: updated_todos,
+                deleted: todos.deleted_ids(),
+            },
+            lists: ChangedLists {
+                created: created_lists,
+                updated: updated_lists,
+                deleted: lists.deleted_ids(),
+            },
+            tags: ChangedTags {
+                created: created_tags,
+                updated: updated_tags,
+                deleted: tags.deleted_ids(),
+            },
+            tombstones: tombstones
+                .into_iter()
+                .map(TombstoneResponse::from)
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(seq: i64, entity: &str, entity_id: i32, action: &str) -> ChangeEventRow {
+        ChangeEventRow {
+            seq,
+            entity: entity.to_string(),
+            entity_id,
+            action: action.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_collapse_keeps_first_creation() {
+        let events = vec![
+            event(1, "todo", 3, "created"),
+            event(2, "todo", 3, "updated"),
+            event(3, "todo", 5, "updated"),
+            event(4, "list", 3, "updated"),
+        ];
+        assert_eq!(
+            collapse(&events, "todo"),
+            EntityDelta {
+                created: vec![3],
+                updated: vec![5],
+                deleted: vec![],
+            }
+        );
+        assert_eq!(collapse(&events, "list").updated, vec![3]);
+        assert_eq!(collapse(&events, "tag"), EntityDelta::default());
+    }
+
+    #[test]
+    fn test_collapse_deletion_wins() {
+        let events = vec![
+            event(1, "tag", 2, "created"),
+            event(2, "tag", 2, "deleted"),
+            event(3, "tag", 4, "deleted"),
+            event(4, "tag", 4, "created"),
+        ];
+        let delta = collapse(&events, "tag");
+        assert_eq!(delta.deleted, vec![2]);
+        // Restored after the deletion, e.g. a todo taken out of the trash
+        assert_eq!(delta.created, vec![4]);
+        assert_eq!(delta.changed_ids(), vec![4]);
+    }
+}
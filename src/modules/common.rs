@@ -2,14 +2,48 @@
 //!
 //! This module contains shared types and utilities used across the application.
 
-use axum::{response::IntoResponse, Json};
-use serde::Serialize;
-use utoipa::ToSchema;
+use std::str::FromStr;
+
+use axum::{
+    http::{
+        header::{
+            CACHE_CONTROL, CONTENT_TYPE, ETAG, IF_MATCH, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+            LAST_MODIFIED, RETRY_AFTER,
+        },
+        HeaderMap, HeaderValue, StatusCode,
+    },
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use time::OffsetDateTime;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::database::is_unavailable;
+use crate::utils::dates::{format_http_date, parse_http_date};
+use crate::utils::request_id::current_request_id;
+use crate::utils::validation::{FieldError, ValidationErrors};
+
+/// Page size used when the client doesn't ask for one
+pub const DEFAULT_PAGE_LIMIT: i64 = 50;
+/// Largest page size a client can ask for
+pub const MAX_PAGE_LIMIT: i64 = 100;
 
 /// Standard error response structure
 #[derive(Serialize, ToSchema, Debug)]
 pub struct ErrorResponse {
     pub message: String,
+    /// Machine readable code of the errors clients handle specifically
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// Every invalid field of the request, for the validation errors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors: Option<Vec<FieldError>>,
+    /// Id of the failed request, also in the `X-Request-Id` header, to quote when
+    /// reporting the error
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
 }
 
 impl ErrorResponse {
@@ -17,17 +51,376 @@ impl ErrorResponse {
     pub fn new(message: impl Into<String>) -> Self {
         Self {
             message: message.into(),
+            code: None,
+            errors: None,
+            request_id: current_request_id(),
+        }
+    }
+
+    /// Create a new error response with a machine readable code
+    pub fn with_code(code: &str, message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            code: Some(code.to_string()),
+            errors: None,
+            request_id: current_request_id(),
+        }
+    }
+
+    /// Create the error response of a server failure, coded `internal_error`. The
+    /// message names the failed action, the cause is only logged.
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::with_code(INTERNAL_ERROR, message)
+    }
+}
+
+impl From<ValidationErrors> for ErrorResponse {
+    fn from(errors: ValidationErrors) -> Self {
+        Self {
+            message: errors.to_string(),
+            code: Some(VALIDATION_ERROR.to_string()),
+            errors: Some(errors.into_errors()),
+            request_id: current_request_id(),
         }
     }
 }
 
 impl IntoResponse for ErrorResponse {
-    fn into_response(self) -> axum::response::Response {
+    fn into_response(self) -> Response {
         (axum::http::StatusCode::BAD_REQUEST, Json(self)).into_response()
     }
 }
 
+/// Code of the invalid requests
+pub const VALIDATION_ERROR: &str = "validation_error";
+
+/// Code of the server failures, whose details are only logged
+pub const INTERNAL_ERROR: &str = "internal_error";
+
+/// Seconds clients wait before retrying a request failed with 503
+const RETRY_AFTER_SECONDS: &str = "1";
+
+/// Error of a request, answered with its status code and a stable machine readable
+/// `code`. Database and internal errors are logged, clients only get a generic message.
+#[derive(Debug)]
+pub enum AppError {
+    /// Invalid request data, 400
+    Validation(String),
+    /// Invalid fields of the request, each with its problem, 400
+    InvalidFields(ValidationErrors),
+    /// Missing resource, 404
+    NotFound(String),
+    /// Request conflicting with the current state, like a taken username, 409
+    Conflict(String),
+    /// Fields conflicting with existing data, each with its problem, 409
+    FieldConflict(ValidationErrors),
+    /// Invalid credentials, 401
+    Unauthorized(String),
+    /// Action not allowed to the user, 403
+    Forbidden(String),
+    /// Valid request refused by a rule of the application, like a WIP limit, with the
+    /// code of the rule, 422
+    Rejected(&'static str, String),
+    /// Login refused until the password is reset through the emailed link, 403
+    PasswordResetRequired(String),
+    /// Write of a resource changed since the version in `If-Match`, 412
+    PreconditionFailed(String),
+    /// Write of a versioned resource without `If-Match`, 428
+    PreconditionRequired(String),
+    /// Database failure, 500, or 503 while the database is unreachable or overloaded
+    Database(sqlx::Error),
+    /// Other server failure, 500. The message is only logged.
+    Internal(String),
+}
+
+impl AppError {
+    /// Status code of the response
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::Validation(_) | Self::InvalidFields(_) => StatusCode::BAD_REQUEST,
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::Conflict(_) | Self::FieldConflict(_) => StatusCode::CONFLICT,
+            Self::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            Self::Forbidden(_) | Self::PasswordResetRequired(_) => StatusCode::FORBIDDEN,
+            Self::Rejected(..) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::PreconditionFailed(_) => StatusCode::PRECONDITION_FAILED,
+            Self::PreconditionRequired(_) => StatusCode::PRECONDITION_REQUIRED,
+            Self::Database(e) if is_unavailable(e) => StatusCode::SERVICE_UNAVAILABLE,
+            Self::Database(_) | Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Machine readable code of the response
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Validation(_) | Self::InvalidFields(_) => VALIDATION_ERROR,
+            Self::NotFound(_) => "not_found",
+            Self::Conflict(_) | Self::FieldConflict(_) => "conflict",
+            Self::Unauthorized(_) => "unauthorized",
+            Self::Forbidden(_) => "forbidden",
+            Self::PasswordResetRequired(_) => "password_reset_required",
+            Self::PreconditionFailed(_) => "precondition_failed",
+            Self::PreconditionRequired(_) => "precondition_required",
+            Self::Rejected(code, _) => code,
+            Self::Database(e) if is_unavailable(e) => "service_unavailable",
+            Self::Database(_) | Self::Internal(_) => INTERNAL_ERROR,
+        }
+    }
+
+    /// Body of the response, without the details of the server failures
+    pub fn to_error_response(&self) -> ErrorResponse {
+        let message = match self {
+            Self::InvalidFields(errors) => return ErrorResponse::from(errors.clone()),
+            Self::FieldConflict(errors) => {
+                return ErrorResponse {
+                    code: Some(self.code().to_string()),
+                    ..ErrorResponse::from(errors.clone())
+                }
+            }
+            Self::Validation(message)
+            | Self::NotFound(message)
+            | Self::Conflict(message)
+            | Self::Unauthorized(message)
+            | Self::Forbidden(message)
+            | Self::Rejected(_, message)
+            | Self::PasswordResetRequired(message)
+            | Self::PreconditionFailed(message)
+            | Self::PreconditionRequired(message) => message.as_str(),
+            Self::Database(e) if is_unavailable(e) => {
+                "Service temporarily unavailable, retry later"
+            }
+            Self::Database(_) | Self::Internal(_) => "Internal server error",
+        };
+        ErrorResponse::with_code(self.code(), message)
+    }
+}
+
+impl From<ValidationErrors> for AppError {
+    fn from(errors: ValidationErrors) -> Self {
+        Self::InvalidFields(errors)
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(error: sqlx::Error) -> Self {
+        Self::Database(error)
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        match &self {
+            Self::Database(e) => tracing::error!("Database error: {}", e),
+            Self::Internal(message) => tracing::error!("Internal error: {}", message),
+            _ => {}
+        }
+        let status = self.status();
+        let mut response = (status, Json(self.to_error_response())).into_response();
+        if status == StatusCode::SERVICE_UNAVAILABLE {
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from_static(RETRY_AFTER_SECONDS));
+        }
+        response
+    }
+}
+
+/// Response of a route: the value with the status of the success, or the error with the
+/// status of its kind
+pub fn respond<T: Serialize>(status: StatusCode, result: Result<T, AppError>) -> Response {
+    match result {
+        Ok(value) => (status, Json(value)).into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+/// Strong entity tag of a version of a resource, changed by every write
+pub fn entity_tag(version: impl std::fmt::Display) -> String {
+    format!("\"{version}\"")
+}
+
+/// Response of a route reading or writing a versioned resource, with the `ETag` of the
+/// version it ends at
+pub fn respond_versioned<T: Serialize>(
+    status: StatusCode,
+    result: Result<T, AppError>,
+    version: impl FnOnce(&T) -> String,
+) -> Response {
+    match result {
+        Ok(value) => match HeaderValue::from_str(&entity_tag(version(&value))) {
+            Ok(etag) => (status, [(ETAG, etag)], Json(value)).into_response(),
+            Err(_) => (status, Json(value)).into_response(),
+        },
+        Err(error) => error.into_response(),
+    }
+}
+
+/// Version a write is conditioned on, from the `If-Match` header. Writes without the
+/// header are refused, `*` matches any version and gives `None`. Weak or unknown tags
+/// never match the current version.
+pub fn required_version<T: FromStr>(headers: &HeaderMap) -> Result<Option<T>, AppError> {
+    let Some(if_match) = headers.get(IF_MATCH) else {
+        return Err(AppError::PreconditionRequired(
+            "Send the ETag of the version being changed in If-Match".to_string(),
+        ));
+    };
+    let if_match = if_match.to_str().unwrap_or_default().trim();
+    if if_match == "*" {
+        return Ok(None);
+    }
+    if_match
+        .strip_prefix('"')
+        .and_then(|tag| tag.strip_suffix('"'))
+        .and_then(|version| version.parse().ok())
+        .map(Some)
+        .ok_or_else(|| AppError::PreconditionFailed("Changed since the given version".to_string()))
+}
+
+/// `Cache-Control` of the reads of a user's own data: kept by the browser only and
+/// revalidated on every use, which the conditional requests make cheap
+pub const PRIVATE_CACHE_CONTROL: &str = "private, no-cache";
+
+/// Validators of a resource answered by a read route, compared with the conditional
+/// headers of the request
+#[derive(Debug, Default)]
+pub struct CacheValidators {
+    etag: Option<String>,
+    last_modified: Option<OffsetDateTime>,
+}
+
+impl CacheValidators {
+    /// Validators of a versioned resource, tagged with its version like the writes
+    pub fn version(version: impl std::fmt::Display) -> Self {
+        Self {
+            etag: Some(entity_tag(version)),
+            last_modified: None,
+        }
+    }
+
+    /// Add the time of the last write, sent as `Last-Modified`
+    #[must_use]
+    pub const fn modified_at(mut self, at: Option<OffsetDateTime>) -> Self {
+        self.last_modified = at;
+        self
+    }
+}
+
+/// Response of a route reading a resource, `304 Not Modified` without a body when the
+/// copy of the client named in `If-None-Match` or `If-Modified-Since` is current.
+/// Resources without a version are tagged with a digest of their body.
+pub fn respond_cached<T: Serialize>(
+    request_headers: &HeaderMap,
+    result: Result<T, AppError>,
+    validators: impl FnOnce(&T) -> CacheValidators,
+) -> Response {
+    let value = match result {
+        Ok(value) => value,
+        Err(error) => return error.into_response(),
+    };
+    let validators = validators(&value);
+    let body = match serde_json::to_vec(&value) {
+        Ok(body) => body,
+        Err(error) => {
+            return AppError::Internal(format!("Failed to serialize the response: {error}"))
+                .into_response()
+        }
+    };
+    let etag = validators
+        .etag
+        .unwrap_or_else(|| entity_tag(hex::encode(Sha256::digest(&body))));
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        CACHE_CONTROL,
+        HeaderValue::from_static(PRIVATE_CACHE_CONTROL),
+    );
+    if let Ok(value) = HeaderValue::from_str(&etag) {
+        headers.insert(ETAG, value);
+    }
+    if let Some(value) = validators
+        .last_modified
+        .and_then(|at| format_http_date(at).ok())
+        .and_then(|at| HeaderValue::from_str(&at).ok())
+    {
+        headers.insert(LAST_MODIFIED, value);
+    }
+
+    if is_not_modified(request_headers, &etag, validators.last_modified) {
+        return (StatusCode::NOT_MODIFIED, headers).into_response();
+    }
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    (StatusCode::OK, headers, body).into_response()
+}
+
+// Whether the copy of the client is current. `If-None-Match` wins over
+// `If-Modified-Since` and compares weakly, HTTP dates have whole seconds.
+fn is_not_modified(headers: &HeaderMap, etag: &str, last_modified: Option<OffsetDateTime>) -> bool {
+    if let Some(if_none_match) = headers.get(IF_NONE_MATCH) {
+        return if_none_match
+            .to_str()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag);
+    }
+    let since = headers
+        .get(IF_MODIFIED_SINCE)
+        .and_then(|since| since.to_str().ok())
+        .and_then(parse_http_date);
+    match (last_modified, since) {
+        (Some(modified), Some(since)) => modified.replace_nanosecond(0).is_ok_and(|m| m <= since),
+        _ => false,
+    }
+}
+
+/// Convert an API identifier into the `SERIAL` column type used in the database
+pub fn to_db_id(id: i64) -> Result<i32, sqlx::Error> {
+    i32::try_from(id).map_err(|_| sqlx::Error::Protocol(format!("Invalid ID: {id}")))
+}
+
+/// Transaction of a multi-step flow. The repository methods taking a `&mut PgConnection`
+/// run in it, the service commits once every step succeeded and dropping it rolls back.
+pub type DbTransaction = sqlx::Transaction<'static, sqlx::Postgres>;
+
+/// Keyset pagination parameters shared by list endpoints
+#[derive(Deserialize, IntoParams, Clone, Debug, Default)]
+#[into_params(parameter_in = Query)]
+pub struct Pagination {
+    /// Maximum number of results, capped at 100
+    pub limit: Option<i64>,
+    /// Opaque cursor returned as `next_cursor` by the previous page
+    pub cursor: Option<String>,
+}
+
+impl Pagination {
+    /// Requested page size, clamped to the server limits
+    pub fn limit(&self) -> i64 {
+        self.limit
+            .unwrap_or(DEFAULT_PAGE_LIMIT)
+            .clamp(1, MAX_PAGE_LIMIT)
+    }
+
+    /// Decode the cursor into the keyset position it was built from
+    pub fn decode_cursor<T: DeserializeOwned>(&self) -> Result<Option<T>, AppError> {
+        self.cursor
+            .as_deref()
+            .map(|cursor| {
+                hex::decode(cursor)
+                    .ok()
+                    .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+                    .ok_or_else(|| AppError::Validation("Invalid cursor".to_string()))
+            })
+            .transpose()
+    }
+}
+
+/// Build an opaque cursor from the keyset position of the last returned row
+pub fn encode_cursor<T: Serialize>(position: &T) -> Option<String> {
+    serde_json::to_vec(position).ok().map(hex::encode)
+}
+
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
 
@@ -51,6 +444,253 @@ mod tests {
         assert_eq!(error.message, "Owned string error");
     }
 
+    #[test]
+    fn test_error_response_internal() {
+        let body = serde_json::to_value(ErrorResponse::internal("Failed to list todos")).unwrap();
+        assert_eq!(body["message"], "Failed to list todos");
+        assert_eq!(body["code"], "internal_error");
+        assert!(body.get("errors").is_none());
+    }
+
+    #[test]
+    fn test_app_error_responses() {
+        let error = AppError::NotFound("User not found".to_string());
+        assert_eq!(error.status(), StatusCode::NOT_FOUND);
+        let body = error.to_error_response();
+        assert_eq!(body.message, "User not found");
+        assert_eq!(body.code.as_deref(), Some("not_found"));
+
+        assert_eq!(
+            AppError::Conflict("Username already exists".to_string()).status(),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            AppError::Unauthorized("Invalid".to_string()).code(),
+            "unauthorized"
+        );
+        assert_eq!(
+            AppError::Forbidden("Owner only".to_string()).status(),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            AppError::PasswordResetRequired("Reset".to_string()).code(),
+            "password_reset_required"
+        );
+
+        let error = AppError::Rejected("wip_limit_exceeded", "Column full".to_string());
+        assert_eq!(error.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = error.to_error_response();
+        assert_eq!(body.message, "Column full");
+        assert_eq!(body.code.as_deref(), Some("wip_limit_exceeded"));
+    }
+
+    fn if_match(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(IF_MATCH, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_required_version() {
+        assert_eq!(entity_tag(7), "\"7\"");
+        assert_eq!(
+            required_version::<i64>(&if_match(&entity_tag(7))).unwrap(),
+            Some(7)
+        );
+        assert_eq!(required_version::<i64>(&if_match("*")).unwrap(), None);
+
+        let error = required_version::<i64>(&HeaderMap::new()).unwrap_err();
+        assert_eq!(error.status(), StatusCode::PRECONDITION_REQUIRED);
+        for stale in ["W/\"7\"", "7", "\"seven\""] {
+            let error = required_version::<i64>(&if_match(stale)).unwrap_err();
+            assert_eq!(error.status(), StatusCode::PRECONDITION_FAILED);
+        }
+    }
+
+    #[test]
+    fn test_respond_versioned() {
+        let response = respond_versioned(StatusCode::OK, Ok::<_, AppError>("todo"), |_| {
+            "3".to_string()
+        });
+        assert_eq!(response.headers().get(ETAG).unwrap(), "\"3\"");
+    }
+
+    #[test]
+    fn test_respond_cached() {
+        let modified_at = time::macros::datetime!(2025-03-10 12:30:15.5 UTC);
+        let validators = |_: &&str| CacheValidators::version(3).modified_at(Some(modified_at));
+        let response = respond_cached(&HeaderMap::new(), Ok::<_, AppError>("todo"), validators);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get(ETAG).unwrap(), "\"3\"");
+        assert_eq!(
+            response.headers().get(CACHE_CONTROL).unwrap(),
+            PRIVATE_CACHE_CONTROL
+        );
+        assert_eq!(
+            response.headers().get(LAST_MODIFIED).unwrap(),
+            "Mon, 10 Mar 2025 12:30:15 GMT"
+        );
+
+        let conditional = |name, value| {
+            let mut headers = HeaderMap::new();
+            headers.insert(name, HeaderValue::from_static(value));
+            respond_cached(&headers, Ok::<_, AppError>("todo"), validators).status()
+        };
+        assert_eq!(
+            conditional(IF_NONE_MATCH, "W/\"3\""),
+            StatusCode::NOT_MODIFIED
+        );
+        assert_eq!(
+            conditional(IF_NONE_MATCH, "\"2\", \"3\""),
+            StatusCode::NOT_MODIFIED
+        );
+        assert_eq!(conditional(IF_NONE_MATCH, "\"2\""), StatusCode::OK);
+        assert_eq!(
+            conditional(IF_MODIFIED_SINCE, "Mon, 10 Mar 2025 12:30:15 GMT"),
+            StatusCode::NOT_MODIFIED
+        );
+        assert_eq!(
+            conditional(IF_MODIFIED_SINCE, "Mon, 10 Mar 2025 12:30:14 GMT"),
+            StatusCode::OK
+        );
+
+        // Without a version the body is tagged by its digest
+        let etag = |value| {
+            respond_cached(&HeaderMap::new(), Ok::<_, AppError>(value), |_| {
+                CacheValidators::default()
+            })
+            .headers()
+            .get(ETAG)
+            .cloned()
+        };
+        assert_eq!(etag("todo"), etag("todo"));
+        assert_ne!(etag("todo"), etag("list"));
+    }
+
+    #[test]
+    fn test_respond() {
+        let response = respond(StatusCode::CREATED, Ok::<_, AppError>("created"));
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let response = respond::<()>(
+            StatusCode::CREATED,
+            Err(AppError::NotFound("Todo not found".to_string())),
+        );
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_app_error_invalid_fields() {
+        let mut errors = ValidationErrors::default();
+        errors.missing("username");
+        errors.add("email", "invalid_email", "Email is not valid");
+
+        let error = AppError::from(errors);
+        assert_eq!(error.status(), StatusCode::BAD_REQUEST);
+        let body = serde_json::to_value(error.to_error_response()).unwrap();
+        assert_eq!(body["code"], "validation_error");
+        assert_eq!(body["errors"][0]["field"], "username");
+        assert_eq!(body["errors"][0]["code"], "missing");
+        assert_eq!(body["errors"][1]["field"], "email");
+        assert_eq!(body["errors"][1]["code"], "invalid_email");
+    }
+
+    #[test]
+    fn test_app_error_field_conflict() {
+        let mut errors = ValidationErrors::default();
+        errors.add("username", "taken", "Username already exists");
+
+        let error = AppError::FieldConflict(errors);
+        assert_eq!(error.status(), StatusCode::CONFLICT);
+        let body = serde_json::to_value(error.to_error_response()).unwrap();
+        assert_eq!(body["code"], "conflict");
+        assert_eq!(body["message"], "Username already exists");
+        assert_eq!(body["errors"][0]["field"], "username");
+        assert_eq!(body["errors"][0]["code"], "taken");
+    }
+
+    #[test]
+    fn test_app_error_hides_server_failures() {
+        let error = AppError::from(sqlx::Error::Protocol("relation users is gone".to_string()));
+        assert_eq!(error.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body = error.to_error_response();
+        assert_eq!(body.message, "Internal server error");
+        assert_eq!(body.code.as_deref(), Some("internal_error"));
+
+        let body = AppError::Internal("hashing failed".to_string()).to_error_response();
+        assert_eq!(body.message, "Internal server error");
+        assert_eq!(body.code.as_deref(), Some("internal_error"));
+    }
+
+    #[test]
+    fn test_app_error_database_unavailable() {
+        let error = AppError::from(sqlx::Error::PoolTimedOut);
+        assert_eq!(error.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = error.to_error_response();
+        assert_eq!(body.code.as_deref(), Some("service_unavailable"));
+        assert!(!body.message.contains("pool"));
+
+        let response = AppError::from(sqlx::Error::Io(std::io::Error::from(
+            std::io::ErrorKind::ConnectionReset,
+        )))
+        .into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            response.headers().get(RETRY_AFTER).unwrap(),
+            RETRY_AFTER_SECONDS
+        );
+    }
+
+    #[test]
+    fn test_error_response_with_code() {
+        let error = ErrorResponse::with_code("wip_limit_exceeded", "Column full");
+        assert_eq!(error.code.as_deref(), Some("wip_limit_exceeded"));
+        assert_eq!(ErrorResponse::new("Column full").code, None);
+    }
+
+    #[test]
+    fn test_to_db_id() {
+        assert_eq!(to_db_id(42).ok(), Some(42));
+        assert!(to_db_id(i64::MAX).is_err());
+    }
+
+    #[test]
+    fn test_pagination_limit_is_capped() {
+        let pagination = |limit| Pagination {
+            limit,
+            cursor: None,
+        };
+        assert_eq!(pagination(None).limit(), DEFAULT_PAGE_LIMIT);
+        assert_eq!(pagination(Some(0)).limit(), 1);
+        assert_eq!(pagination(Some(10)).limit(), 10);
+        assert_eq!(pagination(Some(10_000)).limit(), MAX_PAGE_LIMIT);
+    }
+
+    #[test]
+    fn test_cursor_roundtrip() {
+        let pagination = Pagination {
+            limit: None,
+            cursor: encode_cursor(&(7, "a")),
+        };
+        assert_eq!(
+            pagination.decode_cursor::<(i32, String)>().ok().flatten(),
+            Some((7, "a".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_invalid_cursor_is_rejected() {
+        let pagination = Pagination {
+            limit: None,
+            cursor: Some("not a cursor".to_string()),
+        };
+        assert!(pagination.decode_cursor::<i32>().is_err());
+        assert_eq!(
+            Pagination::default().decode_cursor::<i32>().ok(),
+            Some(None)
+        );
+    }
+
     #[test]
     fn test_error_response_into_response() {
         let error = ErrorResponse::new("Test error");
@@ -0,0 +1,17 @@
+//! # `Demo` Interfaces
+//! This module defines the data structures from Demo module
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+use crate::utils::dates::rfc3339;
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct DemoSessionResponse {
+    // Token of the demo user, valid until the user is deleted
+    pub token: String,
+    pub username: String,
+    #[serde(with = "rfc3339")]
+    pub expires_at: OffsetDateTime,
+}
@@ -0,0 +1,10 @@
+//! # `Demo` Mod
+//! Demo imports for the sandbox sessions trying the API without registering
+
+pub mod interfaces;
+pub mod purge;
+pub mod repository;
+pub mod routes;
+pub mod service;
+
+pub use routes::demo_routes;
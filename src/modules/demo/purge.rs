@@ -0,0 +1,45 @@
+//! # `Demo` Purge
+//! Background deletion of the demo users past their expiry, with all of their data.
+//! With several instances, a single one purges on each run.
+
+use sqlx::{Pool, Postgres};
+use time::OffsetDateTime;
+
+use crate::{
+    modules::demo::repository::DemoRepository,
+    workers::{run_exclusive, WorkerTask},
+};
+
+// Delay between two purges
+const PURGE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+
+// Advisory lock taken by the instance running a purge
+const PURGE_LOCK: &str = "demo_purge";
+
+async fn purge(demo_repository: &DemoRepository) {
+    match demo_repository
+        .purge_expired(OffsetDateTime::now_utc())
+        .await
+    {
+        Ok(0) => {}
+        Ok(purged) => tracing::info!("Purged {} expired demo users", purged),
+        Err(e) => tracing::warn!("Error purging demo users: {}", e),
+    }
+}
+
+// Worker deleting the expired demo users. Runs are skipped while another instance
+// purges, database errors are logged and retried on the next run.
+pub fn demo_purge_worker(pool: Pool<Postgres>, demo_repository: DemoRepository) -> WorkerTask {
+    Box::pin(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            match run_exclusive(&pool, PURGE_LOCK, purge(&demo_repository)).await {
+                Ok(Some(())) => {}
+                Ok(None) => tracing::debug!("Demo purge running on another instance"),
+                Err(e) => tracing::warn!("Error locking demo purge: {}", e),
+            }
+        }
+    })
+}
@@ -0,0 +1,99 @@
+//! # `Demo` Repository
+//! This module defines the demo repository, creating the throwaway users with their
+//! sample todos and deleting them once expired.
+
+use sqlx::{Error, Pool, Postgres};
+use time::OffsetDateTime;
+
+use crate::telemetry::observe_query;
+
+// Name of the list holding the sample todos
+const DEMO_LIST_NAME: &str = "Getting started";
+
+pub struct DemoRepository {
+    pool: Pool<Postgres>,
+}
+
+impl DemoRepository {
+    // Constructor
+    pub const fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Create a verified demo user expiring at `expires_at`, with a list of sample todos in
+    // its personal workspace. Returns the id of the user.
+    pub async fn create_demo_user(
+        &self,
+        username: &str,
+        email: &str,
+        password: &str,
+        expires_at: OffsetDateTime,
+    ) -> Result<i64, Error> {
+        observe_query("demo.create_demo_user", async move {
+            let mut tx = self.pool.begin().await?;
+
+            let user_id = sqlx::query_scalar::<_, i32>(
+                "INSERT INTO users (username, email, password, name, active, email_verified_at,
+                    demo_expires_at)
+                 VALUES ($1, $2, $3, 'Demo', true, NOW(), $4)
+                 RETURNING id",
+            )
+            .bind(username)
+            .bind(email)
+            .bind(password)
+            .bind(expires_at)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            // The personal workspace is created by the insert trigger of the user
+            let (list_id, workspace_id) = sqlx::query_as::<_, (i32, i32)>(
+                "INSERT INTO lists (user_id, workspace_id, name)
+                 SELECT $1, id, $2 FROM workspaces WHERE personal_user_id = $1
+                 RETURNING id, workspace_id",
+            )
+            .bind(user_id)
+            .bind(DEMO_LIST_NAME)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                "INSERT INTO todos (user_id, workspace_id, list_id, title, description, status,
+                    completed, completed_at, due_at)
+                 VALUES
+                    ($1, $2, $3, 'Open a demo session',
+                     'Done already, the token of this session is in the Authorize dialog',
+                     'done', true, NOW(), NULL),
+                    ($1, $2, $3, 'Move a todo through the workflow',
+                     'PUT /todos/{id} with the status done',
+                     'in_progress', false, NULL, NOW() + INTERVAL '1 day'),
+                    ($1, $2, $3, 'Create your own list',
+                     'POST /lists, then add todos to it', 'backlog', false, NULL, NULL),
+                    ($1, $2, NULL, 'Search your todos',
+                     'GET /todos/search?q=list finds the todos mentioning lists',
+                     'backlog', false, NULL, NOW() + INTERVAL '3 days')",
+            )
+            .bind(user_id)
+            .bind(workspace_id)
+            .bind(list_id)
+            .execute(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            Ok(i64::from(user_id))
+        })
+        .await
+    }
+
+    // Delete the demo users expired before `now`, their data goes with them. Returns the
+    // number of users deleted.
+    pub async fn purge_expired(&self, now: OffsetDateTime) -> Result<u64, Error> {
+        observe_query("demo.purge_expired", async move {
+            let result = sqlx::query("DELETE FROM users WHERE demo_expires_at < $1")
+                .bind(now)
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected())
+        })
+        .await
+    }
+}
@@ -0,0 +1,51 @@
+//! #`Demo` Routes
+//! This module defines the HTTP route opening a demo session.
+
+use axum::routing::post;
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Router};
+
+use crate::modules::common::{respond, ErrorResponse};
+use crate::modules::demo::interfaces::DemoSessionResponse;
+use crate::modules::demo::repository::DemoRepository;
+use crate::modules::demo::service::DemoService;
+use crate::AppState;
+
+// Creates and returns the demo routes
+pub fn demo_routes() -> Router<AppState> {
+    Router::new().route("/demo/session", post(create_demo_session_route))
+}
+
+// Create Demo Session Route
+#[utoipa::path(
+    post,
+    path = "/demo/session",
+    tag = "Demo",
+    responses(
+        (status = 201, description = "Throwaway user with sample todos, deleted with its data once the token expires", body = DemoSessionResponse),
+        (status = 403, description = "Demo sessions are disabled", body = ErrorResponse),
+        (status = 429, description = "Too many demo sessions from this client", body = ErrorResponse),
+        (status = 500, description = "Failed to create demo session", body = ErrorResponse)
+    )
+)]
+pub async fn create_demo_session_route(State(app_state): State<AppState>) -> impl IntoResponse {
+    let service = DemoService::new(
+        DemoRepository::new(app_state.db_pool.clone()),
+        app_state.config.demo.clone(),
+    );
+    respond(
+        StatusCode::CREATED,
+        service.create_session(&app_state.encoding_key).await,
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_demo_routes_creation() {
+        let _routes = demo_routes();
+        assert!(true);
+    }
+}
@@ -0,0 +1,98 @@
+//! # `Demo` Service
+//!
+//! This module contains the bussiness logic of the demo sessions: a throwaway user with
+//! sample todos and a token expiring along with it.
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use jsonwebtoken::EncodingKey;
+use time::{Duration, OffsetDateTime};
+
+use crate::auth::generate_workspace_token;
+use crate::config::DemoConfig;
+use crate::modules::{
+    common::AppError,
+    demo::{interfaces::DemoSessionResponse, repository::DemoRepository},
+};
+use crate::utils::{password::hash_password, token::generate_random_token};
+
+// Random bytes of the demo usernames
+const USERNAME_BYTES: usize = 6;
+
+// Domain of the demo emails, reserved so nothing is ever delivered to it
+const DEMO_EMAIL_DOMAIN: &str = "demo.invalid";
+
+// Authentication method of the demo tokens
+const DEMO_AMR: &str = "demo";
+
+pub struct DemoService {
+    demo_repository: DemoRepository,
+    config: DemoConfig,
+}
+
+// Random username of a demo user
+fn demo_username() -> String {
+    let mut bytes = [0u8; USERNAME_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    format!("demo_{}", hex::encode(bytes))
+}
+
+impl DemoService {
+    // Constructor
+    pub const fn new(demo_repository: DemoRepository, config: DemoConfig) -> Self {
+        Self {
+            demo_repository,
+            config,
+        }
+    }
+
+    // Create a demo user and log it in. Its password is random and never returned, the
+    // token is the only way in.
+    pub async fn create_session(
+        &self,
+        encoding_key: &EncodingKey,
+    ) -> Result<DemoSessionResponse, AppError> {
+        if !self.config.enabled {
+            return Err(AppError::Forbidden(
+                "Demo sessions are disabled on this server".to_string(),
+            ));
+        }
+
+        let username = demo_username();
+        let email = format!("{username}@{DEMO_EMAIL_DOMAIN}");
+        let password = hash_password(&generate_random_token()).map_err(AppError::Internal)?;
+        let expires_at = OffsetDateTime::now_utc() + Duration::minutes(self.config.session_minutes);
+
+        let user_id = self
+            .demo_repository
+            .create_demo_user(&username, &email, &password, expires_at)
+            .await?;
+        let token = generate_workspace_token(
+            self.config.session_minutes,
+            user_id,
+            None,
+            vec![DEMO_AMR.to_string()],
+            None,
+            encoding_key,
+        )?;
+
+        tracing::info!("Demo session started for user {}", user_id);
+        Ok(DemoSessionResponse {
+            token,
+            username,
+            expires_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_demo_username() {
+        let username = demo_username();
+        assert!(username.starts_with("demo_"));
+        assert_eq!(username.len(), "demo_".len() + USERNAME_BYTES * 2);
+        assert_ne!(username, demo_username());
+    }
+}
@@ -0,0 +1,148 @@
+//! # Email Diagnostics
+//! Hints about the email configuration, for the self-hosters wondering why their emails
+//! never arrive. Only the configuration is checked, the DNS records of the sender domain
+//! are left to the administrators.
+
+use crate::modules::email::mailer::{email_domain, EmailSettings};
+
+// Whether the domain can't send emails to the internet
+#[allow(clippy::case_sensitive_file_extension_comparisons)]
+fn is_local_domain(domain: &str) -> bool {
+    !domain.contains('.')
+        || domain.ends_with(".local")
+        || domain.ends_with(".localhost")
+        || domain.ends_with(".internal")
+}
+
+// Problems of the email configuration likely to lose or flag the emails, and the
+// checks left to do
+pub fn email_hints(settings: &EmailSettings) -> Vec<String> {
+    let mut hints = Vec::new();
+    if settings.transport == "log" {
+        hints.push(
+            "No SMTP server is configured (SMTP_HOST), emails are only written to the log"
+                .to_string(),
+        );
+        return hints;
+    }
+
+    let from_domain = settings.from.as_deref().and_then(email_domain);
+    match from_domain.as_deref() {
+        None => hints.push("EMAIL_FROM is not a valid email address".to_string()),
+        Some(domain) if is_local_domain(domain) => hints.push(format!(
+            "EMAIL_FROM uses the local domain {domain}, receiving servers reject it: \
+             send from a domain you own"
+        )),
+        Some(domain) => {
+            if !settings.sender_domains.is_empty()
+                && !settings
+                    .sender_domains
+                    .iter()
+                    .any(|verified| verified == domain)
+            {
+                hints.push(format!(
+                    "EMAIL_FROM uses {domain}, which is not a verified sender domain \
+                     (EMAIL_SENDER_DOMAINS): the provider may refuse the emails"
+                ));
+            }
+            hints.push(format!(
+                "Check that {domain} publishes an SPF record allowing the SMTP server, the \
+                 DKIM key of the provider and a DMARC policy, or the emails land in spam"
+            ));
+        }
+    }
+
+    if let (Some(username_domain), Some(from_domain)) = (
+        settings.username.as_deref().and_then(email_domain),
+        from_domain.as_deref(),
+    ) {
+        if username_domain != from_domain {
+            hints.push(format!(
+                "SMTP_USERNAME belongs to {username_domain} while EMAIL_FROM uses \
+                 {from_domain}, most providers only send for the domain of the account"
+            ));
+        }
+    }
+
+    let local_host = settings
+        .host
+        .as_deref()
+        .is_some_and(|host| host == "localhost" || host == "127.0.0.1" || host == "::1");
+    if settings.tls.as_deref() == Some("none") && !local_host {
+        hints.push(
+            "SMTP_TLS is none, the credentials and the emails are sent unencrypted".to_string(),
+        );
+    }
+    if settings.username.is_none() && !local_host {
+        hints.push(
+            "SMTP_USERNAME is not set, most SMTP servers only relay for logged in senders"
+                .to_string(),
+        );
+    }
+    hints
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn smtp_settings(from: &str) -> EmailSettings {
+        EmailSettings {
+            transport: "smtp".to_string(),
+            host: Some("smtp.example.com".to_string()),
+            port: Some(587),
+            tls: Some("starttls".to_string()),
+            username: Some("app@example.com".to_string()),
+            from: Some(from.to_string()),
+            timeout_seconds: Some(10),
+            retry_attempts: 3,
+            sender_domains: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_email_hints_log_transport() {
+        let settings = EmailSettings {
+            transport: "log".to_string(),
+            ..EmailSettings::default()
+        };
+        let hints = email_hints(&settings);
+        assert_eq!(hints.len(), 1);
+        assert!(hints[0].contains("SMTP_HOST"));
+    }
+
+    #[test]
+    fn test_email_hints_valid_configuration() {
+        let hints = email_hints(&smtp_settings("Todo App <no-reply@example.com>"));
+        // Only the DNS records are left to check
+        assert_eq!(hints.len(), 1);
+        assert!(hints[0].contains("DKIM"));
+    }
+
+    #[test]
+    fn test_email_hints_sender_problems() {
+        let hints = email_hints(&smtp_settings("Todo App <no-reply@localhost>"));
+        assert!(hints.iter().any(|hint| hint.contains("local domain")));
+
+        let mut settings = smtp_settings("no-reply@other.org");
+        settings.sender_domains = vec!["example.com".to_string()];
+        let hints = email_hints(&settings);
+        assert!(hints
+            .iter()
+            .any(|hint| hint.contains("EMAIL_SENDER_DOMAINS")));
+        assert!(hints.iter().any(|hint| hint.contains("SMTP_USERNAME")));
+    }
+
+    #[test]
+    fn test_email_hints_insecure_transport() {
+        let mut settings = smtp_settings("no-reply@example.com");
+        settings.tls = Some("none".to_string());
+        settings.username = None;
+        let hints = email_hints(&settings);
+        assert!(hints.iter().any(|hint| hint.contains("SMTP_TLS")));
+        assert!(hints.iter().any(|hint| hint.contains("not set")));
+
+        settings.host = Some("localhost".to_string());
+        assert_eq!(email_hints(&settings).len(), 1);
+    }
+}
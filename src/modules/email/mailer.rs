@@ -0,0 +1,408 @@
+//! # Mailer
+//! This module defines how emails are sent, over SMTP when `SMTP_HOST` is set and to
+//! the log otherwise. Failed sends are retried with a growing delay.
+
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use lettre::{
+    message::{header::ContentType, Mailbox, MultiPart},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::{config::SmtpConfig, tasks::BackgroundTasks};
+
+// Delay before the first retry, doubled on each following one
+const RETRY_BACKOFF: Duration = Duration::from_secs(1);
+
+// Email ready to be sent
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EmailMessage {
+    pub to: String,
+    pub subject: String,
+    pub text_body: String,
+    pub html_body: Option<String>,
+}
+
+// Configuration of the outgoing emails shown to the administrators, without the secrets
+#[derive(Serialize, ToSchema, Clone, Debug, Default, PartialEq, Eq)]
+pub struct EmailSettings {
+    // `smtp`, or `log` when no SMTP server is configured
+    pub transport: String,
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    // `starttls`, `tls` or `none`
+    pub tls: Option<String>,
+    // Login to the SMTP server, the password is never shown
+    pub username: Option<String>,
+    pub from: Option<String>,
+    pub timeout_seconds: Option<u64>,
+    pub retry_attempts: u32,
+    // Domains verified with the email provider, from `EMAIL_SENDER_DOMAINS`
+    pub sender_domains: Vec<String>,
+}
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    // Send the email, an error means it was not accepted
+    async fn send(&self, message: &EmailMessage) -> Result<(), String>;
+
+    // Configuration of the mailer, without the secrets
+    fn settings(&self) -> EmailSettings {
+        EmailSettings {
+            transport: "custom".to_string(),
+            retry_attempts: 1,
+            ..EmailSettings::default()
+        }
+    }
+
+    // Check the mailer can send, e.g. that the SMTP server accepts the connection
+    async fn check(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+// Emails written to the log, used when no SMTP server is configured
+pub struct LogMailer;
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, message: &EmailMessage) -> Result<(), String> {
+        tracing::info!("Email to {}: {}", message.to, message.subject);
+        tracing::debug!("{}", message.text_body);
+        Ok(())
+    }
+
+    fn settings(&self) -> EmailSettings {
+        EmailSettings {
+            transport: "log".to_string(),
+            retry_attempts: 1,
+            ..EmailSettings::default()
+        }
+    }
+}
+
+// How the connection to the SMTP server is secured
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SmtpTls {
+    StartTls,
+    Tls,
+    None,
+}
+
+impl SmtpTls {
+    fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "starttls" => Ok(Self::StartTls),
+            "tls" => Ok(Self::Tls),
+            "none" => Ok(Self::None),
+            other => Err(format!("Unknown SMTP_TLS: {other}")),
+        }
+    }
+
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::StartTls => "starttls",
+            Self::Tls => "tls",
+            Self::None => "none",
+        }
+    }
+}
+
+// Emails sent through an SMTP server
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    settings: EmailSettings,
+}
+
+impl SmtpMailer {
+    // Connection to `host` with the settings of the configuration
+    fn from_config(host: &str, config: &SmtpConfig) -> Result<Self, String> {
+        let tls = SmtpTls::parse(&config.tls)?;
+        let port = config.port;
+        let timeout = config.timeout_seconds;
+        let from = config
+            .from
+            .parse::<Mailbox>()
+            .map_err(|e| format!("Invalid EMAIL_FROM: {e}"))?;
+
+        let mut builder = match tls {
+            SmtpTls::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+                .map_err(|e| format!("Invalid SMTP_HOST: {e}"))?,
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host)
+                .map_err(|e| format!("Invalid SMTP_HOST: {e}"))?,
+            SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+        }
+        .port(port)
+        .timeout(Some(Duration::from_secs(timeout)));
+        let username = config.username.clone();
+        if let Some(username) = username.clone() {
+            builder = builder.credentials(Credentials::new(username, config.password.clone()));
+        }
+
+        let settings = EmailSettings {
+            transport: "smtp".to_string(),
+            host: Some(host.to_string()),
+            port: Some(port),
+            tls: Some(tls.as_str().to_string()),
+            username,
+            from: Some(from.to_string()),
+            timeout_seconds: Some(timeout),
+            retry_attempts: 1,
+            sender_domains: Vec::new(),
+        };
+        Ok(Self {
+            transport: builder.build(),
+            from,
+            settings,
+        })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, message: &EmailMessage) -> Result<(), String> {
+        let to = message
+            .to
+            .parse::<Mailbox>()
+            .map_err(|e| format!("Invalid recipient {}: {e}", message.to))?;
+        let builder = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(&message.subject);
+        let email = match &message.html_body {
+            Some(html) => builder.multipart(MultiPart::alternative_plain_html(
+                message.text_body.clone(),
+                html.clone(),
+            )),
+            None => builder
+                .header(ContentType::TEXT_PLAIN)
+                .body(message.text_body.clone()),
+        }
+        .map_err(|e| e.to_string())?;
+
+        self.transport
+            .send(email)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    fn settings(&self) -> EmailSettings {
+        self.settings.clone()
+    }
+
+    async fn check(&self) -> Result<(), String> {
+        match self.transport.test_connection().await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err("The SMTP server did not accept the connection".to_string()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+// Mailer retrying failed sends, waiting twice as long before each new attempt
+pub struct RetryMailer {
+    inner: Arc<dyn Mailer>,
+    attempts: u32,
+    backoff: Duration,
+    // Domains verified with the email provider, reported in the settings
+    sender_domains: Vec<String>,
+}
+
+impl RetryMailer {
+    pub fn new(inner: Arc<dyn Mailer>, attempts: u32, backoff: Duration) -> Self {
+        Self {
+            inner,
+            attempts: attempts.max(1),
+            backoff,
+            sender_domains: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_sender_domains(mut self, sender_domains: Vec<String>) -> Self {
+        self.sender_domains = sender_domains;
+        self
+    }
+}
+
+#[async_trait]
+impl Mailer for RetryMailer {
+    async fn send(&self, message: &EmailMessage) -> Result<(), String> {
+        let mut delay = self.backoff;
+        let mut attempt = 1;
+        loop {
+            match self.inner.send(message).await {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt >= self.attempts => return Err(e),
+                Err(e) => {
+                    tracing::warn!(
+                        "Email to {} failed on attempt {}: {}",
+                        message.to,
+                        attempt,
+                        e
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay = delay.saturating_mul(2);
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    fn settings(&self) -> EmailSettings {
+        EmailSettings {
+            retry_attempts: self.attempts,
+            sender_domains: self.sender_domains.clone(),
+            ..self.inner.settings()
+        }
+    }
+
+    async fn check(&self) -> Result<(), String> {
+        self.inner.check().await
+    }
+}
+
+// Domain of an email address or mailbox, e.g. `example.com` for
+// `Todo App <no-reply@example.com>`
+pub fn email_domain(address: &str) -> Option<String> {
+    let address = address
+        .rsplit_once('<')
+        .map_or(address, |(_, rest)| rest.trim_end_matches('>'))
+        .trim();
+    let (local, domain) = address.rsplit_once('@')?;
+    if local.is_empty() || domain.is_empty() || address.contains(char::is_whitespace) {
+        return None;
+    }
+    Some(domain.to_ascii_lowercase())
+}
+
+// Mailer of the handlers, sending on the background tasks without waiting
+#[derive(Clone)]
+pub struct BackgroundMailer {
+    mailer: Arc<dyn Mailer>,
+    tasks: BackgroundTasks,
+}
+
+impl BackgroundMailer {
+    pub const fn new(mailer: Arc<dyn Mailer>, tasks: BackgroundTasks) -> Self {
+        Self { mailer, tasks }
+    }
+
+    // Send an email without waiting for it, failures are logged
+    pub fn send(&self, message: EmailMessage) {
+        let mailer = self.mailer.clone();
+        self.tasks.spawn(async move {
+            if let Err(e) = mailer.send(&message).await {
+                tracing::error!("Email to {} not sent: {}", message.to, e);
+            }
+        });
+    }
+}
+
+// Mailer of the configuration, retrying `retry_attempts` times. Emails are only
+// logged when no SMTP host is configured.
+pub fn mailer_from_config(config: &SmtpConfig) -> Result<Arc<dyn Mailer>, String> {
+    let inner: Arc<dyn Mailer> = match config.host.as_deref() {
+        Some(host) => Arc::new(SmtpMailer::from_config(host, config)?),
+        None => Arc::new(LogMailer),
+    };
+
+    Ok(Arc::new(
+        RetryMailer::new(inner, config.retry_attempts, RETRY_BACKOFF)
+            .with_sender_domains(config.sender_domains.clone()),
+    ))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+
+    // Fails the first `failures` sends
+    struct FlakyMailer {
+        failures: u32,
+        calls: AtomicU32,
+    }
+
+    #[async_trait]
+    impl Mailer for FlakyMailer {
+        async fn send(&self, _message: &EmailMessage) -> Result<(), String> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+                return Err("unavailable".to_string());
+            }
+            Ok(())
+        }
+    }
+
+    fn message() -> EmailMessage {
+        EmailMessage {
+            to: "ana@example.com".to_string(),
+            subject: "Hello".to_string(),
+            text_body: "Hello".to_string(),
+            html_body: None,
+        }
+    }
+
+    #[test]
+    fn test_email_domain() {
+        assert_eq!(
+            email_domain("Todo App <no-reply@Example.com>").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(
+            email_domain("ana@mail.example.org").as_deref(),
+            Some("mail.example.org")
+        );
+        assert_eq!(email_domain("not an address"), None);
+    }
+
+    #[test]
+    fn test_retry_mailer_settings() {
+        let mailer = RetryMailer::new(Arc::new(LogMailer), 3, Duration::ZERO)
+            .with_sender_domains(vec!["example.com".to_string()]);
+        let settings = mailer.settings();
+        assert_eq!(settings.transport, "log");
+        assert_eq!(settings.retry_attempts, 3);
+        assert_eq!(settings.sender_domains, vec!["example.com"]);
+    }
+
+    #[test]
+    fn test_smtp_tls_parse() {
+        assert_eq!(SmtpTls::parse("").unwrap(), SmtpTls::StartTls);
+        assert_eq!(SmtpTls::parse(" TLS ").unwrap(), SmtpTls::Tls);
+        assert_eq!(SmtpTls::parse("none").unwrap(), SmtpTls::None);
+        assert!(SmtpTls::parse("ssl3").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_retry_mailer_retries_until_sent() {
+        let flaky = Arc::new(FlakyMailer {
+            failures: 2,
+            calls: AtomicU32::new(0),
+        });
+        let mailer = RetryMailer::new(flaky.clone(), 3, Duration::ZERO);
+
+        mailer.send(&message()).await.unwrap();
+        assert_eq!(flaky.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_mailer_gives_up() {
+        let flaky = Arc::new(FlakyMailer {
+            failures: 5,
+            calls: AtomicU32::new(0),
+        });
+        let mailer = RetryMailer::new(flaky.clone(), 2, Duration::ZERO);
+
+        assert_eq!(mailer.send(&message()).await.unwrap_err(), "unavailable");
+        assert_eq!(flaky.calls.load(Ordering::SeqCst), 2);
+    }
+}
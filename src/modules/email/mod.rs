@@ -0,0 +1,6 @@
+//! # `Email` Mod
+//! Email imports for the outgoing emails and their templates
+
+pub mod diagnostics;
+pub mod mailer;
+pub mod templates;
@@ -0,0 +1,220 @@
+//! # Email Templates
+//! Emails sent by the application, with `{{name}}` placeholders filled when rendered.
+//! Values are HTML escaped in the HTML body.
+
+use crate::modules::email::mailer::EmailMessage;
+
+pub struct EmailTemplate {
+    subject: &'static str,
+    text: &'static str,
+    html: &'static str,
+}
+
+// Placeholders: `token`, `expires_at`
+pub const WORKSPACE_INVITATION: EmailTemplate = EmailTemplate {
+    subject: "You are invited to join a workspace",
+    text: "You were invited to join a workspace on Todo App.\n\n\
+           Sign in with this email and accept the invitation with the token below \
+           before {{expires_at}}:\n\n{{token}}\n",
+    html: "<p>You were invited to join a workspace on Todo App.</p>\
+           <p>Sign in with this email and accept the invitation with the token below \
+           before {{expires_at}}:</p><p><code>{{token}}</code></p>",
+};
+
+// Placeholders: `username`, `title`, `due_at`
+pub const REMINDER: EmailTemplate = EmailTemplate {
+    subject: "Reminder: {{title}}",
+    text: "Hi {{username}},\n\nThis is your reminder for \"{{title}}\".\nDue: {{due_at}}\n",
+    html: "<p>Hi {{username}},</p><p>This is your reminder for <strong>{{title}}</strong>.</p>\
+           <p>Due: {{due_at}}</p>",
+};
+
+// Placeholders: `username`, `link`, `expires_at`
+pub const EMAIL_VERIFICATION: EmailTemplate = EmailTemplate {
+    subject: "Verify your email address",
+    text: "Hi {{username}},\n\nConfirm your email address by opening the link below \
+           before {{expires_at}}:\n\n{{link}}\n",
+    html: "<p>Hi {{username}},</p><p>Confirm your email address by opening the link below \
+           before {{expires_at}}:</p><p><a href=\"{{link}}\">{{link}}</a></p>",
+};
+
+// Placeholders: `username`, `token`, `expires_at`
+pub const PASSWORD_RESET: EmailTemplate = EmailTemplate {
+    subject: "Reset your password",
+    text: "Hi {{username}},\n\nThe password of your account must be reset before you can \
+           log in again. Choose a new password with the token below before \
+           {{expires_at}}:\n\n{{token}}\n",
+    html: "<p>Hi {{username}},</p><p>The password of your account must be reset before you \
+           can log in again. Choose a new password with the token below before \
+           {{expires_at}}:</p><p><code>{{token}}</code></p>",
+};
+
+// Placeholders: `username`, `count`, `days`
+pub const STALE_REVIEW: EmailTemplate = EmailTemplate {
+    subject: "Review your stale tasks",
+    text: "Hi {{username}},\n\n{{count}} of your open tasks were not touched for \
+           {{days}} days or more. Take a moment to review them: finish, reschedule or \
+           archive what you no longer need.\n",
+    html: "<p>Hi {{username}},</p><p><strong>{{count}}</strong> of your open tasks were not \
+           touched for {{days}} days or more.</p><p>Take a moment to review them: finish, \
+           reschedule or archive what you no longer need.</p>",
+};
+
+// Placeholders: `username`, `assigner`, `title`
+pub const ASSIGNMENT_REQUEST: EmailTemplate = EmailTemplate {
+    subject: "{{assigner}} assigned you \"{{title}}\"",
+    text: "Hi {{username}},\n\n{{assigner}} assigned you the task \"{{title}}\". It waits \
+           in your review queue until you accept or decline it.\n",
+    html: "<p>Hi {{username}},</p><p>{{assigner}} assigned you the task \
+           <strong>{{title}}</strong>.</p><p>It waits in your review queue until you accept \
+           or decline it.</p>",
+};
+
+// Placeholders: `username`, `assignee`, `title`, `decision`
+pub const ASSIGNMENT_RESPONSE: EmailTemplate = EmailTemplate {
+    subject: "{{assignee}} {{decision}} \"{{title}}\"",
+    text: "Hi {{username}},\n\n{{assignee}} {{decision}} the task \"{{title}}\" you \
+           assigned.\n",
+    html: "<p>Hi {{username}},</p><p>{{assignee}} {{decision}} the task \
+           <strong>{{title}}</strong> you assigned.</p>",
+};
+
+// Placeholders: `username`, `time`, `ip_address`, `location`, `user_agent`
+pub const LOGIN_ALERT: EmailTemplate = EmailTemplate {
+    subject: "New sign-in to your account",
+    text: "Hi {{username}},\n\nYour account was signed in from a new device.\n\n\
+           Time: {{time}}\nIP address: {{ip_address}}\nLocation: {{location}}\n\
+           Device: {{user_agent}}\n\nIf this was you, there is nothing to do. Otherwise \
+           change your password and revoke the session from your devices.\n",
+    html: "<p>Hi {{username}},</p><p>Your account was signed in from a new device.</p>\
+           <p>Time: {{time}}<br>IP address: {{ip_address}}<br>Location: {{location}}<br>\
+           Device: {{user_agent}}</p><p>If this was you, there is nothing to do. Otherwise \
+           change your password and revoke the session from your devices.</p>",
+};
+
+impl EmailTemplate {
+    // Render the email to `to`, placeholders without a value are left empty
+    pub fn render(&self, to: impl Into<String>, values: &[(&str, &str)]) -> EmailMessage {
+        EmailMessage {
+            to: to.into(),
+            subject: fill(self.subject, values, false),
+            text_body: fill(self.text, values, false),
+            html_body: Some(fill(self.html, values, true)),
+        }
+    }
+}
+
+// Replace the placeholders in a single pass, so values are never read as placeholders
+fn fill(template: &str, values: &[(&str, &str)], html: bool) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        filled.push_str(&rest[..start]);
+        let name = rest[start + 2..start + end].trim();
+        if let Some((_, value)) = values.iter().find(|(key, _)| *key == name) {
+            if html {
+                filled.push_str(&escape_html(value));
+            } else {
+                filled.push_str(value);
+            }
+        }
+        rest = &rest[start + end + 2..];
+    }
+    filled.push_str(rest);
+    filled
+}
+
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_replaces_placeholders_once() {
+        assert_eq!(
+            fill(
+                "Hi {{ name }}, {{missing}}see {{title}}",
+                &[("name", "{{title}}"), ("title", "rent")],
+                false
+            ),
+            "Hi {{title}}, see rent"
+        );
+        assert_eq!(fill("Open {{ brace", &[], false), "Open {{ brace");
+    }
+
+    #[test]
+    fn test_render_escapes_html_body() {
+        let message = REMINDER.render(
+            "ana@example.com",
+            &[
+                ("username", "ana"),
+                ("title", "<b>rent</b>"),
+                ("due_at", ""),
+            ],
+        );
+
+        assert_eq!(message.to, "ana@example.com");
+        assert_eq!(message.subject, "Reminder: <b>rent</b>");
+        assert!(message.text_body.contains("\"<b>rent</b>\""));
+        assert!(message
+            .html_body
+            .unwrap()
+            .contains("<strong>&lt;b&gt;rent&lt;/b&gt;</strong>"));
+    }
+
+    #[test]
+    fn test_render_assignment_response() {
+        let message = ASSIGNMENT_RESPONSE.render(
+            "ana@example.com",
+            &[
+                ("username", "ana"),
+                ("assignee", "bob"),
+                ("title", "rent"),
+                ("decision", "declined"),
+            ],
+        );
+
+        assert_eq!(message.subject, "bob declined \"rent\"");
+        assert!(message
+            .text_body
+            .contains("bob declined the task \"rent\" you assigned"));
+    }
+
+    #[test]
+    fn test_render_verification_link() {
+        let message = EMAIL_VERIFICATION.render(
+            "ana@example.com",
+            &[
+                ("username", "ana"),
+                ("link", "http://localhost/user/verify?token=a&b"),
+                ("expires_at", "tomorrow"),
+            ],
+        );
+
+        assert!(message
+            .text_body
+            .contains("http://localhost/user/verify?token=a&b"));
+        assert!(message
+            .html_body
+            .unwrap()
+            .contains("href=\"http://localhost/user/verify?token=a&amp;b\""));
+    }
+}
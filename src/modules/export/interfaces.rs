@@ -0,0 +1,176 @@
+//! # `Export` Interfaces
+//! This module defines the formats of the account exports and the export jobs
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::utils::dates::{rfc3339, rfc3339_option};
+
+#[derive(Deserialize, IntoParams, Clone, Debug, Default)]
+pub struct DataExportQuery {
+    /// Format of the export, `json` (the default) for a single document or `csv` for a
+    /// zip archive of one table per kind of data
+    pub format: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Json,
+    Csv,
+}
+
+impl ExportFormat {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+        }
+    }
+
+    // Content type of the exported file
+    pub const fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Csv => "application/zip",
+        }
+    }
+
+    // Name the exported file is saved as
+    pub const fn file_name(self) -> &'static str {
+        match self {
+            Self::Json => "export.json",
+            Self::Csv => "export.zip",
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            _ => Err(format!(
+                "Unsupported export format: {value}, expected json or csv"
+            )),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportStatus {
+    Pending,
+    Ready,
+    Failed,
+}
+
+impl FromStr for ExportStatus {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "pending" => Ok(Self::Pending),
+            "ready" => Ok(Self::Ready),
+            "failed" => Ok(Self::Failed),
+            _ => Err(format!("Unknown export status: {value}")),
+        }
+    }
+}
+
+// Export job row as stored in database
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct DataExportRow {
+    pub id: i32,
+    pub format: String,
+    pub status: String,
+    pub storage_key: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub created_at: OffsetDateTime,
+    pub completed_at: Option<OffsetDateTime>,
+    pub expires_at: OffsetDateTime,
+}
+
+// Stored file of an export to delete
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct ExpiredExportRow {
+    pub id: i32,
+    pub storage_key: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct DataExportResponse {
+    pub id: i64,
+    pub format: ExportFormat,
+    pub status: ExportStatus,
+    // Where the file is downloaded from, once ready
+    pub download_url: Option<String>,
+    pub size_bytes: Option<i64>,
+    #[serde(with = "rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "rfc3339_option")]
+    pub completed_at: Option<OffsetDateTime>,
+    // The file is deleted afterwards
+    #[serde(with = "rfc3339")]
+    pub expires_at: OffsetDateTime,
+}
+
+impl From<DataExportRow> for DataExportResponse {
+    fn from(row: DataExportRow) -> Self {
+        let id = i64::from(row.id);
+        // The check constraints of the table only allow the known values
+        let status = row.status.parse().unwrap_or(ExportStatus::Failed);
+        Self {
+            id,
+            format: row.format.parse().unwrap_or(ExportFormat::Json),
+            status,
+            download_url: (status == ExportStatus::Ready).then(|| format!("/export/{id}/download")),
+            size_bytes: row.size_bytes,
+            created_at: row.created_at,
+            completed_at: row.completed_at,
+            expires_at: row.expires_at,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn export_row(status: &str) -> DataExportRow {
+        DataExportRow {
+            id: 3,
+            format: "csv".to_string(),
+            status: status.to_string(),
+            storage_key: Some("exports/1/3.zip".to_string()),
+            size_bytes: Some(2048),
+            created_at: OffsetDateTime::UNIX_EPOCH,
+            completed_at: None,
+            expires_at: OffsetDateTime::UNIX_EPOCH,
+        }
+    }
+
+    #[test]
+    fn test_export_format() {
+        assert_eq!("".parse::<ExportFormat>().unwrap(), ExportFormat::Json);
+        assert_eq!(" CSV ".parse::<ExportFormat>().unwrap(), ExportFormat::Csv);
+        assert!("xml".parse::<ExportFormat>().is_err());
+    }
+
+    #[test]
+    fn test_download_url_once_ready() {
+        let pending = DataExportResponse::from(export_row("pending"));
+        assert_eq!(pending.status, ExportStatus::Pending);
+        assert_eq!(pending.format, ExportFormat::Csv);
+        assert!(pending.download_url.is_none());
+
+        let ready = DataExportResponse::from(export_row("ready"));
+        assert_eq!(ready.download_url.as_deref(), Some("/export/3/download"));
+    }
+}
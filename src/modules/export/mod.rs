@@ -0,0 +1,11 @@
+//! # `Export` Mod
+//! Export imports for the data portability of the accounts
+
+pub mod interfaces;
+pub mod purge;
+pub mod repository;
+pub mod routes;
+pub mod service;
+pub mod writer;
+
+pub use routes::export_routes;
@@ -0,0 +1,84 @@
+//! # `Export` Purge
+//! Background deletion of the expired exports with their files, and failure of the export
+//! jobs lost with a restart. With several instances, a single one purges on each run.
+
+use std::sync::Arc;
+
+use sqlx::{Pool, Postgres};
+use time::OffsetDateTime;
+
+use crate::{
+    modules::export::repository::ExportRepository,
+    storage::BlobStorage,
+    workers::{run_exclusive, WorkerTask},
+};
+
+// Delay between two purges
+const PURGE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15 * 60);
+
+// Age of the pending exports whose job is considered lost
+const STALE_AFTER: time::Duration = time::Duration::hours(1);
+
+// Advisory lock taken by the instance running a purge
+const PURGE_LOCK: &str = "export_purge";
+
+async fn purge(export_repository: &ExportRepository, storage: &dyn BlobStorage) {
+    let now = OffsetDateTime::now_utc();
+    match export_repository.fail_stale(now - STALE_AFTER).await {
+        Ok(0) => {}
+        Ok(failed) => tracing::info!("Failed {} stale exports", failed),
+        Err(e) => tracing::warn!("Error failing stale exports: {}", e),
+    }
+
+    let expired = match export_repository.list_expired(now).await {
+        Ok(expired) => expired,
+        Err(e) => {
+            tracing::warn!("Error listing expired exports: {}", e);
+            return;
+        }
+    };
+    let mut purged = 0;
+    for export in expired {
+        // The row is kept until its file is gone, a failed deletion is retried
+        if let Some(storage_key) = &export.storage_key {
+            if let Err(e) = storage.delete(storage_key).await {
+                tracing::warn!("Error removing export file {}: {}", storage_key, e);
+                continue;
+            }
+        }
+        match export_repository.delete_export(i64::from(export.id)).await {
+            Ok(()) => purged += 1,
+            Err(e) => tracing::warn!("Error deleting export {}: {}", export.id, e),
+        }
+    }
+    if purged > 0 {
+        tracing::info!("Purged {} expired exports", purged);
+    }
+}
+
+// Worker deleting the expired exports. Runs are skipped while another instance purges,
+// errors are logged and retried on the next run.
+pub fn export_purge_worker(
+    pool: Pool<Postgres>,
+    export_repository: ExportRepository,
+    storage: Arc<dyn BlobStorage>,
+) -> WorkerTask {
+    Box::pin(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            match run_exclusive(
+                &pool,
+                PURGE_LOCK,
+                purge(&export_repository, storage.as_ref()),
+            )
+            .await
+            {
+                Ok(Some(())) => {}
+                Ok(None) => tracing::debug!("Export purge running on another instance"),
+                Err(e) => tracing::warn!("Error locking export purge: {}", e),
+            }
+        }
+    })
+}
@@ -0,0 +1,204 @@
+//! # `Export` Repository
+//! This module defines the export repository, reading the account data the todos don't
+//! cover and tracking the export jobs.
+
+use sqlx::{Error, Pool, Postgres};
+use time::OffsetDateTime;
+
+use crate::modules::{
+    common::to_db_id,
+    export::interfaces::{DataExportRow, ExpiredExportRow, ExportFormat},
+    list::{interfaces::ListRow, repository::LIST_COLUMNS},
+};
+use crate::telemetry::observe_query;
+
+const EXPORT_COLUMNS: &str =
+    "id, format, status, storage_key, size_bytes, created_at, completed_at, expires_at";
+
+pub struct ExportRepository {
+    pool: Pool<Postgres>,
+}
+
+impl ExportRepository {
+    // Constructor
+    pub const fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Todos of the user across workspaces, the trash excluded
+    pub async fn count_todos(&self, user_id: i64) -> Result<i64, Error> {
+        observe_query("export.count_todos", async move {
+            sqlx::query_scalar::<_, i64>(
+                "SELECT COUNT(*) FROM todos WHERE user_id = $1 AND deleted_at IS NULL",
+            )
+            .bind(to_db_id(user_id)?)
+            .fetch_one(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Lists of the user across workspaces, archived lists included
+    pub async fn list_lists(&self, user_id: i64) -> Result<Vec<ListRow>, Error> {
+        observe_query("export.list_lists", async move {
+            let query = format!("SELECT {LIST_COLUMNS} FROM lists WHERE user_id = $1 ORDER BY id");
+
+            sqlx::query_as::<_, ListRow>(&query)
+                .bind(to_db_id(user_id)?)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Pending export of the user in the format, while one is being built
+    pub async fn find_pending(
+        &self,
+        user_id: i64,
+        format: ExportFormat,
+    ) -> Result<Option<DataExportRow>, Error> {
+        observe_query("export.find_pending", async move {
+            let query = format!(
+                "SELECT {EXPORT_COLUMNS} FROM data_exports
+                 WHERE user_id = $1 AND format = $2 AND status = 'pending'
+                 ORDER BY id DESC
+                 LIMIT 1"
+            );
+
+            sqlx::query_as::<_, DataExportRow>(&query)
+                .bind(to_db_id(user_id)?)
+                .bind(format.as_str())
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Record a pending export, its file is kept until `expires_at`
+    pub async fn create_export(
+        &self,
+        user_id: i64,
+        format: ExportFormat,
+        expires_at: OffsetDateTime,
+    ) -> Result<DataExportRow, Error> {
+        observe_query("export.create_export", async move {
+            let query = format!(
+                "INSERT INTO data_exports (user_id, format, expires_at)
+                 VALUES ($1, $2, $3)
+                 RETURNING {EXPORT_COLUMNS}"
+            );
+
+            sqlx::query_as::<_, DataExportRow>(&query)
+                .bind(to_db_id(user_id)?)
+                .bind(format.as_str())
+                .bind(expires_at)
+                .fetch_one(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Export of the user, unless expired
+    pub async fn fetch_export(
+        &self,
+        user_id: i64,
+        id: i64,
+    ) -> Result<Option<DataExportRow>, Error> {
+        observe_query("export.fetch_export", async move {
+            let query = format!(
+                "SELECT {EXPORT_COLUMNS} FROM data_exports
+                 WHERE id = $1 AND user_id = $2 AND expires_at > NOW()"
+            );
+
+            sqlx::query_as::<_, DataExportRow>(&query)
+                .bind(to_db_id(id)?)
+                .bind(to_db_id(user_id)?)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Mark a pending export as ready with its stored file
+    pub async fn complete_export(
+        &self,
+        id: i64,
+        storage_key: &str,
+        size_bytes: i64,
+    ) -> Result<(), Error> {
+        observe_query("export.complete_export", async move {
+            sqlx::query(
+                "UPDATE data_exports
+                 SET status = 'ready', storage_key = $2, size_bytes = $3, completed_at = NOW()
+                 WHERE id = $1 AND status = 'pending'",
+            )
+            .bind(to_db_id(id)?)
+            .bind(storage_key)
+            .bind(size_bytes)
+            .execute(&self.pool)
+            .await?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    // Mark a pending export as failed
+    pub async fn fail_export(&self, id: i64) -> Result<(), Error> {
+        observe_query("export.fail_export", async move {
+            sqlx::query(
+                "UPDATE data_exports SET status = 'failed', completed_at = NOW()
+                 WHERE id = $1 AND status = 'pending'",
+            )
+            .bind(to_db_id(id)?)
+            .execute(&self.pool)
+            .await?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    // Fail the exports pending since before `started_before`, their job was lost with a
+    // restart. Returns how many failed.
+    pub async fn fail_stale(&self, started_before: OffsetDateTime) -> Result<u64, Error> {
+        observe_query("export.fail_stale", async move {
+            let result = sqlx::query(
+                "UPDATE data_exports SET status = 'failed', completed_at = NOW()
+                 WHERE status = 'pending' AND created_at < $1",
+            )
+            .bind(started_before)
+            .execute(&self.pool)
+            .await?;
+
+            Ok(result.rows_affected())
+        })
+        .await
+    }
+
+    // Exports expired at `now`, with their stored file
+    pub async fn list_expired(&self, now: OffsetDateTime) -> Result<Vec<ExpiredExportRow>, Error> {
+        observe_query("export.list_expired", async move {
+            sqlx::query_as::<_, ExpiredExportRow>(
+                "SELECT id, storage_key FROM data_exports WHERE expires_at <= $1",
+            )
+            .bind(now)
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Delete an export once its file is gone
+    pub async fn delete_export(&self, id: i64) -> Result<(), Error> {
+        observe_query("export.delete_export", async move {
+            sqlx::query("DELETE FROM data_exports WHERE id = $1")
+                .bind(to_db_id(id)?)
+                .execute(&self.pool)
+                .await?;
+
+            Ok(())
+        })
+        .await
+    }
+}
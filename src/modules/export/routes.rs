@@ -0,0 +1,177 @@
+//! # `Export` Routes
+//! This module defines the HTTP routes exporting the data of the account.
+
+use axum::routing::get;
+use axum::{
+    extract::{Path, Query, State},
+    http::{
+        header::{CONTENT_DISPOSITION, CONTENT_LENGTH, CONTENT_TYPE, LOCATION},
+        StatusCode,
+    },
+    response::{IntoResponse, Redirect},
+    Router,
+};
+
+use crate::auth::Claims;
+use crate::modules::common::{respond, AppError, ErrorResponse};
+use crate::modules::export::{
+    interfaces::{DataExportQuery, DataExportResponse, ExportFormat},
+    service::{ExportService, ExportStart},
+    writer::ExportSource,
+};
+use crate::storage::Download;
+use crate::utils::concurrency_limit::{limit_concurrency, HEAVY_REQUEST_CONCURRENCY};
+use crate::utils::json::Json;
+use crate::AppState;
+
+// Creates and returns the export routes
+pub fn export_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/export",
+            limit_concurrency(get(export_account_route), HEAVY_REQUEST_CONCURRENCY),
+        )
+        .route("/export/{id}", get(fetch_export_route))
+        .route("/export/{id}/download", get(download_export_route))
+}
+
+fn export_service(app_state: &AppState) -> ExportService {
+    ExportService::new(
+        ExportSource::new(app_state.db_pool.clone()),
+        app_state.blob_storage.clone(),
+        app_state.tasks.clone(),
+    )
+}
+
+fn attachment_header(format: ExportFormat) -> String {
+    format!("attachment; filename=\"{}\"", format.file_name())
+}
+
+// Export Account Route
+#[utoipa::path(
+    get,
+    path = "/export",
+    tag = "Export",
+    params(DataExportQuery),
+    responses(
+        (status = 200, description = "Data of the account: the profile, preferences, lists, tags and todos with their subtasks. JSON is a single document, CSV a zip archive of one table per kind of data", content_type = "application/json"),
+        (status = 202, description = "Account too large to export at once, the export is built in the background and downloaded once ready", body = DataExportResponse,
+            headers(
+                ("Location" = String, description = "Status of the export")
+            )),
+        (status = 400, description = "Unsupported export format", body = ErrorResponse),
+        (status = 500, description = "Failed to export the account", body = ErrorResponse),
+        (status = 503, description = "Too many concurrent exports", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn export_account_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Query(query): Query<DataExportQuery>,
+) -> impl IntoResponse {
+    let format = match query
+        .format
+        .as_deref()
+        .unwrap_or_default()
+        .parse::<ExportFormat>()
+    {
+        Ok(format) => format,
+        Err(e) => return AppError::Validation(e).into_response(),
+    };
+
+    match export_service(&app_state)
+        .export_account(claims.user_id, format)
+        .await
+    {
+        Ok(ExportStart::File(body)) => (
+            StatusCode::OK,
+            [
+                (CONTENT_TYPE, format.content_type().to_string()),
+                (CONTENT_DISPOSITION, attachment_header(format)),
+            ],
+            body,
+        )
+            .into_response(),
+        Ok(ExportStart::Job(export)) => (
+            StatusCode::ACCEPTED,
+            [(LOCATION, format!("/export/{}", export.id))],
+            Json(export),
+        )
+            .into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+// Fetch Export Route
+#[utoipa::path(
+    get,
+    path = "/export/{id}",
+    tag = "Export",
+    params(
+        ("id" = i64, Path, description = "Export id")
+    ),
+    responses(
+        (status = 200, description = "Status of the export, with its download link once ready", body = DataExportResponse),
+        (status = 404, description = "Export not found or expired", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn fetch_export_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        export_service(&app_state)
+            .fetch_export(claims.user_id, id)
+            .await,
+    )
+}
+
+// Download Export Route
+#[utoipa::path(
+    get,
+    path = "/export/{id}/download",
+    tag = "Export",
+    params(
+        ("id" = i64, Path, description = "Export id")
+    ),
+    responses(
+        (status = 200, description = "File of the export, a zip archive for CSV", content_type = "application/json"),
+        (status = 307, description = "Redirect to a temporary URL of the file"),
+        (status = 404, description = "Export not found or expired", body = ErrorResponse),
+        (status = 409, description = "Export pending or failed", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn download_export_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    match export_service(&app_state)
+        .download_export(claims.user_id, id)
+        .await
+    {
+        Ok((_, Download::Redirect(url))) => Redirect::temporary(&url).into_response(),
+        Ok((format, Download::Stream { body, size })) => (
+            StatusCode::OK,
+            [
+                (CONTENT_TYPE, format.content_type().to_string()),
+                (CONTENT_LENGTH, size.to_string()),
+                (CONTENT_DISPOSITION, attachment_header(format)),
+            ],
+            body,
+        )
+            .into_response(),
+        Err(error) => error.into_response(),
+    }
+}
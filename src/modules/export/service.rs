@@ -0,0 +1,221 @@
+//! # `Export` Service
+//! Data portability of the accounts. Small accounts are exported in the response, larger
+//! ones by a background job storing the file in the blob storage, downloaded through a
+//! link until the file expires.
+
+use std::sync::Arc;
+
+use axum::body::{Body, Bytes};
+use time::{Duration, OffsetDateTime};
+
+use crate::{
+    modules::{
+        common::AppError,
+        export::{
+            interfaces::{DataExportResponse, ExportFormat, ExportStatus},
+            writer::{csv_archive, json_body, write_json, ExportSource},
+        },
+    },
+    storage::{BlobStorage, Download},
+    tasks::BackgroundTasks,
+    utils::token::generate_random_token,
+};
+
+// Largest account exported in the response, in todos
+const INLINE_MAX_TODOS: i64 = 2_000;
+
+// How long the file of an export job is kept
+const EXPORT_RETENTION: Duration = Duration::days(7);
+
+// Export of an account, in the response or by a job
+pub enum ExportStart {
+    File(Body),
+    Job(DataExportResponse),
+}
+
+pub struct ExportService {
+    source: ExportSource,
+    storage: Arc<dyn BlobStorage>,
+    tasks: BackgroundTasks,
+}
+
+impl ExportService {
+    pub fn new(
+        source: ExportSource,
+        storage: Arc<dyn BlobStorage>,
+        tasks: BackgroundTasks,
+    ) -> Self {
+        Self {
+            source,
+            storage,
+            tasks,
+        }
+    }
+
+    // Export the account of the user, starting a job past `INLINE_MAX_TODOS` todos. A job
+    // already building the same format is returned instead of starting another.
+    pub async fn export_account(
+        self,
+        user_id: i64,
+        format: ExportFormat,
+    ) -> Result<ExportStart, AppError> {
+        let export_repository = &self.source.exports;
+        let todos = export_repository.count_todos(user_id).await.map_err(|e| {
+            tracing::warn!("Error counting todos to export: {}", e);
+            AppError::Database(e)
+        })?;
+
+        if todos <= INLINE_MAX_TODOS {
+            return match format {
+                ExportFormat::Json => Ok(ExportStart::File(json_body(self.source, user_id))),
+                ExportFormat::Csv => csv_archive(&self.source, user_id)
+                    .await
+                    .map(|archive| ExportStart::File(Body::from(archive)))
+                    .map_err(|e| AppError::Internal(format!("Failed to export account: {e}"))),
+            };
+        }
+
+        let pending = export_repository
+            .find_pending(user_id, format)
+            .await
+            .map_err(|e| {
+                tracing::warn!("Error fetching pending export: {}", e);
+                AppError::Database(e)
+            })?;
+        if let Some(export) = pending {
+            return Ok(ExportStart::Job(export.into()));
+        }
+
+        let export = export_repository
+            .create_export(
+                user_id,
+                format,
+                OffsetDateTime::now_utc() + EXPORT_RETENTION,
+            )
+            .await
+            .map_err(|e| {
+                tracing::warn!("Error creating export: {}", e);
+                AppError::Database(e)
+            })?;
+
+        let export_id = i64::from(export.id);
+        self.tasks.spawn(run_export_job(
+            self.source,
+            self.storage,
+            export_id,
+            user_id,
+            format,
+        ));
+        Ok(ExportStart::Job(export.into()))
+    }
+
+    // Export job of the user
+    pub async fn fetch_export(
+        &self,
+        user_id: i64,
+        id: i64,
+    ) -> Result<DataExportResponse, AppError> {
+        match self.source.exports.fetch_export(user_id, id).await {
+            Ok(Some(export)) => Ok(export.into()),
+            Ok(None) => Err(AppError::NotFound("Export not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error fetching export: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // File of a finished export job of the user
+    pub async fn download_export(
+        &self,
+        user_id: i64,
+        id: i64,
+    ) -> Result<(ExportFormat, Download), AppError> {
+        let export = match self.source.exports.fetch_export(user_id, id).await {
+            Ok(Some(export)) => export,
+            Ok(None) => return Err(AppError::NotFound("Export not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error fetching export: {}", e);
+                return Err(AppError::Database(e));
+            }
+        };
+
+        let storage_key = export.storage_key.clone();
+        let export = DataExportResponse::from(export);
+        let Some(storage_key) = storage_key.filter(|_| export.status == ExportStatus::Ready) else {
+            return Err(AppError::Conflict(format!(
+                "Export is {}, not ready for download",
+                match export.status {
+                    ExportStatus::Failed => "failed",
+                    _ => "pending",
+                }
+            )));
+        };
+
+        match self.storage.download(&storage_key).await {
+            Ok(download) => Ok((export.format, download)),
+            Err(e) => Err(AppError::Internal(format!(
+                "Failed to read export file: {e}"
+            ))),
+        }
+    }
+}
+
+// Build the export file and store it, the export is marked failed on error
+async fn run_export_job(
+    source: ExportSource,
+    storage: Arc<dyn BlobStorage>,
+    export_id: i64,
+    user_id: i64,
+    format: ExportFormat,
+) {
+    match store_export(&source, storage.as_ref(), export_id, user_id, format).await {
+        Ok(()) => tracing::info!("Export {} of user {} ready", export_id, user_id),
+        Err(e) => {
+            tracing::warn!("Export {} of user {} failed: {}", export_id, user_id, e);
+            if let Err(e) = source.exports.fail_export(export_id).await {
+                tracing::warn!("Error failing export {}: {}", export_id, e);
+            }
+        }
+    }
+}
+
+async fn store_export(
+    source: &ExportSource,
+    storage: &dyn BlobStorage,
+    export_id: i64,
+    user_id: i64,
+    format: ExportFormat,
+) -> Result<(), String> {
+    let data = match format {
+        ExportFormat::Json => {
+            let mut data = Vec::new();
+            write_json(source, user_id, &mut data).await?;
+            data
+        }
+        ExportFormat::Csv => csv_archive(source, user_id).await?,
+    };
+
+    let storage_key = format!(
+        "exports/{user_id}/{}-{}",
+        generate_random_token(),
+        format.file_name()
+    );
+    let size_bytes = i64::try_from(data.len()).unwrap_or(i64::MAX);
+    storage
+        .put(&storage_key, Bytes::from(data), format.content_type())
+        .await?;
+
+    if let Err(e) = source
+        .exports
+        .complete_export(export_id, &storage_key, size_bytes)
+        .await
+    {
+        // The file would be kept by nothing
+        if let Err(e) = storage.delete(&storage_key).await {
+            tracing::warn!("Error removing export file {}: {}", storage_key, e);
+        }
+        return Err(e.to_string());
+    }
+    Ok(())
+}
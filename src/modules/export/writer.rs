@@ -0,0 +1,383 @@
+//! # `Export` Writer
+//! Files of the account exports. The JSON export is a single document holding the
+//! profile, preferences, lists and tags, then the todos read by pages and written as they
+//! are read. The CSV export is a zip archive with a table per kind of data.
+
+use std::io::{Cursor, Write};
+
+use axum::body::Body;
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+use time::OffsetDateTime;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio_util::io::ReaderStream;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use crate::modules::{
+    export::repository::ExportRepository,
+    list::interfaces::ListResponse,
+    preference::{interfaces::PreferencesResponse, repository::PreferenceRepository},
+    tag::{interfaces::TagResponse, repository::TagRepository},
+    todo::{
+        export::export_page,
+        interfaces::{TodoExportLine, TodoStatus},
+        repository::TodoRepository,
+    },
+    user::{interfaces::FetchUserResponse, repository::UserRepository},
+};
+use crate::utils::dates::{rfc3339, rfc3339_option};
+
+// Bytes buffered between the database reads and the response
+const EXPORT_BUFFER_BYTES: usize = 64 * 1024;
+
+// Repositories an export reads the account from
+pub struct ExportSource {
+    pub exports: ExportRepository,
+    pub users: UserRepository,
+    pub preferences: PreferenceRepository,
+    pub tags: TagRepository,
+    pub todos: TodoRepository,
+}
+
+impl ExportSource {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self {
+            exports: ExportRepository::new(pool.clone()),
+            users: UserRepository::new(pool.clone()),
+            preferences: PreferenceRepository::new(pool.clone()),
+            tags: TagRepository::new(pool.clone()),
+            todos: TodoRepository::new(pool),
+        }
+    }
+}
+
+// Account data written before the todos
+#[derive(Serialize)]
+struct AccountData {
+    #[serde(with = "rfc3339")]
+    exported_at: OffsetDateTime,
+    profile: FetchUserResponse,
+    preferences: PreferencesResponse,
+    lists: Vec<ListResponse>,
+    tags: Vec<TagResponse>,
+}
+
+// Preferences as a CSV row, the quiet hours split in two columns
+#[derive(Serialize)]
+struct PreferencesCsvRow {
+    timezone: String,
+    quiet_start: Option<String>,
+    quiet_end: Option<String>,
+    locale: String,
+    week_start: Option<String>,
+    time_format: Option<String>,
+}
+
+impl From<PreferencesResponse> for PreferencesCsvRow {
+    fn from(preferences: PreferencesResponse) -> Self {
+        let (quiet_start, quiet_end) = preferences
+            .quiet_hours
+            .map(|quiet_hours| (quiet_hours.start, quiet_hours.end))
+            .unzip();
+        Self {
+            timezone: preferences.timezone,
+            quiet_start,
+            quiet_end,
+            locale: preferences.locale,
+            week_start: preferences.week_start,
+            time_format: preferences.time_format,
+        }
+    }
+}
+
+// Todo as a CSV row, the tags and subtasks joined by `; `
+#[derive(Serialize)]
+struct TodoCsvRow {
+    id: i64,
+    list_id: Option<i64>,
+    title: String,
+    description: Option<String>,
+    status: TodoStatus,
+    #[serde(with = "rfc3339_option")]
+    due_at: Option<OffsetDateTime>,
+    due_label: Option<String>,
+    recurrence: Option<String>,
+    #[serde(with = "rfc3339_option")]
+    scheduled_for: Option<OffsetDateTime>,
+    archived: bool,
+    #[serde(with = "rfc3339_option")]
+    completed_at: Option<OffsetDateTime>,
+    #[serde(with = "rfc3339_option")]
+    created_at: Option<OffsetDateTime>,
+    #[serde(with = "rfc3339_option")]
+    updated_at: Option<OffsetDateTime>,
+    tags: String,
+    // Subtasks as `[x] title` once completed, `[ ] title` before
+    subtasks: String,
+}
+
+impl From<TodoExportLine> for TodoCsvRow {
+    fn from(line: TodoExportLine) -> Self {
+        let subtasks: Vec<String> = line
+            .subtasks
+            .iter()
+            .map(|item| {
+                let mark = if item.completed { 'x' } else { ' ' };
+                format!("[{mark}] {}", item.title)
+            })
+            .collect();
+        Self {
+            id: line.todo.id,
+            list_id: line.todo.list_id,
+            title: line.todo.title,
+            description: line.todo.description,
+            status: line.todo.status,
+            due_at: line.todo.due_at,
+            due_label: line.due_label,
+            recurrence: line.todo.recurrence,
+            scheduled_for: line.todo.scheduled_for,
+            archived: line.todo.archived,
+            completed_at: line.todo.completed_at,
+            created_at: line.todo.created_at,
+            updated_at: line.todo.updated_at,
+            tags: line.tags.join("; "),
+            subtasks: subtasks.join("; "),
+        }
+    }
+}
+
+// Everything of the account but the todos
+async fn account_data(source: &ExportSource, user_id: i64) -> Result<AccountData, String> {
+    let profile = source
+        .users
+        .fetch_user(user_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let preferences = source
+        .preferences
+        .fetch_preferences(user_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .map(PreferencesResponse::from)
+        .unwrap_or_default();
+    let lists = source
+        .exports
+        .list_lists(user_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tags = source
+        .tags
+        .list_tags(user_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(AccountData {
+        exported_at: OffsetDateTime::now_utc(),
+        profile,
+        preferences,
+        lists: lists.into_iter().map(ListResponse::from).collect(),
+        tags: tags.into_iter().map(TagResponse::from).collect(),
+    })
+}
+
+// Write the JSON export of the account, the todos page by page
+pub async fn write_json<W: AsyncWrite + Unpin>(
+    source: &ExportSource,
+    user_id: i64,
+    writer: &mut W,
+) -> Result<(), String> {
+    let account = account_data(source, user_id).await?;
+    let mut bytes = serde_json::to_vec(&account).map_err(|e| e.to_string())?;
+    // The todos are the last field, the document is reopened to append them
+    bytes.pop();
+    bytes.extend_from_slice(b",\"todos\":[");
+    writer.write_all(&bytes).await.map_err(|e| e.to_string())?;
+
+    let mut after_id = None;
+    let mut first = true;
+    loop {
+        let page = export_page(&source.todos, user_id, None, after_id)
+            .await
+            .map_err(|e| e.to_string())?;
+        let mut bytes = Vec::new();
+        for line in &page.lines {
+            if !first {
+                bytes.push(b',');
+            }
+            first = false;
+            serde_json::to_writer(&mut bytes, line).map_err(|e| e.to_string())?;
+        }
+        // Fails when the client went away
+        writer.write_all(&bytes).await.map_err(|e| e.to_string())?;
+
+        after_id = page.next_after;
+        if after_id.is_none() {
+            break;
+        }
+    }
+
+    writer.write_all(b"]}").await.map_err(|e| e.to_string())?;
+    writer.flush().await.map_err(|e| e.to_string())
+}
+
+// Body streaming the JSON export of the account. The status is sent before the account
+// is read, a failure ends the export early and is logged.
+pub fn json_body(source: ExportSource, user_id: i64) -> Body {
+    let (reader, mut writer) = tokio::io::duplex(EXPORT_BUFFER_BYTES);
+    tokio::spawn(async move {
+        let written = match write_json(&source, user_id, &mut writer).await {
+            Ok(()) => writer.shutdown().await.map_err(|e| e.to_string()),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = written {
+            tracing::warn!(
+                "Export of the account of user {} ended early: {}",
+                user_id,
+                e
+            );
+        }
+    });
+    Body::from_stream(ReaderStream::new(reader))
+}
+
+// CSV table of the rows, with a header row unless empty
+fn csv_table<T: Serialize>(rows: impl IntoIterator<Item = T>) -> Result<Vec<u8>, String> {
+    let mut table = csv::Writer::from_writer(Vec::new());
+    for row in rows {
+        table.serialize(row).map_err(|e| e.to_string())?;
+    }
+    table.into_inner().map_err(|e| e.to_string())
+}
+
+// Zip archive of the named files
+fn zip_archive(files: Vec<(&str, Vec<u8>)>) -> Result<Vec<u8>, String> {
+    let mut archive = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, content) in files {
+        archive
+            .start_file(name, options)
+            .map_err(|e| e.to_string())?;
+        archive.write_all(&content).map_err(|e| e.to_string())?;
+    }
+    archive
+        .finish()
+        .map(Cursor::into_inner)
+        .map_err(|e| e.to_string())
+}
+
+// Zip archive of the CSV tables of the account
+pub async fn csv_archive(source: &ExportSource, user_id: i64) -> Result<Vec<u8>, String> {
+    let account = account_data(source, user_id).await?;
+
+    let mut todos = csv::Writer::from_writer(Vec::new());
+    let mut after_id = None;
+    loop {
+        let page = export_page(&source.todos, user_id, None, after_id)
+            .await
+            .map_err(|e| e.to_string())?;
+        for line in page.lines {
+            todos
+                .serialize(TodoCsvRow::from(line))
+                .map_err(|e| e.to_string())?;
+        }
+
+        after_id = page.next_after;
+        if after_id.is_none() {
+            break;
+        }
+    }
+
+    zip_archive(vec![
+        ("profile.csv", csv_table([account.profile])?),
+        (
+            "preferences.csv",
+            csv_table([PreferencesCsvRow::from(account.preferences)])?,
+        ),
+        ("lists.csv", csv_table(account.lists)?),
+        ("tags.csv", csv_table(account.tags)?),
+        ("todos.csv", todos.into_inner().map_err(|e| e.to_string())?),
+    ])
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+    use crate::modules::{item::interfaces::ItemResponse, todo::interfaces::TodoResponse};
+
+    fn export_line() -> TodoExportLine {
+        let subtask = |id: i64, title: &str, completed: bool| ItemResponse {
+            id,
+            todo_id: 1,
+            title: title.to_string(),
+            completed,
+            position: 0,
+            created_at: None,
+            updated_at: None,
+        };
+        TodoExportLine {
+            todo: TodoResponse {
+                id: 1,
+                list_id: Some(2),
+                title: "Pay rent".to_string(),
+                description: Some("Before the 5th, \"urgent\"".to_string()),
+                status: TodoStatus::Backlog,
+                status_changed_at: None,
+                completed_at: None,
+                due_at: Some(time::macros::datetime!(2025-03-10 17:30 UTC)),
+                recurrence: None,
+                scheduled_for: None,
+                archived: false,
+                version: 1,
+                created_at: None,
+                updated_at: None,
+                deleted_at: None,
+                warnings: None,
+            },
+            tags: vec!["home".to_string(), "money".to_string()],
+            subtasks: vec![subtask(7, "Find IBAN", true), subtask(8, "Transfer", false)],
+            due_label: Some("10/03/2025 18:30 (Europe/Paris)".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_todo_csv_row() {
+        let table = csv_table([TodoCsvRow::from(export_line())]).unwrap();
+        let mut reader = csv::Reader::from_reader(table.as_slice());
+        let headers = reader.headers().unwrap().clone();
+        let record = reader.records().next().unwrap().unwrap();
+        let field = |name: &str| {
+            let index = headers.iter().position(|header| header == name).unwrap();
+            record.get(index).unwrap().to_string()
+        };
+
+        assert_eq!(field("title"), "Pay rent");
+        assert_eq!(field("description"), "Before the 5th, \"urgent\"");
+        assert_eq!(field("status"), "backlog");
+        assert_eq!(field("due_at"), "2025-03-10T17:30:00Z");
+        assert_eq!(field("scheduled_for"), "");
+        assert_eq!(field("tags"), "home; money");
+        assert_eq!(field("subtasks"), "[x] Find IBAN; [ ] Transfer");
+    }
+
+    #[test]
+    fn test_zip_archive() {
+        let archive = zip_archive(vec![
+            ("tags.csv", b"id,name\n1,home\n".to_vec()),
+            ("todos.csv", Vec::new()),
+        ])
+        .unwrap();
+
+        let mut archive = zip::ZipArchive::new(Cursor::new(archive)).unwrap();
+        assert_eq!(archive.len(), 2);
+        let mut tags = String::new();
+        archive
+            .by_name("tags.csv")
+            .unwrap()
+            .read_to_string(&mut tags)
+            .unwrap();
+        assert_eq!(tags, "id,name\n1,home\n");
+    }
+}
@@ -3,33 +3,80 @@
 //! This module defines the response structure for health check endpoints.
 
 use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
 use utoipa::ToSchema;
 
+use crate::utils::dates::rfc3339;
+use crate::workers::WorkerStatus;
+
 /// Response structure for health check endpoints
 ///
 /// This structure represents the JSON response returned by health check endpoints.
-/// It includes the current status of the application.
+/// It includes the current status of the application and, for the readiness check,
+/// the status of each dependency.
 #[derive(Serialize, Deserialize, ToSchema, Clone)]
 pub struct HealthResponse {
     /// Current health status of the application
     pub status: String,
+    /// Dependencies checked, only listed by `/health/ready`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<DependencyHealth>,
+}
+
+/// Result of the check of a dependency
+///
+/// Details of a failed check are logged, not returned.
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, PartialEq, Eq)]
+pub struct DependencyHealth {
+    /// Name of the dependency, e.g. `database`
+    pub name: String,
+    /// `Up` or `Down`
+    pub status: String,
+    /// Time the check took, in milliseconds
+    pub latency_ms: u64,
+}
+
+impl DependencyHealth {
+    /// Whether the dependency answered
+    pub fn is_up(&self) -> bool {
+        self.status == "Up"
+    }
 }
 
 #[derive(Serialize, Deserialize, ToSchema, Clone)]
 pub struct PingResponse {
     pub message: String,
-    pub timestamp: String,
+    #[serde(with = "rfc3339")]
+    pub timestamp: OffsetDateTime,
+}
+
+/// Response structure for the readiness endpoint
+///
+/// Ready once the database answers, startup checks passed and every
+/// background worker is running.
+#[derive(Serialize, ToSchema, Clone)]
+pub struct ReadinessResponse {
+    /// `Ready` or `Not Ready`
+    pub status: String,
+    /// Whether the database answered
+    pub database: bool,
+    /// Whether the startup checks passed and the workers were started
+    pub workers_started: bool,
+    /// Status of every background worker
+    pub workers: Vec<WorkerStatus>,
 }
 
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
+    use time::macros::datetime;
 
     #[test]
     fn test_health_response_creation() {
         let response = HealthResponse {
             status: "healthy".to_string(),
+            dependencies: Vec::new(),
         };
         assert_eq!(response.status, "healthy");
     }
@@ -38,9 +85,29 @@ mod tests {
     fn test_health_response_serialization() {
         let response = HealthResponse {
             status: "healthy".to_string(),
+            dependencies: Vec::new(),
         };
         let json = serde_json::to_string(&response).unwrap();
         assert!(json.contains("healthy"));
+        assert!(!json.contains("dependencies"));
+    }
+
+    #[test]
+    fn test_health_response_with_dependencies_serialization() {
+        let response = HealthResponse {
+            status: "Ready".to_string(),
+            dependencies: vec![DependencyHealth {
+                name: "database".to_string(),
+                status: "Up".to_string(),
+                latency_ms: 3,
+            }],
+        };
+        let json = serde_json::to_string(&response).unwrap();
+        assert_eq!(
+            json,
+            r#"{"status":"Ready","dependencies":[{"name":"database","status":"Up","latency_ms":3}]}"#
+        );
+        assert!(response.dependencies[0].is_up());
     }
 
     #[test]
@@ -48,23 +115,24 @@ mod tests {
         let json = r#"{"status":"healthy"}"#;
         let response: HealthResponse = serde_json::from_str(json).unwrap();
         assert_eq!(response.status, "healthy");
+        assert!(response.dependencies.is_empty());
     }
 
     #[test]
     fn test_ping_response_creation() {
         let response = PingResponse {
             message: "pong".to_string(),
-            timestamp: "2023-01-01T00:00:00Z".to_string(),
+            timestamp: datetime!(2023-01-01 0:00 UTC),
         };
         assert_eq!(response.message, "pong");
-        assert_eq!(response.timestamp, "2023-01-01T00:00:00Z");
+        assert_eq!(response.timestamp, datetime!(2023-01-01 0:00 UTC));
     }
 
     #[test]
     fn test_ping_response_serialization() {
         let response = PingResponse {
             message: "pong".to_string(),
-            timestamp: "2023-01-01T00:00:00Z".to_string(),
+            timestamp: datetime!(2023-01-01 0:00 UTC),
         };
         let json = serde_json::to_string(&response).unwrap();
         assert!(json.contains("pong"));
@@ -76,6 +144,6 @@ mod tests {
         let json = r#"{"message":"pong","timestamp":"2023-01-01T00:00:00Z"}"#;
         let response: PingResponse = serde_json::from_str(json).unwrap();
         assert_eq!(response.message, "pong");
-        assert_eq!(response.timestamp, "2023-01-01T00:00:00Z");
+        assert_eq!(response.timestamp, datetime!(2023-01-01 0:00 UTC));
     }
 }
@@ -5,6 +5,7 @@
 
 pub mod interfaces;
 pub mod routes;
+pub mod sampler;
 pub mod service;
 
 pub use routes::health_routes;
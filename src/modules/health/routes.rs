@@ -4,7 +4,9 @@
 
 use axum::{routing::get, Router};
 
-use crate::modules::health::service::{health_check, ping, test_login};
+use crate::modules::health::service::{
+    dependency_readiness, health_check, liveness, ping, readiness, test_login,
+};
 use crate::AppState;
 
 /// Creates and returns the health check routes
@@ -14,7 +16,10 @@ use crate::AppState;
 pub fn health_routes() -> Router<AppState> {
     Router::new()
         .route("/health", get(health_check))
+        .route("/health/live", get(liveness))
+        .route("/health/ready", get(dependency_readiness))
         .route("/ping", get(ping))
+        .route("/ready", get(readiness))
         .route("/test_login", get(test_login))
 }
 
@@ -0,0 +1,123 @@
+//! # Database Health Sampler
+//!
+//! A background task checks the database every few seconds. `/ping` and `/test_login`
+//! read its last result, so load balancers polling them don't take a pool connection
+//! on each call.
+
+use std::time::{Duration, Instant};
+
+use sqlx::{Pool, Postgres};
+use tokio::{sync::watch, time::MissedTickBehavior};
+
+// Samples older than this many intervals are stale, the sampler is stuck
+const MAX_SAMPLE_AGE_INTERVALS: u32 = 3;
+
+// Result of one check of the database
+#[derive(Clone, Copy, Debug)]
+struct Sample {
+    healthy: bool,
+    at: Instant,
+}
+
+// Last health of the database, shared by the clones
+#[derive(Clone)]
+pub struct DbHealth {
+    sample: watch::Receiver<Option<Sample>>,
+    max_age: Duration,
+}
+
+impl DbHealth {
+    // Whether the last check succeeded and isn't stale. False until the first check.
+    pub fn is_healthy(&self) -> bool {
+        self.sample
+            .borrow()
+            .is_some_and(|sample| sample.healthy && sample.at.elapsed() <= self.max_age)
+    }
+}
+
+// Check the database, a check taking longer than `timeout` fails
+pub async fn check_database(pool: &Pool<Postgres>, timeout: Duration) -> Result<(), String> {
+    match tokio::time::timeout(timeout, sqlx::query("SELECT 1").execute(pool)).await {
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err(format!("no answer after {}s", timeout.as_secs())),
+    }
+}
+
+// Check the database every `interval`, starting right away. Failures are logged when
+// the database becomes unavailable, not on every check.
+pub fn start_db_health_sampler(pool: Pool<Postgres>, interval: Duration) -> DbHealth {
+    let (sender, sample) = watch::channel(None);
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut was_healthy = true;
+        loop {
+            ticker.tick().await;
+            let healthy = match check_database(&pool, interval).await {
+                Ok(()) => {
+                    if !was_healthy {
+                        tracing::info!("Database connection restored");
+                    }
+                    true
+                }
+                Err(e) => {
+                    if was_healthy {
+                        tracing::error!("Database connection failed: {}", e);
+                    }
+                    false
+                }
+            };
+            was_healthy = healthy;
+            let sample = Sample {
+                healthy,
+                at: Instant::now(),
+            };
+            if sender.send(Some(sample)).is_err() {
+                break;
+            }
+        }
+    });
+
+    DbHealth {
+        sample,
+        max_age: interval * MAX_SAMPLE_AGE_INTERVALS,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn db_health(sample: Option<Sample>) -> DbHealth {
+        let (_sender, sample) = watch::channel(sample);
+        DbHealth {
+            sample,
+            max_age: Duration::from_secs(15),
+        }
+    }
+
+    #[test]
+    fn test_is_healthy() {
+        let now = Instant::now();
+        assert!(!db_health(None).is_healthy());
+        assert!(db_health(Some(Sample {
+            healthy: true,
+            at: now
+        }))
+        .is_healthy());
+        assert!(!db_health(Some(Sample {
+            healthy: false,
+            at: now
+        }))
+        .is_healthy());
+    }
+
+    #[test]
+    fn test_stale_sample() {
+        let Some(at) = Instant::now().checked_sub(Duration::from_secs(60)) else {
+            return;
+        };
+        assert!(!db_health(Some(Sample { healthy: true, at })).is_healthy());
+    }
+}
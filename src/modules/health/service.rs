@@ -2,15 +2,28 @@
 //!
 //! This module contains the business logic for health check operations.
 
-use axum::{extract::State, response::IntoResponse, Json};
-use chrono;
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use time::OffsetDateTime;
 
 use crate::{
     auth::Claims,
-    modules::health::interfaces::health_response::{HealthResponse, PingResponse},
+    modules::health::{
+        interfaces::health_response::{
+            DependencyHealth, HealthResponse, PingResponse, ReadinessResponse,
+        },
+        sampler::{check_database, DbHealth},
+    },
     AppState,
 };
 
+// Longest wait for a dependency of the readiness check
+const DEPENDENCY_TIMEOUT: Duration = Duration::from_secs(2);
+
 /// Health check endpoint handler
 ///
 /// Returns the current health status of the application.
@@ -27,40 +40,156 @@ use crate::{
 pub async fn health_check() -> impl IntoResponse {
     let response = HealthResponse {
         status: "Healthy".to_string(),
+        dependencies: Vec::new(),
     };
     (axum::http::StatusCode::OK, Json(response))
 }
 
+/// Liveness endpoint handler
+///
+/// Returns 200 as long as the process answers, without checking any
+/// dependency, so an orchestrator only restarts the process when it hangs.
+#[utoipa::path(
+    get,
+    path = "/health/live",
+    tag = "Health Check",
+    responses(
+        (status = 200, description = "Process is up", body = HealthResponse)
+    )
+)]
+pub async fn liveness() -> impl IntoResponse {
+    health_check().await
+}
+
+/// Dependency readiness endpoint handler
+///
+/// Checks every dependency the application can't serve without, today the
+/// database, and returns their status and latency. Returns 503 when one of
+/// them is down so the instance is taken out of the load balancer.
+#[utoipa::path(
+    get,
+    path = "/health/ready",
+    tag = "Health Check",
+    responses(
+        (status = 200, description = "Every dependency is up", body = HealthResponse),
+        (status = 503, description = "A dependency is down", body = HealthResponse)
+    )
+)]
+pub async fn dependency_readiness(State(state): State<AppState>) -> impl IntoResponse {
+    let dependencies = vec![
+        check_dependency(
+            "database",
+            check_database(&state.db_pool, DEPENDENCY_TIMEOUT),
+        )
+        .await,
+    ];
+    readiness_response(dependencies)
+}
+
+// Run the check of a dependency, timing it
+async fn check_dependency(
+    name: &str,
+    check: impl Future<Output = Result<(), String>>,
+) -> DependencyHealth {
+    let started = Instant::now();
+    let result = check.await;
+    let latency_ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+    let status = match result {
+        Ok(()) => "Up",
+        Err(e) => {
+            tracing::warn!("Readiness check of the {} failed: {}", name, e);
+            "Down"
+        }
+    };
+    DependencyHealth {
+        name: name.to_string(),
+        status: status.to_string(),
+        latency_ms,
+    }
+}
+
+// Overall readiness of the checked dependencies, ready when all of them are up
+fn readiness_response(dependencies: Vec<DependencyHealth>) -> (StatusCode, Json<HealthResponse>) {
+    let ready = dependencies.iter().all(DependencyHealth::is_up);
+    let (status, state) = if ready {
+        (StatusCode::OK, "Ready")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "Not Ready")
+    };
+    let response = HealthResponse {
+        status: state.to_string(),
+        dependencies,
+    };
+    (status, Json(response))
+}
+
 /// Ping endpoint handler
 /// Returns a simple "Pong" message along with the current server timestamp.
 /// This endpoint can be used to verify that the server is responsive and
-/// to check the current time on the server.
+/// to check the current time on the server. The database is not queried,
+/// its health comes from the last check of the background sampler.
 #[utoipa::path(
     get,
     path = "/ping",
     tag = "Health Check",
     responses(
-        (status = 200, description = "Ping successful", body = PingResponse)
+        (status = 200, description = "Ping successful", body = PingResponse),
+        (status = 503, description = "Database unavailable", body = PingResponse)
     )
 )]
 pub async fn ping(State(state): State<AppState>) -> impl IntoResponse {
-    match sqlx::query!("SELECT NOW()").fetch_one(&state.db_pool).await {
-        Ok(row) => {
-            let response = PingResponse {
-                message: "Pong".to_string(),
-                timestamp: format!("{:?}", row.now),
-            };
-            (axum::http::StatusCode::OK, Json(response))
-        }
+    sampled_ping(&state.db_health)
+}
+
+// Pong while the last check of the database succeeded, read from the sampler
+fn sampled_ping(db_health: &DbHealth) -> (StatusCode, Json<PingResponse>) {
+    let (status, message) = if db_health.is_healthy() {
+        (StatusCode::OK, "Pong")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "Database unavailable")
+    };
+    let response = PingResponse {
+        message: message.to_string(),
+        timestamp: OffsetDateTime::now_utc(),
+    };
+    (status, Json(response))
+}
+
+/// Readiness endpoint handler
+/// Returns 200 once the database answers and every background worker is
+/// running, 503 otherwise. The body lists the workers so partial failures
+/// are visible.
+#[utoipa::path(
+    get,
+    path = "/ready",
+    tag = "Health Check",
+    responses(
+        (status = 200, description = "Application ready", body = ReadinessResponse),
+        (status = 503, description = "Application not ready", body = ReadinessResponse)
+    )
+)]
+pub async fn readiness(State(state): State<AppState>) -> impl IntoResponse {
+    let database = match sqlx::query("SELECT 1").execute(&state.db_pool).await {
+        Ok(_) => true,
         Err(e) => {
             tracing::error!("Database connection failed: {}", e);
-            let response = PingResponse {
-                message: "Database unavailable".to_string(),
-                timestamp: chrono::Utc::now().to_rfc3339(),
-            };
-            (axum::http::StatusCode::SERVICE_UNAVAILABLE, Json(response))
+            false
         }
-    }
+    };
+    let ready = database && state.workers.is_ready();
+
+    let response = ReadinessResponse {
+        status: if ready { "Ready" } else { "Not Ready" }.to_string(),
+        database,
+        workers_started: state.workers.is_started(),
+        workers: state.workers.snapshot(),
+    };
+    let status = if ready {
+        axum::http::StatusCode::OK
+    } else {
+        axum::http::StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(response))
 }
 
 /// Test login endpoint handler
@@ -72,39 +201,27 @@ pub async fn ping(State(state): State<AppState>) -> impl IntoResponse {
     path = "/test_login",
     tag = "Health Check",
     responses(
-        (status = 200, description = "Ping successful", body = PingResponse)
+        (status = 200, description = "Ping successful", body = PingResponse),
+        (status = 503, description = "Database unavailable", body = PingResponse)
     ),
     security(
         ("jwt_auth" = [])
     )
 )]
-pub async fn test_login(state: State<AppState>, claims: Claims) -> impl IntoResponse {
+pub async fn test_login(State(state): State<AppState>, claims: Claims) -> impl IntoResponse {
     tracing::info!("User ID from token: {}", claims.user_id);
-
-    match sqlx::query!("SELECT NOW()").fetch_one(&state.db_pool).await {
-        Ok(row) => {
-            let response = PingResponse {
-                message: "Pong".to_string(),
-                timestamp: format!("{:?}", row.now),
-            };
-            (axum::http::StatusCode::OK, Json(response))
-        }
-        Err(e) => {
-            tracing::error!("Database connection failed: {}", e);
-            let response = PingResponse {
-                message: "Database unavailable".to_string(),
-                timestamp: chrono::Utc::now().to_rfc3339(),
-            };
-            (axum::http::StatusCode::SERVICE_UNAVAILABLE, Json(response))
-        }
-    }
+    sampled_ping(&state.db_health)
 }
 
 #[cfg(test)]
-#[allow(clippy::redundant_clone, clippy::single_char_pattern)]
+#[allow(
+    clippy::redundant_clone,
+    clippy::single_char_pattern,
+    clippy::unwrap_used
+)]
 mod tests {
     use super::*;
-    use axum::http::StatusCode;
+    use time::macros::datetime;
 
     #[tokio::test]
     async fn test_health_check() {
@@ -116,18 +233,54 @@ mod tests {
     fn test_health_response_creation() {
         let response = HealthResponse {
             status: "Healthy".to_string(),
+            dependencies: Vec::new(),
         };
         assert_eq!(response.status, "Healthy");
     }
 
+    #[tokio::test]
+    async fn test_liveness() {
+        let response = liveness().await.into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_check_dependency() {
+        let up = check_dependency("database", async { Ok(()) }).await;
+        assert!(up.is_up());
+        assert_eq!(up.name, "database");
+
+        let down = check_dependency("database", async { Err("refused".to_string()) }).await;
+        assert_eq!(down.status, "Down");
+    }
+
+    #[test]
+    fn test_readiness_response() {
+        let dependency = |status: &str| DependencyHealth {
+            name: "database".to_string(),
+            status: status.to_string(),
+            latency_ms: 1,
+        };
+
+        let (status, Json(response)) = readiness_response(vec![dependency("Up")]);
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(response.status, "Ready");
+
+        let (status, Json(response)) =
+            readiness_response(vec![dependency("Up"), dependency("Down")]);
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.status, "Not Ready");
+        assert_eq!(response.dependencies.len(), 2);
+    }
+
     #[test]
     fn test_ping_response_creation() {
         let response = PingResponse {
             message: "Pong".to_string(),
-            timestamp: "2023-01-01T00:00:00Z".to_string(),
+            timestamp: datetime!(2023-01-01 0:00 UTC),
         };
         assert_eq!(response.message, "Pong");
-        assert_eq!(response.timestamp, "2023-01-01T00:00:00Z");
+        assert_eq!(response.timestamp, datetime!(2023-01-01 0:00 UTC));
     }
 
     #[test]
@@ -135,32 +288,32 @@ mod tests {
         // Test error response creation
         let error_response = HealthResponse {
             status: "Unhealthy".to_string(),
+            dependencies: Vec::new(),
         };
         assert_eq!(error_response.status, "Unhealthy");
     }
 
     #[test]
     fn test_ping_timestamp_format() {
-        let timestamp = chrono::Utc::now().to_rfc3339();
         let response = PingResponse {
             message: "Pong".to_string(),
-            timestamp: timestamp.clone(),
+            timestamp: OffsetDateTime::now_utc(),
         };
-        assert!(response.timestamp.contains("T"));
-        // RFC3339 format may end with +00:00 instead of Z
-        assert!(
-            response.timestamp.contains("T")
-                && (response.timestamp.contains("Z") || response.timestamp.contains("+00:00"))
-        );
+        let json = serde_json::to_value(&response).unwrap();
+        let timestamp = json["timestamp"].as_str().unwrap();
+        // Timestamps are RFC3339 in UTC
+        assert!(timestamp.contains('T') && timestamp.ends_with('Z'));
     }
 
     #[test]
     fn test_health_service_responses() {
         let healthy = HealthResponse {
             status: "OK".to_string(),
+            dependencies: Vec::new(),
         };
         let unhealthy = HealthResponse {
             status: "ERROR".to_string(),
+            dependencies: Vec::new(),
         };
 
         assert_ne!(healthy.status, unhealthy.status);
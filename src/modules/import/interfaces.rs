@@ -0,0 +1,144 @@
+//! # `Import` Interfaces
+//! This module defines the sources of the imports, the todos read from their files and
+//! the report of an import
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Deserialize, IntoParams, Clone, Debug, Default)]
+pub struct ImportQuery {
+    /// Format of the uploaded file: `todoist` (JSON export of the tasks and projects),
+    /// `trello` (JSON export of a board) or `csv`
+    pub source: Option<String>,
+    /// Only report what the import would do, nothing is created
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+// Multipart body of an import, only documents the `file` field
+#[allow(dead_code)]
+#[derive(ToSchema)]
+pub struct UploadImportRequest {
+    #[schema(value_type = String, format = Binary)]
+    pub file: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportSource {
+    Todoist,
+    Trello,
+    Csv,
+}
+
+impl FromStr for ImportSource {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "todoist" => Ok(Self::Todoist),
+            "trello" => Ok(Self::Trello),
+            "csv" => Ok(Self::Csv),
+            _ => Err(format!(
+                "Unsupported import source: {value}, expected todoist, trello or csv"
+            )),
+        }
+    }
+}
+
+// Checklist item of an imported todo
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImportedSubtask {
+    pub title: String,
+    pub completed: bool,
+}
+
+// Todo read from an imported file, `row` is its position in the file from 1
+#[derive(Clone, Debug, Default)]
+pub struct ImportedTodo {
+    pub row: usize,
+    pub title: String,
+    pub description: Option<String>,
+    // Name of the list, created when missing
+    pub list: Option<String>,
+    pub tags: Vec<String>,
+    pub due_at: Option<OffsetDateTime>,
+    pub completed: bool,
+    pub archived: bool,
+    pub subtasks: Vec<ImportedSubtask>,
+}
+
+// Row of an imported file that could not be read
+#[derive(Clone, Debug)]
+pub struct ImportRowError {
+    pub row: usize,
+    pub title: Option<String>,
+    pub message: String,
+}
+
+// Content of an imported file
+#[derive(Debug, Default)]
+pub struct ParsedImport {
+    pub todos: Vec<ImportedTodo>,
+    pub errors: Vec<ImportRowError>,
+}
+
+// Existing todo of the workspace, imports skip the todos already there
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct ExistingTodoRow {
+    pub list_name: Option<String>,
+    pub title: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportOutcome {
+    Created,
+    Skipped,
+    Errored,
+}
+
+// What happened to a row of the file, what would happen in a dry run
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ImportRowResponse {
+    // Position in the file from 1, the header of a CSV file excluded
+    pub row: usize,
+    pub title: Option<String>,
+    pub outcome: ImportOutcome,
+    // Created todo, not set in a dry run
+    pub todo_id: Option<i64>,
+    // Why the row was skipped or errored
+    pub message: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ImportReport {
+    pub source: ImportSource,
+    pub dry_run: bool,
+    pub created: usize,
+    pub skipped: usize,
+    pub errored: usize,
+    // Lists created for the imported todos, or to be created in a dry run
+    pub lists_created: Vec<String>,
+    pub rows: Vec<ImportRowResponse>,
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_source() {
+        assert_eq!(
+            " Todoist".parse::<ImportSource>().unwrap(),
+            ImportSource::Todoist
+        );
+        assert_eq!("CSV".parse::<ImportSource>().unwrap(), ImportSource::Csv);
+        assert!("asana".parse::<ImportSource>().is_err());
+        assert!("".parse::<ImportSource>().is_err());
+    }
+}
@@ -0,0 +1,10 @@
+//! # `Import` Mod
+//! Import imports for the todos exported from other apps
+
+pub mod interfaces;
+pub mod parsers;
+pub mod repository;
+pub mod routes;
+pub mod service;
+
+pub use routes::import_routes;
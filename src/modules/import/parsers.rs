@@ -0,0 +1,575 @@
+//! # `Import` Parsers
+//! Adapters reading the todos of the imported files. Each source maps to the same todos:
+//! Todoist projects and Trello lists become lists, labels become tags, Todoist sub-tasks
+//! and Trello checklists become checklist items. A file that can't be read at all fails
+//! the import, a row that can't be read is reported and the others are imported.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Deserialize;
+use time::{
+    format_description::{well_known::Rfc3339, FormatItem},
+    macros::format_description,
+    Date, OffsetDateTime, PrimitiveDateTime,
+};
+
+use crate::modules::import::interfaces::{
+    ImportRowError, ImportSource, ImportedSubtask, ImportedTodo, ParsedImport,
+};
+
+const DATE_TIME: &[FormatItem<'_>] =
+    format_description!("[year]-[month]-[day]T[hour]:[minute]:[second]");
+const DATE_TIME_MINUTES: &[FormatItem<'_>] =
+    format_description!("[year]-[month]-[day]T[hour]:[minute]");
+const DATE: &[FormatItem<'_>] = format_description!("[year]-[month]-[day]");
+
+// Read the todos of a file exported from the source
+pub fn parse_import(source: ImportSource, data: &[u8]) -> Result<ParsedImport, String> {
+    match source {
+        ImportSource::Todoist => parse_todoist(data),
+        ImportSource::Trello => parse_trello(data),
+        ImportSource::Csv => parse_csv(data),
+    }
+}
+
+// Due date of an imported todo: RFC 3339, or a date and time without offset read as UTC,
+// or a day read as its midnight in UTC
+fn parse_due(value: &str) -> Result<OffsetDateTime, String> {
+    let value = value.trim().replacen(' ', "T", 1);
+    if let Ok(due_at) = OffsetDateTime::parse(&value, &Rfc3339) {
+        return Ok(due_at);
+    }
+    PrimitiveDateTime::parse(&value, DATE_TIME)
+        .or_else(|_| PrimitiveDateTime::parse(&value, DATE_TIME_MINUTES))
+        .or_else(|_| Date::parse(&value, DATE).map(Date::midnight))
+        .map(PrimitiveDateTime::assume_utc)
+        .map_err(|_| format!("Invalid due date: {value}"))
+}
+
+// Trimmed text, `None` when blank
+fn non_blank(value: Option<&str>) -> Option<String> {
+    value
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+// Ids are strings in the recent exports and numbers in the older ones
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(untagged)]
+enum ExternalId {
+    Text(String),
+    Number(i64),
+}
+
+#[derive(Deserialize)]
+struct TodoistExport {
+    #[serde(default)]
+    projects: Vec<TodoistProject>,
+    #[serde(default, alias = "tasks")]
+    items: Vec<TodoistTask>,
+}
+
+#[derive(Deserialize)]
+struct TodoistProject {
+    id: ExternalId,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct TodoistTask {
+    id: ExternalId,
+    content: Option<String>,
+    description: Option<String>,
+    project_id: Option<ExternalId>,
+    parent_id: Option<ExternalId>,
+    #[serde(default, alias = "is_completed")]
+    checked: bool,
+    #[serde(default)]
+    is_deleted: bool,
+    #[serde(default)]
+    labels: Vec<String>,
+    due: Option<TodoistDue>,
+}
+
+#[derive(Deserialize)]
+struct TodoistDue {
+    date: Option<String>,
+    datetime: Option<String>,
+}
+
+// Todoist export of the tasks and projects, as returned by its API. Sub-tasks become
+// checklist items of their top task, deleted tasks are left out.
+fn parse_todoist(data: &[u8]) -> Result<ParsedImport, String> {
+    let export: TodoistExport =
+        serde_json::from_slice(data).map_err(|e| format!("Invalid Todoist export: {e}"))?;
+    let projects: HashMap<ExternalId, String> = export
+        .projects
+        .into_iter()
+        .map(|project| (project.id, project.name))
+        .collect();
+    let parents: HashMap<ExternalId, ExternalId> = export
+        .items
+        .iter()
+        .filter_map(|task| Some((task.id.clone(), task.parent_id.clone()?)))
+        .collect();
+    let task_ids: HashSet<&ExternalId> = export.items.iter().map(|task| &task.id).collect();
+    // Top task of a sub-task, whatever its depth
+    let root = |id: &ExternalId| {
+        let mut root = id.clone();
+        let mut depth = 0;
+        while let Some(parent) = parents
+            .get(&root)
+            .filter(|parent| task_ids.contains(parent))
+        {
+            root = parent.clone();
+            depth += 1;
+            // Parents looping on each other
+            if depth > parents.len() {
+                break;
+            }
+        }
+        root
+    };
+
+    let mut parsed = ParsedImport::default();
+    let mut subtasks: HashMap<ExternalId, Vec<ImportedSubtask>> = HashMap::new();
+    let mut todo_ids = Vec::new();
+    for (index, task) in export.items.iter().enumerate() {
+        let row = index + 1;
+        if task.is_deleted {
+            continue;
+        }
+        let title = non_blank(task.content.as_deref());
+        let Some(title) = title else {
+            parsed.errors.push(ImportRowError {
+                row,
+                title: None,
+                message: "Missing task content".to_string(),
+            });
+            continue;
+        };
+
+        let root = root(&task.id);
+        if root != task.id {
+            subtasks.entry(root).or_default().push(ImportedSubtask {
+                title,
+                completed: task.checked,
+            });
+            continue;
+        }
+
+        let due = task
+            .due
+            .as_ref()
+            .and_then(|due| due.datetime.as_deref().or(due.date.as_deref()));
+        let due_at = match due.map(parse_due).transpose() {
+            Ok(due_at) => due_at,
+            Err(message) => {
+                parsed.errors.push(ImportRowError {
+                    row,
+                    title: Some(title),
+                    message,
+                });
+                continue;
+            }
+        };
+
+        todo_ids.push(task.id.clone());
+        parsed.todos.push(ImportedTodo {
+            row,
+            title,
+            description: non_blank(task.description.as_deref()),
+            list: task
+                .project_id
+                .as_ref()
+                .and_then(|project_id| projects.get(project_id))
+                .cloned(),
+            tags: task.labels.clone(),
+            due_at,
+            completed: task.checked,
+            archived: false,
+            subtasks: Vec::new(),
+        });
+    }
+
+    for (todo, id) in parsed.todos.iter_mut().zip(todo_ids) {
+        todo.subtasks = subtasks.remove(&id).unwrap_or_default();
+    }
+    Ok(parsed)
+}
+
+#[derive(Deserialize)]
+struct TrelloBoard {
+    #[serde(default)]
+    lists: Vec<TrelloList>,
+    #[serde(default)]
+    cards: Vec<TrelloCard>,
+    #[serde(default)]
+    checklists: Vec<TrelloChecklist>,
+}
+
+#[derive(Deserialize)]
+struct TrelloList {
+    id: String,
+    name: String,
+    #[serde(default)]
+    closed: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrelloCard {
+    id: String,
+    name: Option<String>,
+    desc: Option<String>,
+    id_list: Option<String>,
+    #[serde(default)]
+    closed: bool,
+    due: Option<String>,
+    #[serde(default)]
+    due_complete: bool,
+    #[serde(default)]
+    labels: Vec<TrelloLabel>,
+}
+
+#[derive(Deserialize)]
+struct TrelloLabel {
+    name: Option<String>,
+    // Labels without a name are shown by their color
+    color: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrelloChecklist {
+    id_card: String,
+    #[serde(default)]
+    check_items: Vec<TrelloCheckItem>,
+}
+
+#[derive(Deserialize)]
+struct TrelloCheckItem {
+    name: String,
+    #[serde(default)]
+    state: String,
+    #[serde(default)]
+    pos: f64,
+}
+
+// Trello board export. Cards of the closed lists and closed cards are imported archived,
+// a card is done once its due date is marked complete.
+fn parse_trello(data: &[u8]) -> Result<ParsedImport, String> {
+    let board: TrelloBoard =
+        serde_json::from_slice(data).map_err(|e| format!("Invalid Trello export: {e}"))?;
+    let lists: HashMap<&str, &TrelloList> = board
+        .lists
+        .iter()
+        .map(|list| (list.id.as_str(), list))
+        .collect();
+    let mut check_items: HashMap<&str, Vec<&TrelloCheckItem>> = HashMap::new();
+    for checklist in &board.checklists {
+        check_items
+            .entry(checklist.id_card.as_str())
+            .or_default()
+            .extend(&checklist.check_items);
+    }
+
+    let mut parsed = ParsedImport::default();
+    for (index, card) in board.cards.iter().enumerate() {
+        let row = index + 1;
+        let Some(title) = non_blank(card.name.as_deref()) else {
+            parsed.errors.push(ImportRowError {
+                row,
+                title: None,
+                message: "Missing card name".to_string(),
+            });
+            continue;
+        };
+        let due_at = match card.due.as_deref().map(parse_due).transpose() {
+            Ok(due_at) => due_at,
+            Err(message) => {
+                parsed.errors.push(ImportRowError {
+                    row,
+                    title: Some(title),
+                    message,
+                });
+                continue;
+            }
+        };
+
+        let list = card.id_list.as_deref().and_then(|id| lists.get(id));
+        let mut items = check_items.remove(card.id.as_str()).unwrap_or_default();
+        items.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+        parsed.todos.push(ImportedTodo {
+            row,
+            title,
+            description: non_blank(card.desc.as_deref()),
+            list: list.map(|list| list.name.clone()),
+            tags: card
+                .labels
+                .iter()
+                .filter_map(|label| {
+                    non_blank(label.name.as_deref()).or_else(|| non_blank(label.color.as_deref()))
+                })
+                .collect(),
+            due_at,
+            completed: card.due_complete,
+            archived: card.closed || list.is_some_and(|list| list.closed),
+            subtasks: items
+                .into_iter()
+                .map(|item| ImportedSubtask {
+                    title: item.name.trim().to_string(),
+                    completed: item.state == "complete",
+                })
+                .collect(),
+        });
+    }
+    Ok(parsed)
+}
+
+// Column of a CSV file by any of its accepted headers
+fn column(headers: &csv::StringRecord, names: &[&str]) -> Option<usize> {
+    headers
+        .iter()
+        .position(|header| names.contains(&header.trim().to_ascii_lowercase().as_str()))
+}
+
+// Whether a CSV cell reads as a yes
+fn is_yes(value: &str) -> bool {
+    matches!(
+        value.trim().to_ascii_lowercase().as_str(),
+        "true" | "yes" | "y" | "1" | "x" | "done" | "completed"
+    )
+}
+
+// Checklist item of a CSV cell, `[x] title` once completed
+fn csv_subtask(value: &str) -> Option<ImportedSubtask> {
+    let value = value.trim();
+    let (title, completed) = value
+        .strip_prefix("[x]")
+        .or_else(|| value.strip_prefix("[X]"))
+        .map_or_else(
+            || (value.strip_prefix("[ ]").unwrap_or(value), false),
+            |title| (title, true),
+        );
+    non_blank(Some(title)).map(|title| ImportedSubtask { title, completed })
+}
+
+// Generic CSV with a header row. Only the `title` column is required, the columns of the
+// CSV export of the accounts are read back: tags and subtasks are separated by `;`.
+fn parse_csv(data: &[u8]) -> Result<ParsedImport, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(data);
+    let headers = reader
+        .headers()
+        .map_err(|e| format!("Invalid CSV file: {e}"))?
+        .clone();
+    let Some(title_column) = column(&headers, &["title", "name", "content", "task"]) else {
+        return Err("CSV file must have a title column".to_string());
+    };
+    let description_column = column(&headers, &["description", "desc", "notes"]);
+    let list_column = column(&headers, &["list", "project"]);
+    let tags_column = column(&headers, &["tags", "labels"]);
+    let due_column = column(&headers, &["due_at", "due", "due_date"]);
+    let status_column = column(&headers, &["status"]);
+    let completed_column = column(&headers, &["completed", "done"]);
+    let archived_column = column(&headers, &["archived"]);
+    let subtasks_column = column(&headers, &["subtasks", "checklist"]);
+
+    let mut parsed = ParsedImport::default();
+    for (index, record) in reader.records().enumerate() {
+        let row = index + 1;
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                parsed.errors.push(ImportRowError {
+                    row,
+                    title: None,
+                    message: format!("Invalid CSV row: {e}"),
+                });
+                continue;
+            }
+        };
+        let cell = |column: Option<usize>| column.and_then(|column| record.get(column));
+
+        let Some(title) = non_blank(cell(Some(title_column))) else {
+            parsed.errors.push(ImportRowError {
+                row,
+                title: None,
+                message: "Missing title".to_string(),
+            });
+            continue;
+        };
+        let due_at = match non_blank(cell(due_column))
+            .as_deref()
+            .map(parse_due)
+            .transpose()
+        {
+            Ok(due_at) => due_at,
+            Err(message) => {
+                parsed.errors.push(ImportRowError {
+                    row,
+                    title: Some(title),
+                    message,
+                });
+                continue;
+            }
+        };
+
+        parsed.todos.push(ImportedTodo {
+            row,
+            title,
+            description: non_blank(cell(description_column)),
+            list: non_blank(cell(list_column)),
+            tags: cell(tags_column)
+                .map(|tags| {
+                    tags.split([';', ','])
+                        .filter_map(|tag| non_blank(Some(tag)))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            due_at,
+            completed: cell(status_column).is_some_and(is_yes)
+                || cell(completed_column).is_some_and(is_yes),
+            archived: cell(archived_column).is_some_and(is_yes),
+            subtasks: cell(subtasks_column)
+                .map(|subtasks| subtasks.split(';').filter_map(csv_subtask).collect())
+                .unwrap_or_default(),
+        });
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    #[test]
+    fn test_parse_due() {
+        assert_eq!(
+            parse_due("2025-03-10T18:30:00+01:00").unwrap(),
+            datetime!(2025-03-10 17:30 UTC)
+        );
+        assert_eq!(
+            parse_due("2025-03-10T18:30:00").unwrap(),
+            datetime!(2025-03-10 18:30 UTC)
+        );
+        assert_eq!(
+            parse_due("2025-03-10 18:30").unwrap(),
+            datetime!(2025-03-10 18:30 UTC)
+        );
+        assert_eq!(
+            parse_due("2025-03-10").unwrap(),
+            datetime!(2025-03-10 0:00 UTC)
+        );
+        assert!(parse_due("next monday").is_err());
+    }
+
+    #[test]
+    fn test_parse_todoist() {
+        let parsed = parse_todoist(
+            br#"{
+                "projects": [{"id": "10", "name": "Home"}],
+                "items": [
+                    {"id": "1", "content": "Pay rent", "project_id": "10", "labels": ["money"],
+                     "due": {"date": "2025-03-10"}},
+                    {"id": "2", "content": "Find IBAN", "parent_id": "1", "checked": true},
+                    {"id": "3", "content": "Old task", "is_deleted": true},
+                    {"id": 4, "content": "  "},
+                    {"id": 5, "content": "Call mom", "is_completed": true}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(parsed.todos.len(), 2);
+        let rent = &parsed.todos[0];
+        assert_eq!(rent.row, 1);
+        assert_eq!(rent.list.as_deref(), Some("Home"));
+        assert_eq!(rent.tags, vec!["money"]);
+        assert_eq!(rent.due_at, Some(datetime!(2025-03-10 0:00 UTC)));
+        assert_eq!(
+            rent.subtasks,
+            vec![ImportedSubtask {
+                title: "Find IBAN".to_string(),
+                completed: true
+            }]
+        );
+        assert_eq!(parsed.todos[1].row, 5);
+        assert!(parsed.todos[1].completed);
+
+        assert_eq!(parsed.errors.len(), 1);
+        assert_eq!(parsed.errors[0].row, 4);
+        assert!(parse_todoist(b"[1, 2]").is_err());
+    }
+
+    #[test]
+    fn test_parse_trello() {
+        let parsed = parse_trello(
+            br#"{
+                "name": "Board",
+                "lists": [{"id": "l1", "name": "Doing", "closed": false},
+                          {"id": "l2", "name": "Old", "closed": true}],
+                "cards": [
+                    {"id": "c1", "name": "Pay rent", "desc": "", "idList": "l1",
+                     "due": "2025-03-10T17:30:00.000Z", "dueComplete": true,
+                     "labels": [{"name": "money", "color": "green"}, {"name": "", "color": "red"}]},
+                    {"id": "c2", "name": "Archive me", "idList": "l2", "due": "soon"},
+                    {"id": "c3", "name": "Plan trip", "idList": "l2"}
+                ],
+                "checklists": [{"idCard": "c1", "checkItems": [
+                    {"name": "Transfer", "state": "incomplete", "pos": 2},
+                    {"name": "Find IBAN", "state": "complete", "pos": 1}
+                ]}]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(parsed.todos.len(), 2);
+        let rent = &parsed.todos[0];
+        assert_eq!(rent.list.as_deref(), Some("Doing"));
+        assert!(rent.description.is_none());
+        assert!(rent.completed);
+        assert!(!rent.archived);
+        assert_eq!(rent.tags, vec!["money", "red"]);
+        assert_eq!(rent.subtasks[0].title, "Find IBAN");
+        assert!(rent.subtasks[0].completed);
+        assert!(!rent.subtasks[1].completed);
+        assert!(parsed.todos[1].archived);
+
+        assert_eq!(parsed.errors[0].row, 2);
+        assert_eq!(parsed.errors[0].title.as_deref(), Some("Archive me"));
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let parsed = parse_csv(
+            b"Title,List,Tags,due_at,status,subtasks\n\
+              Pay rent,Home,home; money,2025-03-10,done,[x] Find IBAN; [ ] Transfer\n\
+              ,Home,,,,\n\
+              Call mom,,,tomorrow,,\n\
+              Plan trip\n",
+        )
+        .unwrap();
+
+        assert_eq!(parsed.todos.len(), 2);
+        let rent = &parsed.todos[0];
+        assert_eq!(rent.list.as_deref(), Some("Home"));
+        assert_eq!(rent.tags, vec!["home", "money"]);
+        assert!(rent.completed);
+        assert_eq!(rent.subtasks.len(), 2);
+        assert!(rent.subtasks[0].completed);
+        assert_eq!(rent.subtasks[1].title, "Transfer");
+        assert_eq!(parsed.todos[1].row, 4);
+        assert!(parsed.todos[1].list.is_none());
+
+        let rows: Vec<usize> = parsed.errors.iter().map(|error| error.row).collect();
+        assert_eq!(rows, vec![2, 3]);
+        assert!(parse_csv(b"list\nHome\n").is_err());
+    }
+}
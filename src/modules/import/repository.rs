@@ -0,0 +1,144 @@
+//! # `Import` Repository
+//! This module defines the import repository, creating each imported todo with its tags
+//! and checklist items in a single transaction.
+
+use sqlx::{Error, Pool, Postgres};
+
+use crate::modules::{
+    common::to_db_id,
+    import::interfaces::{ExistingTodoRow, ImportedTodo},
+    list::{interfaces::ListRow, repository::LIST_COLUMNS},
+};
+use crate::telemetry::observe_query;
+
+pub struct ImportRepository {
+    pool: Pool<Postgres>,
+}
+
+impl ImportRepository {
+    // Constructor
+    pub const fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Lists of the user in the workspace, oldest first
+    pub async fn list_lists(&self, user_id: i64, workspace_id: i64) -> Result<Vec<ListRow>, Error> {
+        observe_query("import.list_lists", async move {
+            let query = format!(
+                "SELECT {LIST_COLUMNS} FROM lists
+                 WHERE user_id = $1 AND workspace_id = $2
+                 ORDER BY id"
+            );
+
+            sqlx::query_as::<_, ListRow>(&query)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Titles of the todos of the user in the workspace with the name of their list, the
+    // trash excluded
+    pub async fn list_existing(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+    ) -> Result<Vec<ExistingTodoRow>, Error> {
+        observe_query("import.list_existing", async move {
+            sqlx::query_as::<_, ExistingTodoRow>(
+                "SELECT l.name AS list_name, t.title
+                 FROM todos t
+                 LEFT JOIN lists l ON l.id = t.list_id
+                 WHERE t.user_id = $1 AND t.workspace_id = $2 AND t.deleted_at IS NULL",
+            )
+            .bind(to_db_id(user_id)?)
+            .bind(to_db_id(workspace_id)?)
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Create an imported todo in the list, done when completed, with its tags and
+    // checklist items. Returns the id of the todo.
+    pub async fn import_todo(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        list_id: Option<i64>,
+        todo: &ImportedTodo,
+    ) -> Result<i32, Error> {
+        observe_query("import.import_todo", async move {
+            let user_id = to_db_id(user_id)?;
+            let mut tx = self.pool.begin().await?;
+
+            let status = if todo.completed { "done" } else { "backlog" };
+            let id = sqlx::query_scalar::<_, i32>(
+                "INSERT INTO todos (user_id, workspace_id, list_id, title, description, due_at,
+                    status, completed_at, archived)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7,
+                    CASE WHEN $7 = 'done' THEN CURRENT_TIMESTAMP END, $8)
+                 RETURNING id",
+            )
+            .bind(user_id)
+            .bind(to_db_id(workspace_id)?)
+            .bind(list_id.map(to_db_id).transpose()?)
+            .bind(&todo.title)
+            .bind(&todo.description)
+            .bind(todo.due_at)
+            .bind(status)
+            .bind(todo.archived)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            if !todo.tags.is_empty() {
+                sqlx::query(
+                    "WITH imported AS (
+                        INSERT INTO tags (user_id, name)
+                        SELECT $1, name FROM UNNEST($3::TEXT[]) AS t(name)
+                        ON CONFLICT (user_id, name) DO UPDATE SET name = EXCLUDED.name
+                        RETURNING id
+                     )
+                     INSERT INTO todo_tags (todo_id, tag_id)
+                     SELECT $2, id FROM imported
+                     ON CONFLICT DO NOTHING",
+                )
+                .bind(user_id)
+                .bind(id)
+                .bind(&todo.tags)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            if !todo.subtasks.is_empty() {
+                let titles: Vec<&str> = todo
+                    .subtasks
+                    .iter()
+                    .map(|subtask| subtask.title.as_str())
+                    .collect();
+                let completed: Vec<bool> = todo
+                    .subtasks
+                    .iter()
+                    .map(|subtask| subtask.completed)
+                    .collect();
+                sqlx::query(
+                    "INSERT INTO todo_items (todo_id, title, completed, position)
+                     SELECT $1, item.title, item.completed, (item.position - 1)::INTEGER
+                     FROM UNNEST($2::TEXT[], $3::BOOLEAN[])
+                        WITH ORDINALITY AS item(title, completed, position)",
+                )
+                .bind(id)
+                .bind(titles)
+                .bind(completed)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            tx.commit().await?;
+            Ok(id)
+        })
+        .await
+    }
+}
@@ -0,0 +1,104 @@
+//! # `Import` Routes
+//! This module defines the HTTP routes importing todos from other apps.
+
+use axum::extract::{DefaultBodyLimit, Multipart, Query};
+use axum::routing::post;
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Router};
+
+use crate::modules::attachment::service::read_file;
+use crate::modules::audit::{repository::AuditRepository, service::AuditService};
+use crate::modules::common::{respond, AppError, ErrorResponse};
+use crate::modules::import::{
+    interfaces::{ImportQuery, ImportReport, ImportSource, UploadImportRequest},
+    repository::ImportRepository,
+    service::ImportService,
+};
+use crate::modules::list::{repository::ListRepository, service::ListService};
+use crate::modules::moderation::{repository::ModerationRepository, service::ModerationService};
+use crate::modules::todo::repository::TodoRepository;
+use crate::modules::workspace::{context::WorkspaceContext, repository::WorkspaceRepository};
+use crate::utils::concurrency_limit::{limit_concurrency, HEAVY_REQUEST_CONCURRENCY};
+use crate::AppState;
+
+// Largest imported file, 10 MiB
+const MAX_IMPORT_BYTES: usize = 10 * 1024 * 1024;
+
+// Room left for the multipart boundaries and headers around the file
+const MULTIPART_OVERHEAD_BYTES: usize = 64 * 1024;
+
+// Creates and returns the import routes
+pub fn import_routes() -> Router<AppState> {
+    Router::new().route(
+        "/import",
+        limit_concurrency(post(import_route), HEAVY_REQUEST_CONCURRENCY).layer(
+            DefaultBodyLimit::max(MAX_IMPORT_BYTES + MULTIPART_OVERHEAD_BYTES),
+        ),
+    )
+}
+
+fn import_service(app_state: &AppState) -> ImportService {
+    ImportService::new(
+        ImportRepository::new(app_state.db_pool.clone()),
+        TodoRepository::new(app_state.db_pool.clone()),
+        WorkspaceRepository::new(app_state.db_pool.clone()),
+        ListService::new(
+            ListRepository::new(app_state.db_pool.clone()),
+            app_state.view_cache.clone(),
+            ModerationService::new(
+                ModerationRepository::new(app_state.db_pool.clone()),
+                app_state.moderator.clone(),
+                AuditService::new(AuditRepository::new(app_state.db_pool.clone())),
+            ),
+        ),
+        app_state.view_cache.clone(),
+    )
+}
+
+// Import Route
+#[utoipa::path(
+    post,
+    path = "/import",
+    tag = "Import",
+    params(ImportQuery),
+    request_body(content = UploadImportRequest, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "Report of the import: the rows created, skipped as already there and errored, with the lists created for them", body = ImportReport),
+        (status = 400, description = "Unsupported source, file too large or not readable", body = ErrorResponse),
+        (status = 503, description = "Too many concurrent imports", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn import_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Query(query): Query<ImportQuery>,
+    mut multipart: Multipart,
+) -> impl IntoResponse {
+    let source = match query.source.as_deref().map(str::parse::<ImportSource>) {
+        Some(Ok(source)) => source,
+        Some(Err(e)) => return AppError::Validation(e).into_response(),
+        None => {
+            return AppError::Validation("source is required".to_string()).into_response();
+        }
+    };
+    let file = match read_file(&mut multipart, MAX_IMPORT_BYTES).await {
+        Ok(file) => file,
+        Err(error) => return error.into_response(),
+    };
+
+    respond(
+        StatusCode::OK,
+        import_service(&app_state)
+            .import_file(
+                workspace.user_id,
+                workspace.workspace_id,
+                source,
+                &file.data,
+                query.dry_run,
+            )
+            .await,
+    )
+    .into_response()
+}
@@ -0,0 +1,426 @@
+//! # `Import` Service
+//! Import of the todos exported from other apps into a workspace. The lists named by the
+//! file are matched by name regardless of case and created when missing, through the
+//! list service so their names are moderated. A todo whose title is already in the same
+//! list is skipped, so importing a file twice creates nothing the second time.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::modules::{
+    common::AppError,
+    import::{
+        interfaces::{ImportOutcome, ImportReport, ImportRowResponse, ImportSource, ImportedTodo},
+        parsers::parse_import,
+        repository::ImportRepository,
+    },
+    list::{interfaces::ListRequest, service::ListService},
+    todo::{
+        cache::ViewCache, interfaces::TodoStatus, repository::TodoRepository,
+        service::WIP_LIMIT_EXCEEDED,
+    },
+    workspace::{permissions::WorkspacePermission, repository::WorkspaceRepository},
+};
+
+// Todos of a single import
+const MAX_IMPORT_TODOS: usize = 5_000;
+
+// Lengths of the database columns
+const MAX_TITLE_LENGTH: usize = 255;
+const MAX_TAG_NAME_LENGTH: usize = 64;
+
+// Key matching the todos already imported, by list and title regardless of case
+fn todo_key(list: Option<&str>, title: &str) -> (Option<String>, String) {
+    (list.map(str::to_lowercase), title.to_lowercase())
+}
+
+// Check the lengths of the imported todo, its tags are deduplicated
+fn validate_todo(todo: &mut ImportedTodo) -> Result<(), String> {
+    if todo.title.chars().count() > MAX_TITLE_LENGTH {
+        return Err(format!(
+            "Title must have at most {MAX_TITLE_LENGTH} characters"
+        ));
+    }
+    if todo
+        .list
+        .as_ref()
+        .is_some_and(|list| list.chars().count() > MAX_TITLE_LENGTH)
+    {
+        return Err(format!(
+            "List name must have at most {MAX_TITLE_LENGTH} characters"
+        ));
+    }
+    if let Some(tag) = todo
+        .tags
+        .iter()
+        .find(|tag| tag.chars().count() > MAX_TAG_NAME_LENGTH)
+    {
+        return Err(format!(
+            "Tag {tag} must have at most {MAX_TAG_NAME_LENGTH} characters"
+        ));
+    }
+    if todo
+        .subtasks
+        .iter()
+        .any(|subtask| subtask.title.chars().count() > MAX_TITLE_LENGTH)
+    {
+        return Err(format!(
+            "Subtasks must have at most {MAX_TITLE_LENGTH} characters"
+        ));
+    }
+
+    let mut seen = HashSet::new();
+    todo.tags.retain(|tag| seen.insert(tag.clone()));
+    Ok(())
+}
+
+// Report row of an imported todo
+fn row_response(
+    todo: &ImportedTodo,
+    outcome: ImportOutcome,
+    todo_id: Option<i64>,
+    message: Option<String>,
+) -> ImportRowResponse {
+    ImportRowResponse {
+        row: todo.row,
+        title: Some(todo.title.clone()),
+        outcome,
+        todo_id,
+        message,
+    }
+}
+
+// Where the todos of a list named by the file go
+#[derive(Clone, Copy)]
+enum TargetList {
+    Existing(i64),
+    // Created by the import, only in a dry run
+    Planned,
+}
+
+// What the import knows of the workspace: the lists by lowercase name, with the ones
+// created by the import, and the todos already there
+#[derive(Default)]
+struct ImportState {
+    lists: HashMap<String, TargetList>,
+    created_lists: Vec<String>,
+    // Checked once a list is missing
+    may_create_lists: Option<bool>,
+    imported: HashSet<(Option<String>, String)>,
+}
+
+pub struct ImportService {
+    import_repository: ImportRepository,
+    todo_repository: TodoRepository,
+    workspace_repository: WorkspaceRepository,
+    list_service: ListService,
+    view_cache: ViewCache,
+}
+
+impl ImportService {
+    pub const fn new(
+        import_repository: ImportRepository,
+        todo_repository: TodoRepository,
+        workspace_repository: WorkspaceRepository,
+        list_service: ListService,
+        view_cache: ViewCache,
+    ) -> Self {
+        Self {
+            import_repository,
+            todo_repository,
+            workspace_repository,
+            list_service,
+            view_cache,
+        }
+    }
+
+    // Import the todos of a file exported from the source into the workspace. A dry run
+    // reports the same outcomes without creating anything.
+    pub async fn import_file(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        source: ImportSource,
+        data: &[u8],
+        dry_run: bool,
+    ) -> Result<ImportReport, AppError> {
+        let parsed = parse_import(source, data).map_err(AppError::Validation)?;
+        if parsed.todos.len() > MAX_IMPORT_TODOS {
+            return Err(AppError::Validation(format!(
+                "Imports are limited to {MAX_IMPORT_TODOS} todos"
+            )));
+        }
+
+        let mut state = ImportState::default();
+        for list in self
+            .import_repository
+            .list_lists(user_id, workspace_id)
+            .await
+            .map_err(|e| {
+                tracing::warn!("Error listing lists to import into: {}", e);
+                AppError::Database(e)
+            })?
+        {
+            state
+                .lists
+                .entry(list.name.to_lowercase())
+                .or_insert_with(|| TargetList::Existing(i64::from(list.id)));
+        }
+        state.imported = self
+            .import_repository
+            .list_existing(user_id, workspace_id)
+            .await
+            .map_err(|e| {
+                tracing::warn!("Error listing todos to import into: {}", e);
+                AppError::Database(e)
+            })?
+            .into_iter()
+            .map(|todo| todo_key(todo.list_name.as_deref(), &todo.title))
+            .collect();
+
+        let mut rows: Vec<ImportRowResponse> = parsed
+            .errors
+            .into_iter()
+            .map(|error| ImportRowResponse {
+                row: error.row,
+                title: error.title,
+                outcome: ImportOutcome::Errored,
+                todo_id: None,
+                message: Some(error.message),
+            })
+            .collect();
+        for todo in parsed.todos {
+            rows.push(
+                self.import_todo(user_id, workspace_id, todo, dry_run, &mut state)
+                    .await,
+            );
+        }
+
+        rows.sort_by_key(|row| row.row);
+        let count = |outcome| rows.iter().filter(|row| row.outcome == outcome).count();
+        let created = count(ImportOutcome::Created);
+        if created > 0 && !dry_run {
+            self.view_cache.invalidate(user_id).await;
+        }
+        Ok(ImportReport {
+            source,
+            dry_run,
+            created,
+            skipped: count(ImportOutcome::Skipped),
+            errored: count(ImportOutcome::Errored),
+            lists_created: state.created_lists,
+            rows,
+        })
+    }
+
+    // Import a todo of the file, reporting what happened to it
+    async fn import_todo(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        mut todo: ImportedTodo,
+        dry_run: bool,
+        state: &mut ImportState,
+    ) -> ImportRowResponse {
+        if let Err(message) = validate_todo(&mut todo) {
+            return row_response(&todo, ImportOutcome::Errored, None, Some(message));
+        }
+
+        let target = match todo.list.as_deref() {
+            Some(name) => match self
+                .target_list(user_id, workspace_id, name, dry_run, state)
+                .await
+            {
+                Ok(target) => Some(target),
+                Err(error) => {
+                    let message = error.to_error_response().message;
+                    return row_response(
+                        &todo,
+                        ImportOutcome::Errored,
+                        None,
+                        Some(format!("Failed to create list {name}: {message}")),
+                    );
+                }
+            },
+            None => None,
+        };
+
+        let key = todo_key(todo.list.as_deref(), &todo.title);
+        if state.imported.contains(&key) {
+            return row_response(
+                &todo,
+                ImportOutcome::Skipped,
+                None,
+                Some("A todo with this title is already in the list".to_string()),
+            );
+        }
+
+        let list_id = match target {
+            Some(TargetList::Existing(list_id)) => Some(list_id),
+            _ => None,
+        };
+        if let Err(error) = self.check_wip_limit(list_id, &todo).await {
+            let message = error.to_error_response().message;
+            return row_response(&todo, ImportOutcome::Errored, None, Some(message));
+        }
+        state.imported.insert(key);
+
+        if dry_run {
+            return row_response(&todo, ImportOutcome::Created, None, None);
+        }
+        match self
+            .import_repository
+            .import_todo(user_id, workspace_id, list_id, &todo)
+            .await
+        {
+            Ok(id) => row_response(&todo, ImportOutcome::Created, Some(i64::from(id)), None),
+            Err(e) => {
+                tracing::warn!("Error importing todo: {}", e);
+                row_response(
+                    &todo,
+                    ImportOutcome::Errored,
+                    None,
+                    Some("Failed to create the todo".to_string()),
+                )
+            }
+        }
+    }
+
+    // List of the workspace with the name, created when missing, only checked in a dry
+    // run. Members without the permission to create lists can only import into the
+    // existing ones.
+    async fn target_list(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        name: &str,
+        dry_run: bool,
+        state: &mut ImportState,
+    ) -> Result<TargetList, AppError> {
+        if let Some(target) = state.lists.get(&name.to_lowercase()) {
+            return Ok(*target);
+        }
+        let allowed = if let Some(allowed) = state.may_create_lists {
+            allowed
+        } else {
+            let allowed = self.may_create_lists(user_id, workspace_id).await?;
+            state.may_create_lists = Some(allowed);
+            allowed
+        };
+        if !allowed {
+            return Err(AppError::Forbidden(format!(
+                "Creating lists needs the {} permission",
+                WorkspacePermission::ListsWrite.as_str()
+            )));
+        }
+
+        let target = if dry_run {
+            TargetList::Planned
+        } else {
+            let list = self
+                .list_service
+                .create_list(
+                    user_id,
+                    workspace_id,
+                    ListRequest {
+                        name: Some(name.to_string()),
+                    },
+                )
+                .await?;
+            TargetList::Existing(list.id)
+        };
+        state.lists.insert(name.to_lowercase(), target);
+        state.created_lists.push(name.to_string());
+        Ok(target)
+    }
+
+    // Whether the member may create lists in the workspace
+    async fn may_create_lists(&self, user_id: i64, workspace_id: i64) -> Result<bool, AppError> {
+        match self
+            .workspace_repository
+            .active_workspace(user_id, Some(workspace_id))
+            .await
+        {
+            Ok(workspace) => Ok(workspace.is_some_and(|workspace| {
+                WorkspacePermission::ListsWrite.granted_by(workspace.permissions.as_deref())
+            })),
+            Err(e) => {
+                tracing::warn!("Error checking workspace permissions: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Refuse a todo going into a full column of the list, archived todos don't count
+    async fn check_wip_limit(
+        &self,
+        list_id: Option<i64>,
+        todo: &ImportedTodo,
+    ) -> Result<(), AppError> {
+        let Some(list_id) = list_id.filter(|_| !todo.archived) else {
+            return Ok(());
+        };
+        let status = if todo.completed {
+            TodoStatus::Done
+        } else {
+            TodoStatus::Backlog
+        };
+        match self
+            .todo_repository
+            .reached_wip_limit(list_id, status, None)
+            .await
+        {
+            Ok(None) => Ok(()),
+            Ok(Some(limit)) => Err(AppError::Rejected(
+                WIP_LIMIT_EXCEEDED,
+                format!("The {status} column of the list is limited to {limit} todos"),
+            )),
+            Err(e) => {
+                tracing::warn!("Error checking WIP limit: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::modules::import::interfaces::ImportedSubtask;
+
+    #[test]
+    fn test_validate_todo() {
+        let mut todo = ImportedTodo {
+            title: "Pay rent".to_string(),
+            tags: vec!["money".to_string(), "home".to_string(), "money".to_string()],
+            ..ImportedTodo::default()
+        };
+        validate_todo(&mut todo).unwrap();
+        assert_eq!(todo.tags, vec!["money", "home"]);
+
+        todo.tags.push("t".repeat(MAX_TAG_NAME_LENGTH + 1));
+        assert!(validate_todo(&mut todo).unwrap_err().starts_with("Tag"));
+
+        let mut todo = ImportedTodo {
+            title: "Pay rent".to_string(),
+            subtasks: vec![ImportedSubtask {
+                title: "s".repeat(MAX_TITLE_LENGTH + 1),
+                completed: false,
+            }],
+            ..ImportedTodo::default()
+        };
+        assert!(validate_todo(&mut todo).is_err());
+    }
+
+    #[test]
+    fn test_todo_key_ignores_case() {
+        assert_eq!(
+            todo_key(Some("Home"), "Pay Rent"),
+            todo_key(Some("home"), "pay rent")
+        );
+        assert_ne!(
+            todo_key(None, "Pay rent"),
+            todo_key(Some("Home"), "Pay rent")
+        );
+    }
+}
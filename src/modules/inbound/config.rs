@@ -0,0 +1,70 @@
+//! # Inbound Email Configuration
+//! The domain receiving the emails of the lists and the secret of the webhook the email
+//! provider posts them to, read from the `INBOUND_EMAIL_*` variables.
+
+use std::sync::Arc;
+
+use sha2::{Digest, Sha256};
+
+// Shortest accepted webhook secret
+const MIN_SECRET_LENGTH: usize = 16;
+
+pub struct InboundEmailConfig {
+    // Domain of the list addresses, e.g. `inbound.example.com`
+    pub domain: String,
+    secret: String,
+}
+
+impl InboundEmailConfig {
+    pub fn new(domain: &str, secret: &str) -> Result<Self, String> {
+        let domain = domain.trim().trim_start_matches('@').to_ascii_lowercase();
+        if domain.is_empty() || !domain.contains('.') {
+            return Err("INBOUND_EMAIL_DOMAIN must be a domain name".to_string());
+        }
+        if secret.trim().len() < MIN_SECRET_LENGTH {
+            return Err(format!(
+                "INBOUND_EMAIL_SECRET must be at least {MIN_SECRET_LENGTH} characters"
+            ));
+        }
+        Ok(Self {
+            domain,
+            secret: secret.trim().to_string(),
+        })
+    }
+
+    // Check the secret given to the webhook. The digests are compared so the time taken
+    // doesn't tell how much of the secret matched.
+    pub fn verify_secret(&self, secret: &str) -> bool {
+        Sha256::digest(secret.as_bytes()) == Sha256::digest(self.secret.as_bytes())
+    }
+}
+
+// Inbound email configured in the environment, when `INBOUND_EMAIL_DOMAIN` is set
+pub fn inbound_email_from_env() -> Result<Option<Arc<InboundEmailConfig>>, String> {
+    let Some(domain) = std::env::var("INBOUND_EMAIL_DOMAIN")
+        .ok()
+        .filter(|domain| !domain.trim().is_empty())
+    else {
+        return Ok(None);
+    };
+    let secret = std::env::var("INBOUND_EMAIL_SECRET").unwrap_or_default();
+    Ok(Some(Arc::new(InboundEmailConfig::new(&domain, &secret)?)))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inbound_email_config() {
+        let config = InboundEmailConfig::new(" @Inbound.Example.com ", "0123456789abcdef").unwrap();
+        assert_eq!(config.domain, "inbound.example.com");
+        assert!(config.verify_secret("0123456789abcdef"));
+        assert!(!config.verify_secret("0123456789abcdeF"));
+        assert!(!config.verify_secret(""));
+
+        assert!(InboundEmailConfig::new("localhost", "0123456789abcdef").is_err());
+        assert!(InboundEmailConfig::new("inbound.example.com", "short").is_err());
+    }
+}
@@ -0,0 +1,126 @@
+//! # `Inbound Email` Interfaces
+//! This module defines the data structures from the Inbound Email module
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::modules::attachment::interfaces::{AttachmentResponse, UploadedFile};
+use crate::modules::todo::interfaces::TodoResponse;
+use crate::utils::dates::rfc3339_option;
+
+// Inbound address row as stored in database
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct InboundAddressRow {
+    pub list_id: i32,
+    pub alias: String,
+    pub allowed_senders: Vec<String>,
+    pub created_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct InboundAddressResponse {
+    pub list_id: i64,
+    // Emails sent to this address become todos of the list
+    pub address: String,
+    // Addresses, or `@domain` for a whole domain, allowed to send besides the list owner
+    pub allowed_senders: Vec<String>,
+    #[serde(with = "rfc3339_option")]
+    pub created_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct UpdateInboundSendersRequest {
+    // Allowed senders, replacing the current ones
+    pub senders: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct InboundMessageResponse {
+    pub message: String,
+}
+
+// List an alias delivers to, with its owner and allowed senders
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct InboundTargetRow {
+    pub list_id: i32,
+    pub workspace_id: i32,
+    pub owner_id: i32,
+    pub owner_email: String,
+    pub allowed_senders: Vec<String>,
+}
+
+// Query parameters of the inbound email webhook
+#[derive(Deserialize, IntoParams, Clone, Debug, Default)]
+#[into_params(parameter_in = Query)]
+pub struct InboundEmailQuery {
+    /// Shared secret of the webhook, `INBOUND_EMAIL_SECRET`
+    pub secret: Option<String>,
+}
+
+// Multipart body of the inbound email webhook, as posted by the email providers. Only
+// documents the fields, `recipient`, `sender` and `body-plain` are also accepted.
+#[allow(dead_code)]
+#[derive(ToSchema)]
+pub struct InboundEmailRequest {
+    // Recipients of the email, one of them the address of a list
+    pub to: String,
+    // Sender of the email, e.g. `Ana <ana@example.com>`
+    pub from: String,
+    // Title of the todo
+    pub subject: Option<String>,
+    // Plain text body, the description of the todo
+    pub text: Option<String>,
+    // Files of the email, attached to the todo
+    #[schema(value_type = Vec<String>, format = Binary)]
+    pub attachments: Vec<Vec<u8>>,
+}
+
+// Email read from the webhook body
+#[derive(Default)]
+pub struct InboundEmail {
+    pub recipients: String,
+    pub sender: String,
+    pub subject: Option<String>,
+    pub text: Option<String>,
+    pub attachments: Vec<UploadedFile>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct InboundEmailResponse {
+    pub todo: TodoResponse,
+    pub attachments: Vec<AttachmentResponse>,
+}
+
+impl InboundAddressResponse {
+    // Response of a row, the alias at the inbound domain
+    pub fn from_row(row: InboundAddressRow, domain: &str) -> Self {
+        Self {
+            list_id: i64::from(row.list_id),
+            address: format!("{}@{domain}", row.alias),
+            allowed_senders: row.allowed_senders,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inbound_address_response_from_row() {
+        let response = InboundAddressResponse::from_row(
+            InboundAddressRow {
+                list_id: 3,
+                alias: "list-abc123".to_string(),
+                allowed_senders: vec!["@example.com".to_string()],
+                created_at: None,
+            },
+            "inbound.example.com",
+        );
+        assert_eq!(response.list_id, 3);
+        assert_eq!(response.address, "list-abc123@inbound.example.com");
+        assert_eq!(response.allowed_senders, vec!["@example.com"]);
+    }
+}
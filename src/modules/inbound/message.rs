@@ -0,0 +1,279 @@
+//! # Inbound Email Messages
+//! Reading of the emails posted by the email providers, as multipart bodies with the
+//! `to`, `from`, `subject` and `text` fields (or the `recipient`, `sender` and
+//! `body-plain` fields) and the attached files, and their conversion to a todo.
+
+use axum::{body::Bytes, extract::Multipart};
+use email_address::EmailAddress;
+
+use crate::modules::{
+    attachment::{
+        interfaces::UploadedFile,
+        service::{normalize_content_type, sanitize_file_name},
+    },
+    common::AppError,
+    inbound::interfaces::InboundEmail,
+};
+
+// Prefix of the generated aliases
+pub const ALIAS_PREFIX: &str = "list-";
+
+// Files of an email attached to its todo, the others are dropped
+pub const MAX_INBOUND_ATTACHMENTS: usize = 10;
+
+// Longest todo title, the length of the column
+const MAX_TITLE_CHARS: usize = 255;
+
+// Longest todo description taken from an email body
+const MAX_DESCRIPTION_CHARS: usize = 10_000;
+
+// Longest text field read from the body
+const MAX_FIELD_BYTES: usize = 256 * 1024;
+
+const DEFAULT_TITLE: &str = "(no subject)";
+
+fn invalid_email(error: impl std::fmt::Display) -> AppError {
+    AppError::Validation(format!("Invalid email: {error}"))
+}
+
+// Read the email of a webhook body. Attachments larger than `max_bytes` and those past
+// the limit are dropped, the email is still delivered.
+pub async fn read_inbound_email(
+    multipart: &mut Multipart,
+    max_bytes: usize,
+) -> Result<InboundEmail, AppError> {
+    let mut email = InboundEmail::default();
+    while let Some(mut field) = multipart.next_field().await.map_err(invalid_email)? {
+        let name = field.name().unwrap_or_default().to_ascii_lowercase();
+
+        if let Some(file_name) = field.file_name().map(sanitize_file_name) {
+            let content_type = field.content_type().map_or_else(
+                || "application/octet-stream".to_string(),
+                normalize_content_type,
+            );
+            let mut data = Vec::new();
+            let mut too_large = false;
+            while let Some(chunk) = field.chunk().await.map_err(invalid_email)? {
+                too_large = too_large || data.len() + chunk.len() > max_bytes;
+                if !too_large {
+                    data.extend_from_slice(&chunk);
+                }
+            }
+            if too_large || email.attachments.len() >= MAX_INBOUND_ATTACHMENTS {
+                tracing::info!("Inbound email attachment {} dropped", file_name);
+                continue;
+            }
+            email.attachments.push(UploadedFile {
+                file_name,
+                content_type,
+                data: Bytes::from(data),
+            });
+            continue;
+        }
+
+        let mut value = Vec::new();
+        while let Some(chunk) = field.chunk().await.map_err(invalid_email)? {
+            if value.len() + chunk.len() > MAX_FIELD_BYTES {
+                return Err(invalid_email(format!("{name} is too long")));
+            }
+            value.extend_from_slice(&chunk);
+        }
+        let value = String::from_utf8_lossy(&value).into_owned();
+        match name.as_str() {
+            "to" | "recipient" => email.recipients = value,
+            "from" | "sender" => email.sender = value,
+            "subject" => email.subject = Some(value),
+            "text" | "body-plain" => email.text = Some(value),
+            _ => {}
+        }
+    }
+    Ok(email)
+}
+
+// Address of a mailbox, `Ana <ana@example.com>` is `ana@example.com`
+fn mailbox_address(mailbox: &str) -> &str {
+    let mailbox = mailbox.trim();
+    match (mailbox.rfind('<'), mailbox.rfind('>')) {
+        (Some(start), Some(end)) if start < end => mailbox[start + 1..end].trim(),
+        _ => mailbox,
+    }
+}
+
+// Lowercase address of the sender, `None` when it isn't a valid address
+pub fn sender_address(sender: &str) -> Option<String> {
+    let address = mailbox_address(sender);
+    EmailAddress::is_valid(address).then(|| address.to_ascii_lowercase())
+}
+
+// Alias of the first recipient at the inbound domain
+pub fn find_alias(recipients: &str, domain: &str) -> Option<String> {
+    recipients.split(',').find_map(|recipient| {
+        let (local, recipient_domain) = mailbox_address(recipient).rsplit_once('@')?;
+        let local = local.to_ascii_lowercase();
+        (recipient_domain.eq_ignore_ascii_case(domain) && local.starts_with(ALIAS_PREFIX))
+            .then_some(local)
+    })
+}
+
+// Allowed sender entry, a lowercase address or `@domain`
+pub fn normalize_sender(entry: &str) -> Result<String, String> {
+    let entry = entry.trim().to_ascii_lowercase();
+    let valid = entry.strip_prefix('@').map_or_else(
+        || EmailAddress::is_valid(&entry),
+        |domain| domain.contains('.') && EmailAddress::is_valid(&format!("sender@{domain}")),
+    );
+    if valid {
+        Ok(entry)
+    } else {
+        Err(format!("{entry} is not an email address nor an @domain"))
+    }
+}
+
+// Whether the sender may add todos to the list, the owner always can
+pub fn is_allowed_sender(sender: &str, owner_email: &str, allowed_senders: &[String]) -> bool {
+    if sender.eq_ignore_ascii_case(owner_email) {
+        return true;
+    }
+    let domain = sender.rsplit_once('@').map(|(_, domain)| domain);
+    allowed_senders.iter().any(|allowed| {
+        allowed.strip_prefix('@').map_or_else(
+            || allowed.eq_ignore_ascii_case(sender),
+            |allowed_domain| {
+                domain.is_some_and(|domain| domain.eq_ignore_ascii_case(allowed_domain))
+            },
+        )
+    })
+}
+
+// Title of the todo of an email, its subject without the reply and forward prefixes
+pub fn todo_title(subject: Option<&str>) -> String {
+    let mut title = subject.unwrap_or_default().trim();
+    loop {
+        let lower = title.to_ascii_lowercase();
+        let Some(prefix) = ["re:", "fw:", "fwd:"]
+            .into_iter()
+            .find(|prefix| lower.starts_with(prefix))
+        else {
+            break;
+        };
+        title = title[prefix.len()..].trim_start();
+    }
+    let title: String = title
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .take(MAX_TITLE_CHARS)
+        .collect();
+    let title = title.trim();
+    if title.is_empty() {
+        DEFAULT_TITLE.to_string()
+    } else {
+        title.to_string()
+    }
+}
+
+// Description of the todo of an email, its plain text body
+pub fn todo_description(text: Option<&str>) -> Option<String> {
+    let text = text?.trim();
+    (!text.is_empty()).then(|| text.chars().take(MAX_DESCRIPTION_CHARS).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sender_address() {
+        assert_eq!(
+            sender_address("Ana Silva <Ana@Example.com>").as_deref(),
+            Some("ana@example.com")
+        );
+        assert_eq!(
+            sender_address(" ana@example.com ").as_deref(),
+            Some("ana@example.com")
+        );
+        assert_eq!(sender_address("Ana"), None);
+    }
+
+    #[test]
+    fn test_find_alias() {
+        let domain = "inbound.example.com";
+        assert_eq!(
+            find_alias(
+                "Bob <bob@example.com>, Groceries <List-Abc123@Inbound.Example.com>",
+                domain
+            )
+            .as_deref(),
+            Some("list-abc123")
+        );
+        assert_eq!(find_alias("other@inbound.example.com", domain), None);
+        assert_eq!(find_alias("list-abc123@example.com", domain), None);
+        assert_eq!(find_alias("", domain), None);
+    }
+
+    #[test]
+    fn test_normalize_sender() {
+        assert_eq!(
+            normalize_sender(" Ana@Example.com ").as_deref(),
+            Ok("ana@example.com")
+        );
+        assert_eq!(
+            normalize_sender("@Example.com").as_deref(),
+            Ok("@example.com")
+        );
+        assert!(normalize_sender("@localhost").is_err());
+        assert!(normalize_sender("ana").is_err());
+    }
+
+    #[test]
+    fn test_is_allowed_sender() {
+        let allowed = vec!["bob@example.org".to_string(), "@example.com".to_string()];
+        assert!(is_allowed_sender(
+            "owner@mail.com",
+            "Owner@mail.com",
+            &allowed
+        ));
+        assert!(is_allowed_sender(
+            "bob@example.org",
+            "owner@mail.com",
+            &allowed
+        ));
+        assert!(is_allowed_sender(
+            "ana@example.com",
+            "owner@mail.com",
+            &allowed
+        ));
+        assert!(!is_allowed_sender(
+            "eve@example.org",
+            "owner@mail.com",
+            &allowed
+        ));
+        assert!(!is_allowed_sender(
+            "eve@sub.example.com",
+            "owner@mail.com",
+            &allowed
+        ));
+        assert!(!is_allowed_sender("eve@example.org", "owner@mail.com", &[]));
+    }
+
+    #[test]
+    fn test_todo_title() {
+        assert_eq!(todo_title(Some("Re: Fwd: Pay rent")), "Pay rent");
+        assert_eq!(todo_title(Some("RE:FW: Call\tmom")), "Call mom");
+        assert_eq!(todo_title(Some("   ")), DEFAULT_TITLE);
+        assert_eq!(todo_title(None), DEFAULT_TITLE);
+        assert_eq!(
+            todo_title(Some(&"a".repeat(300))).chars().count(),
+            MAX_TITLE_CHARS
+        );
+    }
+
+    #[test]
+    fn test_todo_description() {
+        assert_eq!(
+            todo_description(Some("  Buy milk\n")).as_deref(),
+            Some("Buy milk")
+        );
+        assert_eq!(todo_description(Some(" \n ")), None);
+        assert_eq!(todo_description(None), None);
+    }
+}
@@ -0,0 +1,11 @@
+//! # `Inbound Email` Mod
+//! Inbound email imports for the email addresses adding todos to the lists
+
+pub mod config;
+pub mod interfaces;
+pub mod message;
+pub mod repository;
+pub mod routes;
+pub mod service;
+
+pub use routes::inbound_routes;
@@ -0,0 +1,121 @@
+//! # `Inbound Email` Repository
+//! This module defines the inbound email repository for the inbound addresses of the lists.
+
+use sqlx::{Error, Pool, Postgres};
+
+use crate::modules::{
+    common::to_db_id,
+    inbound::interfaces::{InboundAddressRow, InboundTargetRow},
+    list::{interfaces::ListAccessRow, repository::LIST_ACCESS_QUERY},
+};
+use crate::telemetry::observe_query;
+
+const ADDRESS_COLUMNS: &str = "list_id, alias, allowed_senders, created_at";
+
+pub struct InboundRepository {
+    pool: Pool<Postgres>,
+}
+
+impl InboundRepository {
+    // Constructor
+    pub const fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Owner of a list of the workspace and the role of the user on it, `None` without access
+    pub async fn list_access(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        list_id: i64,
+    ) -> Result<Option<ListAccessRow>, Error> {
+        observe_query("inbound.list_access", async move {
+            sqlx::query_as::<_, ListAccessRow>(LIST_ACCESS_QUERY)
+                .bind(to_db_id(list_id)?)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Inbound address of a list, `None` when it has none
+    pub async fn fetch_address(&self, list_id: i64) -> Result<Option<InboundAddressRow>, Error> {
+        observe_query("inbound.fetch_address", async move {
+            sqlx::query_as::<_, InboundAddressRow>(&format!(
+                "SELECT {ADDRESS_COLUMNS} FROM list_inbound_addresses WHERE list_id = $1"
+            ))
+            .bind(to_db_id(list_id)?)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Give a list a new alias, replacing the current one. The allowed senders are kept.
+    pub async fn set_alias(&self, list_id: i64, alias: &str) -> Result<InboundAddressRow, Error> {
+        observe_query("inbound.set_alias", async move {
+            sqlx::query_as::<_, InboundAddressRow>(&format!(
+                "INSERT INTO list_inbound_addresses (list_id, alias) VALUES ($1, $2)
+                 ON CONFLICT (list_id) DO UPDATE SET alias = EXCLUDED.alias,
+                    created_at = CURRENT_TIMESTAMP
+                 RETURNING {ADDRESS_COLUMNS}"
+            ))
+            .bind(to_db_id(list_id)?)
+            .bind(alias)
+            .fetch_one(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Replace the allowed senders of an address, `None` when the list has no address
+    pub async fn replace_senders(
+        &self,
+        list_id: i64,
+        senders: &[String],
+    ) -> Result<Option<InboundAddressRow>, Error> {
+        observe_query("inbound.replace_senders", async move {
+            sqlx::query_as::<_, InboundAddressRow>(&format!(
+                "UPDATE list_inbound_addresses SET allowed_senders = $2 WHERE list_id = $1
+                 RETURNING {ADDRESS_COLUMNS}"
+            ))
+            .bind(to_db_id(list_id)?)
+            .bind(senders)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Remove the address of a list, returns whether it had one
+    pub async fn delete_address(&self, list_id: i64) -> Result<bool, Error> {
+        observe_query("inbound.delete_address", async move {
+            let result = sqlx::query("DELETE FROM list_inbound_addresses WHERE list_id = $1")
+                .bind(to_db_id(list_id)?)
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    // List an alias delivers to, archived lists don't receive emails
+    pub async fn find_target(&self, alias: &str) -> Result<Option<InboundTargetRow>, Error> {
+        observe_query("inbound.find_target", async move {
+            sqlx::query_as::<_, InboundTargetRow>(
+                "SELECT l.id AS list_id, l.workspace_id, l.user_id AS owner_id,
+                    u.email AS owner_email, a.allowed_senders
+                 FROM list_inbound_addresses a
+                 JOIN lists l ON l.id = a.list_id
+                 JOIN users u ON u.id = l.user_id
+                 WHERE a.alias = $1 AND NOT l.archived AND u.active",
+            )
+            .bind(alias)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+}
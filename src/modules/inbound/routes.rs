@@ -0,0 +1,239 @@
+//! #`Inbound Email` Routes
+//! This module defines the HTTP routes for the inbound addresses of the lists and the
+//! webhook the email provider posts their emails to.
+
+use axum::extract::{DefaultBodyLimit, Multipart, Path, Query};
+use axum::routing::{get, post, put};
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Router};
+
+use crate::modules::attachment::repository::AttachmentRepository;
+use crate::modules::attachment::service::AttachmentService;
+use crate::modules::common::{respond, ErrorResponse};
+use crate::modules::inbound::interfaces::{
+    InboundAddressResponse, InboundEmailQuery, InboundEmailRequest, InboundEmailResponse,
+    InboundMessageResponse, UpdateInboundSendersRequest,
+};
+use crate::modules::inbound::message::{read_inbound_email, MAX_INBOUND_ATTACHMENTS};
+use crate::modules::inbound::repository::InboundRepository;
+use crate::modules::inbound::service::InboundService;
+use crate::modules::todo::repository::TodoRepository;
+use crate::modules::todo::service::TodoService;
+use crate::modules::workspace::context::WorkspaceContext;
+use crate::utils::json::Json;
+use crate::AppState;
+
+// Room left for the text fields and the multipart boundaries around the files
+const INBOUND_OVERHEAD_BYTES: usize = 1024 * 1024;
+
+// Creates and returns the inbound email routes, emails are capped to the attachments
+// of `max_bytes` they may hold
+pub fn inbound_routes(max_bytes: usize) -> Router<AppState> {
+    Router::new()
+        .route(
+            "/lists/{id}/inbound",
+            get(fetch_inbound_address_route)
+                .post(create_inbound_address_route)
+                .delete(delete_inbound_address_route),
+        )
+        .route(
+            "/lists/{id}/inbound/senders",
+            put(update_inbound_senders_route),
+        )
+        .route(
+            "/inbound/email",
+            post(inbound_email_route).layer(DefaultBodyLimit::max(
+                max_bytes
+                    .saturating_mul(MAX_INBOUND_ATTACHMENTS)
+                    .saturating_add(INBOUND_OVERHEAD_BYTES),
+            )),
+        )
+}
+
+fn inbound_service(app_state: &AppState) -> InboundService {
+    InboundService::new(
+        InboundRepository::new(app_state.db_pool.clone()),
+        TodoService::new(
+            TodoRepository::new(app_state.db_pool.clone()),
+            app_state.view_cache.clone(),
+            app_state.create_dedup.clone(),
+        ),
+        AttachmentService::new(
+            AttachmentRepository::new(app_state.db_pool.clone()),
+            app_state.attachment_storage.clone(),
+        ),
+        app_state.inbound_email.clone(),
+    )
+}
+
+// Fetch Inbound Address Route
+#[utoipa::path(
+    get,
+    path = "/lists/{id}/inbound",
+    tag = "Inbound Email",
+    params(
+        ("id" = i64, Path, description = "List id")
+    ),
+    responses(
+        (status = 200, description = "Inbound address of the list", body = InboundAddressResponse),
+        (status = 403, description = "Not the owner of the list", body = ErrorResponse),
+        (status = 404, description = "List not found, without inbound address or inbound email not configured", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn fetch_inbound_address_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        inbound_service(&app_state)
+            .fetch_address(workspace.user_id, workspace.workspace_id, id)
+            .await,
+    )
+}
+
+// Create Inbound Address Route
+#[utoipa::path(
+    post,
+    path = "/lists/{id}/inbound",
+    tag = "Inbound Email",
+    params(
+        ("id" = i64, Path, description = "List id")
+    ),
+    responses(
+        (status = 201, description = "New inbound address of the list, the previous one stops receiving emails", body = InboundAddressResponse),
+        (status = 403, description = "Not the owner of the list", body = ErrorResponse),
+        (status = 404, description = "List not found or inbound email not configured", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn create_inbound_address_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::CREATED,
+        inbound_service(&app_state)
+            .create_address(workspace.user_id, workspace.workspace_id, id)
+            .await,
+    )
+}
+
+// Update Inbound Senders Route
+#[utoipa::path(
+    put,
+    path = "/lists/{id}/inbound/senders",
+    tag = "Inbound Email",
+    params(
+        ("id" = i64, Path, description = "List id")
+    ),
+    request_body = UpdateInboundSendersRequest,
+    responses(
+        (status = 200, description = "Allowed senders replaced, the list owner is always allowed", body = InboundAddressResponse),
+        (status = 400, description = "Invalid sender", body = ErrorResponse),
+        (status = 403, description = "Not the owner of the list", body = ErrorResponse),
+        (status = 404, description = "List not found or without inbound address", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn update_inbound_senders_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path(id): Path<i64>,
+    Json(update_request): Json<UpdateInboundSendersRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        inbound_service(&app_state)
+            .update_senders(
+                workspace.user_id,
+                workspace.workspace_id,
+                id,
+                update_request,
+            )
+            .await,
+    )
+}
+
+// Delete Inbound Address Route
+#[utoipa::path(
+    delete,
+    path = "/lists/{id}/inbound",
+    tag = "Inbound Email",
+    params(
+        ("id" = i64, Path, description = "List id")
+    ),
+    responses(
+        (status = 200, description = "Inbound address deleted", body = InboundMessageResponse),
+        (status = 403, description = "Not the owner of the list", body = ErrorResponse),
+        (status = 404, description = "List not found or without inbound address", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn delete_inbound_address_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        inbound_service(&app_state)
+            .delete_address(workspace.user_id, workspace.workspace_id, id)
+            .await,
+    )
+}
+
+// Inbound Email Route
+#[utoipa::path(
+    post,
+    path = "/inbound/email",
+    tag = "Inbound Email",
+    params(InboundEmailQuery),
+    request_body(content = InboundEmailRequest, content_type = "multipart/form-data"),
+    responses(
+        (status = 201, description = "Todo created in the list of the recipient address", body = InboundEmailResponse),
+        (status = 400, description = "Invalid email or the list refused the todo", body = ErrorResponse),
+        (status = 401, description = "Invalid webhook secret", body = ErrorResponse),
+        (status = 403, description = "Sender not allowed on the list", body = ErrorResponse),
+        (status = 404, description = "Unknown recipient or inbound email not configured", body = ErrorResponse)
+    )
+)]
+pub async fn inbound_email_route(
+    State(app_state): State<AppState>,
+    Query(query): Query<InboundEmailQuery>,
+    mut multipart: Multipart,
+) -> impl IntoResponse {
+    let service = inbound_service(&app_state);
+    // Checked first so emails of unknown senders are never read
+    if let Err(error) = service.authorize(query.secret.as_deref()) {
+        return error.into_response();
+    }
+    let email =
+        match read_inbound_email(&mut multipart, app_state.attachment_storage.max_bytes()).await {
+            Ok(email) => email,
+            Err(error) => return error.into_response(),
+        };
+    respond(StatusCode::CREATED, service.receive_email(email).await)
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inbound_routes_creation() {
+        let _routes = inbound_routes(1024);
+        assert!(true);
+    }
+}
@@ -0,0 +1,284 @@
+//! # `Inbound Email` Service
+//!
+//! This module contains the bussiness logic for the inbound addresses of the lists. An
+//! email sent to the address of a list becomes a todo of the list, its subject the title,
+//! its plain text body the description and its files the attachments. Only the list
+//! owner and the allowed senders may add todos this way.
+
+use std::sync::Arc;
+
+use crate::{
+    modules::{
+        attachment::service::AttachmentService,
+        common::AppError,
+        inbound::{
+            config::InboundEmailConfig,
+            interfaces::{
+                InboundAddressResponse, InboundEmail, InboundEmailResponse, InboundMessageResponse,
+                UpdateInboundSendersRequest,
+            },
+            message::{
+                find_alias, is_allowed_sender, normalize_sender, sender_address, todo_description,
+                todo_title, ALIAS_PREFIX,
+            },
+            repository::InboundRepository,
+        },
+        list::interfaces::{ListAccess, ListRole},
+        todo::{interfaces::CreateTodoRequest, service::TodoService},
+    },
+    utils::token::generate_random_token,
+};
+
+// Allowed senders of an address
+const MAX_ALLOWED_SENDERS: usize = 50;
+
+// Random hex characters of an alias
+const ALIAS_RANDOM_CHARS: usize = 12;
+
+// New random alias, e.g. `list-3f9a0c1b7d2e`
+fn generate_alias() -> String {
+    let token = generate_random_token();
+    format!("{ALIAS_PREFIX}{}", &token[..ALIAS_RANDOM_CHARS])
+}
+
+// Validated allowed senders, lowercased and without duplicates
+fn parse_senders(senders: &[String]) -> Result<Vec<String>, AppError> {
+    if senders.len() > MAX_ALLOWED_SENDERS {
+        return Err(AppError::Validation(format!(
+            "At most {MAX_ALLOWED_SENDERS} senders can be allowed"
+        )));
+    }
+    let mut parsed: Vec<String> = Vec::with_capacity(senders.len());
+    for sender in senders {
+        let sender = normalize_sender(sender).map_err(AppError::Validation)?;
+        if !parsed.contains(&sender) {
+            parsed.push(sender);
+        }
+    }
+    Ok(parsed)
+}
+
+pub struct InboundService {
+    inbound_repository: InboundRepository,
+    todo_service: TodoService,
+    attachment_service: AttachmentService,
+    config: Option<Arc<InboundEmailConfig>>,
+}
+
+impl InboundService {
+    pub const fn new(
+        inbound_repository: InboundRepository,
+        todo_service: TodoService,
+        attachment_service: AttachmentService,
+        config: Option<Arc<InboundEmailConfig>>,
+    ) -> Self {
+        Self {
+            inbound_repository,
+            todo_service,
+            attachment_service,
+            config,
+        }
+    }
+
+    fn config(&self) -> Result<&InboundEmailConfig, AppError> {
+        self.config.as_deref().ok_or_else(|| {
+            AppError::NotFound("Inbound email is not configured on this server".to_string())
+        })
+    }
+
+    // Check that the user owns the list, only owners manage its address
+    async fn check_owner(&self, user_id: i64, workspace_id: i64, id: i64) -> Result<(), AppError> {
+        let access = self
+            .inbound_repository
+            .list_access(user_id, workspace_id, id)
+            .await?
+            .map(ListAccess::from)
+            .ok_or_else(|| AppError::NotFound("List not found".to_string()))?;
+        if access.role == ListRole::Owner {
+            Ok(())
+        } else {
+            Err(AppError::Forbidden(
+                "Only the owner manages the inbound address of the list".to_string(),
+            ))
+        }
+    }
+
+    // Inbound address of a list
+    pub async fn fetch_address(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+    ) -> Result<InboundAddressResponse, AppError> {
+        let config = self.config()?;
+        self.check_owner(user_id, workspace_id, id).await?;
+
+        self.inbound_repository
+            .fetch_address(id)
+            .await?
+            .map(|row| InboundAddressResponse::from_row(row, &config.domain))
+            .ok_or_else(|| AppError::NotFound("List has no inbound address".to_string()))
+    }
+
+    // Generate the inbound address of a list, replacing the current one so a leaked
+    // address can be revoked. The allowed senders are kept.
+    pub async fn create_address(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+    ) -> Result<InboundAddressResponse, AppError> {
+        let config = self.config()?;
+        self.check_owner(user_id, workspace_id, id).await?;
+
+        let row = self
+            .inbound_repository
+            .set_alias(id, &generate_alias())
+            .await?;
+        Ok(InboundAddressResponse::from_row(row, &config.domain))
+    }
+
+    // Replace the senders allowed besides the list owner
+    pub async fn update_senders(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+        update_request: UpdateInboundSendersRequest,
+    ) -> Result<InboundAddressResponse, AppError> {
+        let config = self.config()?;
+        let senders = parse_senders(&update_request.senders)?;
+        self.check_owner(user_id, workspace_id, id).await?;
+
+        self.inbound_repository
+            .replace_senders(id, &senders)
+            .await?
+            .map(|row| InboundAddressResponse::from_row(row, &config.domain))
+            .ok_or_else(|| AppError::NotFound("List has no inbound address".to_string()))
+    }
+
+    // Remove the inbound address of a list, emails sent to it are refused afterwards
+    pub async fn delete_address(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+    ) -> Result<InboundMessageResponse, AppError> {
+        self.config()?;
+        self.check_owner(user_id, workspace_id, id).await?;
+
+        if self.inbound_repository.delete_address(id).await? {
+            Ok(InboundMessageResponse {
+                message: "Inbound address deleted".to_string(),
+            })
+        } else {
+            Err(AppError::NotFound(
+                "List has no inbound address".to_string(),
+            ))
+        }
+    }
+
+    // Check the secret the email provider posts emails with
+    pub fn authorize(&self, secret: Option<&str>) -> Result<(), AppError> {
+        let config = self.config()?;
+        if secret.is_some_and(|secret| config.verify_secret(secret)) {
+            Ok(())
+        } else {
+            Err(AppError::Unauthorized("Invalid webhook secret".to_string()))
+        }
+    }
+
+    // Add the todo of an email to the list of its recipient address. The todo belongs to
+    // the list owner, attachments that can't be stored are skipped.
+    pub async fn receive_email(
+        &self,
+        email: InboundEmail,
+    ) -> Result<InboundEmailResponse, AppError> {
+        let config = self.config()?;
+        let alias = find_alias(&email.recipients, &config.domain)
+            .ok_or_else(|| AppError::NotFound("Unknown recipient".to_string()))?;
+        let sender = sender_address(&email.sender)
+            .ok_or_else(|| AppError::Validation("Invalid sender".to_string()))?;
+
+        let target = self
+            .inbound_repository
+            .find_target(&alias)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Unknown recipient".to_string()))?;
+        if !is_allowed_sender(&sender, &target.owner_email, &target.allowed_senders) {
+            tracing::info!("Inbound email from {} to {} refused", sender, alias);
+            return Err(AppError::Forbidden(
+                "Sender is not allowed to add todos to this list".to_string(),
+            ));
+        }
+
+        let owner_id = i64::from(target.owner_id);
+        let workspace_id = i64::from(target.workspace_id);
+        let (todo, _) = self
+            .todo_service
+            .create_todo(
+                owner_id,
+                workspace_id,
+                CreateTodoRequest {
+                    title: Some(todo_title(email.subject.as_deref())),
+                    description: todo_description(email.text.as_deref()),
+                    list_id: Some(i64::from(target.list_id)),
+                    due_at: None,
+                    recurrence: None,
+                    scheduled_for: None,
+                },
+            )
+            .await?;
+
+        let mut attachments = Vec::with_capacity(email.attachments.len());
+        for file in email.attachments {
+            let file_name = file.file_name.clone();
+            match self
+                .attachment_service
+                .attach_file(owner_id, workspace_id, todo.id, file)
+                .await
+            {
+                Ok(attachment) => attachments.push(attachment),
+                Err(error) => tracing::info!(
+                    "Inbound email attachment {} skipped: {}",
+                    file_name,
+                    error.to_error_response().message
+                ),
+            }
+        }
+
+        tracing::info!("Inbound email from {} added todo {}", sender, todo.id);
+        Ok(InboundEmailResponse { todo, attachments })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_alias() {
+        let alias = generate_alias();
+        assert!(alias.starts_with(ALIAS_PREFIX));
+        assert_eq!(alias.len(), ALIAS_PREFIX.len() + ALIAS_RANDOM_CHARS);
+        assert_ne!(alias, generate_alias());
+    }
+
+    #[test]
+    fn test_parse_senders() {
+        let senders = parse_senders(&[
+            "Ana@Example.com".to_string(),
+            "ana@example.com".to_string(),
+            "@example.org".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(senders, vec!["ana@example.com", "@example.org"]);
+
+        assert!(parse_senders(&["not an email".to_string()]).is_err());
+        let too_many: Vec<String> = (0..=MAX_ALLOWED_SENDERS)
+            .map(|i| format!("user{i}@example.com"))
+            .collect();
+        assert!(parse_senders(&too_many).is_err());
+    }
+}
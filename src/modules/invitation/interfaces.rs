@@ -0,0 +1,76 @@
+//! # `Invitations` Interfaces
+//! This module defines the data structures from Invitations module
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+use crate::modules::{list::interfaces::ListResponse, todo::interfaces::TodoResponse};
+use crate::utils::dates::rfc3339;
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct InviteGuestRequest {
+    // Email of the guest
+    pub email: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct GuestInvitationResponse {
+    pub id: i64,
+    pub list_id: i64,
+    pub email: String,
+    // Invitation token, only returned once
+    pub token: String,
+    #[serde(with = "rfc3339")]
+    pub expires_at: OffsetDateTime,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct AcceptInvitationRequest {
+    // Invitation token received by the guest
+    pub token: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct GuestLoginResponse {
+    // Token limited to the shared list
+    pub token: String,
+    pub list_id: i64,
+    #[serde(with = "rfc3339")]
+    pub expires_at: OffsetDateTime,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct GuestListResponse {
+    pub list: ListResponse,
+    pub todos: Vec<TodoResponse>,
+}
+
+// Guest invitation row as stored in database
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct GuestInvitationRow {
+    pub id: i32,
+    pub list_id: i32,
+    pub guest_user_id: i32,
+    pub email: String,
+    pub expires_at: OffsetDateTime,
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invite_guest_request_deserialization() {
+        let request: InviteGuestRequest =
+            serde_json::from_str(r#"{"email":"guest@example.com"}"#).unwrap();
+        assert_eq!(request.email, Some("guest@example.com".to_string()));
+    }
+
+    #[test]
+    fn test_accept_invitation_request_missing_token() {
+        let request: AcceptInvitationRequest = serde_json::from_str("{}").unwrap();
+        assert_eq!(request.token, None);
+    }
+}
@@ -0,0 +1,9 @@
+//! # `Invitation` Mod
+//! Invitation imports for the invitation module
+
+pub mod interfaces;
+pub mod repository;
+pub mod routes;
+pub mod service;
+
+pub use routes::invitation_routes;
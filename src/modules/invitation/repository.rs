@@ -0,0 +1,173 @@
+//! # `Invitation` Repository
+//! This module defines the invitation repository for guest invitation operations.
+
+use sqlx::{Error, Pool, Postgres};
+use time::OffsetDateTime;
+
+use crate::modules::{
+    common::to_db_id,
+    invitation::interfaces::GuestInvitationRow,
+    list::interfaces::ListRow,
+    todo::{interfaces::TodoRow, repository::TODO_COLUMNS},
+};
+use crate::telemetry::observe_query;
+
+pub struct InvitationRepository {
+    pool: Pool<Postgres>,
+}
+
+impl InvitationRepository {
+    // Constructor
+    pub const fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Check if a list of the workspace is owned by the user
+    pub async fn is_list_owner(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        list_id: i64,
+    ) -> Result<bool, Error> {
+        observe_query("invitation.is_list_owner", async move {
+            sqlx::query_scalar::<_, bool>(
+                "SELECT EXISTS(
+                    SELECT 1 FROM lists WHERE id = $1 AND user_id = $2 AND workspace_id = $3)",
+            )
+            .bind(to_db_id(list_id)?)
+            .bind(to_db_id(user_id)?)
+            .bind(to_db_id(workspace_id)?)
+            .fetch_one(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Create or extend the guest account for the email and store the invitation.
+    // Returns `None` when the email belongs to a registered user.
+    pub async fn create_guest_invitation(
+        &self,
+        user_id: i64,
+        list_id: i64,
+        email: &str,
+        guest_username: &str,
+        token_hash: &str,
+        expires_at: OffsetDateTime,
+    ) -> Result<Option<GuestInvitationRow>, Error> {
+        observe_query("invitation.create_guest_invitation", async move {
+            let mut tx = self.pool.begin().await?;
+
+            // Guests have an unusable password, they can only log in through invitations
+            let guest_user_id = sqlx::query_scalar::<_, i32>(
+                "INSERT INTO users (username, email, password, active, is_guest, guest_expires_at)
+                 VALUES ($1, $2, '!', TRUE, TRUE, $3)
+                 ON CONFLICT (email) DO UPDATE
+                    SET guest_expires_at = GREATEST(users.guest_expires_at, EXCLUDED.guest_expires_at)
+                    WHERE users.is_guest
+                 RETURNING id",
+            )
+            .bind(guest_username)
+            .bind(email)
+            .bind(expires_at)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            let Some(guest_user_id) = guest_user_id else {
+                return Ok(None);
+            };
+
+            let invitation = sqlx::query_as::<_, GuestInvitationRow>(
+                "INSERT INTO guest_invitations
+                    (list_id, invited_by, guest_user_id, email, token_hash, expires_at)
+                 VALUES ($1, $2, $3, $4, $5, $6)
+                 RETURNING id, list_id, guest_user_id, email, expires_at",
+            )
+            .bind(to_db_id(list_id)?)
+            .bind(to_db_id(user_id)?)
+            .bind(guest_user_id)
+            .bind(email)
+            .bind(token_hash)
+            .bind(expires_at)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            Ok(Some(invitation))
+        })
+        .await
+    }
+
+    // Mark a valid invitation as accepted, ignoring expired or converted guests
+    pub async fn accept_invitation(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<GuestInvitationRow>, Error> {
+        observe_query("invitation.accept_invitation", async move {
+            sqlx::query_as::<_, GuestInvitationRow>(
+                "UPDATE guest_invitations i SET accepted_at = COALESCE(i.accepted_at, NOW())
+                 FROM users u
+                 WHERE i.token_hash = $1 AND i.expires_at > NOW()
+                   AND u.id = i.guest_user_id AND u.is_guest
+                 RETURNING i.id, i.list_id, i.guest_user_id, i.email, i.expires_at",
+            )
+            .bind(token_hash)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Fetch the shared list if the guest still has a valid invitation to it
+    pub async fn fetch_guest_list(
+        &self,
+        guest_user_id: i64,
+        list_id: i64,
+    ) -> Result<Option<ListRow>, Error> {
+        observe_query("invitation.fetch_guest_list", async move {
+            sqlx::query_as::<_, ListRow>(
+                "SELECT l.id, l.name, l.archived, l.created_at, l.updated_at
+                 FROM lists l
+                 WHERE l.id = $1 AND EXISTS(
+                    SELECT 1 FROM guest_invitations i
+                    JOIN users u ON u.id = i.guest_user_id
+                    WHERE i.list_id = l.id AND i.guest_user_id = $2
+                      AND i.expires_at > NOW() AND u.is_guest)",
+            )
+            .bind(to_db_id(list_id)?)
+            .bind(to_db_id(guest_user_id)?)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // List the todos of a shared list
+    pub async fn list_guest_todos(&self, list_id: i64) -> Result<Vec<TodoRow>, Error> {
+        observe_query("invitation.list_guest_todos", async move {
+            let query = format!(
+                "SELECT {TODO_COLUMNS} FROM todos
+                 WHERE list_id = $1 AND deleted_at IS NULL
+                 ORDER BY created_at DESC, id DESC"
+            );
+
+            sqlx::query_as::<_, TodoRow>(&query)
+                .bind(to_db_id(list_id)?)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Remove guest accounts that expired without being converted
+    pub async fn purge_expired_guests(&self) -> Result<u64, Error> {
+        observe_query("invitation.purge_expired_guests", async move {
+            let result =
+                sqlx::query("DELETE FROM users WHERE is_guest AND guest_expires_at < NOW()")
+                    .execute(&self.pool)
+                    .await?;
+
+            Ok(result.rows_affected())
+        })
+        .await
+    }
+}
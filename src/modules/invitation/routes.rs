@@ -0,0 +1,126 @@
+//! #`Invitation` Routes
+//! This module defines the HTTP routes for guest invitations.
+
+use axum::extract::Path;
+use axum::routing::{get, post};
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Router};
+
+use crate::auth::GuestClaims;
+use crate::modules::common::{respond, ErrorResponse};
+use crate::modules::invitation::interfaces::{
+    AcceptInvitationRequest, GuestInvitationResponse, GuestListResponse, GuestLoginResponse,
+    InviteGuestRequest,
+};
+use crate::modules::invitation::repository::InvitationRepository;
+use crate::modules::invitation::service::InvitationService;
+use crate::modules::workspace::context::WorkspaceContext;
+use crate::utils::json::Json;
+use crate::AppState;
+
+// Creates and returns the invitation routes
+pub fn invitation_routes() -> Router<AppState> {
+    Router::new()
+        .route("/lists/{id}/guests", post(invite_guest_route))
+        .route("/invitations/accept", post(accept_invitation_route))
+        .route("/guest/list", get(fetch_guest_list_route))
+}
+
+fn invitation_service(app_state: &AppState) -> InvitationService {
+    InvitationService::new(InvitationRepository::new(app_state.db_pool.clone()))
+}
+
+// Invite Guest Route
+#[utoipa::path(
+    post,
+    path = "/lists/{id}/guests",
+    tag = "Invitations",
+    params(
+        ("id" = i64, Path, description = "List id")
+    ),
+    request_body = InviteGuestRequest,
+    responses(
+        (status = 201, description = "Guest invited successfully", body = GuestInvitationResponse),
+        (status = 400, description = "Invalid invitation data", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn invite_guest_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path(id): Path<i64>,
+    Json(invite_request): Json<InviteGuestRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::CREATED,
+        invitation_service(&app_state)
+            .invite_guest(
+                workspace.user_id,
+                workspace.workspace_id,
+                id,
+                invite_request,
+            )
+            .await,
+    )
+}
+
+// Accept Invitation Route
+#[utoipa::path(
+    post,
+    path = "/invitations/accept",
+    tag = "Invitations",
+    request_body = AcceptInvitationRequest,
+    responses(
+        (status = 200, description = "Invitation accepted", body = GuestLoginResponse),
+        (status = 401, description = "Invitation is invalid or expired", body = ErrorResponse)
+    )
+)]
+pub async fn accept_invitation_route(
+    State(app_state): State<AppState>,
+    Json(accept_request): Json<AcceptInvitationRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        invitation_service(&app_state)
+            .accept_invitation(accept_request, &app_state.encoding_key)
+            .await,
+    )
+}
+
+// Fetch Guest List Route
+#[utoipa::path(
+    get,
+    path = "/guest/list",
+    tag = "Invitations",
+    responses(
+        (status = 200, description = "Shared list fetched successfully", body = GuestListResponse),
+        (status = 404, description = "List not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn fetch_guest_list_route(
+    State(app_state): State<AppState>,
+    claims: GuestClaims,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        invitation_service(&app_state)
+            .fetch_guest_list(&claims)
+            .await,
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invitation_routes_creation() {
+        let _routes = invitation_routes();
+        assert!(true);
+    }
+}
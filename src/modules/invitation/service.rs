@@ -0,0 +1,212 @@
+//! # `Invitation` Service
+//!
+//! This module contains the bussiness logic for guest invitations.
+
+use email_address::EmailAddress;
+use jsonwebtoken::EncodingKey;
+use time::{Duration, OffsetDateTime};
+
+use crate::{
+    auth::{generate_guest_token, GuestClaims},
+    modules::{
+        common::AppError,
+        invitation::{
+            interfaces::{
+                AcceptInvitationRequest, GuestInvitationResponse, GuestListResponse,
+                GuestLoginResponse, InviteGuestRequest,
+            },
+            repository::InvitationRepository,
+        },
+        list::interfaces::ListResponse,
+        todo::interfaces::TodoResponse,
+    },
+    utils::token::{generate_random_token, hash_token},
+};
+
+// Lifetime of a guest account and its invitations
+const GUEST_ACCOUNT_DAYS: i64 = 7;
+
+pub struct InvitationService {
+    invitation_repository: InvitationRepository,
+}
+
+impl InvitationService {
+    pub const fn new(invitation_repository: InvitationRepository) -> Self {
+        Self {
+            invitation_repository,
+        }
+    }
+
+    // Invite a guest by email to one of the user lists in the workspace
+    pub async fn invite_guest(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        list_id: i64,
+        invite_request: InviteGuestRequest,
+    ) -> Result<GuestInvitationResponse, AppError> {
+        let Some(email) = invite_request
+            .email
+            .map(|email| email.trim().to_lowercase())
+        else {
+            return Err(AppError::Validation(
+                "Missing required fields: email".to_string(),
+            ));
+        };
+        if !EmailAddress::is_valid(&email) {
+            return Err(AppError::Validation("Email is not valid".to_string()));
+        }
+
+        match self
+            .invitation_repository
+            .is_list_owner(user_id, workspace_id, list_id)
+            .await
+        {
+            Ok(true) => {}
+            Ok(false) => return Err(AppError::NotFound("List not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error checking list ownership: {}", e);
+                return Err(AppError::Database(e));
+            }
+        }
+
+        // Expired guests are cleaned up so their emails can be invited again
+        if let Err(e) = self.invitation_repository.purge_expired_guests().await {
+            tracing::warn!("Error purging expired guests: {}", e);
+        }
+
+        let token = generate_random_token();
+        let guest_username = format!("guest_{}", &generate_random_token()[..16]);
+        let expires_at = OffsetDateTime::now_utc() + Duration::days(GUEST_ACCOUNT_DAYS);
+
+        match self
+            .invitation_repository
+            .create_guest_invitation(
+                user_id,
+                list_id,
+                &email,
+                &guest_username,
+                &hash_token(&token),
+                expires_at,
+            )
+            .await
+        {
+            Ok(Some(invitation)) => Ok(GuestInvitationResponse {
+                id: i64::from(invitation.id),
+                list_id: i64::from(invitation.list_id),
+                email: invitation.email,
+                token,
+                expires_at: invitation.expires_at,
+            }),
+            Ok(None) => Err(AppError::Conflict(
+                "Email belongs to a registered user".to_string(),
+            )),
+            Err(e) => {
+                tracing::warn!("Error creating guest invitation: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Exchange an invitation token for a guest session
+    pub async fn accept_invitation(
+        &self,
+        accept_request: AcceptInvitationRequest,
+        encoding_key: &EncodingKey,
+    ) -> Result<GuestLoginResponse, AppError> {
+        let Some(token) = accept_request.token else {
+            return Err(AppError::Validation(
+                "Missing required fields: token".to_string(),
+            ));
+        };
+
+        let invitation = match self
+            .invitation_repository
+            .accept_invitation(&hash_token(token.trim()))
+            .await
+        {
+            Ok(Some(invitation)) => invitation,
+            Ok(None) => {
+                return Err(AppError::Unauthorized(
+                    "Invitation is invalid or expired".to_string(),
+                ))
+            }
+            Err(e) => {
+                tracing::warn!("Error accepting invitation: {}", e);
+                return Err(AppError::Database(e));
+            }
+        };
+
+        tracing::info!(
+            "Guest {} accepted invitation {} to list {}",
+            invitation.guest_user_id,
+            invitation.id,
+            invitation.list_id
+        );
+
+        let token = generate_guest_token(
+            invitation.expires_at.unix_timestamp(),
+            i64::from(invitation.guest_user_id),
+            i64::from(invitation.list_id),
+            encoding_key,
+        )?;
+
+        Ok(GuestLoginResponse {
+            token,
+            list_id: i64::from(invitation.list_id),
+            expires_at: invitation.expires_at,
+        })
+    }
+
+    // Fetch the list shared with a guest
+    pub async fn fetch_guest_list(
+        &self,
+        claims: &GuestClaims,
+    ) -> Result<GuestListResponse, AppError> {
+        let list = match self
+            .invitation_repository
+            .fetch_guest_list(claims.user_id, claims.list_id)
+            .await
+        {
+            Ok(Some(list)) => list,
+            Ok(None) => return Err(AppError::NotFound("List not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error fetching guest list: {}", e);
+                return Err(AppError::Database(e));
+            }
+        };
+
+        match self
+            .invitation_repository
+            .list_guest_todos(claims.list_id)
+            .await
+        {
+            Ok(todos) => Ok(GuestListResponse {
+                list: ListResponse::from(list),
+                todos: todos.into_iter().map(TodoResponse::from).collect(),
+            }),
+            Err(e) => {
+                tracing::warn!("Error listing guest todos: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guest_account_lifetime() {
+        let expires_at = OffsetDateTime::now_utc() + Duration::days(GUEST_ACCOUNT_DAYS);
+        assert!(expires_at > OffsetDateTime::now_utc() + Duration::days(6));
+    }
+
+    #[test]
+    fn test_guest_username_format() {
+        let guest_username = format!("guest_{}", &generate_random_token()[..16]);
+        assert!(guest_username.starts_with("guest_"));
+        assert_eq!(guest_username.len(), 22);
+    }
+}
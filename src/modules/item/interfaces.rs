@@ -0,0 +1,120 @@
+//! # `Items` Interfaces
+//! This module defines the data structures from checklist Items module
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+use crate::utils::dates::rfc3339_option;
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct CreateItemRequest {
+    // Item title
+    pub title: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ValidatedCreateItemRequest {
+    pub title: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct UpdateItemRequest {
+    pub title: Option<String>,
+    pub completed: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ReorderItemsRequest {
+    // Every item id of the todo, in the new order
+    pub item_ids: Option<Vec<i64>>,
+}
+
+// Checklist item row as stored in database
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct ItemRow {
+    pub id: i32,
+    pub todo_id: i32,
+    pub title: String,
+    pub completed: bool,
+    pub position: i32,
+    pub created_at: Option<OffsetDateTime>,
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ItemResponse {
+    pub id: i64,
+    pub todo_id: i64,
+    pub title: String,
+    pub completed: bool,
+    pub position: i32,
+    #[serde(with = "rfc3339_option")]
+    pub created_at: Option<OffsetDateTime>,
+    #[serde(with = "rfc3339_option")]
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+impl From<ItemRow> for ItemResponse {
+    fn from(row: ItemRow) -> Self {
+        Self {
+            id: i64::from(row.id),
+            todo_id: i64::from(row.todo_id),
+            title: row.title,
+            completed: row.completed,
+            position: row.position,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+// Checklist progress of a todo, aggregated in database
+#[derive(Serialize, Deserialize, ToSchema, sqlx::FromRow, Clone, Debug)]
+pub struct ChecklistProgress {
+    pub total_items: i64,
+    pub completed_items: i64,
+    // Rounded percentage of completed items, 0 when the todo has no items
+    pub completion_percentage: i32,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ChecklistResponse {
+    pub todo_id: i64,
+    pub items: Vec<ItemResponse>,
+    pub progress: ChecklistProgress,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ItemMessageResponse {
+    pub message: String,
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reorder_items_request_deserialization() {
+        let request: ReorderItemsRequest = serde_json::from_str(r#"{"item_ids":[3,1,2]}"#).unwrap();
+        assert_eq!(request.item_ids, Some(vec![3, 1, 2]));
+    }
+
+    #[test]
+    fn test_item_response_from_row() {
+        let response = ItemResponse::from(ItemRow {
+            id: 4,
+            todo_id: 9,
+            title: "Pack charger".to_string(),
+            completed: true,
+            position: 2,
+            created_at: None,
+            updated_at: None,
+        });
+        assert_eq!(response.id, 4);
+        assert_eq!(response.todo_id, 9);
+        assert!(response.completed);
+        assert_eq!(response.position, 2);
+    }
+}
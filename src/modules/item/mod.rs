@@ -0,0 +1,9 @@
+//! # `Item` Mod
+//! Item imports for the checklist item module
+
+pub mod interfaces;
+pub mod repository;
+pub mod routes;
+pub mod service;
+
+pub use routes::item_routes;
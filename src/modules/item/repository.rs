@@ -0,0 +1,298 @@
+//! # `Item` Repository
+//! This module defines the item repository for checklist item operations.
+
+use sqlx::{Error, Pool, Postgres};
+
+use crate::database::retry_transient;
+use crate::modules::{
+    common::to_db_id,
+    item::interfaces::{ChecklistProgress, ItemRow},
+};
+use crate::telemetry::observe_query;
+
+const ITEM_COLUMNS: &str = "id, todo_id, title, completed, position, created_at, updated_at";
+
+pub struct ItemRepository {
+    pool: Pool<Postgres>,
+}
+
+impl ItemRepository {
+    // Constructor
+    pub const fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Check if a todo of the workspace is owned by the user
+    pub async fn is_todo_owner(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+    ) -> Result<bool, Error> {
+        observe_query("item.is_todo_owner", async move {
+            sqlx::query_scalar::<_, bool>(
+                "SELECT EXISTS(
+                    SELECT 1 FROM todos
+                    WHERE id = $1 AND user_id = $2 AND workspace_id = $3 AND deleted_at IS NULL)",
+            )
+            .bind(to_db_id(todo_id)?)
+            .bind(to_db_id(user_id)?)
+            .bind(to_db_id(workspace_id)?)
+            .fetch_one(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Append an item to the checklist of a todo, returns `None` when the todo is not owned
+    pub async fn create_item(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+        title: &str,
+    ) -> Result<Option<ItemRow>, Error> {
+        observe_query("item.create_item", async move {
+            let query = format!(
+                "INSERT INTO todo_items (todo_id, title, position)
+                 SELECT t.id, $3, COALESCE(
+                    (SELECT MAX(position) + 1 FROM todo_items WHERE todo_id = t.id), 0)
+                 FROM todos t
+                 WHERE t.id = $1 AND t.user_id = $2 AND t.workspace_id = $4 AND t.deleted_at IS NULL
+                 RETURNING {ITEM_COLUMNS}"
+            );
+
+            sqlx::query_as::<_, ItemRow>(&query)
+                .bind(to_db_id(todo_id)?)
+                .bind(to_db_id(user_id)?)
+                .bind(title)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // List the checklist of a todo in order
+    pub async fn list_items(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+    ) -> Result<Vec<ItemRow>, Error> {
+        observe_query("item.list_items", async move {
+            let query = format!(
+                "SELECT {ITEM_COLUMNS} FROM todo_items
+                 WHERE todo_id = $1
+                   AND EXISTS(
+                        SELECT 1 FROM todos
+                        WHERE id = $1 AND user_id = $2 AND workspace_id = $3 AND deleted_at IS NULL)
+                 ORDER BY position, id"
+            );
+
+            sqlx::query_as::<_, ItemRow>(&query)
+                .bind(to_db_id(todo_id)?)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Aggregate the checklist progress of a todo in a single query
+    pub async fn checklist_progress(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+    ) -> Result<ChecklistProgress, Error> {
+        observe_query("item.checklist_progress", async move {
+            sqlx::query_as::<_, ChecklistProgress>(
+                "SELECT
+                    COUNT(i.id) AS total_items,
+                    COUNT(i.id) FILTER (WHERE i.completed) AS completed_items,
+                    COALESCE(ROUND(
+                        100.0 * COUNT(i.id) FILTER (WHERE i.completed) / NULLIF(COUNT(i.id), 0)
+                    ), 0)::INTEGER AS completion_percentage
+                 FROM todos t
+                 LEFT JOIN todo_items i ON i.todo_id = t.id
+                 WHERE t.id = $1 AND t.user_id = $2 AND t.workspace_id = $3 AND t.deleted_at IS NULL",
+            )
+            .bind(to_db_id(todo_id)?)
+            .bind(to_db_id(user_id)?)
+            .bind(to_db_id(workspace_id)?)
+            .fetch_one(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Update item data, fields not provided are kept
+    pub async fn update_item(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+        id: i64,
+        title: Option<&str>,
+        completed: Option<bool>,
+    ) -> Result<Option<ItemRow>, Error> {
+        observe_query("item.update_item", async move {
+            let query = format!(
+                "UPDATE todo_items SET
+                    title = COALESCE($1, title),
+                    completed = COALESCE($2, completed)
+                 WHERE id = $3 AND todo_id = $4
+                   AND EXISTS(
+                        SELECT 1 FROM todos
+                        WHERE id = $4 AND user_id = $5 AND workspace_id = $6 AND deleted_at IS NULL)
+                 RETURNING {ITEM_COLUMNS}"
+            );
+
+            sqlx::query_as::<_, ItemRow>(&query)
+                .bind(title)
+                .bind(completed)
+                .bind(to_db_id(id)?)
+                .bind(to_db_id(todo_id)?)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Flip the completion of an item
+    pub async fn toggle_item(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+        id: i64,
+    ) -> Result<Option<ItemRow>, Error> {
+        observe_query("item.toggle_item", async move {
+            let query = format!(
+                "UPDATE todo_items SET completed = NOT completed
+                 WHERE id = $1 AND todo_id = $2
+                   AND EXISTS(
+                        SELECT 1 FROM todos
+                        WHERE id = $2 AND user_id = $3 AND workspace_id = $4 AND deleted_at IS NULL)
+                 RETURNING {ITEM_COLUMNS}"
+            );
+
+            sqlx::query_as::<_, ItemRow>(&query)
+                .bind(to_db_id(id)?)
+                .bind(to_db_id(todo_id)?)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Reorder the checklist of a todo. `item_ids` must list every item of the todo
+    // exactly once, otherwise nothing is changed and `false` is returned.
+    pub async fn reorder_items(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+        item_ids: &[i64],
+    ) -> Result<bool, Error> {
+        retry_transient("item.reorder_items", move || async move {
+            let item_ids = item_ids
+                .iter()
+                .map(|id| to_db_id(*id))
+                .collect::<Result<Vec<i32>, Error>>()?;
+            let mut tx = self.pool.begin().await?;
+
+            // Lock the checklist so concurrent inserts can't slip between the checks
+            let current_ids = sqlx::query_scalar::<_, i32>(
+                "SELECT i.id FROM todo_items i
+                 JOIN todos t ON t.id = i.todo_id
+                 WHERE i.todo_id = $1 AND t.user_id = $2 AND t.workspace_id = $3
+                   AND t.deleted_at IS NULL
+                 FOR UPDATE OF i",
+            )
+            .bind(to_db_id(todo_id)?)
+            .bind(to_db_id(user_id)?)
+            .bind(to_db_id(workspace_id)?)
+            .fetch_all(&mut *tx)
+            .await?;
+
+            let mut sorted_ids = item_ids.clone();
+            sorted_ids.sort_unstable();
+            sorted_ids.dedup();
+            if sorted_ids.len() != item_ids.len() || sorted_ids.len() != current_ids.len() {
+                return Ok(false);
+            }
+
+            let updated = sqlx::query(
+                "UPDATE todo_items i SET position = o.ord - 1
+                 FROM UNNEST($1::INTEGER[]) WITH ORDINALITY AS o(id, ord)
+                 WHERE i.id = o.id AND i.todo_id = $2",
+            )
+            .bind(&item_ids)
+            .bind(to_db_id(todo_id)?)
+            .execute(&mut *tx)
+            .await?;
+
+            if usize::try_from(updated.rows_affected()).ok() != Some(item_ids.len()) {
+                return Ok(false);
+            }
+
+            tx.commit().await?;
+            Ok(true)
+        })
+        .await
+    }
+
+    // Delete an item, returns whether a row was removed
+    pub async fn delete_item(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+        id: i64,
+    ) -> Result<bool, Error> {
+        observe_query("item.delete_item", async move {
+            let result = sqlx::query(
+                "DELETE FROM todo_items i
+                 USING todos t
+                 WHERE i.todo_id = t.id AND i.id = $1 AND i.todo_id = $2 AND t.user_id = $3
+                   AND t.workspace_id = $4 AND t.deleted_at IS NULL",
+            )
+            .bind(to_db_id(id)?)
+            .bind(to_db_id(todo_id)?)
+            .bind(to_db_id(user_id)?)
+            .bind(to_db_id(workspace_id)?)
+            .execute(&self.pool)
+            .await?;
+
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_item_columns_match_row() {
+        for column in [
+            "id",
+            "todo_id",
+            "title",
+            "completed",
+            "position",
+            "created_at",
+            "updated_at",
+        ] {
+            assert!(ITEM_COLUMNS.contains(column));
+        }
+    }
+}
@@ -0,0 +1,264 @@
+//! # `Item` Service
+//!
+//! This module contains the bussiness logic for checklist item operations.
+
+use crate::{
+    modules::{
+        common::AppError,
+        item::{
+            interfaces::{
+                ChecklistResponse, CreateItemRequest, ItemMessageResponse, ItemResponse,
+                ReorderItemsRequest, UpdateItemRequest, ValidatedCreateItemRequest,
+            },
+            repository::ItemRepository,
+        },
+    },
+    utils::validation::validate_required_fields,
+};
+
+// Maximum length of an item title
+const MAX_ITEM_TITLE_LENGTH: usize = 255;
+
+// Validate an item title
+fn validate_title(title: &str) -> Result<&str, AppError> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err(AppError::Validation("Title cannot be empty".to_string()));
+    }
+    if title.chars().count() > MAX_ITEM_TITLE_LENGTH {
+        return Err(AppError::Validation(format!(
+            "Title must have at most {MAX_ITEM_TITLE_LENGTH} characters"
+        )));
+    }
+    Ok(title)
+}
+
+pub struct ItemService {
+    item_repository: ItemRepository,
+}
+
+impl ItemService {
+    pub const fn new(item_repository: ItemRepository) -> Self {
+        Self { item_repository }
+    }
+
+    // Add an item at the end of a todo checklist
+    pub async fn create_item(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+        create_request: CreateItemRequest,
+    ) -> Result<ItemResponse, AppError> {
+        let validated: ValidatedCreateItemRequest =
+            match validate_required_fields(&create_request, vec!["title"]) {
+                Err(missing) => return Err(AppError::InvalidFields(missing)),
+                Ok(item) => item,
+            };
+        let title = validate_title(&validated.title)?;
+
+        match self
+            .item_repository
+            .create_item(user_id, workspace_id, todo_id, title)
+            .await
+        {
+            Ok(Some(item)) => Ok(ItemResponse::from(item)),
+            Ok(None) => Err(AppError::NotFound("Todo not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error creating checklist item: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // List the checklist of a todo with its progress
+    pub async fn list_items(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+    ) -> Result<ChecklistResponse, AppError> {
+        self.ensure_todo_owner(user_id, workspace_id, todo_id)
+            .await?;
+
+        let items = match self
+            .item_repository
+            .list_items(user_id, workspace_id, todo_id)
+            .await
+        {
+            Ok(items) => items,
+            Err(e) => {
+                tracing::warn!("Error listing checklist items: {}", e);
+                return Err(AppError::Database(e));
+            }
+        };
+
+        match self
+            .item_repository
+            .checklist_progress(user_id, workspace_id, todo_id)
+            .await
+        {
+            Ok(progress) => Ok(ChecklistResponse {
+                todo_id,
+                items: items.into_iter().map(ItemResponse::from).collect(),
+                progress,
+            }),
+            Err(e) => {
+                tracing::warn!("Error computing checklist progress: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Update item data
+    pub async fn update_item(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+        id: i64,
+        update_request: UpdateItemRequest,
+    ) -> Result<ItemResponse, AppError> {
+        let title = update_request
+            .title
+            .as_deref()
+            .map(validate_title)
+            .transpose()?;
+
+        match self
+            .item_repository
+            .update_item(
+                user_id,
+                workspace_id,
+                todo_id,
+                id,
+                title,
+                update_request.completed,
+            )
+            .await
+        {
+            Ok(Some(item)) => Ok(ItemResponse::from(item)),
+            Ok(None) => Err(AppError::NotFound("Item not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error updating checklist item: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Toggle the completion of an item
+    pub async fn toggle_item(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+        id: i64,
+    ) -> Result<ItemResponse, AppError> {
+        match self
+            .item_repository
+            .toggle_item(user_id, workspace_id, todo_id, id)
+            .await
+        {
+            Ok(Some(item)) => Ok(ItemResponse::from(item)),
+            Ok(None) => Err(AppError::NotFound("Item not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error toggling checklist item: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Reorder the checklist of a todo
+    pub async fn reorder_items(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+        reorder_request: ReorderItemsRequest,
+    ) -> Result<ChecklistResponse, AppError> {
+        let Some(item_ids) = reorder_request.item_ids else {
+            return Err(AppError::Validation(
+                "Missing required fields: item_ids".to_string(),
+            ));
+        };
+        self.ensure_todo_owner(user_id, workspace_id, todo_id)
+            .await?;
+
+        match self
+            .item_repository
+            .reorder_items(user_id, workspace_id, todo_id, &item_ids)
+            .await
+        {
+            Ok(true) => self.list_items(user_id, workspace_id, todo_id).await,
+            Ok(false) => Err(AppError::Validation(
+                "item_ids must list every item of the todo exactly once".to_string(),
+            )),
+            Err(e) => {
+                tracing::warn!("Error reordering checklist items: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Delete an item
+    pub async fn delete_item(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+        id: i64,
+    ) -> Result<ItemMessageResponse, AppError> {
+        match self
+            .item_repository
+            .delete_item(user_id, workspace_id, todo_id, id)
+            .await
+        {
+            Ok(true) => Ok(ItemMessageResponse {
+                message: "Item deleted successfully".to_string(),
+            }),
+            Ok(false) => Err(AppError::NotFound("Item not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error deleting checklist item: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Make sure the todo exists and belongs to the user
+    async fn ensure_todo_owner(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+    ) -> Result<(), AppError> {
+        match self
+            .item_repository
+            .is_todo_owner(user_id, workspace_id, todo_id)
+            .await
+        {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(AppError::NotFound("Todo not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error checking todo ownership: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_title_trims() {
+        assert_eq!(validate_title("  Pack bags ").unwrap(), "Pack bags");
+    }
+
+    #[test]
+    fn test_validate_title_rejects_empty_and_long() {
+        assert!(validate_title("   ").is_err());
+        assert!(validate_title(&"a".repeat(MAX_ITEM_TITLE_LENGTH + 1)).is_err());
+    }
+}
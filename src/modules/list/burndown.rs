@@ -0,0 +1,115 @@
+//! # `List` Burndown
+//! Daily open todos of a list, rebuilt from the todos entering and leaving it. The
+//! database sums the changes per day, this module validates the asked range and
+//! accumulates the changes into one count per day.
+
+use time::{Date, Duration};
+
+use crate::{
+    modules::list::interfaces::{BurndownDayResponse, BurndownDeltaRow, BurndownQuery},
+    utils::dates::parse_day,
+};
+
+// Days covered when no start day is asked, two weeks sprints
+const DEFAULT_BURNDOWN_DAYS: i64 = 14;
+
+// Largest range a burndown can cover
+const MAX_BURNDOWN_DAYS: i64 = 366;
+
+// Validate the asked range as `(from, to)`, ending `today` by default
+pub fn burndown_range(query: &BurndownQuery, today: Date) -> Result<(Date, Date), String> {
+    let to = query
+        .to
+        .as_deref()
+        .map(|to| parse_day(to, "to"))
+        .transpose()?
+        .unwrap_or(today);
+    let from = query
+        .from
+        .as_deref()
+        .map(|from| parse_day(from, "from"))
+        .transpose()?
+        .unwrap_or_else(|| to - Duration::days(DEFAULT_BURNDOWN_DAYS - 1));
+
+    if from > to {
+        return Err("from must not be after to".to_string());
+    }
+    if (to - from).whole_days() >= MAX_BURNDOWN_DAYS {
+        return Err(format!("Range must not exceed {MAX_BURNDOWN_DAYS} days"));
+    }
+    Ok((from, to))
+}
+
+// Open todos at the end of each day from `from` to `to`, starting with `open_before`
+// todos. `deltas` come sorted by day.
+pub fn burndown_days(
+    from: Date,
+    to: Date,
+    open_before: i64,
+    deltas: &[BurndownDeltaRow],
+) -> Vec<BurndownDayResponse> {
+    let mut days = Vec::new();
+    let mut deltas = deltas.iter().peekable();
+    let mut open = open_before;
+    let mut day = Some(from);
+    while let Some(current) = day.filter(|current| *current <= to) {
+        while let Some(delta) = deltas.next_if(|delta| delta.day <= current) {
+            open += delta.delta;
+        }
+        days.push(BurndownDayResponse {
+            day: current.to_string(),
+            open,
+        });
+        day = current.next_day();
+    }
+    days
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use time::macros::date;
+
+    use super::*;
+
+    fn delta(day: Date, delta: i64) -> BurndownDeltaRow {
+        BurndownDeltaRow { day, delta }
+    }
+
+    #[test]
+    fn test_burndown_range_defaults_to_two_weeks() {
+        let (from, to) = burndown_range(&BurndownQuery::default(), date!(2025 - 03 - 14)).unwrap();
+        assert_eq!(from, date!(2025 - 03 - 01));
+        assert_eq!(to, date!(2025 - 03 - 14));
+    }
+
+    #[test]
+    fn test_burndown_range_rejects_invalid_ranges() {
+        let today = date!(2025 - 03 - 14);
+        let query = |from: &str, to: &str| BurndownQuery {
+            from: Some(from.to_string()),
+            to: Some(to.to_string()),
+        };
+        assert!(burndown_range(&query("2025-03-02", "2025-03-01"), today).is_err());
+        assert!(burndown_range(&query("2024-01-01", "2025-03-01"), today).is_err());
+        assert!(burndown_range(&query("2025-03-01", "2025-03-01"), today).is_ok());
+    }
+
+    #[test]
+    fn test_burndown_days_accumulates_deltas() {
+        let days = burndown_days(
+            date!(2025 - 03 - 01),
+            date!(2025 - 03 - 04),
+            5,
+            &[
+                delta(date!(2025 - 03 - 02), 3),
+                delta(date!(2025 - 03 - 04), -6),
+            ],
+        );
+
+        let open: Vec<i64> = days.iter().map(|day| day.open).collect();
+        assert_eq!(open, vec![5, 8, 8, 2]);
+        assert_eq!(days[0].day, "2025-03-01");
+        assert_eq!(days[3].day, "2025-03-04");
+    }
+}
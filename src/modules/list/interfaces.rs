@@ -0,0 +1,383 @@
+//! # `Lists` Interfaces
+//! This module defines the data structures from Lists module
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use time::{Date, OffsetDateTime};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::utils::dates::rfc3339_option;
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ListRequest {
+    // List name
+    pub name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ValidatedListRequest {
+    pub name: String,
+}
+
+// Query parameters of the list listing
+#[derive(Deserialize, IntoParams, Clone, Debug, Default)]
+#[into_params(parameter_in = Query)]
+pub struct ListFilter {
+    /// Also return archived lists, defaults to false
+    pub include_archived: Option<bool>,
+}
+
+// List row as stored in database
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct ListRow {
+    pub id: i32,
+    pub name: String,
+    pub archived: bool,
+    pub created_at: Option<OffsetDateTime>,
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ListResponse {
+    pub id: i64,
+    pub name: String,
+    pub archived: bool,
+    #[serde(with = "rfc3339_option")]
+    pub created_at: Option<OffsetDateTime>,
+    #[serde(with = "rfc3339_option")]
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+impl From<ListRow> for ListResponse {
+    fn from(row: ListRow) -> Self {
+        Self {
+            id: i64::from(row.id),
+            name: row.name,
+            archived: row.archived,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ListMessageResponse {
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct TransferListRequest {
+    // Username of the user receiving the list
+    pub to_username: Option<String>,
+}
+
+// List ownership transfer row as stored in database
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct ListTransferRow {
+    pub id: i32,
+    pub list_id: i32,
+    pub from_user_id: i32,
+    pub to_user_id: i32,
+    pub status: String,
+    pub created_at: Option<OffsetDateTime>,
+    pub resolved_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ListTransferResponse {
+    pub id: i64,
+    pub list_id: i64,
+    pub from_user_id: i64,
+    pub to_user_id: i64,
+    // One of `pending`, `accepted` or `declined`
+    pub status: String,
+    #[serde(with = "rfc3339_option")]
+    pub created_at: Option<OffsetDateTime>,
+    #[serde(with = "rfc3339_option")]
+    pub resolved_at: Option<OffsetDateTime>,
+}
+
+impl From<ListTransferRow> for ListTransferResponse {
+    fn from(row: ListTransferRow) -> Self {
+        Self {
+            id: i64::from(row.id),
+            list_id: i64::from(row.list_id),
+            from_user_id: i64::from(row.from_user_id),
+            to_user_id: i64::from(row.to_user_id),
+            status: row.status,
+            created_at: row.created_at,
+            resolved_at: row.resolved_at,
+        }
+    }
+}
+
+// Role of a user on a list, members are either viewers or editors
+#[derive(Serialize, Deserialize, ToSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ListRole {
+    Owner,
+    Editor,
+    Viewer,
+}
+
+impl ListRole {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Owner => "owner",
+            Self::Editor => "editor",
+            Self::Viewer => "viewer",
+        }
+    }
+
+    // Owners and editors may change the todos of the list
+    pub const fn can_edit(self) -> bool {
+        matches!(self, Self::Owner | Self::Editor)
+    }
+}
+
+impl FromStr for ListRole {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "owner" => Ok(Self::Owner),
+            "editor" => Ok(Self::Editor),
+            "viewer" => Ok(Self::Viewer),
+            _ => Err("Role must be one of viewer, editor".to_string()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ShareListRequest {
+    // Username of the user the list is shared with
+    pub username: Option<String>,
+    // `viewer` or `editor`, defaults to `viewer`
+    pub role: Option<String>,
+}
+
+// Owner of a list or todo and the role of the requesting user on it
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct ListAccessRow {
+    pub owner_id: i32,
+    pub role: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ListAccess {
+    pub owner_id: i64,
+    pub role: ListRole,
+}
+
+impl From<ListAccessRow> for ListAccess {
+    fn from(row: ListAccessRow) -> Self {
+        Self {
+            owner_id: i64::from(row.owner_id),
+            // Unknown roles get the least privileges
+            role: row.role.parse().unwrap_or(ListRole::Viewer),
+        }
+    }
+}
+
+// List member row as stored in database, with the member username
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct ListMemberRow {
+    pub list_id: i32,
+    pub user_id: i32,
+    pub username: String,
+    pub role: String,
+    pub created_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ListMemberResponse {
+    pub list_id: i64,
+    pub user_id: i64,
+    pub username: String,
+    pub role: ListRole,
+    #[serde(with = "rfc3339_option")]
+    pub created_at: Option<OffsetDateTime>,
+}
+
+impl From<ListMemberRow> for ListMemberResponse {
+    fn from(row: ListMemberRow) -> Self {
+        Self {
+            list_id: i64::from(row.list_id),
+            user_id: i64::from(row.user_id),
+            username: row.username,
+            role: row.role.parse().unwrap_or(ListRole::Viewer),
+            created_at: row.created_at,
+        }
+    }
+}
+
+// List shared with the user by another one
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct SharedListRow {
+    #[sqlx(flatten)]
+    pub list: ListRow,
+    pub owner_id: i32,
+    pub role: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct SharedListResponse {
+    #[serde(flatten)]
+    pub list: ListResponse,
+    pub owner_id: i64,
+    pub role: ListRole,
+}
+
+impl From<SharedListRow> for SharedListResponse {
+    fn from(row: SharedListRow) -> Self {
+        Self {
+            list: ListResponse::from(row.list),
+            owner_id: i64::from(row.owner_id),
+            role: row.role.parse().unwrap_or(ListRole::Viewer),
+        }
+    }
+}
+
+// Query parameters of the burndown of a list
+#[derive(Deserialize, IntoParams, Clone, Debug, Default)]
+#[into_params(parameter_in = Query)]
+pub struct BurndownQuery {
+    /// First day, as `YYYY-MM-DD`, defaults to 13 days before `to`
+    pub from: Option<String>,
+    /// Last day included, as `YYYY-MM-DD`, defaults to today
+    pub to: Option<String>,
+}
+
+// Change of the open todos of a list over a day
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct BurndownDeltaRow {
+    pub day: Date,
+    pub delta: i64,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, PartialEq, Eq)]
+pub struct BurndownDayResponse {
+    pub day: String,
+    // Open todos in the list at the end of the day
+    pub open: i64,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct BurndownResponse {
+    pub list_id: i64,
+    pub from: String,
+    pub to: String,
+    // One entry per day of the range
+    pub days: Vec<BurndownDayResponse>,
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_request_deserialization() {
+        let request: ListRequest = serde_json::from_str(r#"{"name":"Groceries"}"#).unwrap();
+        assert_eq!(request.name, Some("Groceries".to_string()));
+    }
+
+    #[test]
+    fn test_list_response_from_row() {
+        let row = ListRow {
+            id: 3,
+            name: "Work".to_string(),
+            archived: true,
+            created_at: None,
+            updated_at: None,
+        };
+
+        let response = ListResponse::from(row);
+        assert_eq!(response.id, 3);
+        assert_eq!(response.name, "Work");
+        assert!(response.archived);
+        assert_eq!(response.created_at, None);
+    }
+
+    #[test]
+    fn test_list_transfer_response_from_row() {
+        let response = ListTransferResponse::from(ListTransferRow {
+            id: 1,
+            list_id: 3,
+            from_user_id: 10,
+            to_user_id: 20,
+            status: "pending".to_string(),
+            created_at: None,
+            resolved_at: None,
+        });
+        assert_eq!(response.list_id, 3);
+        assert_eq!(response.to_user_id, 20);
+        assert_eq!(response.status, "pending");
+    }
+
+    #[test]
+    fn test_list_role_permissions() {
+        assert_eq!("Editor".parse::<ListRole>(), Ok(ListRole::Editor));
+        assert!("admin".parse::<ListRole>().is_err());
+        assert!(ListRole::Owner.can_edit());
+        assert!(ListRole::Editor.can_edit());
+        assert!(!ListRole::Viewer.can_edit());
+    }
+
+    #[test]
+    fn test_list_access_defaults_to_viewer() {
+        let access = ListAccess::from(ListAccessRow {
+            owner_id: 4,
+            role: "unknown".to_string(),
+        });
+        assert_eq!(access.owner_id, 4);
+        assert_eq!(access.role, ListRole::Viewer);
+    }
+}
+
+// Most todos a status column of a list may hold
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct WipLimit {
+    // Todo status of the column, e.g. `in_progress`
+    pub status: String,
+    pub max_todos: i64,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct UpdateWipLimitsRequest {
+    // Limits of the list, replacing the current ones. Columns left out are unlimited.
+    pub limits: Vec<WipLimit>,
+}
+
+// WIP limit row as stored in database, with the todos currently in the column
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct WipLimitRow {
+    pub status: String,
+    pub max_todos: i32,
+    pub todos: i64,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct WipLimitResponse {
+    pub status: String,
+    pub max_todos: i64,
+    // Unarchived todos currently in the column
+    pub todos: i64,
+}
+
+impl From<WipLimitRow> for WipLimitResponse {
+    fn from(row: WipLimitRow) -> Self {
+        Self {
+            status: row.status,
+            max_todos: i64::from(row.max_todos),
+            todos: row.todos,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct WipLimitsResponse {
+    pub list_id: i64,
+    pub limits: Vec<WipLimitResponse>,
+}
@@ -0,0 +1,10 @@
+//! # `List` Mod
+//! List imports for the list module
+
+pub mod burndown;
+pub mod interfaces;
+pub mod repository;
+pub mod routes;
+pub mod service;
+
+pub use routes::list_routes;
@@ -0,0 +1,574 @@
+//! # `List` Repository
+//! This module defines the list repository for list operations.
+
+use sqlx::{Error, Pool, Postgres};
+use time::OffsetDateTime;
+
+use crate::modules::{
+    common::to_db_id,
+    list::interfaces::{
+        BurndownDeltaRow, ListAccessRow, ListMemberRow, ListRow, ListTransferRow, SharedListRow,
+        WipLimitRow,
+    },
+};
+use crate::telemetry::observe_query;
+
+pub const LIST_COLUMNS: &str = "id, name, archived, created_at, updated_at";
+
+// Owner of the list `$1` in the workspace `$3` and the role of the user `$2` on it,
+// owner or member
+pub const LIST_ACCESS_QUERY: &str = "SELECT l.user_id AS owner_id,
+        CASE WHEN l.user_id = $2 THEN 'owner' ELSE m.role END AS role
+     FROM lists l
+     LEFT JOIN list_members m ON m.list_id = l.id AND m.user_id = $2
+     WHERE l.id = $1 AND l.workspace_id = $3 AND (l.user_id = $2 OR m.user_id IS NOT NULL)";
+
+// Todos counted against the WIP limit of the status column `w.status` of the list
+// `w.list_id`, the unarchived todos in the column
+pub const WIP_COLUMN_TODOS: &str = "SELECT COUNT(*) FROM todos t
+     WHERE t.list_id = w.list_id AND t.status = w.status
+       AND t.deleted_at IS NULL AND NOT t.archived";
+
+const TRANSFER_COLUMNS: &str =
+    "id, list_id, from_user_id, to_user_id, status, created_at, resolved_at";
+
+pub struct ListRepository {
+    pool: Pool<Postgres>,
+}
+
+impl ListRepository {
+    // Constructor
+    pub const fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Create a list for an user in a workspace
+    pub async fn create_list(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        name: &str,
+    ) -> Result<ListRow, Error> {
+        observe_query("list.create_list", async move {
+            let query = format!(
+                "INSERT INTO lists (user_id, workspace_id, name) VALUES ($1, $2, $3)
+                 RETURNING {LIST_COLUMNS}"
+            );
+
+            sqlx::query_as::<_, ListRow>(&query)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .bind(name)
+                .fetch_one(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // List the lists from an user in a workspace, archived lists only when asked for
+    pub async fn list_lists(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        include_archived: bool,
+    ) -> Result<Vec<ListRow>, Error> {
+        observe_query("list.list_lists", async move {
+            let query = format!(
+                "SELECT {LIST_COLUMNS} FROM lists
+                 WHERE user_id = $1 AND workspace_id = $3 AND ($2 OR NOT archived)
+                 ORDER BY name, id"
+            );
+
+            sqlx::query_as::<_, ListRow>(&query)
+                .bind(to_db_id(user_id)?)
+                .bind(include_archived)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Rename a list owned by the user
+    pub async fn rename_list(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+        name: &str,
+    ) -> Result<Option<ListRow>, Error> {
+        observe_query("list.rename_list", async move {
+            let query = format!(
+                "UPDATE lists SET name = $1 WHERE id = $2 AND user_id = $3 AND workspace_id = $4
+                 RETURNING {LIST_COLUMNS}"
+            );
+
+            sqlx::query_as::<_, ListRow>(&query)
+                .bind(name)
+                .bind(to_db_id(id)?)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Archive or unarchive a list owned by the user
+    pub async fn set_archived(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+        archived: bool,
+    ) -> Result<Option<ListRow>, Error> {
+        observe_query("list.set_archived", async move {
+            let query = format!(
+                "UPDATE lists SET archived = $1 WHERE id = $2 AND user_id = $3 AND workspace_id = $4
+                 RETURNING {LIST_COLUMNS}"
+            );
+
+            sqlx::query_as::<_, ListRow>(&query)
+                .bind(archived)
+                .bind(to_db_id(id)?)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Delete a list, its todos are kept and detached from it
+    pub async fn delete_list(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+    ) -> Result<bool, Error> {
+        observe_query("list.delete_list", async move {
+            let result = sqlx::query(
+                "DELETE FROM lists WHERE id = $1 AND user_id = $2 AND workspace_id = $3",
+            )
+            .bind(to_db_id(id)?)
+            .bind(to_db_id(user_id)?)
+            .bind(to_db_id(workspace_id)?)
+            .execute(&self.pool)
+            .await?;
+
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    // Offer a list to another member of its workspace, replacing any pending offer for the list
+    pub async fn create_transfer(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        list_id: i64,
+        to_username: &str,
+    ) -> Result<Option<ListTransferRow>, Error> {
+        observe_query("list.create_transfer", async move {
+            let query = format!(
+                "INSERT INTO list_transfers (list_id, from_user_id, to_user_id)
+                 SELECT l.id, l.user_id, u.id
+                 FROM lists l
+                 JOIN users u ON u.username = $3
+                 JOIN workspace_members wm ON wm.workspace_id = l.workspace_id AND wm.user_id = u.id
+                 WHERE l.id = $1 AND l.user_id = $2 AND l.workspace_id = $4 AND u.id <> l.user_id
+                 ON CONFLICT (list_id) WHERE status = 'pending'
+                 DO UPDATE SET to_user_id = EXCLUDED.to_user_id, created_at = NOW()
+                 RETURNING {TRANSFER_COLUMNS}"
+            );
+
+            sqlx::query_as::<_, ListTransferRow>(&query)
+                .bind(to_db_id(list_id)?)
+                .bind(to_db_id(user_id)?)
+                .bind(to_username)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // List pending transfers offered to the user
+    pub async fn list_incoming_transfers(
+        &self,
+        user_id: i64,
+    ) -> Result<Vec<ListTransferRow>, Error> {
+        observe_query("list.list_incoming_transfers", async move {
+            let query = format!(
+                "SELECT {TRANSFER_COLUMNS} FROM list_transfers
+                 WHERE to_user_id = $1 AND status = 'pending'
+                 ORDER BY created_at DESC"
+            );
+
+            sqlx::query_as::<_, ListTransferRow>(&query)
+                .bind(to_db_id(user_id)?)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Accept a pending transfer, moving the list, its todos and their tags to the recipient
+    pub async fn accept_transfer(
+        &self,
+        user_id: i64,
+        transfer_id: i64,
+    ) -> Result<Option<ListTransferRow>, Error> {
+        observe_query("list.accept_transfer", async move {
+            let mut tx = self.pool.begin().await?;
+
+            let query = format!(
+                "UPDATE list_transfers SET status = 'accepted', resolved_at = NOW()
+                 WHERE id = $1 AND to_user_id = $2 AND status = 'pending'
+                 RETURNING {TRANSFER_COLUMNS}"
+            );
+            let Some(transfer) = sqlx::query_as::<_, ListTransferRow>(&query)
+                .bind(to_db_id(transfer_id)?)
+                .bind(to_db_id(user_id)?)
+                .fetch_optional(&mut *tx)
+                .await?
+            else {
+                return Ok(None);
+            };
+
+            // Tags are private to each user, the tags of the list todos are replaced by the
+            // recipient's tags of the same names, created when missing
+            sqlx::query(
+                "INSERT INTO tags (user_id, name)
+                 SELECT DISTINCT $2::INTEGER, tg.name
+                 FROM todo_tags tt
+                 JOIN todos t ON t.id = tt.todo_id
+                 JOIN tags tg ON tg.id = tt.tag_id
+                 WHERE t.list_id = $1 AND tg.user_id <> $2
+                 ON CONFLICT (user_id, name) DO NOTHING",
+            )
+            .bind(transfer.list_id)
+            .bind(transfer.to_user_id)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query(
+                "INSERT INTO todo_tags (todo_id, tag_id)
+                 SELECT tt.todo_id, rt.id
+                 FROM todo_tags tt
+                 JOIN todos t ON t.id = tt.todo_id
+                 JOIN tags tg ON tg.id = tt.tag_id
+                 JOIN tags rt ON rt.user_id = $2 AND rt.name = tg.name
+                 WHERE t.list_id = $1 AND tg.user_id <> $2
+                 ON CONFLICT (todo_id, tag_id) DO NOTHING",
+            )
+            .bind(transfer.list_id)
+            .bind(transfer.to_user_id)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query(
+                "DELETE FROM todo_tags tt USING todos t, tags tg
+                 WHERE tt.todo_id = t.id AND tg.id = tt.tag_id
+                   AND t.list_id = $1 AND tg.user_id <> $2",
+            )
+            .bind(transfer.list_id)
+            .bind(transfer.to_user_id)
+            .execute(&mut *tx)
+            .await?;
+
+            // The recipient no longer needs to be a member of its own list, the previous
+            // owner stays one as an editor
+            sqlx::query("DELETE FROM list_members WHERE list_id = $1 AND user_id = $2")
+                .bind(transfer.list_id)
+                .bind(transfer.to_user_id)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query(
+                "INSERT INTO list_members (list_id, user_id, role) VALUES ($1, $2, 'editor')
+                 ON CONFLICT (list_id, user_id) DO UPDATE SET role = EXCLUDED.role",
+            )
+            .bind(transfer.list_id)
+            .bind(transfer.from_user_id)
+            .execute(&mut *tx)
+            .await?;
+
+            // Todos follow the list through the ON UPDATE CASCADE foreign key.
+            // The list stays in its workspace, the recipient must still be a member of it.
+            let moved = sqlx::query(
+                "UPDATE lists SET user_id = $1 WHERE id = $2 AND user_id = $3
+                   AND EXISTS(SELECT 1 FROM workspace_members wm
+                              WHERE wm.workspace_id = lists.workspace_id AND wm.user_id = $1)",
+            )
+            .bind(transfer.to_user_id)
+            .bind(transfer.list_id)
+            .bind(transfer.from_user_id)
+            .execute(&mut *tx)
+            .await?;
+            if moved.rows_affected() == 0 {
+                return Ok(None);
+            }
+
+            tx.commit().await?;
+            Ok(Some(transfer))
+        })
+        .await
+    }
+
+    // Decline a pending transfer offered to the user
+    pub async fn decline_transfer(&self, user_id: i64, transfer_id: i64) -> Result<bool, Error> {
+        observe_query("list.decline_transfer", async move {
+            let result = sqlx::query(
+                "UPDATE list_transfers SET status = 'declined', resolved_at = NOW()
+                 WHERE id = $1 AND to_user_id = $2 AND status = 'pending'",
+            )
+            .bind(to_db_id(transfer_id)?)
+            .bind(to_db_id(user_id)?)
+            .execute(&self.pool)
+            .await?;
+
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    // Role of the user on a list of the workspace, `None` when the list is neither owned
+    // nor shared with them
+    pub async fn list_access(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        list_id: i64,
+    ) -> Result<Option<ListAccessRow>, Error> {
+        observe_query("list.list_access", async move {
+            sqlx::query_as::<_, ListAccessRow>(LIST_ACCESS_QUERY)
+                .bind(to_db_id(list_id)?)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Open todos of a list just before `at`
+    pub async fn open_todos_at(&self, list_id: i64, at: OffsetDateTime) -> Result<i64, Error> {
+        observe_query("list.open_todos_at", async move {
+            sqlx::query_scalar::<_, i64>(
+                "SELECT COALESCE(SUM(delta), 0)::BIGINT FROM todo_list_events
+                 WHERE list_id = $1 AND occurred_at < $2",
+            )
+            .bind(to_db_id(list_id)?)
+            .bind(at)
+            .fetch_one(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // WIP limits of a list in workflow order, with the todos of each column
+    pub async fn wip_limits(&self, list_id: i64) -> Result<Vec<WipLimitRow>, Error> {
+        observe_query("list.wip_limits", async move {
+            let query = format!(
+                "SELECT w.status, w.max_todos, ({WIP_COLUMN_TODOS}) AS todos
+                 FROM list_wip_limits w
+                 WHERE w.list_id = $1
+                 ORDER BY array_position(
+                    ARRAY['backlog', 'in_progress', 'blocked', 'done', 'cancelled']::VARCHAR[],
+                    w.status)"
+            );
+            sqlx::query_as::<_, WipLimitRow>(&query)
+                .bind(to_db_id(list_id)?)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Replace the WIP limits of a list
+    pub async fn replace_wip_limits(
+        &self,
+        list_id: i64,
+        limits: &[(String, i32)],
+    ) -> Result<(), Error> {
+        observe_query("list.replace_wip_limits", async move {
+            let list_id = to_db_id(list_id)?;
+            let (statuses, max_todos): (Vec<String>, Vec<i32>) = limits.iter().cloned().unzip();
+
+            let mut tx = self.pool.begin().await?;
+            sqlx::query("DELETE FROM list_wip_limits WHERE list_id = $1")
+                .bind(list_id)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query(
+                "INSERT INTO list_wip_limits (list_id, status, max_todos)
+                 SELECT $1, status, max_todos FROM UNNEST($2::VARCHAR[], $3::INTEGER[])
+                    AS l(status, max_todos)",
+            )
+            .bind(list_id)
+            .bind(statuses)
+            .bind(max_todos)
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await
+        })
+        .await
+    }
+
+    // Daily change of the open todos of a list between `from` and `until`, days without
+    // change are left out
+    pub async fn burndown_deltas(
+        &self,
+        list_id: i64,
+        from: OffsetDateTime,
+        until: OffsetDateTime,
+    ) -> Result<Vec<BurndownDeltaRow>, Error> {
+        observe_query("list.burndown_deltas", async move {
+            sqlx::query_as::<_, BurndownDeltaRow>(
+                "SELECT (occurred_at AT TIME ZONE 'UTC')::DATE AS day, SUM(delta)::BIGINT AS delta
+                 FROM todo_list_events
+                 WHERE list_id = $1 AND occurred_at >= $2 AND occurred_at < $3
+                 GROUP BY day ORDER BY day",
+            )
+            .bind(to_db_id(list_id)?)
+            .bind(from)
+            .bind(until)
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Share a list owned by the user with another member of its workspace, changing the
+    // role of an existing member. Returns `None` when the list or the user is not found.
+    pub async fn add_member(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        list_id: i64,
+        username: &str,
+        role: &str,
+    ) -> Result<Option<ListMemberRow>, Error> {
+        observe_query("list.add_member", async move {
+            sqlx::query_as::<_, ListMemberRow>(
+                "WITH member AS (
+                    INSERT INTO list_members (list_id, user_id, role)
+                    SELECT l.id, u.id, $4
+                    FROM lists l
+                    JOIN users u ON u.username = $3
+                    JOIN workspace_members wm
+                        ON wm.workspace_id = l.workspace_id AND wm.user_id = u.id
+                    WHERE l.id = $1 AND l.user_id = $2 AND l.workspace_id = $5
+                      AND u.id <> l.user_id AND NOT u.is_guest
+                    ON CONFLICT (list_id, user_id) DO UPDATE SET role = EXCLUDED.role
+                    RETURNING list_id, user_id, role, created_at
+                 )
+                 SELECT m.list_id, m.user_id, u.username, m.role, m.created_at
+                 FROM member m JOIN users u ON u.id = m.user_id",
+            )
+            .bind(to_db_id(list_id)?)
+            .bind(to_db_id(user_id)?)
+            .bind(username)
+            .bind(role)
+            .bind(to_db_id(workspace_id)?)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // List the members of a list, oldest first
+    pub async fn list_members(&self, list_id: i64) -> Result<Vec<ListMemberRow>, Error> {
+        observe_query("list.list_members", async move {
+            sqlx::query_as::<_, ListMemberRow>(
+                "SELECT m.list_id, m.user_id, u.username, m.role, m.created_at
+                 FROM list_members m JOIN users u ON u.id = m.user_id
+                 WHERE m.list_id = $1
+                 ORDER BY m.created_at, m.user_id",
+            )
+            .bind(to_db_id(list_id)?)
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Remove a member, by the list owner or by the member leaving the list
+    pub async fn remove_member(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        list_id: i64,
+        member_id: i64,
+    ) -> Result<bool, Error> {
+        observe_query("list.remove_member", async move {
+            let result = sqlx::query(
+                "DELETE FROM list_members m USING lists l
+                 WHERE m.list_id = l.id AND m.list_id = $1 AND m.user_id = $3
+                   AND l.workspace_id = $4 AND (l.user_id = $2 OR m.user_id = $2)",
+            )
+            .bind(to_db_id(list_id)?)
+            .bind(to_db_id(user_id)?)
+            .bind(to_db_id(member_id)?)
+            .bind(to_db_id(workspace_id)?)
+            .execute(&self.pool)
+            .await?;
+
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    // List the lists of the workspace shared with the user, archived lists only when asked for
+    pub async fn list_shared_lists(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        include_archived: bool,
+    ) -> Result<Vec<SharedListRow>, Error> {
+        observe_query("list.list_shared_lists", async move {
+            sqlx::query_as::<_, SharedListRow>(
+                "SELECT l.id, l.name, l.archived, l.created_at, l.updated_at,
+                        l.user_id AS owner_id, m.role
+                 FROM list_members m JOIN lists l ON l.id = m.list_id
+                 WHERE m.user_id = $1 AND l.workspace_id = $3 AND ($2 OR NOT l.archived)
+                 ORDER BY l.name, l.id",
+            )
+            .bind(to_db_id(user_id)?)
+            .bind(include_archived)
+            .bind(to_db_id(workspace_id)?)
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transfer_columns_match_row() {
+        for column in [
+            "list_id",
+            "from_user_id",
+            "to_user_id",
+            "status",
+            "resolved_at",
+        ] {
+            assert!(TRANSFER_COLUMNS.contains(column));
+        }
+    }
+
+    #[test]
+    fn test_access_query_binds_list_then_user() {
+        assert!(LIST_ACCESS_QUERY.contains("l.id = $1"));
+        assert!(LIST_ACCESS_QUERY.contains("m.user_id = $2"));
+        assert!(LIST_ACCESS_QUERY.contains("l.workspace_id = $3"));
+    }
+
+    #[test]
+    fn test_invalid_list_id_is_rejected() {
+        assert!(to_db_id(i64::MIN).is_err());
+        assert_eq!(to_db_id(10).ok(), Some(10));
+    }
+}
@@ -0,0 +1,608 @@
+//! #`List` Routes
+//! This module defines the HTTP routes for lists functionality.
+
+use axum::extract::{Path, Query};
+use axum::routing::{delete, get, post, put};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Router,
+};
+
+use crate::auth::Claims;
+use crate::modules::audit::repository::AuditRepository;
+use crate::modules::audit::service::AuditService;
+use crate::modules::common::{respond, respond_cached, CacheValidators, ErrorResponse};
+use crate::modules::list::interfaces::{
+    BurndownQuery, BurndownResponse, ListFilter, ListMemberResponse, ListMessageResponse,
+    ListRequest, ListResponse, ListTransferResponse, ShareListRequest, SharedListResponse,
+    TransferListRequest, UpdateWipLimitsRequest, WipLimitsResponse,
+};
+use crate::modules::list::repository::ListRepository;
+use crate::modules::list::service::ListService;
+use crate::modules::moderation::repository::ModerationRepository;
+use crate::modules::moderation::service::ModerationService;
+use crate::modules::workspace::context::WorkspaceContext;
+use crate::utils::json::Json;
+use crate::AppState;
+
+// Creates and returns the list routes
+pub fn list_routes() -> Router<AppState> {
+    Router::new()
+        .route("/lists", get(list_lists_route).post(create_list_route))
+        .route(
+            "/lists/{id}",
+            put(rename_list_route).delete(delete_list_route),
+        )
+        .route("/lists/{id}/archive", post(archive_list_route))
+        .route("/lists/{id}/unarchive", post(unarchive_list_route))
+        .route("/lists/{id}/transfer", post(transfer_list_route))
+        .route("/lists/transfers", get(list_incoming_transfers_route))
+        .route("/lists/shared", get(list_shared_lists_route))
+        .route(
+            "/lists/{id}/members",
+            get(list_members_route).post(share_list_route),
+        )
+        .route("/lists/{id}/members/{user_id}", delete(remove_member_route))
+        .route("/lists/{id}/burndown", get(burndown_route))
+        .route(
+            "/lists/{id}/wip-limits",
+            get(wip_limits_route).put(update_wip_limits_route),
+        )
+        .route(
+            "/lists/transfers/{transfer_id}/accept",
+            post(accept_transfer_route),
+        )
+        .route(
+            "/lists/transfers/{transfer_id}/decline",
+            post(decline_transfer_route),
+        )
+}
+
+fn list_service(app_state: &AppState) -> ListService {
+    ListService::new(
+        ListRepository::new(app_state.db_pool.clone()),
+        app_state.view_cache.clone(),
+        ModerationService::new(
+            ModerationRepository::new(app_state.db_pool.clone()),
+            app_state.moderator.clone(),
+            AuditService::new(AuditRepository::new(app_state.db_pool.clone())),
+        ),
+    )
+}
+
+// Create List Route
+#[utoipa::path(
+    post,
+    path = "/lists",
+    tag = "Lists",
+    request_body = ListRequest,
+    responses(
+        (status = 201, description = "List created successfully", body = ListResponse),
+        (status = 400, description = "Invalid list data", body = ErrorResponse),
+        (status = 422, description = "Name with a banned term, code `banned_term`", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn create_list_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Json(list_request): Json<ListRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::CREATED,
+        list_service(&app_state)
+            .create_list(workspace.user_id, workspace.workspace_id, list_request)
+            .await,
+    )
+}
+
+// List Lists Route
+#[utoipa::path(
+    get,
+    path = "/lists",
+    tag = "Lists",
+    params(
+        ListFilter,
+        ("If-None-Match" = Option<String>, Header, description = "ETag of the cached lists, answered with 304 while current")
+    ),
+    responses(
+        (status = 200, description = "Lists fetched successfully", body = [ListResponse],
+            headers(
+                ("ETag" = String, description = "Digest of the lists"),
+                ("Cache-Control" = String, description = "private, no-cache")
+            )),
+        (status = 304, description = "Cached lists still current"),
+        (status = 500, description = "Failed to list lists", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn list_lists_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Query(filter): Query<ListFilter>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    respond_cached(
+        &headers,
+        list_service(&app_state)
+            .list_lists(workspace.user_id, workspace.workspace_id, &filter)
+            .await,
+        |_| CacheValidators::default(),
+    )
+}
+
+// Archive List Route
+#[utoipa::path(
+    post,
+    path = "/lists/{id}/archive",
+    tag = "Lists",
+    params(
+        ("id" = i64, Path, description = "List id")
+    ),
+    responses(
+        (status = 200, description = "List archived", body = ListResponse),
+        (status = 404, description = "List not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn archive_list_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        list_service(&app_state)
+            .set_archived(workspace.user_id, workspace.workspace_id, id, true)
+            .await,
+    )
+}
+
+// Unarchive List Route
+#[utoipa::path(
+    post,
+    path = "/lists/{id}/unarchive",
+    tag = "Lists",
+    params(
+        ("id" = i64, Path, description = "List id")
+    ),
+    responses(
+        (status = 200, description = "List unarchived", body = ListResponse),
+        (status = 404, description = "List not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn unarchive_list_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        list_service(&app_state)
+            .set_archived(workspace.user_id, workspace.workspace_id, id, false)
+            .await,
+    )
+}
+
+// Rename List Route
+#[utoipa::path(
+    put,
+    path = "/lists/{id}",
+    tag = "Lists",
+    params(
+        ("id" = i64, Path, description = "List id")
+    ),
+    request_body = ListRequest,
+    responses(
+        (status = 200, description = "List renamed successfully", body = ListResponse),
+        (status = 400, description = "Invalid list data", body = ErrorResponse),
+        (status = 403, description = "List shared as viewer", body = ErrorResponse),
+        (status = 404, description = "List not found", body = ErrorResponse),
+        (status = 422, description = "Name with a banned term, code `banned_term`", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn rename_list_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path(id): Path<i64>,
+    Json(list_request): Json<ListRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        list_service(&app_state)
+            .rename_list(workspace.user_id, workspace.workspace_id, id, list_request)
+            .await,
+    )
+}
+
+// Delete List Route
+#[utoipa::path(
+    delete,
+    path = "/lists/{id}",
+    tag = "Lists",
+    params(
+        ("id" = i64, Path, description = "List id")
+    ),
+    responses(
+        (status = 200, description = "List deleted successfully", body = ListMessageResponse),
+        (status = 404, description = "List not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn delete_list_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        list_service(&app_state)
+            .delete_list(workspace.user_id, workspace.workspace_id, id)
+            .await,
+    )
+}
+
+// Transfer List Route
+#[utoipa::path(
+    post,
+    path = "/lists/{id}/transfer",
+    tag = "Lists",
+    params(
+        ("id" = i64, Path, description = "List id")
+    ),
+    request_body = TransferListRequest,
+    responses(
+        (status = 201, description = "List transfer offered", body = ListTransferResponse),
+        (status = 400, description = "Missing recipient", body = ErrorResponse),
+        (status = 404, description = "List or recipient not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn transfer_list_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path(id): Path<i64>,
+    Json(transfer_request): Json<TransferListRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::CREATED,
+        list_service(&app_state)
+            .transfer_list(
+                workspace.user_id,
+                workspace.workspace_id,
+                id,
+                transfer_request,
+            )
+            .await,
+    )
+}
+
+// List Incoming Transfers Route
+#[utoipa::path(
+    get,
+    path = "/lists/transfers",
+    tag = "Lists",
+    responses(
+        (status = 200, description = "Pending transfers listed", body = [ListTransferResponse]),
+        (status = 500, description = "Failed to list transfers", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn list_incoming_transfers_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        list_service(&app_state)
+            .list_incoming_transfers(claims.user_id)
+            .await,
+    )
+}
+
+// Accept Transfer Route
+#[utoipa::path(
+    post,
+    path = "/lists/transfers/{transfer_id}/accept",
+    tag = "Lists",
+    params(
+        ("transfer_id" = i64, Path, description = "Transfer id")
+    ),
+    responses(
+        (status = 200, description = "Transfer accepted", body = ListTransferResponse),
+        (status = 404, description = "Transfer not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn accept_transfer_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Path(transfer_id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        list_service(&app_state)
+            .accept_transfer(claims.user_id, transfer_id)
+            .await,
+    )
+}
+
+// Decline Transfer Route
+#[utoipa::path(
+    post,
+    path = "/lists/transfers/{transfer_id}/decline",
+    tag = "Lists",
+    params(
+        ("transfer_id" = i64, Path, description = "Transfer id")
+    ),
+    responses(
+        (status = 200, description = "Transfer declined", body = ListMessageResponse),
+        (status = 404, description = "Transfer not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn decline_transfer_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Path(transfer_id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        list_service(&app_state)
+            .decline_transfer(claims.user_id, transfer_id)
+            .await,
+    )
+}
+
+// Share List Route
+#[utoipa::path(
+    post,
+    path = "/lists/{id}/members",
+    tag = "Lists",
+    params(
+        ("id" = i64, Path, description = "List id")
+    ),
+    request_body = ShareListRequest,
+    responses(
+        (status = 201, description = "List shared, or role of the member changed", body = ListMemberResponse),
+        (status = 400, description = "Invalid role", body = ErrorResponse),
+        (status = 404, description = "List or user not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn share_list_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path(id): Path<i64>,
+    Json(share_request): Json<ShareListRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::CREATED,
+        list_service(&app_state)
+            .share_list(workspace.user_id, workspace.workspace_id, id, share_request)
+            .await,
+    )
+}
+
+// List Members Route
+#[utoipa::path(
+    get,
+    path = "/lists/{id}/members",
+    tag = "Lists",
+    params(
+        ("id" = i64, Path, description = "List id")
+    ),
+    responses(
+        (status = 200, description = "Members fetched successfully", body = [ListMemberResponse]),
+        (status = 404, description = "List not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn list_members_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        list_service(&app_state)
+            .list_members(workspace.user_id, workspace.workspace_id, id)
+            .await,
+    )
+}
+
+// Remove Member Route
+#[utoipa::path(
+    delete,
+    path = "/lists/{id}/members/{user_id}",
+    tag = "Lists",
+    params(
+        ("id" = i64, Path, description = "List id"),
+        ("user_id" = i64, Path, description = "Member user id")
+    ),
+    responses(
+        (status = 200, description = "Member removed successfully", body = ListMessageResponse),
+        (status = 404, description = "Member not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn remove_member_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path((id, user_id)): Path<(i64, i64)>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        list_service(&app_state)
+            .remove_member(workspace.user_id, workspace.workspace_id, id, user_id)
+            .await,
+    )
+}
+
+// List Shared Lists Route
+#[utoipa::path(
+    get,
+    path = "/lists/shared",
+    tag = "Lists",
+    params(ListFilter),
+    responses(
+        (status = 200, description = "Lists shared with the user", body = [SharedListResponse]),
+        (status = 500, description = "Failed to list shared lists", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn list_shared_lists_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Query(filter): Query<ListFilter>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        list_service(&app_state)
+            .list_shared_lists(workspace.user_id, workspace.workspace_id, &filter)
+            .await,
+    )
+}
+
+// Burndown Route
+#[utoipa::path(
+    get,
+    path = "/lists/{id}/burndown",
+    tag = "Lists",
+    params(
+        ("id" = i64, Path, description = "List id"),
+        BurndownQuery
+    ),
+    responses(
+        (status = 200, description = "Open todos of the list at the end of each day", body = BurndownResponse),
+        (status = 400, description = "Invalid range", body = ErrorResponse),
+        (status = 404, description = "List not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn burndown_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path(id): Path<i64>,
+    Query(query): Query<BurndownQuery>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        list_service(&app_state)
+            .burndown(workspace.user_id, workspace.workspace_id, id, &query)
+            .await,
+    )
+}
+
+// WIP Limits Route
+#[utoipa::path(
+    get,
+    path = "/lists/{id}/wip-limits",
+    tag = "Lists",
+    params(
+        ("id" = i64, Path, description = "List id")
+    ),
+    responses(
+        (status = 200, description = "WIP limits of the status columns of the list", body = WipLimitsResponse),
+        (status = 404, description = "List not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn wip_limits_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        list_service(&app_state)
+            .wip_limits(workspace.user_id, workspace.workspace_id, id)
+            .await,
+    )
+}
+
+// Update WIP Limits Route
+#[utoipa::path(
+    put,
+    path = "/lists/{id}/wip-limits",
+    tag = "Lists",
+    params(
+        ("id" = i64, Path, description = "List id")
+    ),
+    request_body = UpdateWipLimitsRequest,
+    responses(
+        (status = 200, description = "WIP limits replaced", body = WipLimitsResponse),
+        (status = 400, description = "Invalid limits", body = ErrorResponse),
+        (status = 403, description = "List shared as viewer", body = ErrorResponse),
+        (status = 404, description = "List not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn update_wip_limits_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path(id): Path<i64>,
+    Json(update_request): Json<UpdateWipLimitsRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        list_service(&app_state)
+            .update_wip_limits(
+                workspace.user_id,
+                workspace.workspace_id,
+                id,
+                update_request,
+            )
+            .await,
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_routes_creation() {
+        let _routes = list_routes();
+        assert!(true);
+    }
+}
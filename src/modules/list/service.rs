@@ -0,0 +1,588 @@
+//! # `List` Service
+//!
+//! This module contains the bussiness logic for list operations.
+
+use time::{Duration, OffsetDateTime};
+
+use crate::{
+    modules::{
+        common::AppError,
+        list::{
+            burndown::{burndown_days, burndown_range},
+            interfaces::{
+                BurndownQuery, BurndownResponse, ListAccess, ListFilter, ListMemberResponse,
+                ListMessageResponse, ListRequest, ListResponse, ListRole, ListTransferResponse,
+                ShareListRequest, SharedListResponse, TransferListRequest, UpdateWipLimitsRequest,
+                ValidatedListRequest, WipLimit, WipLimitResponse, WipLimitsResponse,
+            },
+            repository::ListRepository,
+        },
+        moderation::{interfaces::ModeratedContent, service::ModerationService},
+        todo::{cache::ViewCache, interfaces::TodoStatus},
+    },
+    utils::validation::validate_required_fields,
+};
+
+// Highest WIP limit of a status column
+pub const MAX_WIP_LIMIT: i64 = 1000;
+
+// Validate the WIP limits of a list, one per status column
+fn parse_wip_limits(limits: &[WipLimit]) -> Result<Vec<(String, i32)>, AppError> {
+    let mut parsed: Vec<(String, i32)> = Vec::with_capacity(limits.len());
+    for limit in limits {
+        let status = limit
+            .status
+            .parse::<TodoStatus>()
+            .map_err(AppError::Validation)?;
+        if parsed.iter().any(|(parsed, _)| parsed == status.as_str()) {
+            return Err(AppError::Validation(format!(
+                "Duplicate WIP limit for {status}"
+            )));
+        }
+        let max_todos = i32::try_from(limit.max_todos)
+            .ok()
+            .filter(|max_todos| (1..=MAX_WIP_LIMIT).contains(&i64::from(*max_todos)))
+            .ok_or_else(|| {
+                AppError::Validation(format!("WIP limits must be between 1 and {MAX_WIP_LIMIT}"))
+            })?;
+        parsed.push((status.as_str().to_string(), max_todos));
+    }
+    Ok(parsed)
+}
+
+pub struct ListService {
+    list_repository: ListRepository,
+    view_cache: ViewCache,
+    moderation_service: ModerationService,
+}
+
+impl ListService {
+    pub const fn new(
+        list_repository: ListRepository,
+        view_cache: ViewCache,
+        moderation_service: ModerationService,
+    ) -> Self {
+        Self {
+            list_repository,
+            view_cache,
+            moderation_service,
+        }
+    }
+
+    // Queue a saved list name flagged by the moderation for review
+    async fn flag_name(&self, user_id: i64, list: &ListResponse, reason: Option<String>) {
+        if let Some(reason) = reason {
+            self.moderation_service
+                .flag(
+                    user_id,
+                    ModeratedContent::ListName,
+                    list.id,
+                    &list.name,
+                    &reason,
+                )
+                .await;
+        }
+    }
+
+    // Validate list payload
+    fn validate(list_request: &ListRequest) -> Result<ValidatedListRequest, AppError> {
+        validate_required_fields(list_request, vec!["name"]).map_err(AppError::InvalidFields)
+    }
+
+    // Role of the user on a list they own or that is shared with them
+    pub async fn list_access(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+    ) -> Result<ListAccess, AppError> {
+        match self
+            .list_repository
+            .list_access(user_id, workspace_id, id)
+            .await
+        {
+            Ok(Some(access)) => Ok(ListAccess::from(access)),
+            Ok(None) => Err(AppError::NotFound("List not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error checking list access: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Create a list for the user
+    pub async fn create_list(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        list_request: ListRequest,
+    ) -> Result<ListResponse, AppError> {
+        let validated = Self::validate(&list_request)?;
+        let name = validated.name.trim();
+        let flag_reason = self.moderation_service.check(name).await?;
+
+        match self
+            .list_repository
+            .create_list(user_id, workspace_id, name)
+            .await
+        {
+            Ok(list) => {
+                let list = ListResponse::from(list);
+                self.flag_name(user_id, &list, flag_reason).await;
+                Ok(list)
+            }
+            Err(e) => {
+                tracing::warn!("Error creating list: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // List the lists of the user
+    pub async fn list_lists(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        filter: &ListFilter,
+    ) -> Result<Vec<ListResponse>, AppError> {
+        match self
+            .list_repository
+            .list_lists(
+                user_id,
+                workspace_id,
+                filter.include_archived.unwrap_or(false),
+            )
+            .await
+        {
+            Ok(lists) => Ok(lists.into_iter().map(ListResponse::from).collect()),
+            Err(e) => {
+                tracing::warn!("Error listing lists: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Rename a list
+    pub async fn rename_list(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+        list_request: ListRequest,
+    ) -> Result<ListResponse, AppError> {
+        let validated = Self::validate(&list_request)?;
+
+        // Editors may rename a shared list
+        let access = self.list_access(user_id, workspace_id, id).await?;
+        if !access.role.can_edit() {
+            return Err(AppError::Forbidden(
+                "Viewers cannot rename the list".to_string(),
+            ));
+        }
+        let name = validated.name.trim();
+        let flag_reason = self.moderation_service.check(name).await?;
+
+        match self
+            .list_repository
+            .rename_list(access.owner_id, workspace_id, id, name)
+            .await
+        {
+            Ok(Some(list)) => {
+                let list = ListResponse::from(list);
+                self.flag_name(user_id, &list, flag_reason).await;
+                Ok(list)
+            }
+            Ok(None) => Err(AppError::NotFound("List not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error renaming list: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Archive or unarchive a list
+    pub async fn set_archived(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+        archived: bool,
+    ) -> Result<ListResponse, AppError> {
+        match self
+            .list_repository
+            .set_archived(user_id, workspace_id, id, archived)
+            .await
+        {
+            Ok(Some(list)) => Ok(ListResponse::from(list)),
+            Ok(None) => Err(AppError::NotFound("List not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error archiving list: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Delete a list
+    pub async fn delete_list(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+    ) -> Result<ListMessageResponse, AppError> {
+        match self
+            .list_repository
+            .delete_list(user_id, workspace_id, id)
+            .await
+        {
+            Ok(true) => {
+                // Todos of the list are kept without a list
+                self.view_cache.invalidate(user_id).await;
+                Ok(ListMessageResponse {
+                    message: "List deleted successfully".to_string(),
+                })
+            }
+            Ok(false) => Err(AppError::NotFound("List not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error deleting list: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Offer the ownership of a list to another user
+    pub async fn transfer_list(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+        transfer_request: TransferListRequest,
+    ) -> Result<ListTransferResponse, AppError> {
+        let to_username = match transfer_request.to_username.as_deref().map(str::trim) {
+            Some(username) if !username.is_empty() => username.to_string(),
+            _ => {
+                return Err(AppError::Validation(
+                    "Missing required fields: to_username".to_string(),
+                ))
+            }
+        };
+
+        match self
+            .list_repository
+            .create_transfer(user_id, workspace_id, id, &to_username)
+            .await
+        {
+            Ok(Some(transfer)) => Ok(ListTransferResponse::from(transfer)),
+            Ok(None) => Err(AppError::NotFound(
+                "List or recipient not found in the workspace".to_string(),
+            )),
+            Err(e) => {
+                tracing::warn!("Error creating list transfer: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // List transfers waiting for the user answer
+    pub async fn list_incoming_transfers(
+        &self,
+        user_id: i64,
+    ) -> Result<Vec<ListTransferResponse>, AppError> {
+        match self.list_repository.list_incoming_transfers(user_id).await {
+            Ok(transfers) => Ok(transfers
+                .into_iter()
+                .map(ListTransferResponse::from)
+                .collect()),
+            Err(e) => {
+                tracing::warn!("Error listing list transfers: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Accept a transfer, becoming the list owner
+    pub async fn accept_transfer(
+        &self,
+        user_id: i64,
+        transfer_id: i64,
+    ) -> Result<ListTransferResponse, AppError> {
+        match self
+            .list_repository
+            .accept_transfer(user_id, transfer_id)
+            .await
+        {
+            Ok(Some(transfer)) => {
+                // The list todos moved from one user to the other
+                self.view_cache
+                    .invalidate(i64::from(transfer.from_user_id))
+                    .await;
+                self.view_cache.invalidate(user_id).await;
+                Ok(ListTransferResponse::from(transfer))
+            }
+            Ok(None) => Err(AppError::NotFound("Transfer not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error accepting list transfer: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Decline a transfer
+    pub async fn decline_transfer(
+        &self,
+        user_id: i64,
+        transfer_id: i64,
+    ) -> Result<ListMessageResponse, AppError> {
+        match self
+            .list_repository
+            .decline_transfer(user_id, transfer_id)
+            .await
+        {
+            Ok(true) => Ok(ListMessageResponse {
+                message: "Transfer declined".to_string(),
+            }),
+            Ok(false) => Err(AppError::NotFound("Transfer not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error declining list transfer: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Share a list with another user as viewer or editor
+    pub async fn share_list(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+        share_request: ShareListRequest,
+    ) -> Result<ListMemberResponse, AppError> {
+        let username = match share_request.username.as_deref().map(str::trim) {
+            Some(username) if !username.is_empty() => username.to_string(),
+            _ => {
+                return Err(AppError::Validation(
+                    "Missing required fields: username".to_string(),
+                ))
+            }
+        };
+        let role = match share_request.role.as_deref() {
+            Some(role) => role.parse().map_err(AppError::Validation)?,
+            None => ListRole::Viewer,
+        };
+        if role == ListRole::Owner {
+            return Err(AppError::Validation(
+                "Transfer the list to change its owner".to_string(),
+            ));
+        }
+
+        match self
+            .list_repository
+            .add_member(user_id, workspace_id, id, &username, role.as_str())
+            .await
+        {
+            Ok(Some(member)) => Ok(ListMemberResponse::from(member)),
+            Ok(None) => Err(AppError::NotFound(
+                "List or user not found in the workspace".to_string(),
+            )),
+            Err(e) => {
+                tracing::warn!("Error sharing list: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // List the members of a list, visible to its owner and members
+    pub async fn list_members(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+    ) -> Result<Vec<ListMemberResponse>, AppError> {
+        self.list_access(user_id, workspace_id, id).await?;
+
+        match self.list_repository.list_members(id).await {
+            Ok(members) => Ok(members.into_iter().map(ListMemberResponse::from).collect()),
+            Err(e) => {
+                tracing::warn!("Error listing list members: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Open todos of a list at the end of each day of the asked range, visible to its
+    // owner and members
+    pub async fn burndown(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+        query: &BurndownQuery,
+    ) -> Result<BurndownResponse, AppError> {
+        let (from, to) = burndown_range(query, OffsetDateTime::now_utc().date())
+            .map_err(AppError::Validation)?;
+        self.list_access(user_id, workspace_id, id).await?;
+
+        let start = from.midnight().assume_utc();
+        let end = to.midnight().assume_utc() + Duration::days(1);
+        let history = match self.list_repository.open_todos_at(id, start).await {
+            Ok(open_before) => self
+                .list_repository
+                .burndown_deltas(id, start, end)
+                .await
+                .map(|deltas| (open_before, deltas)),
+            Err(e) => Err(e),
+        };
+
+        match history {
+            Ok((open_before, deltas)) => Ok(BurndownResponse {
+                list_id: id,
+                from: from.to_string(),
+                to: to.to_string(),
+                days: burndown_days(from, to, open_before, &deltas),
+            }),
+            Err(e) => {
+                tracing::warn!("Error loading list burndown: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // WIP limits of the status columns of a list, visible to its owner and members
+    pub async fn wip_limits(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+    ) -> Result<WipLimitsResponse, AppError> {
+        self.list_access(user_id, workspace_id, id).await?;
+
+        match self.list_repository.wip_limits(id).await {
+            Ok(limits) => Ok(WipLimitsResponse {
+                list_id: id,
+                limits: limits.into_iter().map(WipLimitResponse::from).collect(),
+            }),
+            Err(e) => {
+                tracing::warn!("Error listing WIP limits: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Replace the WIP limits of a list. Columns already holding more todos than their
+    // new limit keep them, only additions are refused.
+    pub async fn update_wip_limits(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+        update_request: UpdateWipLimitsRequest,
+    ) -> Result<WipLimitsResponse, AppError> {
+        let limits = parse_wip_limits(&update_request.limits)?;
+
+        // Editors may change the limits of a shared list
+        let access = self.list_access(user_id, workspace_id, id).await?;
+        if !access.role.can_edit() {
+            return Err(AppError::Forbidden(
+                "Viewers cannot change the WIP limits of the list".to_string(),
+            ));
+        }
+
+        if let Err(e) = self.list_repository.replace_wip_limits(id, &limits).await {
+            tracing::warn!("Error updating WIP limits: {}", e);
+            return Err(AppError::Internal(
+                "Failed to update WIP limits".to_string(),
+            ));
+        }
+        self.wip_limits(user_id, workspace_id, id).await
+    }
+
+    // Remove a member from a list, members may remove themselves
+    pub async fn remove_member(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+        member_id: i64,
+    ) -> Result<ListMessageResponse, AppError> {
+        match self
+            .list_repository
+            .remove_member(user_id, workspace_id, id, member_id)
+            .await
+        {
+            Ok(true) => Ok(ListMessageResponse {
+                message: "Member removed successfully".to_string(),
+            }),
+            Ok(false) => Err(AppError::NotFound("Member not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error removing list member: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // List the lists other users shared with the user
+    pub async fn list_shared_lists(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        filter: &ListFilter,
+    ) -> Result<Vec<SharedListResponse>, AppError> {
+        match self
+            .list_repository
+            .list_shared_lists(
+                user_id,
+                workspace_id,
+                filter.include_archived.unwrap_or(false),
+            )
+            .await
+        {
+            Ok(lists) => Ok(lists.into_iter().map(SharedListResponse::from).collect()),
+            Err(e) => {
+                tracing::warn!("Error listing shared lists: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_wip_limits() {
+        let limit = |status: &str, max_todos| WipLimit {
+            status: status.to_string(),
+            max_todos,
+        };
+
+        assert_eq!(
+            parse_wip_limits(&[limit("in_progress", 3), limit(" blocked ", 1)]).unwrap(),
+            vec![("in_progress".to_string(), 3), ("blocked".to_string(), 1)]
+        );
+        assert!(parse_wip_limits(&[]).unwrap().is_empty());
+        assert!(parse_wip_limits(&[limit("doing", 3)]).is_err());
+        assert!(parse_wip_limits(&[limit("in_progress", 0)]).is_err());
+        assert!(parse_wip_limits(&[limit("in_progress", 1001)]).is_err());
+        assert!(parse_wip_limits(&[limit("in_progress", 3), limit("in_progress", 4)]).is_err());
+    }
+
+    #[test]
+    fn test_validate_missing_name() {
+        let request = ListRequest { name: None };
+        let error = ListService::validate(&request).unwrap_err();
+        assert_eq!(
+            error.to_error_response().message,
+            "Missing required fields: name"
+        );
+    }
+
+    #[test]
+    fn test_validate_valid_name() {
+        let request = ListRequest {
+            name: Some("Groceries".to_string()),
+        };
+        let validated = ListService::validate(&request).unwrap();
+        assert_eq!(validated.name, "Groceries");
+    }
+}
@@ -0,0 +1,49 @@
+//! # `Meta` Interfaces
+//! This module defines the data structures from Meta module
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+use crate::utils::dates::rfc3339_option;
+
+// Limits enforced by the deployment
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, PartialEq, Eq)]
+pub struct MetaLimits {
+    // Largest attachment upload, in bytes
+    pub attachment_max_bytes: u64,
+    // Largest page of the paginated endpoints
+    pub page_max_size: i64,
+    // Highest WIP limit of a status column
+    pub wip_limit_max: i64,
+    // Lifetime of the session tokens, in minutes
+    pub session_duration_minutes: i64,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, PartialEq, Eq)]
+pub struct MetaResponse {
+    // Name of the deployment, from `INSTANCE_NAME`
+    pub instance_name: String,
+    // Version of the API
+    pub api_version: String,
+    // Optional features available on the deployment, such as `oauth` or `nats`
+    pub features: Vec<String>,
+    // Ways to log in, `password` and the enabled OAuth providers
+    pub auth_providers: Vec<String>,
+    // Who can sign up: `open`, `invite` (with an invite code) or `closed`
+    pub signup_mode: String,
+    pub limits: MetaLimits,
+}
+
+// Build of the running binary, captured at compile time
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, PartialEq, Eq)]
+pub struct VersionResponse {
+    // Version of the crate
+    pub version: String,
+    // Commit the binary was built from, `unknown` outside of a git checkout
+    pub git_sha: String,
+    #[serde(with = "rfc3339_option")]
+    pub build_timestamp: Option<OffsetDateTime>,
+    // Output of `rustc --version`
+    pub rustc_version: String,
+}
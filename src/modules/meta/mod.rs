@@ -0,0 +1,8 @@
+//! # `Meta` Mod
+//! Meta imports for the description of the deployment served to the frontends
+
+pub mod interfaces;
+pub mod routes;
+pub mod service;
+
+pub use routes::meta_routes;
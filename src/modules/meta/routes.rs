@@ -0,0 +1,66 @@
+//! #`Meta` Routes
+//! This module defines the HTTP routes describing the deployment and its build.
+
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::response::Response;
+use axum::{routing::get, Router};
+
+use crate::modules::meta::interfaces::{MetaResponse, VersionResponse};
+use crate::utils::static_json::StaticJson;
+use crate::AppState;
+
+// Creates and returns the meta routes, serving the descriptions built at startup
+pub fn meta_routes(meta: StaticJson, version: StaticJson) -> Router<AppState> {
+    Router::new()
+        .route("/meta", get(meta_route))
+        .with_state(meta)
+        .merge(
+            Router::new()
+                .route("/version", get(version_route))
+                .with_state(version),
+        )
+}
+
+// Meta Route
+#[utoipa::path(
+    get,
+    path = "/meta",
+    tag = "Meta",
+    responses(
+        (status = 200, description = "Name, features, login methods and limits of the deployment", body = MetaResponse),
+        (status = 304, description = "Description unchanged since the `If-None-Match` ETag")
+    )
+)]
+pub async fn meta_route(State(meta): State<StaticJson>, headers: HeaderMap) -> Response {
+    meta.respond(&headers)
+}
+
+// Version Route
+#[utoipa::path(
+    get,
+    path = "/version",
+    tag = "Meta",
+    responses(
+        (status = 200, description = "Version, git commit, build time and compiler of the running binary", body = VersionResponse),
+        (status = 304, description = "Build unchanged since the `If-None-Match` ETag")
+    )
+)]
+pub async fn version_route(State(version): State<StaticJson>, headers: HeaderMap) -> Response {
+    version.respond(&headers)
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants, clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_meta_routes_creation() {
+        let _routes = meta_routes(
+            StaticJson::new(&"meta").unwrap(),
+            StaticJson::new(&"version").unwrap(),
+        );
+        assert!(true);
+    }
+}
@@ -0,0 +1,106 @@
+//! # `Meta` Service
+//!
+//! This module describes the deployment to the frontends: its name, the optional
+//! features and login methods it offers and its limits, so a single frontend build adapts
+//! to each deployment. The build of the running binary is described for the ops tooling.
+
+use time::OffsetDateTime;
+
+use crate::{
+    modules::{
+        common::MAX_PAGE_LIMIT,
+        list::service::MAX_WIP_LIMIT,
+        meta::interfaces::{MetaLimits, MetaResponse, VersionResponse},
+    },
+    AppState,
+};
+
+// Optional features: the outbox brokers compiled in with the cargo features, and the
+// integrations configured in the environment
+fn features(oauth: bool, moderation_api: bool) -> Vec<String> {
+    [
+        ("nats", cfg!(feature = "nats")),
+        ("amqp", cfg!(feature = "amqp")),
+        ("kafka", cfg!(feature = "kafka")),
+        ("oauth", oauth),
+        ("moderation_api", moderation_api),
+    ]
+    .into_iter()
+    .filter(|(_, enabled)| *enabled)
+    .map(|(feature, _)| feature.to_string())
+    .collect()
+}
+
+// Description of the deployment, built once at startup
+pub fn instance_meta(app_state: &AppState, instance_name: &str, api_version: &str) -> MetaResponse {
+    let oauth_providers = app_state.oauth_clients.enabled_providers();
+
+    MetaResponse {
+        instance_name: instance_name.to_string(),
+        api_version: api_version.to_string(),
+        features: features(!oauth_providers.is_empty(), app_state.moderator.is_some()),
+        auth_providers: std::iter::once("password")
+            .chain(oauth_providers.iter().map(|provider| provider.as_str()))
+            .map(str::to_string)
+            .collect(),
+        signup_mode: app_state.signup_mode.as_str().to_string(),
+        limits: MetaLimits {
+            attachment_max_bytes: u64::try_from(app_state.attachment_storage.max_bytes())
+                .unwrap_or(u64::MAX),
+            page_max_size: MAX_PAGE_LIMIT,
+            wip_limit_max: MAX_WIP_LIMIT,
+            session_duration_minutes: app_state.config.session.duration_minutes,
+        },
+    }
+}
+
+// Build time recorded by the build script in seconds since the epoch
+fn build_time(seconds: &str) -> Option<OffsetDateTime> {
+    let seconds = seconds.trim().parse::<i64>().ok()?;
+    OffsetDateTime::from_unix_timestamp(seconds).ok()
+}
+
+// Version, commit, build time and compiler of the running binary
+pub fn build_info() -> VersionResponse {
+    VersionResponse {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_sha: env!("BUILD_GIT_SHA").to_string(),
+        build_timestamp: build_time(env!("BUILD_TIMESTAMP")),
+        rustc_version: env!("BUILD_RUSTC_VERSION").to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_time() {
+        assert_eq!(
+            build_time("1735689600"),
+            Some(time::macros::datetime!(2025-01-01 00:00 UTC))
+        );
+        assert_eq!(build_time(""), None);
+        assert_eq!(build_time("yesterday"), None);
+    }
+
+    #[test]
+    fn test_build_info() {
+        let info = build_info();
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        assert!(!info.git_sha.is_empty());
+        assert!(info.build_timestamp.is_some());
+        assert!(info.rustc_version.starts_with("rustc") || info.rustc_version == "unknown");
+    }
+
+    #[test]
+    fn test_features() {
+        let configured = features(true, false);
+        assert!(configured.contains(&"oauth".to_string()));
+        assert!(!configured.contains(&"moderation_api".to_string()));
+        assert_eq!(
+            configured.contains(&"nats".to_string()),
+            cfg!(feature = "nats")
+        );
+    }
+}
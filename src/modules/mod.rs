@@ -3,6 +3,36 @@
 //! This module contains all the application-specific modules including
 //! health checks, todo management, and other business logic.
 
+pub mod admin;
+pub mod api_key;
+pub mod assignment;
+pub mod attachment;
+pub mod audit;
+pub mod badge;
+pub mod changes;
 pub mod common;
+pub mod demo;
+pub mod email;
+pub mod export;
 pub mod health;
+pub mod import;
+pub mod inbound;
+pub mod invitation;
+pub mod item;
+pub mod list;
+pub mod meta;
+pub mod moderation;
+pub mod outbox;
+pub mod preference;
+pub mod presence;
+pub mod reminder;
+pub mod report;
+pub mod search;
+pub mod security_webhook;
+pub mod session;
+pub mod sync;
+pub mod tag;
+pub mod todo;
+pub mod two_factor;
 pub mod user;
+pub mod workspace;
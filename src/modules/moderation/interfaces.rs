@@ -0,0 +1,145 @@
+//! # `Moderation` Interfaces
+//! This module defines the data structures from Moderation module
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::utils::dates::rfc3339_option;
+
+// User content checked by the moderation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModeratedContent {
+    ListName,
+}
+
+impl ModeratedContent {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::ListName => "list_name",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct BannedTermRequest {
+    // Word or phrase refused in the moderated content
+    pub term: Option<String>,
+}
+
+// Banned term row as stored in database
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct BannedTermRow {
+    pub id: i32,
+    pub term: String,
+    pub created_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct BannedTermResponse {
+    pub id: i64,
+    pub term: String,
+    #[serde(with = "rfc3339_option")]
+    pub created_at: Option<OffsetDateTime>,
+}
+
+impl From<BannedTermRow> for BannedTermResponse {
+    fn from(row: BannedTermRow) -> Self {
+        Self {
+            id: i64::from(row.id),
+            term: row.term,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ModerationMessageResponse {
+    pub message: String,
+}
+
+// Query parameters of the review queue
+#[derive(Deserialize, IntoParams, Clone, Debug, Default)]
+#[into_params(parameter_in = Query)]
+pub struct FlagQuery {
+    /// One of `pending`, `dismissed` or `removed`, defaults to `pending`
+    pub status: Option<String>,
+}
+
+// Review of a flagged content by an administrator
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlagDecision {
+    // The content is acceptable and kept
+    Dismiss,
+    // The content is replaced in place
+    Remove,
+}
+
+impl FlagDecision {
+    // Status of the flag once reviewed
+    pub const fn status(self) -> &'static str {
+        match self {
+            Self::Dismiss => "dismissed",
+            Self::Remove => "removed",
+        }
+    }
+}
+
+// Flag row as stored in database, with the username of the author
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct FlagRow {
+    pub id: i32,
+    pub username: String,
+    pub content_type: String,
+    pub content_id: i32,
+    pub content: String,
+    pub reason: String,
+    pub status: String,
+    pub created_at: Option<OffsetDateTime>,
+    pub reviewed_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct FlagResponse {
+    pub id: i64,
+    // Username of the author of the content
+    pub username: String,
+    // Kind of content, `list_name`
+    pub content_type: String,
+    // Id of the list whose name was flagged
+    pub content_id: i64,
+    // Content as written when it was flagged
+    pub content: String,
+    // Reason given by the moderation API
+    pub reason: String,
+    // One of `pending`, `dismissed` or `removed`
+    pub status: String,
+    #[serde(with = "rfc3339_option")]
+    pub created_at: Option<OffsetDateTime>,
+    #[serde(with = "rfc3339_option")]
+    pub reviewed_at: Option<OffsetDateTime>,
+}
+
+impl From<FlagRow> for FlagResponse {
+    fn from(row: FlagRow) -> Self {
+        Self {
+            id: i64::from(row.id),
+            username: row.username,
+            content_type: row.content_type,
+            content_id: i64::from(row.content_id),
+            content: row.content,
+            reason: row.reason,
+            status: row.status,
+            created_at: row.created_at,
+            reviewed_at: row.reviewed_at,
+        }
+    }
+}
+
+// Answer of the moderation API
+#[derive(Deserialize, Clone, Debug)]
+pub struct ModerationApiResponse {
+    pub flagged: bool,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
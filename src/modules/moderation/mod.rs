@@ -0,0 +1,10 @@
+//! # `Moderation` Mod
+//! Moderation imports for the banned terms and the review queue of the flagged content
+
+pub mod interfaces;
+pub mod moderator;
+pub mod repository;
+pub mod routes;
+pub mod service;
+
+pub use routes::moderation_routes;
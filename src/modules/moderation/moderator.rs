@@ -0,0 +1,133 @@
+//! # Content Moderators
+//! This module defines the checks of the user content: the banned terms, refusing the
+//! content, and the moderation API configured with the `MODERATION_*` variables, which
+//! flags it for review.
+
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use reqwest::{Client, Url};
+use serde_json::json;
+
+use crate::modules::moderation::interfaces::ModerationApiResponse;
+
+const DEFAULT_API_TIMEOUT_SECONDS: u64 = 5;
+
+#[async_trait]
+pub trait Moderator: Send + Sync {
+    // Name used in the logs
+    fn name(&self) -> &'static str;
+
+    // Reason the content should be reviewed, `None` when it's acceptable
+    async fn review(&self, content: &str) -> Result<Option<String>, String>;
+}
+
+// Content posted as JSON to an HTTP endpoint answering `{"flagged": bool, "reason": ...}`
+pub struct ApiModerator {
+    client: Client,
+    url: Url,
+}
+
+impl ApiModerator {
+    pub fn new(url: &str, timeout: Duration) -> Result<Self, String> {
+        let url = Url::parse(url).map_err(|e| format!("Invalid MODERATION_API_URL: {e}"))?;
+        let client = Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| format!("Failed to create moderation client: {e}"))?;
+        Ok(Self { client, url })
+    }
+}
+
+#[async_trait]
+impl Moderator for ApiModerator {
+    fn name(&self) -> &'static str {
+        "api"
+    }
+
+    async fn review(&self, content: &str) -> Result<Option<String>, String> {
+        let answer = self
+            .client
+            .post(self.url.clone())
+            .json(&json!({ "content": content }))
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| e.to_string())?
+            .json::<ModerationApiResponse>()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(answer
+            .flagged
+            .then(|| answer.reason.unwrap_or_else(|| "Flagged".to_string())))
+    }
+}
+
+// Moderator configured in the environment, the moderation API when
+// `MODERATION_API_URL` is set
+pub fn moderator_from_env() -> Result<Option<Arc<dyn Moderator>>, String> {
+    let Some(url) = std::env::var("MODERATION_API_URL")
+        .ok()
+        .filter(|url| !url.trim().is_empty())
+    else {
+        return Ok(None);
+    };
+    let timeout = std::env::var("MODERATION_TIMEOUT_SECONDS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_API_TIMEOUT_SECONDS);
+
+    Ok(Some(Arc::new(ApiModerator::new(
+        url.trim(),
+        Duration::from_secs(timeout),
+    )?)))
+}
+
+// Lowercase words of a text, separated by single spaces and padded with a space
+fn normalize_words(text: &str) -> String {
+    let words = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>();
+    format!(" {} ", words.join(" "))
+}
+
+// First banned term found in the content, matched as whole words regardless of case
+pub fn find_banned_term<'a>(content: &str, terms: &'a [String]) -> Option<&'a str> {
+    let content = normalize_words(content);
+    terms
+        .iter()
+        .find(|term| {
+            let term = normalize_words(term);
+            term.trim() != "" && content.contains(&term)
+        })
+        .map(String::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_moderator_requires_valid_url() {
+        assert!(ApiModerator::new("not a url", Duration::from_secs(1)).is_err());
+        assert!(ApiModerator::new("https://example.com/moderate", Duration::from_secs(1)).is_ok());
+    }
+
+    #[test]
+    fn test_find_banned_term() {
+        let terms = vec!["spam".to_string(), "Bad Word".to_string()];
+
+        assert_eq!(find_banned_term("Buy SPAM now", &terms), Some("spam"));
+        assert_eq!(
+            find_banned_term("a bad-word list", &terms),
+            Some("Bad Word")
+        );
+        // Terms only match whole words
+        assert_eq!(find_banned_term("Spammers", &terms), None);
+        assert_eq!(find_banned_term("bad words", &terms), None);
+        assert_eq!(find_banned_term("Groceries", &[String::new()]), None);
+    }
+}
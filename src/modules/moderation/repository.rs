@@ -0,0 +1,164 @@
+//! # `Moderation` Repository
+//! This module defines the moderation repository for the moderation endpoints.
+
+use sqlx::{Error, Pool, Postgres};
+
+use crate::modules::{
+    common::to_db_id,
+    moderation::interfaces::{BannedTermRow, FlagDecision, FlagRow, ModeratedContent},
+};
+use crate::telemetry::observe_query;
+
+// Columns of a `FlagRow` from the flag `f` and its author `u`
+const FLAG_COLUMNS: &str = "f.id, u.username, f.content_type, f.content_id, f.content, f.reason,
+    f.status, f.created_at, f.reviewed_at";
+
+// Name given to the lists whose name was removed by an administrator
+pub const REMOVED_LIST_NAME: &str = "Removed by moderation";
+
+pub struct ModerationRepository {
+    pool: Pool<Postgres>,
+}
+
+impl ModerationRepository {
+    // Constructor
+    pub const fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Banned terms, alphabetically
+    pub async fn banned_terms(&self) -> Result<Vec<BannedTermRow>, Error> {
+        observe_query("moderation.banned_terms", async move {
+            sqlx::query_as::<_, BannedTermRow>(
+                "SELECT id, term, created_at FROM banned_terms ORDER BY LOWER(term)",
+            )
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Ban a term, returns `None` when it's already banned
+    pub async fn add_banned_term(
+        &self,
+        admin_id: i64,
+        term: &str,
+    ) -> Result<Option<BannedTermRow>, Error> {
+        observe_query("moderation.add_banned_term", async move {
+            sqlx::query_as::<_, BannedTermRow>(
+                "INSERT INTO banned_terms (term, created_by) VALUES ($1, $2)
+                 ON CONFLICT (LOWER(term)) DO NOTHING
+                 RETURNING id, term, created_at",
+            )
+            .bind(term)
+            .bind(to_db_id(admin_id)?)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Remove a banned term, returns whether it existed
+    pub async fn delete_banned_term(&self, id: i64) -> Result<bool, Error> {
+        observe_query("moderation.delete_banned_term", async move {
+            let result = sqlx::query("DELETE FROM banned_terms WHERE id = $1")
+                .bind(to_db_id(id)?)
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    // Queue a content flagged by the moderation API for review
+    pub async fn create_flag(
+        &self,
+        user_id: i64,
+        content_type: ModeratedContent,
+        content_id: i64,
+        content: &str,
+        reason: &str,
+    ) -> Result<(), Error> {
+        observe_query("moderation.create_flag", async move {
+            sqlx::query(
+                "INSERT INTO moderation_flags (user_id, content_type, content_id, content, reason)
+                 VALUES ($1, $2, $3, $4, $5)",
+            )
+            .bind(to_db_id(user_id)?)
+            .bind(content_type.as_str())
+            .bind(to_db_id(content_id)?)
+            .bind(content)
+            .bind(reason)
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    // Flags with the status, the oldest first
+    pub async fn list_flags(&self, status: &str) -> Result<Vec<FlagRow>, Error> {
+        observe_query("moderation.list_flags", async move {
+            let query = format!(
+                "SELECT {FLAG_COLUMNS}
+                 FROM moderation_flags f
+                 JOIN users u ON u.id = f.user_id
+                 WHERE f.status = $1
+                 ORDER BY f.created_at, f.id"
+            );
+            sqlx::query_as::<_, FlagRow>(&query)
+                .bind(status)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Review a pending flag. A removed list name is replaced unless the list was renamed
+    // since. Returns `None` without such a flag.
+    pub async fn review_flag(
+        &self,
+        admin_id: i64,
+        id: i64,
+        decision: FlagDecision,
+    ) -> Result<Option<FlagRow>, Error> {
+        observe_query("moderation.review_flag", async move {
+            let mut tx = self.pool.begin().await?;
+
+            let query = format!(
+                "WITH f AS (
+                    UPDATE moderation_flags SET status = $3, reviewed_by = $2, reviewed_at = NOW()
+                    WHERE id = $1 AND status = 'pending'
+                    RETURNING *
+                 )
+                 SELECT {FLAG_COLUMNS}
+                 FROM f
+                 JOIN users u ON u.id = f.user_id"
+            );
+            let Some(flag) = sqlx::query_as::<_, FlagRow>(&query)
+                .bind(to_db_id(id)?)
+                .bind(to_db_id(admin_id)?)
+                .bind(decision.status())
+                .fetch_optional(&mut *tx)
+                .await?
+            else {
+                return Ok(None);
+            };
+
+            if decision == FlagDecision::Remove
+                && flag.content_type == ModeratedContent::ListName.as_str()
+            {
+                sqlx::query("UPDATE lists SET name = $1 WHERE id = $2 AND name = $3")
+                    .bind(REMOVED_LIST_NAME)
+                    .bind(flag.content_id)
+                    .bind(&flag.content)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+
+            tx.commit().await?;
+            Ok(Some(flag))
+        })
+        .await
+    }
+}
@@ -0,0 +1,235 @@
+//! #`Moderation` Routes
+//! This module defines the HTTP routes for the administrators moderating the content.
+
+use axum::extract::{Path, Query};
+use axum::routing::{delete, get, post};
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Router};
+
+use crate::auth::AdminClaims;
+use crate::modules::audit::repository::AuditRepository;
+use crate::modules::audit::service::AuditService;
+use crate::modules::common::{respond, ErrorResponse};
+use crate::modules::moderation::interfaces::{
+    BannedTermRequest, BannedTermResponse, FlagDecision, FlagQuery, FlagResponse,
+    ModerationMessageResponse,
+};
+use crate::modules::moderation::repository::ModerationRepository;
+use crate::modules::moderation::service::ModerationService;
+use crate::modules::session::interfaces::SessionDevice;
+use crate::utils::json::Json;
+use crate::AppState;
+
+// Creates and returns the moderation routes
+pub fn moderation_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/admin/moderation/banned-terms",
+            get(list_banned_terms_route).post(add_banned_term_route),
+        )
+        .route(
+            "/admin/moderation/banned-terms/{id}",
+            delete(delete_banned_term_route),
+        )
+        .route("/admin/moderation/flags", get(list_flags_route))
+        .route(
+            "/admin/moderation/flags/{id}/dismiss",
+            post(dismiss_flag_route),
+        )
+        .route(
+            "/admin/moderation/flags/{id}/remove",
+            post(remove_flag_route),
+        )
+}
+
+fn moderation_service(app_state: &AppState) -> ModerationService {
+    ModerationService::new(
+        ModerationRepository::new(app_state.db_pool.clone()),
+        app_state.moderator.clone(),
+        AuditService::new(AuditRepository::new(app_state.db_pool.clone())),
+    )
+}
+
+// List Banned Terms Route
+#[utoipa::path(
+    get,
+    path = "/admin/moderation/banned-terms",
+    tag = "Moderation",
+    responses(
+        (status = 200, description = "Banned terms, alphabetically", body = [BannedTermResponse]),
+        (status = 403, description = "Not an administrator"),
+        (status = 500, description = "Failed to fetch banned terms", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn list_banned_terms_route(
+    State(app_state): State<AppState>,
+    _admin: AdminClaims,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        moderation_service(&app_state).banned_terms().await,
+    )
+}
+
+// Add Banned Term Route
+#[utoipa::path(
+    post,
+    path = "/admin/moderation/banned-terms",
+    tag = "Moderation",
+    request_body = BannedTermRequest,
+    responses(
+        (status = 201, description = "Term banned from the list names", body = BannedTermResponse),
+        (status = 400, description = "Invalid term", body = ErrorResponse),
+        (status = 403, description = "Not an administrator"),
+        (status = 409, description = "Term already banned", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn add_banned_term_route(
+    State(app_state): State<AppState>,
+    admin: AdminClaims,
+    device: SessionDevice,
+    Json(term_request): Json<BannedTermRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::CREATED,
+        moderation_service(&app_state)
+            .add_banned_term(admin.user_id, term_request, &device)
+            .await,
+    )
+}
+
+// Delete Banned Term Route
+#[utoipa::path(
+    delete,
+    path = "/admin/moderation/banned-terms/{id}",
+    tag = "Moderation",
+    params(
+        ("id" = i64, Path, description = "Banned term id")
+    ),
+    responses(
+        (status = 200, description = "Term no longer banned", body = ModerationMessageResponse),
+        (status = 403, description = "Not an administrator"),
+        (status = 404, description = "Banned term not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn delete_banned_term_route(
+    State(app_state): State<AppState>,
+    admin: AdminClaims,
+    device: SessionDevice,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        moderation_service(&app_state)
+            .delete_banned_term(admin.user_id, id, &device)
+            .await,
+    )
+}
+
+// List Flags Route
+#[utoipa::path(
+    get,
+    path = "/admin/moderation/flags",
+    tag = "Moderation",
+    params(FlagQuery),
+    responses(
+        (status = 200, description = "Content flagged by the moderation API, the oldest first", body = [FlagResponse]),
+        (status = 400, description = "Invalid status", body = ErrorResponse),
+        (status = 403, description = "Not an administrator")
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn list_flags_route(
+    State(app_state): State<AppState>,
+    _admin: AdminClaims,
+    Query(query): Query<FlagQuery>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        moderation_service(&app_state).list_flags(&query).await,
+    )
+}
+
+// Dismiss Flag Route
+#[utoipa::path(
+    post,
+    path = "/admin/moderation/flags/{id}/dismiss",
+    tag = "Moderation",
+    params(
+        ("id" = i64, Path, description = "Flag id")
+    ),
+    responses(
+        (status = 200, description = "Content kept", body = FlagResponse),
+        (status = 403, description = "Not an administrator"),
+        (status = 404, description = "Pending flag not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn dismiss_flag_route(
+    State(app_state): State<AppState>,
+    admin: AdminClaims,
+    device: SessionDevice,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        moderation_service(&app_state)
+            .review_flag(admin.user_id, id, FlagDecision::Dismiss, &device)
+            .await,
+    )
+}
+
+// Remove Flag Route
+#[utoipa::path(
+    post,
+    path = "/admin/moderation/flags/{id}/remove",
+    tag = "Moderation",
+    params(
+        ("id" = i64, Path, description = "Flag id")
+    ),
+    responses(
+        (status = 200, description = "Content replaced, unless changed since it was flagged", body = FlagResponse),
+        (status = 403, description = "Not an administrator"),
+        (status = 404, description = "Pending flag not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn remove_flag_route(
+    State(app_state): State<AppState>,
+    admin: AdminClaims,
+    device: SessionDevice,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        moderation_service(&app_state)
+            .review_flag(admin.user_id, id, FlagDecision::Remove, &device)
+            .await,
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_moderation_routes_creation() {
+        let _routes = moderation_routes();
+        assert!(true);
+    }
+}
@@ -0,0 +1,258 @@
+//! # `Moderation` Service
+//!
+//! This module contains the bussiness logic for the moderation of the user content. Content
+//! with a banned term is refused, content flagged by the moderation API is saved and
+//! queued for the administrators, who keep or remove it.
+
+use std::sync::Arc;
+
+use crate::modules::{
+    audit::{
+        interfaces::{AuditAction, NewAuditEntry},
+        service::AuditService,
+    },
+    common::AppError,
+    moderation::{
+        interfaces::{
+            BannedTermRequest, BannedTermResponse, FlagDecision, FlagQuery, FlagResponse,
+            ModeratedContent, ModerationMessageResponse,
+        },
+        moderator::{find_banned_term, Moderator},
+        repository::ModerationRepository,
+    },
+    session::interfaces::SessionDevice,
+};
+
+// Code of the errors refusing content with a banned term
+pub const BANNED_TERM: &str = "banned_term";
+
+// Longest banned term
+const MAX_TERM_LENGTH: usize = 100;
+
+// Status of the flags listed by the review queue, `pending` by default
+fn parse_status(status: Option<&str>) -> Result<&'static str, AppError> {
+    match status.map(str::trim) {
+        None | Some("pending") => Ok("pending"),
+        Some("dismissed") => Ok("dismissed"),
+        Some("removed") => Ok("removed"),
+        Some(_) => Err(AppError::Validation(
+            "Status must be one of pending, dismissed or removed".to_string(),
+        )),
+    }
+}
+
+pub struct ModerationService {
+    moderation_repository: ModerationRepository,
+    moderator: Option<Arc<dyn Moderator>>,
+    audit_service: AuditService,
+}
+
+impl ModerationService {
+    pub const fn new(
+        moderation_repository: ModerationRepository,
+        moderator: Option<Arc<dyn Moderator>>,
+        audit_service: AuditService,
+    ) -> Self {
+        Self {
+            moderation_repository,
+            moderator,
+            audit_service,
+        }
+    }
+
+    // Check a content before saving it. Content with a banned term is refused, the
+    // reason of the moderation API is returned for content to flag once saved. The
+    // content is accepted when the moderation API fails.
+    pub async fn check(&self, content: &str) -> Result<Option<String>, AppError> {
+        let terms = match self.moderation_repository.banned_terms().await {
+            Ok(terms) => terms.into_iter().map(|row| row.term).collect::<Vec<_>>(),
+            Err(e) => {
+                tracing::warn!("Error fetching banned terms: {}", e);
+                return Err(AppError::Database(e));
+            }
+        };
+        if find_banned_term(content, &terms).is_some() {
+            return Err(AppError::Rejected(
+                BANNED_TERM,
+                "Content contains a banned term".to_string(),
+            ));
+        }
+
+        let Some(moderator) = &self.moderator else {
+            return Ok(None);
+        };
+        match moderator.review(content).await {
+            Ok(reason) => Ok(reason),
+            Err(e) => {
+                tracing::warn!("Error moderating content with {}: {}", moderator.name(), e);
+                Ok(None)
+            }
+        }
+    }
+
+    // Queue a saved content flagged by `check` for review
+    pub async fn flag(
+        &self,
+        user_id: i64,
+        content_type: ModeratedContent,
+        content_id: i64,
+        content: &str,
+        reason: &str,
+    ) {
+        if let Err(e) = self
+            .moderation_repository
+            .create_flag(user_id, content_type, content_id, content, reason)
+            .await
+        {
+            tracing::warn!("Error flagging content: {}", e);
+        }
+    }
+
+    // List the banned terms
+    pub async fn banned_terms(&self) -> Result<Vec<BannedTermResponse>, AppError> {
+        match self.moderation_repository.banned_terms().await {
+            Ok(terms) => Ok(terms.into_iter().map(BannedTermResponse::from).collect()),
+            Err(e) => {
+                tracing::warn!("Error fetching banned terms: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Ban a term
+    pub async fn add_banned_term(
+        &self,
+        admin_id: i64,
+        term_request: BannedTermRequest,
+        device: &SessionDevice,
+    ) -> Result<BannedTermResponse, AppError> {
+        let Some(term) = term_request
+            .term
+            .as_deref()
+            .map(str::trim)
+            .filter(|term| !term.is_empty())
+        else {
+            return Err(AppError::Validation(
+                "Missing required fields: term".to_string(),
+            ));
+        };
+        if term.chars().count() > MAX_TERM_LENGTH {
+            return Err(AppError::Validation(format!(
+                "Banned terms must be at most {MAX_TERM_LENGTH} characters"
+            )));
+        }
+
+        match self
+            .moderation_repository
+            .add_banned_term(admin_id, term)
+            .await
+        {
+            Ok(Some(term)) => {
+                self.audit_service
+                    .record(NewAuditEntry {
+                        user_id: None,
+                        actor_id: Some(admin_id),
+                        action: AuditAction::BannedTermAdded,
+                        device,
+                        details: Some(term.term.clone()),
+                    })
+                    .await;
+                Ok(BannedTermResponse::from(term))
+            }
+            Ok(None) => Err(AppError::Conflict("Term already banned".to_string())),
+            Err(e) => {
+                tracing::warn!("Error banning term: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Remove a banned term
+    pub async fn delete_banned_term(
+        &self,
+        admin_id: i64,
+        id: i64,
+        device: &SessionDevice,
+    ) -> Result<ModerationMessageResponse, AppError> {
+        match self.moderation_repository.delete_banned_term(id).await {
+            Ok(true) => {
+                self.audit_service
+                    .record(NewAuditEntry {
+                        user_id: None,
+                        actor_id: Some(admin_id),
+                        action: AuditAction::BannedTermDeleted,
+                        device,
+                        details: Some(format!("banned term {id}")),
+                    })
+                    .await;
+                Ok(ModerationMessageResponse {
+                    message: "Banned term deleted".to_string(),
+                })
+            }
+            Ok(false) => Err(AppError::NotFound("Banned term not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error deleting banned term: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Review queue of the flagged content
+    pub async fn list_flags(&self, query: &FlagQuery) -> Result<Vec<FlagResponse>, AppError> {
+        let status = parse_status(query.status.as_deref())?;
+
+        match self.moderation_repository.list_flags(status).await {
+            Ok(flags) => Ok(flags.into_iter().map(FlagResponse::from).collect()),
+            Err(e) => {
+                tracing::warn!("Error fetching moderation flags: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Keep or remove a pending flagged content
+    pub async fn review_flag(
+        &self,
+        admin_id: i64,
+        id: i64,
+        decision: FlagDecision,
+        device: &SessionDevice,
+    ) -> Result<FlagResponse, AppError> {
+        match self
+            .moderation_repository
+            .review_flag(admin_id, id, decision)
+            .await
+        {
+            Ok(Some(flag)) => {
+                self.audit_service
+                    .record(NewAuditEntry {
+                        user_id: None,
+                        actor_id: Some(admin_id),
+                        action: AuditAction::FlagReviewed,
+                        device,
+                        details: Some(format!("flag {id} {}", flag.status)),
+                    })
+                    .await;
+                Ok(FlagResponse::from(flag))
+            }
+            Ok(None) => Err(AppError::NotFound("Pending flag not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error reviewing moderation flag: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_status() {
+        assert_eq!(parse_status(None).ok(), Some("pending"));
+        assert_eq!(parse_status(Some(" dismissed ")).ok(), Some("dismissed"));
+        assert_eq!(parse_status(Some("removed")).ok(), Some("removed"));
+        assert!(parse_status(Some("confirmed")).is_err());
+    }
+}
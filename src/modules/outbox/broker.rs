@@ -0,0 +1,294 @@
+//! # Outbox Broker
+//! This module defines the message broker the relayed events are published to, selected
+//! with `EVENT_BROKER`. Events stay in process by default, NATS, `RabbitMQ` and Kafka
+//! are available with the `nats`, `amqp` and `kafka` features.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::broadcast;
+
+use crate::modules::outbox::{interfaces::OutboxMessage, sink::EventSink};
+
+// Events buffered for in-process subscribers, slow subscribers skip older events
+const BROADCAST_BUFFER: usize = 256;
+
+// Events published in process to the subscribers of the relaying instance
+#[derive(Clone)]
+pub struct BroadcastSink {
+    sender: broadcast::Sender<OutboxMessage>,
+}
+
+impl Default for BroadcastSink {
+    fn default() -> Self {
+        Self {
+            sender: broadcast::channel(BROADCAST_BUFFER).0,
+        }
+    }
+}
+
+impl BroadcastSink {
+    pub fn subscribe(&self) -> broadcast::Receiver<OutboxMessage> {
+        self.sender.subscribe()
+    }
+}
+
+#[async_trait]
+impl EventSink for BroadcastSink {
+    fn name(&self) -> &'static str {
+        "broadcast"
+    }
+
+    async fn deliver(&self, message: &OutboxMessage) -> Result<(), String> {
+        // Sending only fails without subscribers, nobody missed the event
+        if self.sender.send(message.clone()).is_err() {
+            tracing::debug!("Outbox event {} without subscribers", message.id);
+        }
+        Ok(())
+    }
+}
+
+// Events published on `<prefix>.<type>` subjects, e.g. `todo_app.todo.created`.
+// The event id is sent as `Nats-Msg-Id` so JetStream streams drop redeliveries.
+#[cfg(feature = "nats")]
+pub struct NatsSink {
+    client: async_nats::Client,
+    subject_prefix: String,
+}
+
+#[cfg(feature = "nats")]
+impl NatsSink {
+    pub async fn connect(url: &str, subject_prefix: String) -> Result<Self, String> {
+        let client = async_nats::connect(url)
+            .await
+            .map_err(|e| format!("Failed to connect to NATS: {e}"))?;
+        Ok(Self {
+            client,
+            subject_prefix,
+        })
+    }
+}
+
+#[cfg(feature = "nats")]
+#[async_trait]
+impl EventSink for NatsSink {
+    fn name(&self) -> &'static str {
+        "nats"
+    }
+
+    async fn deliver(&self, message: &OutboxMessage) -> Result<(), String> {
+        let payload = serde_json::to_vec(message).map_err(|e| e.to_string())?;
+        let mut headers = async_nats::HeaderMap::new();
+        headers.insert("Nats-Msg-Id", message.id.to_string().as_str());
+
+        self.client
+            .publish_with_headers(
+                format!("{}.{}", self.subject_prefix, message.event_type),
+                headers,
+                payload.into(),
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        // Published once the server has it
+        self.client.flush().await.map_err(|e| e.to_string())
+    }
+}
+
+// Events published to a RabbitMQ exchange with the event type as routing key.
+// Publisher confirms make a nack a failed delivery.
+#[cfg(feature = "amqp")]
+pub struct AmqpSink {
+    // Kept open for the lifetime of the channel
+    _connection: lapin::Connection,
+    channel: lapin::Channel,
+    exchange: String,
+}
+
+#[cfg(feature = "amqp")]
+impl AmqpSink {
+    pub async fn connect(url: &str, exchange: String) -> Result<Self, String> {
+        let connection = lapin::Connection::connect(url, lapin::ConnectionProperties::default())
+            .await
+            .map_err(|e| format!("Failed to connect to RabbitMQ: {e}"))?;
+        let channel = connection
+            .create_channel()
+            .await
+            .map_err(|e| format!("Failed to open RabbitMQ channel: {e}"))?;
+        channel
+            .confirm_select(lapin::options::ConfirmSelectOptions::default())
+            .await
+            .map_err(|e| format!("Failed to enable RabbitMQ confirms: {e}"))?;
+        Ok(Self {
+            _connection: connection,
+            channel,
+            exchange,
+        })
+    }
+}
+
+#[cfg(feature = "amqp")]
+#[async_trait]
+impl EventSink for AmqpSink {
+    fn name(&self) -> &'static str {
+        "amqp"
+    }
+
+    async fn deliver(&self, message: &OutboxMessage) -> Result<(), String> {
+        let payload = serde_json::to_vec(message).map_err(|e| e.to_string())?;
+        let properties = lapin::BasicProperties::default()
+            .with_message_id(message.id.to_string().into())
+            .with_content_type("application/json".into())
+            .with_delivery_mode(2);
+
+        let confirmation = self
+            .channel
+            .basic_publish(
+                &self.exchange,
+                &message.event_type,
+                lapin::options::BasicPublishOptions::default(),
+                &payload,
+                properties,
+            )
+            .await
+            .map_err(|e| e.to_string())?
+            .await
+            .map_err(|e| e.to_string())?;
+        if confirmation.is_nack() {
+            return Err("Message rejected by RabbitMQ".to_string());
+        }
+        Ok(())
+    }
+}
+
+// Events produced to a Kafka topic, keyed by workspace so the events of a workspace
+// keep their order within a partition. The producer is idempotent, retries of a send
+// are not duplicated.
+#[cfg(feature = "kafka")]
+pub struct KafkaSink {
+    producer: rdkafka::producer::FutureProducer,
+    topic: String,
+}
+
+#[cfg(feature = "kafka")]
+impl KafkaSink {
+    pub fn connect(brokers: &str, topic: String) -> Result<Self, String> {
+        let producer = rdkafka::ClientConfig::new()
+            .set("bootstrap.servers", brokers)
+            .set("enable.idempotence", "true")
+            .set("message.timeout.ms", "10000")
+            .create()
+            .map_err(|e| format!("Failed to create Kafka producer: {e}"))?;
+        Ok(Self { producer, topic })
+    }
+}
+
+#[cfg(feature = "kafka")]
+#[async_trait]
+impl EventSink for KafkaSink {
+    fn name(&self) -> &'static str {
+        "kafka"
+    }
+
+    async fn deliver(&self, message: &OutboxMessage) -> Result<(), String> {
+        let payload = serde_json::to_vec(message).map_err(|e| e.to_string())?;
+        let key = message.workspace_id.to_string();
+        let id = message.id.to_string();
+        let headers = rdkafka::message::OwnedHeaders::new().insert(rdkafka::message::Header {
+            key: "event_id",
+            value: Some(&id),
+        });
+
+        // Delivered once acknowledged by the brokers
+        self.producer
+            .send(
+                rdkafka::producer::FutureRecord::to(&self.topic)
+                    .key(&key)
+                    .payload(&payload)
+                    .headers(headers),
+                std::time::Duration::from_secs(5),
+            )
+            .await
+            .map(|_| ())
+            .map_err(|(e, _)| e.to_string())
+    }
+}
+
+fn env_or(name: &str, default: &str) -> String {
+    std::env::var(name)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| default.to_string())
+}
+
+#[cfg(not(all(feature = "nats", feature = "amqp", feature = "kafka")))]
+fn missing_feature(broker: &str) -> String {
+    format!("EVENT_BROKER={broker} needs the application built with the {broker} feature")
+}
+
+// Broker configured in the environment, the in-process broadcast when not set.
+// Only brokers built in connect, hence nothing to await without them.
+#[cfg_attr(
+    not(any(feature = "nats", feature = "amqp")),
+    allow(clippy::unused_async)
+)]
+pub async fn broker_from_env(broadcast: &BroadcastSink) -> Result<Arc<dyn EventSink>, String> {
+    match env_or("EVENT_BROKER", "broadcast").as_str() {
+        "broadcast" => Ok(Arc::new(broadcast.clone())),
+        #[cfg(feature = "nats")]
+        "nats" => Ok(Arc::new(
+            NatsSink::connect(
+                &env_or("NATS_URL", "nats://localhost:4222"),
+                env_or("NATS_SUBJECT_PREFIX", "todo_app"),
+            )
+            .await?,
+        )),
+        #[cfg(feature = "amqp")]
+        "amqp" => Ok(Arc::new(
+            AmqpSink::connect(
+                &env_or("AMQP_URL", "amqp://localhost:5672/%2f"),
+                env_or("AMQP_EXCHANGE", "todo_app.events"),
+            )
+            .await?,
+        )),
+        #[cfg(feature = "kafka")]
+        "kafka" => Ok(Arc::new(KafkaSink::connect(
+            &env_or("KAFKA_BROKERS", "localhost:9092"),
+            env_or("KAFKA_TOPIC", "todo_app.events"),
+        )?)),
+        #[cfg(not(feature = "nats"))]
+        "nats" => Err(missing_feature("nats")),
+        #[cfg(not(feature = "amqp"))]
+        "amqp" => Err(missing_feature("amqp")),
+        #[cfg(not(feature = "kafka"))]
+        "kafka" => Err(missing_feature("kafka")),
+        broker => Err(format!("Unknown EVENT_BROKER: {broker}")),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn message() -> OutboxMessage {
+        OutboxMessage {
+            id: 8,
+            event_type: "todo.created".to_string(),
+            user_id: 1,
+            workspace_id: 2,
+            occurred_at: time::OffsetDateTime::UNIX_EPOCH,
+            data: serde_json::json!({ "id": 3 }),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_reaches_subscribers() {
+        let sink = BroadcastSink::default();
+        // Without subscribers the event is dropped, not failed
+        sink.deliver(&message()).await.unwrap();
+
+        let mut events = sink.subscribe();
+        sink.deliver(&message()).await.unwrap();
+        assert_eq!(events.recv().await.unwrap().id, 8);
+    }
+}
@@ -0,0 +1,82 @@
+//! # `Outbox` Interfaces
+//! This module defines the domain events stored in the outbox and their delivered form
+
+use serde::Serialize;
+use time::OffsetDateTime;
+
+use crate::utils::dates::rfc3339;
+
+// Outbox event row as stored in database, the payload is read as JSON text
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct OutboxEventRow {
+    pub id: i64,
+    pub user_id: i32,
+    pub workspace_id: i32,
+    pub event_type: String,
+    pub payload: String,
+    pub created_at: OffsetDateTime,
+}
+
+// Event delivered to the sinks. Delivery is at least once, consumers drop the ids
+// they have already processed.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct OutboxMessage {
+    pub id: i64,
+    // `user.created`, `todo.created`, `todo.completed` or `todo.updated`
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub user_id: i64,
+    pub workspace_id: i64,
+    #[serde(with = "rfc3339")]
+    pub occurred_at: OffsetDateTime,
+    // State of the user or todo when the event happened
+    pub data: serde_json::Value,
+}
+
+impl TryFrom<OutboxEventRow> for OutboxMessage {
+    type Error = serde_json::Error;
+
+    fn try_from(row: OutboxEventRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.id,
+            event_type: row.event_type,
+            user_id: i64::from(row.user_id),
+            workspace_id: i64::from(row.workspace_id),
+            occurred_at: row.created_at,
+            data: serde_json::from_str(&row.payload)?,
+        })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn row(payload: &str) -> OutboxEventRow {
+        OutboxEventRow {
+            id: 12,
+            user_id: 3,
+            workspace_id: 4,
+            event_type: "todo.completed".to_string(),
+            payload: payload.to_string(),
+            created_at: time::macros::datetime!(2025-03-01 12:00 UTC),
+        }
+    }
+
+    #[test]
+    fn test_message_from_row() {
+        let message = OutboxMessage::try_from(row(r#"{"id":7,"status":"done"}"#)).unwrap();
+        assert_eq!(message.data["status"], "done");
+
+        let json = serde_json::to_value(&message).unwrap();
+        assert_eq!(json["type"], "todo.completed");
+        assert_eq!(json["id"], 12);
+        assert_eq!(json["data"]["id"], 7);
+    }
+
+    #[test]
+    fn test_message_rejects_invalid_payload() {
+        assert!(OutboxMessage::try_from(row("not json")).is_err());
+    }
+}
@@ -0,0 +1,8 @@
+//! # `Outbox` Mod
+//! Outbox imports for the domain events relayed to external consumers
+
+pub mod broker;
+pub mod interfaces;
+pub mod relay;
+pub mod repository;
+pub mod sink;
@@ -0,0 +1,187 @@
+//! # Outbox Relay
+//! Background delivery of the outbox events to the configured sinks. A single instance
+//! relays at a time so events leave in the order they happened. Delivery is at least
+//! once, an event is only marked published once every sink accepted it.
+
+use std::sync::Arc;
+
+use sqlx::{Pool, Postgres};
+use time::OffsetDateTime;
+
+use crate::{
+    modules::outbox::{interfaces::OutboxMessage, repository::OutboxRepository, sink::EventSink},
+    workers::{run_exclusive, WorkerTask},
+};
+
+// Delay between two relay runs
+const RELAY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+// Events read per run
+const RELAY_BATCH: i64 = 100;
+
+// Failed deliveries before an event is set aside
+const MAX_ATTEMPTS: i32 = 10;
+
+// Delay between two purges of the published events
+const PURGE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+// Published events are kept this long
+const PUBLISHED_RETENTION: time::Duration = time::Duration::days(7);
+
+// Advisory lock taken by the instance relaying
+const RELAY_LOCK: &str = "outbox_relay";
+
+// Deliver a message to every sink, stopping at the first failure
+async fn deliver(sinks: &[Arc<dyn EventSink>], message: &OutboxMessage) -> Result<(), String> {
+    for sink in sinks {
+        sink.deliver(message)
+            .await
+            .map_err(|e| format!("{} sink: {e}", sink.name()))?;
+    }
+    Ok(())
+}
+
+// Relay the pending events in order, returns how many were published.
+// A failed event stops the run so the next ones don't overtake it.
+async fn relay_pending(
+    outbox_repository: &OutboxRepository,
+    sinks: &[Arc<dyn EventSink>],
+) -> Result<usize, sqlx::Error> {
+    let mut published = 0;
+    for row in outbox_repository.list_pending(RELAY_BATCH).await? {
+        let id = row.id;
+        let result = match OutboxMessage::try_from(row) {
+            Ok(message) => deliver(sinks, &message).await,
+            Err(e) => Err(format!("Invalid payload: {e}")),
+        };
+
+        match result {
+            Ok(()) => {
+                outbox_repository.mark_published(id).await?;
+                published += 1;
+            }
+            Err(e) => {
+                if outbox_repository
+                    .record_failure(id, &e, MAX_ATTEMPTS)
+                    .await?
+                {
+                    tracing::error!("Outbox event {} failed for good: {}", id, e);
+                } else {
+                    tracing::warn!("Outbox event {} not delivered: {}", id, e);
+                }
+                break;
+            }
+        }
+    }
+    Ok(published)
+}
+
+// Worker relaying the outbox events, then purging the events published for longer than
+// `PUBLISHED_RETENTION`. Database errors are logged and retried on the next run.
+pub fn relay_worker(
+    pool: Pool<Postgres>,
+    outbox_repository: OutboxRepository,
+    sinks: Vec<Arc<dyn EventSink>>,
+) -> WorkerTask {
+    Box::pin(async move {
+        let mut relay_interval = tokio::time::interval(RELAY_INTERVAL);
+        let mut purge_interval = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = relay_interval.tick() => {
+                    let job = relay_pending(&outbox_repository, &sinks);
+                    match run_exclusive(&pool, RELAY_LOCK, job).await {
+                        Ok(Some(Ok(0)) | None) => {}
+                        Ok(Some(Ok(published))) => {
+                            tracing::debug!("Relayed {} outbox events", published);
+                        }
+                        Ok(Some(Err(e))) => tracing::warn!("Error relaying outbox events: {}", e),
+                        Err(e) => tracing::warn!("Error locking outbox relay: {}", e),
+                    }
+                }
+                _ = purge_interval.tick() => {
+                    let cutoff = OffsetDateTime::now_utc()
+                        .checked_sub(PUBLISHED_RETENTION)
+                        .unwrap_or(OffsetDateTime::UNIX_EPOCH);
+                    match outbox_repository.purge_published(cutoff).await {
+                        Ok(0) => {}
+                        Ok(purged) => tracing::info!("Purged {} published outbox events", purged),
+                        Err(e) => tracing::warn!("Error purging outbox events: {}", e),
+                    }
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+
+    use super::*;
+
+    struct RecordingSink {
+        fail: bool,
+        delivered: Mutex<Vec<i64>>,
+    }
+
+    impl RecordingSink {
+        fn new(fail: bool) -> Arc<Self> {
+            Arc::new(Self {
+                fail,
+                delivered: Mutex::new(Vec::new()),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl EventSink for RecordingSink {
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+
+        async fn deliver(&self, message: &OutboxMessage) -> Result<(), String> {
+            if self.fail {
+                return Err("unavailable".to_string());
+            }
+            self.delivered.lock().unwrap().push(message.id);
+            Ok(())
+        }
+    }
+
+    fn message() -> OutboxMessage {
+        OutboxMessage {
+            id: 5,
+            event_type: "todo.created".to_string(),
+            user_id: 1,
+            workspace_id: 2,
+            occurred_at: OffsetDateTime::UNIX_EPOCH,
+            data: serde_json::json!({ "id": 9 }),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deliver_reaches_every_sink() {
+        let first = RecordingSink::new(false);
+        let second = RecordingSink::new(false);
+        let sinks: Vec<Arc<dyn EventSink>> = vec![first.clone(), second.clone()];
+
+        deliver(&sinks, &message()).await.unwrap();
+        assert_eq!(*first.delivered.lock().unwrap(), vec![5]);
+        assert_eq!(*second.delivered.lock().unwrap(), vec![5]);
+    }
+
+    #[tokio::test]
+    async fn test_deliver_stops_at_failure() {
+        let failing = RecordingSink::new(true);
+        let after = RecordingSink::new(false);
+        let sinks: Vec<Arc<dyn EventSink>> = vec![failing, after.clone()];
+
+        let error = deliver(&sinks, &message()).await.unwrap_err();
+        assert_eq!(error, "recording sink: unavailable");
+        assert!(after.delivered.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,88 @@
+//! # `Outbox` Repository
+//! This module defines the outbox repository read by the relay worker.
+
+use sqlx::{Error, Pool, Postgres};
+use time::OffsetDateTime;
+
+use crate::modules::outbox::interfaces::OutboxEventRow;
+use crate::telemetry::observe_query;
+
+pub struct OutboxRepository {
+    pool: Pool<Postgres>,
+}
+
+impl OutboxRepository {
+    // Constructor
+    pub const fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // List the oldest events neither published nor failed, in the order they happened
+    pub async fn list_pending(&self, limit: i64) -> Result<Vec<OutboxEventRow>, Error> {
+        observe_query("outbox.list_pending", async move {
+            sqlx::query_as::<_, OutboxEventRow>(
+                "SELECT id, user_id, workspace_id, event_type, payload::TEXT AS payload,
+                    created_at
+                 FROM outbox_events
+                 WHERE published_at IS NULL AND failed_at IS NULL
+                 ORDER BY id ASC
+                 LIMIT $1",
+            )
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Mark an event delivered to every sink
+    pub async fn mark_published(&self, id: i64) -> Result<(), Error> {
+        observe_query("outbox.mark_published", async move {
+            sqlx::query("UPDATE outbox_events SET published_at = NOW() WHERE id = $1")
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    // Record a failed delivery. After `max_attempts` the event is marked failed and
+    // skipped, returns whether that happened.
+    pub async fn record_failure(
+        &self,
+        id: i64,
+        error: &str,
+        max_attempts: i32,
+    ) -> Result<bool, Error> {
+        observe_query("outbox.record_failure", async move {
+            sqlx::query_scalar::<_, bool>(
+                "UPDATE outbox_events SET
+                    attempts = attempts + 1,
+                    last_error = $2,
+                    failed_at = CASE WHEN attempts + 1 >= $3 THEN NOW() END
+                 WHERE id = $1
+                 RETURNING failed_at IS NOT NULL",
+            )
+            .bind(id)
+            .bind(error)
+            .bind(max_attempts)
+            .fetch_one(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Delete the events published before `before`, returns how many were deleted.
+    // Failed events are kept for inspection.
+    pub async fn purge_published(&self, before: OffsetDateTime) -> Result<u64, Error> {
+        observe_query("outbox.purge_published", async move {
+            let result = sqlx::query("DELETE FROM outbox_events WHERE published_at < $1")
+                .bind(before)
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected())
+        })
+        .await
+    }
+}
@@ -0,0 +1,96 @@
+//! # Outbox Sinks
+//! This module defines where the relayed events are delivered, configured with the
+//! `OUTBOX_*` variables.
+
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use reqwest::{Client, Url};
+
+use crate::modules::outbox::{
+    broker::{broker_from_env, BroadcastSink},
+    interfaces::OutboxMessage,
+};
+
+const DEFAULT_WEBHOOK_TIMEOUT_SECONDS: u64 = 10;
+
+// Header carrying the event id, consumers use it to drop redeliveries
+const EVENT_ID_HEADER: &str = "X-Outbox-Event-Id";
+
+#[async_trait]
+pub trait EventSink: Send + Sync {
+    // Name used in the logs
+    fn name(&self) -> &'static str;
+
+    // Deliver the event, an error has it retried later
+    async fn deliver(&self, message: &OutboxMessage) -> Result<(), String>;
+}
+
+// Events posted as JSON to an HTTP endpoint, any non 2xx answer is a failure
+pub struct WebhookSink {
+    client: Client,
+    url: Url,
+}
+
+impl WebhookSink {
+    pub fn new(url: &str, timeout: Duration) -> Result<Self, String> {
+        let url = Url::parse(url).map_err(|e| format!("Invalid OUTBOX_WEBHOOK_URL: {e}"))?;
+        let client = Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| format!("Failed to create webhook client: {e}"))?;
+        Ok(Self { client, url })
+    }
+}
+
+#[async_trait]
+impl EventSink for WebhookSink {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    async fn deliver(&self, message: &OutboxMessage) -> Result<(), String> {
+        self.client
+            .post(self.url.clone())
+            .header(EVENT_ID_HEADER, message.id.to_string())
+            .json(message)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+// Sinks configured in the environment, the broker of `EVENT_BROKER` then the webhook
+// when `OUTBOX_WEBHOOK_URL` is set
+pub async fn sinks_from_env(broadcast: &BroadcastSink) -> Result<Vec<Arc<dyn EventSink>>, String> {
+    let mut sinks = vec![broker_from_env(broadcast).await?];
+
+    if let Some(url) = std::env::var("OUTBOX_WEBHOOK_URL")
+        .ok()
+        .filter(|url| !url.trim().is_empty())
+    {
+        let timeout = std::env::var("OUTBOX_WEBHOOK_TIMEOUT_SECONDS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_WEBHOOK_TIMEOUT_SECONDS);
+        sinks.push(Arc::new(WebhookSink::new(
+            url.trim(),
+            Duration::from_secs(timeout),
+        )?));
+    }
+
+    Ok(sinks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_webhook_requires_valid_url() {
+        assert!(WebhookSink::new("not a url", Duration::from_secs(1)).is_err());
+        assert!(WebhookSink::new("https://example.com/hooks", Duration::from_secs(1)).is_ok());
+    }
+}
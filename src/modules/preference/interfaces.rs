@@ -0,0 +1,232 @@
+//! # `Preference` Interfaces
+//! This module defines the data structures from Preferences module
+
+use serde::{Deserialize, Serialize};
+use time::{OffsetDateTime, Time};
+use utoipa::ToSchema;
+
+use crate::utils::{
+    dates::rfc3339_option,
+    format::{Formatter, DEFAULT_LOCALE},
+};
+
+// Timezone of the users without preferences
+pub const DEFAULT_TIMEZONE: &str = "UTC";
+
+// Version of the settings export, bumped when the format changes
+pub const SETTINGS_VERSION: u32 = 1;
+
+// Daily window, `HH:MM` in the user timezone. It wraps around midnight when it ends
+// before it starts, e.g. 22:00 to 07:00.
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, PartialEq, Eq)]
+pub struct QuietHours {
+    pub start: String,
+    pub end: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct UpdatePreferencesRequest {
+    // IANA timezone, e.g. Europe/Lisbon, UTC when not set
+    pub timezone: Option<String>,
+    // Window in which only critical notifications are sent, the others are held until
+    // it ends. Not set disables the quiet hours.
+    pub quiet_hours: Option<QuietHours>,
+    // Locale of the dates and numbers in the emails and exports, e.g. pt-BR, en-US
+    // when not set
+    pub locale: Option<String>,
+    // `saturday`, `sunday` or `monday`, the locale decides when not set
+    pub week_start: Option<String>,
+    // `12h` or `24h`, the locale decides when not set
+    pub time_format: Option<String>,
+}
+
+// Validated preferences, as written to the database
+#[derive(Clone, Debug)]
+pub struct PreferenceValues {
+    pub timezone: String,
+    pub quiet_hours: Option<(Time, Time)>,
+    pub locale: String,
+    pub week_start: Option<String>,
+    pub time_format: Option<String>,
+}
+
+// Preferences row as stored in database
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct PreferencesRow {
+    pub timezone: String,
+    pub quiet_start: Option<Time>,
+    pub quiet_end: Option<Time>,
+    pub locale: String,
+    pub week_start: Option<String>,
+    pub time_format: Option<String>,
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct PreferencesResponse {
+    pub timezone: String,
+    pub quiet_hours: Option<QuietHours>,
+    pub locale: String,
+    pub week_start: Option<String>,
+    pub time_format: Option<String>,
+    #[serde(with = "rfc3339_option")]
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+// Time of day as `HH:MM`
+fn format_time(time: Time) -> String {
+    format!("{:02}:{:02}", time.hour(), time.minute())
+}
+
+impl From<PreferencesRow> for PreferencesResponse {
+    fn from(row: PreferencesRow) -> Self {
+        Self {
+            timezone: row.timezone,
+            quiet_hours: row
+                .quiet_start
+                .zip(row.quiet_end)
+                .map(|(start, end)| QuietHours {
+                    start: format_time(start),
+                    end: format_time(end),
+                }),
+            locale: row.locale,
+            week_start: row.week_start,
+            time_format: row.time_format,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+impl Default for PreferencesResponse {
+    fn default() -> Self {
+        Self {
+            timezone: DEFAULT_TIMEZONE.to_string(),
+            quiet_hours: None,
+            locale: DEFAULT_LOCALE.to_string(),
+            week_start: None,
+            time_format: None,
+            updated_at: None,
+        }
+    }
+}
+
+// How the clients should format dates, times and numbers for the user, the same rules
+// the server follows in the emails and exports
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, PartialEq, Eq)]
+pub struct FormatSettingsResponse {
+    pub locale: String,
+    pub timezone: String,
+    // `saturday`, `sunday` or `monday`
+    pub week_start: String,
+    // `12h` or `24h`
+    pub time_format: String,
+    // e.g. `MM/DD/YYYY`
+    pub date_pattern: String,
+    // `h:mm A` or `HH:mm`
+    pub time_pattern: String,
+    pub decimal_separator: String,
+    pub group_separator: String,
+}
+
+impl From<PreferencesResponse> for FormatSettingsResponse {
+    fn from(preferences: PreferencesResponse) -> Self {
+        let formatter = Formatter::new(&preferences.locale, preferences.time_format.as_deref());
+        let rules = formatter.rules();
+        Self {
+            locale: rules.tag.to_string(),
+            timezone: preferences.timezone,
+            week_start: preferences
+                .week_start
+                .unwrap_or_else(|| rules.week_start.as_str().to_string()),
+            time_format: formatter.time_format().as_str().to_string(),
+            date_pattern: rules.date_pattern(),
+            time_pattern: formatter.time_pattern().to_string(),
+            decimal_separator: rules.decimal_separator.to_string(),
+            group_separator: rules.group_separator.to_string(),
+        }
+    }
+}
+
+// Notifications of a settings export
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct NotificationSettings {
+    // Days of the monthly stale todos review, not set when not subscribed
+    pub stale_review_days: Option<i64>,
+}
+
+// Portable settings of a user, exported from one account and imported into another,
+// possibly on another deployment
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct SettingsExport {
+    // Format of the export, only `SETTINGS_VERSION` is imported
+    pub version: u32,
+    #[serde(default, with = "rfc3339_option")]
+    pub exported_at: Option<OffsetDateTime>,
+    pub preferences: UpdatePreferencesRequest,
+    #[serde(default)]
+    pub notifications: NotificationSettings,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::time;
+
+    #[test]
+    fn test_preferences_response_formats_quiet_hours() {
+        let response = PreferencesResponse::from(PreferencesRow {
+            timezone: "Europe/Lisbon".to_string(),
+            quiet_start: Some(time!(22:00)),
+            quiet_end: Some(time!(7:30)),
+            locale: "en-GB".to_string(),
+            week_start: None,
+            time_format: None,
+            updated_at: None,
+        });
+
+        assert_eq!(
+            response.quiet_hours,
+            Some(QuietHours {
+                start: "22:00".to_string(),
+                end: "07:30".to_string(),
+            })
+        );
+        assert!(PreferencesResponse::default().quiet_hours.is_none());
+    }
+
+    #[test]
+    fn test_format_settings_follow_locale() {
+        let settings = FormatSettingsResponse::from(PreferencesResponse::default());
+        assert_eq!(settings.locale, DEFAULT_LOCALE);
+        assert_eq!(settings.week_start, "sunday");
+        assert_eq!(settings.time_format, "12h");
+        assert_eq!(settings.date_pattern, "MM/DD/YYYY");
+
+        let settings = FormatSettingsResponse::from(PreferencesResponse {
+            timezone: "Europe/Berlin".to_string(),
+            locale: "de-DE".to_string(),
+            week_start: Some("sunday".to_string()),
+            time_format: Some("12h".to_string()),
+            ..PreferencesResponse::default()
+        });
+        assert_eq!(settings.timezone, "Europe/Berlin");
+        assert_eq!(settings.week_start, "sunday");
+        assert_eq!(settings.time_pattern, "h:mm A");
+        assert_eq!(settings.date_pattern, "DD.MM.YYYY");
+        assert_eq!(settings.decimal_separator, ",");
+    }
+
+    #[test]
+    fn test_settings_export_rejects_unknown_fields() {
+        let export: Result<SettingsExport, _> = serde_json::from_str(
+            r#"{"version": 1, "preferences": {"timezone": "UTC", "quiet_hours": null}}"#,
+        );
+        assert!(export.is_ok_and(|export| export.notifications.stale_review_days.is_none()));
+
+        let export: Result<SettingsExport, _> =
+            serde_json::from_str(r#"{"version": 1, "preferences": {}, "themes": []}"#);
+        assert!(export.is_err());
+    }
+}
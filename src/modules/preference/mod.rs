@@ -0,0 +1,9 @@
+//! # `Preference` Mod
+//! Preference imports for the timezone, quiet hours and formatting of the users
+
+pub mod interfaces;
+pub mod repository;
+pub mod routes;
+pub mod service;
+
+pub use routes::preference_routes;
@@ -0,0 +1,149 @@
+//! # `Preference` Repository
+//! This module defines the preference repository for the user preferences operations.
+
+use sqlx::{Error, Pool, Postgres};
+
+use crate::database::retry_transient;
+use crate::modules::{
+    common::to_db_id,
+    preference::interfaces::{PreferenceValues, PreferencesRow},
+};
+use crate::telemetry::observe_query;
+
+const PREFERENCE_COLUMNS: &str =
+    "timezone, quiet_start, quiet_end, locale, week_start, time_format, updated_at";
+
+// Upsert of all the preferences of a user, binding the user id then `PreferenceValues`
+const UPSERT_PREFERENCES: &str = "INSERT INTO user_preferences
+        (user_id, timezone, quiet_start, quiet_end, locale, week_start, time_format)
+     VALUES ($1, $2, $3, $4, $5, $6, $7)
+     ON CONFLICT (user_id) DO UPDATE SET
+        timezone = EXCLUDED.timezone,
+        quiet_start = EXCLUDED.quiet_start,
+        quiet_end = EXCLUDED.quiet_end,
+        locale = EXCLUDED.locale,
+        week_start = EXCLUDED.week_start,
+        time_format = EXCLUDED.time_format,
+        updated_at = NOW()";
+
+pub struct PreferenceRepository {
+    pool: Pool<Postgres>,
+}
+
+impl PreferenceRepository {
+    // Constructor
+    pub const fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Check if the database knows a timezone
+    pub async fn is_timezone(&self, timezone: &str) -> Result<bool, Error> {
+        observe_query("preference.is_timezone", async move {
+            sqlx::query_scalar::<_, bool>(
+                "SELECT EXISTS(SELECT 1 FROM pg_timezone_names WHERE name = $1)",
+            )
+            .bind(timezone)
+            .fetch_one(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Preferences of the user, `None` when never set
+    pub async fn fetch_preferences(&self, user_id: i64) -> Result<Option<PreferencesRow>, Error> {
+        retry_transient("preference.fetch_preferences", move || async move {
+            sqlx::query_as::<_, PreferencesRow>(&format!(
+                "SELECT {PREFERENCE_COLUMNS} FROM user_preferences WHERE user_id = $1"
+            ))
+            .bind(to_db_id(user_id)?)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Days of the stale todos review the user is subscribed to
+    pub async fn fetch_stale_review_days(&self, user_id: i64) -> Result<Option<i32>, Error> {
+        observe_query("preference.fetch_stale_review_days", async move {
+            sqlx::query_scalar::<_, i32>(
+                "SELECT days FROM stale_report_subscriptions WHERE user_id = $1",
+            )
+            .bind(to_db_id(user_id)?)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Replace the preferences and the stale todos review of the user at once, `None`
+    // days unsubscribe it
+    pub async fn import_settings(
+        &self,
+        user_id: i64,
+        preferences: &PreferenceValues,
+        stale_review_days: Option<i32>,
+    ) -> Result<(), Error> {
+        retry_transient("preference.import_settings", move || async move {
+            let user_id = to_db_id(user_id)?;
+            let (quiet_start, quiet_end) = preferences.quiet_hours.unzip();
+            let mut tx = self.pool.begin().await?;
+
+            sqlx::query(UPSERT_PREFERENCES)
+                .bind(user_id)
+                .bind(&preferences.timezone)
+                .bind(quiet_start)
+                .bind(quiet_end)
+                .bind(&preferences.locale)
+                .bind(&preferences.week_start)
+                .bind(&preferences.time_format)
+                .execute(&mut *tx)
+                .await?;
+
+            match stale_review_days {
+                Some(days) => {
+                    sqlx::query(
+                        "INSERT INTO stale_report_subscriptions (user_id, days) VALUES ($1, $2)
+                         ON CONFLICT (user_id) DO UPDATE SET days = EXCLUDED.days",
+                    )
+                    .bind(user_id)
+                    .bind(days)
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                None => {
+                    sqlx::query("DELETE FROM stale_report_subscriptions WHERE user_id = $1")
+                        .bind(user_id)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+            }
+
+            tx.commit().await
+        })
+        .await
+    }
+
+    // Replace the preferences of the user
+    pub async fn upsert_preferences(
+        &self,
+        user_id: i64,
+        preferences: &PreferenceValues,
+    ) -> Result<PreferencesRow, Error> {
+        retry_transient("preference.upsert_preferences", move || async move {
+            let (quiet_start, quiet_end) = preferences.quiet_hours.unzip();
+            sqlx::query_as::<_, PreferencesRow>(&format!(
+                "{UPSERT_PREFERENCES} RETURNING {PREFERENCE_COLUMNS}"
+            ))
+            .bind(to_db_id(user_id)?)
+            .bind(&preferences.timezone)
+            .bind(quiet_start)
+            .bind(quiet_end)
+            .bind(&preferences.locale)
+            .bind(&preferences.week_start)
+            .bind(&preferences.time_format)
+            .fetch_one(&self.pool)
+            .await
+        })
+        .await
+    }
+}
@@ -0,0 +1,187 @@
+//! #`Preference` Routes
+//! This module defines the HTTP routes for the user preferences.
+
+use axum::routing::{get, post};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Router,
+};
+
+use crate::auth::Claims;
+use crate::modules::common::{respond, respond_cached, CacheValidators, ErrorResponse};
+use crate::modules::preference::interfaces::{
+    FormatSettingsResponse, PreferencesResponse, SettingsExport, UpdatePreferencesRequest,
+};
+use crate::modules::preference::repository::PreferenceRepository;
+use crate::modules::preference::service::PreferenceService;
+use crate::utils::json::Json;
+use crate::AppState;
+
+// Creates and returns the preference routes
+pub fn preference_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/preferences",
+            get(fetch_preferences_route).put(update_preferences_route),
+        )
+        .route("/preferences/export", get(export_settings_route))
+        .route("/preferences/import", post(import_settings_route))
+        .route("/user/format-settings", get(format_settings_route))
+}
+
+fn preference_service(app_state: &AppState) -> PreferenceService {
+    PreferenceService::new(PreferenceRepository::new(app_state.db_pool.clone()))
+}
+
+// Fetch Preferences Route
+#[utoipa::path(
+    get,
+    path = "/preferences",
+    tag = "Preferences",
+    params(
+        ("If-None-Match" = Option<String>, Header, description = "ETag of the cached preferences, answered with 304 while current")
+    ),
+    responses(
+        (status = 200, description = "Preferences of the user", body = PreferencesResponse,
+            headers(
+                ("ETag" = String, description = "Digest of the preferences"),
+                ("Cache-Control" = String, description = "private, no-cache")
+            )),
+        (status = 304, description = "Cached preferences still current"),
+        (status = 500, description = "Failed to fetch preferences", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn fetch_preferences_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    respond_cached(
+        &headers,
+        preference_service(&app_state)
+            .fetch_preferences(claims.user_id)
+            .await,
+        |_| CacheValidators::default(),
+    )
+}
+
+// Update Preferences Route
+#[utoipa::path(
+    put,
+    path = "/preferences",
+    tag = "Preferences",
+    request_body = UpdatePreferencesRequest,
+    responses(
+        (status = 200, description = "Preferences updated", body = PreferencesResponse),
+        (status = 400, description = "Invalid timezone or quiet hours", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn update_preferences_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Json(update_request): Json<UpdatePreferencesRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        preference_service(&app_state)
+            .update_preferences(claims.user_id, update_request)
+            .await,
+    )
+}
+
+// Format Settings Route
+#[utoipa::path(
+    get,
+    path = "/user/format-settings",
+    tag = "Preferences",
+    responses(
+        (status = 200, description = "Locale, week start, clock and patterns to format dates and numbers for the user", body = FormatSettingsResponse),
+        (status = 500, description = "Failed to fetch preferences", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn format_settings_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        preference_service(&app_state)
+            .format_settings(claims.user_id)
+            .await,
+    )
+}
+
+// Export Settings Route
+#[utoipa::path(
+    get,
+    path = "/preferences/export",
+    tag = "Preferences",
+    responses(
+        (status = 200, description = "Portable preferences and notification settings of the user", body = SettingsExport),
+        (status = 500, description = "Failed to export settings", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn export_settings_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        preference_service(&app_state)
+            .export_settings(claims.user_id)
+            .await,
+    )
+}
+
+// Import Settings Route
+#[utoipa::path(
+    post,
+    path = "/preferences/import",
+    tag = "Preferences",
+    request_body = SettingsExport,
+    responses(
+        (status = 200, description = "Settings imported, returns the settings now in place", body = SettingsExport),
+        (status = 400, description = "Unsupported version, invalid timezone, quiet hours or review days", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn import_settings_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Json(export): Json<SettingsExport>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        preference_service(&app_state)
+            .import_settings(claims.user_id, export)
+            .await,
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preference_routes_creation() {
+        let _routes = preference_routes();
+        assert!(true);
+    }
+}
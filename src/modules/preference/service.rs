@@ -0,0 +1,299 @@
+//! # `Preference` Service
+//!
+//! This module contains the bussiness logic for the user preferences, and their export
+//! and import between accounts.
+
+use time::{macros::format_description, OffsetDateTime, Time};
+
+use crate::modules::{
+    common::AppError,
+    preference::{
+        interfaces::{
+            FormatSettingsResponse, NotificationSettings, PreferenceValues, PreferencesResponse,
+            QuietHours, SettingsExport, UpdatePreferencesRequest, DEFAULT_TIMEZONE,
+            SETTINGS_VERSION,
+        },
+        repository::PreferenceRepository,
+    },
+    report::service::stale_days,
+};
+use crate::utils::format::{locale_rules, locale_tags, TimeFormat, WeekStart, DEFAULT_LOCALE};
+
+// Parse a time of day of the quiet hours, `HH:MM`
+fn parse_quiet_time(value: &str, field: &str) -> Result<Time, AppError> {
+    Time::parse(value.trim(), format_description!("[hour]:[minute]")).map_err(|_| {
+        AppError::Validation(format!(
+            "Quiet hours {field} must be a time as HH:MM, e.g. 22:00"
+        ))
+    })
+}
+
+// Parse the quiet hours window, it can't be empty
+fn parse_quiet_hours(quiet_hours: &QuietHours) -> Result<(Time, Time), AppError> {
+    let start = parse_quiet_time(&quiet_hours.start, "start")?;
+    let end = parse_quiet_time(&quiet_hours.end, "end")?;
+    if start == end {
+        return Err(AppError::Validation(
+            "Quiet hours must not start and end at the same time".to_string(),
+        ));
+    }
+    Ok((start, end))
+}
+
+// Validate the preferences of a request, all but the timezone which the database knows
+fn preference_values(request: &UpdatePreferencesRequest) -> Result<PreferenceValues, AppError> {
+    let quiet_hours = request
+        .quiet_hours
+        .as_ref()
+        .map(parse_quiet_hours)
+        .transpose()?;
+    let locale = match request.locale.as_deref() {
+        None => DEFAULT_LOCALE,
+        Some(locale) => locale_rules(locale).map(|rules| rules.tag).ok_or_else(|| {
+            AppError::Validation(format!("Locale must be one of {}", locale_tags()))
+        })?,
+    };
+    let week_start = request
+        .week_start
+        .as_deref()
+        .map(|value| {
+            WeekStart::parse(value).ok_or_else(|| {
+                AppError::Validation("Week start must be saturday, sunday or monday".to_string())
+            })
+        })
+        .transpose()?;
+    let time_format = request
+        .time_format
+        .as_deref()
+        .map(|value| {
+            TimeFormat::parse(value)
+                .ok_or_else(|| AppError::Validation("Time format must be 12h or 24h".to_string()))
+        })
+        .transpose()?;
+
+    Ok(PreferenceValues {
+        timezone: request
+            .timezone
+            .as_deref()
+            .map_or(DEFAULT_TIMEZONE, str::trim)
+            .to_string(),
+        quiet_hours,
+        locale: locale.to_string(),
+        week_start: week_start.map(|day| day.as_str().to_string()),
+        time_format: time_format.map(|format| format.as_str().to_string()),
+    })
+}
+
+// Check the version of an imported export
+fn check_version(version: u32) -> Result<(), AppError> {
+    if version == SETTINGS_VERSION {
+        Ok(())
+    } else {
+        Err(AppError::Validation(format!(
+            "Unsupported settings version {version}, expected {SETTINGS_VERSION}"
+        )))
+    }
+}
+
+pub struct PreferenceService {
+    preference_repository: PreferenceRepository,
+}
+
+impl PreferenceService {
+    pub const fn new(preference_repository: PreferenceRepository) -> Self {
+        Self {
+            preference_repository,
+        }
+    }
+
+    // Preferences of the user, the defaults when never set
+    pub async fn fetch_preferences(&self, user_id: i64) -> Result<PreferencesResponse, AppError> {
+        match self.preference_repository.fetch_preferences(user_id).await {
+            Ok(preferences) => Ok(preferences
+                .map(PreferencesResponse::from)
+                .unwrap_or_default()),
+            Err(e) => {
+                tracing::warn!("Error fetching preferences: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Replace the preferences of the user
+    pub async fn update_preferences(
+        &self,
+        user_id: i64,
+        update_request: UpdatePreferencesRequest,
+    ) -> Result<PreferencesResponse, AppError> {
+        let preferences = preference_values(&update_request)?;
+        self.check_timezone(&preferences.timezone).await?;
+
+        match self
+            .preference_repository
+            .upsert_preferences(user_id, &preferences)
+            .await
+        {
+            Ok(preferences) => Ok(PreferencesResponse::from(preferences)),
+            Err(e) => {
+                tracing::warn!("Error updating preferences: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Formatting of the dates, times and numbers of the user
+    pub async fn format_settings(&self, user_id: i64) -> Result<FormatSettingsResponse, AppError> {
+        self.fetch_preferences(user_id)
+            .await
+            .map(FormatSettingsResponse::from)
+    }
+
+    // Check that the database knows the timezone
+    async fn check_timezone(&self, timezone: &str) -> Result<(), AppError> {
+        match self.preference_repository.is_timezone(timezone).await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(AppError::Validation(
+                "Timezone must be an IANA timezone, e.g. Europe/Lisbon".to_string(),
+            )),
+            Err(e) => {
+                tracing::warn!("Error checking timezone: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Portable settings of the user
+    pub async fn export_settings(&self, user_id: i64) -> Result<SettingsExport, AppError> {
+        let preferences = self.fetch_preferences(user_id).await?;
+        let stale_review_days = match self
+            .preference_repository
+            .fetch_stale_review_days(user_id)
+            .await
+        {
+            Ok(days) => days.map(i64::from),
+            Err(e) => {
+                tracing::warn!("Error fetching stale review subscription: {}", e);
+                return Err(AppError::Database(e));
+            }
+        };
+
+        Ok(SettingsExport {
+            version: SETTINGS_VERSION,
+            exported_at: Some(OffsetDateTime::now_utc()),
+            preferences: UpdatePreferencesRequest {
+                timezone: Some(preferences.timezone),
+                quiet_hours: preferences.quiet_hours,
+                locale: Some(preferences.locale),
+                week_start: preferences.week_start,
+                time_format: preferences.time_format,
+            },
+            notifications: NotificationSettings { stale_review_days },
+        })
+    }
+
+    // Replace the settings of the user with an export. Everything is validated before
+    // anything is written, an invalid export changes nothing.
+    pub async fn import_settings(
+        &self,
+        user_id: i64,
+        export: SettingsExport,
+    ) -> Result<SettingsExport, AppError> {
+        check_version(export.version)?;
+        let preferences = preference_values(&export.preferences)?;
+        let stale_review_days = export
+            .notifications
+            .stale_review_days
+            .map(|days| stale_days(Some(days)))
+            .transpose()?
+            .map(|days| i32::try_from(days).unwrap_or(i32::MAX));
+        self.check_timezone(&preferences.timezone).await?;
+
+        if let Err(e) = self
+            .preference_repository
+            .import_settings(user_id, &preferences, stale_review_days)
+            .await
+        {
+            tracing::warn!("Error importing settings: {}", e);
+            return Err(AppError::Internal("Failed to import settings".to_string()));
+        }
+        self.export_settings(user_id).await
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use time::macros::time;
+
+    fn quiet_hours(start: &str, end: &str) -> QuietHours {
+        QuietHours {
+            start: start.to_string(),
+            end: end.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_quiet_hours() {
+        assert_eq!(
+            parse_quiet_hours(&quiet_hours("22:00", " 07:30 ")).ok(),
+            Some((time!(22:00), time!(7:30)))
+        );
+        assert!(parse_quiet_hours(&quiet_hours("9:00", "17:00")).is_err());
+        assert!(parse_quiet_hours(&quiet_hours("24:00", "07:00")).is_err());
+        assert!(parse_quiet_hours(&quiet_hours("22:00", "late")).is_err());
+        assert!(parse_quiet_hours(&quiet_hours("22:00", "22:00")).is_err());
+    }
+
+    #[test]
+    fn test_preference_values() {
+        let request = UpdatePreferencesRequest {
+            timezone: Some(" Europe/Lisbon ".to_string()),
+            quiet_hours: None,
+            locale: Some("pt-pt".to_string()),
+            week_start: Some("Sunday".to_string()),
+            time_format: Some("12H".to_string()),
+        };
+        let values = preference_values(&request).unwrap();
+        assert_eq!(values.timezone, "Europe/Lisbon");
+        assert_eq!(values.locale, "pt-PT");
+        assert_eq!(values.week_start.as_deref(), Some("sunday"));
+        assert_eq!(values.time_format.as_deref(), Some("12h"));
+
+        let defaults = preference_values(&UpdatePreferencesRequest {
+            timezone: None,
+            quiet_hours: None,
+            locale: None,
+            week_start: None,
+            time_format: None,
+        })
+        .unwrap();
+        assert_eq!(defaults.timezone, DEFAULT_TIMEZONE);
+        assert_eq!(defaults.locale, DEFAULT_LOCALE);
+        assert!(defaults.week_start.is_none());
+
+        for invalid in [
+            UpdatePreferencesRequest {
+                locale: Some("klingon".to_string()),
+                ..request.clone()
+            },
+            UpdatePreferencesRequest {
+                week_start: Some("friday".to_string()),
+                ..request.clone()
+            },
+            UpdatePreferencesRequest {
+                time_format: Some("am/pm".to_string()),
+                ..request
+            },
+        ] {
+            assert!(preference_values(&invalid).is_err());
+        }
+    }
+
+    #[test]
+    fn test_check_version() {
+        assert!(check_version(SETTINGS_VERSION).is_ok());
+        assert!(check_version(0).is_err());
+        assert!(check_version(SETTINGS_VERSION + 1).is_err());
+    }
+}
@@ -0,0 +1,243 @@
+//! # `Presence` Hub
+//! In-memory registry of the users viewing each list. A user stays present while one
+//! of their sockets is open and active within `PRESENCE_TTL`.
+//! Viewers also get the created and completed events of the todos of the list.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+use tokio::sync::broadcast;
+
+use crate::modules::{outbox::interfaces::OutboxMessage, presence::interfaces::PresenceEvent};
+
+// Sockets silent for longer are closed and their user leaves the list
+pub const PRESENCE_TTL: Duration = Duration::from_secs(30);
+
+// Lifetime of a typing indicator on the clients
+pub const TYPING_TTL: Duration = Duration::from_secs(5);
+
+// Events buffered per list, slow viewers skip older events
+const EVENT_BUFFER: usize = 64;
+
+struct ListRoom {
+    events: broadcast::Sender<PresenceEvent>,
+    // Open sockets per viewing user
+    viewers: BTreeMap<i64, usize>,
+}
+
+impl ListRoom {
+    fn publish(&self, event: PresenceEvent) {
+        // Sending only fails when nobody listens, which is fine for presence
+        if self.events.send(event).is_err() {
+            tracing::debug!("Presence event without viewers");
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct PresenceHub {
+    rooms: Arc<Mutex<HashMap<i64, ListRoom>>>,
+}
+
+impl PresenceHub {
+    fn rooms(&self) -> MutexGuard<'_, HashMap<i64, ListRoom>> {
+        self.rooms.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // Add a viewer socket to the list, returns its event stream and the current viewers
+    pub fn join(
+        &self,
+        list_id: i64,
+        user_id: i64,
+    ) -> (broadcast::Receiver<PresenceEvent>, Vec<i64>) {
+        let mut rooms = self.rooms();
+        let room = rooms.entry(list_id).or_insert_with(|| ListRoom {
+            events: broadcast::channel(EVENT_BUFFER).0,
+            viewers: BTreeMap::new(),
+        });
+
+        let sockets = room.viewers.entry(user_id).or_insert(0);
+        *sockets += 1;
+        if *sockets == 1 {
+            room.publish(PresenceEvent::Joined { user_id });
+        }
+
+        // Subscribed after the join event, viewers get the full list instead
+        let joined = (
+            room.events.subscribe(),
+            room.viewers.keys().copied().collect(),
+        );
+        drop(rooms);
+        joined
+    }
+
+    // Remove a viewer socket, the user leaves once all their sockets are closed
+    pub fn leave(&self, list_id: i64, user_id: i64) {
+        let mut rooms = self.rooms();
+        let Some(room) = rooms.get_mut(&list_id) else {
+            return;
+        };
+
+        if let Some(sockets) = room.viewers.get_mut(&user_id) {
+            *sockets -= 1;
+            if *sockets == 0 {
+                room.viewers.remove(&user_id);
+                room.publish(PresenceEvent::Left { user_id });
+            }
+        }
+
+        if room.viewers.is_empty() {
+            rooms.remove(&list_id);
+        }
+    }
+
+    // Tell the viewers of the list that the user is typing
+    pub fn typing(&self, list_id: i64, user_id: i64, todo_id: Option<i64>) {
+        let rooms = self.rooms();
+        let Some(room) = rooms.get(&list_id) else {
+            return;
+        };
+
+        if room.viewers.contains_key(&user_id) {
+            room.publish(PresenceEvent::Typing {
+                user_id,
+                todo_id,
+                expires_in_seconds: TYPING_TTL.as_secs(),
+            });
+        }
+        drop(rooms);
+    }
+
+    // Tell the viewers of the todo list about a domain event
+    pub fn todo_event(&self, message: &OutboxMessage) {
+        let (Some(list_id), Some(todo_id)) = (
+            message.data["list_id"].as_i64(),
+            message.data["id"].as_i64(),
+        ) else {
+            return;
+        };
+
+        if let Some(room) = self.rooms().get(&list_id) {
+            room.publish(PresenceEvent::Todo {
+                event: message.event_type.clone(),
+                todo_id,
+            });
+        }
+    }
+
+    // Forward the in-process domain events to the list viewers until the sender closes
+    pub async fn forward_events(self, mut events: broadcast::Receiver<OutboxMessage>) {
+        loop {
+            match events.recv().await {
+                Ok(message) => self.todo_event(&message),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::debug!("Presence skipped {} todo events", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn viewers(hub: &PresenceHub, list_id: i64) -> Vec<i64> {
+        hub.rooms()
+            .get(&list_id)
+            .map(|room| room.viewers.keys().copied().collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_join_and_leave_broadcast_presence() {
+        let hub = PresenceHub::default();
+        let (mut first, present) = hub.join(1, 10);
+        assert_eq!(present, vec![10]);
+
+        let (_second, present) = hub.join(1, 20);
+        assert_eq!(present, vec![10, 20]);
+        assert_eq!(
+            first.try_recv().unwrap(),
+            PresenceEvent::Joined { user_id: 20 }
+        );
+
+        hub.leave(1, 20);
+        assert_eq!(
+            first.try_recv().unwrap(),
+            PresenceEvent::Left { user_id: 20 }
+        );
+        assert_eq!(viewers(&hub, 1), vec![10]);
+
+        hub.leave(1, 10);
+        assert!(viewers(&hub, 1).is_empty());
+        assert!(hub.rooms().is_empty());
+    }
+
+    #[test]
+    fn test_user_stays_while_a_socket_is_open() {
+        let hub = PresenceHub::default();
+        let (mut watcher, _) = hub.join(1, 10);
+        let _tab = hub.join(1, 20);
+        let _other_tab = hub.join(1, 20);
+        assert_eq!(
+            watcher.try_recv().unwrap(),
+            PresenceEvent::Joined { user_id: 20 }
+        );
+
+        hub.leave(1, 20);
+        assert_eq!(viewers(&hub, 1), vec![10, 20]);
+        assert!(watcher.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_typing_requires_presence() {
+        let hub = PresenceHub::default();
+        let (mut watcher, _) = hub.join(1, 10);
+
+        hub.typing(1, 30, Some(5));
+        assert!(watcher.try_recv().is_err());
+
+        hub.typing(1, 10, Some(5));
+        assert_eq!(
+            watcher.try_recv().unwrap(),
+            PresenceEvent::Typing {
+                user_id: 10,
+                todo_id: Some(5),
+                expires_in_seconds: TYPING_TTL.as_secs(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_todo_events_reach_list_viewers() {
+        let hub = PresenceHub::default();
+        let (mut watcher, _) = hub.join(1, 10);
+        let message = |list_id: Option<i64>| OutboxMessage {
+            id: 4,
+            event_type: "todo.completed".to_string(),
+            user_id: 10,
+            workspace_id: 2,
+            occurred_at: time::OffsetDateTime::UNIX_EPOCH,
+            data: serde_json::json!({ "id": 7, "list_id": list_id }),
+        };
+
+        hub.todo_event(&message(Some(2)));
+        hub.todo_event(&message(None));
+        assert!(watcher.try_recv().is_err());
+
+        hub.todo_event(&message(Some(1)));
+        assert_eq!(
+            watcher.try_recv().unwrap(),
+            PresenceEvent::Todo {
+                event: "todo.completed".to_string(),
+                todo_id: 7,
+            }
+        );
+    }
+}
@@ -0,0 +1,71 @@
+//! # `Presence` Interfaces
+//! This module defines the messages exchanged on the live list sockets
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+// Event pushed to the viewers of a list
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PresenceEvent {
+    // Users viewing the list, sent once when the socket opens
+    Viewers {
+        user_ids: Vec<i64>,
+    },
+    // A user started viewing the list
+    Joined {
+        user_id: i64,
+    },
+    // A user closed the list or its presence expired
+    Left {
+        user_id: i64,
+    },
+    // A user is typing on a todo, clients hide it after `expires_in_seconds`
+    Typing {
+        user_id: i64,
+        todo_id: Option<i64>,
+        expires_in_seconds: u64,
+    },
+    // A todo of the list was created or completed, `event` is the outbox event type
+    Todo {
+        event: String,
+        todo_id: i64,
+    },
+}
+
+// Message sent by a viewer, any message keeps the presence alive
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PresenceCommand {
+    Heartbeat,
+    Typing { todo_id: Option<i64> },
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presence_event_serialization() {
+        let event = PresenceEvent::Typing {
+            user_id: 3,
+            todo_id: Some(7),
+            expires_in_seconds: 5,
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"type":"typing","user_id":3,"todo_id":7,"expires_in_seconds":5}"#
+        );
+    }
+
+    #[test]
+    fn test_presence_command_deserialization() {
+        let command: PresenceCommand = serde_json::from_str(r#"{"type":"heartbeat"}"#).unwrap();
+        assert_eq!(command, PresenceCommand::Heartbeat);
+
+        let command: PresenceCommand =
+            serde_json::from_str(r#"{"type":"typing","todo_id":null}"#).unwrap();
+        assert_eq!(command, PresenceCommand::Typing { todo_id: None });
+    }
+}
@@ -0,0 +1,9 @@
+//! # `Presence` Mod
+//! Presence imports for the presence module
+
+pub mod hub;
+pub mod interfaces;
+pub mod routes;
+pub mod service;
+
+pub use routes::presence_routes;
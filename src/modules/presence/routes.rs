@@ -0,0 +1,189 @@
+//! # `Presence` Routes
+//! This module defines the WebSocket routes broadcasting who is viewing a list.
+//!
+//! Viewers send JSON `PresenceCommand` messages and receive `PresenceEvent` messages.
+//! A socket that sends nothing, not even a ping, for `PRESENCE_TTL` is closed.
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, State,
+    },
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+use tokio::{sync::broadcast, time::Instant};
+
+use crate::auth::GuestClaims;
+use crate::modules::common::ErrorResponse;
+use crate::modules::invitation::repository::InvitationRepository;
+use crate::modules::list::repository::ListRepository;
+use crate::modules::presence::hub::{PresenceHub, PRESENCE_TTL};
+use crate::modules::presence::interfaces::{PresenceCommand, PresenceEvent};
+use crate::modules::presence::service::PresenceService;
+use crate::modules::workspace::context::WorkspaceContext;
+use crate::AppState;
+
+// Creates and returns the presence routes
+pub fn presence_routes() -> Router<AppState> {
+    Router::new()
+        .route("/lists/{id}/live", get(list_live_route))
+        .route("/guest/list/live", get(guest_list_live_route))
+}
+
+fn presence_service(app_state: &AppState) -> PresenceService {
+    PresenceService::new(
+        ListRepository::new(app_state.db_pool.clone()),
+        InvitationRepository::new(app_state.db_pool.clone()),
+    )
+}
+
+// List Live Route
+#[utoipa::path(
+    get,
+    path = "/lists/{id}/live",
+    tag = "Presence",
+    params(
+        ("id" = i64, Path, description = "List id")
+    ),
+    responses(
+        (status = 101, description = "WebSocket streaming presence events", body = PresenceEvent),
+        (status = 404, description = "List not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn list_live_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path(id): Path<i64>,
+    upgrade: WebSocketUpgrade,
+) -> impl IntoResponse {
+    match presence_service(&app_state)
+        .authorize_member(workspace.user_id, workspace.workspace_id, id)
+        .await
+    {
+        Ok(()) => upgrade
+            .on_upgrade(move |socket| {
+                serve_viewer(socket, app_state.presence, id, workspace.user_id)
+            })
+            .into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+// Guest List Live Route
+#[utoipa::path(
+    get,
+    path = "/guest/list/live",
+    tag = "Presence",
+    responses(
+        (status = 101, description = "WebSocket streaming presence events", body = PresenceEvent),
+        (status = 404, description = "List not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn guest_list_live_route(
+    State(app_state): State<AppState>,
+    claims: GuestClaims,
+    upgrade: WebSocketUpgrade,
+) -> impl IntoResponse {
+    match presence_service(&app_state).authorize_guest(&claims).await {
+        Ok(()) => upgrade
+            .on_upgrade(move |socket| {
+                serve_viewer(socket, app_state.presence, claims.list_id, claims.user_id)
+            })
+            .into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+// Keep the user present on the list for the lifetime of the socket
+async fn serve_viewer(mut socket: WebSocket, hub: PresenceHub, list_id: i64, user_id: i64) {
+    let (mut events, viewers) = hub.join(list_id, user_id);
+    tracing::debug!("User {} viewing list {}", user_id, list_id);
+
+    let snapshot = PresenceEvent::Viewers { user_ids: viewers };
+    if send_event(&mut socket, &snapshot).await {
+        relay_events(&mut socket, &hub, &mut events, list_id, user_id).await;
+    }
+
+    hub.leave(list_id, user_id);
+    tracing::debug!("User {} left list {}", user_id, list_id);
+}
+
+// Forward presence events to the socket and commands to the hub until the
+// socket closes or its presence expires
+async fn relay_events(
+    socket: &mut WebSocket,
+    hub: &PresenceHub,
+    events: &mut broadcast::Receiver<PresenceEvent>,
+    list_id: i64,
+    user_id: i64,
+) {
+    let mut last_seen = Instant::now();
+    let mut expiry = tokio::time::interval(PRESENCE_TTL / 3);
+
+    loop {
+        tokio::select! {
+            message = socket.recv() => {
+                let Some(Ok(message)) = message else {
+                    return;
+                };
+                last_seen = Instant::now();
+
+                match message {
+                    Message::Text(text) => match serde_json::from_str::<PresenceCommand>(text.as_str()) {
+                        Ok(PresenceCommand::Typing { todo_id }) => hub.typing(list_id, user_id, todo_id),
+                        Ok(PresenceCommand::Heartbeat) => {}
+                        Err(e) => tracing::debug!("Ignoring presence message: {}", e),
+                    },
+                    Message::Close(_) => return,
+                    // Pings are answered by axum and only refresh the presence
+                    _ => {}
+                }
+            }
+            event = events.recv() => match event {
+                Ok(event) => {
+                    if !send_event(socket, &event).await {
+                        return;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::debug!("Viewer of list {} skipped {} events", list_id, skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+            _ = expiry.tick() => {
+                if last_seen.elapsed() > PRESENCE_TTL {
+                    tracing::debug!("Presence of user {} on list {} expired", user_id, list_id);
+                    return;
+                }
+            }
+        }
+    }
+}
+
+// Send an event, returns whether the socket is still open
+async fn send_event(socket: &mut WebSocket, event: &PresenceEvent) -> bool {
+    let Ok(text) = serde_json::to_string(event) else {
+        return false;
+    };
+    socket.send(Message::Text(text.into())).await.is_ok()
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presence_routes_creation() {
+        let _routes = presence_routes();
+        assert!(true);
+    }
+}
@@ -0,0 +1,76 @@
+//! # `Presence` Service
+//!
+//! This module checks who may join the live view of a list.
+
+use crate::{
+    auth::GuestClaims,
+    modules::{
+        common::AppError, invitation::repository::InvitationRepository,
+        list::repository::ListRepository,
+    },
+};
+
+pub struct PresenceService {
+    list_repository: ListRepository,
+    invitation_repository: InvitationRepository,
+}
+
+impl PresenceService {
+    pub const fn new(
+        list_repository: ListRepository,
+        invitation_repository: InvitationRepository,
+    ) -> Self {
+        Self {
+            list_repository,
+            invitation_repository,
+        }
+    }
+
+    // Members may watch the lists of their workspace they own or that are shared with them
+    pub async fn authorize_member(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        list_id: i64,
+    ) -> Result<(), AppError> {
+        match self
+            .list_repository
+            .list_access(user_id, workspace_id, list_id)
+            .await
+        {
+            Ok(Some(_)) => Ok(()),
+            Ok(None) => Err(AppError::NotFound("List not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error checking list access: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Guests may watch the list they were invited to while the invitation is valid
+    pub async fn authorize_guest(&self, claims: &GuestClaims) -> Result<(), AppError> {
+        match self
+            .invitation_repository
+            .fetch_guest_list(claims.user_id, claims.list_id)
+            .await
+        {
+            Ok(Some(_)) => Ok(()),
+            Ok(None) => Err(AppError::NotFound("List not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error fetching guest list: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod tests {
+    use crate::modules::presence::hub::{PRESENCE_TTL, TYPING_TTL};
+
+    #[test]
+    fn test_typing_expires_before_presence() {
+        assert!(TYPING_TTL < PRESENCE_TTL);
+    }
+}
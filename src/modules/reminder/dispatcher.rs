@@ -0,0 +1,170 @@
+//! # Reminder Dispatcher
+//! Background dispatch of the due reminders to the configured notifiers. A single
+//! instance dispatches at a time so a reminder is not sent twice by two instances.
+
+use std::sync::Arc;
+
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    modules::reminder::{
+        interfaces::ReminderNotification, notifier::Notifier, repository::ReminderRepository,
+    },
+    workers::{run_exclusive, WorkerTask},
+};
+
+// Reminders read per scan
+const DISPATCH_BATCH: i64 = 100;
+
+// Failed dispatches before a reminder is given up
+const MAX_ATTEMPTS: i32 = 5;
+
+// Advisory lock taken by the instance dispatching
+const DISPATCH_LOCK: &str = "reminder_dispatch";
+
+// Send a reminder through every notifier, stopping at the first failure
+async fn notify(
+    notifiers: &[Arc<dyn Notifier>],
+    notification: &ReminderNotification,
+) -> Result<(), String> {
+    for notifier in notifiers {
+        notifier
+            .notify(notification)
+            .await
+            .map_err(|e| format!("{} notifier: {e}", notifier.name()))?;
+    }
+    Ok(())
+}
+
+// Dispatch the due reminders, returns how many were sent. A failed reminder is retried
+// on the next scan without holding back the others.
+async fn dispatch_due(
+    reminder_repository: &ReminderRepository,
+    notifiers: &[Arc<dyn Notifier>],
+) -> Result<usize, sqlx::Error> {
+    let cancelled = reminder_repository.cancel_closed().await?;
+    if cancelled > 0 {
+        tracing::debug!("Cancelled {} reminders of closed todos", cancelled);
+    }
+
+    let mut sent = 0;
+    for row in reminder_repository.list_due(DISPATCH_BATCH).await? {
+        let id = row.id;
+        match notify(notifiers, &ReminderNotification::from(row)).await {
+            Ok(()) => {
+                reminder_repository.mark_sent(id).await?;
+                sent += 1;
+            }
+            Err(e) => {
+                if reminder_repository
+                    .record_failure(id, &e, MAX_ATTEMPTS)
+                    .await?
+                {
+                    tracing::error!("Reminder {} failed for good: {}", id, e);
+                } else {
+                    tracing::warn!("Reminder {} not sent: {}", id, e);
+                }
+            }
+        }
+    }
+    Ok(sent)
+}
+
+// Worker scanning the due reminders every `poll_interval`. Database errors are logged
+// and retried on the next scan.
+pub fn reminder_worker(
+    pool: Pool<Postgres>,
+    reminder_repository: ReminderRepository,
+    notifiers: Vec<Arc<dyn Notifier>>,
+    poll_interval: std::time::Duration,
+) -> WorkerTask {
+    Box::pin(async move {
+        let mut interval = tokio::time::interval(poll_interval);
+        loop {
+            interval.tick().await;
+            let job = dispatch_due(&reminder_repository, &notifiers);
+            match run_exclusive(&pool, DISPATCH_LOCK, job).await {
+                Ok(Some(Ok(0)) | None) => {}
+                Ok(Some(Ok(sent))) => tracing::debug!("Sent {} reminders", sent),
+                Ok(Some(Err(e))) => tracing::warn!("Error dispatching reminders: {}", e),
+                Err(e) => tracing::warn!("Error locking reminder dispatch: {}", e),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+
+    use super::*;
+
+    struct RecordingNotifier {
+        fail: bool,
+        sent: Mutex<Vec<i64>>,
+    }
+
+    impl RecordingNotifier {
+        fn new(fail: bool) -> Arc<Self> {
+            Arc::new(Self {
+                fail,
+                sent: Mutex::new(Vec::new()),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl Notifier for RecordingNotifier {
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+
+        async fn notify(&self, notification: &ReminderNotification) -> Result<(), String> {
+            if self.fail {
+                return Err("unavailable".to_string());
+            }
+            self.sent.lock().unwrap().push(notification.reminder_id);
+            Ok(())
+        }
+    }
+
+    fn notification() -> ReminderNotification {
+        ReminderNotification {
+            reminder_id: 4,
+            todo_id: 9,
+            user_id: 1,
+            workspace_id: 2,
+            username: "ana".to_string(),
+            email: "ana@example.com".to_string(),
+            title: "Pay rent".to_string(),
+            due_at: None,
+            remind_at: time::OffsetDateTime::UNIX_EPOCH,
+            due_label: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_notify_reaches_every_notifier() {
+        let first = RecordingNotifier::new(false);
+        let second = RecordingNotifier::new(false);
+        let notifiers: Vec<Arc<dyn Notifier>> = vec![first.clone(), second.clone()];
+
+        notify(&notifiers, &notification()).await.unwrap();
+        assert_eq!(*first.sent.lock().unwrap(), vec![4]);
+        assert_eq!(*second.sent.lock().unwrap(), vec![4]);
+    }
+
+    #[tokio::test]
+    async fn test_notify_stops_at_failure() {
+        let failing = RecordingNotifier::new(true);
+        let after = RecordingNotifier::new(false);
+        let notifiers: Vec<Arc<dyn Notifier>> = vec![failing, after.clone()];
+
+        let error = notify(&notifiers, &notification()).await.unwrap_err();
+        assert_eq!(error, "recording notifier: unavailable");
+        assert!(after.sent.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,207 @@
+//! # `Reminder` Interfaces
+//! This module defines the data structures from Reminders module
+
+use serde::{Deserialize, Serialize};
+use time::{OffsetDateTime, PrimitiveDateTime};
+use utoipa::ToSchema;
+
+use crate::utils::{
+    dates::{rfc3339, rfc3339_option},
+    format::Formatter,
+};
+
+// Either `remind_at` or `before_due` is required
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct CreateReminderRequest {
+    // RFC 3339 date the reminder is sent at, e.g. 2025-01-31T09:00:00Z
+    pub remind_at: Option<String>,
+    // Time before the todo due date the reminder is sent at, e.g. `30 minutes` or
+    // `1 day`. The reminder follows the due date when it changes.
+    pub before_due: Option<String>,
+    // Critical reminders are sent during the quiet hours of the user, false by default
+    pub critical: Option<bool>,
+}
+
+// When a reminder is sent
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReminderTime {
+    // At a fixed date
+    At(OffsetDateTime),
+    // This many minutes before the todo due date, resolved at dispatch time
+    BeforeDue(i32),
+}
+
+// Reminder row as stored in database
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct ReminderRow {
+    pub id: i32,
+    pub todo_id: i32,
+    pub remind_at: Option<OffsetDateTime>,
+    pub offset_minutes: Option<i32>,
+    pub critical: bool,
+    pub status: String,
+    pub sent_at: Option<OffsetDateTime>,
+    pub created_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ReminderResponse {
+    pub id: i64,
+    pub todo_id: i64,
+    // Fixed date of the reminder, `null` for reminders relative to the due date
+    #[serde(with = "rfc3339_option")]
+    pub remind_at: Option<OffsetDateTime>,
+    // Minutes before the todo due date the reminder is sent at
+    pub before_due_minutes: Option<i64>,
+    // Sent during the quiet hours of the user
+    pub critical: bool,
+    // One of `pending`, `sent`, `cancelled` or `failed`
+    pub status: String,
+    #[serde(with = "rfc3339_option")]
+    pub sent_at: Option<OffsetDateTime>,
+    #[serde(with = "rfc3339_option")]
+    pub created_at: Option<OffsetDateTime>,
+    // Issues of the reminder that did not fail it, e.g. a time after the due date. Only
+    // set in the create response.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warnings: Option<Vec<String>>,
+}
+
+impl From<ReminderRow> for ReminderResponse {
+    fn from(row: ReminderRow) -> Self {
+        Self {
+            id: i64::from(row.id),
+            todo_id: i64::from(row.todo_id),
+            remind_at: row.remind_at,
+            before_due_minutes: row.offset_minutes.map(i64::from),
+            critical: row.critical,
+            status: row.status,
+            sent_at: row.sent_at,
+            created_at: row.created_at,
+            warnings: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ReminderMessageResponse {
+    pub message: String,
+}
+
+// Pending reminder whose time has come, with the todo it is about
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct DueReminderRow {
+    pub id: i32,
+    pub todo_id: i32,
+    pub user_id: i32,
+    pub workspace_id: i32,
+    pub username: String,
+    pub email: String,
+    pub title: String,
+    pub due_at: Option<OffsetDateTime>,
+    pub remind_at: OffsetDateTime,
+    // Formatting preferences of the user, with the due date in its timezone
+    pub locale: String,
+    pub time_format: Option<String>,
+    pub timezone: String,
+    pub local_due_at: Option<PrimitiveDateTime>,
+}
+
+// Reminder handed to the notifiers
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ReminderNotification {
+    pub reminder_id: i64,
+    pub todo_id: i64,
+    pub user_id: i64,
+    pub workspace_id: i64,
+    pub username: String,
+    // Only used to email the reminder, never sent to webhooks
+    #[serde(skip)]
+    pub email: String,
+    pub title: String,
+    #[serde(with = "rfc3339_option")]
+    pub due_at: Option<OffsetDateTime>,
+    #[serde(with = "rfc3339")]
+    pub remind_at: OffsetDateTime,
+    // Due date in the locale and timezone of the user, only used to email the reminder
+    #[serde(skip)]
+    pub due_label: Option<String>,
+}
+
+impl From<DueReminderRow> for ReminderNotification {
+    fn from(row: DueReminderRow) -> Self {
+        Self {
+            reminder_id: i64::from(row.id),
+            todo_id: i64::from(row.todo_id),
+            user_id: i64::from(row.user_id),
+            workspace_id: i64::from(row.workspace_id),
+            username: row.username,
+            email: row.email,
+            title: row.title,
+            due_at: row.due_at,
+            remind_at: row.remind_at,
+            due_label: row.local_due_at.map(|at| {
+                Formatter::new(&row.locale, row.time_format.as_deref())
+                    .zoned_datetime(at, &row.timezone)
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    #[test]
+    fn test_notification_from_due_reminder() {
+        let notification = ReminderNotification::from(DueReminderRow {
+            id: 3,
+            todo_id: 7,
+            user_id: 1,
+            workspace_id: 2,
+            username: "ana".to_string(),
+            email: "ana@example.com".to_string(),
+            title: "Pay rent".to_string(),
+            due_at: None,
+            remind_at: OffsetDateTime::UNIX_EPOCH,
+            locale: "en-US".to_string(),
+            time_format: None,
+            timezone: "UTC".to_string(),
+            local_due_at: None,
+        });
+
+        assert_eq!(notification.reminder_id, 3);
+        assert_eq!(notification.todo_id, 7);
+        assert_eq!(notification.workspace_id, 2);
+        assert!(notification.due_at.is_none());
+        assert!(notification.due_label.is_none());
+    }
+
+    #[test]
+    fn test_notification_due_label_follows_preferences() {
+        let notification = ReminderNotification::from(DueReminderRow {
+            id: 3,
+            todo_id: 7,
+            user_id: 1,
+            workspace_id: 2,
+            username: "ana".to_string(),
+            email: "ana@example.com".to_string(),
+            title: "Pay rent".to_string(),
+            due_at: Some(datetime!(2025-03-10 13:00 UTC)),
+            remind_at: OffsetDateTime::UNIX_EPOCH,
+            locale: "pt-BR".to_string(),
+            time_format: None,
+            timezone: "America/Sao_Paulo".to_string(),
+            local_due_at: Some(datetime!(2025-03-10 10:00)),
+        });
+
+        assert_eq!(
+            notification.due_label.as_deref(),
+            Some("10/03/2025 10:00 (America/Sao_Paulo)")
+        );
+        assert!(!serde_json::to_string(&notification)
+            .unwrap_or_default()
+            .contains("due_label"));
+    }
+}
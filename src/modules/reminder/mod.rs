@@ -0,0 +1,11 @@
+//! # `Reminder` Mod
+//! Reminder imports for the todo reminders and their dispatcher
+
+pub mod dispatcher;
+pub mod interfaces;
+pub mod notifier;
+pub mod repository;
+pub mod routes;
+pub mod service;
+
+pub use routes::reminder_routes;
@@ -0,0 +1,168 @@
+//! # Reminder Notifiers
+//! This module defines how due reminders reach their users, configured with the
+//! `REMINDER_*` variables.
+
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use reqwest::{Client, Url};
+
+use crate::modules::{
+    email::{mailer::Mailer, templates::REMINDER},
+    reminder::interfaces::ReminderNotification,
+};
+use crate::utils::dates::format_timestamp;
+
+const DEFAULT_WEBHOOK_TIMEOUT_SECONDS: u64 = 10;
+
+// Header carrying the reminder id, consumers use it to drop redeliveries
+const REMINDER_ID_HEADER: &str = "X-Reminder-Id";
+
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    // Name used in the logs
+    fn name(&self) -> &'static str;
+
+    // Send the reminder, an error has it retried later
+    async fn notify(&self, notification: &ReminderNotification) -> Result<(), String>;
+}
+
+// Reminders written to the application log, used when no other notifier is configured
+pub struct LogNotifier;
+
+#[async_trait]
+impl Notifier for LogNotifier {
+    fn name(&self) -> &'static str {
+        "log"
+    }
+
+    async fn notify(&self, notification: &ReminderNotification) -> Result<(), String> {
+        tracing::info!(
+            "Reminder {} for user {}: {}",
+            notification.reminder_id,
+            notification.user_id,
+            notification.title
+        );
+        Ok(())
+    }
+}
+
+// Reminders posted as JSON to an HTTP endpoint, any non 2xx answer is a failure
+pub struct WebhookNotifier {
+    client: Client,
+    url: Url,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: &str, timeout: Duration) -> Result<Self, String> {
+        let url = Url::parse(url).map_err(|e| format!("Invalid REMINDER_WEBHOOK_URL: {e}"))?;
+        let client = Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| format!("Failed to create webhook client: {e}"))?;
+        Ok(Self { client, url })
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    async fn notify(&self, notification: &ReminderNotification) -> Result<(), String> {
+        self.client
+            .post(self.url.clone())
+            .header(REMINDER_ID_HEADER, notification.reminder_id.to_string())
+            .json(notification)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+// Reminders emailed to the user owning them
+pub struct EmailNotifier {
+    mailer: Arc<dyn Mailer>,
+}
+
+impl EmailNotifier {
+    pub fn new(mailer: Arc<dyn Mailer>) -> Self {
+        Self { mailer }
+    }
+}
+
+#[async_trait]
+impl Notifier for EmailNotifier {
+    fn name(&self) -> &'static str {
+        "email"
+    }
+
+    async fn notify(&self, notification: &ReminderNotification) -> Result<(), String> {
+        let due_at = notification
+            .due_label
+            .clone()
+            .or_else(|| {
+                notification
+                    .due_at
+                    .and_then(|due_at| format_timestamp(due_at).ok())
+            })
+            .unwrap_or_else(|| "no due date".to_string());
+        let message = REMINDER.render(
+            &notification.email,
+            &[
+                ("username", notification.username.as_str()),
+                ("title", notification.title.as_str()),
+                ("due_at", due_at.as_str()),
+            ],
+        );
+        self.mailer.send(&message).await
+    }
+}
+
+// Notifiers configured in the environment: the webhook when `REMINDER_WEBHOOK_URL` is
+// set, the email when `REMINDER_EMAIL_ENABLED` is true, and the log when neither is
+pub fn notifiers_from_env(mailer: Arc<dyn Mailer>) -> Result<Vec<Arc<dyn Notifier>>, String> {
+    let mut notifiers: Vec<Arc<dyn Notifier>> = Vec::new();
+
+    if let Some(url) = std::env::var("REMINDER_WEBHOOK_URL")
+        .ok()
+        .filter(|url| !url.trim().is_empty())
+    {
+        let timeout = std::env::var("REMINDER_WEBHOOK_TIMEOUT_SECONDS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_WEBHOOK_TIMEOUT_SECONDS);
+        notifiers.push(Arc::new(WebhookNotifier::new(
+            url.trim(),
+            Duration::from_secs(timeout),
+        )?));
+    }
+    if std::env::var("REMINDER_EMAIL_ENABLED")
+        .ok()
+        .and_then(|v| v.parse::<bool>().ok())
+        .unwrap_or(false)
+    {
+        notifiers.push(Arc::new(EmailNotifier::new(mailer)));
+    }
+    if notifiers.is_empty() {
+        notifiers.push(Arc::new(LogNotifier));
+    }
+
+    Ok(notifiers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_webhook_requires_valid_url() {
+        assert!(WebhookNotifier::new("not a url", Duration::from_secs(1)).is_err());
+        assert!(
+            WebhookNotifier::new("https://example.com/reminders", Duration::from_secs(1)).is_ok()
+        );
+    }
+}
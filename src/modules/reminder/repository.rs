@@ -0,0 +1,281 @@
+//! # `Reminder` Repository
+//! This module defines the reminder repository for the reminder endpoints and dispatcher.
+
+use sqlx::{Error, Pool, Postgres};
+use time::OffsetDateTime;
+
+use crate::modules::{
+    common::to_db_id,
+    preference::interfaces::DEFAULT_TIMEZONE,
+    reminder::interfaces::{DueReminderRow, ReminderRow, ReminderTime},
+};
+use crate::telemetry::observe_query;
+use crate::utils::format::DEFAULT_LOCALE;
+
+const REMINDER_COLUMNS: &str =
+    "id, todo_id, remind_at, offset_minutes, critical, status, sent_at, created_at";
+
+// Time a reminder `r` of the todo `t` is due, reminders relative to the due date follow
+// it and are never due while the todo has no due date
+const REMIND_AT: &str = "COALESCE(r.remind_at, t.due_at - make_interval(mins => r.offset_minutes))";
+
+// SQL condition matching reminders `r` held by the quiet hours of their user, read from
+// the preferences `p` in the user timezone. The window wraps around midnight when it ends
+// before it starts.
+const HELD_BY_QUIET_HOURS: &str = "NOT r.critical AND p.quiet_start IS NOT NULL AND CASE
+    WHEN p.quiet_start <= p.quiet_end THEN
+        (NOW() AT TIME ZONE p.timezone)::TIME >= p.quiet_start
+        AND (NOW() AT TIME ZONE p.timezone)::TIME < p.quiet_end
+    ELSE
+        (NOW() AT TIME ZONE p.timezone)::TIME >= p.quiet_start
+        OR (NOW() AT TIME ZONE p.timezone)::TIME < p.quiet_end
+    END";
+
+// SQL condition matching reminders whose todo is still open
+const OPEN_TODO: &str = "EXISTS(
+    SELECT 1 FROM todos t
+    WHERE t.id = r.todo_id AND t.deleted_at IS NULL
+      AND t.status IN ('backlog', 'in_progress', 'blocked'))";
+
+pub struct ReminderRepository {
+    pool: Pool<Postgres>,
+}
+
+impl ReminderRepository {
+    // Constructor
+    pub const fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Check if a todo of the workspace is owned by the user
+    pub async fn is_todo_owner(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+    ) -> Result<bool, Error> {
+        observe_query("reminder.is_todo_owner", async move {
+            sqlx::query_scalar::<_, bool>(
+                "SELECT EXISTS(
+                    SELECT 1 FROM todos
+                    WHERE id = $1 AND user_id = $2 AND workspace_id = $3 AND deleted_at IS NULL)",
+            )
+            .bind(to_db_id(todo_id)?)
+            .bind(to_db_id(user_id)?)
+            .bind(to_db_id(workspace_id)?)
+            .fetch_one(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Due date of a todo
+    pub async fn todo_due_at(&self, todo_id: i64) -> Result<Option<OffsetDateTime>, Error> {
+        observe_query("reminder.todo_due_at", async move {
+            sqlx::query_scalar::<_, Option<OffsetDateTime>>(
+                "SELECT due_at FROM todos WHERE id = $1",
+            )
+            .bind(to_db_id(todo_id)?)
+            .fetch_optional(&self.pool)
+            .await
+            .map(Option::flatten)
+        })
+        .await
+    }
+
+    // Add a reminder to a todo, returns `None` when the todo is not owned
+    pub async fn create_reminder(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+        time: ReminderTime,
+        critical: bool,
+    ) -> Result<Option<ReminderRow>, Error> {
+        observe_query("reminder.create_reminder", async move {
+            let (remind_at, offset_minutes) = match time {
+                ReminderTime::At(remind_at) => (Some(remind_at), None),
+                ReminderTime::BeforeDue(minutes) => (None, Some(minutes)),
+            };
+            let query = format!(
+                "INSERT INTO reminders (todo_id, user_id, remind_at, offset_minutes, critical)
+                 SELECT t.id, t.user_id, $3, $5, $6
+                 FROM todos t
+                 WHERE t.id = $1 AND t.user_id = $2 AND t.workspace_id = $4 AND t.deleted_at IS NULL
+                 RETURNING {REMINDER_COLUMNS}"
+            );
+
+            sqlx::query_as::<_, ReminderRow>(&query)
+                .bind(to_db_id(todo_id)?)
+                .bind(to_db_id(user_id)?)
+                .bind(remind_at)
+                .bind(to_db_id(workspace_id)?)
+                .bind(offset_minutes)
+                .bind(critical)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // List the reminders of a todo by time
+    pub async fn list_reminders(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+    ) -> Result<Vec<ReminderRow>, Error> {
+        observe_query("reminder.list_reminders", async move {
+            let query = format!(
+                "SELECT {REMINDER_COLUMNS} FROM reminders
+                 WHERE todo_id = $1 AND user_id = $2
+                   AND EXISTS(
+                        SELECT 1 FROM todos
+                        WHERE id = $1 AND user_id = $2 AND workspace_id = $3 AND deleted_at IS NULL)
+                 ORDER BY remind_at NULLS LAST, offset_minutes DESC, id"
+            );
+
+            sqlx::query_as::<_, ReminderRow>(&query)
+                .bind(to_db_id(todo_id)?)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Cancel a pending reminder, returns `None` when it is not found or no longer pending
+    pub async fn cancel_reminder(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+        id: i64,
+    ) -> Result<Option<ReminderRow>, Error> {
+        observe_query("reminder.cancel_reminder", async move {
+            let query = format!(
+                "UPDATE reminders SET status = 'cancelled'
+                 WHERE id = $1 AND todo_id = $2 AND user_id = $3 AND status = 'pending'
+                   AND EXISTS(
+                        SELECT 1 FROM todos
+                        WHERE id = $2 AND user_id = $3 AND workspace_id = $4 AND deleted_at IS NULL)
+                 RETURNING {REMINDER_COLUMNS}"
+            );
+
+            sqlx::query_as::<_, ReminderRow>(&query)
+                .bind(to_db_id(id)?)
+                .bind(to_db_id(todo_id)?)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Cancel the due reminders of todos done or deleted in the meantime, returns how
+    // many were cancelled
+    pub async fn cancel_closed(&self) -> Result<u64, Error> {
+        observe_query("reminder.cancel_closed", async move {
+            let query = format!(
+                "UPDATE reminders r SET status = 'cancelled'
+                 FROM todos t
+                 WHERE t.id = r.todo_id AND r.status = 'pending' AND {REMIND_AT} <= NOW()
+                   AND NOT {OPEN_TODO}"
+            );
+
+            let result = sqlx::query(&query).execute(&self.pool).await?;
+            Ok(result.rows_affected())
+        })
+        .await
+    }
+
+    // List the oldest pending reminders whose time has come, holding back the ones in
+    // the quiet hours of their user until the window ends
+    pub async fn list_due(&self, limit: i64) -> Result<Vec<DueReminderRow>, Error> {
+        observe_query("reminder.list_due", async move {
+            let query = format!(
+                "SELECT r.id, r.todo_id, r.user_id, t.workspace_id, u.username, u.email, t.title,
+                    t.due_at, {REMIND_AT} AS remind_at,
+                    COALESCE(p.locale, '{DEFAULT_LOCALE}') AS locale, p.time_format,
+                    COALESCE(p.timezone, '{DEFAULT_TIMEZONE}') AS timezone,
+                    t.due_at AT TIME ZONE COALESCE(p.timezone, '{DEFAULT_TIMEZONE}')
+                        AS local_due_at
+                 FROM reminders r
+                 JOIN todos t ON t.id = r.todo_id
+                 JOIN users u ON u.id = r.user_id
+                 LEFT JOIN user_preferences p ON p.user_id = r.user_id
+                 WHERE r.status = 'pending' AND {REMIND_AT} <= NOW() AND {OPEN_TODO}
+                   AND NOT COALESCE({HELD_BY_QUIET_HOURS}, FALSE)
+                 ORDER BY {REMIND_AT}, r.id
+                 LIMIT $1"
+            );
+
+            sqlx::query_as::<_, DueReminderRow>(&query)
+                .bind(limit)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Mark a reminder sent by every notifier
+    pub async fn mark_sent(&self, id: i32) -> Result<(), Error> {
+        observe_query("reminder.mark_sent", async move {
+            sqlx::query("UPDATE reminders SET status = 'sent', sent_at = NOW() WHERE id = $1")
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    // Record a failed dispatch. After `max_attempts` the reminder is marked failed,
+    // returns whether that happened.
+    pub async fn record_failure(
+        &self,
+        id: i32,
+        error: &str,
+        max_attempts: i32,
+    ) -> Result<bool, Error> {
+        observe_query("reminder.record_failure", async move {
+            sqlx::query_scalar::<_, bool>(
+                "UPDATE reminders SET
+                    attempts = attempts + 1,
+                    last_error = $2,
+                    status = CASE WHEN attempts + 1 >= $3 THEN 'failed' ELSE status END
+                 WHERE id = $1
+                 RETURNING status = 'failed'",
+            )
+            .bind(id)
+            .bind(error)
+            .bind(max_attempts)
+            .fetch_one(&self.pool)
+            .await
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reminder_columns_match_row() {
+        for column in [
+            "id",
+            "todo_id",
+            "remind_at",
+            "offset_minutes",
+            "critical",
+            "status",
+            "sent_at",
+            "created_at",
+        ] {
+            assert!(REMINDER_COLUMNS.contains(column));
+        }
+    }
+}
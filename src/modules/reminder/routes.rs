@@ -0,0 +1,141 @@
+//! #`Reminder` Routes
+//! This module defines the HTTP routes for todo reminders.
+
+use axum::extract::Path;
+use axum::routing::{delete, get};
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Router};
+
+use crate::modules::common::{respond, ErrorResponse};
+use crate::modules::reminder::interfaces::{
+    CreateReminderRequest, ReminderMessageResponse, ReminderResponse,
+};
+use crate::modules::reminder::repository::ReminderRepository;
+use crate::modules::reminder::service::ReminderService;
+use crate::modules::workspace::context::WorkspaceContext;
+use crate::utils::json::Json;
+use crate::AppState;
+
+// Creates and returns the reminder routes
+pub fn reminder_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/todos/{id}/reminders",
+            get(list_reminders_route).post(create_reminder_route),
+        )
+        .route(
+            "/todos/{id}/reminders/{reminder_id}",
+            delete(cancel_reminder_route),
+        )
+}
+
+fn reminder_service(app_state: &AppState) -> ReminderService {
+    ReminderService::new(ReminderRepository::new(app_state.db_pool.clone()))
+}
+
+// Create Reminder Route
+#[utoipa::path(
+    post,
+    path = "/todos/{id}/reminders",
+    tag = "Reminders",
+    params(
+        ("id" = i64, Path, description = "Todo id")
+    ),
+    request_body = CreateReminderRequest,
+    responses(
+        (status = 201, description = "Reminder created successfully", body = ReminderResponse),
+        (status = 400, description = "Invalid reminder time", body = ErrorResponse),
+        (status = 404, description = "Todo not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn create_reminder_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path(id): Path<i64>,
+    Json(create_request): Json<CreateReminderRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::CREATED,
+        reminder_service(&app_state)
+            .create_reminder(
+                workspace.user_id,
+                workspace.workspace_id,
+                id,
+                create_request,
+            )
+            .await,
+    )
+}
+
+// List Reminders Route
+#[utoipa::path(
+    get,
+    path = "/todos/{id}/reminders",
+    tag = "Reminders",
+    params(
+        ("id" = i64, Path, description = "Todo id")
+    ),
+    responses(
+        (status = 200, description = "Reminders of the todo by time", body = [ReminderResponse]),
+        (status = 404, description = "Todo not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn list_reminders_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        reminder_service(&app_state)
+            .list_reminders(workspace.user_id, workspace.workspace_id, id)
+            .await,
+    )
+}
+
+// Cancel Reminder Route
+#[utoipa::path(
+    delete,
+    path = "/todos/{id}/reminders/{reminder_id}",
+    tag = "Reminders",
+    params(
+        ("id" = i64, Path, description = "Todo id"),
+        ("reminder_id" = i64, Path, description = "Reminder id")
+    ),
+    responses(
+        (status = 200, description = "Reminder cancelled successfully", body = ReminderMessageResponse),
+        (status = 404, description = "Pending reminder not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn cancel_reminder_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path((id, reminder_id)): Path<(i64, i64)>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        reminder_service(&app_state)
+            .cancel_reminder(workspace.user_id, workspace.workspace_id, id, reminder_id)
+            .await,
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reminder_routes_creation() {
+        let _routes = reminder_routes();
+        assert!(true);
+    }
+}
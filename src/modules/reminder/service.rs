@@ -0,0 +1,286 @@
+//! # `Reminder` Service
+//!
+//! This module contains the bussiness logic for todo reminders.
+
+use time::{format_description::well_known::Rfc3339, Duration, OffsetDateTime};
+
+use crate::modules::{
+    common::AppError,
+    reminder::{
+        interfaces::{
+            CreateReminderRequest, ReminderMessageResponse, ReminderResponse, ReminderTime,
+        },
+        repository::ReminderRepository,
+    },
+};
+
+// Furthest a reminder can be set before the due date, a year
+const MAX_BEFORE_DUE_MINUTES: i64 = 365 * 24 * 60;
+
+// Parse the time of a reminder, it must be in the future
+fn parse_remind_at(remind_at: &str, now: OffsetDateTime) -> Result<OffsetDateTime, AppError> {
+    let remind_at = OffsetDateTime::parse(remind_at.trim(), &Rfc3339).map_err(|_| {
+        AppError::Validation(
+            "Reminder date must be a RFC 3339 date, e.g. 2025-01-31T09:00:00Z".to_string(),
+        )
+    })?;
+    if remind_at <= now {
+        return Err(AppError::Validation(
+            "Reminder date must be in the future".to_string(),
+        ));
+    }
+    Ok(remind_at)
+}
+
+// Parse the time before the due date of a relative reminder, e.g. `30 minutes`, `2h` or
+// `1 day before due`, into minutes
+fn parse_before_due(before_due: &str) -> Result<i32, AppError> {
+    let invalid = || {
+        AppError::Validation(
+            "Time before due must be a number of minutes, hours, days or weeks, e.g. 30 minutes"
+                .to_string(),
+        )
+    };
+
+    let before_due = before_due.trim().to_lowercase();
+    let before_due = before_due
+        .strip_suffix("before due")
+        .or_else(|| before_due.strip_suffix("before"))
+        .unwrap_or(&before_due)
+        .trim();
+    let split = before_due
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(before_due.len());
+    let (amount, unit) = before_due.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    let unit_minutes = match unit.trim() {
+        "m" | "min" | "mins" | "minute" | "minutes" => 1,
+        "h" | "hour" | "hours" => 60,
+        "d" | "day" | "days" => 24 * 60,
+        "w" | "week" | "weeks" => 7 * 24 * 60,
+        _ => return Err(invalid()),
+    };
+
+    let minutes = amount
+        .checked_mul(unit_minutes)
+        .filter(|minutes| *minutes <= MAX_BEFORE_DUE_MINUTES)
+        .ok_or_else(|| {
+            AppError::Validation("Time before due must not exceed a year".to_string())
+        })?;
+    i32::try_from(minutes).map_err(|_| invalid())
+}
+
+// Warnings of a new reminder from the due date of its todo
+fn reminder_warnings(
+    time: ReminderTime,
+    due_at: Option<OffsetDateTime>,
+    now: OffsetDateTime,
+) -> Vec<String> {
+    let warning = match (time, due_at) {
+        (ReminderTime::BeforeDue(_), None) => {
+            Some("The todo has no due date, the reminder is sent once it gets one")
+        }
+        (ReminderTime::BeforeDue(minutes), Some(due_at))
+            if due_at - Duration::minutes(i64::from(minutes)) <= now =>
+        {
+            Some("The reminder time has already passed, it is sent right away")
+        }
+        (ReminderTime::At(remind_at), Some(due_at)) if remind_at > due_at => {
+            Some("The reminder is sent after the todo due date")
+        }
+        _ => None,
+    };
+    warning.map(str::to_string).into_iter().collect()
+}
+
+pub struct ReminderService {
+    reminder_repository: ReminderRepository,
+}
+
+impl ReminderService {
+    pub const fn new(reminder_repository: ReminderRepository) -> Self {
+        Self {
+            reminder_repository,
+        }
+    }
+
+    // Add a reminder to a todo of the user
+    pub async fn create_reminder(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+        create_request: CreateReminderRequest,
+    ) -> Result<ReminderResponse, AppError> {
+        let time = match (
+            create_request.remind_at.as_deref(),
+            create_request.before_due.as_deref(),
+        ) {
+            (Some(remind_at), None) => {
+                ReminderTime::At(parse_remind_at(remind_at, OffsetDateTime::now_utc())?)
+            }
+            (None, Some(before_due)) => ReminderTime::BeforeDue(parse_before_due(before_due)?),
+            (Some(_), Some(_)) => {
+                return Err(AppError::Validation(
+                    "Set either remind_at or before_due, not both".to_string(),
+                ))
+            }
+            (None, None) => {
+                return Err(AppError::Validation(
+                    "Missing required fields: remind_at or before_due".to_string(),
+                ))
+            }
+        };
+
+        match self
+            .reminder_repository
+            .create_reminder(
+                user_id,
+                workspace_id,
+                todo_id,
+                time,
+                create_request.critical.unwrap_or(false),
+            )
+            .await
+        {
+            Ok(Some(reminder)) => {
+                // Warnings are best effort, the reminder is created either way
+                let warnings = match self.reminder_repository.todo_due_at(todo_id).await {
+                    Ok(due_at) => reminder_warnings(time, due_at, OffsetDateTime::now_utc()),
+                    Err(e) => {
+                        tracing::warn!("Error fetching todo due date: {}", e);
+                        Vec::new()
+                    }
+                };
+                Ok(ReminderResponse {
+                    warnings: Some(warnings),
+                    ..ReminderResponse::from(reminder)
+                })
+            }
+            Ok(None) => Err(AppError::NotFound("Todo not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error creating reminder: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // List the reminders of a todo, sent and cancelled ones included
+    pub async fn list_reminders(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+    ) -> Result<Vec<ReminderResponse>, AppError> {
+        match self
+            .reminder_repository
+            .is_todo_owner(user_id, workspace_id, todo_id)
+            .await
+        {
+            Ok(true) => {}
+            Ok(false) => return Err(AppError::NotFound("Todo not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error checking todo ownership: {}", e);
+                return Err(AppError::Database(e));
+            }
+        }
+
+        match self
+            .reminder_repository
+            .list_reminders(user_id, workspace_id, todo_id)
+            .await
+        {
+            Ok(reminders) => Ok(reminders.into_iter().map(ReminderResponse::from).collect()),
+            Err(e) => {
+                tracing::warn!("Error listing reminders: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Cancel a pending reminder of a todo
+    pub async fn cancel_reminder(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+        id: i64,
+    ) -> Result<ReminderMessageResponse, AppError> {
+        match self
+            .reminder_repository
+            .cancel_reminder(user_id, workspace_id, todo_id, id)
+            .await
+        {
+            Ok(Some(_)) => Ok(ReminderMessageResponse {
+                message: "Reminder cancelled successfully".to_string(),
+            }),
+            Ok(None) => Err(AppError::NotFound("Pending reminder not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error cancelling reminder: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    #[test]
+    fn test_parse_remind_at() {
+        let now = datetime!(2025-01-01 12:00 UTC);
+        assert_eq!(
+            parse_remind_at(" 2025-01-02T09:00:00Z ", now).ok(),
+            Some(datetime!(2025-01-02 09:00 UTC))
+        );
+        assert!(parse_remind_at("2024-12-31T09:00:00Z", now).is_err());
+        assert!(parse_remind_at("tomorrow", now).is_err());
+    }
+
+    #[test]
+    fn test_reminder_warnings() {
+        let now = datetime!(2025-01-01 12:00 UTC);
+        let due_at = Some(datetime!(2025-01-02 12:00 UTC));
+
+        assert_eq!(
+            reminder_warnings(ReminderTime::BeforeDue(30), None, now).len(),
+            1
+        );
+        assert!(reminder_warnings(ReminderTime::BeforeDue(30), due_at, now).is_empty());
+        assert_eq!(
+            reminder_warnings(ReminderTime::BeforeDue(2 * 24 * 60), due_at, now).len(),
+            1
+        );
+        assert!(reminder_warnings(
+            ReminderTime::At(datetime!(2025-01-02 09:00 UTC)),
+            due_at,
+            now
+        )
+        .is_empty());
+        assert_eq!(
+            reminder_warnings(
+                ReminderTime::At(datetime!(2025-01-03 09:00 UTC)),
+                due_at,
+                now
+            ),
+            vec!["The reminder is sent after the todo due date"]
+        );
+        assert!(reminder_warnings(ReminderTime::At(now), None, now).is_empty());
+    }
+
+    #[test]
+    fn test_parse_before_due() {
+        assert_eq!(parse_before_due("30 minutes").ok(), Some(30));
+        assert_eq!(parse_before_due("30 minutes before due").ok(), Some(30));
+        assert_eq!(parse_before_due(" 2h ").ok(), Some(120));
+        assert_eq!(parse_before_due("1 Day before").ok(), Some(1440));
+        assert_eq!(parse_before_due("1 week").ok(), Some(10080));
+        assert_eq!(parse_before_due("0 minutes").ok(), Some(0));
+        assert!(parse_before_due("soon").is_err());
+        assert!(parse_before_due("-5 minutes").is_err());
+        assert!(parse_before_due("3 fortnights").is_err());
+        assert!(parse_before_due("53 weeks").is_err());
+    }
+}
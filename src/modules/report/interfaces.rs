@@ -0,0 +1,93 @@
+//! # `Report` Interfaces
+//! This module defines the data structures from Reports module
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::modules::todo::interfaces::{TodoResponse, TodoRow};
+use crate::utils::dates::{rfc3339, rfc3339_option};
+
+// Query parameters of the stale todos report
+#[derive(Deserialize, IntoParams, Clone, Debug, Default)]
+#[into_params(parameter_in = Query)]
+pub struct StaleQuery {
+    /// Days without changes for an open todo to be stale, defaults to 30
+    pub days: Option<i64>,
+}
+
+// Open todo untouched since the report cutoff
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct StaleTodoRow {
+    #[sqlx(flatten)]
+    pub todo: TodoRow,
+    // Stale todos of the user, beyond the returned ones
+    pub total: i64,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct StaleTodoResponse {
+    pub todo: TodoResponse,
+    // Whole days since the todo last changed
+    pub idle_days: i64,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct StaleReportResponse {
+    pub days: i64,
+    // Number of stale todos, only the oldest ones are listed
+    pub total: i64,
+    // Stale todos, least recently changed first
+    pub todos: Vec<StaleTodoResponse>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct StaleSubscriptionRequest {
+    // Days without changes for an open todo to be stale, defaults to 30
+    pub days: Option<i64>,
+}
+
+// Subscription to the monthly stale todos review, as stored in database
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct StaleSubscriptionRow {
+    pub days: i32,
+    pub last_notified_at: OffsetDateTime,
+    pub created_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct StaleSubscriptionResponse {
+    pub days: i64,
+    // Start of the current month, the review is emailed a month after it
+    #[serde(with = "rfc3339")]
+    pub last_notified_at: OffsetDateTime,
+    #[serde(with = "rfc3339_option")]
+    pub created_at: Option<OffsetDateTime>,
+}
+
+impl From<StaleSubscriptionRow> for StaleSubscriptionResponse {
+    fn from(row: StaleSubscriptionRow) -> Self {
+        Self {
+            days: i64::from(row.days),
+            last_notified_at: row.last_notified_at,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ReportMessageResponse {
+    pub message: String,
+}
+
+// Subscriber due for its monthly review, with its number of stale todos
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct StaleReviewRow {
+    pub user_id: i32,
+    pub days: i32,
+    pub username: String,
+    pub email: String,
+    pub stale_count: i64,
+    // Locale of the numbers of the email
+    pub locale: String,
+}
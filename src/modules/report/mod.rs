@@ -0,0 +1,10 @@
+//! # `Report` Mod
+//! Report imports for the stale todos report and its monthly review email
+
+pub mod interfaces;
+pub mod repository;
+pub mod review;
+pub mod routes;
+pub mod service;
+
+pub use routes::report_routes;
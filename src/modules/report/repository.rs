@@ -0,0 +1,146 @@
+//! # `Report` Repository
+//! This module defines the report repository for the stale todos report operations.
+
+use sqlx::{Error, Pool, Postgres};
+use time::OffsetDateTime;
+
+use crate::modules::{
+    common::to_db_id,
+    report::interfaces::{StaleReviewRow, StaleSubscriptionRow, StaleTodoRow},
+    todo::repository::{OPEN_STATUSES, TODO_COLUMNS},
+};
+use crate::telemetry::observe_query;
+use crate::utils::format::DEFAULT_LOCALE;
+
+// SQL condition matching the todos left aside, active and not archived nor deleted
+const STALE_CANDIDATES: &str = "deleted_at IS NULL AND NOT archived AND scheduled_for IS NULL";
+
+pub struct ReportRepository {
+    pool: Pool<Postgres>,
+}
+
+impl ReportRepository {
+    // Constructor
+    pub const fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Open todos of the user in a workspace unchanged since `cutoff`, oldest first
+    pub async fn list_stale(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        cutoff: OffsetDateTime,
+        limit: i64,
+    ) -> Result<Vec<StaleTodoRow>, Error> {
+        observe_query("report.list_stale", async move {
+            let query = format!(
+                "SELECT {TODO_COLUMNS}, COUNT(*) OVER () AS total FROM todos
+                 WHERE user_id = $1 AND workspace_id = $2 AND {STALE_CANDIDATES}
+                    AND {OPEN_STATUSES} AND updated_at < $3
+                 ORDER BY updated_at, id LIMIT $4"
+            );
+
+            sqlx::query_as::<_, StaleTodoRow>(&query)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .bind(cutoff)
+                .bind(limit)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Subscription of the user to the monthly review
+    pub async fn fetch_subscription(
+        &self,
+        user_id: i64,
+    ) -> Result<Option<StaleSubscriptionRow>, Error> {
+        observe_query("report.fetch_subscription", async move {
+            sqlx::query_as::<_, StaleSubscriptionRow>(
+                "SELECT days, last_notified_at, created_at FROM stale_report_subscriptions
+                 WHERE user_id = $1",
+            )
+            .bind(to_db_id(user_id)?)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Subscribe the user to the monthly review, or change the days of its subscription.
+    // The first review is emailed a month after subscribing.
+    pub async fn upsert_subscription(
+        &self,
+        user_id: i64,
+        days: i32,
+    ) -> Result<StaleSubscriptionRow, Error> {
+        observe_query("report.upsert_subscription", async move {
+            sqlx::query_as::<_, StaleSubscriptionRow>(
+                "INSERT INTO stale_report_subscriptions (user_id, days) VALUES ($1, $2)
+                 ON CONFLICT (user_id) DO UPDATE SET days = EXCLUDED.days
+                 RETURNING days, last_notified_at, created_at",
+            )
+            .bind(to_db_id(user_id)?)
+            .bind(days)
+            .fetch_one(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Unsubscribe the user, returns false when it was not subscribed
+    pub async fn delete_subscription(&self, user_id: i64) -> Result<bool, Error> {
+        observe_query("report.delete_subscription", async move {
+            let result = sqlx::query("DELETE FROM stale_report_subscriptions WHERE user_id = $1")
+                .bind(to_db_id(user_id)?)
+                .execute(&self.pool)
+                .await?;
+
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    // Subscribers whose last review is a month old, with their stale todos in every
+    // workspace. Unverified and guest users are skipped.
+    pub async fn list_due_reviews(&self, limit: i64) -> Result<Vec<StaleReviewRow>, Error> {
+        observe_query("report.list_due_reviews", async move {
+            let query = format!(
+                "SELECT s.user_id, s.days, u.username, u.email,
+                    (SELECT COUNT(*) FROM todos
+                     WHERE todos.user_id = s.user_id AND {STALE_CANDIDATES} AND {OPEN_STATUSES}
+                        AND updated_at < NOW() - make_interval(days => s.days)) AS stale_count,
+                    COALESCE(p.locale, '{DEFAULT_LOCALE}') AS locale
+                 FROM stale_report_subscriptions s
+                 JOIN users u ON u.id = s.user_id
+                 LEFT JOIN user_preferences p ON p.user_id = s.user_id
+                 WHERE s.last_notified_at <= NOW() - INTERVAL '1 month'
+                    AND u.email_verified_at IS NOT NULL AND NOT u.is_guest
+                 ORDER BY s.last_notified_at LIMIT $1"
+            );
+
+            sqlx::query_as::<_, StaleReviewRow>(&query)
+                .bind(limit)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Start the next month of a subscriber
+    pub async fn mark_reviewed(&self, user_id: i32) -> Result<(), Error> {
+        observe_query("report.mark_reviewed", async move {
+            sqlx::query(
+                "UPDATE stale_report_subscriptions SET last_notified_at = NOW() WHERE user_id = $1",
+            )
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+            Ok(())
+        })
+        .await
+    }
+}
@@ -0,0 +1,130 @@
+//! # Stale Review
+//! Background monthly email reminding the subscribed users of their stale todos.
+//! A single instance sends the reviews at a time so a user is not emailed twice.
+
+use std::sync::Arc;
+
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    modules::{
+        email::{
+            mailer::{EmailMessage, Mailer},
+            templates::STALE_REVIEW,
+        },
+        report::{interfaces::StaleReviewRow, repository::ReportRepository},
+    },
+    utils::format::Formatter,
+    workers::{run_exclusive, WorkerTask},
+};
+
+// Delay between two scans of the due reviews
+const REVIEW_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+// Reviews sent per scan
+const REVIEW_BATCH: i64 = 100;
+
+// Advisory lock taken by the instance sending the reviews
+const REVIEW_LOCK: &str = "stale_review";
+
+// Review email of a subscriber, `None` without stale todos
+fn review_message(row: &StaleReviewRow) -> Option<EmailMessage> {
+    if row.stale_count == 0 {
+        return None;
+    }
+
+    let formatter = Formatter::new(&row.locale, None);
+    let count = formatter.integer(row.stale_count);
+    let days = formatter.integer(i64::from(row.days));
+    Some(STALE_REVIEW.render(
+        &row.email,
+        &[
+            ("username", row.username.as_str()),
+            ("count", count.as_str()),
+            ("days", days.as_str()),
+        ],
+    ))
+}
+
+// Email the due reviews, returns how many were sent. Subscribers without stale todos
+// start their next month without an email, a failed email is retried on the next scan.
+async fn send_due(
+    report_repository: &ReportRepository,
+    mailer: &dyn Mailer,
+) -> Result<usize, sqlx::Error> {
+    let mut sent = 0;
+    for row in report_repository.list_due_reviews(REVIEW_BATCH).await? {
+        if let Some(message) = review_message(&row) {
+            if let Err(e) = mailer.send(&message).await {
+                tracing::warn!("Stale review of user {} not sent: {}", row.user_id, e);
+                continue;
+            }
+            sent += 1;
+        }
+        report_repository.mark_reviewed(row.user_id).await?;
+    }
+    Ok(sent)
+}
+
+// Worker scanning the due reviews every hour. Database errors are logged and retried
+// on the next scan.
+pub fn review_worker(
+    pool: Pool<Postgres>,
+    report_repository: ReportRepository,
+    mailer: Arc<dyn Mailer>,
+) -> WorkerTask {
+    Box::pin(async move {
+        let mut interval = tokio::time::interval(REVIEW_INTERVAL);
+        loop {
+            interval.tick().await;
+            let job = send_due(&report_repository, mailer.as_ref());
+            match run_exclusive(&pool, REVIEW_LOCK, job).await {
+                Ok(Some(Ok(0)) | None) => {}
+                Ok(Some(Ok(sent))) => tracing::debug!("Sent {} stale reviews", sent),
+                Ok(Some(Err(e))) => tracing::warn!("Error sending stale reviews: {}", e),
+                Err(e) => tracing::warn!("Error locking stale review: {}", e),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn row(stale_count: i64) -> StaleReviewRow {
+        StaleReviewRow {
+            user_id: 1,
+            days: 30,
+            username: "ana".to_string(),
+            email: "ana@example.com".to_string(),
+            stale_count,
+            locale: "en-US".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_review_message_skips_clean_backlog() {
+        assert!(review_message(&row(0)).is_none());
+    }
+
+    #[test]
+    fn test_review_message_counts_stale_todos() {
+        let message = review_message(&row(7)).unwrap();
+        assert_eq!(message.to, "ana@example.com");
+        assert!(message
+            .text_body
+            .contains("7 of your open tasks were not touched for 30 days"));
+    }
+
+    #[test]
+    fn test_review_message_groups_count_in_locale() {
+        let message = review_message(&StaleReviewRow {
+            locale: "de-DE".to_string(),
+            ..row(1250)
+        })
+        .unwrap();
+        assert!(message.text_body.contains("1.250 of your open tasks"));
+    }
+}
@@ -0,0 +1,152 @@
+//! #`Report` Routes
+//! This module defines the HTTP routes for the stale todos report.
+
+use axum::extract::Query;
+use axum::routing::get;
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Router};
+
+use crate::auth::Claims;
+use crate::modules::common::{respond, ErrorResponse};
+use crate::modules::report::interfaces::{
+    ReportMessageResponse, StaleQuery, StaleReportResponse, StaleSubscriptionRequest,
+    StaleSubscriptionResponse,
+};
+use crate::modules::report::repository::ReportRepository;
+use crate::modules::report::service::ReportService;
+use crate::modules::workspace::context::WorkspaceContext;
+use crate::utils::concurrency_limit::{limit_concurrency, HEAVY_REQUEST_CONCURRENCY};
+use crate::utils::json::Json;
+use crate::AppState;
+
+// Creates and returns the report routes
+pub fn report_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/reports/stale",
+            limit_concurrency(get(stale_report_route), HEAVY_REQUEST_CONCURRENCY),
+        )
+        .route(
+            "/reports/stale/subscription",
+            get(fetch_subscription_route)
+                .put(subscribe_route)
+                .delete(unsubscribe_route),
+        )
+}
+
+fn report_service(app_state: &AppState) -> ReportService {
+    ReportService::new(ReportRepository::new(app_state.db_pool.clone()))
+}
+
+// Stale Report Route
+#[utoipa::path(
+    get,
+    path = "/reports/stale",
+    tag = "Reports",
+    params(StaleQuery),
+    responses(
+        (status = 200, description = "Open todos untouched for the asked days", body = StaleReportResponse),
+        (status = 400, description = "Invalid number of days", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn stale_report_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Query(query): Query<StaleQuery>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        report_service(&app_state)
+            .stale_report(workspace.user_id, workspace.workspace_id, query)
+            .await,
+    )
+}
+
+// Fetch Subscription Route
+#[utoipa::path(
+    get,
+    path = "/reports/stale/subscription",
+    tag = "Reports",
+    responses(
+        (status = 200, description = "Monthly stale todos review of the user", body = StaleSubscriptionResponse),
+        (status = 404, description = "Not subscribed", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn fetch_subscription_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        report_service(&app_state)
+            .fetch_subscription(claims.user_id)
+            .await,
+    )
+}
+
+// Subscribe Route
+#[utoipa::path(
+    put,
+    path = "/reports/stale/subscription",
+    tag = "Reports",
+    request_body = StaleSubscriptionRequest,
+    responses(
+        (status = 200, description = "Subscribed to the monthly stale todos review", body = StaleSubscriptionResponse),
+        (status = 400, description = "Invalid number of days", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn subscribe_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Json(subscription_request): Json<StaleSubscriptionRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        report_service(&app_state)
+            .subscribe(claims.user_id, subscription_request)
+            .await,
+    )
+}
+
+// Unsubscribe Route
+#[utoipa::path(
+    delete,
+    path = "/reports/stale/subscription",
+    tag = "Reports",
+    responses(
+        (status = 200, description = "Unsubscribed from the monthly review", body = ReportMessageResponse),
+        (status = 404, description = "Not subscribed", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn unsubscribe_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        report_service(&app_state).unsubscribe(claims.user_id).await,
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_routes_creation() {
+        let _routes = report_routes();
+        assert!(true);
+    }
+}
@@ -0,0 +1,155 @@
+//! # `Report` Service
+//!
+//! This module contains the bussiness logic for the stale todos report, listing the open
+//! todos left untouched to help users groom their backlog.
+
+use time::{Duration, OffsetDateTime};
+
+use crate::modules::{
+    common::AppError,
+    report::{
+        interfaces::{
+            ReportMessageResponse, StaleQuery, StaleReportResponse, StaleSubscriptionRequest,
+            StaleSubscriptionResponse, StaleTodoResponse,
+        },
+        repository::ReportRepository,
+    },
+    todo::interfaces::TodoResponse,
+};
+
+// Days without changes for a todo to be stale when not asked
+const DEFAULT_STALE_DAYS: i64 = 30;
+
+// Largest number of days a report can look back
+const MAX_STALE_DAYS: i64 = 3650;
+
+// Stale todos listed by a report
+const MAX_STALE_TODOS: i64 = 100;
+
+// Validate the days of a report, `DEFAULT_STALE_DAYS` when not set
+pub fn stale_days(days: Option<i64>) -> Result<i64, AppError> {
+    let days = days.unwrap_or(DEFAULT_STALE_DAYS);
+    if !(1..=MAX_STALE_DAYS).contains(&days) {
+        return Err(AppError::Validation(format!(
+            "Days must be between 1 and {MAX_STALE_DAYS}"
+        )));
+    }
+    Ok(days)
+}
+
+pub struct ReportService {
+    report_repository: ReportRepository,
+}
+
+impl ReportService {
+    pub const fn new(report_repository: ReportRepository) -> Self {
+        Self { report_repository }
+    }
+
+    // Open todos of the user untouched for the asked number of days
+    pub async fn stale_report(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        query: StaleQuery,
+    ) -> Result<StaleReportResponse, AppError> {
+        let days = stale_days(query.days)?;
+        let now = OffsetDateTime::now_utc();
+
+        match self
+            .report_repository
+            .list_stale(
+                user_id,
+                workspace_id,
+                now - Duration::days(days),
+                MAX_STALE_TODOS,
+            )
+            .await
+        {
+            Ok(rows) => Ok(StaleReportResponse {
+                days,
+                total: rows.first().map_or(0, |row| row.total),
+                todos: rows
+                    .into_iter()
+                    .map(|row| StaleTodoResponse {
+                        idle_days: row
+                            .todo
+                            .updated_at
+                            .map_or(days, |updated_at| (now - updated_at).whole_days()),
+                        todo: TodoResponse::from(row.todo),
+                    })
+                    .collect(),
+            }),
+            Err(e) => {
+                tracing::warn!("Error listing stale todos: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Monthly review subscription of the user
+    pub async fn fetch_subscription(
+        &self,
+        user_id: i64,
+    ) -> Result<StaleSubscriptionResponse, AppError> {
+        match self.report_repository.fetch_subscription(user_id).await {
+            Ok(Some(subscription)) => Ok(StaleSubscriptionResponse::from(subscription)),
+            Ok(None) => Err(AppError::NotFound("Not subscribed".to_string())),
+            Err(e) => {
+                tracing::warn!("Error fetching stale review subscription: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Subscribe the user to the monthly review of its stale todos
+    pub async fn subscribe(
+        &self,
+        user_id: i64,
+        subscription_request: StaleSubscriptionRequest,
+    ) -> Result<StaleSubscriptionResponse, AppError> {
+        let days = stale_days(subscription_request.days)?;
+        let days = i32::try_from(days).unwrap_or(i32::MAX);
+
+        match self
+            .report_repository
+            .upsert_subscription(user_id, days)
+            .await
+        {
+            Ok(subscription) => Ok(StaleSubscriptionResponse::from(subscription)),
+            Err(e) => {
+                tracing::warn!("Error subscribing to stale review: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Stop the monthly review of the user
+    pub async fn unsubscribe(&self, user_id: i64) -> Result<ReportMessageResponse, AppError> {
+        match self.report_repository.delete_subscription(user_id).await {
+            Ok(true) => Ok(ReportMessageResponse {
+                message: "Unsubscribed".to_string(),
+            }),
+            Ok(false) => Err(AppError::NotFound("Not subscribed".to_string())),
+            Err(e) => {
+                tracing::warn!("Error unsubscribing from stale review: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stale_days_defaults_and_bounds() {
+        assert_eq!(stale_days(None).ok(), Some(DEFAULT_STALE_DAYS));
+        assert_eq!(stale_days(Some(1)).ok(), Some(1));
+        assert_eq!(stale_days(Some(MAX_STALE_DAYS)).ok(), Some(MAX_STALE_DAYS));
+        assert!(stale_days(Some(0)).is_err());
+        assert!(stale_days(Some(-5)).is_err());
+        assert!(stale_days(Some(MAX_STALE_DAYS + 1)).is_err());
+    }
+}
@@ -0,0 +1,283 @@
+//! # Search Indexes
+//! This module defines where the todo searches run: the full text search of Postgres by
+//! default, or a Meilisearch index for large deployments. Meilisearch is fed by the
+//! outbox events, its hits are read back from the database so stale hits are dropped.
+
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use reqwest::{Client, Method, RequestBuilder, Url};
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+
+use crate::config::{SearchBackend, SearchConfig};
+use crate::modules::{
+    common::AppError,
+    outbox::{interfaces::OutboxMessage, sink::EventSink},
+    search::{
+        interfaces::{MeilisearchResponse, SearchDocument, TodoEventData},
+        repository::SearchRepository,
+    },
+};
+
+// Attributes of the documents searched and filtered on by Meilisearch
+const SEARCHABLE_ATTRIBUTES: [&str; 2] = ["title", "description"];
+const FILTERABLE_ATTRIBUTES: [&str; 2] = ["user_id", "workspace_id"];
+
+#[async_trait]
+pub trait SearchIndex: Send + Sync {
+    // Name used in the logs
+    fn name(&self) -> &'static str;
+
+    // Ids of the todos of the user in the workspace matching the query, best match first
+    async fn search(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        query: &str,
+        limit: i64,
+    ) -> Result<Vec<i64>, AppError>;
+}
+
+// Full text search of the `search_vector` column of the todos
+pub struct PostgresSearchIndex {
+    search_repository: SearchRepository,
+}
+
+impl PostgresSearchIndex {
+    pub const fn new(search_repository: SearchRepository) -> Self {
+        Self { search_repository }
+    }
+}
+
+#[async_trait]
+impl SearchIndex for PostgresSearchIndex {
+    fn name(&self) -> &'static str {
+        "postgres"
+    }
+
+    async fn search(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        query: &str,
+        limit: i64,
+    ) -> Result<Vec<i64>, AppError> {
+        let ids = self
+            .search_repository
+            .search_ids(user_id, workspace_id, query, limit)
+            .await?;
+        Ok(ids.into_iter().map(i64::from).collect())
+    }
+}
+
+// Meilisearch index of the todos, one document per live todo
+pub struct MeilisearchIndex {
+    client: Client,
+    url: Url,
+    api_key: Option<String>,
+    index: String,
+}
+
+impl MeilisearchIndex {
+    pub fn new(config: &SearchConfig) -> Result<Self, String> {
+        let url = Url::parse(config.meilisearch_url.trim())
+            .map_err(|e| format!("Invalid MEILISEARCH_URL: {e}"))?;
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_seconds))
+            .build()
+            .map_err(|e| format!("Failed to create Meilisearch client: {e}"))?;
+        Ok(Self {
+            client,
+            url,
+            api_key: config.meilisearch_api_key.clone(),
+            index: config.meilisearch_index.trim().to_string(),
+        })
+    }
+
+    // Request to a path of the index, with the API key when configured
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let url = format!(
+            "{}/indexes/{}{path}",
+            self.url.as_str().trim_end_matches('/'),
+            self.index
+        );
+        let request = self.client.request(method, url);
+        match &self.api_key {
+            Some(api_key) => request.bearer_auth(api_key),
+            None => request,
+        }
+    }
+
+    async fn send(request: RequestBuilder) -> Result<reqwest::Response, String> {
+        request
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| e.to_string())
+    }
+
+    // Declare the searched and filtered attributes, Meilisearch creates the index
+    pub async fn configure(&self) -> Result<(), String> {
+        Self::send(self.request(Method::PATCH, "/settings").json(&json!({
+            "searchableAttributes": SEARCHABLE_ATTRIBUTES,
+            "filterableAttributes": FILTERABLE_ATTRIBUTES,
+        })))
+        .await
+        .map(|_| ())
+    }
+}
+
+// Filter of the hits of a user in a workspace
+fn owner_filter(user_id: i64, workspace_id: i64) -> String {
+    format!("user_id = {user_id} AND workspace_id = {workspace_id}")
+}
+
+// Document of a todo event, `None` once the todo is in the trash
+pub fn search_document(message: &OutboxMessage) -> Result<Option<SearchDocument>, String> {
+    let data = serde_json::from_value::<TodoEventData>(message.data.clone())
+        .map_err(|e| format!("Invalid todo event: {e}"))?;
+    if data.deleted_at.is_some() {
+        return Ok(None);
+    }
+    Ok(Some(SearchDocument {
+        id: data.id,
+        user_id: message.user_id,
+        workspace_id: message.workspace_id,
+        title: data.title,
+        description: data.description,
+        status: data.status,
+    }))
+}
+
+#[async_trait]
+impl SearchIndex for MeilisearchIndex {
+    fn name(&self) -> &'static str {
+        "meilisearch"
+    }
+
+    async fn search(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        query: &str,
+        limit: i64,
+    ) -> Result<Vec<i64>, AppError> {
+        let answer = Self::send(self.request(Method::POST, "/search").json(&json!({
+            "q": query,
+            "filter": owner_filter(user_id, workspace_id),
+            "limit": limit,
+            "attributesToRetrieve": ["id"],
+        })))
+        .await
+        .map_err(|e| AppError::Internal(format!("Meilisearch search failed: {e}")))?
+        .json::<MeilisearchResponse>()
+        .await
+        .map_err(|e| AppError::Internal(format!("Invalid Meilisearch answer: {e}")))?;
+
+        Ok(answer.hits.into_iter().map(|hit| hit.id).collect())
+    }
+}
+
+// Keeps the index in sync with the todo events, indexing the live todos and removing the
+// trashed ones. Meilisearch applies the writes in the order they were accepted.
+#[async_trait]
+impl EventSink for MeilisearchIndex {
+    fn name(&self) -> &'static str {
+        "meilisearch"
+    }
+
+    async fn deliver(&self, message: &OutboxMessage) -> Result<(), String> {
+        if !message.event_type.starts_with("todo.") {
+            return Ok(());
+        }
+        let request = search_document(message)?.map_or_else(
+            || {
+                let id = message.data["id"].as_i64().unwrap_or_default();
+                self.request(Method::DELETE, &format!("/documents/{id}"))
+            },
+            |document| {
+                self.request(Method::POST, "/documents?primaryKey=id")
+                    .json(&[document])
+            },
+        );
+        Self::send(request).await.map(|_| ())
+    }
+}
+
+// Index of the configured backend, and the sink keeping it in sync with the outbox
+// events when it's external. A Meilisearch index failing to configure is only logged,
+// its settings are applied again on the next start.
+pub async fn search_index_from_config(
+    config: &SearchConfig,
+    pool: &Pool<Postgres>,
+) -> Result<(Arc<dyn SearchIndex>, Option<Arc<dyn EventSink>>), String> {
+    match config.backend {
+        SearchBackend::Postgres => Ok((
+            Arc::new(PostgresSearchIndex::new(SearchRepository::new(
+                pool.clone(),
+            ))),
+            None,
+        )),
+        SearchBackend::Meilisearch => {
+            let index = Arc::new(MeilisearchIndex::new(config)?);
+            if let Err(e) = index.configure().await {
+                tracing::warn!("Failed to configure the Meilisearch index: {}", e);
+            }
+            Ok((index.clone(), Some(index)))
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn message(data: serde_json::Value) -> OutboxMessage {
+        OutboxMessage {
+            id: 1,
+            event_type: "todo.updated".to_string(),
+            user_id: 3,
+            workspace_id: 4,
+            occurred_at: time::macros::datetime!(2025-03-01 12:00 UTC),
+            data,
+        }
+    }
+
+    #[test]
+    fn test_search_document() {
+        let document = search_document(&message(json!({
+            "id": 7, "list_id": null, "title": "Pay rent", "description": null,
+            "status": "backlog", "due_at": null, "completed_at": null, "deleted_at": null
+        })))
+        .unwrap()
+        .unwrap();
+        assert_eq!(document.id, 7);
+        assert_eq!(document.user_id, 3);
+        assert_eq!(document.workspace_id, 4);
+        assert_eq!(document.title, "Pay rent");
+
+        let trashed = search_document(&message(json!({
+            "id": 7, "title": "Pay rent", "status": "backlog",
+            "deleted_at": "2025-03-01T12:00:00+00:00"
+        })))
+        .unwrap();
+        assert!(trashed.is_none());
+
+        assert!(search_document(&message(json!({ "id": 7 }))).is_err());
+    }
+
+    #[test]
+    fn test_owner_filter() {
+        assert_eq!(owner_filter(3, 4), "user_id = 3 AND workspace_id = 4");
+    }
+
+    #[test]
+    fn test_meilisearch_requires_valid_url() {
+        let mut config = SearchConfig::default();
+        assert!(MeilisearchIndex::new(&config).is_ok());
+        config.meilisearch_url = "not a url".to_string();
+        assert!(MeilisearchIndex::new(&config).is_err());
+    }
+}
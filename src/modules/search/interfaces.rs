@@ -0,0 +1,51 @@
+//! # `Search` Interfaces
+//! This module defines the data structures from Search module
+
+use serde::{Deserialize, Serialize};
+use utoipa::IntoParams;
+
+// Results returned when the client doesn't ask for a number
+pub const DEFAULT_SEARCH_LIMIT: i64 = 20;
+
+// Query parameters of the todo search
+#[derive(Deserialize, IntoParams, Clone, Debug, Default)]
+#[into_params(parameter_in = Query)]
+pub struct SearchQuery {
+    /// Words to find in the titles and descriptions of the todos
+    pub q: Option<String>,
+    /// Maximum number of results, capped at 100
+    pub limit: Option<i64>,
+}
+
+// Todo as indexed by an external search engine, the owner and workspace filter the hits
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SearchDocument {
+    pub id: i64,
+    pub user_id: i64,
+    pub workspace_id: i64,
+    pub title: String,
+    pub description: Option<String>,
+    pub status: String,
+}
+
+// Fields of the todo events read by the search indexes
+#[derive(Deserialize, Clone, Debug)]
+pub struct TodoEventData {
+    pub id: i64,
+    pub title: String,
+    pub description: Option<String>,
+    pub status: String,
+    pub deleted_at: Option<String>,
+}
+
+// Id of a hit of a Meilisearch search
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct MeilisearchHit {
+    pub id: i64,
+}
+
+// Answer of a Meilisearch search
+#[derive(Deserialize, Clone, Debug)]
+pub struct MeilisearchResponse {
+    pub hits: Vec<MeilisearchHit>,
+}
@@ -0,0 +1,11 @@
+//! # `Search` Mod
+//! Search imports for the full text search of the todos, run on the index selected by
+//! the `search` configuration
+
+pub mod index;
+pub mod interfaces;
+pub mod repository;
+pub mod routes;
+pub mod service;
+
+pub use routes::search_routes;
@@ -0,0 +1,73 @@
+//! # `Search` Repository
+//! This module defines the search repository, the full text search of Postgres and the
+//! todos of the hits.
+
+use sqlx::{Error, Pool, Postgres};
+
+use crate::modules::{
+    common::to_db_id,
+    todo::{interfaces::TodoRow, repository::TODO_COLUMNS},
+};
+use crate::telemetry::observe_query;
+
+#[derive(Clone)]
+pub struct SearchRepository {
+    pool: Pool<Postgres>,
+}
+
+impl SearchRepository {
+    // Constructor
+    pub const fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Ids of the todos of an user in a workspace matching the words of `query`, best
+    // match first. `websearch_to_tsquery` reads quoted phrases, `or` and `-word`.
+    pub async fn search_ids(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        query: &str,
+        limit: i64,
+    ) -> Result<Vec<i32>, Error> {
+        observe_query("search.search_ids", async move {
+            sqlx::query_scalar::<_, i32>(
+                "SELECT id FROM todos, websearch_to_tsquery('simple', $3) AS query
+                 WHERE user_id = $1 AND workspace_id = $2 AND deleted_at IS NULL
+                   AND search_vector @@ query
+                 ORDER BY ts_rank(search_vector, query) DESC, id DESC
+                 LIMIT $4",
+            )
+            .bind(to_db_id(user_id)?)
+            .bind(to_db_id(workspace_id)?)
+            .bind(query)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Todos of an user in a workspace among `ids`, the deleted ones left out
+    pub async fn fetch_todos(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        ids: &[i32],
+    ) -> Result<Vec<TodoRow>, Error> {
+        observe_query("search.fetch_todos", async move {
+            let query = format!(
+                "SELECT {TODO_COLUMNS} FROM todos
+                 WHERE user_id = $1 AND workspace_id = $2 AND deleted_at IS NULL
+                   AND id = ANY($3)"
+            );
+            sqlx::query_as::<_, TodoRow>(&query)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .bind(ids)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+}
@@ -0,0 +1,71 @@
+//! #`Search` Routes
+//! This module defines the HTTP routes for the todo search.
+
+use axum::extract::Query;
+use axum::routing::get;
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Router};
+
+use crate::modules::common::{respond, ErrorResponse};
+use crate::modules::search::interfaces::SearchQuery;
+use crate::modules::search::repository::SearchRepository;
+use crate::modules::search::service::SearchService;
+use crate::modules::todo::interfaces::TodoResponse;
+use crate::modules::workspace::context::WorkspaceContext;
+use crate::utils::concurrency_limit::{limit_concurrency, HEAVY_REQUEST_CONCURRENCY};
+use crate::AppState;
+
+// Creates and returns the search routes
+pub fn search_routes() -> Router<AppState> {
+    Router::new().route(
+        "/todos/search",
+        limit_concurrency(get(search_todos_route), HEAVY_REQUEST_CONCURRENCY),
+    )
+}
+
+fn search_service(app_state: &AppState) -> SearchService {
+    SearchService::new(
+        app_state.search_index.clone(),
+        SearchRepository::new(app_state.db_pool.clone()),
+    )
+}
+
+// Search Todos Route
+#[utoipa::path(
+    get,
+    path = "/todos/search",
+    tag = "Todos",
+    params(SearchQuery),
+    responses(
+        (status = 200, description = "Todos whose title or description match the words, best match first", body = [TodoResponse]),
+        (status = 400, description = "Missing query", body = ErrorResponse),
+        (status = 500, description = "Failed to search todos", body = ErrorResponse),
+        (status = 503, description = "Too many concurrent searches", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn search_todos_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Query(query): Query<SearchQuery>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        search_service(&app_state)
+            .search(workspace.user_id, workspace.workspace_id, query)
+            .await,
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_routes_creation() {
+        let _routes = search_routes();
+        assert!(true);
+    }
+}
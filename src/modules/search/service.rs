@@ -0,0 +1,112 @@
+//! # `Search` Service
+//!
+//! This module contains the bussiness logic for the todo search. The index finds the
+//! matching ids, the todos are then read from the database.
+
+use std::sync::Arc;
+
+use crate::modules::{
+    common::{AppError, MAX_PAGE_LIMIT},
+    search::{
+        index::SearchIndex,
+        interfaces::{SearchQuery, DEFAULT_SEARCH_LIMIT},
+        repository::SearchRepository,
+    },
+    todo::interfaces::{TodoResponse, TodoRow},
+};
+
+// Rows in the order of the ids, the rows without id last
+fn in_id_order(mut rows: Vec<TodoRow>, ids: &[i32]) -> Vec<TodoRow> {
+    rows.sort_by_key(|row| ids.iter().position(|id| *id == row.id).unwrap_or(ids.len()));
+    rows
+}
+
+pub struct SearchService {
+    search_index: Arc<dyn SearchIndex>,
+    search_repository: SearchRepository,
+}
+
+impl SearchService {
+    // Constructor
+    pub fn new(search_index: Arc<dyn SearchIndex>, search_repository: SearchRepository) -> Self {
+        Self {
+            search_index,
+            search_repository,
+        }
+    }
+
+    // Todos of an user in a workspace matching the query, best match first
+    pub async fn search(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        query: SearchQuery,
+    ) -> Result<Vec<TodoResponse>, AppError> {
+        let Some(text) = query
+            .q
+            .as_deref()
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+        else {
+            return Err(AppError::Validation(
+                "Missing required fields: q".to_string(),
+            ));
+        };
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_SEARCH_LIMIT)
+            .clamp(1, MAX_PAGE_LIMIT);
+
+        let ids: Vec<i32> = self
+            .search_index
+            .search(user_id, workspace_id, text, limit)
+            .await?
+            .into_iter()
+            .filter_map(|id| i32::try_from(id).ok())
+            .collect();
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let rows = self
+            .search_repository
+            .fetch_todos(user_id, workspace_id, &ids)
+            .await?;
+        Ok(in_id_order(rows, &ids)
+            .into_iter()
+            .map(TodoResponse::from)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(id: i32) -> TodoRow {
+        TodoRow {
+            id,
+            list_id: None,
+            title: format!("Todo {id}"),
+            description: None,
+            status: "backlog".to_string(),
+            status_changed_at: None,
+            completed_at: None,
+            due_at: None,
+            recurrence: None,
+            scheduled_for: None,
+            archived: false,
+            version: 1,
+            created_at: None,
+            updated_at: None,
+            deleted_at: None,
+        }
+    }
+
+    #[test]
+    fn test_in_id_order() {
+        let rows = in_id_order(vec![row(1), row(2), row(3)], &[3, 1, 2]);
+        let ids: Vec<i32> = rows.iter().map(|row| row.id).collect();
+        assert_eq!(ids, vec![3, 1, 2]);
+    }
+}
@@ -0,0 +1,196 @@
+//! # Security Webhook Dispatcher
+//! Background delivery of the security events to the webhooks of their user. A single
+//! instance dispatches at a time so events leave each webhook in the order they happened.
+//! Delivery is at least once, a webhook only moves past an event once it accepted it.
+
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use reqwest::{redirect::Policy, Client};
+use sha2::Sha256;
+use sqlx::{Pool, Postgres};
+use time::OffsetDateTime;
+
+use crate::{
+    modules::{
+        audit::interfaces::AuditAction,
+        security_webhook::{
+            interfaces::{PendingSecurityEventRow, SecurityEvent, SecurityEventType},
+            repository::SecurityWebhookRepository,
+        },
+    },
+    workers::{run_exclusive, WorkerTask},
+};
+
+const DEFAULT_WEBHOOK_TIMEOUT_SECONDS: u64 = 10;
+
+// Delay between two dispatch runs
+const DISPATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+// Events read per run, and per webhook
+const DISPATCH_BATCH: i64 = 100;
+const WEBHOOK_BATCH: i64 = 10;
+
+// Failed deliveries before an event is skipped
+const MAX_ATTEMPTS: i32 = 10;
+
+// Advisory lock taken by the instance dispatching
+const DISPATCH_LOCK: &str = "security_webhook_dispatch";
+
+// Header carrying the event id, consumers use it to drop redeliveries
+const EVENT_ID_HEADER: &str = "X-Security-Event-Id";
+
+// Header carrying the signature of the payload
+const SIGNATURE_HEADER: &str = "X-Security-Signature";
+
+// Signature of a payload sent at `timestamp`, as `t=<timestamp>,v1=<hex>` where the hex
+// is the HMAC-SHA256 of `<timestamp>.<body>` keyed with the webhook secret. Consumers
+// recompute it and reject old timestamps to stop replays.
+pub fn sign_payload(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    // HMAC accepts keys of any length
+    let signature = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).map_or_else(
+        |_| String::new(),
+        |mut mac| {
+            mac.update(timestamp.to_string().as_bytes());
+            mac.update(b".");
+            mac.update(body);
+            hex::encode(mac.finalize().into_bytes())
+        },
+    );
+    format!("t={timestamp},v1={signature}")
+}
+
+// Post a signed event to a webhook, any non 2xx answer is a failure
+async fn deliver(client: &Client, row: &PendingSecurityEventRow) -> Result<(), String> {
+    let event = SecurityEvent::from(row);
+    let body = serde_json::to_vec(&event).map_err(|e| e.to_string())?;
+    let signature = sign_payload(
+        &row.secret,
+        OffsetDateTime::now_utc().unix_timestamp(),
+        &body,
+    );
+
+    client
+        .post(&row.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(EVENT_ID_HEADER, event.id.to_string())
+        .header(SIGNATURE_HEADER, signature)
+        .body(body)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+// Deliver the pending events, returns how many were delivered. A failed event holds
+// back the next events of its webhook until the next run, not the other webhooks.
+async fn dispatch_pending(
+    security_webhook_repository: &SecurityWebhookRepository,
+    client: &Client,
+) -> Result<usize, sqlx::Error> {
+    let actions = SecurityEventType::ACTIONS.map(AuditAction::as_str);
+    let mut delivered = 0;
+    let mut failed_webhook = None;
+    for row in security_webhook_repository
+        .list_pending(&actions, WEBHOOK_BATCH, DISPATCH_BATCH)
+        .await?
+    {
+        if failed_webhook == Some(row.webhook_id) {
+            continue;
+        }
+
+        match deliver(client, &row).await {
+            Ok(()) => {
+                security_webhook_repository
+                    .mark_delivered(row.webhook_id, row.id)
+                    .await?;
+                delivered += 1;
+            }
+            Err(e) => {
+                failed_webhook = Some(row.webhook_id);
+                if security_webhook_repository
+                    .record_failure(row.webhook_id, row.id, &e, MAX_ATTEMPTS)
+                    .await?
+                {
+                    tracing::error!(
+                        "Security event {} skipped for webhook {}: {}",
+                        row.id,
+                        row.webhook_id,
+                        e
+                    );
+                } else {
+                    tracing::warn!(
+                        "Security event {} not delivered to webhook {}: {}",
+                        row.id,
+                        row.webhook_id,
+                        e
+                    );
+                }
+            }
+        }
+    }
+    Ok(delivered)
+}
+
+// HTTP client of the webhooks, with the timeout of `SECURITY_WEBHOOK_TIMEOUT_SECONDS`.
+// Redirects are not followed, they could lead to a private address.
+pub fn webhook_client_from_env() -> Result<Client, String> {
+    let timeout = std::env::var("SECURITY_WEBHOOK_TIMEOUT_SECONDS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_WEBHOOK_TIMEOUT_SECONDS);
+    Client::builder()
+        .timeout(Duration::from_secs(timeout))
+        .redirect(Policy::none())
+        .build()
+        .map_err(|e| format!("Failed to create security webhook client: {e}"))
+}
+
+// Worker delivering the security events every `DISPATCH_INTERVAL`. Database errors are
+// logged and retried on the next run.
+pub fn security_webhook_worker(
+    pool: Pool<Postgres>,
+    security_webhook_repository: SecurityWebhookRepository,
+    client: Client,
+) -> WorkerTask {
+    Box::pin(async move {
+        let mut interval = tokio::time::interval(DISPATCH_INTERVAL);
+        loop {
+            interval.tick().await;
+            let job = dispatch_pending(&security_webhook_repository, &client);
+            match run_exclusive(&pool, DISPATCH_LOCK, job).await {
+                Ok(Some(Ok(0)) | None) => {}
+                Ok(Some(Ok(delivered))) => {
+                    tracing::debug!("Delivered {} security events", delivered);
+                }
+                Ok(Some(Err(e))) => tracing::warn!("Error dispatching security events: {}", e),
+                Err(e) => tracing::warn!("Error locking security webhook dispatch: {}", e),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_payload() {
+        let signature = sign_payload("secret", 1_741_608_000, br#"{"id":1}"#);
+        assert!(signature.starts_with("t=1741608000,v1="));
+        assert_eq!(signature.len(), "t=1741608000,v1=".len() + 64);
+        assert_eq!(
+            signature,
+            sign_payload("secret", 1_741_608_000, br#"{"id":1}"#)
+        );
+        assert_ne!(
+            signature,
+            sign_payload("other", 1_741_608_000, br#"{"id":1}"#)
+        );
+        assert_ne!(
+            signature,
+            sign_payload("secret", 1_741_608_001, br#"{"id":1}"#)
+        );
+    }
+}
@@ -0,0 +1,178 @@
+//! # `Security Webhook` Interfaces
+//! This module defines the data structures from Security Webhook module
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+use crate::modules::audit::interfaces::AuditAction;
+use crate::utils::dates::rfc3339_option;
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct CreateSecurityWebhookRequest {
+    // HTTPS endpoint the security events are posted to
+    pub url: Option<String>,
+}
+
+// Security webhook row as stored in database
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct SecurityWebhookRow {
+    pub id: i32,
+    pub url: String,
+    pub secret: String,
+    pub last_error: Option<String>,
+    pub created_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct SecurityWebhookResponse {
+    pub id: i64,
+    pub url: String,
+    // Error of the last failed delivery, cleared by the next successful one
+    pub last_error: Option<String>,
+    #[serde(with = "rfc3339_option")]
+    pub created_at: Option<OffsetDateTime>,
+}
+
+impl From<SecurityWebhookRow> for SecurityWebhookResponse {
+    fn from(row: SecurityWebhookRow) -> Self {
+        Self {
+            id: i64::from(row.id),
+            url: row.url,
+            last_error: row.last_error,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct SecurityWebhookCreatedResponse {
+    pub id: i64,
+    pub url: String,
+    // Key of the payload signatures, only returned once
+    pub secret: String,
+    #[serde(with = "rfc3339_option")]
+    pub created_at: Option<OffsetDateTime>,
+}
+
+impl From<SecurityWebhookRow> for SecurityWebhookCreatedResponse {
+    fn from(row: SecurityWebhookRow) -> Self {
+        Self {
+            id: i64::from(row.id),
+            url: row.url,
+            secret: row.secret,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct SecurityWebhookMessageResponse {
+    pub message: String,
+}
+
+// Audit entry waiting for a webhook of its user, `new_device` is set for the logins from
+// a user agent never seen on the account
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct PendingSecurityEventRow {
+    pub webhook_id: i32,
+    pub url: String,
+    pub secret: String,
+    pub id: i32,
+    pub user_id: i32,
+    pub action: String,
+    pub ip_address: String,
+    pub user_agent: Option<String>,
+    pub created_at: Option<OffsetDateTime>,
+    pub new_device: bool,
+}
+
+// Type of the security events, a login from a new device is sent as `new_device`
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SecurityEventType {
+    Login,
+    NewDevice,
+    PasswordChanged,
+}
+
+impl SecurityEventType {
+    // Audit actions sent to the security webhooks
+    pub const ACTIONS: [AuditAction; 2] = [AuditAction::Login, AuditAction::PasswordChanged];
+}
+
+// Payload posted to the security webhooks
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct SecurityEvent {
+    // Id of the audit entry, consumers use it to drop redeliveries
+    pub id: i64,
+    #[serde(rename = "type")]
+    pub event_type: SecurityEventType,
+    pub user_id: i64,
+    pub ip_address: String,
+    pub user_agent: Option<String>,
+    #[serde(with = "rfc3339_option")]
+    pub occurred_at: Option<OffsetDateTime>,
+}
+
+impl From<&PendingSecurityEventRow> for SecurityEvent {
+    fn from(row: &PendingSecurityEventRow) -> Self {
+        let event_type = if row.action == AuditAction::PasswordChanged.as_str() {
+            SecurityEventType::PasswordChanged
+        } else if row.new_device {
+            SecurityEventType::NewDevice
+        } else {
+            SecurityEventType::Login
+        };
+
+        Self {
+            id: i64::from(row.id),
+            event_type,
+            user_id: i64::from(row.user_id),
+            ip_address: row.ip_address.clone(),
+            user_agent: row.user_agent.clone(),
+            occurred_at: row.created_at,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn pending(action: AuditAction, new_device: bool) -> PendingSecurityEventRow {
+        PendingSecurityEventRow {
+            webhook_id: 1,
+            url: "https://example.com/hooks".to_string(),
+            secret: "secret".to_string(),
+            id: 42,
+            user_id: 7,
+            action: action.as_str().to_string(),
+            ip_address: "203.0.113.9".to_string(),
+            user_agent: Some("curl/8.0".to_string()),
+            created_at: Some(time::macros::datetime!(2025-03-10 12:00 UTC)),
+            new_device,
+        }
+    }
+
+    #[test]
+    fn test_security_event_types() {
+        let event = SecurityEvent::from(&pending(AuditAction::Login, false));
+        assert_eq!(event.event_type, SecurityEventType::Login);
+        let event = SecurityEvent::from(&pending(AuditAction::Login, true));
+        assert_eq!(event.event_type, SecurityEventType::NewDevice);
+        let event = SecurityEvent::from(&pending(AuditAction::PasswordChanged, false));
+        assert_eq!(event.event_type, SecurityEventType::PasswordChanged);
+    }
+
+    #[test]
+    fn test_security_event_serialization() {
+        let event = SecurityEvent::from(&pending(AuditAction::Login, true));
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["id"], 42);
+        assert_eq!(json["type"], "new_device");
+        assert_eq!(json["occurred_at"], "2025-03-10T12:00:00Z");
+        assert!(json.get("secret").is_none());
+    }
+}
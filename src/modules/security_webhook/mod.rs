@@ -0,0 +1,10 @@
+//! # `Security Webhook` Mod
+//! Security webhook imports for the account-level webhooks receiving the security events
+
+pub mod dispatcher;
+pub mod interfaces;
+pub mod repository;
+pub mod routes;
+pub mod service;
+
+pub use routes::security_webhook_routes;
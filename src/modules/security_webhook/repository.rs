@@ -0,0 +1,161 @@
+//! # `Security Webhook` Repository
+//! This module defines the security webhook repository for the security webhook endpoints
+//! and dispatcher.
+
+use sqlx::{Error, Pool, Postgres};
+
+use crate::modules::{
+    common::to_db_id,
+    security_webhook::interfaces::{PendingSecurityEventRow, SecurityWebhookRow},
+};
+use crate::telemetry::observe_query;
+
+const WEBHOOK_COLUMNS: &str = "id, url, secret, last_error, created_at";
+
+pub struct SecurityWebhookRepository {
+    pool: Pool<Postgres>,
+}
+
+impl SecurityWebhookRepository {
+    // Constructor
+    pub const fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Register a webhook starting after the existing audit entries, `None` when the user
+    // already has `max_webhooks` webhooks
+    pub async fn create_webhook(
+        &self,
+        user_id: i64,
+        url: &str,
+        secret: &str,
+        max_webhooks: i64,
+    ) -> Result<Option<SecurityWebhookRow>, Error> {
+        observe_query("security_webhook.create_webhook", async move {
+            let user_id = to_db_id(user_id)?;
+            sqlx::query_as::<_, SecurityWebhookRow>(&format!(
+                "INSERT INTO security_webhooks (user_id, url, secret, last_audit_id)
+                 SELECT $1, $2, $3, (SELECT COALESCE(MAX(id), 0) FROM audit_log)
+                 WHERE (SELECT COUNT(*) FROM security_webhooks WHERE user_id = $1) < $4
+                 RETURNING {WEBHOOK_COLUMNS}"
+            ))
+            .bind(user_id)
+            .bind(url)
+            .bind(secret)
+            .bind(max_webhooks)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Webhooks of a user, the oldest first
+    pub async fn list_webhooks(&self, user_id: i64) -> Result<Vec<SecurityWebhookRow>, Error> {
+        observe_query("security_webhook.list_webhooks", async move {
+            sqlx::query_as::<_, SecurityWebhookRow>(&format!(
+                "SELECT {WEBHOOK_COLUMNS} FROM security_webhooks WHERE user_id = $1 ORDER BY id"
+            ))
+            .bind(to_db_id(user_id)?)
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Delete a webhook of the user, returns whether it existed
+    pub async fn delete_webhook(&self, user_id: i64, id: i64) -> Result<bool, Error> {
+        observe_query("security_webhook.delete_webhook", async move {
+            let result =
+                sqlx::query("DELETE FROM security_webhooks WHERE id = $1 AND user_id = $2")
+                    .bind(to_db_id(id)?)
+                    .bind(to_db_id(user_id)?)
+                    .execute(&self.pool)
+                    .await?;
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    // Audit entries waiting for a webhook of their user, up to `per_webhook` per webhook
+    // in the order they were recorded
+    pub async fn list_pending(
+        &self,
+        actions: &[&str],
+        per_webhook: i64,
+        limit: i64,
+    ) -> Result<Vec<PendingSecurityEventRow>, Error> {
+        observe_query("security_webhook.list_pending", async move {
+            sqlx::query_as::<_, PendingSecurityEventRow>(
+                "SELECT w.id AS webhook_id, w.url, w.secret, a.id, a.user_id, a.action,
+                    a.ip_address, a.user_agent, a.created_at,
+                    a.action = 'login' AND NOT EXISTS (
+                        SELECT 1 FROM audit_log p
+                        WHERE p.user_id = a.user_id AND p.action = 'login' AND p.id < a.id
+                          AND p.user_agent IS NOT DISTINCT FROM a.user_agent
+                    ) AS new_device
+                 FROM security_webhooks w
+                 JOIN LATERAL (
+                    SELECT id, user_id, action, ip_address, user_agent, created_at
+                    FROM audit_log
+                    WHERE user_id = w.user_id AND id > w.last_audit_id AND action = ANY($1)
+                    ORDER BY id
+                    LIMIT $2
+                 ) a ON TRUE
+                 ORDER BY w.id, a.id
+                 LIMIT $3",
+            )
+            .bind(actions)
+            .bind(per_webhook)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Move the webhook past a delivered audit entry
+    pub async fn mark_delivered(&self, webhook_id: i32, audit_id: i32) -> Result<(), Error> {
+        observe_query("security_webhook.mark_delivered", async move {
+            sqlx::query(
+                "UPDATE security_webhooks SET last_audit_id = $2, attempts = 0, last_error = NULL
+                 WHERE id = $1",
+            )
+            .bind(webhook_id)
+            .bind(audit_id)
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    // Count a failed delivery, the audit entry is skipped after `max_attempts` failures.
+    // Returns whether it was skipped.
+    pub async fn record_failure(
+        &self,
+        webhook_id: i32,
+        audit_id: i32,
+        error: &str,
+        max_attempts: i32,
+    ) -> Result<bool, Error> {
+        observe_query("security_webhook.record_failure", async move {
+            let skipped = sqlx::query_scalar::<_, bool>(
+                "UPDATE security_webhooks SET
+                    last_error = $3,
+                    last_audit_id = CASE WHEN attempts + 1 >= $4 THEN $2 ELSE last_audit_id END,
+                    attempts = CASE WHEN attempts + 1 >= $4 THEN 0 ELSE attempts + 1 END
+                 WHERE id = $1
+                 RETURNING last_audit_id = $2",
+            )
+            .bind(webhook_id)
+            .bind(audit_id)
+            .bind(error)
+            .bind(max_attempts)
+            .fetch_optional(&self.pool)
+            .await?;
+            // The webhook may have been deleted meanwhile
+            Ok(skipped.unwrap_or(true))
+        })
+        .await
+    }
+}
@@ -0,0 +1,128 @@
+//! #`Security Webhook` Routes
+//! This module defines the HTTP routes for the account-level security webhooks.
+
+use axum::extract::Path;
+use axum::routing::{delete, get};
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Router};
+
+use crate::auth::Claims;
+use crate::modules::common::{respond, ErrorResponse};
+use crate::modules::security_webhook::interfaces::{
+    CreateSecurityWebhookRequest, SecurityWebhookCreatedResponse, SecurityWebhookMessageResponse,
+    SecurityWebhookResponse,
+};
+use crate::modules::security_webhook::repository::SecurityWebhookRepository;
+use crate::modules::security_webhook::service::SecurityWebhookService;
+use crate::utils::json::Json;
+use crate::AppState;
+
+// Creates and returns the security webhook routes
+pub fn security_webhook_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/user/security-webhooks",
+            get(list_security_webhooks_route).post(create_security_webhook_route),
+        )
+        .route(
+            "/user/security-webhooks/{id}",
+            delete(delete_security_webhook_route),
+        )
+}
+
+fn security_webhook_service(app_state: &AppState) -> SecurityWebhookService {
+    SecurityWebhookService::new(SecurityWebhookRepository::new(app_state.db_pool.clone()))
+}
+
+// Create Security Webhook Route
+#[utoipa::path(
+    post,
+    path = "/user/security-webhooks",
+    tag = "Security Webhooks",
+    request_body = CreateSecurityWebhookRequest,
+    responses(
+        (status = 201, description = "Webhook registered, with the secret of its signatures", body = SecurityWebhookCreatedResponse),
+        (status = 400, description = "Invalid URL", body = ErrorResponse),
+        (status = 409, description = "Too many webhooks", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn create_security_webhook_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Json(create_request): Json<CreateSecurityWebhookRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::CREATED,
+        security_webhook_service(&app_state)
+            .create_webhook(claims.user_id, create_request)
+            .await,
+    )
+}
+
+// List Security Webhooks Route
+#[utoipa::path(
+    get,
+    path = "/user/security-webhooks",
+    tag = "Security Webhooks",
+    responses(
+        (status = 200, description = "Webhooks of the user, the oldest first", body = [SecurityWebhookResponse]),
+        (status = 500, description = "Failed to list security webhooks", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn list_security_webhooks_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        security_webhook_service(&app_state)
+            .list_webhooks(claims.user_id)
+            .await,
+    )
+}
+
+// Delete Security Webhook Route
+#[utoipa::path(
+    delete,
+    path = "/user/security-webhooks/{id}",
+    tag = "Security Webhooks",
+    params(
+        ("id" = i64, Path, description = "Security webhook id")
+    ),
+    responses(
+        (status = 200, description = "Webhook deleted", body = SecurityWebhookMessageResponse),
+        (status = 404, description = "Security webhook not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn delete_security_webhook_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        security_webhook_service(&app_state)
+            .delete_webhook(claims.user_id, id)
+            .await,
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_security_webhook_routes_creation() {
+        let _routes = security_webhook_routes();
+        assert!(true);
+    }
+}
@@ -0,0 +1,185 @@
+//! # `Security Webhook` Service
+//!
+//! This module contains the bussiness logic for the account-level security webhooks. The
+//! logins, logins from a new device and password changes of the user are posted to them
+//! by the dispatcher, signed with the secret returned at creation.
+
+use std::net::IpAddr;
+
+use reqwest::Url;
+
+use crate::modules::{
+    common::AppError,
+    security_webhook::{
+        interfaces::{
+            CreateSecurityWebhookRequest, SecurityWebhookCreatedResponse,
+            SecurityWebhookMessageResponse, SecurityWebhookResponse,
+        },
+        repository::SecurityWebhookRepository,
+    },
+};
+use crate::utils::token::generate_random_token;
+
+// Webhooks a user can register
+const MAX_WEBHOOKS_PER_USER: i64 = 5;
+
+// Longest webhook URL
+const MAX_URL_LENGTH: usize = 2048;
+
+// Whether an address is reachable from the public internet, so webhooks can't be used to
+// reach the services next to the application
+const fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast())
+        }
+        IpAddr::V6(ip) => !(ip.is_loopback() || ip.is_unspecified()),
+    }
+}
+
+// Webhooks must be public HTTPS endpoints
+fn validate_url(url: &str) -> Result<Url, String> {
+    if url.len() > MAX_URL_LENGTH {
+        return Err(format!(
+            "Webhook URL must be at most {MAX_URL_LENGTH} characters"
+        ));
+    }
+    let url = Url::parse(url).map_err(|_| "Invalid webhook URL".to_string())?;
+    if url.scheme() != "https" {
+        return Err("Webhook URL must use https".to_string());
+    }
+    // IPv6 hosts keep their brackets
+    let public = url
+        .host_str()
+        .map(|host| host.trim_matches(['[', ']']))
+        .is_some_and(|host| {
+            host.parse::<IpAddr>().map_or_else(
+                |_| {
+                    let domain = host.trim_end_matches('.').to_ascii_lowercase();
+                    domain != "localhost" && !domain.ends_with(".localhost")
+                },
+                is_public_ip,
+            )
+        });
+    if !public {
+        return Err("Webhook URL must be a public address".to_string());
+    }
+    Ok(url)
+}
+
+pub struct SecurityWebhookService {
+    security_webhook_repository: SecurityWebhookRepository,
+}
+
+impl SecurityWebhookService {
+    pub const fn new(security_webhook_repository: SecurityWebhookRepository) -> Self {
+        Self {
+            security_webhook_repository,
+        }
+    }
+
+    // Register a webhook, its secret is only returned here
+    pub async fn create_webhook(
+        &self,
+        user_id: i64,
+        create_request: CreateSecurityWebhookRequest,
+    ) -> Result<SecurityWebhookCreatedResponse, AppError> {
+        let Some(url) = create_request
+            .url
+            .as_deref()
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+        else {
+            return Err(AppError::Validation(
+                "Missing required fields: url".to_string(),
+            ));
+        };
+        let url = validate_url(url).map_err(AppError::Validation)?;
+
+        match self
+            .security_webhook_repository
+            .create_webhook(
+                user_id,
+                url.as_str(),
+                &generate_random_token(),
+                MAX_WEBHOOKS_PER_USER,
+            )
+            .await
+        {
+            Ok(Some(webhook)) => Ok(SecurityWebhookCreatedResponse::from(webhook)),
+            Ok(None) => Err(AppError::Conflict(format!(
+                "At most {MAX_WEBHOOKS_PER_USER} security webhooks can be registered"
+            ))),
+            Err(e) => {
+                tracing::warn!("Error creating security webhook: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Webhooks of the user
+    pub async fn list_webhooks(
+        &self,
+        user_id: i64,
+    ) -> Result<Vec<SecurityWebhookResponse>, AppError> {
+        match self
+            .security_webhook_repository
+            .list_webhooks(user_id)
+            .await
+        {
+            Ok(webhooks) => Ok(webhooks
+                .into_iter()
+                .map(SecurityWebhookResponse::from)
+                .collect()),
+            Err(e) => {
+                tracing::warn!("Error listing security webhooks: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Delete a webhook of the user
+    pub async fn delete_webhook(
+        &self,
+        user_id: i64,
+        id: i64,
+    ) -> Result<SecurityWebhookMessageResponse, AppError> {
+        match self
+            .security_webhook_repository
+            .delete_webhook(user_id, id)
+            .await
+        {
+            Ok(true) => Ok(SecurityWebhookMessageResponse {
+                message: "Security webhook deleted".to_string(),
+            }),
+            Ok(false) => Err(AppError::NotFound("Security webhook not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error deleting security webhook: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_url() {
+        assert!(validate_url("https://siem.example.com/hooks/security").is_ok());
+        assert!(validate_url("https://203.0.113.9/hooks").is_ok());
+        assert!(validate_url("http://siem.example.com/hooks").is_err());
+        assert!(validate_url("not a url").is_err());
+        assert!(validate_url("https://localhost:8080/hooks").is_err());
+        assert!(validate_url("https://api.localhost/hooks").is_err());
+        assert!(validate_url("https://127.0.0.1/hooks").is_err());
+        assert!(validate_url("https://10.0.0.4/hooks").is_err());
+        assert!(validate_url("https://169.254.169.254/latest").is_err());
+        assert!(validate_url("https://[::1]/hooks").is_err());
+    }
+}
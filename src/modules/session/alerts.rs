@@ -0,0 +1,302 @@
+//! # Login Alerts
+//! Emails sent when an account signs in from a device it never used. Devices are
+//! remembered by a fingerprint of their user agent and address network, the location of
+//! the address comes from the geolocation API configured with the `GEOIP_*` variables.
+
+use std::{net::IpAddr, sync::Arc, time::Duration};
+
+use reqwest::Client;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::modules::{
+    email::{mailer::Mailer, templates::LOGIN_ALERT},
+    session::interfaces::{LoginAlertRow, SessionDevice},
+};
+use crate::tasks::BackgroundTasks;
+use crate::utils::format::Formatter;
+
+const DEFAULT_GEOIP_TIMEOUT_SECONDS: u64 = 3;
+
+// Location shown when the address can't be located
+const UNKNOWN_LOCATION: &str = "Unknown location";
+
+// Fingerprint of a device, a hash of its user agent and the network of its address so a
+// new address from the same network doesn't count as a new device
+pub fn device_fingerprint(device: &SessionDevice) -> String {
+    let network = match device.ip_address.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => {
+            let [a, b, c, _] = ip.octets();
+            format!("{a}.{b}.{c}.0/24")
+        }
+        Ok(IpAddr::V6(ip)) => {
+            let [a, b, c, d, ..] = ip.segments();
+            format!("{a:x}:{b:x}:{c:x}:{d:x}::/64")
+        }
+        Err(_) => device.ip_address.clone(),
+    };
+
+    let mut hasher = Sha256::new();
+    hasher.update(device.user_agent.as_deref().unwrap_or_default().as_bytes());
+    hasher.update(b"\n");
+    hasher.update(network.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+// Whether the address belongs to a local network, which can't be located
+const fn is_local_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            ip.is_private() || ip.is_loopback() || ip.is_link_local() || ip.is_unspecified()
+        }
+        IpAddr::V6(ip) => {
+            let first = ip.segments()[0];
+            ip.is_loopback()
+                || ip.is_unspecified()
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+// Time of the login as the account reads it, e.g. `03/10/2025 2:05 PM (America/Chicago)`
+fn login_time(recipient: &LoginAlertRow) -> String {
+    Formatter::new(&recipient.locale, recipient.time_format.as_deref())
+        .zoned_datetime(recipient.local_time, &recipient.timezone)
+}
+
+// Answer of the geolocation API, the names are preferred to the codes
+#[derive(Deserialize, Debug, Default)]
+struct GeoIpResponse {
+    city: Option<String>,
+    region: Option<String>,
+    #[serde(rename = "regionName")]
+    region_name: Option<String>,
+    country: Option<String>,
+    country_name: Option<String>,
+}
+
+impl GeoIpResponse {
+    // "City, Region, Country", without the missing parts
+    fn location(self) -> Option<String> {
+        let parts: Vec<String> = [
+            self.city,
+            self.region_name.or(self.region),
+            self.country_name.or(self.country),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|part| part.trim().to_string())
+        .filter(|part| !part.is_empty())
+        .collect();
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+}
+
+// Geolocation API answering JSON for the URL with the address in place of `{ip}`
+pub struct GeoIp {
+    client: Client,
+    url: String,
+}
+
+impl GeoIp {
+    pub fn new(url: &str, timeout: Duration) -> Result<Self, String> {
+        if !url.contains("{ip}") {
+            return Err("GEOIP_API_URL must contain the {ip} placeholder".to_string());
+        }
+        let client = Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| format!("Failed to create geolocation client: {e}"))?;
+        Ok(Self {
+            client,
+            url: url.to_string(),
+        })
+    }
+
+    // Approximate location of a public address
+    pub async fn locate(&self, ip: IpAddr) -> Result<Option<String>, String> {
+        if is_local_address(ip) {
+            return Ok(None);
+        }
+        #[allow(clippy::literal_string_with_formatting_args)]
+        let answer = self
+            .client
+            .get(self.url.replace("{ip}", &ip.to_string()))
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| e.to_string())?
+            .json::<GeoIpResponse>()
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(answer.location())
+    }
+}
+
+// Geolocation API configured in the environment, when `GEOIP_API_URL` is set
+pub fn geoip_from_env() -> Result<Option<Arc<GeoIp>>, String> {
+    let Some(url) = std::env::var("GEOIP_API_URL")
+        .ok()
+        .filter(|url| !url.trim().is_empty())
+    else {
+        return Ok(None);
+    };
+    let timeout = std::env::var("GEOIP_TIMEOUT_SECONDS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_GEOIP_TIMEOUT_SECONDS);
+
+    Ok(Some(Arc::new(GeoIp::new(
+        url.trim(),
+        Duration::from_secs(timeout),
+    )?)))
+}
+
+// Sender of the new device alerts
+#[derive(Clone)]
+pub struct LoginAlerts {
+    mailer: Arc<dyn Mailer>,
+    geoip: Option<Arc<GeoIp>>,
+    tasks: BackgroundTasks,
+}
+
+impl LoginAlerts {
+    pub const fn new(
+        mailer: Arc<dyn Mailer>,
+        geoip: Option<Arc<GeoIp>>,
+        tasks: BackgroundTasks,
+    ) -> Self {
+        Self {
+            mailer,
+            geoip,
+            tasks,
+        }
+    }
+
+    // Email the account about a login from a new device, without waiting for the
+    // location nor the delivery
+    pub fn send(&self, recipient: LoginAlertRow, device: SessionDevice) {
+        let mailer = self.mailer.clone();
+        let geoip = self.geoip.clone();
+        self.tasks.spawn(async move {
+            let location = match (geoip, device.ip_address.parse::<IpAddr>()) {
+                (Some(geoip), Ok(ip)) => geoip.locate(ip).await.unwrap_or_else(|e| {
+                    tracing::warn!("Error locating {}: {}", ip, e);
+                    None
+                }),
+                _ => None,
+            };
+            let time = login_time(&recipient);
+            let message = LOGIN_ALERT.render(
+                recipient.email,
+                &[
+                    ("username", recipient.username.as_str()),
+                    ("time", time.as_str()),
+                    ("ip_address", device.ip_address.as_str()),
+                    ("location", location.as_deref().unwrap_or(UNKNOWN_LOCATION)),
+                    (
+                        "user_agent",
+                        device.user_agent.as_deref().unwrap_or("Unknown device"),
+                    ),
+                ],
+            );
+            if let Err(e) = mailer.send(&message).await {
+                tracing::error!("Email to {} not sent: {}", message.to, e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn device(user_agent: &str, ip_address: &str) -> SessionDevice {
+        SessionDevice {
+            user_agent: Some(user_agent.to_string()),
+            ip_address: ip_address.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_login_time() {
+        let recipient = LoginAlertRow {
+            username: "ana".to_string(),
+            email: "ana@example.com".to_string(),
+            local_time: time::macros::datetime!(2025-03-10 14:05),
+            timezone: "Europe/Lisbon".to_string(),
+            locale: "en-GB".to_string(),
+            time_format: Some("12h".to_string()),
+        };
+        assert_eq!(login_time(&recipient), "10/03/2025 2:05 PM (Europe/Lisbon)");
+    }
+
+    #[test]
+    fn test_device_fingerprint() {
+        let fingerprint = device_fingerprint(&device("curl/8.0", "203.0.113.9"));
+        assert_eq!(fingerprint.len(), 64);
+
+        // Same network, same device
+        assert_eq!(
+            fingerprint,
+            device_fingerprint(&device("curl/8.0", "203.0.113.200"))
+        );
+        assert_eq!(
+            device_fingerprint(&device("curl/8.0", "2001:db8:1:2::1")),
+            device_fingerprint(&device("curl/8.0", "2001:db8:1:2:ffff::9"))
+        );
+
+        assert_ne!(
+            fingerprint,
+            device_fingerprint(&device("curl/8.0", "203.0.114.9"))
+        );
+        assert_ne!(
+            fingerprint,
+            device_fingerprint(&device("Firefox/130.0", "203.0.113.9"))
+        );
+    }
+
+    #[test]
+    fn test_is_local_address() {
+        for ip in [
+            "10.0.0.1",
+            "192.168.1.1",
+            "127.0.0.1",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+        ] {
+            assert!(is_local_address(ip.parse().unwrap()), "{ip}");
+        }
+        for ip in ["203.0.113.9", "2001:db8::1"] {
+            assert!(!is_local_address(ip.parse().unwrap()), "{ip}");
+        }
+    }
+
+    #[test]
+    fn test_geoip_location() {
+        let answer: GeoIpResponse = serde_json::from_str(
+            r#"{"city": "Lisbon", "region": "11", "regionName": "Lisbon", "country": "Portugal"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            answer.location().as_deref(),
+            Some("Lisbon, Lisbon, Portugal")
+        );
+
+        let answer: GeoIpResponse =
+            serde_json::from_str(r#"{"city": " ", "country": "PT", "country_name": "Portugal"}"#)
+                .unwrap();
+        assert_eq!(answer.location().as_deref(), Some("Portugal"));
+
+        assert!(GeoIpResponse::default().location().is_none());
+    }
+
+    #[test]
+    fn test_geoip_requires_placeholder() {
+        assert!(GeoIp::new("https://geo.example.com/json", Duration::from_secs(1)).is_err());
+        assert!(GeoIp::new("https://geo.example.com/{ip}/json", Duration::from_secs(1)).is_ok());
+    }
+}
@@ -0,0 +1,95 @@
+//! # `Session` Interfaces
+//! This module defines the data structures from Sessions module
+
+use serde::{Deserialize, Serialize};
+use time::{OffsetDateTime, PrimitiveDateTime};
+use utoipa::ToSchema;
+
+use crate::utils::dates::{rfc3339, rfc3339_option};
+
+// Device a session is opened from, recorded on login
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionDevice {
+    pub user_agent: Option<String>,
+    pub ip_address: String,
+}
+
+// Account signing in from a new device, recipient of the login alert
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct LoginAlertRow {
+    pub username: String,
+    pub email: String,
+    // Time of the login in the timezone of the account, and how to write it
+    pub local_time: PrimitiveDateTime,
+    pub timezone: String,
+    pub locale: String,
+    pub time_format: Option<String>,
+}
+
+// Session row as stored in database
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct SessionRow {
+    pub id: i32,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: Option<OffsetDateTime>,
+    pub last_used_at: OffsetDateTime,
+    pub expires_at: OffsetDateTime,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct SessionResponse {
+    pub id: i64,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    #[serde(with = "rfc3339_option")]
+    pub created_at: Option<OffsetDateTime>,
+    // Last request made with the session, updated at most once a minute
+    #[serde(with = "rfc3339")]
+    pub last_used_at: OffsetDateTime,
+    #[serde(with = "rfc3339")]
+    pub expires_at: OffsetDateTime,
+    // Whether this is the session of the request
+    pub current: bool,
+}
+
+impl SessionResponse {
+    pub fn from_row(row: SessionRow, current_session_id: Option<i64>) -> Self {
+        let id = i64::from(row.id);
+        Self {
+            id,
+            user_agent: row.user_agent,
+            ip_address: row.ip_address,
+            created_at: row.created_at,
+            last_used_at: row.last_used_at,
+            expires_at: row.expires_at,
+            current: current_session_id == Some(id),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct SessionMessageResponse {
+    pub message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_response_flags_current_session() {
+        let row = SessionRow {
+            id: 3,
+            user_agent: Some("curl/8.0".to_string()),
+            ip_address: Some("203.0.113.9".to_string()),
+            created_at: None,
+            last_used_at: OffsetDateTime::UNIX_EPOCH,
+            expires_at: OffsetDateTime::UNIX_EPOCH,
+        };
+
+        assert!(SessionResponse::from_row(row.clone(), Some(3)).current);
+        assert!(!SessionResponse::from_row(row.clone(), Some(4)).current);
+        assert!(!SessionResponse::from_row(row, None).current);
+    }
+}
@@ -0,0 +1,10 @@
+//! # `Session` Mod
+//! Session imports for the devices signed in to an account
+
+pub mod alerts;
+pub mod interfaces;
+pub mod repository;
+pub mod routes;
+pub mod service;
+
+pub use routes::session_routes;
@@ -0,0 +1,198 @@
+//! # `Session` Repository
+//! This module defines the session repository for the signed in devices operations.
+
+use sqlx::{Error, PgConnection, Pool, Postgres};
+use time::OffsetDateTime;
+
+use crate::modules::{
+    common::to_db_id,
+    preference::interfaces::DEFAULT_TIMEZONE,
+    session::interfaces::{LoginAlertRow, SessionDevice, SessionRow},
+};
+use crate::telemetry::observe_query;
+use crate::utils::format::DEFAULT_LOCALE;
+
+const SESSION_COLUMNS: &str = "id, user_agent, ip_address, created_at, last_used_at, expires_at";
+
+pub struct SessionRepository {
+    pool: Pool<Postgres>,
+}
+
+impl SessionRepository {
+    // Constructor
+    pub const fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Open a session of the user, dropping its expired sessions. Returns the session id,
+    // `None` when the account is disabled.
+    pub async fn create_session(
+        &self,
+        user_id: i64,
+        device: &SessionDevice,
+        expires_at: OffsetDateTime,
+    ) -> Result<Option<i64>, Error> {
+        observe_query("session.create_session", async move {
+            let user_id = to_db_id(user_id)?;
+            let mut tx = self.pool.begin().await?;
+
+            sqlx::query("DELETE FROM sessions WHERE user_id = $1 AND expires_at <= NOW()")
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+            let id = sqlx::query_scalar::<_, i32>(
+                "INSERT INTO sessions (user_id, user_agent, ip_address, expires_at)
+                 SELECT id, $2, $3, $4 FROM users WHERE id = $1 AND active
+                 RETURNING id",
+            )
+            .bind(user_id)
+            .bind(&device.user_agent)
+            .bind(&device.ip_address)
+            .bind(expires_at)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            Ok(id.map(i64::from))
+        })
+        .await
+    }
+
+    // Remember the device of a login, by its fingerprint. Returns the account to alert
+    // when the device is new, `None` for the first device of the account, which is
+    // remembered silently, and for unverified or guest accounts.
+    pub async fn remember_device(
+        &self,
+        user_id: i64,
+        fingerprint: &str,
+    ) -> Result<Option<LoginAlertRow>, Error> {
+        observe_query("session.remember_device", async move {
+            sqlx::query_as::<_, LoginAlertRow>(&format!(
+                "WITH previous AS (
+                     SELECT EXISTS (SELECT 1 FROM known_devices WHERE user_id = $1) AS known
+                 ), remembered AS (
+                     INSERT INTO known_devices (user_id, fingerprint)
+                     VALUES ($1, $2)
+                     ON CONFLICT (user_id, fingerprint) DO UPDATE SET last_seen_at = NOW()
+                     RETURNING (xmax = 0) AS inserted
+                 )
+                 SELECT u.username, u.email,
+                    NOW() AT TIME ZONE COALESCE(p.timezone, '{DEFAULT_TIMEZONE}') AS local_time,
+                    COALESCE(p.timezone, '{DEFAULT_TIMEZONE}') AS timezone,
+                    COALESCE(p.locale, '{DEFAULT_LOCALE}') AS locale, p.time_format
+                 FROM users u
+                 CROSS JOIN previous
+                 CROSS JOIN remembered
+                 LEFT JOIN user_preferences p ON p.user_id = u.id
+                 WHERE u.id = $1 AND remembered.inserted AND previous.known
+                   AND u.email_verified_at IS NOT NULL AND NOT u.is_guest"
+            ))
+            .bind(to_db_id(user_id)?)
+            .bind(fingerprint)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Record the use of a session by a token valid until `expires_at`, the session lasts
+    // as long as its latest token. The use is written at most once a minute.
+    // Returns false when the session was revoked.
+    pub async fn touch_session(
+        &self,
+        id: i64,
+        user_id: i64,
+        expires_at: OffsetDateTime,
+    ) -> Result<bool, Error> {
+        observe_query("session.touch_session", async move {
+            sqlx::query_scalar::<_, bool>(
+                "WITH active AS (
+                     SELECT id FROM sessions WHERE id = $1 AND user_id = $2
+                 ), touched AS (
+                     UPDATE sessions
+                     SET last_used_at = NOW(), expires_at = GREATEST(expires_at, $3)
+                     WHERE id IN (SELECT id FROM active)
+                       AND (last_used_at < NOW() - INTERVAL '1 minute' OR expires_at < $3)
+                 )
+                 SELECT EXISTS (SELECT 1 FROM active)",
+            )
+            .bind(to_db_id(id)?)
+            .bind(to_db_id(user_id)?)
+            .bind(expires_at)
+            .fetch_one(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Unexpired sessions of the user, most recently used first
+    pub async fn list_sessions(&self, user_id: i64) -> Result<Vec<SessionRow>, Error> {
+        observe_query("session.list_sessions", async move {
+            let query = format!(
+                "SELECT {SESSION_COLUMNS} FROM sessions
+                 WHERE user_id = $1 AND expires_at > NOW()
+                 ORDER BY last_used_at DESC, id DESC"
+            );
+
+            sqlx::query_as::<_, SessionRow>(&query)
+                .bind(to_db_id(user_id)?)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Revoke a session of the user, returns false when the user has no such session
+    pub async fn delete_session(&self, user_id: i64, id: i64) -> Result<bool, Error> {
+        observe_query("session.delete_session", async move {
+            let result = sqlx::query("DELETE FROM sessions WHERE id = $1 AND user_id = $2")
+                .bind(to_db_id(id)?)
+                .bind(to_db_id(user_id)?)
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    // Revoke every session of the user but `keep` within the transaction of the caller,
+    // returns how many were revoked
+    pub async fn delete_other_sessions(
+        conn: &mut PgConnection,
+        user_id: i64,
+        keep: Option<i64>,
+    ) -> Result<u64, Error> {
+        observe_query("session.delete_other_sessions", async move {
+            let keep = keep.map(to_db_id).transpose()?;
+            let result =
+                sqlx::query("DELETE FROM sessions WHERE user_id = $1 AND id IS DISTINCT FROM $2")
+                    .bind(to_db_id(user_id)?)
+                    .bind(keep)
+                    .execute(conn)
+                    .await?;
+            Ok(result.rows_affected())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_columns_match_row() {
+        let columns: Vec<&str> = SESSION_COLUMNS.split(", ").collect();
+        assert_eq!(
+            columns,
+            [
+                "id",
+                "user_agent",
+                "ip_address",
+                "created_at",
+                "last_used_at",
+                "expires_at"
+            ]
+        );
+    }
+}
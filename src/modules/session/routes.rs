@@ -0,0 +1,139 @@
+//! #`Session` Routes
+//! This module defines the HTTP routes for the signed in devices of a user.
+
+use axum::routing::{delete, get, post};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json, Router,
+};
+use axum_extra::extract::CookieJar;
+
+use crate::auth::{cookie::end_cookie_session, Claims};
+use crate::modules::audit::repository::AuditRepository;
+use crate::modules::audit::service::AuditService;
+use crate::modules::common::{respond, ErrorResponse};
+use crate::modules::session::interfaces::{SessionMessageResponse, SessionResponse};
+use crate::modules::session::repository::SessionRepository;
+use crate::modules::session::service::SessionService;
+use crate::AppState;
+
+// Creates and returns the session routes
+pub fn session_routes() -> Router<AppState> {
+    Router::new()
+        .route("/auth/sessions", get(list_sessions_route))
+        .route("/auth/sessions/{id}", delete(revoke_session_route))
+        .route("/auth/logout", post(logout_route))
+}
+
+fn session_service(app_state: &AppState) -> SessionService {
+    SessionService::new(
+        SessionRepository::new(app_state.db_pool.clone()),
+        AuditService::new(AuditRepository::new(app_state.db_pool.clone())),
+        app_state.login_alerts.clone(),
+    )
+}
+
+// List Sessions Route
+#[utoipa::path(
+    get,
+    path = "/auth/sessions",
+    tag = "Sessions",
+    responses(
+        (status = 200, description = "Signed in devices of the user", body = Vec<SessionResponse>),
+        (status = 500, description = "Failed to list sessions", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn list_sessions_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        session_service(&app_state)
+            .list_sessions(claims.user_id, claims.session_id)
+            .await,
+    )
+}
+
+// Revoke Session Route
+#[utoipa::path(
+    delete,
+    path = "/auth/sessions/{id}",
+    tag = "Sessions",
+    params(
+        ("id" = i64, Path, description = "Session id")
+    ),
+    responses(
+        (status = 200, description = "Session revoked, its device is signed out", body = SessionMessageResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn revoke_session_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        session_service(&app_state)
+            .revoke_session(claims.user_id, id)
+            .await,
+    )
+}
+
+// Logout Route
+#[utoipa::path(
+    post,
+    path = "/auth/logout",
+    tag = "Sessions",
+    responses(
+        (status = 200, description = "Session of the token revoked and session cookies removed", body = SessionMessageResponse),
+        (status = 403, description = "Session cookie sent without its CSRF token", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = []),
+        ("session_cookie" = [])
+    )
+)]
+pub async fn logout_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    jar: CookieJar,
+) -> impl IntoResponse {
+    // Tokens issued without a session expire on their own
+    let revoked = match claims.session_id {
+        Some(id) => {
+            session_service(&app_state)
+                .revoke_session(claims.user_id, id)
+                .await
+        }
+        None => Ok(SessionMessageResponse {
+            message: "Logged out".to_string(),
+        }),
+    };
+    match revoked {
+        Ok(response) => (StatusCode::OK, end_cookie_session(jar), Json(response)).into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_routes_creation() {
+        let _routes = session_routes();
+        assert!(true);
+    }
+}
@@ -0,0 +1,218 @@
+//! # `Session` Service
+//!
+//! This module contains the bussiness logic for the signed in devices of a user.
+
+use std::{convert::Infallible, net::IpAddr};
+
+use axum::{
+    extract::FromRequestParts,
+    http::{header::USER_AGENT, request::Parts},
+};
+use jsonwebtoken::EncodingKey;
+use time::{Duration, OffsetDateTime};
+
+use crate::auth::generate_token;
+use crate::modules::{
+    audit::{
+        interfaces::{AuditAction, NewAuditEntry},
+        service::AuditService,
+    },
+    common::AppError,
+    session::{
+        alerts::{device_fingerprint, LoginAlerts},
+        interfaces::{SessionDevice, SessionMessageResponse, SessionResponse},
+        repository::SessionRepository,
+    },
+};
+use crate::utils::client_ip::ClientIp;
+use crate::AppState;
+
+// Longest user agent kept for a session
+const MAX_USER_AGENT_CHARS: usize = 512;
+
+// Device of a login request, from its user agent header and client address
+pub fn session_device(user_agent: Option<&str>, ip_address: IpAddr) -> SessionDevice {
+    SessionDevice {
+        user_agent: user_agent
+            .map(str::trim)
+            .filter(|user_agent| !user_agent.is_empty())
+            .map(|user_agent| user_agent.chars().take(MAX_USER_AGENT_CHARS).collect()),
+        ip_address: ip_address.to_string(),
+    }
+}
+
+// Device of the request, for the routes recording it in the audit log
+impl FromRequestParts<AppState> for SessionDevice {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let ClientIp(client_ip) = ClientIp::from_request_parts(parts, state).await?;
+        Ok(session_device(
+            parts
+                .headers
+                .get(USER_AGENT)
+                .and_then(|value| value.to_str().ok()),
+            client_ip,
+        ))
+    }
+}
+
+pub struct SessionService {
+    session_repository: SessionRepository,
+    audit_service: AuditService,
+    login_alerts: LoginAlerts,
+}
+
+impl SessionService {
+    pub const fn new(
+        session_repository: SessionRepository,
+        audit_service: AuditService,
+        login_alerts: LoginAlerts,
+    ) -> Self {
+        Self {
+            session_repository,
+            audit_service,
+            login_alerts,
+        }
+    }
+
+    // Record a failed login in the audit log, `user_id` is `None` for unknown users
+    pub async fn record_failed_login(
+        &self,
+        user_id: Option<i64>,
+        device: &SessionDevice,
+        reason: &str,
+    ) {
+        self.audit_service
+            .record(NewAuditEntry {
+                user_id,
+                actor_id: user_id,
+                action: AuditAction::LoginFailed,
+                device,
+                details: Some(reason.to_string()),
+            })
+            .await;
+    }
+
+    // Open a session of the device, returns its token
+    pub async fn open_session(
+        &self,
+        user_id: i64,
+        device: &SessionDevice,
+        amr: Vec<String>,
+        encoding_key: &EncodingKey,
+        session_duration: i64,
+    ) -> Result<String, AppError> {
+        let expires_at = OffsetDateTime::now_utc() + Duration::minutes(session_duration);
+        let session_id = match self
+            .session_repository
+            .create_session(user_id, device, expires_at)
+            .await
+        {
+            Ok(Some(session_id)) => session_id,
+            Ok(None) => {
+                tracing::warn!("Login of disabled user {}", user_id);
+                self.record_failed_login(Some(user_id), device, "account disabled")
+                    .await;
+                return Err(AppError::Forbidden("Account disabled".to_string()));
+            }
+            Err(e) => {
+                tracing::warn!("Error opening session: {}", e);
+                return Err(AppError::Database(e));
+            }
+        };
+
+        self.audit_service
+            .record(NewAuditEntry {
+                user_id: Some(user_id),
+                actor_id: Some(user_id),
+                action: AuditAction::Login,
+                device,
+                details: Some(amr.join(",")),
+            })
+            .await;
+        self.alert_new_device(user_id, device).await;
+
+        generate_token(session_duration, user_id, session_id, amr, encoding_key)
+    }
+
+    // Remember the device of a login, emailing the user when it's new. Failures are
+    // logged, they don't fail the login.
+    async fn alert_new_device(&self, user_id: i64, device: &SessionDevice) {
+        match self
+            .session_repository
+            .remember_device(user_id, &device_fingerprint(device))
+            .await
+        {
+            Ok(Some(recipient)) => {
+                self.login_alerts.send(recipient, device.clone());
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Error remembering device of user {}: {}", user_id, e),
+        }
+    }
+
+    // Sessions of the user, flagging the one of the request
+    pub async fn list_sessions(
+        &self,
+        user_id: i64,
+        current_session_id: Option<i64>,
+    ) -> Result<Vec<SessionResponse>, AppError> {
+        match self.session_repository.list_sessions(user_id).await {
+            Ok(rows) => Ok(rows
+                .into_iter()
+                .map(|row| SessionResponse::from_row(row, current_session_id))
+                .collect()),
+            Err(e) => {
+                tracing::warn!("Error listing sessions: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Revoke a session of the user, its tokens are rejected from now on
+    pub async fn revoke_session(
+        &self,
+        user_id: i64,
+        id: i64,
+    ) -> Result<SessionMessageResponse, AppError> {
+        match self.session_repository.delete_session(user_id, id).await {
+            Ok(true) => Ok(SessionMessageResponse {
+                message: "Session revoked".to_string(),
+            }),
+            Ok(false) => Err(AppError::NotFound("Session not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error revoking session: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_device() {
+        let ip: IpAddr = "203.0.113.9".parse().unwrap();
+
+        let device = session_device(Some(" curl/8.0 "), ip);
+        assert_eq!(device.user_agent.as_deref(), Some("curl/8.0"));
+        assert_eq!(device.ip_address, "203.0.113.9");
+
+        assert!(session_device(Some("  "), ip).user_agent.is_none());
+        assert!(session_device(None, ip).user_agent.is_none());
+
+        let long = "a".repeat(1000);
+        let device = session_device(Some(&long), ip);
+        assert_eq!(
+            device.user_agent.map(|user_agent| user_agent.len()),
+            Some(MAX_USER_AGENT_CHARS)
+        );
+    }
+}
@@ -0,0 +1,128 @@
+//! # `Sync` Hub
+//! In-memory rooms of the sync sockets, one per user. The todo events of the outbox wake
+//! the sockets of their user, which then pull the changes since their cursor. Wake-ups
+//! carry no data, a socket lagging behind loses nothing and pulls once.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use tokio::sync::broadcast;
+
+use crate::modules::outbox::interfaces::OutboxMessage;
+
+// Wake-ups buffered per user, a lagging socket pulls once for all of them
+const CHANGE_BUFFER: usize = 16;
+
+struct UserRoom {
+    // Workspaces of the changed todos
+    changes: broadcast::Sender<i64>,
+    sockets: usize,
+}
+
+#[derive(Clone, Default)]
+pub struct SyncHub {
+    rooms: Arc<Mutex<HashMap<i64, UserRoom>>>,
+}
+
+impl SyncHub {
+    fn rooms(&self) -> MutexGuard<'_, HashMap<i64, UserRoom>> {
+        self.rooms.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // Add a socket of the user, returns the workspaces whose todos change from now on
+    pub fn join(&self, user_id: i64) -> broadcast::Receiver<i64> {
+        let mut rooms = self.rooms();
+        let room = rooms.entry(user_id).or_insert_with(|| UserRoom {
+            changes: broadcast::channel(CHANGE_BUFFER).0,
+            sockets: 0,
+        });
+        room.sockets += 1;
+        let changes = room.changes.subscribe();
+        drop(rooms);
+        changes
+    }
+
+    // Remove a socket of the user, the room goes with the last one
+    pub fn leave(&self, user_id: i64) {
+        let mut rooms = self.rooms();
+        if let Some(room) = rooms.get_mut(&user_id) {
+            room.sockets = room.sockets.saturating_sub(1);
+            if room.sockets == 0 {
+                rooms.remove(&user_id);
+            }
+        }
+    }
+
+    // Wake the sockets of the user of a todo event
+    pub fn todo_event(&self, message: &OutboxMessage) {
+        if !message.event_type.starts_with("todo.") {
+            return;
+        }
+        if let Some(room) = self.rooms().get(&message.user_id) {
+            // Sending only fails once the sockets closed
+            if room.changes.send(message.workspace_id).is_err() {
+                tracing::debug!("Todo event {} without sync sockets", message.id);
+            }
+        }
+    }
+
+    // Forward the in-process domain events to the sync sockets until the sender closes
+    pub async fn forward_events(self, mut events: broadcast::Receiver<OutboxMessage>) {
+        loop {
+            match events.recv().await {
+                Ok(message) => self.todo_event(&message),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::debug!("Sync hub skipped {} todo events", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn event(event_type: &str, user_id: i64) -> OutboxMessage {
+        OutboxMessage {
+            id: 1,
+            event_type: event_type.to_string(),
+            user_id,
+            workspace_id: 4,
+            occurred_at: time::OffsetDateTime::UNIX_EPOCH,
+            data: serde_json::json!({ "id": 3 }),
+        }
+    }
+
+    #[test]
+    fn test_todo_events_wake_the_sockets_of_their_user() {
+        let hub = SyncHub::default();
+        let mut phone = hub.join(10);
+        let mut laptop = hub.join(10);
+        let mut other = hub.join(20);
+
+        hub.todo_event(&event("todo.updated", 10));
+        assert_eq!(phone.try_recv().unwrap(), 4);
+        assert_eq!(laptop.try_recv().unwrap(), 4);
+        assert!(other.try_recv().is_err());
+
+        hub.todo_event(&event("user.created", 10));
+        assert!(phone.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_room_closes_with_the_last_socket() {
+        let hub = SyncHub::default();
+        let _phone = hub.join(10);
+        let _laptop = hub.join(10);
+
+        hub.leave(10);
+        assert!(hub.rooms().contains_key(&10));
+        hub.leave(10);
+        assert!(hub.rooms().is_empty());
+    }
+}
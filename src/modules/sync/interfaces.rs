@@ -0,0 +1,166 @@
+//! # `Sync` Interfaces
+//! This module defines the data structures of the offline sync protocol
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::modules::changes::interfaces::ChangesResponse;
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct SyncRequest {
+    // Cursor returned by the previous sync, `GET /sync` or `GET /changes`. Omitted on the
+    // first sync, after a full download.
+    pub cursor: Option<String>,
+    // Changes made offline, applied in order before pulling
+    #[serde(default)]
+    pub changes: Vec<SyncTodoChange>,
+}
+
+// Offline change of a todo, `None` fields are kept as they are
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, Default)]
+pub struct SyncTodoChange {
+    // Server id, omitted for todos created offline
+    pub id: Option<i64>,
+    // Client id of a todo created offline, echoed back with its server id
+    pub client_id: Option<String>,
+    // Version of the todo the change was made on
+    pub base_version: Option<i64>,
+    // Move the todo to the trash
+    #[serde(default)]
+    pub deleted: bool,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    // Only used when creating a todo
+    pub list_id: Option<i64>,
+    pub status: Option<String>,
+    // Due date in RFC 3339 format
+    pub due_at: Option<String>,
+    pub recurrence: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct SyncCreated {
+    pub client_id: Option<String>,
+    pub id: i64,
+}
+
+// Claim of a client id by a push creating a todo, without todo until it is created
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct SyncClientTodoRow {
+    pub todo_id: Option<i32>,
+    pub claimed_at: OffsetDateTime,
+}
+
+// How a conflicting change was resolved
+#[derive(Serialize, Deserialize, ToSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncResolution {
+    // The todo was written on the server after the server time of the client cursor,
+    // the later server copy is kept
+    ServerWins,
+    // The todo was deleted on the server, deletions always win
+    Deleted,
+    // The todo doesn't exist
+    NotFound,
+    // The change is invalid, e.g. a forbidden status transition
+    Rejected,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct SyncConflict {
+    pub id: Option<i64>,
+    pub client_id: Option<String>,
+    pub resolution: SyncResolution,
+    pub message: String,
+}
+
+// Changes since the cursor, as returned by the changes feed, with the push results
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct SyncResponse {
+    #[serde(flatten)]
+    pub changes: ChangesResponse,
+    // Server ids of the todos created offline
+    pub created: Vec<SyncCreated>,
+    // Changes that were not applied
+    pub conflicts: Vec<SyncConflict>,
+}
+
+// Query of the sync socket handshake
+#[derive(Deserialize, IntoParams, Clone, Debug, Default)]
+#[into_params(parameter_in = Query)]
+pub struct SyncSocketQuery {
+    /// Cursor of the last sync of the device, omitted to start from the current position
+    pub cursor: Option<String>,
+}
+
+// Message sent by a device on the sync socket
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SyncCommand {
+    // Apply changes made on the device, answered with a `synced` event
+    Push { changes: Vec<SyncTodoChange> },
+    // Pull the changes since the cursor of the socket
+    Pull,
+}
+
+// Event pushed on the sync socket
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SyncEvent {
+    // Changes since the cursor of the socket, with the results of a push. The socket
+    // keeps the new cursor, devices store it to resume on their next connection.
+    Synced(Box<SyncResponse>),
+    // Refused command or failed sync, the socket stays open unless access was revoked
+    Error { message: String },
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::modules::changes::interfaces::{ChangedLists, ChangedTags, ChangedTodos};
+
+    #[test]
+    fn test_sync_request_defaults() {
+        let request: SyncRequest = serde_json::from_str("{}").unwrap();
+        assert_eq!(request.cursor, None);
+        assert!(request.changes.is_empty());
+
+        let change: SyncTodoChange = serde_json::from_str(r#"{"id":4,"base_version":2}"#).unwrap();
+        assert!(!change.deleted);
+        assert_eq!(change.base_version, Some(2));
+    }
+
+    #[test]
+    fn test_sync_socket_messages() {
+        let command: SyncCommand =
+            serde_json::from_str(r#"{"type":"push","changes":[{"id":4,"deleted":true}]}"#).unwrap();
+        assert!(matches!(command, SyncCommand::Push { changes } if changes[0].deleted));
+
+        let event = SyncEvent::Synced(Box::new(SyncResponse {
+            changes: ChangesResponse {
+                cursor: "abc".to_string(),
+                has_more: false,
+                todos: ChangedTodos::default(),
+                lists: ChangedLists::default(),
+                tags: ChangedTags::default(),
+                tombstones: Vec::new(),
+            },
+            created: Vec::new(),
+            conflicts: Vec::new(),
+        }));
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "synced");
+        assert_eq!(json["cursor"], "abc");
+        assert!(json["lists"]["deleted"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_sync_resolution_serialization() {
+        assert_eq!(
+            serde_json::to_string(&SyncResolution::ServerWins).unwrap(),
+            r#""server_wins""#
+        );
+    }
+}
@@ -0,0 +1,10 @@
+//! # `Sync` Mod
+//! Sync imports for the offline sync module
+
+pub mod hub;
+pub mod interfaces;
+pub mod repository;
+pub mod routes;
+pub mod service;
+
+pub use routes::sync_routes;
@@ -0,0 +1,158 @@
+//! # `Sync` Repository
+//! This module defines the sync repository, reading the server copies of pushed todos
+//! and recording the client ids of the todos created offline.
+
+use sqlx::{Error, Pool, Postgres};
+use time::OffsetDateTime;
+
+use crate::modules::{
+    common::to_db_id,
+    sync::interfaces::SyncClientTodoRow,
+    todo::{interfaces::TodoRow, repository::TODO_COLUMNS},
+};
+use crate::telemetry::observe_query;
+
+pub struct SyncRepository {
+    pool: Pool<Postgres>,
+}
+
+impl SyncRepository {
+    // Constructor
+    pub const fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Fetch a todo of the user in a workspace, including a trashed one
+    pub async fn fetch_todo_state(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+    ) -> Result<Option<TodoRow>, Error> {
+        observe_query("sync.fetch_todo_state", async move {
+            let query = format!(
+                "SELECT {TODO_COLUMNS} FROM todos
+                 WHERE id = $1 AND user_id = $2 AND workspace_id = $3"
+            );
+
+            sqlx::query_as::<_, TodoRow>(&query)
+                .bind(to_db_id(id)?)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Claim a client id before creating its todo, `false` when it was already claimed
+    pub async fn claim_client_id(&self, user_id: i64, client_id: &str) -> Result<bool, Error> {
+        observe_query("sync.claim_client_id", async move {
+            let claimed = sqlx::query(
+                "INSERT INTO sync_client_todos (user_id, client_id) VALUES ($1, $2)
+                 ON CONFLICT (user_id, client_id) DO NOTHING",
+            )
+            .bind(to_db_id(user_id)?)
+            .bind(client_id)
+            .execute(&self.pool)
+            .await?;
+            Ok(claimed.rows_affected() == 1)
+        })
+        .await
+    }
+
+    // Fetch the claim of a client id
+    pub async fn fetch_client_todo(
+        &self,
+        user_id: i64,
+        client_id: &str,
+    ) -> Result<Option<SyncClientTodoRow>, Error> {
+        observe_query("sync.fetch_client_todo", async move {
+            sqlx::query_as::<_, SyncClientTodoRow>(
+                "SELECT todo_id, claimed_at FROM sync_client_todos
+                 WHERE user_id = $1 AND client_id = $2",
+            )
+            .bind(to_db_id(user_id)?)
+            .bind(client_id)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Take over a claim left without todo, `false` when another push took it first
+    pub async fn reclaim_client_id(
+        &self,
+        user_id: i64,
+        client_id: &str,
+        claimed_at: OffsetDateTime,
+    ) -> Result<bool, Error> {
+        observe_query("sync.reclaim_client_id", async move {
+            let reclaimed = sqlx::query(
+                "UPDATE sync_client_todos SET claimed_at = CURRENT_TIMESTAMP
+                 WHERE user_id = $1 AND client_id = $2 AND todo_id IS NULL AND claimed_at = $3",
+            )
+            .bind(to_db_id(user_id)?)
+            .bind(client_id)
+            .bind(claimed_at)
+            .execute(&self.pool)
+            .await?;
+            Ok(reclaimed.rows_affected() == 1)
+        })
+        .await
+    }
+
+    // Record the todo created for a claimed client id
+    pub async fn record_client_todo(
+        &self,
+        user_id: i64,
+        client_id: &str,
+        todo_id: i64,
+    ) -> Result<(), Error> {
+        observe_query("sync.record_client_todo", async move {
+            sqlx::query(
+                "UPDATE sync_client_todos SET todo_id = $3
+                 WHERE user_id = $1 AND client_id = $2",
+            )
+            .bind(to_db_id(user_id)?)
+            .bind(client_id)
+            .bind(to_db_id(todo_id)?)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+        })
+        .await
+    }
+
+    // Release the claim of a client id whose todo could not be created
+    pub async fn release_client_id(&self, user_id: i64, client_id: &str) -> Result<(), Error> {
+        observe_query("sync.release_client_id", async move {
+            sqlx::query(
+                "DELETE FROM sync_client_todos
+                 WHERE user_id = $1 AND client_id = $2 AND todo_id IS NULL",
+            )
+            .bind(to_db_id(user_id)?)
+            .bind(client_id)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_todo_columns_include_version() {
+        assert!(TODO_COLUMNS.contains("version"));
+        assert!(TODO_COLUMNS.contains("deleted_at"));
+    }
+
+    #[test]
+    fn test_invalid_id_is_rejected() {
+        assert!(to_db_id(i64::MAX).is_err());
+    }
+}
@@ -0,0 +1,327 @@
+//! #`Sync` Routes
+//! This module defines the HTTP routes of the offline sync protocol, and the sync socket
+//! keeping the devices of a user in sync in real time.
+//!
+//! `GET /sync` is the changes feed of `GET /changes`: the todos and lists of the workspace,
+//! and the tags, changed since a change sequence, with the tombstones of the deleted ones.
+//! `POST /sync` pushes the todo changes made offline, then pulls the same feed. Both verbs
+//! and the socket share one cursor, a position in the change sequence.
+//!
+//! Devices send JSON `SyncCommand` messages on the socket and receive `SyncEvent`
+//! messages. The socket pulls the changes since its cursor when it opens, then each
+//! time the todos of the user change, so devices only store the last cursor.
+
+use std::time::Duration;
+
+use axum::extract::{
+    ws::{Message, WebSocket, WebSocketUpgrade},
+    Query,
+};
+use axum::routing::get;
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Router};
+use tokio::sync::broadcast::{
+    self,
+    error::{RecvError, TryRecvError},
+};
+
+use crate::auth::BEARER_PROTOCOL;
+use crate::modules::changes::interfaces::{ChangesQuery, ChangesResponse};
+use crate::modules::changes::repository::ChangesRepository;
+use crate::modules::changes::routes::list_changes_route;
+use crate::modules::changes::service::ChangesService;
+use crate::modules::common::{respond, AppError, ErrorResponse};
+use crate::modules::sync::interfaces::{
+    SyncCommand, SyncEvent, SyncRequest, SyncResponse, SyncSocketQuery, SyncTodoChange,
+};
+use crate::modules::sync::repository::SyncRepository;
+use crate::modules::sync::service::SyncService;
+use crate::modules::todo::repository::TodoRepository;
+use crate::modules::todo::service::TodoService;
+use crate::modules::workspace::context::WorkspaceContext;
+use crate::modules::workspace::permissions::WorkspacePermission;
+use crate::modules::workspace::repository::WorkspaceRepository;
+use crate::utils::json::Json;
+use crate::AppState;
+
+// Changes a device can push in one message
+const MAX_PUSH_CHANGES: usize = 100;
+
+// Devices not reading their socket for this long are disconnected, they catch up from
+// their cursor on the next connection
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Creates and returns the sync routes
+pub fn sync_routes() -> Router<AppState> {
+    Router::new()
+        .route("/sync", get(sync_delta_route).post(sync_route))
+        .route("/ws", get(sync_socket_route))
+}
+
+fn sync_service(app_state: &AppState) -> SyncService {
+    SyncService::new(
+        SyncRepository::new(app_state.db_pool.clone()),
+        ChangesService::new(ChangesRepository::new(app_state.db_pool.clone())),
+        TodoService::new(
+            TodoRepository::new(app_state.db_pool.clone()),
+            app_state.view_cache.clone(),
+            app_state.create_dedup.clone(),
+        ),
+    )
+}
+
+// Sync Delta Route
+#[utoipa::path(
+    get,
+    path = "/sync",
+    tag = "Sync",
+    params(ChangesQuery),
+    responses(
+        (status = 200, description = "Todos and lists of the workspace, and tags, changed since the cursor, with the tombstones of the deleted ones", body = ChangesResponse),
+        (status = 400, description = "Invalid or expired cursor", body = ErrorResponse),
+        (status = 500, description = "Failed to list changes", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn sync_delta_route(
+    app_state: State<AppState>,
+    workspace: WorkspaceContext,
+    query: Query<ChangesQuery>,
+) -> impl IntoResponse {
+    list_changes_route(app_state, workspace, query).await
+}
+
+// Sync Route
+#[utoipa::path(
+    post,
+    path = "/sync",
+    tag = "Sync",
+    request_body = SyncRequest,
+    responses(
+        (status = 200, description = "Changes pushed and pulled, conflicts are listed", body = SyncResponse),
+        (status = 400, description = "Invalid or expired cursor, nothing was pushed", body = ErrorResponse),
+        (status = 500, description = "Sync failure", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn sync_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Json(sync_request): Json<SyncRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        sync_service(&app_state)
+            .sync(workspace.user_id, workspace.workspace_id, sync_request)
+            .await,
+    )
+}
+
+// Sync Socket Route
+#[utoipa::path(
+    get,
+    path = "/ws",
+    tag = "Sync",
+    params(
+        SyncSocketQuery,
+        ("Sec-WebSocket-Protocol" = Option<String>, Header, description = "`bearer, <token>` for the clients that can't set the Authorization header")
+    ),
+    responses(
+        (status = 101, description = "WebSocket pushing sync events and accepting sync commands", body = SyncEvent),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 403, description = "Not a member of the workspace")
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn sync_socket_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Query(query): Query<SyncSocketQuery>,
+    upgrade: WebSocketUpgrade,
+) -> impl IntoResponse {
+    upgrade
+        .protocols([BEARER_PROTOCOL])
+        .on_upgrade(move |socket| serve_device(socket, app_state, workspace, query.cursor))
+}
+
+// Keep a device in sync for the lifetime of its socket
+async fn serve_device(
+    mut socket: WebSocket,
+    app_state: AppState,
+    workspace: WorkspaceContext,
+    mut cursor: Option<String>,
+) {
+    let hub = app_state.sync_hub.clone();
+    // Joined before the first pull, changes made meanwhile wake the socket again
+    let mut wake_ups = hub.join(workspace.user_id);
+    tracing::debug!("Device of user {} syncing", workspace.user_id);
+
+    if sync_device(&mut socket, &app_state, workspace, &mut cursor, Vec::new()).await {
+        relay_changes(
+            &mut socket,
+            &app_state,
+            workspace,
+            &mut wake_ups,
+            &mut cursor,
+        )
+        .await;
+    }
+
+    hub.leave(workspace.user_id);
+    tracing::debug!("Device of user {} disconnected", workspace.user_id);
+}
+
+// Answer the commands of the device and pull the changes of the other devices until
+// the socket closes
+async fn relay_changes(
+    socket: &mut WebSocket,
+    app_state: &AppState,
+    workspace: WorkspaceContext,
+    wake_ups: &mut broadcast::Receiver<i64>,
+    cursor: &mut Option<String>,
+) {
+    loop {
+        let open = tokio::select! {
+            message = socket.recv() => {
+                let Some(Ok(message)) = message else {
+                    return;
+                };
+                match message {
+                    Message::Text(text) => match serde_json::from_str::<SyncCommand>(text.as_str()) {
+                        Ok(SyncCommand::Push { changes }) if changes.len() > MAX_PUSH_CHANGES => {
+                            let message = format!("Push at most {MAX_PUSH_CHANGES} changes at once");
+                            send_error(socket, message).await
+                        }
+                        Ok(SyncCommand::Push { changes }) => {
+                            sync_device(socket, app_state, workspace, cursor, changes).await
+                        }
+                        Ok(SyncCommand::Pull) => {
+                            sync_device(socket, app_state, workspace, cursor, Vec::new()).await
+                        }
+                        Err(e) => send_error(socket, format!("Invalid sync command: {e}")).await,
+                    },
+                    Message::Close(_) => return,
+                    // Pings are answered by axum
+                    _ => true,
+                }
+            }
+            wake_up = wake_ups.recv() => match wake_up {
+                Ok(workspace_id) if workspace_id != workspace.workspace_id => true,
+                Ok(_) | Err(RecvError::Lagged(_)) => {
+                    // The wake-ups received meanwhile are served by the same pull
+                    while !matches!(
+                        wake_ups.try_recv(),
+                        Err(TryRecvError::Empty | TryRecvError::Closed)
+                    ) {}
+                    sync_device(socket, app_state, workspace, cursor, Vec::new()).await
+                }
+                Err(RecvError::Closed) => return,
+            },
+        };
+        if !open {
+            return;
+        }
+    }
+}
+
+// Push the changes of the device, then send it every change since its cursor. Returns
+// whether the socket is still open.
+async fn sync_device(
+    socket: &mut WebSocket,
+    app_state: &AppState,
+    workspace: WorkspaceContext,
+    cursor: &mut Option<String>,
+    changes: Vec<SyncTodoChange>,
+) -> bool {
+    let needed = if changes.is_empty() {
+        WorkspacePermission::Read
+    } else {
+        WorkspacePermission::TodosWrite
+    };
+    if let Err(error) = authorize_device(app_state, workspace, needed).await {
+        let revoked = matches!(error, AppError::Forbidden(_));
+        return send_error(socket, error.to_error_response().message).await && !revoked;
+    }
+
+    let sync_service = sync_service(app_state);
+    let mut request = SyncRequest {
+        cursor: cursor.clone(),
+        changes,
+    };
+    loop {
+        let response = match sync_service
+            .sync(workspace.user_id, workspace.workspace_id, request)
+            .await
+        {
+            Ok(response) => response,
+            Err(error) => return send_error(socket, error.to_error_response().message).await,
+        };
+        *cursor = Some(response.changes.cursor.clone());
+        let has_more = response.changes.has_more;
+        if !send_event(socket, &SyncEvent::Synced(Box::new(response))).await {
+            return false;
+        }
+        if !has_more {
+            return true;
+        }
+        request = SyncRequest {
+            cursor: cursor.clone(),
+            changes: Vec::new(),
+        };
+    }
+}
+
+// Check the member still holds the permission, the socket outlives the checks of the
+// handshake
+async fn authorize_device(
+    app_state: &AppState,
+    workspace: WorkspaceContext,
+    needed: WorkspacePermission,
+) -> Result<(), AppError> {
+    match WorkspaceRepository::new(app_state.db_pool.clone())
+        .active_workspace(workspace.user_id, Some(workspace.workspace_id))
+        .await
+    {
+        Ok(Some(active)) if needed.granted_by(active.permissions.as_deref()) => Ok(()),
+        Ok(_) => Err(AppError::Forbidden(format!(
+            "The {} permission in the workspace was revoked",
+            needed.as_str()
+        ))),
+        Err(e) => {
+            tracing::warn!("Error checking sync socket access: {}", e);
+            Err(AppError::Database(e))
+        }
+    }
+}
+
+async fn send_error(socket: &mut WebSocket, message: String) -> bool {
+    send_event(socket, &SyncEvent::Error { message }).await
+}
+
+// Send an event, returns whether the socket is still open and reading
+async fn send_event(socket: &mut WebSocket, event: &SyncEvent) -> bool {
+    let Ok(text) = serde_json::to_string(event) else {
+        return false;
+    };
+    matches!(
+        tokio::time::timeout(SEND_TIMEOUT, socket.send(Message::Text(text.into()))).await,
+        Ok(Ok(()))
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sync_routes_creation() {
+        let _routes = sync_routes();
+        assert!(true);
+    }
+}
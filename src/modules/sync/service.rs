@@ -0,0 +1,539 @@
+//! # `Sync` Service
+//!
+//! This module contains the offline sync protocol. Offline changes are pushed first,
+//! in order, then the changes since the client cursor are pulled from the changes feed.
+//! The cursor is a position in the change sequence, the one of `GET /sync` and
+//! `GET /changes`.
+//!
+//! Conflicts are resolved deterministically:
+//! - a todo deleted on the server stays deleted
+//! - a deletion from the client always applies
+//! - a change made on the current server version applies
+//! - otherwise the last writer wins on server timestamps
+//!
+//! The clocks of the clients are never trusted. An offline change is stamped with the
+//! server time of the cursor it was made after, the time of the last change the client
+//! pulled. It wins over a server copy written before that time, and loses to one written
+//! since by another device.
+//!
+//! Todos created offline are created once per client id. A push repeated after a lost
+//! response returns the todo of the first push.
+
+use time::{Duration, OffsetDateTime};
+
+use crate::modules::{
+    changes::{
+        interfaces::{ChangeCursor, ChangesQuery},
+        service::ChangesService,
+    },
+    common::AppError,
+    sync::{
+        interfaces::{
+            SyncClientTodoRow, SyncConflict, SyncCreated, SyncRequest, SyncResolution,
+            SyncResponse, SyncTodoChange,
+        },
+        repository::SyncRepository,
+    },
+    todo::{
+        interfaces::{CreateTodoRequest, TodoRow, UpdateTodoRequest},
+        service::TodoService,
+    },
+};
+
+// Why a change on an existing todo can't apply, `None` when it applies. `synced_at` is
+// the server time of the cursor the client synced at before making the change.
+fn detect_conflict(
+    change: &SyncTodoChange,
+    server: &TodoRow,
+    synced_at: Option<OffsetDateTime>,
+) -> Option<SyncResolution> {
+    if server.deleted_at.is_some() {
+        return Some(SyncResolution::Deleted);
+    }
+    if change.deleted || change.base_version == Some(server.version) {
+        return None;
+    }
+
+    // Versions diverged, the last write in server time wins
+    match (server.updated_at, synced_at) {
+        (Some(updated_at), Some(synced_at)) if updated_at <= synced_at => None,
+        _ => Some(SyncResolution::ServerWins),
+    }
+}
+
+// Time after which a claimed client id without todo is taken over, its push failed
+// before creating the todo or releasing the claim
+const CLIENT_ID_CLAIM_TIMEOUT: Duration = Duration::minutes(1);
+
+// State of a client id claimed by an earlier push
+#[derive(Debug, PartialEq, Eq)]
+enum ClientIdState {
+    // Its todo was created
+    Created(i64),
+    // Another push is creating its todo
+    Pending,
+    // The push that claimed it at that time never finished
+    Stale(OffsetDateTime),
+}
+
+fn client_id_state(claim: Option<&SyncClientTodoRow>, now: OffsetDateTime) -> ClientIdState {
+    match claim {
+        Some(SyncClientTodoRow {
+            todo_id: Some(todo_id),
+            ..
+        }) => ClientIdState::Created(i64::from(*todo_id)),
+        Some(claim) if claim.claimed_at + CLIENT_ID_CLAIM_TIMEOUT < now => {
+            ClientIdState::Stale(claim.claimed_at)
+        }
+        // A claim released since is retried on the next push
+        _ => ClientIdState::Pending,
+    }
+}
+
+const fn conflict_message(resolution: SyncResolution) -> &'static str {
+    match resolution {
+        SyncResolution::ServerWins => "Todo changed on the server since the last sync",
+        SyncResolution::Deleted => "Todo was deleted on the server",
+        SyncResolution::NotFound => "Todo not found",
+        SyncResolution::Rejected => "Change rejected",
+    }
+}
+
+pub struct SyncService {
+    sync_repository: SyncRepository,
+    changes_service: ChangesService,
+    todo_service: TodoService,
+}
+
+impl SyncService {
+    pub const fn new(
+        sync_repository: SyncRepository,
+        changes_service: ChangesService,
+        todo_service: TodoService,
+    ) -> Self {
+        Self {
+            sync_repository,
+            changes_service,
+            todo_service,
+        }
+    }
+
+    // Push the offline changes, then pull the changes since the cursor
+    pub async fn sync(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        request: SyncRequest,
+    ) -> Result<SyncResponse, AppError> {
+        // Checked before pushing, nothing is applied on an invalid or expired cursor
+        let synced_at = match ChangeCursor::decode(request.cursor.clone())? {
+            Some(cursor) => {
+                self.changes_service
+                    .cursor_time(user_id, workspace_id, cursor)
+                    .await?
+            }
+            None => None,
+        };
+
+        let mut created = Vec::new();
+        let mut conflicts = Vec::new();
+        for change in request.changes {
+            match change.id {
+                Some(id) => {
+                    if let Err(conflict) = self
+                        .push_change(user_id, workspace_id, id, synced_at, change)
+                        .await
+                    {
+                        conflicts.push(conflict);
+                    }
+                }
+                // Todos created and deleted while offline never reach the server
+                None if change.deleted => {}
+                None => {
+                    let (todo, conflict) = self.push_create(user_id, workspace_id, change).await;
+                    created.extend(todo);
+                    conflicts.extend(conflict);
+                }
+            }
+        }
+
+        let changes = self
+            .changes_service
+            .list_changes(
+                user_id,
+                workspace_id,
+                ChangesQuery {
+                    since: request.cursor,
+                },
+            )
+            .await?;
+
+        Ok(SyncResponse {
+            changes,
+            created,
+            conflicts,
+        })
+    }
+
+    // Claim the client id of a todo created offline, returns the todo created by an
+    // earlier push of the same client id
+    async fn claim_client_id(
+        &self,
+        user_id: i64,
+        client_id: &str,
+    ) -> Result<Option<i64>, AppError> {
+        let failed = |e: sqlx::Error| {
+            tracing::warn!("Error claiming sync client id: {}", e);
+            AppError::Database(e)
+        };
+
+        if self
+            .sync_repository
+            .claim_client_id(user_id, client_id)
+            .await
+            .map_err(failed)?
+        {
+            return Ok(None);
+        }
+
+        let claim = self
+            .sync_repository
+            .fetch_client_todo(user_id, client_id)
+            .await
+            .map_err(failed)?;
+        let pending = || AppError::Conflict("Todo is still being created, sync again".to_string());
+        match client_id_state(claim.as_ref(), OffsetDateTime::now_utc()) {
+            ClientIdState::Created(id) => Ok(Some(id)),
+            ClientIdState::Pending => Err(pending()),
+            ClientIdState::Stale(claimed_at) => {
+                if self
+                    .sync_repository
+                    .reclaim_client_id(user_id, client_id, claimed_at)
+                    .await
+                    .map_err(failed)?
+                {
+                    Ok(None)
+                } else {
+                    Err(pending())
+                }
+            }
+        }
+    }
+
+    // Create a todo made offline, its status is applied once it exists. A client id
+    // already pushed returns its todo as it is.
+    async fn push_create(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        change: SyncTodoChange,
+    ) -> (Option<SyncCreated>, Option<SyncConflict>) {
+        let client_id = change.client_id;
+        let rejected = |error: AppError| SyncConflict {
+            id: None,
+            client_id: client_id.clone(),
+            resolution: SyncResolution::Rejected,
+            message: error.to_error_response().message,
+        };
+
+        if let Some(client_id) = &client_id {
+            match self.claim_client_id(user_id, client_id).await {
+                Ok(None) => {}
+                Ok(Some(id)) => {
+                    return (
+                        Some(SyncCreated {
+                            client_id: Some(client_id.clone()),
+                            id,
+                        }),
+                        None,
+                    )
+                }
+                Err(error) => return (None, Some(rejected(error))),
+            }
+        }
+
+        let create_request = CreateTodoRequest {
+            title: change.title,
+            description: change.description,
+            list_id: change.list_id,
+            due_at: change.due_at,
+            recurrence: change.recurrence,
+            scheduled_for: None,
+        };
+        let created = self
+            .todo_service
+            .create_todo(user_id, workspace_id, create_request)
+            .await;
+        if let Some(client_id) = &client_id {
+            let recorded = match &created {
+                Ok((todo, _)) => {
+                    self.sync_repository
+                        .record_client_todo(user_id, client_id, todo.id)
+                        .await
+                }
+                Err(_) => {
+                    self.sync_repository
+                        .release_client_id(user_id, client_id)
+                        .await
+                }
+            };
+            if let Err(e) = recorded {
+                tracing::warn!("Error recording sync client id: {}", e);
+            }
+        }
+        let todo = match created {
+            Ok((todo, _)) => todo,
+            Err(error) => return (None, Some(rejected(error))),
+        };
+
+        let conflict = match change.status {
+            Some(status) => {
+                let update_request = UpdateTodoRequest {
+                    title: None,
+                    description: None,
+                    status: Some(status),
+                    due_at: None,
+                    recurrence: None,
+                    scheduled_for: None,
+                    version: Some(todo.version),
+                };
+                self.todo_service
+                    .update_todo(user_id, workspace_id, todo.id, update_request)
+                    .await
+                    .err()
+                    .map(|error| SyncConflict {
+                        id: Some(todo.id),
+                        ..rejected(error)
+                    })
+            }
+            None => None,
+        };
+
+        (
+            Some(SyncCreated {
+                client_id,
+                id: todo.id,
+            }),
+            conflict,
+        )
+    }
+
+    // Apply an offline change to an existing todo, made after the sync at `synced_at`
+    async fn push_change(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+        synced_at: Option<OffsetDateTime>,
+        change: SyncTodoChange,
+    ) -> Result<(), SyncConflict> {
+        let client_id = change.client_id.clone();
+        let conflict = |resolution: SyncResolution, message: String| SyncConflict {
+            id: Some(id),
+            client_id: client_id.clone(),
+            resolution,
+            message,
+        };
+
+        let server = match self
+            .sync_repository
+            .fetch_todo_state(user_id, workspace_id, id)
+            .await
+        {
+            Ok(Some(todo)) => todo,
+            Ok(None) => {
+                let resolution = SyncResolution::NotFound;
+                return Err(conflict(
+                    resolution,
+                    conflict_message(resolution).to_string(),
+                ));
+            }
+            Err(e) => {
+                tracing::warn!("Error fetching todo to sync: {}", e);
+                return Err(conflict(
+                    SyncResolution::Rejected,
+                    "Failed to sync todo".to_string(),
+                ));
+            }
+        };
+
+        if let Some(resolution) = detect_conflict(&change, &server, synced_at) {
+            return Err(conflict(
+                resolution,
+                conflict_message(resolution).to_string(),
+            ));
+        }
+
+        let result = if change.deleted {
+            self.todo_service
+                .delete_todo(user_id, workspace_id, id)
+                .await
+                .map(|_| ())
+        } else {
+            // Guarded by the version read above, a concurrent write turns into a conflict
+            let update_request = UpdateTodoRequest {
+                title: change.title,
+                description: change.description,
+                status: change.status,
+                due_at: change.due_at,
+                recurrence: change.recurrence,
+                scheduled_for: None,
+                version: Some(server.version),
+            };
+            self.todo_service
+                .update_todo(user_id, workspace_id, id, update_request)
+                .await
+                .map(|_| ())
+        };
+
+        result
+            .map_err(|error| conflict(SyncResolution::Rejected, error.to_error_response().message))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use time::macros::datetime;
+
+    // Server copy last written at `updated_at`
+    fn server_todo(version: i64, updated_at: OffsetDateTime, deleted: bool) -> TodoRow {
+        TodoRow {
+            id: 1,
+            list_id: None,
+            title: "Buy milk".to_string(),
+            description: None,
+            status: "backlog".to_string(),
+            status_changed_at: None,
+            completed_at: None,
+            due_at: None,
+            recurrence: None,
+            scheduled_for: None,
+            archived: false,
+            version,
+            created_at: None,
+            updated_at: Some(updated_at),
+            deleted_at: deleted.then_some(updated_at),
+        }
+    }
+
+    fn change(base_version: i64) -> SyncTodoChange {
+        SyncTodoChange {
+            id: Some(1),
+            base_version: Some(base_version),
+            title: Some("Buy oat milk".to_string()),
+            ..SyncTodoChange::default()
+        }
+    }
+
+    #[test]
+    fn test_change_on_current_version_applies() {
+        let server = server_todo(3, datetime!(2025-03-10 14:00 UTC), false);
+        assert_eq!(
+            detect_conflict(&change(3), &server, Some(datetime!(2025-03-10 12:00 UTC))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_diverged_versions_use_server_time() {
+        let synced_at = Some(datetime!(2025-03-10 12:00 UTC));
+        // Written before the last sync of the client, the offline change is more recent
+        let server = server_todo(4, datetime!(2025-03-10 11:00 UTC), false);
+        assert_eq!(detect_conflict(&change(3), &server, synced_at), None);
+        let server = server_todo(4, datetime!(2025-03-10 12:00 UTC), false);
+        assert_eq!(detect_conflict(&change(3), &server, synced_at), None);
+        // Written by another device since, the client never saw it
+        let server = server_todo(4, datetime!(2025-03-10 13:00 UTC), false);
+        assert_eq!(
+            detect_conflict(&change(3), &server, synced_at),
+            Some(SyncResolution::ServerWins)
+        );
+        // Without cursor the client never synced, the server copy is kept
+        assert_eq!(
+            detect_conflict(&change(3), &server, None),
+            Some(SyncResolution::ServerWins)
+        );
+    }
+
+    #[test]
+    fn test_deletions_win() {
+        let synced_at = Some(datetime!(2025-03-10 12:00 UTC));
+        let server = server_todo(3, datetime!(2025-03-10 11:00 UTC), true);
+        assert_eq!(
+            detect_conflict(&change(3), &server, synced_at),
+            Some(SyncResolution::Deleted)
+        );
+
+        let delete = SyncTodoChange {
+            deleted: true,
+            ..change(3)
+        };
+        let server = server_todo(9, datetime!(2025-03-10 13:00 UTC), false);
+        assert_eq!(detect_conflict(&delete, &server, synced_at), None);
+    }
+
+    // Push the creates of a batch against the recorded client ids, as `push_create`
+    // does, returns the server id of each todo
+    fn push_creates(
+        claims: &mut HashMap<String, SyncClientTodoRow>,
+        batch: &[&str],
+        next_id: &mut i32,
+        now: OffsetDateTime,
+    ) -> Vec<i64> {
+        batch
+            .iter()
+            .map(|client_id| {
+                let state = client_id_state(claims.get(*client_id), now);
+                if let ClientIdState::Created(id) = state {
+                    return id;
+                }
+                assert!(claims.get(*client_id).is_none());
+                *next_id += 1;
+                claims.insert(
+                    (*client_id).to_string(),
+                    SyncClientTodoRow {
+                        todo_id: Some(*next_id),
+                        claimed_at: now,
+                    },
+                );
+                i64::from(*next_id)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_same_batch_pushed_twice_creates_once() {
+        let now = datetime!(2025-03-10 12:00 UTC);
+        let mut claims = HashMap::new();
+        let mut next_id = 0;
+
+        let batch = ["a", "b", "a"];
+        let first = push_creates(&mut claims, &batch, &mut next_id, now);
+        assert_eq!(first, vec![1, 2, 1]);
+        // The response was lost, the device pushes the batch again
+        let second = push_creates(&mut claims, &batch, &mut next_id, now);
+        assert_eq!(second, first);
+        assert_eq!(next_id, 2);
+    }
+
+    #[test]
+    fn test_unfinished_claims_are_taken_over() {
+        let claimed_at = datetime!(2025-03-10 12:00 UTC);
+        let claim = SyncClientTodoRow {
+            todo_id: None,
+            claimed_at,
+        };
+        assert_eq!(
+            client_id_state(Some(&claim), claimed_at + Duration::seconds(30)),
+            ClientIdState::Pending
+        );
+        assert_eq!(
+            client_id_state(Some(&claim), claimed_at + Duration::minutes(2)),
+            ClientIdState::Stale(claimed_at)
+        );
+        assert_eq!(client_id_state(None, claimed_at), ClientIdState::Pending);
+    }
+}
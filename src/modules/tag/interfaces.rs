@@ -0,0 +1,135 @@
+//! # `Tags` Interfaces
+//! This module defines the data structures from Tags module
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+use crate::modules::todo::interfaces::TodoStatus;
+use crate::utils::dates::rfc3339_option;
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct CreateTagRequest {
+    // Tag name
+    pub name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ValidatedCreateTagRequest {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct AttachTagRequest {
+    // Tag to attach to the todo
+    pub tag_id: Option<i64>,
+}
+
+// Todos a bulk tag operation applies to, every condition set must match. An empty
+// filter selects all the todos of the workspace.
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, Default)]
+pub struct BulkTagFilter {
+    // Only the todos of this list
+    pub list_id: Option<i64>,
+    // Only the todos in this status, e.g. `in_progress`
+    pub status: Option<String>,
+    // Only the open todos past their due date
+    pub overdue: Option<bool>,
+    // Only the todos carrying this tag
+    pub tag: Option<String>,
+    // Also the archived todos, defaults to false
+    pub include_archived: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct BulkTagRequest {
+    // Name of the tag, created when attaching a new one
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub filter: BulkTagFilter,
+}
+
+// Bulk tag filter after validation
+#[derive(Clone, Debug, Default)]
+pub struct BulkTagSelection {
+    pub list_id: Option<i64>,
+    pub status: Option<TodoStatus>,
+    pub overdue: bool,
+    pub tag: Option<String>,
+    pub include_archived: bool,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct BulkTagResponse {
+    pub tag: TagResponse,
+    // Todos the tag was attached to or detached from, not counting the unchanged ones
+    pub count: u64,
+}
+
+// Tag row as stored in database
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct TagRow {
+    pub id: i32,
+    pub name: String,
+    pub created_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct TagResponse {
+    pub id: i64,
+    pub name: String,
+    #[serde(with = "rfc3339_option")]
+    pub created_at: Option<OffsetDateTime>,
+}
+
+impl From<TagRow> for TagResponse {
+    fn from(row: TagRow) -> Self {
+        Self {
+            id: i64::from(row.id),
+            name: row.name,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct TagMessageResponse {
+    pub message: String,
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attach_tag_request_deserialization() {
+        let request: AttachTagRequest = serde_json::from_str(r#"{"tag_id":5}"#).unwrap();
+        assert_eq!(request.tag_id, Some(5));
+    }
+
+    #[test]
+    fn test_bulk_tag_request_default_filter() {
+        let request: BulkTagRequest = serde_json::from_str(r#"{"tag":"late"}"#).unwrap();
+        assert_eq!(request.tag.as_deref(), Some("late"));
+        assert_eq!(request.filter.list_id, None);
+        assert_eq!(request.filter.overdue, None);
+
+        let request: BulkTagRequest =
+            serde_json::from_str(r#"{"tag":"late","filter":{"list_id":3,"overdue":true}}"#)
+                .unwrap();
+        assert_eq!(request.filter.list_id, Some(3));
+        assert_eq!(request.filter.overdue, Some(true));
+    }
+
+    #[test]
+    fn test_tag_response_from_row() {
+        let response = TagResponse::from(TagRow {
+            id: 2,
+            name: "work".to_string(),
+            created_at: None,
+        });
+        assert_eq!(response.id, 2);
+        assert_eq!(response.name, "work");
+    }
+}
@@ -0,0 +1,9 @@
+//! # `Tag` Mod
+//! Tag imports for the tag module
+
+pub mod interfaces;
+pub mod repository;
+pub mod routes;
+pub mod service;
+
+pub use routes::tag_routes;
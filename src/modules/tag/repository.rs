@@ -0,0 +1,251 @@
+//! # `Tag` Repository
+//! This module defines the tag repository for tag operations.
+
+use sqlx::{Error, Pool, Postgres};
+
+use crate::modules::{
+    common::to_db_id,
+    tag::interfaces::{BulkTagSelection, TagRow},
+    todo::{interfaces::TodoStatus, repository::OPEN_STATUSES},
+};
+use crate::telemetry::observe_query;
+
+// Tag upsert, returning the existing tag when the name is already used
+const UPSERT_TAG_QUERY: &str = "INSERT INTO tags (user_id, name) VALUES ($1, $2)
+     ON CONFLICT (user_id, name) DO UPDATE SET name = EXCLUDED.name
+     RETURNING id, name, created_at";
+
+// Ids of the todos of a bulk tag operation, bound by `bind_selection` as $1 to $7
+fn bulk_targets() -> String {
+    format!(
+        "SELECT id FROM todos
+         WHERE user_id = $1 AND workspace_id = $2 AND deleted_at IS NULL
+           AND ($3::INTEGER IS NULL OR list_id = $3)
+           AND ($4::TEXT IS NULL OR status = $4)
+           AND (NOT $5 OR ({OPEN_STATUSES} AND due_at < NOW()))
+           AND ($6::TEXT IS NULL OR EXISTS(
+                SELECT 1 FROM todo_tags tt
+                JOIN tags g ON g.id = tt.tag_id
+                WHERE tt.todo_id = todos.id AND g.user_id = $1 AND g.name = $6))
+           AND ($7 OR NOT archived)"
+    )
+}
+
+fn bind_selection<'q>(
+    query: sqlx::query::Query<'q, Postgres, sqlx::postgres::PgArguments>,
+    user_id: i64,
+    workspace_id: i64,
+    selection: &'q BulkTagSelection,
+) -> Result<sqlx::query::Query<'q, Postgres, sqlx::postgres::PgArguments>, Error> {
+    Ok(query
+        .bind(to_db_id(user_id)?)
+        .bind(to_db_id(workspace_id)?)
+        .bind(selection.list_id.map(to_db_id).transpose()?)
+        .bind(selection.status.map(TodoStatus::as_str))
+        .bind(selection.overdue)
+        .bind(selection.tag.as_deref())
+        .bind(selection.include_archived))
+}
+
+pub struct TagRepository {
+    pool: Pool<Postgres>,
+}
+
+impl TagRepository {
+    // Constructor
+    pub const fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Create a tag, returns the existing one when the name is already used
+    pub async fn create_tag(&self, user_id: i64, name: &str) -> Result<TagRow, Error> {
+        observe_query("tag.create_tag", async move {
+            sqlx::query_as::<_, TagRow>(UPSERT_TAG_QUERY)
+                .bind(to_db_id(user_id)?)
+                .bind(name)
+                .fetch_one(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // List all tags from an user
+    pub async fn list_tags(&self, user_id: i64) -> Result<Vec<TagRow>, Error> {
+        observe_query("tag.list_tags", async move {
+            sqlx::query_as::<_, TagRow>(
+                "SELECT id, name, created_at FROM tags WHERE user_id = $1 ORDER BY name",
+            )
+            .bind(to_db_id(user_id)?)
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Delete a tag, detaching it from every todo
+    pub async fn delete_tag(&self, user_id: i64, id: i64) -> Result<bool, Error> {
+        observe_query("tag.delete_tag", async move {
+            let result = sqlx::query("DELETE FROM tags WHERE id = $1 AND user_id = $2")
+                .bind(to_db_id(id)?)
+                .bind(to_db_id(user_id)?)
+                .execute(&self.pool)
+                .await?;
+
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    // Attach a tag to a todo of the workspace, returns false when either is not owned
+    // by the user. Tags are per user and shared by all their workspaces.
+    pub async fn attach_tag(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+        tag_id: i64,
+    ) -> Result<bool, Error> {
+        observe_query("tag.attach_tag", async move {
+            sqlx::query_scalar::<_, bool>(
+                "WITH target AS (
+                    SELECT t.id AS todo_id, g.id AS tag_id
+                    FROM todos t
+                    JOIN tags g ON g.user_id = t.user_id
+                    WHERE t.id = $1 AND g.id = $2 AND t.user_id = $3 AND t.workspace_id = $4
+                      AND t.deleted_at IS NULL
+                ), inserted AS (
+                    INSERT INTO todo_tags (todo_id, tag_id)
+                    SELECT todo_id, tag_id FROM target
+                    ON CONFLICT DO NOTHING
+                )
+                SELECT EXISTS(SELECT 1 FROM target)",
+            )
+            .bind(to_db_id(todo_id)?)
+            .bind(to_db_id(tag_id)?)
+            .bind(to_db_id(user_id)?)
+            .bind(to_db_id(workspace_id)?)
+            .fetch_one(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Detach a tag from a todo of the workspace, returns whether the association existed
+    pub async fn detach_tag(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+        tag_id: i64,
+    ) -> Result<bool, Error> {
+        observe_query("tag.detach_tag", async move {
+            let result = sqlx::query(
+                "DELETE FROM todo_tags tt
+                 USING todos t
+                 WHERE tt.todo_id = t.id AND tt.todo_id = $1 AND tt.tag_id = $2 AND t.user_id = $3
+                   AND t.workspace_id = $4 AND t.deleted_at IS NULL",
+            )
+            .bind(to_db_id(todo_id)?)
+            .bind(to_db_id(tag_id)?)
+            .bind(to_db_id(user_id)?)
+            .bind(to_db_id(workspace_id)?)
+            .execute(&self.pool)
+            .await?;
+
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    // Attach the tag named `name` to the todos of the workspace matching `selection` in
+    // one transaction, creating the tag when needed. Returns the tag and the number of
+    // todos newly tagged.
+    pub async fn bulk_attach(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        name: &str,
+        selection: &BulkTagSelection,
+    ) -> Result<(TagRow, u64), Error> {
+        observe_query("tag.bulk_attach", async move {
+            let mut tx = self.pool.begin().await?;
+            let tag = sqlx::query_as::<_, TagRow>(UPSERT_TAG_QUERY)
+                .bind(to_db_id(user_id)?)
+                .bind(name)
+                .fetch_one(&mut *tx)
+                .await?;
+
+            let query = format!(
+                "INSERT INTO todo_tags (todo_id, tag_id)
+                 SELECT id, $8 FROM ({}) targets
+                 ON CONFLICT DO NOTHING",
+                bulk_targets()
+            );
+            let result = bind_selection(sqlx::query(&query), user_id, workspace_id, selection)?
+                .bind(tag.id)
+                .execute(&mut *tx)
+                .await?;
+
+            tx.commit().await?;
+            Ok((tag, result.rows_affected()))
+        })
+        .await
+    }
+
+    // Detach the tag named `name` from the todos of the workspace matching `selection` in
+    // one transaction. Returns the tag and the number of todos untagged, `None` when the
+    // user has no such tag.
+    pub async fn bulk_detach(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        name: &str,
+        selection: &BulkTagSelection,
+    ) -> Result<Option<(TagRow, u64)>, Error> {
+        observe_query("tag.bulk_detach", async move {
+            let mut tx = self.pool.begin().await?;
+            let Some(tag) = sqlx::query_as::<_, TagRow>(
+                "SELECT id, name, created_at FROM tags WHERE user_id = $1 AND name = $2
+                 FOR UPDATE",
+            )
+            .bind(to_db_id(user_id)?)
+            .bind(name)
+            .fetch_optional(&mut *tx)
+            .await?
+            else {
+                return Ok(None);
+            };
+
+            let query = format!(
+                "DELETE FROM todo_tags WHERE tag_id = $8 AND todo_id IN ({})",
+                bulk_targets()
+            );
+            let result = bind_selection(sqlx::query(&query), user_id, workspace_id, selection)?
+                .bind(tag.id)
+                .execute(&mut *tx)
+                .await?;
+
+            tx.commit().await?;
+            Ok(Some((tag, result.rows_affected())))
+        })
+        .await
+    }
+
+    // List the tags attached to a todo
+    pub async fn list_todo_tags(&self, user_id: i64, todo_id: i64) -> Result<Vec<TagRow>, Error> {
+        observe_query("tag.list_todo_tags", async move {
+            sqlx::query_as::<_, TagRow>(
+                "SELECT g.id, g.name, g.created_at
+                 FROM todo_tags tt
+                 JOIN tags g ON g.id = tt.tag_id
+                 WHERE tt.todo_id = $1 AND g.user_id = $2
+                 ORDER BY g.name",
+            )
+            .bind(to_db_id(todo_id)?)
+            .bind(to_db_id(user_id)?)
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+}
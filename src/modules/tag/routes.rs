@@ -0,0 +1,309 @@
+//! #`Tag` Routes
+//! This module defines the HTTP routes for tags functionality.
+
+use axum::extract::Path;
+use axum::routing::{delete, get, post};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Router,
+};
+
+use crate::auth::Claims;
+use crate::modules::audit::{repository::AuditRepository, service::AuditService};
+use crate::modules::common::{respond, respond_cached, CacheValidators, ErrorResponse};
+use crate::modules::session::interfaces::SessionDevice;
+use crate::modules::tag::interfaces::{
+    AttachTagRequest, BulkTagRequest, BulkTagResponse, CreateTagRequest, TagMessageResponse,
+    TagResponse,
+};
+use crate::modules::tag::repository::TagRepository;
+use crate::modules::tag::service::TagService;
+use crate::modules::workspace::context::WorkspaceContext;
+use crate::utils::json::Json;
+use crate::AppState;
+
+// Creates and returns the tag routes
+pub fn tag_routes() -> Router<AppState> {
+    Router::new()
+        .route("/tags", get(list_tags_route).post(create_tag_route))
+        .route("/tags/{id}", delete(delete_tag_route))
+        .route("/tags/bulk/attach", post(bulk_attach_tag_route))
+        .route("/tags/bulk/detach", post(bulk_detach_tag_route))
+        .route(
+            "/todos/{id}/tags",
+            get(list_todo_tags_route).post(attach_tag_route),
+        )
+        .route("/todos/{id}/tags/{tag_id}", delete(detach_tag_route))
+}
+
+fn tag_service(app_state: &AppState) -> TagService {
+    TagService::new(
+        TagRepository::new(app_state.db_pool.clone()),
+        AuditService::new(AuditRepository::new(app_state.db_pool.clone())),
+        app_state.view_cache.clone(),
+    )
+}
+
+// Create Tag Route
+#[utoipa::path(
+    post,
+    path = "/tags",
+    tag = "Tags",
+    request_body = CreateTagRequest,
+    responses(
+        (status = 201, description = "Tag created successfully", body = TagResponse),
+        (status = 400, description = "Invalid tag data", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn create_tag_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Json(create_request): Json<CreateTagRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::CREATED,
+        tag_service(&app_state)
+            .create_tag(claims.user_id, create_request)
+            .await,
+    )
+}
+
+// List Tags Route
+#[utoipa::path(
+    get,
+    path = "/tags",
+    tag = "Tags",
+    params(
+        ("If-None-Match" = Option<String>, Header, description = "ETag of the cached tags, answered with 304 while current")
+    ),
+    responses(
+        (status = 200, description = "Tags listed successfully", body = [TagResponse],
+            headers(
+                ("ETag" = String, description = "Digest of the tags"),
+                ("Cache-Control" = String, description = "private, no-cache")
+            )),
+        (status = 304, description = "Cached tags still current"),
+        (status = 500, description = "Failed to list tags", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn list_tags_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    respond_cached(
+        &headers,
+        tag_service(&app_state).list_tags(claims.user_id).await,
+        |_| CacheValidators::default(),
+    )
+}
+
+// Delete Tag Route
+#[utoipa::path(
+    delete,
+    path = "/tags/{id}",
+    tag = "Tags",
+    params(
+        ("id" = i64, Path, description = "Tag id")
+    ),
+    responses(
+        (status = 200, description = "Tag deleted successfully", body = TagMessageResponse),
+        (status = 404, description = "Tag not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn delete_tag_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        tag_service(&app_state).delete_tag(claims.user_id, id).await,
+    )
+}
+
+// Bulk Attach Tag Route
+#[utoipa::path(
+    post,
+    path = "/tags/bulk/attach",
+    tag = "Tags",
+    request_body = BulkTagRequest,
+    responses(
+        (status = 200, description = "Tag attached to the matching todos, the tag is created when needed", body = BulkTagResponse),
+        (status = 400, description = "Invalid tag or filter", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn bulk_attach_tag_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    device: SessionDevice,
+    Json(bulk_request): Json<BulkTagRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        tag_service(&app_state)
+            .bulk_attach(
+                workspace.user_id,
+                workspace.workspace_id,
+                bulk_request,
+                &device,
+            )
+            .await,
+    )
+}
+
+// Bulk Detach Tag Route
+#[utoipa::path(
+    post,
+    path = "/tags/bulk/detach",
+    tag = "Tags",
+    request_body = BulkTagRequest,
+    responses(
+        (status = 200, description = "Tag detached from the matching todos", body = BulkTagResponse),
+        (status = 400, description = "Invalid filter", body = ErrorResponse),
+        (status = 404, description = "Tag not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn bulk_detach_tag_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    device: SessionDevice,
+    Json(bulk_request): Json<BulkTagRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        tag_service(&app_state)
+            .bulk_detach(
+                workspace.user_id,
+                workspace.workspace_id,
+                bulk_request,
+                &device,
+            )
+            .await,
+    )
+}
+
+// List Todo Tags Route
+#[utoipa::path(
+    get,
+    path = "/todos/{id}/tags",
+    tag = "Tags",
+    params(
+        ("id" = i64, Path, description = "Todo id")
+    ),
+    responses(
+        (status = 200, description = "Todo tags listed successfully", body = [TagResponse]),
+        (status = 500, description = "Failed to list todo tags", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn list_todo_tags_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        tag_service(&app_state)
+            .list_todo_tags(claims.user_id, id)
+            .await,
+    )
+}
+
+// Attach Tag Route
+#[utoipa::path(
+    post,
+    path = "/todos/{id}/tags",
+    tag = "Tags",
+    params(
+        ("id" = i64, Path, description = "Todo id")
+    ),
+    request_body = AttachTagRequest,
+    responses(
+        (status = 200, description = "Tag attached successfully", body = [TagResponse]),
+        (status = 400, description = "Missing tag", body = ErrorResponse),
+        (status = 404, description = "Todo or tag not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn attach_tag_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path(id): Path<i64>,
+    Json(attach_request): Json<AttachTagRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        tag_service(&app_state)
+            .attach_tag(
+                workspace.user_id,
+                workspace.workspace_id,
+                id,
+                attach_request,
+            )
+            .await,
+    )
+}
+
+// Detach Tag Route
+#[utoipa::path(
+    delete,
+    path = "/todos/{id}/tags/{tag_id}",
+    tag = "Tags",
+    params(
+        ("id" = i64, Path, description = "Todo id"),
+        ("tag_id" = i64, Path, description = "Tag id")
+    ),
+    responses(
+        (status = 200, description = "Tag detached successfully", body = TagMessageResponse),
+        (status = 404, description = "Tag is not attached to todo", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn detach_tag_route(
+    State(app_state): State<AppState>,
+    workspace: WorkspaceContext,
+    Path((id, tag_id)): Path<(i64, i64)>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        tag_service(&app_state)
+            .detach_tag(workspace.user_id, workspace.workspace_id, id, tag_id)
+            .await,
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_routes_creation() {
+        let _routes = tag_routes();
+        assert!(true);
+    }
+}
@@ -0,0 +1,366 @@
+//! # `Tag` Service
+//!
+//! This module contains the bussiness logic for tag operations. Every tag write drops
+//! the cached views of the user.
+
+use crate::{
+    modules::{
+        audit::{
+            interfaces::{AuditAction, NewAuditEntry},
+            service::AuditService,
+        },
+        common::AppError,
+        session::interfaces::SessionDevice,
+        tag::{
+            interfaces::{
+                AttachTagRequest, BulkTagFilter, BulkTagRequest, BulkTagResponse, BulkTagSelection,
+                CreateTagRequest, TagMessageResponse, TagResponse, ValidatedCreateTagRequest,
+            },
+            repository::TagRepository,
+        },
+        todo::{cache::ViewCache, interfaces::TodoStatus},
+    },
+    utils::validation::validate_required_fields,
+};
+
+// Maximum length of a tag name, matches the database column
+const MAX_TAG_NAME_LENGTH: usize = 64;
+
+// Trimmed tag name, refused when blank or too long
+fn validate_tag_name(name: Option<&str>) -> Result<&str, AppError> {
+    let Some(name) = name.map(str::trim).filter(|name| !name.is_empty()) else {
+        return Err(AppError::Validation(
+            "Missing required fields: tag".to_string(),
+        ));
+    };
+    if name.chars().count() > MAX_TAG_NAME_LENGTH {
+        return Err(AppError::Validation(format!(
+            "Tag name must have at most {MAX_TAG_NAME_LENGTH} characters"
+        )));
+    }
+    Ok(name)
+}
+
+// Validate the filter of a bulk tag operation
+fn validate_bulk_filter(filter: BulkTagFilter) -> Result<BulkTagSelection, AppError> {
+    let status = filter
+        .status
+        .as_deref()
+        .map(str::parse::<TodoStatus>)
+        .transpose()
+        .map_err(AppError::Validation)?;
+
+    Ok(BulkTagSelection {
+        list_id: filter.list_id,
+        status,
+        overdue: filter.overdue.unwrap_or(false),
+        tag: filter
+            .tag
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty()),
+        include_archived: filter.include_archived.unwrap_or(false),
+    })
+}
+
+pub struct TagService {
+    tag_repository: TagRepository,
+    audit_service: AuditService,
+    view_cache: ViewCache,
+}
+
+impl TagService {
+    pub const fn new(
+        tag_repository: TagRepository,
+        audit_service: AuditService,
+        view_cache: ViewCache,
+    ) -> Self {
+        Self {
+            tag_repository,
+            audit_service,
+            view_cache,
+        }
+    }
+
+    // Create a tag for the user
+    pub async fn create_tag(
+        &self,
+        user_id: i64,
+        create_request: CreateTagRequest,
+    ) -> Result<TagResponse, AppError> {
+        let validated: ValidatedCreateTagRequest =
+            match validate_required_fields(&create_request, vec!["name"]) {
+                Err(missing) => return Err(AppError::InvalidFields(missing)),
+                Ok(tag) => tag,
+            };
+
+        let name = validated.name.trim();
+        if name.chars().count() > MAX_TAG_NAME_LENGTH {
+            return Err(AppError::Validation(format!(
+                "Tag name must have at most {MAX_TAG_NAME_LENGTH} characters"
+            )));
+        }
+
+        match self.tag_repository.create_tag(user_id, name).await {
+            Ok(tag) => {
+                self.view_cache.invalidate(user_id).await;
+                Ok(TagResponse::from(tag))
+            }
+            Err(e) => {
+                tracing::warn!("Error creating tag: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // List the user tags
+    pub async fn list_tags(&self, user_id: i64) -> Result<Vec<TagResponse>, AppError> {
+        match self.tag_repository.list_tags(user_id).await {
+            Ok(tags) => Ok(tags.into_iter().map(TagResponse::from).collect()),
+            Err(e) => {
+                tracing::warn!("Error listing tags: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Delete a tag
+    pub async fn delete_tag(&self, user_id: i64, id: i64) -> Result<TagMessageResponse, AppError> {
+        match self.tag_repository.delete_tag(user_id, id).await {
+            Ok(true) => {
+                self.view_cache.invalidate(user_id).await;
+                Ok(TagMessageResponse {
+                    message: "Tag deleted successfully".to_string(),
+                })
+            }
+            Ok(false) => Err(AppError::NotFound("Tag not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error deleting tag: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Attach a tag to a todo, returning the todo tags
+    pub async fn attach_tag(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+        attach_request: AttachTagRequest,
+    ) -> Result<Vec<TagResponse>, AppError> {
+        let Some(tag_id) = attach_request.tag_id else {
+            return Err(AppError::Validation(
+                "Missing required fields: tag_id".to_string(),
+            ));
+        };
+
+        match self
+            .tag_repository
+            .attach_tag(user_id, workspace_id, todo_id, tag_id)
+            .await
+        {
+            Ok(true) => {
+                self.view_cache.invalidate(user_id).await;
+                self.list_todo_tags(user_id, todo_id).await
+            }
+            Ok(false) => Err(AppError::NotFound("Todo or tag not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error attaching tag: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Detach a tag from a todo
+    pub async fn detach_tag(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo_id: i64,
+        tag_id: i64,
+    ) -> Result<TagMessageResponse, AppError> {
+        match self
+            .tag_repository
+            .detach_tag(user_id, workspace_id, todo_id, tag_id)
+            .await
+        {
+            Ok(true) => {
+                self.view_cache.invalidate(user_id).await;
+                Ok(TagMessageResponse {
+                    message: "Tag detached successfully".to_string(),
+                })
+            }
+            Ok(false) => Err(AppError::NotFound(
+                "Tag is not attached to todo".to_string(),
+            )),
+            Err(e) => {
+                tracing::warn!("Error detaching tag: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Attach a tag to every todo of the workspace matching the filter
+    pub async fn bulk_attach(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        bulk_request: BulkTagRequest,
+        device: &SessionDevice,
+    ) -> Result<BulkTagResponse, AppError> {
+        let name = validate_tag_name(bulk_request.tag.as_deref())?;
+        let selection = validate_bulk_filter(bulk_request.filter)?;
+
+        match self
+            .tag_repository
+            .bulk_attach(user_id, workspace_id, name, &selection)
+            .await
+        {
+            Ok((tag, count)) => {
+                self.view_cache.invalidate(user_id).await;
+                self.record_bulk(
+                    user_id,
+                    AuditAction::TagBulkAttached,
+                    &tag.name,
+                    count,
+                    device,
+                )
+                .await;
+                Ok(BulkTagResponse {
+                    tag: TagResponse::from(tag),
+                    count,
+                })
+            }
+            Err(e) => {
+                tracing::warn!("Error attaching tag in bulk: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Detach a tag from every todo of the workspace matching the filter
+    pub async fn bulk_detach(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        bulk_request: BulkTagRequest,
+        device: &SessionDevice,
+    ) -> Result<BulkTagResponse, AppError> {
+        let name = validate_tag_name(bulk_request.tag.as_deref())?;
+        let selection = validate_bulk_filter(bulk_request.filter)?;
+
+        match self
+            .tag_repository
+            .bulk_detach(user_id, workspace_id, name, &selection)
+            .await
+        {
+            Ok(Some((tag, count))) => {
+                self.view_cache.invalidate(user_id).await;
+                self.record_bulk(
+                    user_id,
+                    AuditAction::TagBulkDetached,
+                    &tag.name,
+                    count,
+                    device,
+                )
+                .await;
+                Ok(BulkTagResponse {
+                    tag: TagResponse::from(tag),
+                    count,
+                })
+            }
+            Ok(None) => Err(AppError::NotFound("Tag not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error detaching tag in bulk: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Record a bulk operation in the audit log of the user
+    async fn record_bulk(
+        &self,
+        user_id: i64,
+        action: AuditAction,
+        name: &str,
+        count: u64,
+        device: &SessionDevice,
+    ) {
+        self.audit_service
+            .record(NewAuditEntry {
+                user_id: Some(user_id),
+                actor_id: Some(user_id),
+                action,
+                device,
+                details: Some(format!("tag {name:?} on {count} todos")),
+            })
+            .await;
+    }
+
+    // List the tags of a todo
+    pub async fn list_todo_tags(
+        &self,
+        user_id: i64,
+        todo_id: i64,
+    ) -> Result<Vec<TagResponse>, AppError> {
+        match self.tag_repository.list_todo_tags(user_id, todo_id).await {
+            Ok(tags) => Ok(tags.into_iter().map(TagResponse::from).collect()),
+            Err(e) => {
+                tracing::warn!("Error listing todo tags: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::utils::validation::ValidationErrors;
+
+    #[test]
+    fn test_create_tag_requires_name() {
+        let request = CreateTagRequest { name: None };
+        let result: Result<ValidatedCreateTagRequest, ValidationErrors> =
+            validate_required_fields(&request, vec!["name"]);
+        assert_eq!(result.unwrap_err().fields(), vec!["name"]);
+    }
+
+    #[test]
+    fn test_validate_tag_name() {
+        assert_eq!(validate_tag_name(Some(" late ")).unwrap(), "late");
+        assert!(validate_tag_name(Some("  ")).is_err());
+        assert!(validate_tag_name(None).is_err());
+        assert!(validate_tag_name(Some(&"a".repeat(MAX_TAG_NAME_LENGTH + 1))).is_err());
+    }
+
+    #[test]
+    fn test_validate_bulk_filter() {
+        let selection = validate_bulk_filter(BulkTagFilter {
+            list_id: Some(3),
+            status: Some("in_progress".to_string()),
+            overdue: Some(true),
+            tag: Some(" ".to_string()),
+            include_archived: None,
+        })
+        .unwrap();
+        assert_eq!(selection.list_id, Some(3));
+        assert_eq!(selection.status, Some(TodoStatus::InProgress));
+        assert!(selection.overdue);
+        assert_eq!(selection.tag, None);
+        assert!(!selection.include_archived);
+
+        assert!(validate_bulk_filter(BulkTagFilter {
+            status: Some("finished".to_string()),
+            ..BulkTagFilter::default()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_max_tag_name_length() {
+        let name = "a".repeat(MAX_TAG_NAME_LENGTH + 1);
+        assert!(name.chars().count() > MAX_TAG_NAME_LENGTH);
+    }
+}
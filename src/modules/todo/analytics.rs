@@ -0,0 +1,182 @@
+//! # `Todo` Analytics
+//! Completion analytics of the todos per list and per tag. The database counts the todos
+//! created and completed per period, this module validates the asked range and folds
+//! the rows into one breakdown per list or tag.
+
+use time::{Date, Duration, OffsetDateTime};
+
+use crate::{
+    modules::todo::interfaces::{
+        StatsBreakdownQuery, StatsBreakdownRow, StatsGroupResponse, StatsInterval,
+        StatsPeriodResponse,
+    },
+    utils::dates::parse_day,
+};
+
+// Days covered when no start day is asked
+const DEFAULT_RANGE_DAYS: i64 = 90;
+
+// Largest range a breakdown can cover
+const MAX_RANGE_DAYS: i64 = 731;
+
+// Range of days and period length of a breakdown
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StatsRange {
+    pub from: Date,
+    // Last day included
+    pub to: Date,
+    pub interval: StatsInterval,
+}
+
+impl StatsRange {
+    // Start of the first day
+    pub const fn start(&self) -> OffsetDateTime {
+        self.from.midnight().assume_utc()
+    }
+
+    // Start of the day after the last one
+    pub fn end(&self) -> OffsetDateTime {
+        self.to.midnight().assume_utc() + Duration::days(1)
+    }
+}
+
+// Validate the asked range, ending `today` and covering `DEFAULT_RANGE_DAYS` by default
+pub fn stats_range(query: &StatsBreakdownQuery, today: Date) -> Result<StatsRange, String> {
+    let to = query
+        .to
+        .as_deref()
+        .map(|to| parse_day(to, "to"))
+        .transpose()?
+        .unwrap_or(today);
+    let from = query
+        .from
+        .as_deref()
+        .map(|from| parse_day(from, "from"))
+        .transpose()?
+        .unwrap_or_else(|| to - Duration::days(DEFAULT_RANGE_DAYS - 1));
+
+    if from > to {
+        return Err("from must not be after to".to_string());
+    }
+    if (to - from).whole_days() >= MAX_RANGE_DAYS {
+        return Err(format!("Range must not exceed {MAX_RANGE_DAYS} days"));
+    }
+
+    Ok(StatsRange {
+        from,
+        to,
+        interval: query.interval.unwrap_or_default(),
+    })
+}
+
+// Fold the rows of each list or tag into its breakdown. `rows` come sorted by group.
+pub fn group_breakdown(rows: Vec<StatsBreakdownRow>) -> Vec<StatsGroupResponse> {
+    let mut groups: Vec<(StatsGroupResponse, f64)> = Vec::new();
+    for row in rows {
+        let id = row.group_id.map(i64::from);
+        if !groups.last().is_some_and(|(group, _)| group.id == id) {
+            groups.push((
+                StatsGroupResponse {
+                    id,
+                    name: row.name,
+                    created: 0,
+                    completed: 0,
+                    avg_completion_hours: None,
+                    periods: Vec::new(),
+                },
+                0.0,
+            ));
+        }
+        if let Some((group, completion_seconds)) = groups.last_mut() {
+            group.created += row.created;
+            group.completed += row.completed;
+            *completion_seconds += row.completion_seconds;
+            group.periods.push(StatsPeriodResponse {
+                start: row.period.date().to_string(),
+                created: row.created,
+                completed: row.completed,
+            });
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(mut group, completion_seconds)| {
+            if group.completed > 0 {
+                #[allow(clippy::cast_precision_loss)]
+                let completed = group.completed as f64;
+                group.avg_completion_hours = Some(completion_seconds / completed / 3600.0);
+            }
+            group
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use time::macros::{date, datetime};
+
+    use super::*;
+
+    fn query(from: Option<&str>, to: Option<&str>) -> StatsBreakdownQuery {
+        StatsBreakdownQuery {
+            from: from.map(str::to_string),
+            to: to.map(str::to_string),
+            interval: None,
+        }
+    }
+
+    fn row(
+        group_id: Option<i32>,
+        day: u8,
+        created: i64,
+        completed: i64,
+        completion_seconds: f64,
+    ) -> StatsBreakdownRow {
+        StatsBreakdownRow {
+            group_id,
+            name: group_id.map(|id| format!("group {id}")),
+            period: datetime!(2025-03-01 0:00 UTC).replace_day(day).unwrap(),
+            created,
+            completed,
+            completion_seconds,
+        }
+    }
+
+    #[test]
+    fn test_stats_range_defaults_to_recent_days() {
+        let range = stats_range(&query(None, None), date!(2025 - 03 - 31)).unwrap();
+        assert_eq!(range.to, date!(2025 - 03 - 31));
+        assert_eq!(range.from, date!(2025 - 01 - 01));
+        assert_eq!(range.interval, StatsInterval::Week);
+        assert_eq!(range.end(), datetime!(2025-04-01 0:00 UTC));
+    }
+
+    #[test]
+    fn test_stats_range_rejects_invalid_ranges() {
+        let today = date!(2025 - 03 - 31);
+        assert!(stats_range(&query(Some("2025-03-02"), Some("2025-03-01")), today).is_err());
+        assert!(stats_range(&query(Some("03/01/2025"), None), today).is_err());
+        assert!(stats_range(&query(Some("2020-01-01"), Some("2025-01-01")), today).is_err());
+        assert!(stats_range(&query(Some("2025-03-01"), Some("2025-03-01")), today).is_ok());
+    }
+
+    #[test]
+    fn test_group_breakdown_folds_periods() {
+        let groups = group_breakdown(vec![
+            row(Some(1), 3, 2, 1, 3600.0),
+            row(Some(1), 10, 1, 1, 10800.0),
+            row(None, 3, 4, 0, 0.0),
+        ]);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].id, Some(1));
+        assert_eq!(groups[0].created, 3);
+        assert_eq!(groups[0].completed, 2);
+        assert_eq!(groups[0].avg_completion_hours, Some(2.0));
+        assert_eq!(groups[0].periods[1].start, "2025-03-10");
+        assert_eq!(groups[1].id, None);
+        assert_eq!(groups[1].avg_completion_hours, None);
+    }
+}
@@ -0,0 +1,208 @@
+//! # `Todo` View Cache
+//! Short lived, per user cache for the smart views polled by dashboards.
+//! Entries remember the workspace they were loaded from, a user switching workspaces
+//! misses the cache instead of seeing the views of the previous one.
+
+use std::time::Duration;
+
+use moka::future::Cache;
+
+use crate::modules::todo::interfaces::{
+    SmartView, StatsBreakdownResponse, StatsGroup, TodoResponse, TodoStatsResponse,
+};
+
+// Upper bound of cached entries, old entries are evicted first
+const MAX_CACHED_VIEWS: u64 = 10_000;
+
+#[derive(Clone)]
+pub struct ViewCache {
+    todos: Cache<(i64, SmartView), (i64, Vec<TodoResponse>)>,
+    stats: Cache<i64, (i64, TodoStatsResponse)>,
+    // Last breakdown loaded per grouping, dashboards poll the same range
+    breakdowns: Cache<(i64, StatsGroup), (i64, StatsBreakdownResponse)>,
+}
+
+impl ViewCache {
+    // Create a cache whose entries expire after `ttl`
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            todos: Cache::builder()
+                .max_capacity(MAX_CACHED_VIEWS)
+                .time_to_live(ttl)
+                .build(),
+            stats: Cache::builder()
+                .max_capacity(MAX_CACHED_VIEWS)
+                .time_to_live(ttl)
+                .build(),
+            breakdowns: Cache::builder()
+                .max_capacity(MAX_CACHED_VIEWS)
+                .time_to_live(ttl)
+                .build(),
+        }
+    }
+
+    pub async fn todos(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        view: SmartView,
+    ) -> Option<Vec<TodoResponse>> {
+        self.todos
+            .get(&(user_id, view))
+            .await
+            .filter(|(cached_workspace, _)| *cached_workspace == workspace_id)
+            .map(|(_, todos)| todos)
+    }
+
+    pub async fn store_todos(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        view: SmartView,
+        todos: Vec<TodoResponse>,
+    ) {
+        self.todos
+            .insert((user_id, view), (workspace_id, todos))
+            .await;
+    }
+
+    pub async fn stats(&self, user_id: i64, workspace_id: i64) -> Option<TodoStatsResponse> {
+        self.stats
+            .get(&user_id)
+            .await
+            .filter(|(cached_workspace, _)| *cached_workspace == workspace_id)
+            .map(|(_, stats)| stats)
+    }
+
+    pub async fn store_stats(&self, user_id: i64, workspace_id: i64, stats: TodoStatsResponse) {
+        self.stats.insert(user_id, (workspace_id, stats)).await;
+    }
+
+    // Cached breakdown matching the asked range and interval
+    pub async fn breakdown(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        group: StatsGroup,
+        matches: impl Fn(&StatsBreakdownResponse) -> bool,
+    ) -> Option<StatsBreakdownResponse> {
+        self.breakdowns
+            .get(&(user_id, group))
+            .await
+            .filter(|(cached_workspace, breakdown)| {
+                *cached_workspace == workspace_id && matches(breakdown)
+            })
+            .map(|(_, breakdown)| breakdown)
+    }
+
+    pub async fn store_breakdown(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        group: StatsGroup,
+        breakdown: StatsBreakdownResponse,
+    ) {
+        self.breakdowns
+            .insert((user_id, group), (workspace_id, breakdown))
+            .await;
+    }
+
+    // Drop every cached view of the user, called after any write to its todos
+    pub async fn invalidate(&self, user_id: i64) {
+        for view in SmartView::ALL {
+            self.todos.invalidate(&(user_id, view)).await;
+        }
+        self.stats.invalidate(&user_id).await;
+        for group in StatsGroup::ALL {
+            self.breakdowns.invalidate(&(user_id, group)).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::todo::interfaces::StatsInterval;
+
+    fn stats() -> TodoStatsResponse {
+        TodoStatsResponse {
+            total: 3,
+            completed: 1,
+            open: 2,
+            overdue: 1,
+            due_today: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cached_views_are_returned() {
+        let cache = ViewCache::new(Duration::from_secs(60));
+        cache.store_todos(1, 10, SmartView::Today, Vec::new()).await;
+        cache.store_stats(1, 10, stats()).await;
+
+        assert!(cache.todos(1, 10, SmartView::Today).await.is_some());
+        assert!(cache.todos(1, 10, SmartView::Upcoming).await.is_none());
+        assert_eq!(cache.stats(1, 10).await.map(|stats| stats.total), Some(3));
+        assert!(cache.stats(2, 10).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cached_views_are_scoped_to_workspace() {
+        let cache = ViewCache::new(Duration::from_secs(60));
+        cache.store_todos(1, 10, SmartView::Today, Vec::new()).await;
+        cache.store_stats(1, 10, stats()).await;
+
+        assert!(cache.todos(1, 11, SmartView::Today).await.is_none());
+        assert!(cache.stats(1, 11).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cached_breakdown_matches_range() {
+        let cache = ViewCache::new(Duration::from_secs(60));
+        let breakdown = StatsBreakdownResponse {
+            from: "2025-01-01".to_string(),
+            to: "2025-03-31".to_string(),
+            interval: StatsInterval::Week,
+            groups: Vec::new(),
+        };
+        cache
+            .store_breakdown(1, 10, StatsGroup::Tag, breakdown)
+            .await;
+
+        let same_range = |cached: &StatsBreakdownResponse| cached.from == "2025-01-01";
+        assert!(cache
+            .breakdown(1, 10, StatsGroup::Tag, same_range)
+            .await
+            .is_some());
+        assert!(cache
+            .breakdown(1, 10, StatsGroup::List, same_range)
+            .await
+            .is_none());
+        assert!(cache
+            .breakdown(1, 10, StatsGroup::Tag, |cached| cached.from == "2024-01-01")
+            .await
+            .is_none());
+
+        cache.invalidate(1).await;
+        assert!(cache
+            .breakdown(1, 10, StatsGroup::Tag, same_range)
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_only_drops_user_views() {
+        let cache = ViewCache::new(Duration::from_secs(60));
+        cache
+            .store_todos(1, 10, SmartView::Upcoming, Vec::new())
+            .await;
+        cache.store_stats(1, 10, stats()).await;
+        cache.store_stats(2, 10, stats()).await;
+
+        cache.invalidate(1).await;
+
+        assert!(cache.todos(1, 10, SmartView::Upcoming).await.is_none());
+        assert!(cache.stats(1, 10).await.is_none());
+        assert!(cache.stats(2, 10).await.is_some());
+    }
+}
@@ -0,0 +1,185 @@
+//! # `Todo` Create Deduplication
+//! Opt-in detection of double-submitted todo creations.
+
+use std::{future::Future, sync::Arc, time::Duration};
+
+use moka::future::Cache;
+use sha2::{Digest, Sha256};
+
+use crate::modules::{
+    common::AppError,
+    todo::interfaces::{CreateTodoRequest, TodoResponse},
+};
+
+// Upper bound of remembered creations, old entries are evicted first
+const MAX_REMEMBERED_CREATIONS: u64 = 10_000;
+
+#[derive(Clone)]
+pub struct CreateDedup {
+    // `None` when deduplication is disabled
+    recent: Option<Cache<(i64, [u8; 32]), TodoResponse>>,
+}
+
+impl CreateDedup {
+    // Remember creations for `window`, a zero window disables deduplication
+    pub fn new(window: Duration) -> Self {
+        Self {
+            recent: (!window.is_zero()).then(|| {
+                Cache::builder()
+                    .max_capacity(MAX_REMEMBERED_CREATIONS)
+                    .time_to_live(window)
+                    .build()
+            }),
+        }
+    }
+
+    // Run `create` unless the user sent an identical request within the window.
+    // Concurrent duplicates wait for the first one and share its todo.
+    // Returns the todo and whether it was created by this call.
+    pub async fn create<F>(
+        &self,
+        user_id: i64,
+        request: &CreateTodoRequest,
+        create: F,
+    ) -> Result<(TodoResponse, bool), AppError>
+    where
+        F: Future<Output = Result<TodoResponse, AppError>>,
+    {
+        let Some(recent) = &self.recent else {
+            return create.await.map(|todo| (todo, true));
+        };
+        let Some(fingerprint) = fingerprint(request) else {
+            return create.await.map(|todo| (todo, true));
+        };
+
+        match recent
+            .entry((user_id, fingerprint))
+            .or_try_insert_with(create)
+            .await
+        {
+            Ok(entry) => {
+                let created = entry.is_fresh();
+                Ok((entry.into_value(), created))
+            }
+            // Failed creations are not remembered, so a retry runs again
+            Err(error) => Err(shared_error(error)),
+        }
+    }
+}
+
+// Error of a failed creation, also returned to the concurrent duplicates that waited for
+// it. They get a copy, the database error itself can't be copied.
+fn shared_error(error: Arc<AppError>) -> AppError {
+    Arc::try_unwrap(error).unwrap_or_else(|error| match error.as_ref() {
+        AppError::Validation(message) => AppError::Validation(message.clone()),
+        AppError::InvalidFields(errors) => AppError::InvalidFields(errors.clone()),
+        AppError::NotFound(message) => AppError::NotFound(message.clone()),
+        AppError::Conflict(message) => AppError::Conflict(message.clone()),
+        AppError::FieldConflict(errors) => AppError::FieldConflict(errors.clone()),
+        AppError::Unauthorized(message) => AppError::Unauthorized(message.clone()),
+        AppError::Forbidden(message) => AppError::Forbidden(message.clone()),
+        AppError::Rejected(code, message) => AppError::Rejected(code, message.clone()),
+        AppError::PasswordResetRequired(message) => {
+            AppError::PasswordResetRequired(message.clone())
+        }
+        AppError::PreconditionFailed(message) => AppError::PreconditionFailed(message.clone()),
+        AppError::PreconditionRequired(message) => AppError::PreconditionRequired(message.clone()),
+        AppError::Database(e) => AppError::Internal(format!("Database error: {e}")),
+        AppError::Internal(message) => AppError::Internal(message.clone()),
+    })
+}
+
+// Hash of the request body, identical bodies give identical fingerprints
+fn fingerprint(request: &CreateTodoRequest) -> Option<[u8; 32]> {
+    let body = serde_json::to_vec(request).ok()?;
+    Some(Sha256::digest(&body).into())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::modules::todo::interfaces::TodoStatus;
+    use std::sync::atomic::{AtomicI64, Ordering};
+
+    fn request(title: &str) -> CreateTodoRequest {
+        CreateTodoRequest {
+            title: Some(title.to_string()),
+            description: None,
+            list_id: None,
+            due_at: None,
+            recurrence: None,
+            scheduled_for: None,
+        }
+    }
+
+    fn todo(id: i64) -> TodoResponse {
+        TodoResponse {
+            id,
+            list_id: Some(1),
+            title: "Buy milk".to_string(),
+            description: None,
+            status: TodoStatus::Backlog,
+            status_changed_at: None,
+            completed_at: None,
+            due_at: None,
+            recurrence: None,
+            scheduled_for: None,
+            archived: false,
+            version: 1,
+            created_at: None,
+            updated_at: None,
+            deleted_at: None,
+            warnings: None,
+        }
+    }
+
+    async fn create(
+        dedup: &CreateDedup,
+        user_id: i64,
+        title: &str,
+        next_id: &AtomicI64,
+    ) -> (i64, bool) {
+        let (todo, created) = dedup
+            .create(user_id, &request(title), async {
+                Ok(todo(next_id.fetch_add(1, Ordering::SeqCst)))
+            })
+            .await
+            .unwrap();
+        (todo.id, created)
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_returns_original() {
+        let dedup = CreateDedup::new(Duration::from_secs(10));
+        let next_id = AtomicI64::new(1);
+
+        assert_eq!(create(&dedup, 1, "Buy milk", &next_id).await, (1, true));
+        assert_eq!(create(&dedup, 1, "Buy milk", &next_id).await, (1, false));
+        assert_eq!(create(&dedup, 1, "Buy eggs", &next_id).await, (2, true));
+        assert_eq!(create(&dedup, 2, "Buy milk", &next_id).await, (3, true));
+    }
+
+    #[tokio::test]
+    async fn test_disabled_always_creates() {
+        let dedup = CreateDedup::new(Duration::ZERO);
+        let next_id = AtomicI64::new(1);
+
+        assert_eq!(create(&dedup, 1, "Buy milk", &next_id).await, (1, true));
+        assert_eq!(create(&dedup, 1, "Buy milk", &next_id).await, (2, true));
+    }
+
+    #[tokio::test]
+    async fn test_failures_are_not_remembered() {
+        let dedup = CreateDedup::new(Duration::from_secs(10));
+        let failed = dedup
+            .create(1, &request("Buy milk"), async {
+                Err(AppError::NotFound("List not found".to_string()))
+            })
+            .await;
+        assert!(failed.is_err());
+
+        let next_id = AtomicI64::new(1);
+        assert_eq!(create(&dedup, 1, "Buy milk", &next_id).await, (1, true));
+    }
+}
@@ -0,0 +1,235 @@
+//! # `Todo` Export
+//! Export of the todos of a workspace as JSON Lines, one todo per line with its tags and
+//! checklist items, and its due date as the user reads it. The todos are read by pages and
+//! streamed as they are read, so large accounts are never held in memory. The pages are
+//! shared with the export of the whole account.
+
+use std::collections::HashMap;
+
+use axum::body::Body;
+use tokio::io::{AsyncWriteExt, DuplexStream};
+use tokio_util::io::ReaderStream;
+
+use crate::modules::{
+    item::interfaces::{ItemResponse, ItemRow},
+    todo::{
+        interfaces::{TodoExportLine, TodoLocalDueRow, TodoRow, TodoTagNameRow},
+        repository::TodoRepository,
+    },
+};
+use crate::utils::format::Formatter;
+
+// Content type of the JSON Lines exports
+pub const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+
+// Todos read from the database at once
+const EXPORT_PAGE_SIZE: i64 = 500;
+
+// Bytes buffered between the database reads and the response
+const EXPORT_BUFFER_BYTES: usize = 64 * 1024;
+
+// Page of exported todos, `next_after` is set while more pages follow
+pub struct ExportPage {
+    pub lines: Vec<TodoExportLine>,
+    pub next_after: Option<i32>,
+}
+
+// Export lines of a page of todos with their tags, checklist items and due dates
+pub fn export_lines(
+    rows: Vec<TodoRow>,
+    tags: Vec<TodoTagNameRow>,
+    items: Vec<ItemRow>,
+    due_dates: Vec<TodoLocalDueRow>,
+) -> Vec<TodoExportLine> {
+    let mut tags_by_todo: HashMap<i32, Vec<String>> = HashMap::new();
+    for tag in tags {
+        tags_by_todo.entry(tag.todo_id).or_default().push(tag.name);
+    }
+    let mut items_by_todo: HashMap<i32, Vec<ItemResponse>> = HashMap::new();
+    for item in items {
+        items_by_todo
+            .entry(item.todo_id)
+            .or_default()
+            .push(ItemResponse::from(item));
+    }
+    let mut due_labels: HashMap<i32, String> = due_dates
+        .into_iter()
+        .map(|due| {
+            let label = Formatter::new(&due.locale, due.time_format.as_deref())
+                .zoned_datetime(due.local_due_at, &due.timezone);
+            (due.todo_id, label)
+        })
+        .collect();
+
+    rows.into_iter()
+        .map(|row| {
+            let id = row.id;
+            TodoExportLine {
+                todo: row.into(),
+                tags: tags_by_todo.remove(&id).unwrap_or_default(),
+                subtasks: items_by_todo.remove(&id).unwrap_or_default(),
+                due_label: due_labels.remove(&id),
+            }
+        })
+        .collect()
+}
+
+// Export lines as JSON Lines, each ending with a newline
+pub fn ndjson(lines: &[TodoExportLine]) -> Result<Vec<u8>, serde_json::Error> {
+    let mut bytes = Vec::new();
+    for line in lines {
+        serde_json::to_writer(&mut bytes, line)?;
+        bytes.push(b'\n');
+    }
+    Ok(bytes)
+}
+
+// Page of the todos of the user after `after_id`, in the workspace or in all of them
+pub async fn export_page(
+    todo_repository: &TodoRepository,
+    user_id: i64,
+    workspace_id: Option<i64>,
+    after_id: Option<i32>,
+) -> Result<ExportPage, sqlx::Error> {
+    let rows = todo_repository
+        .export_todos(user_id, workspace_id, after_id, EXPORT_PAGE_SIZE)
+        .await?;
+    let page_full = i64::try_from(rows.len()).unwrap_or(i64::MAX) >= EXPORT_PAGE_SIZE;
+    let next_after = rows.last().filter(|_| page_full).map(|last| last.id);
+    if rows.is_empty() {
+        return Ok(ExportPage {
+            lines: Vec::new(),
+            next_after,
+        });
+    }
+
+    let ids: Vec<i32> = rows.iter().map(|row| row.id).collect();
+    let tags = todo_repository.export_tags(&ids).await?;
+    let items = todo_repository.export_items(&ids).await?;
+    let due_dates = todo_repository.export_due_dates(user_id, &ids).await?;
+
+    Ok(ExportPage {
+        lines: export_lines(rows, tags, items, due_dates),
+        next_after,
+    })
+}
+
+// Write every todo of the user in the workspace to the pipe, page by page
+async fn write_export(
+    todo_repository: &TodoRepository,
+    user_id: i64,
+    workspace_id: i64,
+    mut writer: DuplexStream,
+) -> Result<(), String> {
+    let mut after_id = None;
+    loop {
+        let page = export_page(todo_repository, user_id, Some(workspace_id), after_id)
+            .await
+            .map_err(|e| e.to_string())?;
+        let lines = ndjson(&page.lines).map_err(|e| e.to_string())?;
+        // Fails when the client went away
+        writer.write_all(&lines).await.map_err(|e| e.to_string())?;
+
+        after_id = page.next_after;
+        if after_id.is_none() {
+            break;
+        }
+    }
+    writer.shutdown().await.map_err(|e| e.to_string())
+}
+
+// Body streaming the export of the todos of the user in the workspace. The status is
+// sent before the todos are read, a failure ends the export early and is logged.
+pub fn export_body(todo_repository: TodoRepository, user_id: i64, workspace_id: i64) -> Body {
+    let (reader, writer) = tokio::io::duplex(EXPORT_BUFFER_BYTES);
+    tokio::spawn(async move {
+        if let Err(e) = write_export(&todo_repository, user_id, workspace_id, writer).await {
+            tracing::warn!("Export of the todos of user {} ended early: {}", user_id, e);
+        }
+    });
+    Body::from_stream(ReaderStream::new(reader))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn todo_row(id: i32, title: &str) -> TodoRow {
+        TodoRow {
+            id,
+            list_id: None,
+            title: title.to_string(),
+            description: None,
+            status: "backlog".to_string(),
+            status_changed_at: None,
+            completed_at: None,
+            due_at: None,
+            recurrence: None,
+            scheduled_for: None,
+            archived: false,
+            version: 1,
+            created_at: None,
+            updated_at: None,
+            deleted_at: None,
+        }
+    }
+
+    #[test]
+    fn test_export_lines() {
+        let lines = ndjson(&export_lines(
+            vec![todo_row(1, "Pay rent"), todo_row(2, "Call mom")],
+            vec![
+                TodoTagNameRow {
+                    todo_id: 1,
+                    name: "home".to_string(),
+                },
+                TodoTagNameRow {
+                    todo_id: 1,
+                    name: "money".to_string(),
+                },
+            ],
+            vec![ItemRow {
+                id: 7,
+                todo_id: 2,
+                title: "Find number".to_string(),
+                completed: true,
+                position: 0,
+                created_at: None,
+                updated_at: None,
+            }],
+            vec![TodoLocalDueRow {
+                todo_id: 2,
+                local_due_at: time::macros::datetime!(2025-03-10 18:30),
+                timezone: "Europe/Paris".to_string(),
+                locale: "fr-FR".to_string(),
+                time_format: None,
+            }],
+        ))
+        .unwrap();
+
+        let text = String::from_utf8(lines).unwrap();
+        let lines: Vec<serde_json::Value> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert!(text.ends_with('\n'));
+
+        assert_eq!(lines[0]["id"], 1);
+        assert_eq!(lines[0]["title"], "Pay rent");
+        assert_eq!(lines[0]["tags"], serde_json::json!(["home", "money"]));
+        assert_eq!(lines[0]["subtasks"], serde_json::json!([]));
+        assert!(lines[0].get("due_label").is_none());
+
+        assert_eq!(lines[1]["tags"], serde_json::json!([]));
+        assert_eq!(lines[1]["subtasks"][0]["title"], "Find number");
+        assert_eq!(lines[1]["subtasks"][0]["completed"], true);
+        assert_eq!(lines[1]["due_label"], "10/03/2025 18:30 (Europe/Paris)");
+    }
+
+    #[test]
+    fn test_export_lines_empty() {
+        assert!(export_lines(vec![], vec![], vec![], vec![]).is_empty());
+    }
+}
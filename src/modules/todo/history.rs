@@ -0,0 +1,150 @@
+//! # `Todo` History
+//! Edits of the todo title, description and due date are recorded per field by a trigger.
+//! This module groups them per version and rebuilds the fields as they were at a version.
+
+use time::{format_description::well_known::Rfc3339, UtcOffset};
+
+use crate::modules::todo::interfaces::{
+    TodoFieldChange, TodoHistoryEntryResponse, TodoHistoryRow, TodoRestore, TodoRow,
+};
+
+// Field values of a todo in the form they are recorded in the history
+pub fn current_fields(todo: &TodoRow) -> TodoRestore {
+    let due_at = todo.due_at.map(|due_at| {
+        due_at
+            .to_offset(UtcOffset::UTC)
+            .replace_nanosecond(0)
+            .ok()
+            .and_then(|due_at| due_at.format(&Rfc3339).ok())
+    });
+
+    TodoRestore {
+        title: Some(todo.title.clone()),
+        description: Some(todo.description.clone()),
+        due_at: Some(due_at.flatten()),
+    }
+}
+
+// Group the field edits per version, newest first. `rows` come sorted by version.
+pub fn group_entries(rows: Vec<TodoHistoryRow>) -> Vec<TodoHistoryEntryResponse> {
+    let mut entries: Vec<TodoHistoryEntryResponse> = Vec::new();
+    for row in rows {
+        let change = TodoFieldChange {
+            field: row.field,
+            from: row.old_value,
+            to: row.new_value,
+        };
+        match entries.last_mut() {
+            Some(entry) if entry.version == row.version => entry.changes.push(change),
+            _ => entries.push(TodoHistoryEntryResponse {
+                version: row.version,
+                changed_by: row.changed_by.map(i64::from),
+                changed_by_username: row.username,
+                changed_at: row.changed_at,
+                changes: vec![change],
+            }),
+        }
+    }
+    entries.reverse();
+    entries
+}
+
+// Value of a field at `version`: the last edit up to it, or the value the first later
+// edit replaced. `None` when the field was never edited.
+#[allow(clippy::option_option)]
+fn value_at(rows: &[TodoHistoryRow], field: &str, version: i64) -> Option<Option<String>> {
+    let mut value = None;
+    for row in rows.iter().filter(|row| row.field == field) {
+        if row.version > version {
+            return Some(value.unwrap_or_else(|| row.old_value.clone()));
+        }
+        value = Some(row.new_value.clone());
+    }
+    value
+}
+
+// Fields to change so the todo matches `version` again, `current` are the field values
+// of the todo today. Unchanged fields are left out.
+pub fn restore_to(rows: &[TodoHistoryRow], version: i64, current: &TodoRestore) -> TodoRestore {
+    let title = value_at(rows, "title", version)
+        .flatten()
+        .filter(|title| Some(title) != current.title.as_ref());
+    let description = value_at(rows, "description", version)
+        .filter(|description| Some(description) != current.description.as_ref());
+    let due_at =
+        value_at(rows, "due_at", version).filter(|due_at| Some(due_at) != current.due_at.as_ref());
+
+    TodoRestore {
+        title,
+        description,
+        due_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(version: i64, field: &str, old: Option<&str>, new: Option<&str>) -> TodoHistoryRow {
+        TodoHistoryRow {
+            version,
+            changed_by: Some(1),
+            username: Some("ana".to_string()),
+            field: field.to_string(),
+            old_value: old.map(str::to_string),
+            new_value: new.map(str::to_string),
+            changed_at: None,
+        }
+    }
+
+    fn history() -> Vec<TodoHistoryRow> {
+        vec![
+            row(2, "title", Some("Milk"), Some("Buy milk")),
+            row(2, "description", None, Some("2 liters")),
+            row(5, "title", Some("Buy milk"), Some("Buy oat milk")),
+            row(7, "description", Some("2 liters"), None),
+        ]
+    }
+
+    #[test]
+    fn test_group_entries_newest_first() {
+        let entries = group_entries(history());
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| entry.version)
+                .collect::<Vec<_>>(),
+            vec![7, 5, 2]
+        );
+        assert_eq!(entries[2].changes.len(), 2);
+        assert_eq!(entries[2].changes[0].from.as_deref(), Some("Milk"));
+    }
+
+    #[test]
+    fn test_restore_to_rebuilds_fields() {
+        let current = TodoRestore {
+            title: Some("Buy oat milk".to_string()),
+            description: Some(None),
+            due_at: Some(None),
+        };
+
+        // Before the first recorded edit
+        assert_eq!(
+            restore_to(&history(), 1, &current),
+            TodoRestore {
+                title: Some("Milk".to_string()),
+                description: None,
+                due_at: None,
+            }
+        );
+        assert_eq!(
+            restore_to(&history(), 5, &current),
+            TodoRestore {
+                title: None,
+                description: Some(Some("2 liters".to_string())),
+                due_at: None,
+            }
+        );
+        assert!(restore_to(&history(), 7, &current).is_empty());
+    }
+}
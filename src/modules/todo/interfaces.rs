@@ -0,0 +1,557 @@
+//! # `Todos` Interfaces
+//! This module defines the data structures from Todos module
+
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use time::{OffsetDateTime, PrimitiveDateTime};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::modules::item::interfaces::ItemResponse;
+use crate::utils::dates::rfc3339_option;
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct CreateTodoRequest {
+    // Todo title
+    pub title: Option<String>,
+    // Todo description
+    pub description: Option<String>,
+    // List the todo belongs to
+    pub list_id: Option<i64>,
+    // Due date in RFC 3339 format
+    pub due_at: Option<String>,
+    // iCalendar RRULE, e.g. `FREQ=WEEKLY;BYDAY=MO,TH`
+    pub recurrence: Option<String>,
+    // Date in RFC 3339 format the todo becomes active, hidden from the views until then
+    pub scheduled_for: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ValidatedCreateTodoRequest {
+    pub title: String,
+    pub description: Option<String>,
+    pub list_id: Option<i64>,
+    pub due_at: Option<String>,
+    pub recurrence: Option<String>,
+    pub scheduled_for: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct UpdateTodoRequest {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    // One of `backlog`, `in_progress`, `blocked`, `done` or `cancelled`
+    pub status: Option<String>,
+    // Due date in RFC 3339 format
+    pub due_at: Option<String>,
+    // iCalendar RRULE, an empty string stops the recurrence
+    pub recurrence: Option<String>,
+    // Activation date in RFC 3339 format, an empty string activates the todo now
+    pub scheduled_for: Option<String>,
+    // Only update the todo while it is still at this version
+    pub version: Option<i64>,
+}
+
+// Todo changes after validation, `None` fields are kept as they are
+#[derive(Clone, Debug, Default)]
+pub struct TodoChanges {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub status: Option<TodoStatus>,
+    pub due_at: Option<OffsetDateTime>,
+    // `Some("")` clears the recurrence
+    pub recurrence: Option<String>,
+    // `Some(None)` activates the todo
+    #[allow(clippy::option_option)]
+    pub scheduled_for: Option<Option<OffsetDateTime>>,
+    // Version the todo must be at for the changes to apply
+    pub expected_version: Option<i64>,
+}
+
+// Step of the todo workflow
+#[derive(Serialize, Deserialize, ToSchema, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TodoStatus {
+    #[default]
+    Backlog,
+    InProgress,
+    Blocked,
+    Done,
+    Cancelled,
+}
+
+impl TodoStatus {
+    pub const ALL: [Self; 5] = [
+        Self::Backlog,
+        Self::InProgress,
+        Self::Blocked,
+        Self::Done,
+        Self::Cancelled,
+    ];
+
+    // Value stored in the `status` column
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Backlog => "backlog",
+            Self::InProgress => "in_progress",
+            Self::Blocked => "blocked",
+            Self::Done => "done",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    // Whether a todo can move from this status to `next`. Closed todos have to be
+    // reopened first, e.g. a cancelled todo goes back to the backlog before done.
+    pub const fn can_move_to(self, next: Self) -> bool {
+        match (self, next) {
+            (Self::Backlog | Self::InProgress, _) => true,
+            (Self::Blocked, next) => !matches!(next, Self::Done),
+            (Self::Done, next) => matches!(next, Self::Done | Self::Backlog | Self::InProgress),
+            (Self::Cancelled, next) => matches!(next, Self::Cancelled | Self::Backlog),
+        }
+    }
+}
+
+impl fmt::Display for TodoStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TodoStatus {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|status| status.as_str() == value.trim())
+            .ok_or_else(|| {
+                let allowed: Vec<&str> = Self::ALL.iter().map(|status| status.as_str()).collect();
+                format!("Status must be one of: {}", allowed.join(", "))
+            })
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct AssignListRequest {
+    // List to attach the todo to, `null` detaches it
+    pub list_id: Option<i64>,
+}
+
+#[derive(Deserialize, IntoParams, Clone, Debug, Default)]
+pub struct TodoFilter {
+    /// Only return todos attached to this list
+    pub list_id: Option<i64>,
+    /// Only return todos tagged with this tag name
+    pub tag: Option<String>,
+    /// Also return archived todos, defaults to false
+    pub include_archived: Option<bool>,
+    /// Also return the scheduled todos not active yet, defaults to false
+    pub include_scheduled: Option<bool>,
+}
+
+#[derive(Deserialize, IntoParams, Clone, Debug, Default)]
+pub struct OccurrencesQuery {
+    /// Number of occurrences to preview, defaults to 5 and is capped at 50
+    pub count: Option<usize>,
+}
+
+// Todo row as stored in database
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct TodoRow {
+    pub id: i32,
+    pub list_id: Option<i32>,
+    pub title: String,
+    pub description: Option<String>,
+    pub status: String,
+    pub status_changed_at: Option<OffsetDateTime>,
+    pub completed_at: Option<OffsetDateTime>,
+    pub due_at: Option<OffsetDateTime>,
+    pub recurrence: Option<String>,
+    pub scheduled_for: Option<OffsetDateTime>,
+    pub archived: bool,
+    pub version: i64,
+    pub created_at: Option<OffsetDateTime>,
+    pub updated_at: Option<OffsetDateTime>,
+    pub deleted_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct TodoResponse {
+    pub id: i64,
+    pub list_id: Option<i64>,
+    pub title: String,
+    pub description: Option<String>,
+    pub status: TodoStatus,
+    // Last time the status changed
+    #[serde(with = "rfc3339_option")]
+    pub status_changed_at: Option<OffsetDateTime>,
+    // Set while the todo is done
+    #[serde(with = "rfc3339_option")]
+    pub completed_at: Option<OffsetDateTime>,
+    #[serde(with = "rfc3339_option")]
+    pub due_at: Option<OffsetDateTime>,
+    pub recurrence: Option<String>,
+    // Set while the todo waits for its activation date
+    #[serde(with = "rfc3339_option")]
+    pub scheduled_for: Option<OffsetDateTime>,
+    pub archived: bool,
+    // Bumped on every change of the todo
+    pub version: i64,
+    #[serde(with = "rfc3339_option")]
+    pub created_at: Option<OffsetDateTime>,
+    #[serde(with = "rfc3339_option")]
+    pub updated_at: Option<OffsetDateTime>,
+    // Set while the todo is in the trash
+    #[serde(with = "rfc3339_option")]
+    pub deleted_at: Option<OffsetDateTime>,
+    // Issues of the change that did not fail it, e.g. a due date in the past. Only set
+    // in the create and update responses.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warnings: Option<Vec<String>>,
+}
+
+impl From<TodoRow> for TodoResponse {
+    fn from(row: TodoRow) -> Self {
+        Self {
+            id: i64::from(row.id),
+            list_id: row.list_id.map(i64::from),
+            title: row.title,
+            description: row.description,
+            // The column is constrained to the known statuses
+            status: row.status.parse().unwrap_or_default(),
+            status_changed_at: row.status_changed_at,
+            completed_at: row.completed_at,
+            due_at: row.due_at,
+            recurrence: row.recurrence,
+            scheduled_for: row.scheduled_for,
+            archived: row.archived,
+            version: row.version,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+            deleted_at: row.deleted_at,
+            warnings: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct CheckDuplicatesRequest {
+    // Title of the todo about to be created
+    pub title: Option<String>,
+    // Only compare with the todos of this list
+    pub list_id: Option<i64>,
+}
+
+// Open todo with a title similar to the checked one
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct DuplicateTodoRow {
+    #[sqlx(flatten)]
+    pub todo: TodoRow,
+    pub similarity: f32,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct DuplicateTodoResponse {
+    pub todo: TodoResponse,
+    // Trigram similarity of the titles, from 0 to 1
+    pub similarity: f32,
+}
+
+impl From<DuplicateTodoRow> for DuplicateTodoResponse {
+    fn from(row: DuplicateTodoRow) -> Self {
+        Self {
+            todo: TodoResponse::from(row.todo),
+            similarity: row.similarity,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct TodoPageResponse {
+    pub todos: Vec<TodoResponse>,
+    // Cursor of the next page, `null` on the last page
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Deserialize, IntoParams, Clone, Debug, Default)]
+pub struct ExportQuery {
+    /// Format of the export, only `ndjson` (JSON Lines, also `jsonl`) is supported and is
+    /// the default
+    pub format: Option<String>,
+}
+
+// Line of an export, a todo with its tag names and checklist items
+#[derive(Serialize, ToSchema, Clone, Debug)]
+pub struct TodoExportLine {
+    #[serde(flatten)]
+    pub todo: TodoResponse,
+    pub tags: Vec<String>,
+    pub subtasks: Vec<ItemResponse>,
+    // Due date in the locale and timezone of the user, for people reading the export
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_label: Option<String>,
+}
+
+// Due date of an exported todo in the timezone of the user, and how to write it
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct TodoLocalDueRow {
+    pub todo_id: i32,
+    pub local_due_at: PrimitiveDateTime,
+    pub timezone: String,
+    pub locale: String,
+    pub time_format: Option<String>,
+}
+
+// Tag name of an exported todo
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct TodoTagNameRow {
+    pub todo_id: i32,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct TodoMessageResponse {
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct TodoOccurrencesResponse {
+    pub todo_id: i64,
+    pub recurrence: String,
+    // Upcoming due dates, the first one follows the todo due date
+    pub occurrences: Vec<String>,
+}
+
+// Smart views computed from the todo due dates
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SmartView {
+    // Open todos due today or overdue
+    Today,
+    // Open todos due in the next days
+    Upcoming,
+}
+
+impl SmartView {
+    pub const ALL: [Self; 2] = [Self::Today, Self::Upcoming];
+}
+
+// Edit of one todo field as stored in database
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct TodoHistoryRow {
+    pub version: i64,
+    pub changed_by: Option<i32>,
+    pub username: Option<String>,
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub changed_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, PartialEq, Eq)]
+pub struct TodoFieldChange {
+    // One of `title`, `description` or `due_at`
+    pub field: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, PartialEq, Eq)]
+pub struct TodoHistoryEntryResponse {
+    // Version of the todo after the edit, the todo can be reverted to it
+    pub version: i64,
+    // `null` when the editor was deleted or the change came from the system
+    pub changed_by: Option<i64>,
+    pub changed_by_username: Option<String>,
+    #[serde(with = "rfc3339_option")]
+    pub changed_at: Option<OffsetDateTime>,
+    pub changes: Vec<TodoFieldChange>,
+}
+
+// Field values restored by a revert, `None` keeps the current value
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[allow(clippy::option_option)]
+pub struct TodoRestore {
+    pub title: Option<String>,
+    pub description: Option<Option<String>>,
+    pub due_at: Option<Option<String>>,
+}
+
+impl TodoRestore {
+    pub const fn is_empty(&self) -> bool {
+        self.title.is_none() && self.description.is_none() && self.due_at.is_none()
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, sqlx::FromRow, Clone, Debug)]
+pub struct TodoStatsResponse {
+    pub total: i64,
+    pub completed: i64,
+    pub open: i64,
+    // Open todos whose due date has passed
+    pub overdue: i64,
+    // Open todos due later today
+    pub due_today: i64,
+}
+
+// Grouping of the completion analytics
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StatsGroup {
+    List,
+    Tag,
+}
+
+impl StatsGroup {
+    pub const ALL: [Self; 2] = [Self::List, Self::Tag];
+}
+
+// Length of the periods of the completion analytics
+#[derive(Serialize, Deserialize, ToSchema, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum StatsInterval {
+    Day,
+    #[default]
+    Week,
+    Month,
+}
+
+impl StatsInterval {
+    // Field given to `date_trunc`
+    pub const fn as_sql(self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+        }
+    }
+}
+
+// Query parameters of the completion analytics
+#[derive(Deserialize, IntoParams, Clone, Debug, Default)]
+#[into_params(parameter_in = Query)]
+pub struct StatsBreakdownQuery {
+    /// First day, as `YYYY-MM-DD`, defaults to 90 days before `to`
+    pub from: Option<String>,
+    /// Last day included, as `YYYY-MM-DD`, defaults to today
+    pub to: Option<String>,
+    /// Period length, `week` by default
+    pub interval: Option<StatsInterval>,
+}
+
+// Todos created and completed in a period for one list or tag, as computed in database
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct StatsBreakdownRow {
+    pub group_id: Option<i32>,
+    pub name: Option<String>,
+    pub period: OffsetDateTime,
+    pub created: i64,
+    pub completed: i64,
+    // Summed age of the todos completed in the period
+    pub completion_seconds: f64,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, PartialEq, Eq)]
+pub struct StatsPeriodResponse {
+    // First day of the period
+    pub start: String,
+    pub created: i64,
+    pub completed: i64,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, PartialEq)]
+pub struct StatsGroupResponse {
+    // List or tag id, `null` for the todos without list or tag
+    pub id: Option<i64>,
+    pub name: Option<String>,
+    pub created: i64,
+    pub completed: i64,
+    // Average hours from creation to completion of the todos completed in the range
+    pub avg_completion_hours: Option<f64>,
+    // Periods with activity, oldest first
+    pub periods: Vec<StatsPeriodResponse>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, PartialEq)]
+pub struct StatsBreakdownResponse {
+    pub from: String,
+    pub to: String,
+    pub interval: StatsInterval,
+    pub groups: Vec<StatsGroupResponse>,
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_todo_request_deserialization() {
+        let json = r#"{"title":"Buy milk","description":null,"list_id":3}"#;
+        let request: CreateTodoRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(request.title, Some("Buy milk".to_string()));
+        assert_eq!(request.description, None);
+        assert_eq!(request.list_id, Some(3));
+    }
+
+    #[test]
+    fn test_assign_list_request_null_detaches() {
+        let request: AssignListRequest = serde_json::from_str(r#"{"list_id":null}"#).unwrap();
+        assert_eq!(request.list_id, None);
+    }
+
+    #[test]
+    fn test_todo_response_from_row() {
+        let row = TodoRow {
+            id: 7,
+            list_id: Some(2),
+            title: "Write tests".to_string(),
+            description: Some("For the todo module".to_string()),
+            status: "in_progress".to_string(),
+            status_changed_at: None,
+            completed_at: None,
+            due_at: None,
+            recurrence: Some("FREQ=DAILY".to_string()),
+            scheduled_for: Some(time::macros::datetime!(2025-03-17 08:00 UTC)),
+            archived: false,
+            version: 3,
+            created_at: None,
+            updated_at: None,
+            deleted_at: None,
+        };
+
+        let response = TodoResponse::from(row);
+        assert_eq!(response.id, 7);
+        assert_eq!(response.list_id, Some(2));
+        assert_eq!(response.title, "Write tests");
+        assert_eq!(response.status, TodoStatus::InProgress);
+        assert_eq!(response.recurrence, Some("FREQ=DAILY".to_string()));
+        assert_eq!(
+            response.scheduled_for,
+            Some(time::macros::datetime!(2025-03-17 08:00 UTC))
+        );
+        assert_eq!(response.version, 3);
+        assert_eq!(response.deleted_at, None);
+    }
+
+    #[test]
+    fn test_todo_status_round_trip() {
+        for status in TodoStatus::ALL {
+            assert_eq!(status.as_str().parse::<TodoStatus>().unwrap(), status);
+        }
+        assert!("finished".parse::<TodoStatus>().is_err());
+        assert_eq!(
+            serde_json::to_string(&TodoStatus::InProgress).unwrap(),
+            r#""in_progress""#
+        );
+    }
+
+    #[test]
+    fn test_todo_status_transitions() {
+        assert!(TodoStatus::Backlog.can_move_to(TodoStatus::Done));
+        assert!(TodoStatus::Done.can_move_to(TodoStatus::InProgress));
+        assert!(TodoStatus::Cancelled.can_move_to(TodoStatus::Backlog));
+        assert!(!TodoStatus::Cancelled.can_move_to(TodoStatus::Done));
+        assert!(!TodoStatus::Blocked.can_move_to(TodoStatus::Done));
+        assert!(!TodoStatus::Done.can_move_to(TodoStatus::Cancelled));
+    }
+}
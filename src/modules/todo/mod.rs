@@ -0,0 +1,17 @@
+//! # `Todo` Mod
+//! Todo imports for the todo module
+
+pub mod analytics;
+pub mod cache;
+pub mod dedup;
+pub mod export;
+pub mod history;
+pub mod interfaces;
+pub mod recurrence;
+pub mod repository;
+pub mod routes;
+pub mod scheduler;
+pub mod service;
+pub mod trash;
+
+pub use routes::todo_routes;
@@ -0,0 +1,403 @@
+//! # `Todo` Recurrence
+//! This module parses iCalendar RRULE strings and computes the occurrences of recurring todos.
+//!
+//! Supported parts are `FREQ` (`DAILY`, `WEEKLY`, `MONTHLY`, `YEARLY`), `INTERVAL`, `COUNT`,
+//! `UNTIL` and plain weekday `BYDAY` values for weekly rules.
+
+use std::{fmt, str::FromStr};
+
+use time::{Date, Duration, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset, Weekday};
+
+// Upper bound for `INTERVAL`, keeps occurrence computation cheap
+const MAX_INTERVAL: u32 = 1000;
+
+// Upper bound of candidate periods checked when looking for the next valid date,
+// e.g. a rule on February 29th only matches every four years
+const MAX_PERIODS: u32 = 100;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Frequency {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Daily => "DAILY",
+            Self::Weekly => "WEEKLY",
+            Self::Monthly => "MONTHLY",
+            Self::Yearly => "YEARLY",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    // Number of occurrences left, including the current one
+    pub count: Option<u32>,
+    pub until: Option<OffsetDateTime>,
+    pub by_day: Vec<Weekday>,
+}
+
+impl RecurrenceRule {
+    // Next occurrence strictly after `anchor`, ignoring `COUNT` and `UNTIL`
+    fn next_candidate(&self, anchor: OffsetDateTime) -> Option<OffsetDateTime> {
+        match self.frequency {
+            Frequency::Daily => anchor.checked_add(Duration::days(i64::from(self.interval))),
+            Frequency::Weekly if self.by_day.is_empty() => {
+                anchor.checked_add(Duration::weeks(i64::from(self.interval)))
+            }
+            Frequency::Weekly => self.next_weekday(anchor),
+            Frequency::Monthly => (1..=MAX_PERIODS).find_map(|period| {
+                let date = add_months(anchor.date(), period * self.interval)?;
+                Some(anchor.replace_date(date))
+            }),
+            Frequency::Yearly => (1..=MAX_PERIODS).find_map(|period| {
+                let date = add_months(anchor.date(), period * self.interval * 12)?;
+                Some(anchor.replace_date(date))
+            }),
+        }
+    }
+
+    // Next day listed in `BYDAY`, only considering weeks that match the interval
+    fn next_weekday(&self, anchor: OffsetDateTime) -> Option<OffsetDateTime> {
+        let anchor_week = week_start(anchor.date());
+        let max_days = 7 * i64::from(self.interval) + 7;
+
+        (1..=max_days).find_map(|days| {
+            let candidate = anchor.checked_add(Duration::days(days))?;
+            let weeks = (week_start(candidate.date()) - anchor_week).whole_weeks();
+            let in_interval = weeks % i64::from(self.interval) == 0;
+
+            (in_interval && self.by_day.contains(&candidate.weekday())).then_some(candidate)
+        })
+    }
+
+    // Next occurrence after `anchor` together with the rule describing the remaining ones.
+    // Returns `None` once the recurrence is exhausted.
+    pub fn next_occurrence(&self, anchor: OffsetDateTime) -> Option<(OffsetDateTime, Self)> {
+        if self.count.is_some_and(|count| count <= 1) {
+            return None;
+        }
+
+        let next = self.next_candidate(anchor)?;
+        if self.until.is_some_and(|until| next > until) {
+            return None;
+        }
+
+        let remaining = Self {
+            count: self.count.map(|count| count - 1),
+            ..self.clone()
+        };
+        Some((next, remaining))
+    }
+
+    // Up to `limit` occurrences following `anchor`
+    pub fn preview(&self, anchor: OffsetDateTime, limit: usize) -> Vec<OffsetDateTime> {
+        let mut occurrences = Vec::with_capacity(limit);
+        let mut current = (anchor, self.clone());
+
+        while occurrences.len() < limit {
+            let Some((next, remaining)) = current.1.next_occurrence(current.0) else {
+                break;
+            };
+            occurrences.push(next);
+            current = (next, remaining);
+        }
+
+        occurrences
+    }
+}
+
+impl FromStr for RecurrenceRule {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        let value = match value.get(..6) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("RRULE:") => &value[6..],
+            _ => value,
+        };
+
+        let mut frequency = None;
+        let mut interval = None;
+        let mut count = None;
+        let mut until = None;
+        let mut by_day = None;
+
+        for part in value.split(';').filter(|part| !part.is_empty()) {
+            let Some((key, part_value)) = part.split_once('=') else {
+                return Err(format!("Invalid RRULE part: {part}"));
+            };
+
+            let duplicated = match key.to_uppercase().as_str() {
+                "FREQ" => frequency.replace(parse_frequency(part_value)?).is_some(),
+                "INTERVAL" => interval.replace(parse_positive(key, part_value)?).is_some(),
+                "COUNT" => count.replace(parse_positive(key, part_value)?).is_some(),
+                "UNTIL" => until.replace(parse_until(part_value)?).is_some(),
+                "BYDAY" => by_day.replace(parse_by_day(part_value)?).is_some(),
+                _ => return Err(format!("Unsupported RRULE part: {key}")),
+            };
+            if duplicated {
+                return Err(format!("Duplicated RRULE part: {key}"));
+            }
+        }
+
+        let Some(frequency) = frequency else {
+            return Err("RRULE must define FREQ".to_string());
+        };
+        let interval = interval.unwrap_or(1);
+        if interval > MAX_INTERVAL {
+            return Err(format!("INTERVAL must be at most {MAX_INTERVAL}"));
+        }
+        if count.is_some() && until.is_some() {
+            return Err("COUNT and UNTIL cannot be used together".to_string());
+        }
+        let by_day = by_day.unwrap_or_default();
+        if !by_day.is_empty() && frequency != Frequency::Weekly {
+            return Err("BYDAY is only supported with FREQ=WEEKLY".to_string());
+        }
+
+        Ok(Self {
+            frequency,
+            interval,
+            count,
+            until,
+            by_day,
+        })
+    }
+}
+
+impl fmt::Display for RecurrenceRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FREQ={}", self.frequency.as_str())?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={count}")?;
+        }
+        if let Some(until) = self.until {
+            let until = until.to_offset(UtcOffset::UTC);
+            write!(
+                f,
+                ";UNTIL={:04}{:02}{:02}T{:02}{:02}{:02}Z",
+                until.year(),
+                u8::from(until.month()),
+                until.day(),
+                until.hour(),
+                until.minute(),
+                until.second()
+            )?;
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<&str> = self.by_day.iter().map(|day| weekday_code(*day)).collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_frequency(value: &str) -> Result<Frequency, String> {
+    match value.to_uppercase().as_str() {
+        "DAILY" => Ok(Frequency::Daily),
+        "WEEKLY" => Ok(Frequency::Weekly),
+        "MONTHLY" => Ok(Frequency::Monthly),
+        "YEARLY" => Ok(Frequency::Yearly),
+        _ => Err(format!("Unsupported FREQ: {value}")),
+    }
+}
+
+fn parse_positive(key: &str, value: &str) -> Result<u32, String> {
+    match value.parse::<u32>() {
+        Ok(number) if number > 0 => Ok(number),
+        _ => Err(format!("{key} must be a positive integer")),
+    }
+}
+
+// Accepts `YYYYMMDD` (end of that day, UTC) and `YYYYMMDDTHHMMSSZ`
+fn parse_until(value: &str) -> Result<OffsetDateTime, String> {
+    let invalid = || format!("Invalid UNTIL: {value}");
+    let number = |range: std::ops::Range<usize>| -> Result<u8, String> {
+        value
+            .get(range)
+            .and_then(|digits| digits.parse::<u8>().ok())
+            .ok_or_else(invalid)
+    };
+
+    let year = value
+        .get(0..4)
+        .and_then(|digits| digits.parse::<i32>().ok())
+        .ok_or_else(invalid)?;
+    let month = Month::try_from(number(4..6)?).map_err(|_| invalid())?;
+    let date = Date::from_calendar_date(year, month, number(6..8)?).map_err(|_| invalid())?;
+
+    let time = match value.len() {
+        8 => Time::from_hms(23, 59, 59),
+        16 if value.get(8..9) == Some("T") && value.ends_with('Z') => {
+            Time::from_hms(number(9..11)?, number(11..13)?, number(13..15)?)
+        }
+        _ => return Err(invalid()),
+    }
+    .map_err(|_| invalid())?;
+
+    Ok(PrimitiveDateTime::new(date, time).assume_utc())
+}
+
+fn parse_by_day(value: &str) -> Result<Vec<Weekday>, String> {
+    let mut days = Vec::new();
+    for code in value.split(',') {
+        let day = match code.trim().to_uppercase().as_str() {
+            "MO" => Weekday::Monday,
+            "TU" => Weekday::Tuesday,
+            "WE" => Weekday::Wednesday,
+            "TH" => Weekday::Thursday,
+            "FR" => Weekday::Friday,
+            "SA" => Weekday::Saturday,
+            "SU" => Weekday::Sunday,
+            _ => return Err(format!("Unsupported BYDAY value: {code}")),
+        };
+        if !days.contains(&day) {
+            days.push(day);
+        }
+    }
+    Ok(days)
+}
+
+const fn weekday_code(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Monday => "MO",
+        Weekday::Tuesday => "TU",
+        Weekday::Wednesday => "WE",
+        Weekday::Thursday => "TH",
+        Weekday::Friday => "FR",
+        Weekday::Saturday => "SA",
+        Weekday::Sunday => "SU",
+    }
+}
+
+// Monday of the week containing the date, weeks start on Monday as in RFC 5545
+fn week_start(date: Date) -> Date {
+    date - Duration::days(i64::from(date.weekday().number_days_from_monday()))
+}
+
+// Same day `months` later, `None` when that day doesn't exist in the target month
+fn add_months(date: Date, months: u32) -> Option<Date> {
+    let total =
+        i64::from(date.year()) * 12 + i64::from(u8::from(date.month())) - 1 + i64::from(months);
+    let year = i32::try_from(total.div_euclid(12)).ok()?;
+    let month = Month::try_from(u8::try_from(total.rem_euclid(12) + 1).ok()?).ok()?;
+
+    Date::from_calendar_date(year, month, date.day()).ok()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    #[test]
+    fn test_parse_and_display_roundtrip() {
+        let rule: RecurrenceRule = "RRULE:FREQ=weekly;INTERVAL=2;BYDAY=MO,FR;COUNT=5"
+            .parse()
+            .unwrap();
+        assert_eq!(rule.frequency, Frequency::Weekly);
+        assert_eq!(rule.interval, 2);
+        assert_eq!(rule.count, Some(5));
+        assert_eq!(
+            rule.to_string(),
+            "FREQ=WEEKLY;INTERVAL=2;COUNT=5;BYDAY=MO,FR"
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_rules() {
+        for rule in [
+            "",
+            "INTERVAL=2",
+            "FREQ=HOURLY",
+            "FREQ=DAILY;INTERVAL=0",
+            "FREQ=DAILY;FREQ=WEEKLY",
+            "FREQ=DAILY;COUNT=2;UNTIL=20300101",
+            "FREQ=DAILY;BYDAY=MO",
+            "FREQ=WEEKLY;BYDAY=1MO",
+            "FREQ=DAILY;UNTIL=20301301",
+            "FREQ=DAILY;BYMONTH=2",
+        ] {
+            assert!(rule.parse::<RecurrenceRule>().is_err(), "{rule}");
+        }
+    }
+
+    #[test]
+    fn test_daily_preview() {
+        let rule: RecurrenceRule = "FREQ=DAILY;INTERVAL=3".parse().unwrap();
+        let occurrences = rule.preview(datetime!(2025-01-30 09:00 UTC), 2);
+        assert_eq!(
+            occurrences,
+            vec![
+                datetime!(2025-02-02 09:00 UTC),
+                datetime!(2025-02-05 09:00 UTC)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_weekly_by_day_with_interval() {
+        // 2025-01-01 is a Wednesday
+        let rule: RecurrenceRule = "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE".parse().unwrap();
+        let occurrences = rule.preview(datetime!(2025-01-01 08:00 UTC), 3);
+        assert_eq!(
+            occurrences,
+            vec![
+                datetime!(2025-01-13 08:00 UTC),
+                datetime!(2025-01-15 08:00 UTC),
+                datetime!(2025-01-27 08:00 UTC)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_monthly_skips_missing_days() {
+        let rule: RecurrenceRule = "FREQ=MONTHLY".parse().unwrap();
+        let occurrences = rule.preview(datetime!(2025-01-31 12:00 UTC), 2);
+        assert_eq!(
+            occurrences,
+            vec![
+                datetime!(2025-03-31 12:00 UTC),
+                datetime!(2025-05-31 12:00 UTC)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_yearly_leap_day() {
+        let rule: RecurrenceRule = "FREQ=YEARLY".parse().unwrap();
+        let occurrences = rule.preview(datetime!(2024-02-29 00:00 UTC), 1);
+        assert_eq!(occurrences, vec![datetime!(2028-02-29 00:00 UTC)]);
+    }
+
+    #[test]
+    fn test_count_decreases_and_ends() {
+        let rule: RecurrenceRule = "FREQ=DAILY;COUNT=2".parse().unwrap();
+        let (next, remaining) = rule
+            .next_occurrence(datetime!(2025-01-01 00:00 UTC))
+            .unwrap();
+        assert_eq!(next, datetime!(2025-01-02 00:00 UTC));
+        assert_eq!(remaining.count, Some(1));
+        assert!(remaining.next_occurrence(next).is_none());
+    }
+
+    #[test]
+    fn test_until_is_inclusive() {
+        let rule: RecurrenceRule = "FREQ=DAILY;UNTIL=20250103".parse().unwrap();
+        let occurrences = rule.preview(datetime!(2025-01-01 10:00 UTC), 10);
+        assert_eq!(occurrences.len(), 2);
+        assert_eq!(rule.to_string(), "FREQ=DAILY;UNTIL=20250103T235959Z");
+    }
+}
@@ -0,0 +1,854 @@
+//! # `Todo` Repository
+//! This module defines the todo repository for todo operations.
+
+use sqlx::{Error, Pool, Postgres};
+use time::OffsetDateTime;
+
+use crate::database::retry_transient;
+use crate::modules::{
+    common::to_db_id,
+    item::interfaces::ItemRow,
+    list::{
+        interfaces::ListAccessRow,
+        repository::{LIST_ACCESS_QUERY, WIP_COLUMN_TODOS},
+    },
+    preference::interfaces::DEFAULT_TIMEZONE,
+    todo::interfaces::{
+        DuplicateTodoRow, StatsBreakdownRow, StatsGroup, StatsInterval, TodoChanges, TodoFilter,
+        TodoHistoryRow, TodoLocalDueRow, TodoRestore, TodoRow, TodoStatsResponse, TodoStatus,
+        TodoTagNameRow, ValidatedCreateTodoRequest,
+    },
+};
+use crate::telemetry::observe_query;
+use crate::utils::format::DEFAULT_LOCALE;
+
+pub const TODO_COLUMNS: &str = "id, list_id, title, description, status, status_changed_at, \
+     completed_at, due_at, recurrence, scheduled_for, archived, version, created_at, updated_at, \
+     deleted_at";
+
+// SQL condition matching todos that still have work left
+pub const OPEN_STATUSES: &str = "status IN ('backlog', 'in_progress', 'blocked')";
+
+// SQL condition matching todos shown in the views, scheduled todos wait for activation
+pub const ACTIVE_TODOS: &str = "scheduled_for IS NULL";
+
+// Minimum trigram similarity of a title to be suggested as a duplicate
+const DUPLICATE_SIMILARITY: f32 = 0.4;
+
+// Tells the history trigger who edits the todos in the transaction
+const SET_ACTOR_QUERY: &str = "SELECT set_config('app.actor_id', $1, true)";
+
+pub struct TodoRepository {
+    pool: Pool<Postgres>,
+}
+
+impl TodoRepository {
+    // Constructor
+    pub const fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Owner of a todo of the workspace and the role of the user on it, the user owning
+    // the todo, being a member of its list or having accepted its assignment. Accepted
+    // assignees edit the todo. Returns `None` without access.
+    pub async fn todo_access(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+    ) -> Result<Option<ListAccessRow>, Error> {
+        observe_query("todo.todo_access", async move {
+            sqlx::query_as::<_, ListAccessRow>(
+                "SELECT t.user_id AS owner_id,
+                    CASE
+                        WHEN t.user_id = $2 THEN 'owner'
+                        WHEN a.assignee_id IS NOT NULL THEN 'editor'
+                        ELSE m.role
+                    END AS role
+                 FROM todos t
+                 LEFT JOIN list_members m ON m.list_id = t.list_id AND m.user_id = $2
+                 LEFT JOIN todo_assignments a
+                    ON a.todo_id = t.id AND a.assignee_id = $2 AND a.status = 'accepted'
+                 WHERE t.id = $1 AND t.workspace_id = $3
+                   AND (t.user_id = $2 OR m.user_id IS NOT NULL OR a.assignee_id IS NOT NULL)",
+            )
+            .bind(to_db_id(id)?)
+            .bind(to_db_id(user_id)?)
+            .bind(to_db_id(workspace_id)?)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // WIP limit of the status column of a list when the column is full, not counting the
+    // todo `todo_id` already in it. `None` while the column has room or no limit.
+    pub async fn reached_wip_limit(
+        &self,
+        list_id: i64,
+        status: TodoStatus,
+        todo_id: Option<i64>,
+    ) -> Result<Option<i32>, Error> {
+        observe_query("todo.reached_wip_limit", async move {
+            let query = format!(
+                "SELECT w.max_todos FROM list_wip_limits w
+                 WHERE w.list_id = $1 AND w.status = $2
+                   AND ({WIP_COLUMN_TODOS} AND t.id IS DISTINCT FROM $3) >= w.max_todos"
+            );
+            sqlx::query_scalar::<_, i32>(&query)
+                .bind(to_db_id(list_id)?)
+                .bind(status.as_str())
+                .bind(todo_id.map(to_db_id).transpose()?)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Owner of a list of the workspace and the role of the user on it, `None` without access
+    pub async fn list_access(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        list_id: i64,
+    ) -> Result<Option<ListAccessRow>, Error> {
+        observe_query("todo.list_access", async move {
+            sqlx::query_as::<_, ListAccessRow>(LIST_ACCESS_QUERY)
+                .bind(to_db_id(list_id)?)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Create a todo in a workspace, returns `None` when the list is not owned by the user
+    // in that workspace
+    pub async fn create_todo(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo: ValidatedCreateTodoRequest,
+        due_at: Option<OffsetDateTime>,
+        scheduled_for: Option<OffsetDateTime>,
+    ) -> Result<Option<TodoRow>, Error> {
+        observe_query("todo.create_todo", async move {
+            let list_id = todo.list_id.map(to_db_id).transpose()?;
+            let query = format!(
+                "INSERT INTO todos (user_id, title, description, list_id, due_at, recurrence, workspace_id,
+                    scheduled_for)
+                 SELECT $1, $2, $3, $4, $5, $6, $7, $8
+                 WHERE $4::INTEGER IS NULL
+                    OR EXISTS(SELECT 1 FROM lists WHERE id = $4 AND user_id = $1 AND workspace_id = $7)
+                 RETURNING {TODO_COLUMNS}"
+            );
+
+            sqlx::query_as::<_, TodoRow>(&query)
+                .bind(to_db_id(user_id)?)
+                .bind(todo.title)
+                .bind(todo.description)
+                .bind(list_id)
+                .bind(due_at)
+                .bind(todo.recurrence)
+                .bind(to_db_id(workspace_id)?)
+                .bind(scheduled_for)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // List todos from an user in a workspace, optionally filtered by list and tag, newest
+    // first. Archived and scheduled todos are only included when the filter asks for them.
+    // Pages are keyed on the id, `before_id` is the last id of the previous page.
+    pub async fn list_todos(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        filter: &TodoFilter,
+        before_id: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<TodoRow>, Error> {
+        observe_query("todo.list_todos", async move {
+            let query = format!(
+                "SELECT {TODO_COLUMNS} FROM todos
+                 WHERE user_id = $1 AND workspace_id = $7 AND deleted_at IS NULL
+                   AND ($2::INTEGER IS NULL OR list_id = $2)
+                   AND ($3::TEXT IS NULL OR EXISTS(
+                        SELECT 1 FROM todo_tags tt
+                        JOIN tags g ON g.id = tt.tag_id
+                        WHERE tt.todo_id = todos.id AND g.user_id = $1 AND g.name = $3))
+                   AND ($4::INTEGER IS NULL OR id < $4)
+                   AND ($6 OR NOT archived)
+                   AND ($8 OR {ACTIVE_TODOS})
+                 ORDER BY id DESC
+                 LIMIT $5"
+            );
+
+            sqlx::query_as::<_, TodoRow>(&query)
+                .bind(to_db_id(user_id)?)
+                .bind(filter.list_id.map(to_db_id).transpose()?)
+                .bind(filter.tag.as_deref())
+                .bind(before_id.map(to_db_id).transpose()?)
+                .bind(limit)
+                .bind(filter.include_archived.unwrap_or(false))
+                .bind(to_db_id(workspace_id)?)
+                .bind(filter.include_scheduled.unwrap_or(false))
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // List the open todos from an user in a workspace whose title is similar to `title`,
+    // most similar first. The `%` operator lets the trigram index narrow the candidates.
+    pub async fn find_similar(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        title: &str,
+        list_id: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<DuplicateTodoRow>, Error> {
+        observe_query("todo.find_similar", async move {
+            let query = format!(
+                "SELECT {TODO_COLUMNS}, similarity(title, $1) AS similarity FROM todos
+                 WHERE user_id = $2 AND workspace_id = $3 AND deleted_at IS NULL
+                   AND NOT archived AND {OPEN_STATUSES}
+                   AND ($4::INTEGER IS NULL OR list_id = $4)
+                   AND title % $1 AND similarity(title, $1) >= $5
+                 ORDER BY similarity DESC, id DESC
+                 LIMIT $6"
+            );
+
+            sqlx::query_as::<_, DuplicateTodoRow>(&query)
+                .bind(title)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .bind(list_id.map(to_db_id).transpose()?)
+                .bind(DUPLICATE_SIMILARITY)
+                .bind(limit)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // List open todos from an user in a workspace due before `until`, and not before
+    // `from` when given
+    pub async fn list_due_todos(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        from: Option<OffsetDateTime>,
+        until: OffsetDateTime,
+    ) -> Result<Vec<TodoRow>, Error> {
+        observe_query("todo.list_due_todos", async move {
+            let query = format!(
+                "SELECT {TODO_COLUMNS} FROM todos
+                 WHERE user_id = $1 AND workspace_id = $4 AND {OPEN_STATUSES}
+                   AND NOT archived AND deleted_at IS NULL AND {ACTIVE_TODOS}
+                   AND ($2::TIMESTAMPTZ IS NULL OR due_at >= $2)
+                   AND due_at < $3
+                 ORDER BY due_at ASC, id ASC"
+            );
+
+            sqlx::query_as::<_, TodoRow>(&query)
+                .bind(to_db_id(user_id)?)
+                .bind(from)
+                .bind(until)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Count the user todos of a workspace by state in a single pass
+    pub async fn todo_stats(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        now: OffsetDateTime,
+        end_of_day: OffsetDateTime,
+    ) -> Result<TodoStatsResponse, Error> {
+        observe_query("todo.todo_stats", async move {
+            let query = format!(
+                "SELECT
+                    COUNT(*) AS total,
+                    COUNT(*) FILTER (WHERE status = 'done') AS completed,
+                    COUNT(*) FILTER (WHERE {OPEN_STATUSES}) AS open,
+                    COUNT(*) FILTER (WHERE {OPEN_STATUSES} AND due_at < $2) AS overdue,
+                    COUNT(*) FILTER (WHERE {OPEN_STATUSES} AND due_at >= $2 AND due_at < $3) AS due_today
+                 FROM todos WHERE user_id = $1 AND workspace_id = $4 AND deleted_at IS NULL"
+            );
+
+            sqlx::query_as::<_, TodoStatsResponse>(&query)
+                .bind(to_db_id(user_id)?)
+                .bind(now)
+                .bind(end_of_day)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_one(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Todos created and completed between `from` and `until` per list or tag and per
+    // period, with the summed age of the completed ones. A todo with several tags counts
+    // for each of them. Ordered by group, then period.
+    pub async fn stats_breakdown(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        group: StatsGroup,
+        interval: StatsInterval,
+        from: OffsetDateTime,
+        until: OffsetDateTime,
+    ) -> Result<Vec<StatsBreakdownRow>, Error> {
+        observe_query("todo.stats_breakdown", async move {
+            let (group_key, group_join, group_table) = match group {
+                StatsGroup::List => ("t.list_id", "", "lists"),
+                StatsGroup::Tag => (
+                    "tt.tag_id",
+                    "LEFT JOIN todo_tags tt ON tt.todo_id = t.id",
+                    "tags",
+                ),
+            };
+            let query = format!(
+                "WITH scoped AS (
+                     SELECT {group_key} AS group_id, t.created_at, t.completed_at
+                     FROM todos t {group_join}
+                     WHERE t.user_id = $1 AND t.workspace_id = $2 AND t.deleted_at IS NULL
+                 ),
+                 events AS (
+                     SELECT group_id, date_trunc($3, created_at) AS period,
+                         1 AS created, 0 AS completed, 0::FLOAT8 AS completion_seconds
+                     FROM scoped WHERE created_at >= $4 AND created_at < $5
+                     UNION ALL
+                     SELECT group_id, date_trunc($3, completed_at),
+                         0, 1, EXTRACT(EPOCH FROM completed_at - created_at)::FLOAT8
+                     FROM scoped WHERE completed_at >= $4 AND completed_at < $5
+                 )
+                 SELECT e.group_id, g.name, e.period,
+                     SUM(e.created)::BIGINT AS created,
+                     SUM(e.completed)::BIGINT AS completed,
+                     SUM(e.completion_seconds)::FLOAT8 AS completion_seconds
+                 FROM events e LEFT JOIN {group_table} g ON g.id = e.group_id
+                 GROUP BY e.group_id, g.name, e.period
+                 ORDER BY e.group_id NULLS LAST, e.period"
+            );
+
+            sqlx::query_as::<_, StatsBreakdownRow>(&query)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .bind(interval.as_sql())
+                .bind(from)
+                .bind(until)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Fetch a single todo from an user in a workspace
+    pub async fn fetch_todo(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+    ) -> Result<Option<TodoRow>, Error> {
+        observe_query("todo.fetch_todo", async move {
+            let query = format!(
+                "SELECT {TODO_COLUMNS} FROM todos
+                 WHERE id = $1 AND user_id = $2 AND workspace_id = $3 AND deleted_at IS NULL"
+            );
+
+            sqlx::query_as::<_, TodoRow>(&query)
+                .bind(to_db_id(id)?)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Update todo data, fields not provided are kept. A status change stamps
+    // `status_changed_at`, and `completed_at` while the todo is done.
+    // Returns `None` when the todo is not found or no longer at the expected version.
+    // The edit is recorded in the todo history as made by `actor_id`.
+    pub async fn update_todo(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+        actor_id: i64,
+        changes: TodoChanges,
+    ) -> Result<Option<TodoRow>, Error> {
+        observe_query("todo.update_todo", async move {
+            let query = format!(
+                "UPDATE todos SET
+                    title = COALESCE($1, title),
+                    description = COALESCE($2, description),
+                    status = COALESCE($3::TEXT, status),
+                    status_changed_at = CASE WHEN $3 <> status THEN NOW() ELSE status_changed_at END,
+                    completed_at = CASE
+                        WHEN $3 = 'done' AND status <> 'done' THEN NOW()
+                        WHEN $3 <> 'done' THEN NULL
+                        ELSE completed_at
+                    END,
+                    due_at = COALESCE($4, due_at),
+                    recurrence = CASE WHEN $5::TEXT IS NULL THEN recurrence ELSE NULLIF($5, '') END,
+                    scheduled_for = CASE WHEN $10 THEN $11 ELSE scheduled_for END
+                 WHERE id = $6 AND user_id = $7 AND workspace_id = $9 AND deleted_at IS NULL
+                   AND ($8::BIGINT IS NULL OR version = $8)
+                 RETURNING {TODO_COLUMNS}"
+            );
+
+            let mut tx = self.pool.begin().await?;
+            sqlx::query(SET_ACTOR_QUERY)
+                .bind(actor_id.to_string())
+                .execute(&mut *tx)
+                .await?;
+
+            let todo = sqlx::query_as::<_, TodoRow>(&query)
+                .bind(changes.title)
+                .bind(changes.description)
+                .bind(changes.status.map(TodoStatus::as_str))
+                .bind(changes.due_at)
+                .bind(changes.recurrence)
+                .bind(to_db_id(id)?)
+                .bind(to_db_id(user_id)?)
+                .bind(changes.expected_version)
+                .bind(to_db_id(workspace_id)?)
+                .bind(changes.scheduled_for.is_some())
+                .bind(changes.scheduled_for.flatten())
+                .fetch_optional(&mut *tx)
+                .await?;
+
+            tx.commit().await?;
+            Ok(todo)
+        })
+        .await
+    }
+
+    // Edits of a todo title, description and due date, oldest first
+    pub async fn list_history(&self, id: i64) -> Result<Vec<TodoHistoryRow>, Error> {
+        observe_query("todo.list_history", async move {
+            sqlx::query_as::<_, TodoHistoryRow>(
+                "SELECT h.version, h.changed_by, u.username, h.field, h.old_value, h.new_value,
+                        h.changed_at
+                 FROM todo_history h
+                 LEFT JOIN users u ON u.id = h.changed_by
+                 WHERE h.todo_id = $1
+                 ORDER BY h.version, h.id",
+            )
+            .bind(to_db_id(id)?)
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Set back the fields of a todo given by `restore`, recorded in the todo history as
+    // made by `actor_id`. Returns `None` when the todo is not found.
+    pub async fn restore_fields(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+        actor_id: i64,
+        restore: TodoRestore,
+    ) -> Result<Option<TodoRow>, Error> {
+        observe_query("todo.restore_fields", async move {
+            let mut tx = self.pool.begin().await?;
+            sqlx::query(SET_ACTOR_QUERY)
+                .bind(actor_id.to_string())
+                .execute(&mut *tx)
+                .await?;
+
+            let query = format!(
+                "UPDATE todos SET
+                    title = COALESCE($1, title),
+                    description = CASE WHEN $2 THEN $3 ELSE description END,
+                    due_at = CASE WHEN $4 THEN $5::TEXT::TIMESTAMPTZ ELSE due_at END
+                 WHERE id = $6 AND user_id = $7 AND workspace_id = $8 AND deleted_at IS NULL
+                 RETURNING {TODO_COLUMNS}"
+            );
+
+            let todo = sqlx::query_as::<_, TodoRow>(&query)
+                .bind(restore.title)
+                .bind(restore.description.is_some())
+                .bind(restore.description.flatten())
+                .bind(restore.due_at.is_some())
+                .bind(restore.due_at.flatten())
+                .bind(to_db_id(id)?)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_optional(&mut *tx)
+                .await?;
+
+            tx.commit().await?;
+            Ok(todo)
+        })
+        .await
+    }
+
+    // Attach a todo to a list owned by the same user in the same workspace, or detach
+    // it with `None`
+    pub async fn assign_list(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+        list_id: Option<i64>,
+    ) -> Result<Option<TodoRow>, Error> {
+        observe_query("todo.assign_list", async move {
+            let query = format!(
+                "UPDATE todos SET list_id = $1
+                 WHERE id = $2 AND user_id = $3 AND workspace_id = $4 AND deleted_at IS NULL
+                   AND ($1::INTEGER IS NULL OR EXISTS(
+                        SELECT 1 FROM lists WHERE id = $1 AND user_id = $3 AND workspace_id = $4))
+                 RETURNING {TODO_COLUMNS}"
+            );
+
+            sqlx::query_as::<_, TodoRow>(&query)
+                .bind(list_id.map(to_db_id).transpose()?)
+                .bind(to_db_id(id)?)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Archive or unarchive a todo, returns `None` when the todo is not found
+    pub async fn set_archived(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+        archived: bool,
+    ) -> Result<Option<TodoRow>, Error> {
+        observe_query("todo.set_archived", async move {
+            let query = format!(
+                "UPDATE todos SET archived = $1
+                 WHERE id = $2 AND user_id = $3 AND workspace_id = $4 AND deleted_at IS NULL
+                 RETURNING {TODO_COLUMNS}"
+            );
+
+            sqlx::query_as::<_, TodoRow>(&query)
+                .bind(archived)
+                .bind(to_db_id(id)?)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Move the recurrence of a done todo to its next occurrence, copying its
+    // content, tags and checklist. Returns `None` when the recurrence was already moved.
+    pub async fn create_next_occurrence(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+        due_at: OffsetDateTime,
+        recurrence: &str,
+    ) -> Result<Option<TodoRow>, Error> {
+        observe_query("todo.create_next_occurrence", async move {
+            let mut tx = self.pool.begin().await?;
+
+            let moved = sqlx::query(
+                "UPDATE todos SET recurrence = NULL
+                 WHERE id = $1 AND user_id = $2 AND workspace_id = $3
+                   AND recurrence IS NOT NULL AND deleted_at IS NULL",
+            )
+            .bind(to_db_id(id)?)
+            .bind(to_db_id(user_id)?)
+            .bind(to_db_id(workspace_id)?)
+            .execute(&mut *tx)
+            .await?;
+            if moved.rows_affected() == 0 {
+                return Ok(None);
+            }
+
+            let query = format!(
+                "INSERT INTO todos (user_id, title, description, list_id, due_at, recurrence, workspace_id)
+                 SELECT user_id, title, description, list_id, $2, $3, workspace_id
+                 FROM todos WHERE id = $1
+                 RETURNING {TODO_COLUMNS}"
+            );
+            let next = sqlx::query_as::<_, TodoRow>(&query)
+                .bind(to_db_id(id)?)
+                .bind(due_at)
+                .bind(recurrence)
+                .fetch_one(&mut *tx)
+                .await?;
+
+            sqlx::query(
+                "INSERT INTO todo_tags (todo_id, tag_id)
+                 SELECT $1, tag_id FROM todo_tags WHERE todo_id = $2",
+            )
+            .bind(next.id)
+            .bind(to_db_id(id)?)
+            .execute(&mut *tx)
+            .await?;
+
+            // The checklist starts over on every occurrence
+            sqlx::query(
+                "INSERT INTO todo_items (todo_id, title, position)
+                 SELECT $1, title, position FROM todo_items WHERE todo_id = $2",
+            )
+            .bind(next.id)
+            .bind(to_db_id(id)?)
+            .execute(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            Ok(Some(next))
+        })
+        .await
+    }
+
+    // Move a todo to the trash, returns whether a todo was moved
+    pub async fn delete_todo(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+    ) -> Result<bool, Error> {
+        observe_query("todo.delete_todo", async move {
+            let result = sqlx::query(
+                "UPDATE todos SET deleted_at = NOW()
+                 WHERE id = $1 AND user_id = $2 AND workspace_id = $3 AND deleted_at IS NULL",
+            )
+            .bind(to_db_id(id)?)
+            .bind(to_db_id(user_id)?)
+            .bind(to_db_id(workspace_id)?)
+            .execute(&self.pool)
+            .await?;
+
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    // List the todos in the trash of an user in a workspace, most recently deleted first
+    pub async fn list_trash(&self, user_id: i64, workspace_id: i64) -> Result<Vec<TodoRow>, Error> {
+        observe_query("todo.list_trash", async move {
+            let query = format!(
+                "SELECT {TODO_COLUMNS} FROM todos
+                 WHERE user_id = $1 AND workspace_id = $2 AND deleted_at IS NOT NULL
+                 ORDER BY deleted_at DESC, id DESC"
+            );
+
+            sqlx::query_as::<_, TodoRow>(&query)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // List the scheduled todos of an user in a workspace, the first to activate first
+    pub async fn list_scheduled(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+    ) -> Result<Vec<TodoRow>, Error> {
+        observe_query("todo.list_scheduled", async move {
+            let query = format!(
+                "SELECT {TODO_COLUMNS} FROM todos
+                 WHERE user_id = $1 AND workspace_id = $2 AND deleted_at IS NULL
+                   AND NOT archived AND scheduled_for IS NOT NULL
+                 ORDER BY scheduled_for ASC, id ASC"
+            );
+
+            sqlx::query_as::<_, TodoRow>(&query)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Page of the todos of an user in a workspace, or in all of them, to export. Archived
+    // and scheduled todos included, in id order after `after_id`.
+    pub async fn export_todos(
+        &self,
+        user_id: i64,
+        workspace_id: Option<i64>,
+        after_id: Option<i32>,
+        limit: i64,
+    ) -> Result<Vec<TodoRow>, Error> {
+        retry_transient("todo.export_todos", move || async move {
+            let query = format!(
+                "SELECT {TODO_COLUMNS} FROM todos
+                 WHERE user_id = $1 AND ($2::INTEGER IS NULL OR workspace_id = $2)
+                   AND deleted_at IS NULL AND ($3::INTEGER IS NULL OR id > $3)
+                 ORDER BY id ASC
+                 LIMIT $4"
+            );
+
+            sqlx::query_as::<_, TodoRow>(&query)
+                .bind(to_db_id(user_id)?)
+                .bind(workspace_id.map(to_db_id).transpose()?)
+                .bind(after_id)
+                .bind(limit)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Tag names of the exported todos, in name order
+    pub async fn export_tags(&self, todo_ids: &[i32]) -> Result<Vec<TodoTagNameRow>, Error> {
+        retry_transient("todo.export_tags", move || async move {
+            sqlx::query_as::<_, TodoTagNameRow>(
+                "SELECT tt.todo_id, g.name FROM todo_tags tt
+                 JOIN tags g ON g.id = tt.tag_id
+                 WHERE tt.todo_id = ANY($1)
+                 ORDER BY g.name ASC",
+            )
+            .bind(todo_ids)
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Due dates of the exported todos in the timezone of the user, with its locale
+    pub async fn export_due_dates(
+        &self,
+        user_id: i64,
+        todo_ids: &[i32],
+    ) -> Result<Vec<TodoLocalDueRow>, Error> {
+        retry_transient("todo.export_due_dates", move || async move {
+            let query = format!(
+                "SELECT t.id AS todo_id,
+                    t.due_at AT TIME ZONE COALESCE(p.timezone, '{DEFAULT_TIMEZONE}') AS local_due_at,
+                    COALESCE(p.timezone, '{DEFAULT_TIMEZONE}') AS timezone,
+                    COALESCE(p.locale, '{DEFAULT_LOCALE}') AS locale, p.time_format
+                 FROM todos t
+                 LEFT JOIN user_preferences p ON p.user_id = $1
+                 WHERE t.id = ANY($2) AND t.due_at IS NOT NULL"
+            );
+
+            sqlx::query_as::<_, TodoLocalDueRow>(&query)
+                .bind(to_db_id(user_id)?)
+                .bind(todo_ids)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Checklist items of the exported todos, in position order
+    pub async fn export_items(&self, todo_ids: &[i32]) -> Result<Vec<ItemRow>, Error> {
+        retry_transient("todo.export_items", move || async move {
+            sqlx::query_as::<_, ItemRow>(
+                "SELECT id, todo_id, title, completed, position, created_at, updated_at
+                 FROM todo_items
+                 WHERE todo_id = ANY($1)
+                 ORDER BY position ASC, id ASC",
+            )
+            .bind(todo_ids)
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Activate the todos scheduled up to `now`, returns the owner of each activated todo
+    pub async fn activate_scheduled(&self, now: OffsetDateTime) -> Result<Vec<i32>, Error> {
+        observe_query("todo.activate_scheduled", async move {
+            sqlx::query_scalar::<_, i32>(
+                "UPDATE todos SET scheduled_for = NULL
+                 WHERE scheduled_for <= $1
+                 RETURNING user_id",
+            )
+            .bind(now)
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Take a todo out of the trash, returns `None` when it is not in the trash
+    pub async fn restore_todo(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+    ) -> Result<Option<TodoRow>, Error> {
+        observe_query("todo.restore_todo", async move {
+            let query = format!(
+                "UPDATE todos SET deleted_at = NULL
+                 WHERE id = $1 AND user_id = $2 AND workspace_id = $3 AND deleted_at IS NOT NULL
+                 RETURNING {TODO_COLUMNS}"
+            );
+
+            sqlx::query_as::<_, TodoRow>(&query)
+                .bind(to_db_id(id)?)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Permanently delete the todos trashed before `deleted_before`, returns how many
+    pub async fn purge_trash(&self, deleted_before: OffsetDateTime) -> Result<u64, Error> {
+        observe_query("todo.purge_trash", async move {
+            let result = sqlx::query("DELETE FROM todos WHERE deleted_at < $1")
+                .bind(deleted_before)
+                .execute(&self.pool)
+                .await?;
+
+            Ok(result.rows_affected())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_todo_columns_match_row() {
+        for column in [
+            "id",
+            "list_id",
+            "title",
+            "description",
+            "status",
+            "status_changed_at",
+            "completed_at",
+            "due_at",
+            "recurrence",
+            "scheduled_for",
+            "archived",
+            "version",
+            "created_at",
+            "updated_at",
+            "deleted_at",
+        ] {
+            assert!(TODO_COLUMNS.contains(column));
+        }
+    }
+
+    #[test]
+    fn test_invalid_id_is_rejected() {
+        assert!(to_db_id(i64::from(i32::MAX) + 1).is_err());
+    }
+}
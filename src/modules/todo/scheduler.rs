@@ -0,0 +1,66 @@
+//! # `Todo` Scheduler
+//! Background activation of the scheduled todos. A todo deferred to a later date stays
+//! out of the views until then, its activation is an update so synced clients and live
+//! lists see the todo appear.
+//! With several instances, a single one activates on each run.
+
+use std::time::Duration;
+
+use sqlx::{Pool, Postgres};
+use time::OffsetDateTime;
+
+use crate::{
+    modules::todo::{cache::ViewCache, repository::TodoRepository},
+    workers::{run_exclusive, WorkerTask},
+};
+
+// Delay between two activations, the latest a scheduled todo shows up
+const ACTIVATION_INTERVAL: Duration = Duration::from_secs(60);
+
+// Advisory lock taken by the instance activating
+const ACTIVATION_LOCK: &str = "todo_activation";
+
+// Activate the todos whose date came, returns how many
+async fn activate(
+    todo_repository: &TodoRepository,
+    view_cache: &ViewCache,
+) -> Result<usize, sqlx::Error> {
+    let owners = todo_repository
+        .activate_scheduled(OffsetDateTime::now_utc())
+        .await?;
+
+    // Views cached by the other instances expire on their own
+    let mut owner_ids = owners.clone();
+    owner_ids.sort_unstable();
+    owner_ids.dedup();
+    for owner_id in owner_ids {
+        view_cache.invalidate(i64::from(owner_id)).await;
+    }
+    Ok(owners.len())
+}
+
+// Worker activating the scheduled todos every `ACTIVATION_INTERVAL`. Runs are skipped
+// while another instance activates. Database errors are logged and retried on the next
+// run.
+pub fn activation_worker(
+    pool: Pool<Postgres>,
+    todo_repository: TodoRepository,
+    view_cache: ViewCache,
+) -> WorkerTask {
+    Box::pin(async move {
+        let mut interval = tokio::time::interval(ACTIVATION_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let job = activate(&todo_repository, &view_cache);
+            match run_exclusive(&pool, ACTIVATION_LOCK, job).await {
+                Ok(Some(Ok(0)) | None) => {}
+                Ok(Some(Ok(activated))) => {
+                    tracing::info!("Activated {} scheduled todos", activated);
+                }
+                Ok(Some(Err(e))) => tracing::warn!("Error activating scheduled todos: {}", e),
+                Err(e) => tracing::warn!("Error locking todo activation: {}", e),
+            }
+        }
+    })
+}
@@ -0,0 +1,1121 @@
+//! # `Todo` Service
+//!
+//! This module contains the bussiness logic for todo operations.
+
+use axum::body::Body;
+use time::{format_description::well_known::Rfc3339, Duration, OffsetDateTime};
+
+use crate::{
+    modules::{
+        common::{encode_cursor, AppError},
+        list::interfaces::ListAccess,
+        todo::{
+            analytics::{group_breakdown, stats_range},
+            cache::ViewCache,
+            dedup::CreateDedup,
+            export::export_body,
+            history,
+            interfaces::{
+                AssignListRequest, CheckDuplicatesRequest, CreateTodoRequest,
+                DuplicateTodoResponse, ExportQuery, SmartView, StatsBreakdownQuery,
+                StatsBreakdownResponse, StatsGroup, TodoChanges, TodoFilter,
+                TodoHistoryEntryResponse, TodoMessageResponse, TodoOccurrencesResponse,
+                TodoPageResponse, TodoResponse, TodoRow, TodoStatsResponse, TodoStatus,
+                UpdateTodoRequest, ValidatedCreateTodoRequest,
+            },
+            recurrence::RecurrenceRule,
+            repository::TodoRepository,
+        },
+    },
+    utils::validation::validate_required_fields,
+};
+
+// Occurrences returned by the preview when no count is requested
+const DEFAULT_PREVIEW_OCCURRENCES: usize = 5;
+const MAX_PREVIEW_OCCURRENCES: usize = 50;
+
+// Duplicates suggested for a title
+const MAX_DUPLICATE_SUGGESTIONS: i64 = 5;
+
+// Days covered by the upcoming view, starting tomorrow
+const UPCOMING_DAYS: i64 = 7;
+
+// Start of the next day (UTC) after `now`
+fn end_of_day(now: OffsetDateTime) -> Option<OffsetDateTime> {
+    Some(now.date().next_day()?.midnight().assume_utc())
+}
+
+// Due date window of a smart view as `(from, until)`
+fn view_window(
+    view: SmartView,
+    now: OffsetDateTime,
+) -> Option<(Option<OffsetDateTime>, OffsetDateTime)> {
+    let tomorrow = end_of_day(now)?;
+    match view {
+        SmartView::Today => Some((None, tomorrow)),
+        SmartView::Upcoming => Some((
+            Some(tomorrow),
+            tomorrow.checked_add(Duration::days(UPCOMING_DAYS))?,
+        )),
+    }
+}
+
+// Parse an optional RFC 3339 due date
+fn parse_due_at(due_at: Option<&str>) -> Result<Option<OffsetDateTime>, AppError> {
+    due_at
+        .map(|value| {
+            OffsetDateTime::parse(value.trim(), &Rfc3339).map_err(|_| {
+                AppError::Validation(
+                    "Due date must be a RFC 3339 date, e.g. 2025-01-31T09:00:00Z".to_string(),
+                )
+            })
+        })
+        .transpose()
+}
+
+// Parse an optional RFC 3339 activation date, a blank date or one not after `now`
+// activates the todo right away as `Some(None)`
+#[allow(clippy::option_option)]
+fn parse_scheduled_for(
+    scheduled_for: Option<&str>,
+    now: OffsetDateTime,
+) -> Result<Option<Option<OffsetDateTime>>, AppError> {
+    match scheduled_for.map(str::trim) {
+        None => Ok(None),
+        Some("") => Ok(Some(None)),
+        Some(value) => OffsetDateTime::parse(value, &Rfc3339)
+            .map(|date| Some(Some(date).filter(|date| *date > now)))
+            .map_err(|_| {
+                AppError::Validation(
+                    "Scheduled date must be a RFC 3339 date, e.g. 2025-01-31T09:00:00Z".to_string(),
+                )
+            }),
+    }
+}
+
+// Validate a RRULE and return it in canonical form, blank rules are kept blank
+fn parse_recurrence(recurrence: Option<String>) -> Result<Option<String>, AppError> {
+    match recurrence {
+        Some(rule) if rule.trim().is_empty() => Ok(Some(String::new())),
+        Some(rule) => rule
+            .parse::<RecurrenceRule>()
+            .map(|rule| Some(rule.to_string()))
+            .map_err(|e| AppError::Validation(format!("Invalid recurrence: {e}"))),
+        None => Ok(None),
+    }
+}
+
+// Validate a requested status change against the workflow
+fn validate_transition(
+    current: TodoStatus,
+    next: Option<&str>,
+) -> Result<Option<TodoStatus>, AppError> {
+    let Some(next) = next else {
+        return Ok(None);
+    };
+    let next = next.parse::<TodoStatus>().map_err(AppError::Validation)?;
+
+    if current.can_move_to(next) {
+        Ok(Some(next))
+    } else {
+        Err(AppError::Validation(format!(
+            "Cannot move a {current} todo to {next}"
+        )))
+    }
+}
+
+// Warnings of a created or updated todo, from the due date and recurrence set by the
+// request and the resulting due date of the todo
+fn todo_warnings(
+    due_at: Option<OffsetDateTime>,
+    recurrence: Option<&str>,
+    todo_due_at: Option<OffsetDateTime>,
+    now: OffsetDateTime,
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if due_at.is_some_and(|due_at| due_at < now) {
+        warnings.push("Due date is in the past".to_string());
+    }
+    if recurrence.is_some_and(|rule| !rule.is_empty()) && todo_due_at.is_none() {
+        warnings.push(
+            "Recurring todo has no due date, its next occurrence is due from its completion"
+                .to_string(),
+        );
+    }
+    warnings
+}
+
+// Warning of a todo activated only after it is due
+fn schedule_warning(todo: &TodoRow) -> Option<String> {
+    match (todo.scheduled_for, todo.due_at) {
+        (Some(scheduled_for), Some(due_at)) if scheduled_for > due_at => {
+            Some("Todo is scheduled after its due date".to_string())
+        }
+        _ => None,
+    }
+}
+
+// Error code of the changes refused by the WIP limit of a list column
+pub const WIP_LIMIT_EXCEEDED: &str = "wip_limit_exceeded";
+
+// Owner whose todos are accessed, editing needs the owner or editor role
+fn authorize(access: ListAccess, edit: bool) -> Result<i64, AppError> {
+    if edit && !access.role.can_edit() {
+        return Err(AppError::Forbidden(
+            "Viewers cannot modify the todos of a shared list".to_string(),
+        ));
+    }
+    Ok(access.owner_id)
+}
+
+pub struct TodoService {
+    todo_repository: TodoRepository,
+    view_cache: ViewCache,
+    create_dedup: CreateDedup,
+}
+
+impl TodoService {
+    pub const fn new(
+        todo_repository: TodoRepository,
+        view_cache: ViewCache,
+        create_dedup: CreateDedup,
+    ) -> Self {
+        Self {
+            todo_repository,
+            view_cache,
+            create_dedup,
+        }
+    }
+
+    // Owner of a todo the user owns or that belongs to a list shared with them
+    async fn todo_owner(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+        edit: bool,
+    ) -> Result<i64, AppError> {
+        match self
+            .todo_repository
+            .todo_access(user_id, workspace_id, id)
+            .await
+        {
+            Ok(Some(access)) => authorize(ListAccess::from(access), edit),
+            Ok(None) => Err(AppError::NotFound("Todo not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error checking todo access: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Owner of a list the user owns or that is shared with them
+    async fn list_owner(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        list_id: i64,
+        edit: bool,
+    ) -> Result<i64, AppError> {
+        match self
+            .todo_repository
+            .list_access(user_id, workspace_id, list_id)
+            .await
+        {
+            Ok(Some(access)) => authorize(ListAccess::from(access), edit),
+            Ok(None) => Err(AppError::NotFound("List not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error checking list access: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Create a todo for the user in the workspace. When deduplication is enabled, an
+    // identical request sent shortly before returns the original todo, the flag tells if
+    // it was created now.
+    pub async fn create_todo(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        create_request: CreateTodoRequest,
+    ) -> Result<(TodoResponse, bool), AppError> {
+        self.create_dedup
+            .create(
+                user_id,
+                &create_request,
+                self.insert_todo(user_id, workspace_id, create_request.clone()),
+            )
+            .await
+    }
+
+    async fn insert_todo(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        create_request: CreateTodoRequest,
+    ) -> Result<TodoResponse, AppError> {
+        let mut validated_todo: ValidatedCreateTodoRequest =
+            match validate_required_fields(&create_request, vec!["title"]) {
+                Err(missing) => return Err(AppError::InvalidFields(missing)),
+                Ok(todo) => todo,
+            };
+        let due_at = parse_due_at(validated_todo.due_at.as_deref())?;
+        let scheduled_for = parse_scheduled_for(
+            validated_todo.scheduled_for.as_deref(),
+            OffsetDateTime::now_utc(),
+        )?
+        .flatten();
+        validated_todo.recurrence =
+            parse_recurrence(validated_todo.recurrence.take())?.filter(|rule| !rule.is_empty());
+
+        // Todos added by editors to a shared list belong to the list owner
+        let owner_id = match validated_todo.list_id {
+            Some(list_id) => {
+                let owner_id = self
+                    .list_owner(user_id, workspace_id, list_id, true)
+                    .await?;
+                self.check_wip_limit(list_id, TodoStatus::default(), None)
+                    .await?;
+                owner_id
+            }
+            None => user_id,
+        };
+
+        let recurrence = validated_todo.recurrence.clone();
+        match self
+            .todo_repository
+            .create_todo(
+                owner_id,
+                workspace_id,
+                validated_todo,
+                due_at,
+                scheduled_for,
+            )
+            .await
+        {
+            Ok(Some(todo)) => {
+                self.view_cache.invalidate(owner_id).await;
+                let mut warnings = todo_warnings(
+                    due_at,
+                    recurrence.as_deref(),
+                    todo.due_at,
+                    OffsetDateTime::now_utc(),
+                );
+                warnings.extend(schedule_warning(&todo));
+                Ok(TodoResponse {
+                    warnings: Some(warnings),
+                    ..TodoResponse::from(todo)
+                })
+            }
+            Ok(None) => Err(AppError::NotFound("List not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error creating todo: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Open todos of the user whose title looks like the one about to be created, so
+    // clients can ask before creating a duplicate
+    pub async fn check_duplicates(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        check_request: CheckDuplicatesRequest,
+    ) -> Result<Vec<DuplicateTodoResponse>, AppError> {
+        let Some(title) = check_request
+            .title
+            .as_deref()
+            .map(str::trim)
+            .filter(|title| !title.is_empty())
+        else {
+            return Err(AppError::Validation(
+                "Missing required fields: title".to_string(),
+            ));
+        };
+
+        match self
+            .todo_repository
+            .find_similar(
+                user_id,
+                workspace_id,
+                title,
+                check_request.list_id,
+                MAX_DUPLICATE_SUGGESTIONS,
+            )
+            .await
+        {
+            Ok(todos) => Ok(todos.into_iter().map(DuplicateTodoResponse::from).collect()),
+            Err(e) => {
+                tracing::warn!("Error checking duplicate todos: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // List a page of the user todos, `before_id` comes from the page cursor
+    pub async fn list_todos(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        filter: &TodoFilter,
+        before_id: Option<i64>,
+        limit: i64,
+    ) -> Result<TodoPageResponse, AppError> {
+        // The todos of a shared list are listed from its owner, other lists list nothing
+        let owner_id = match filter.list_id {
+            Some(list_id) => self
+                .list_owner(user_id, workspace_id, list_id, false)
+                .await
+                .unwrap_or(user_id),
+            None => user_id,
+        };
+
+        // One extra row tells whether there is a next page
+        match self
+            .todo_repository
+            .list_todos(owner_id, workspace_id, filter, before_id, limit + 1)
+            .await
+        {
+            Ok(mut todos) => {
+                let page_size = usize::try_from(limit).unwrap_or(1);
+                let next_cursor = if todos.len() > page_size {
+                    todos.truncate(page_size);
+                    todos
+                        .last()
+                        .and_then(|todo| encode_cursor(&i64::from(todo.id)))
+                } else {
+                    None
+                };
+
+                Ok(TodoPageResponse {
+                    todos: todos.into_iter().map(TodoResponse::from).collect(),
+                    next_cursor,
+                })
+            }
+            Err(e) => {
+                tracing::warn!("Error listing todos: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Fetch a single todo
+    pub async fn fetch_todo(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+    ) -> Result<TodoResponse, AppError> {
+        let owner_id = self.todo_owner(user_id, workspace_id, id, false).await?;
+
+        match self
+            .todo_repository
+            .fetch_todo(owner_id, workspace_id, id)
+            .await
+        {
+            Ok(Some(todo)) => Ok(TodoResponse::from(todo)),
+            Ok(None) => Err(AppError::NotFound("Todo not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error fetching todo: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Update todo data
+    pub async fn update_todo(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+        update_request: UpdateTodoRequest,
+    ) -> Result<TodoResponse, AppError> {
+        if let Some(ref title) = update_request.title {
+            if title.trim().is_empty() {
+                return Err(AppError::Validation("Title cannot be empty".to_string()));
+            }
+        }
+        let owner_id = self.todo_owner(user_id, workspace_id, id, true).await?;
+
+        // Transitions are checked against the current status
+        let (current, list_id) = if update_request.status.is_some() {
+            let todo = self.fetch_todo(owner_id, workspace_id, id).await?;
+            (todo.status, todo.list_id)
+        } else {
+            (TodoStatus::default(), None)
+        };
+        let status = validate_transition(current, update_request.status.as_deref())?;
+        if let (Some(next), Some(list_id)) = (status, list_id) {
+            if next != current {
+                self.check_wip_limit(list_id, next, Some(id)).await?;
+            }
+        }
+
+        let changes = TodoChanges {
+            status,
+            due_at: parse_due_at(update_request.due_at.as_deref())?,
+            recurrence: parse_recurrence(update_request.recurrence)?,
+            scheduled_for: parse_scheduled_for(
+                update_request.scheduled_for.as_deref(),
+                OffsetDateTime::now_utc(),
+            )?,
+            title: update_request.title,
+            description: update_request.description,
+            expected_version: update_request.version,
+        };
+        let completed = current != TodoStatus::Done && changes.status == Some(TodoStatus::Done);
+
+        let expected_version = changes.expected_version;
+        let (due_at, recurrence) = (changes.due_at, changes.recurrence.clone());
+        let todo = match self
+            .todo_repository
+            .update_todo(owner_id, workspace_id, id, user_id, changes)
+            .await
+        {
+            Ok(Some(todo)) => todo,
+            Ok(None) if expected_version.is_some() => {
+                return Err(AppError::PreconditionFailed(
+                    "Todo changed since the given version".to_string(),
+                ))
+            }
+            Ok(None) => return Err(AppError::NotFound("Todo not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error updating todo: {}", e);
+                return Err(AppError::Database(e));
+            }
+        };
+
+        self.view_cache.invalidate(owner_id).await;
+        if completed && todo.recurrence.is_some() {
+            self.create_next_occurrence(owner_id, workspace_id, &todo)
+                .await?;
+        }
+
+        let mut warnings = todo_warnings(
+            due_at,
+            recurrence.as_deref(),
+            todo.due_at,
+            OffsetDateTime::now_utc(),
+        );
+        warnings.extend(schedule_warning(&todo));
+        Ok(TodoResponse {
+            warnings: Some(warnings),
+            ..TodoResponse::from(todo)
+        })
+    }
+
+    // Refuse to put a todo in a status column of a list holding as many todos as its WIP
+    // limit. `todo_id` is the todo put there, not counted when already in the column.
+    async fn check_wip_limit(
+        &self,
+        list_id: i64,
+        status: TodoStatus,
+        todo_id: Option<i64>,
+    ) -> Result<(), AppError> {
+        match self
+            .todo_repository
+            .reached_wip_limit(list_id, status, todo_id)
+            .await
+        {
+            Ok(None) => Ok(()),
+            Ok(Some(limit)) => Err(AppError::Rejected(
+                WIP_LIMIT_EXCEEDED,
+                format!("The {status} column of the list is limited to {limit} todos"),
+            )),
+            Err(e) => {
+                tracing::warn!("Error checking WIP limit: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Edits of a todo, newest first
+    pub async fn todo_history(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+    ) -> Result<Vec<TodoHistoryEntryResponse>, AppError> {
+        self.todo_owner(user_id, workspace_id, id, false).await?;
+
+        match self.todo_repository.list_history(id).await {
+            Ok(rows) => Ok(history::group_entries(rows)),
+            Err(e) => {
+                tracing::warn!("Error listing todo history: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Set the title, description and due date of a todo back to how they were at
+    // `version`. The revert is recorded in the history as a new version.
+    pub async fn revert_todo(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+        version: i64,
+    ) -> Result<TodoResponse, AppError> {
+        let owner_id = self.todo_owner(user_id, workspace_id, id, true).await?;
+        let todo = match self
+            .todo_repository
+            .fetch_todo(owner_id, workspace_id, id)
+            .await
+        {
+            Ok(Some(todo)) => todo,
+            Ok(None) => return Err(AppError::NotFound("Todo not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error fetching todo: {}", e);
+                return Err(AppError::Database(e));
+            }
+        };
+        if version < 1 || version >= todo.version {
+            return Err(AppError::NotFound("Todo version not found".to_string()));
+        }
+
+        let restore = match self.todo_repository.list_history(id).await {
+            Ok(rows) => history::restore_to(&rows, version, &history::current_fields(&todo)),
+            Err(e) => {
+                tracing::warn!("Error listing todo history: {}", e);
+                return Err(AppError::Database(e));
+            }
+        };
+        if restore.is_empty() {
+            return Err(AppError::Validation(
+                "Todo has no changes since this version".to_string(),
+            ));
+        }
+
+        match self
+            .todo_repository
+            .restore_fields(owner_id, workspace_id, id, user_id, restore)
+            .await
+        {
+            Ok(Some(todo)) => {
+                self.view_cache.invalidate(owner_id).await;
+                Ok(TodoResponse::from(todo))
+            }
+            Ok(None) => Err(AppError::NotFound("Todo not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error reverting todo: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Materialize the next occurrence of a completed recurring todo
+    async fn create_next_occurrence(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        todo: &TodoRow,
+    ) -> Result<(), AppError> {
+        let Some(rule) = todo
+            .recurrence
+            .as_deref()
+            .and_then(|rule| rule.parse::<RecurrenceRule>().ok())
+        else {
+            tracing::warn!("Todo {} has an invalid recurrence", todo.id);
+            return Ok(());
+        };
+
+        // Todos without due date repeat from the moment they are completed
+        let anchor = todo.due_at.unwrap_or_else(OffsetDateTime::now_utc);
+        let Some((due_at, remaining)) = rule.next_occurrence(anchor) else {
+            return Ok(());
+        };
+
+        match self
+            .todo_repository
+            .create_next_occurrence(
+                user_id,
+                workspace_id,
+                i64::from(todo.id),
+                due_at,
+                &remaining.to_string(),
+            )
+            .await
+        {
+            Ok(Some(next)) => {
+                tracing::info!("Created occurrence {} of todo {}", next.id, todo.id);
+                self.view_cache.invalidate(user_id).await;
+                Ok(())
+            }
+            Ok(None) => Ok(()),
+            Err(e) => {
+                tracing::warn!("Error creating next occurrence: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Open todos of a smart view, served from the cache when fresh
+    pub async fn smart_view(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        view: SmartView,
+    ) -> Result<Vec<TodoResponse>, AppError> {
+        if let Some(todos) = self.view_cache.todos(user_id, workspace_id, view).await {
+            return Ok(todos);
+        }
+
+        let Some((from, until)) = view_window(view, OffsetDateTime::now_utc()) else {
+            return Err(AppError::Internal("Failed to load view".to_string()));
+        };
+
+        match self
+            .todo_repository
+            .list_due_todos(user_id, workspace_id, from, until)
+            .await
+        {
+            Ok(rows) => {
+                let todos: Vec<TodoResponse> = rows.into_iter().map(TodoResponse::from).collect();
+                self.view_cache
+                    .store_todos(user_id, workspace_id, view, todos.clone())
+                    .await;
+                Ok(todos)
+            }
+            Err(e) => {
+                tracing::warn!("Error loading {:?} view: {}", view, e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Todo counters of the user, served from the cache when fresh
+    pub async fn todo_stats(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+    ) -> Result<TodoStatsResponse, AppError> {
+        if let Some(stats) = self.view_cache.stats(user_id, workspace_id).await {
+            return Ok(stats);
+        }
+
+        let now = OffsetDateTime::now_utc();
+        let Some(end_of_day) = end_of_day(now) else {
+            return Err(AppError::Internal("Failed to load stats".to_string()));
+        };
+
+        match self
+            .todo_repository
+            .todo_stats(user_id, workspace_id, now, end_of_day)
+            .await
+        {
+            Ok(stats) => {
+                self.view_cache
+                    .store_stats(user_id, workspace_id, stats.clone())
+                    .await;
+                Ok(stats)
+            }
+            Err(e) => {
+                tracing::warn!("Error loading todo stats: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Todos created and completed per list or tag over the asked range
+    pub async fn stats_breakdown(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        group: StatsGroup,
+        query: &StatsBreakdownQuery,
+    ) -> Result<StatsBreakdownResponse, AppError> {
+        let range =
+            stats_range(query, OffsetDateTime::now_utc().date()).map_err(AppError::Validation)?;
+        let (from, to) = (range.from.to_string(), range.to.to_string());
+
+        let same_range = |cached: &StatsBreakdownResponse| {
+            cached.from == from && cached.to == to && cached.interval == range.interval
+        };
+        if let Some(breakdown) = self
+            .view_cache
+            .breakdown(user_id, workspace_id, group, same_range)
+            .await
+        {
+            return Ok(breakdown);
+        }
+
+        match self
+            .todo_repository
+            .stats_breakdown(
+                user_id,
+                workspace_id,
+                group,
+                range.interval,
+                range.start(),
+                range.end(),
+            )
+            .await
+        {
+            Ok(rows) => {
+                let breakdown = StatsBreakdownResponse {
+                    from,
+                    to,
+                    interval: range.interval,
+                    groups: group_breakdown(rows),
+                };
+                self.view_cache
+                    .store_breakdown(user_id, workspace_id, group, breakdown.clone())
+                    .await;
+                Ok(breakdown)
+            }
+            Err(e) => {
+                tracing::warn!("Error loading todo stats breakdown: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Preview the next occurrences of a recurring todo
+    pub async fn preview_occurrences(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+        count: Option<usize>,
+    ) -> Result<TodoOccurrencesResponse, AppError> {
+        let owner_id = self.todo_owner(user_id, workspace_id, id, false).await?;
+        let todo = match self
+            .todo_repository
+            .fetch_todo(owner_id, workspace_id, id)
+            .await
+        {
+            Ok(Some(todo)) => todo,
+            Ok(None) => return Err(AppError::NotFound("Todo not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error fetching todo: {}", e);
+                return Err(AppError::Database(e));
+            }
+        };
+
+        let Some(recurrence) = todo.recurrence else {
+            return Err(AppError::Validation("Todo is not recurring".to_string()));
+        };
+        let rule = recurrence.parse::<RecurrenceRule>().map_err(|e| {
+            tracing::warn!("Todo {} has an invalid recurrence: {}", todo.id, e);
+            AppError::Validation("Todo has an invalid recurrence".to_string())
+        })?;
+
+        let count = count
+            .unwrap_or(DEFAULT_PREVIEW_OCCURRENCES)
+            .min(MAX_PREVIEW_OCCURRENCES);
+        let anchor = todo.due_at.unwrap_or_else(OffsetDateTime::now_utc);
+
+        Ok(TodoOccurrencesResponse {
+            todo_id: i64::from(todo.id),
+            recurrence,
+            occurrences: rule
+                .preview(anchor, count)
+                .into_iter()
+                .map(|dt| dt.to_string())
+                .collect(),
+        })
+    }
+
+    // Attach the todo to a list or detach it
+    pub async fn assign_list(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+        assign_request: AssignListRequest,
+    ) -> Result<TodoResponse, AppError> {
+        // Make sure the todo exists so a missing list can be reported precisely.
+        // Only the owner moves todos, a shared list never changes hands this way.
+        if self.todo_owner(user_id, workspace_id, id, true).await? != user_id {
+            return Err(AppError::Forbidden(
+                "Only the list owner can move its todos".to_string(),
+            ));
+        }
+        if let Some(list_id) = assign_request.list_id {
+            let status = self.fetch_todo(user_id, workspace_id, id).await?.status;
+            self.check_wip_limit(list_id, status, Some(id)).await?;
+        }
+
+        match self
+            .todo_repository
+            .assign_list(user_id, workspace_id, id, assign_request.list_id)
+            .await
+        {
+            Ok(Some(todo)) => {
+                self.view_cache.invalidate(user_id).await;
+                Ok(TodoResponse::from(todo))
+            }
+            Ok(None) => Err(AppError::NotFound("List not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error assigning todo to list: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Delete a todo
+    pub async fn delete_todo(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+    ) -> Result<TodoMessageResponse, AppError> {
+        let owner_id = self.todo_owner(user_id, workspace_id, id, true).await?;
+
+        match self
+            .todo_repository
+            .delete_todo(owner_id, workspace_id, id)
+            .await
+        {
+            Ok(true) => {
+                self.view_cache.invalidate(owner_id).await;
+                Ok(TodoMessageResponse {
+                    message: "Todo moved to trash".to_string(),
+                })
+            }
+            Ok(false) => Err(AppError::NotFound("Todo not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error deleting todo: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Archive or unarchive a todo
+    pub async fn set_archived(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+        archived: bool,
+    ) -> Result<TodoResponse, AppError> {
+        let owner_id = self.todo_owner(user_id, workspace_id, id, true).await?;
+
+        match self
+            .todo_repository
+            .set_archived(owner_id, workspace_id, id, archived)
+            .await
+        {
+            Ok(Some(todo)) => {
+                self.view_cache.invalidate(owner_id).await;
+                Ok(TodoResponse::from(todo))
+            }
+            Ok(None) => Err(AppError::NotFound("Todo not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error archiving todo: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // List the todos in the trash
+    pub async fn list_trash(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+    ) -> Result<Vec<TodoResponse>, AppError> {
+        match self.todo_repository.list_trash(user_id, workspace_id).await {
+            Ok(todos) => Ok(todos.into_iter().map(TodoResponse::from).collect()),
+            Err(e) => {
+                tracing::warn!("Error listing trash: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // List the scheduled todos waiting for their activation
+    pub async fn list_scheduled(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+    ) -> Result<Vec<TodoResponse>, AppError> {
+        match self
+            .todo_repository
+            .list_scheduled(user_id, workspace_id)
+            .await
+        {
+            Ok(todos) => Ok(todos.into_iter().map(TodoResponse::from).collect()),
+            Err(e) => {
+                tracing::warn!("Error listing scheduled todos: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Stream the todos of the user in the workspace in the requested format, only JSON
+    // Lines for now
+    pub fn export_todos(
+        self,
+        user_id: i64,
+        workspace_id: i64,
+        query: &ExportQuery,
+    ) -> Result<Body, AppError> {
+        match query.format.as_deref().map(str::trim) {
+            None | Some("ndjson" | "jsonl") => {
+                Ok(export_body(self.todo_repository, user_id, workspace_id))
+            }
+            Some(format) => Err(AppError::Validation(format!(
+                "Unsupported export format: {format}"
+            ))),
+        }
+    }
+
+    // Restore a todo from the trash
+    pub async fn restore_todo(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        id: i64,
+    ) -> Result<TodoResponse, AppError> {
+        match self
+            .todo_repository
+            .restore_todo(user_id, workspace_id, id)
+            .await
+        {
+            Ok(Some(todo)) => {
+                self.view_cache.invalidate(user_id).await;
+                Ok(TodoResponse::from(todo))
+            }
+            Ok(None) => Err(AppError::NotFound("Todo not found in trash".to_string())),
+            Err(e) => {
+                tracing::warn!("Error restoring todo: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::utils::validation::ValidationErrors;
+
+    #[test]
+    fn test_todo_warnings() {
+        let now = OffsetDateTime::now_utc();
+        let yesterday = now - Duration::days(1);
+        let tomorrow = now + Duration::days(1);
+
+        assert!(todo_warnings(Some(tomorrow), None, Some(tomorrow), now).is_empty());
+        assert!(todo_warnings(None, None, Some(yesterday), now).is_empty());
+        assert_eq!(
+            todo_warnings(Some(yesterday), None, Some(yesterday), now),
+            vec!["Due date is in the past"]
+        );
+        assert_eq!(todo_warnings(None, Some("FREQ=DAILY"), None, now).len(), 1);
+        assert!(todo_warnings(None, Some(""), None, now).is_empty());
+        assert!(todo_warnings(None, Some("FREQ=DAILY"), Some(tomorrow), now).is_empty());
+    }
+
+    #[test]
+    fn test_create_todo_requires_title() {
+        let request = CreateTodoRequest {
+            title: Some("   ".to_string()),
+            description: None,
+            list_id: None,
+            due_at: None,
+            recurrence: None,
+            scheduled_for: None,
+        };
+
+        let result: Result<ValidatedCreateTodoRequest, ValidationErrors> =
+            validate_required_fields(&request, vec!["title"]);
+        assert_eq!(result.unwrap_err().fields(), vec!["title"]);
+    }
+
+    #[test]
+    fn test_create_todo_validation_keeps_optional_fields() {
+        let request = CreateTodoRequest {
+            title: Some("Buy milk".to_string()),
+            description: None,
+            list_id: Some(4),
+            due_at: None,
+            recurrence: Some("FREQ=DAILY".to_string()),
+            scheduled_for: None,
+        };
+
+        let validated: ValidatedCreateTodoRequest =
+            validate_required_fields(&request, vec!["title"]).unwrap();
+        assert_eq!(validated.title, "Buy milk");
+        assert_eq!(validated.description, None);
+        assert_eq!(validated.list_id, Some(4));
+        assert_eq!(validated.recurrence, Some("FREQ=DAILY".to_string()));
+    }
+
+    #[test]
+    fn test_view_windows() {
+        let now = time::macros::datetime!(2025-03-10 15:30 UTC);
+
+        let (from, until) = view_window(SmartView::Today, now).unwrap();
+        assert_eq!(from, None);
+        assert_eq!(until, time::macros::datetime!(2025-03-11 00:00 UTC));
+
+        let (from, until) = view_window(SmartView::Upcoming, now).unwrap();
+        assert_eq!(from, Some(time::macros::datetime!(2025-03-11 00:00 UTC)));
+        assert_eq!(until, time::macros::datetime!(2025-03-18 00:00 UTC));
+    }
+
+    #[test]
+    fn test_parse_due_at() {
+        assert_eq!(parse_due_at(None).unwrap(), None);
+        assert!(parse_due_at(Some("2025-01-31T09:00:00Z"))
+            .unwrap()
+            .is_some());
+        assert!(parse_due_at(Some("tomorrow")).is_err());
+    }
+
+    #[test]
+    fn test_parse_scheduled_for() {
+        let now = time::macros::datetime!(2025-03-10 15:30 UTC);
+        assert_eq!(parse_scheduled_for(None, now).unwrap(), None);
+        assert_eq!(parse_scheduled_for(Some(" "), now).unwrap(), Some(None));
+        assert_eq!(
+            parse_scheduled_for(Some("2025-03-17T08:00:00Z"), now).unwrap(),
+            Some(Some(time::macros::datetime!(2025-03-17 08:00 UTC)))
+        );
+        // A date already past activates the todo
+        assert_eq!(
+            parse_scheduled_for(Some("2025-03-10T08:00:00Z"), now).unwrap(),
+            Some(None)
+        );
+        assert!(parse_scheduled_for(Some("next monday"), now).is_err());
+    }
+
+    #[test]
+    fn test_parse_recurrence_normalizes_rule() {
+        assert_eq!(
+            parse_recurrence(Some("rrule:freq=daily;interval=1".to_string())).unwrap(),
+            Some("FREQ=DAILY".to_string())
+        );
+        assert_eq!(
+            parse_recurrence(Some("  ".to_string())).unwrap(),
+            Some(String::new())
+        );
+        assert!(parse_recurrence(Some("FREQ=SOMETIMES".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_validate_transition() {
+        assert_eq!(validate_transition(TodoStatus::Done, None).unwrap(), None);
+        assert_eq!(
+            validate_transition(TodoStatus::Backlog, Some("in_progress")).unwrap(),
+            Some(TodoStatus::InProgress)
+        );
+        assert!(validate_transition(TodoStatus::Cancelled, Some("done")).is_err());
+        assert!(validate_transition(TodoStatus::Backlog, Some("finished")).is_err());
+    }
+
+    #[test]
+    fn test_authorize_shared_todos() {
+        use crate::modules::list::interfaces::ListRole;
+
+        let access = |role| ListAccess { owner_id: 7, role };
+        assert_eq!(authorize(access(ListRole::Owner), true).ok(), Some(7));
+        assert_eq!(authorize(access(ListRole::Editor), true).ok(), Some(7));
+        assert_eq!(authorize(access(ListRole::Viewer), false).ok(), Some(7));
+        assert!(authorize(access(ListRole::Viewer), true).is_err());
+    }
+}
@@ -0,0 +1,103 @@
+//! # `Todo` Trash
+//! Background purge of the todos kept in the trash past the retention window,
+//! and of the tombstones and change events past theirs.
+//! With several instances, a single one purges on each run.
+
+use sqlx::{Pool, Postgres};
+use time::{Duration, OffsetDateTime};
+
+use crate::{
+    modules::{changes::repository::ChangesRepository, todo::repository::TodoRepository},
+    workers::{run_exclusive, WorkerTask},
+};
+
+// Delay between two purges
+const PURGE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+// Advisory lock taken by the instance running a purge
+const PURGE_LOCK: &str = "trash_purge";
+
+// Deletion date before which trashed todos are purged
+fn purge_cutoff(now: OffsetDateTime, retention: Duration) -> OffsetDateTime {
+    now.checked_sub(retention)
+        .unwrap_or(OffsetDateTime::UNIX_EPOCH)
+}
+
+// Purge the trash, then the tombstones and change events past their retention
+async fn purge(
+    todo_repository: &TodoRepository,
+    changes_repository: &ChangesRepository,
+    retention: Duration,
+    tombstone_retention: Duration,
+) {
+    let now = OffsetDateTime::now_utc();
+    match todo_repository
+        .purge_trash(purge_cutoff(now, retention))
+        .await
+    {
+        Ok(0) => {}
+        Ok(purged) => tracing::info!("Purged {} todos from the trash", purged),
+        Err(e) => tracing::warn!("Error purging trash: {}", e),
+    }
+
+    // Runs after the trash purge so its tombstones start their own retention
+    match changes_repository
+        .purge_tombstones(purge_cutoff(now, tombstone_retention))
+        .await
+    {
+        Ok(0) => {}
+        Ok(purged) => tracing::info!("Purged {} tombstones", purged),
+        Err(e) => tracing::warn!("Error purging tombstones: {}", e),
+    }
+}
+
+// Worker permanently deleting todos trashed for longer than `retention`, then the
+// tombstones and change events older than `tombstone_retention`.
+// Runs are skipped while another instance purges. Database errors are logged and
+// retried on the next run.
+pub fn purge_worker(
+    pool: Pool<Postgres>,
+    todo_repository: TodoRepository,
+    changes_repository: ChangesRepository,
+    retention: Duration,
+    tombstone_retention: Duration,
+) -> WorkerTask {
+    Box::pin(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let job = purge(
+                &todo_repository,
+                &changes_repository,
+                retention,
+                tombstone_retention,
+            );
+            match run_exclusive(&pool, PURGE_LOCK, job).await {
+                Ok(Some(())) => {}
+                Ok(None) => tracing::debug!("Trash purge running on another instance"),
+                Err(e) => tracing::warn!("Error locking trash purge: {}", e),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_purge_cutoff() {
+        let now = time::macros::datetime!(2025-03-31 12:00 UTC);
+        assert_eq!(
+            purge_cutoff(now, Duration::days(30)),
+            time::macros::datetime!(2025-03-01 12:00 UTC)
+        );
+    }
+
+    #[test]
+    fn test_purge_cutoff_saturates() {
+        let now = time::macros::datetime!(2025-03-31 12:00 UTC);
+        assert_eq!(purge_cutoff(now, Duration::MAX), OffsetDateTime::UNIX_EPOCH);
+    }
+}
@@ -0,0 +1,48 @@
+//! # `TwoFactor` Interfaces
+//! This module defines the data structures from Two-factor module
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+// Secret of a two-factor setup, to register in an authenticator app
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct EnableTwoFactorResponse {
+    // Base32 secret, for apps that can't scan the URI
+    pub secret: String,
+    // otpauth URI of the secret, usually shown as a QR code
+    pub otpauth_uri: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct TwoFactorCodeRequest {
+    // Current code of the authenticator app
+    pub code: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct RecoveryCodesResponse {
+    // Single use codes replacing the authenticator app, only returned once
+    pub recovery_codes: Vec<String>,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct TwoFactorLoginRequest {
+    // Pre-auth token returned by the login
+    pub token: Option<String>,
+    // Code of the authenticator app or a recovery code
+    pub code: Option<String>,
+    // Keep the session in an `HttpOnly` cookie, as asked to the login
+    pub cookie: Option<bool>,
+}
+
+// Two-factor setup as stored in database
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct TwoFactorRow {
+    // Hex encoded TOTP secret
+    pub secret: String,
+    // Set once the setup is confirmed with a code
+    pub enabled_at: Option<OffsetDateTime>,
+}
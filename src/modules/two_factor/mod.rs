@@ -0,0 +1,9 @@
+//! # `TwoFactor` Mod
+//! Two-factor imports for the TOTP second step of the login
+
+pub mod interfaces;
+pub mod repository;
+pub mod routes;
+pub mod service;
+
+pub use routes::two_factor_routes;
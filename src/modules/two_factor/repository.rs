@@ -0,0 +1,212 @@
+//! # `TwoFactor` Repository
+//! This module defines the two-factor repository for the TOTP setup and login challenges.
+
+use sqlx::{Error, Pool, Postgres};
+use time::OffsetDateTime;
+
+use crate::modules::{common::to_db_id, two_factor::interfaces::TwoFactorRow};
+use crate::telemetry::observe_query;
+
+pub struct TwoFactorRepository {
+    pool: Pool<Postgres>,
+}
+
+impl TwoFactorRepository {
+    // Constructor
+    pub const fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Start or restart the setup of the user with a new secret. Returns the username the
+    // secret is registered for, `None` when two-factor authentication is already enabled.
+    pub async fn start_setup(&self, user_id: i64, secret: &str) -> Result<Option<String>, Error> {
+        observe_query("two_factor.start_setup", async move {
+            sqlx::query_scalar::<_, String>(
+                "WITH started AS (
+                     INSERT INTO user_two_factor (user_id, secret) VALUES ($1, $2)
+                     ON CONFLICT (user_id) DO UPDATE SET
+                        secret = EXCLUDED.secret,
+                        last_used_step = NULL,
+                        created_at = NOW()
+                     WHERE user_two_factor.enabled_at IS NULL
+                     RETURNING user_id
+                 )
+                 SELECT u.username FROM started JOIN users u ON u.id = started.user_id",
+            )
+            .bind(to_db_id(user_id)?)
+            .bind(secret)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Two-factor setup of the user, pending or enabled
+    pub async fn fetch_setup(&self, user_id: i64) -> Result<Option<TwoFactorRow>, Error> {
+        observe_query("two_factor.fetch_setup", async move {
+            sqlx::query_as::<_, TwoFactorRow>(
+                "SELECT secret, enabled_at FROM user_two_factor WHERE user_id = $1",
+            )
+            .bind(to_db_id(user_id)?)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Check if the user logs in with a second factor
+    pub async fn is_enabled(&self, user_id: i64) -> Result<bool, Error> {
+        observe_query("two_factor.is_enabled", async move {
+            sqlx::query_scalar::<_, bool>(
+                "SELECT EXISTS(
+                    SELECT 1 FROM user_two_factor WHERE user_id = $1 AND enabled_at IS NOT NULL)",
+            )
+            .bind(to_db_id(user_id)?)
+            .fetch_one(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Enable the pending setup confirmed by the code of `step`, replacing the recovery
+    // codes. Returns false when there is no pending setup or the step was already used.
+    pub async fn enable(
+        &self,
+        user_id: i64,
+        step: i64,
+        recovery_code_hashes: &[String],
+    ) -> Result<bool, Error> {
+        observe_query("two_factor.enable", async move {
+            let user_id = to_db_id(user_id)?;
+            let mut tx = self.pool.begin().await?;
+
+            let enabled = sqlx::query(
+                "UPDATE user_two_factor SET enabled_at = NOW(), last_used_step = $2
+                 WHERE user_id = $1 AND enabled_at IS NULL
+                   AND (last_used_step IS NULL OR last_used_step < $2)",
+            )
+            .bind(user_id)
+            .bind(step)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected()
+                > 0;
+            if !enabled {
+                return Ok(false);
+            }
+
+            sqlx::query("DELETE FROM two_factor_recovery_codes WHERE user_id = $1")
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query(
+                "INSERT INTO two_factor_recovery_codes (user_id, code_hash)
+                 SELECT $1, code_hash FROM UNNEST($2::TEXT[]) AS c(code_hash)",
+            )
+            .bind(user_id)
+            .bind(recovery_code_hashes)
+            .execute(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            Ok(true)
+        })
+        .await
+    }
+
+    // Record the use of the code of `step`, returns false when it or a later one was
+    // already used so a code works only once
+    pub async fn use_step(&self, user_id: i64, step: i64) -> Result<bool, Error> {
+        observe_query("two_factor.use_step", async move {
+            let result = sqlx::query(
+                "UPDATE user_two_factor SET last_used_step = $2
+                 WHERE user_id = $1 AND enabled_at IS NOT NULL
+                   AND (last_used_step IS NULL OR last_used_step < $2)",
+            )
+            .bind(to_db_id(user_id)?)
+            .bind(step)
+            .execute(&self.pool)
+            .await?;
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    // Consume a recovery code of the user, returns false for an unknown or used code
+    pub async fn use_recovery_code(&self, user_id: i64, code_hash: &str) -> Result<bool, Error> {
+        observe_query("two_factor.use_recovery_code", async move {
+            let result = sqlx::query(
+                "DELETE FROM two_factor_recovery_codes WHERE user_id = $1 AND code_hash = $2",
+            )
+            .bind(to_db_id(user_id)?)
+            .bind(code_hash)
+            .execute(&self.pool)
+            .await?;
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    // Store the pre-auth token of a login waiting for its second factor, dropping the
+    // expired ones
+    pub async fn create_challenge(
+        &self,
+        user_id: i64,
+        first_factor: &str,
+        token_hash: &str,
+        expires_at: OffsetDateTime,
+    ) -> Result<(), Error> {
+        observe_query("two_factor.create_challenge", async move {
+            let mut tx = self.pool.begin().await?;
+            sqlx::query("DELETE FROM two_factor_challenges WHERE expires_at <= NOW()")
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query(
+                "INSERT INTO two_factor_challenges (token_hash, user_id, first_factor, expires_at)
+                 VALUES ($1, $2, $3, $4)",
+            )
+            .bind(token_hash)
+            .bind(to_db_id(user_id)?)
+            .bind(first_factor)
+            .bind(expires_at)
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await
+        })
+        .await
+    }
+
+    // Count an attempt on a pre-auth token, returns its user and the method of the first
+    // factor while the token is unexpired and has attempts left
+    pub async fn attempt_challenge(
+        &self,
+        token_hash: &str,
+        max_attempts: i32,
+    ) -> Result<Option<(i64, String)>, Error> {
+        observe_query("two_factor.attempt_challenge", async move {
+            let challenge = sqlx::query_as::<_, (i32, String)>(
+                "UPDATE two_factor_challenges SET attempts = attempts + 1
+                 WHERE token_hash = $1 AND expires_at > NOW() AND attempts < $2
+                 RETURNING user_id, first_factor",
+            )
+            .bind(token_hash)
+            .bind(max_attempts)
+            .fetch_optional(&self.pool)
+            .await?;
+            Ok(challenge.map(|(user_id, first_factor)| (i64::from(user_id), first_factor)))
+        })
+        .await
+    }
+
+    // Drop a pre-auth token once exchanged
+    pub async fn delete_challenge(&self, token_hash: &str) -> Result<(), Error> {
+        observe_query("two_factor.delete_challenge", async move {
+            sqlx::query("DELETE FROM two_factor_challenges WHERE token_hash = $1")
+                .bind(token_hash)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+}
@@ -0,0 +1,158 @@
+//! #`TwoFactor` Routes
+//! This module defines the HTTP routes for the TOTP two-factor authentication.
+
+use axum::routing::post;
+use axum::{
+    extract::State,
+    http::{header::USER_AGENT, HeaderMap, StatusCode},
+    response::IntoResponse,
+    Router,
+};
+use axum_extra::extract::CookieJar;
+
+use crate::auth::{cookie::cookie_login, Claims};
+use crate::modules::audit::repository::AuditRepository;
+use crate::modules::audit::service::AuditService;
+use crate::modules::common::{respond, ErrorResponse};
+use crate::modules::session::repository::SessionRepository;
+use crate::modules::session::service::{session_device, SessionService};
+use crate::modules::two_factor::interfaces::{
+    EnableTwoFactorResponse, RecoveryCodesResponse, TwoFactorCodeRequest, TwoFactorLoginRequest,
+};
+use crate::modules::two_factor::repository::TwoFactorRepository;
+use crate::modules::two_factor::service::TwoFactorService;
+use crate::modules::user::interfaces::LoginUserResponse;
+use crate::utils::client_ip::ClientIp;
+use crate::utils::json::Json;
+use crate::AppState;
+
+// Creates and returns the two-factor routes
+pub fn two_factor_routes() -> Router<AppState> {
+    Router::new()
+        .route("/auth/2fa/enable", post(enable_two_factor_route))
+        .route("/auth/2fa/verify", post(verify_two_factor_route))
+        .route("/auth/2fa/login", post(two_factor_login_route))
+}
+
+fn two_factor_service(app_state: &AppState) -> TwoFactorService {
+    TwoFactorService::new(
+        TwoFactorRepository::new(app_state.db_pool.clone()),
+        SessionService::new(
+            SessionRepository::new(app_state.db_pool.clone()),
+            AuditService::new(AuditRepository::new(app_state.db_pool.clone())),
+            app_state.login_alerts.clone(),
+        ),
+    )
+}
+
+// Enable Two-Factor Route
+#[utoipa::path(
+    post,
+    path = "/auth/2fa/enable",
+    tag = "Two-Factor Authentication",
+    responses(
+        (status = 200, description = "Secret to register in an authenticator app, enabled once verified", body = EnableTwoFactorResponse),
+        (status = 409, description = "Two-factor authentication already enabled", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn enable_two_factor_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        two_factor_service(&app_state).enable(claims.user_id).await,
+    )
+}
+
+// Verify Two-Factor Route
+#[utoipa::path(
+    post,
+    path = "/auth/2fa/verify",
+    tag = "Two-Factor Authentication",
+    request_body = TwoFactorCodeRequest,
+    responses(
+        (status = 200, description = "Two-factor authentication enabled, the recovery codes are only returned once", body = RecoveryCodesResponse),
+        (status = 400, description = "Invalid code", body = ErrorResponse),
+        (status = 409, description = "No pending setup, or already enabled", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn verify_two_factor_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Json(code_request): Json<TwoFactorCodeRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        two_factor_service(&app_state)
+            .verify(claims.user_id, code_request)
+            .await,
+    )
+}
+
+// Two-Factor Login Route
+#[utoipa::path(
+    post,
+    path = "/auth/2fa/login",
+    tag = "Two-Factor Authentication",
+    request_body = TwoFactorLoginRequest,
+    responses(
+        (status = 200, description = "User logged in, with `cookie` the session is set in an HttpOnly cookie and the body carries its CSRF token", body = LoginUserResponse),
+        (status = 400, description = "Missing token or code", body = ErrorResponse),
+        (status = 401, description = "Invalid code or expired login token", body = ErrorResponse),
+        (status = 429, description = "Too many attempts", body = ErrorResponse)
+    )
+)]
+pub async fn two_factor_login_route(
+    State(app_state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    headers: HeaderMap,
+    jar: CookieJar,
+    Json(login_request): Json<TwoFactorLoginRequest>,
+) -> impl IntoResponse {
+    let cookie = login_request.cookie.unwrap_or(false);
+    let device = session_device(
+        headers
+            .get(USER_AGENT)
+            .and_then(|value| value.to_str().ok()),
+        client_ip,
+    );
+
+    match two_factor_service(&app_state)
+        .login(
+            login_request,
+            &device,
+            &app_state.encoding_key,
+            app_state.config.session.duration_minutes,
+        )
+        .await
+    {
+        Ok(response) if cookie => {
+            let (jar, response) = cookie_login(jar, response, &app_state);
+            (StatusCode::OK, jar, Json(response)).into_response()
+        }
+        Ok(response) => (StatusCode::OK, Json(response)).into_response(),
+        Err(error) => {
+            tracing::warn!("Two-factor login failed from {}", client_ip);
+            error.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_two_factor_routes_creation() {
+        let _routes = two_factor_routes();
+        assert!(true);
+    }
+}
@@ -0,0 +1,324 @@
+//! # `TwoFactor` Service
+//!
+//! This module contains the bussiness logic for the TOTP two-factor authentication.
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use jsonwebtoken::EncodingKey;
+use time::{Duration, OffsetDateTime};
+
+use crate::auth::{AMR_MULTI_FACTOR, AMR_OTP, AMR_RECOVERY_CODE};
+use crate::telemetry::{record_login, LoginOutcome};
+use crate::{
+    modules::{
+        common::AppError,
+        session::{interfaces::SessionDevice, service::SessionService},
+        two_factor::{
+            interfaces::{
+                EnableTwoFactorResponse, RecoveryCodesResponse, TwoFactorCodeRequest,
+                TwoFactorLoginRequest,
+            },
+            repository::TwoFactorRepository,
+        },
+        user::interfaces::LoginUserResponse,
+    },
+    utils::{
+        token::{generate_random_token, hash_token},
+        totp::{base32_encode, generate_secret, otpauth_uri, verify_code},
+    },
+};
+
+// Issuer shown by the authenticator apps
+const TOTP_ISSUER: &str = "Todo App";
+// Recovery codes handed out when enabling two-factor authentication
+const RECOVERY_CODES: usize = 10;
+// Random bytes of a recovery code
+const RECOVERY_CODE_BYTES: usize = 5;
+// Lifetime of a pre-auth token
+const CHALLENGE_MINUTES: i64 = 5;
+// Codes tried on a pre-auth token before it is refused
+const MAX_CHALLENGE_ATTEMPTS: i32 = 5;
+
+// Generate a recovery code, e.g. `3f9a1-c07b2`
+fn generate_recovery_code() -> String {
+    let mut bytes = [0u8; RECOVERY_CODE_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    let code = hex::encode(bytes);
+    let (first, second) = code.split_at(code.len() / 2);
+    format!("{first}-{second}")
+}
+
+// Recovery code as hashed, ignoring case, dashes and spaces
+fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect::<String>()
+        .to_lowercase()
+}
+
+pub struct TwoFactorService {
+    two_factor_repository: TwoFactorRepository,
+    session_service: SessionService,
+}
+
+impl TwoFactorService {
+    pub const fn new(
+        two_factor_repository: TwoFactorRepository,
+        session_service: SessionService,
+    ) -> Self {
+        Self {
+            two_factor_repository,
+            session_service,
+        }
+    }
+
+    // Start the setup with a new secret, enabled once confirmed with a code
+    pub async fn enable(&self, user_id: i64) -> Result<EnableTwoFactorResponse, AppError> {
+        let secret = generate_secret();
+
+        match self
+            .two_factor_repository
+            .start_setup(user_id, &hex::encode(&secret))
+            .await
+        {
+            Ok(Some(username)) => Ok(EnableTwoFactorResponse {
+                secret: base32_encode(&secret),
+                otpauth_uri: otpauth_uri(TOTP_ISSUER, &username, &secret),
+                message: "Confirm the setup with a code of the authenticator app".to_string(),
+            }),
+            Ok(None) => Err(AppError::Conflict(
+                "Two-factor authentication already enabled".to_string(),
+            )),
+            Err(e) => {
+                tracing::warn!("Error starting two-factor setup: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Confirm the pending setup with a code, enabling it and handing out recovery codes
+    pub async fn verify(
+        &self,
+        user_id: i64,
+        code_request: TwoFactorCodeRequest,
+    ) -> Result<RecoveryCodesResponse, AppError> {
+        let Some(code) = code_request.code.filter(|code| !code.trim().is_empty()) else {
+            return Err(AppError::Validation(
+                "Missing required fields: code".to_string(),
+            ));
+        };
+
+        let setup = match self.two_factor_repository.fetch_setup(user_id).await {
+            Ok(Some(setup)) if setup.enabled_at.is_none() => setup,
+            Ok(Some(_)) => {
+                return Err(AppError::Conflict(
+                    "Two-factor authentication already enabled".to_string(),
+                ))
+            }
+            Ok(None) => {
+                return Err(AppError::Conflict(
+                    "Two-factor authentication setup not started".to_string(),
+                ))
+            }
+            Err(e) => {
+                tracing::warn!("Error fetching two-factor setup: {}", e);
+                return Err(AppError::Database(e));
+            }
+        };
+
+        let step = hex::decode(&setup.secret)
+            .ok()
+            .and_then(|secret| {
+                verify_code(&secret, &code, OffsetDateTime::now_utc().unix_timestamp())
+            })
+            .ok_or_else(|| AppError::Validation("Invalid code".to_string()))?;
+
+        let recovery_codes: Vec<String> = (0..RECOVERY_CODES)
+            .map(|_| generate_recovery_code())
+            .collect();
+        let hashes: Vec<String> = recovery_codes
+            .iter()
+            .map(|code| hash_token(&normalize_recovery_code(code)))
+            .collect();
+
+        match self
+            .two_factor_repository
+            .enable(user_id, step, &hashes)
+            .await
+        {
+            Ok(true) => Ok(RecoveryCodesResponse {
+                recovery_codes,
+                message: "Two-factor authentication enabled, keep the recovery codes safe"
+                    .to_string(),
+            }),
+            Ok(false) => Err(AppError::Validation("Invalid code".to_string())),
+            Err(e) => {
+                tracing::warn!("Error enabling two-factor authentication: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Pre-auth token of a login with a valid first factor, `None` when the user has no
+    // second factor and can be logged in right away
+    pub async fn start_challenge(
+        &self,
+        user_id: i64,
+        first_factor: &str,
+    ) -> Result<Option<String>, AppError> {
+        let enabled = self
+            .two_factor_repository
+            .is_enabled(user_id)
+            .await
+            .map_err(|e| {
+                tracing::warn!("Error checking two-factor authentication: {}", e);
+                AppError::Internal("Failed to check two-factor authentication".to_string())
+            })?;
+        if !enabled {
+            return Ok(None);
+        }
+
+        let token = generate_random_token();
+        let expires_at = OffsetDateTime::now_utc() + Duration::minutes(CHALLENGE_MINUTES);
+        match self
+            .two_factor_repository
+            .create_challenge(user_id, first_factor, &hash_token(&token), expires_at)
+            .await
+        {
+            Ok(()) => Ok(Some(token)),
+            Err(e) => {
+                tracing::warn!("Error creating two-factor challenge: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Method the code authenticates with, a TOTP code or a recovery code, `None` when it
+    // is neither
+    async fn check_code(
+        &self,
+        user_id: i64,
+        secret: &str,
+        code: &str,
+    ) -> Result<Option<&'static str>, sqlx::Error> {
+        let step = hex::decode(secret).ok().and_then(|secret| {
+            verify_code(&secret, code, OffsetDateTime::now_utc().unix_timestamp())
+        });
+        if let Some(step) = step {
+            let unused = self.two_factor_repository.use_step(user_id, step).await?;
+            return Ok(unused.then_some(AMR_OTP));
+        }
+
+        let recovered = self
+            .two_factor_repository
+            .use_recovery_code(user_id, &hash_token(&normalize_recovery_code(code)))
+            .await?;
+        Ok(recovered.then_some(AMR_RECOVERY_CODE))
+    }
+
+    // Exchange a pre-auth token and a code for the session token
+    pub async fn login(
+        &self,
+        login_request: TwoFactorLoginRequest,
+        device: &SessionDevice,
+        encoding_key: &EncodingKey,
+        session_duration: i64,
+    ) -> Result<LoginUserResponse, AppError> {
+        let (Some(token), Some(code)) = (login_request.token, login_request.code) else {
+            record_login(LoginOutcome::MissingFields);
+            return Err(AppError::Validation(
+                "Missing required fields: token, code".to_string(),
+            ));
+        };
+        let token_hash = hash_token(token.trim());
+
+        let invalid = || {
+            record_login(LoginOutcome::InvalidSecondFactor);
+            AppError::Unauthorized("Invalid code or expired login token".to_string())
+        };
+        let (user_id, first_factor) = match self
+            .two_factor_repository
+            .attempt_challenge(&token_hash, MAX_CHALLENGE_ATTEMPTS)
+            .await
+        {
+            Ok(Some(challenge)) => challenge,
+            Ok(None) => return Err(invalid()),
+            Err(e) => {
+                tracing::warn!("Error checking two-factor challenge: {}", e);
+                record_login(LoginOutcome::Error);
+                return Err(AppError::Database(e));
+            }
+        };
+
+        let checked = match self.two_factor_repository.fetch_setup(user_id).await {
+            Ok(Some(setup)) => self.check_code(user_id, &setup.secret, &code).await,
+            Ok(None) => Ok(None),
+            Err(e) => Err(e),
+        };
+        let method = match checked {
+            Ok(Some(method)) => method,
+            Ok(None) => {
+                self.session_service
+                    .record_failed_login(Some(user_id), device, "invalid second factor")
+                    .await;
+                return Err(invalid());
+            }
+            Err(e) => {
+                tracing::warn!("Error checking two-factor code: {}", e);
+                record_login(LoginOutcome::Error);
+                return Err(AppError::Database(e));
+            }
+        };
+
+        if let Err(e) = self
+            .two_factor_repository
+            .delete_challenge(&token_hash)
+            .await
+        {
+            tracing::warn!("Error deleting two-factor challenge: {}", e);
+        }
+
+        let amr = vec![
+            first_factor,
+            method.to_string(),
+            AMR_MULTI_FACTOR.to_string(),
+        ];
+        let token = self
+            .session_service
+            .open_session(user_id, device, amr, encoding_key, session_duration)
+            .await
+            .map_err(|e| {
+                record_login(LoginOutcome::Error);
+                e
+            })?;
+
+        record_login(LoginOutcome::Success);
+        Ok(LoginUserResponse {
+            token,
+            message: "User logged in".to_string(),
+            two_factor_required: false,
+            csrf_token: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_recovery_code() {
+        let code = generate_recovery_code();
+        assert_eq!(code.len(), RECOVERY_CODE_BYTES * 2 + 1);
+        assert_eq!(code.chars().nth(RECOVERY_CODE_BYTES), Some('-'));
+        assert_ne!(generate_recovery_code(), generate_recovery_code());
+    }
+
+    #[test]
+    fn test_normalize_recovery_code() {
+        assert_eq!(normalize_recovery_code(" 3F9A1-c07b2 "), "3f9a1c07b2");
+        assert_eq!(
+            normalize_recovery_code("3f9a1c07b2"),
+            normalize_recovery_code("3F9A1 C07B2")
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! # Password Hash Audit
+//! Background scan of the stored password hashes, at startup then daily. Accounts whose
+//! hash can't be verified, like the values left by the old signup, must reset their
+//! password on their next login. Outdated hashes are only counted, they are replaced when
+//! their user logs in. The counts of each scan are shown to the administrators.
+
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    modules::user::{
+        interfaces::{PasswordHashCounts, PasswordHashRow},
+        repository::UserRepository,
+    },
+    utils::password::{password_hash_status, PasswordHashStatus},
+    workers::{run_exclusive, WorkerTask},
+};
+
+// Delay between two scans
+const AUDIT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
+// Hashes read per query
+const AUDIT_BATCH: i64 = 500;
+
+// Advisory lock taken by the instance scanning the hashes
+const AUDIT_LOCK: &str = "password_hash_audit";
+
+// Count the hashes of a page by state, returns the users with a malformed hash
+fn tally(rows: &[PasswordHashRow], counts: &mut PasswordHashCounts) -> Vec<i32> {
+    let mut malformed = Vec::new();
+    for row in rows {
+        match password_hash_status(&row.password) {
+            PasswordHashStatus::Current => counts.current += 1,
+            PasswordHashStatus::Outdated => counts.outdated += 1,
+            PasswordHashStatus::Malformed => {
+                counts.malformed += 1;
+                malformed.push(row.id);
+            }
+        }
+    }
+    malformed
+}
+
+// Scan every stored hash, flagging the accounts to reset and recording the counts.
+// Returns the counts and how many accounts were newly flagged.
+async fn audit_hashes(
+    user_repository: &UserRepository,
+) -> Result<(PasswordHashCounts, u64), sqlx::Error> {
+    let mut counts = PasswordHashCounts::default();
+    let mut flagged = 0;
+    let mut after_id = 0;
+    loop {
+        let rows = user_repository
+            .list_password_hashes(after_id, AUDIT_BATCH)
+            .await?;
+        let malformed = tally(&rows, &mut counts);
+        if !malformed.is_empty() {
+            flagged += user_repository.require_password_reset(&malformed).await?;
+        }
+        let Some(last) = rows.last() else {
+            break;
+        };
+        after_id = last.id;
+        if i64::try_from(rows.len()).unwrap_or(0) < AUDIT_BATCH {
+            break;
+        }
+    }
+    user_repository.record_password_hash_audit(counts).await?;
+    Ok((counts, flagged))
+}
+
+// Worker scanning the password hashes every day, starting right away. Database errors
+// are logged and retried on the next scan.
+pub fn password_audit_worker(pool: Pool<Postgres>, user_repository: UserRepository) -> WorkerTask {
+    Box::pin(async move {
+        let mut interval = tokio::time::interval(AUDIT_INTERVAL);
+        loop {
+            interval.tick().await;
+            let job = audit_hashes(&user_repository);
+            match run_exclusive(&pool, AUDIT_LOCK, job).await {
+                Ok(Some(Ok((counts, flagged)))) => {
+                    if counts.outdated > 0 || counts.malformed > 0 {
+                        tracing::warn!(
+                            "Password hashes: {} outdated, {} malformed, {} accounts newly \
+                             required to reset their password",
+                            counts.outdated,
+                            counts.malformed,
+                            flagged
+                        );
+                    }
+                }
+                Ok(None) => {}
+                Ok(Some(Err(e))) => tracing::warn!("Error auditing password hashes: {}", e),
+                Err(e) => tracing::warn!("Error locking password hash audit: {}", e),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::utils::password::hash_password;
+
+    #[test]
+    fn test_tally() {
+        let row = |id, password: &str| PasswordHashRow {
+            id,
+            password: password.to_string(),
+        };
+        let rows = vec![
+            row(1, &hash_password("TestPassword123!").unwrap()),
+            row(2, "TestPassword123!"),
+            row(3, ""),
+        ];
+
+        let mut counts = PasswordHashCounts::default();
+        assert_eq!(tally(&rows, &mut counts), vec![2, 3]);
+        assert_eq!(counts.current, 1);
+        assert_eq!(counts.outdated, 0);
+        assert_eq!(counts.malformed, 2);
+        assert_eq!(counts.total(), 3);
+    }
+}
@@ -2,7 +2,10 @@
 //! This module defines the data structures from Users module
 
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use time::OffsetDateTime;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::utils::dates::rfc3339_option;
 
 #[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
 pub struct ValidatedUserSignUp {
@@ -28,6 +31,22 @@ pub struct UserSignUp {
     pub fone: Option<String>,
     // User password
     pub password: Option<String>,
+    // Invite code, required when the instance signup is invite only
+    pub invite_code: Option<String>,
+    // Token of a guest invitation sent to the email, required to convert its guest account
+    pub invitation_token: Option<String>,
+}
+
+// Result of a signup insert
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignupOutcome {
+    Created(i32),
+    // The email belongs to a full account
+    EmailTaken,
+    // The invite code is unknown, expired or used up
+    InvalidInviteCode,
+    // The email belongs to a guest account, converted only with a valid invitation token
+    InvitationRequired,
 }
 
 #[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
@@ -42,6 +61,9 @@ pub struct LoginUserRequest {
     pub username: Option<String>,
     // User password
     pub password: Option<String>,
+    // Keep the session in an `HttpOnly` cookie instead of returning its token, for the
+    // browser frontends
+    pub cookie: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
@@ -54,10 +76,17 @@ pub struct ValidatedLoginUserRequest {
 
 #[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
 pub struct LoginUserResponse {
-    // Token for authentications
+    // Token for authentications, a pre-auth token when a second factor is required
     pub token: String,
     // Message for authentication
     pub message: String,
+    // Whether the token must be exchanged with a code on /auth/2fa/login
+    #[serde(default)]
+    pub two_factor_required: bool,
+    // Token to send in the `X-CSRF-Token` header with the session cookie, the token of
+    // the session is then left empty
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub csrf_token: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
@@ -66,6 +95,8 @@ pub struct GetUserForLoginDb {
     pub password: String,
     // Message for authentication
     pub id: i64,
+    // The stored hash can't be verified, the password must be reset
+    pub password_reset_required: bool,
 }
 
 // Fetch User Data
@@ -76,16 +107,37 @@ pub struct FetchUserResponse {
     pub surname: Option<String>,
     pub email: String,
     pub fone: Option<String>,
-    pub created_at: Option<String>,
-    pub updated_at: Option<String>,
+    #[serde(with = "rfc3339_option")]
+    pub created_at: Option<OffsetDateTime>,
+    #[serde(with = "rfc3339_option")]
+    pub updated_at: Option<OffsetDateTime>,
     // Check if user is active
     pub active: bool,
     // User activation date
-    pub activated_at: Option<String>,
+    #[serde(with = "rfc3339_option")]
+    pub activated_at: Option<OffsetDateTime>,
+}
+
+impl FetchUserResponse {
+    // Version of the user data, its last update time in microseconds
+    pub fn version(&self) -> i64 {
+        self.updated_at.or(self.created_at).map_or(0, user_version)
+    }
+}
+
+// Version of the user data last updated at `updated_at`, Postgres keeps microseconds
+pub fn user_version(updated_at: OffsetDateTime) -> i64 {
+    i64::try_from(updated_at.unix_timestamp_nanos() / 1000).unwrap_or(i64::MAX)
+}
+
+// Update time of a version of the user data, `None` for versions never given out
+pub fn user_version_time(version: i64) -> Option<OffsetDateTime> {
+    OffsetDateTime::from_unix_timestamp_nanos(i128::from(version) * 1000).ok()
 }
 
 #[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
 pub struct UpdateUserRequest {
+    // Omitted fields are kept, an empty string clears the field
     pub name: Option<String>,
     pub surname: Option<String>,
     pub fone: Option<String>,
@@ -102,10 +154,83 @@ pub struct UpdateUserResponse {
     pub message: String,
 }
 
+// Query parameters of the email verification link
+#[derive(Deserialize, IntoParams, Clone, Debug)]
+#[into_params(parameter_in = Query)]
+pub struct VerifyEmailQuery {
+    /// Verification token sent by email
+    pub token: Option<String>,
+}
+
+// Query parameters of the availability check, at least one of them is required
+#[derive(Deserialize, IntoParams, Clone, Debug, Default)]
+#[into_params(parameter_in = Query)]
+pub struct AvailabilityQuery {
+    /// Username to check
+    pub username: Option<String>,
+    /// Email to check
+    pub email: Option<String>,
+}
+
+// Whether the asked username and email are free, `None` for the ones not asked
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, PartialEq, Eq)]
+pub struct AvailabilityResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<bool>,
+}
+
+// User an email verification is sent to
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct VerificationTargetRow {
+    pub username: String,
+    pub email: String,
+    pub verified: bool,
+    // Creation date of the pending verification
+    pub last_sent_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ResetPasswordRequest {
+    // Reset token sent by email
+    pub token: Option<String>,
+    pub new_password: Option<String>,
+}
+
+// User a password reset link is sent to
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct PasswordResetTargetRow {
+    pub username: String,
+    pub email: String,
+}
+
+// Stored password hash of a user, read by the hash audit
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct PasswordHashRow {
+    pub id: i32,
+    pub password: String,
+}
+
+// Stored password hashes of a scan by state
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PasswordHashCounts {
+    pub current: i32,
+    pub outdated: i32,
+    pub malformed: i32,
+}
+
+impl PasswordHashCounts {
+    pub const fn total(&self) -> i32 {
+        self.current + self.outdated + self.malformed
+    }
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
+    use time::macros::datetime;
 
     #[test]
     fn test_user_signup_serialization() {
@@ -116,6 +241,8 @@ mod tests {
             email: Some("test@example.com".to_string()),
             fone: Some("1234567890".to_string()),
             password: Some("password123".to_string()),
+            invite_code: None,
+            invitation_token: None,
         };
 
         let json = serde_json::to_string(&signup).unwrap();
@@ -149,15 +276,19 @@ mod tests {
             surname: Some("User".to_string()),
             email: "test@example.com".to_string(),
             fone: Some("1234567890".to_string()),
-            created_at: Some("2023-01-01T00:00:00Z".to_string()),
+            created_at: Some(datetime!(2023-01-01 0:00 UTC)),
             updated_at: None,
             active: true,
-            activated_at: Some("2023-01-01T00:00:00Z".to_string()),
+            activated_at: Some(datetime!(2023-01-01 0:00 UTC)),
         };
 
         assert_eq!(response.username, "testuser");
         assert_eq!(response.email, "test@example.com");
         assert!(response.active);
+
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["created_at"], "2023-01-01T00:00:00Z");
+        assert!(json["updated_at"].is_null());
     }
 
     #[test]
@@ -198,4 +329,13 @@ mod tests {
         assert_eq!(validated.username, "testuser");
         assert_eq!(validated.email, "test@example.com");
     }
+
+    #[test]
+    fn test_user_version() {
+        let updated_at = datetime!(2025-03-01 12:00:00.123456 UTC);
+        let version = user_version(updated_at);
+        assert_eq!(version, 1_740_830_400_123_456);
+        assert_eq!(user_version_time(version).unwrap(), updated_at);
+        assert!(user_version_time(i64::MAX).is_none());
+    }
 }
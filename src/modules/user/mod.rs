@@ -1,9 +1,11 @@
 //! # `User` Mod
 //! User imports for the user module
 
+pub mod hash_audit;
 pub mod interfaces;
 pub mod repository;
 pub mod routes;
 pub mod service;
+pub mod signup;
 
 pub use routes::user_routes;
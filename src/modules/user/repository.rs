@@ -1,9 +1,15 @@
 //! # `User` Repository
 //! This module defines the user repository for user operations.
 
-use sqlx::{Error, Pool, Postgres};
+use sqlx::{Error, PgConnection, Pool, Postgres};
+use time::OffsetDateTime;
 
-use crate::modules::user::interfaces::{FetchUserResponse, GetUserForLoginDb, ValidatedUserSignUp};
+use crate::modules::common::DbTransaction;
+use crate::modules::user::interfaces::{
+    FetchUserResponse, GetUserForLoginDb, PasswordHashCounts, PasswordHashRow,
+    PasswordResetTargetRow, SignupOutcome, ValidatedUserSignUp, VerificationTargetRow,
+};
+use crate::telemetry::observe_query;
 
 pub struct UserRepository {
     pool: Pool<Postgres>,
@@ -15,124 +21,450 @@ impl UserRepository {
         Self { pool }
     }
 
-    // Method that checks if an username is already taken
-    pub async fn exists_user_by_username(&self, username: &str) -> Result<Option<bool>, Error> {
-        let exists = sqlx::query_scalar!(
-            "SELECT EXISTS(SELECT 1 FROM users where username = $1)",
-            username
-        )
-        .fetch_one(&self.pool)
-        .await?;
-
-        Ok(exists)
-    }
-
-    // Method that checks if an email is already taken
-    pub async fn exists_user_by_email(&self, email: &str) -> Result<Option<bool>, Error> {
-        let exists =
-            sqlx::query_scalar!("SELECT EXISTS(SELECT 1 FROM users where email = $1)", email)
-                .fetch_one(&self.pool)
+    // Start a transaction for the methods taking a connection
+    pub async fn begin(&self) -> Result<DbTransaction, Error> {
+        self.pool.begin().await
+    }
+
+    // Method that creates user in database, converting a guest account with the same email
+    // when the token of one of its invitations is given. The converted account joins the
+    // lists it was invited to as a viewer. A taken username fails with the unique
+    // violation of `users_username_key`. A use of the invite code is consumed along, the
+    // transaction must be rolled back unless the user is created.
+    pub async fn create_user(
+        conn: &mut PgConnection,
+        user_signup: ValidatedUserSignUp,
+        invite_code_hash: Option<&str>,
+        invitation_token_hash: Option<&str>,
+    ) -> Result<SignupOutcome, Error> {
+        observe_query("user.create_user", async move {
+            if let Some(invite_code_hash) = invite_code_hash {
+                let consumed = sqlx::query(
+                    "UPDATE invite_codes SET uses = uses + 1
+                     WHERE code_hash = $1 AND uses < max_uses
+                       AND (expires_at IS NULL OR expires_at > NOW())",
+                )
+                .bind(invite_code_hash)
+                .execute(&mut *conn)
                 .await?;
-
-        Ok(exists)
-    }
-
-    // Method that creates user in database
-    pub async fn create_user(&self, user_signup: ValidatedUserSignUp) -> Result<i32, Error> {
-        let created = sqlx::query_scalar!(
-            "INSERT INTO users (username, email, password, name, surname, fone, active) VALUES ($1, $2, $3, $4, $5, $6, true) RETURNING id",
-            user_signup.username,
-            user_signup.email,
-            user_signup.password,
-            user_signup.name,
-            user_signup.surname,
-            user_signup.fone
-        ).fetch_one(&self.pool).await?;
-
-        Ok(created)
+                if consumed.rows_affected() == 0 {
+                    return Ok(SignupOutcome::InvalidInviteCode);
+                }
+            }
+
+            // Only the owner of the email can convert its guest account, the invitation
+            // token was sent to it
+            let email = user_signup.email.clone();
+            let created = sqlx::query_scalar::<_, i32>(
+                "INSERT INTO users (username, email, password, name, surname, fone, active) \
+                 VALUES ($1, $2, $3, $4, $5, $6, true) \
+                 ON CONFLICT (email) DO UPDATE SET username = EXCLUDED.username, \
+                 password = EXCLUDED.password, name = EXCLUDED.name, \
+                 surname = EXCLUDED.surname, fone = EXCLUDED.fone, active = true, \
+                 is_guest = false, guest_expires_at = NULL \
+                 WHERE users.is_guest AND EXISTS( \
+                     SELECT 1 FROM guest_invitations i \
+                     WHERE i.guest_user_id = users.id AND i.token_hash = $7 \
+                       AND i.expires_at > NOW()) \
+                 RETURNING id",
+            )
+            .bind(user_signup.username)
+            .bind(user_signup.email)
+            .bind(user_signup.password)
+            .bind(user_signup.name)
+            .bind(user_signup.surname)
+            .bind(user_signup.fone)
+            .bind(invitation_token_hash)
+            .fetch_optional(&mut *conn)
+            .await?;
+
+            let Some(user_id) = created else {
+                let is_guest =
+                    sqlx::query_scalar::<_, bool>("SELECT is_guest FROM users WHERE email = $1")
+                        .bind(email)
+                        .fetch_optional(&mut *conn)
+                        .await?;
+                return Ok(if is_guest == Some(true) {
+                    SignupOutcome::InvitationRequired
+                } else {
+                    SignupOutcome::EmailTaken
+                });
+            };
+
+            // The lists of a converted guest are shared with the account, new users have
+            // no invitations
+            sqlx::query(
+                "INSERT INTO workspace_members (workspace_id, user_id, role)
+                 SELECT DISTINCT l.workspace_id, i.guest_user_id, 'member'
+                 FROM guest_invitations i JOIN lists l ON l.id = i.list_id
+                 WHERE i.guest_user_id = $1 AND i.expires_at > NOW()
+                 ON CONFLICT (workspace_id, user_id) DO NOTHING",
+            )
+            .bind(user_id)
+            .execute(&mut *conn)
+            .await?;
+            sqlx::query(
+                "INSERT INTO list_members (list_id, user_id, role)
+                 SELECT i.list_id, i.guest_user_id, 'viewer'
+                 FROM guest_invitations i
+                 WHERE i.guest_user_id = $1 AND i.expires_at > NOW()
+                 ON CONFLICT (list_id, user_id) DO NOTHING",
+            )
+            .bind(user_id)
+            .execute(&mut *conn)
+            .await?;
+
+            Ok(SignupOutcome::Created(user_id))
+        })
+        .await
     }
 
     // Get User password
     pub async fn get_user_for_login(&self, username: &str) -> Result<GetUserForLoginDb, Error> {
-        let result = sqlx::query!(
-            "SELECT password, id from users where username = $1",
-            username.to_string()
-        )
-        .fetch_one(&self.pool)
-        .await?;
-
-        Ok(GetUserForLoginDb {
-            password: result.password,
-            id: i64::from(result.id),
+        observe_query("user.get_user_for_login", async move {
+            let (password, id, password_reset_required) = sqlx::query_as::<_, (String, i32, bool)>(
+                "SELECT password, id, password_reset_required FROM users WHERE username = $1",
+            )
+            .bind(username)
+            .fetch_one(&self.pool)
+            .await?;
+
+            Ok(GetUserForLoginDb {
+                password,
+                id: i64::from(id),
+                password_reset_required,
+            })
         })
+        .await
     }
 
     // Fetch User Data
     pub async fn fetch_user(&self, id: i64) -> Result<FetchUserResponse, Error> {
-        let result = sqlx::query!(
-            "SELECT id, username, name, surname, email, fone, created_at, updated_at, active, activated_at FROM users WHERE id = $1",
-            i32::try_from(id).map_err(|_| Error::Protocol("Invalid user ID".into()))?
-        )
-        .fetch_one(&self.pool)
-        .await?;
-
-        Ok(FetchUserResponse {
-            username: result.username,
-            name: result.name,
-            surname: result.surname,
-            email: result.email,
-            fone: result.fone,
-            created_at: result.created_at.map(|dt| dt.to_string()),
-            updated_at: result.updated_at.map(|dt| dt.to_string()),
-            active: result.active,
-            activated_at: result.activated_at.map(|dt| dt.to_string()),
+        observe_query("user.fetch_user", async move {
+            let result = sqlx::query!(
+                "SELECT id, username, name, surname, email, fone, created_at, updated_at, active, activated_at FROM users WHERE id = $1",
+                i32::try_from(id).map_err(|_| Error::Protocol("Invalid user ID".into()))?
+            )
+            .fetch_one(&self.pool)
+            .await?;
+
+            Ok(FetchUserResponse {
+                username: result.username,
+                name: result.name,
+                surname: result.surname,
+                email: result.email,
+                fone: result.fone,
+                created_at: result.created_at,
+                updated_at: result.updated_at,
+                active: result.active,
+                activated_at: result.activated_at,
+            })
         })
+        .await
     }
 
-    // Update User Data
+    // Update User Data, `None` fields are kept and empty strings clear the field. With
+    // `expected`, only while the user was last updated at that time. Returns the new update
+    // time, `None` when the user changed since `expected`.
     pub async fn update_user(
         &self,
         id: i64,
         name: Option<String>,
         surname: Option<String>,
         fone: Option<String>,
+        expected: Option<OffsetDateTime>,
+    ) -> Result<Option<OffsetDateTime>, Error> {
+        observe_query("user.update_user", async move {
+            sqlx::query_scalar(
+                "UPDATE users SET
+                     name = CASE WHEN $1::TEXT IS NULL THEN name ELSE NULLIF($1, '') END,
+                     surname = CASE WHEN $2::TEXT IS NULL THEN surname ELSE NULLIF($2, '') END,
+                     fone = CASE WHEN $3::TEXT IS NULL THEN fone ELSE NULLIF($3, '') END,
+                     updated_at = NOW()
+                 WHERE id = $4
+                   AND ($5::TIMESTAMPTZ IS NULL OR COALESCE(updated_at, created_at) = $5)
+                 RETURNING updated_at",
+            )
+            .bind(name)
+            .bind(surname)
+            .bind(fone)
+            .bind(i32::try_from(id).map_err(|_| Error::Protocol("Invalid user ID".into()))?)
+            .bind(expected)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Update User Password, a new hash lifts the required reset
+    pub async fn update_password(
+        conn: &mut PgConnection,
+        id: i64,
+        new_password: &str,
     ) -> Result<(), Error> {
-        sqlx::query!(
-            "UPDATE users SET name = $1, surname = $2, fone = $3, updated_at = NOW() WHERE id = $4",
-            name,
-            surname,
-            fone,
-            i32::try_from(id).map_err(|_| Error::Protocol("Invalid user ID".into()))?
-        )
-        .execute(&self.pool)
-        .await?;
+        observe_query("user.update_password", async move {
+            sqlx::query(
+                "UPDATE users SET password = $1, password_reset_required = FALSE,
+                     updated_at = NOW()
+                 WHERE id = $2",
+            )
+            .bind(new_password)
+            .bind(i32::try_from(id).map_err(|_| Error::Protocol("Invalid user ID".into()))?)
+            .execute(conn)
+            .await?;
+
+            Ok(())
+        })
+        .await
+    }
 
-        Ok(())
+    // Delete User
+    pub async fn delete_user(&self, id: i64) -> Result<(), Error> {
+        observe_query("user.delete_user", async move {
+            sqlx::query!(
+                "DELETE FROM users WHERE id = $1",
+                i32::try_from(id).map_err(|_| Error::Protocol("Invalid user ID".into()))?
+            )
+            .execute(&self.pool)
+            .await?;
+
+            Ok(())
+        })
+        .await
     }
 
-    // Update User Password
-    pub async fn update_password(&self, id: i64, new_password: &str) -> Result<(), Error> {
-        sqlx::query!(
-            "UPDATE users SET password = $1, updated_at = NOW() WHERE id = $2",
-            new_password,
-            i32::try_from(id).map_err(|_| Error::Protocol("Invalid user ID".into()))?
-        )
-        .execute(&self.pool)
-        .await?;
+    // Replace the pending email verification of the user, its email is unverified until
+    // the token is used. Also resets the verification of converted guest accounts.
+    pub async fn start_email_verification(
+        conn: &mut PgConnection,
+        user_id: i64,
+        token_hash: &str,
+        expires_at: OffsetDateTime,
+    ) -> Result<(), Error> {
+        observe_query("user.start_email_verification", async move {
+            let user_id =
+                i32::try_from(user_id).map_err(|_| Error::Protocol("Invalid user ID".into()))?;
 
-        Ok(())
+            sqlx::query("UPDATE users SET email_verified_at = NULL WHERE id = $1")
+                .bind(user_id)
+                .execute(&mut *conn)
+                .await?;
+            sqlx::query("DELETE FROM email_verifications WHERE user_id = $1")
+                .bind(user_id)
+                .execute(&mut *conn)
+                .await?;
+            sqlx::query(
+                "INSERT INTO email_verifications (user_id, token_hash, expires_at) VALUES ($1, $2, $3)",
+            )
+            .bind(user_id)
+            .bind(token_hash)
+            .bind(expires_at)
+            .execute(conn)
+            .await?;
+
+            Ok(())
+        })
+        .await
     }
 
-    // Delete User
-    pub async fn delete_user(&self, id: i64) -> Result<(), Error> {
-        sqlx::query!(
-            "DELETE FROM users WHERE id = $1",
-            i32::try_from(id).map_err(|_| Error::Protocol("Invalid user ID".into()))?
-        )
-        .execute(&self.pool)
-        .await?;
+    // User to send a new verification email to
+    pub async fn fetch_verification_target(
+        &self,
+        user_id: i64,
+    ) -> Result<Option<VerificationTargetRow>, Error> {
+        observe_query("user.fetch_verification_target", async move {
+            sqlx::query_as::<_, VerificationTargetRow>(
+                "SELECT u.username, u.email, u.email_verified_at IS NOT NULL AS verified,
+                        (SELECT MAX(v.created_at) FROM email_verifications v WHERE v.user_id = u.id)
+                            AS last_sent_at
+                 FROM users u WHERE u.id = $1",
+            )
+            .bind(i32::try_from(user_id).map_err(|_| Error::Protocol("Invalid user ID".into()))?)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Use a verification token, verifying the email of its user.
+    // The token is consumed even when expired. Returns false for an unknown or expired token.
+    pub async fn verify_email(&self, token_hash: &str) -> Result<bool, Error> {
+        observe_query("user.verify_email", async move {
+            let verified = sqlx::query_scalar::<_, i32>(
+                "WITH used AS (
+                     DELETE FROM email_verifications WHERE token_hash = $1
+                     RETURNING user_id, expires_at
+                 )
+                 UPDATE users u SET email_verified_at = NOW(), updated_at = NOW()
+                 FROM used WHERE u.id = used.user_id AND used.expires_at > NOW()
+                 RETURNING u.id",
+            )
+            .bind(token_hash)
+            .fetch_optional(&self.pool)
+            .await?;
+
+            Ok(verified.is_some())
+        })
+        .await
+    }
+
+    // Replace the pending password reset of the user, unless one was started less than
+    // `cooldown_seconds` ago. Returns the user to email the link to when replaced.
+    pub async fn start_password_reset(
+        &self,
+        user_id: i64,
+        token_hash: &str,
+        expires_at: OffsetDateTime,
+        cooldown_seconds: i64,
+    ) -> Result<Option<PasswordResetTargetRow>, Error> {
+        observe_query("user.start_password_reset", async move {
+            sqlx::query_as::<_, PasswordResetTargetRow>(
+                "WITH reset AS (
+                     INSERT INTO password_resets (user_id, token_hash, expires_at)
+                     VALUES ($1, $2, $3)
+                     ON CONFLICT (user_id) DO UPDATE SET token_hash = EXCLUDED.token_hash,
+                         expires_at = EXCLUDED.expires_at, created_at = NOW()
+                     WHERE password_resets.created_at < NOW() - make_interval(secs => $4)
+                     RETURNING user_id
+                 )
+                 SELECT u.username, u.email FROM reset JOIN users u ON u.id = reset.user_id",
+            )
+            .bind(i32::try_from(user_id).map_err(|_| Error::Protocol("Invalid user ID".into()))?)
+            .bind(token_hash)
+            .bind(expires_at)
+            .bind(cooldown_seconds)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Use a password reset token, returns its user unless it is unknown or expired
+    pub async fn consume_password_reset(
+        conn: &mut PgConnection,
+        token_hash: &str,
+    ) -> Result<Option<i64>, Error> {
+        observe_query("user.consume_password_reset", async move {
+            let user_id = sqlx::query_scalar::<_, i32>(
+                "WITH used AS (
+                     DELETE FROM password_resets WHERE token_hash = $1
+                     RETURNING user_id, expires_at
+                 )
+                 SELECT user_id FROM used WHERE expires_at > NOW()",
+            )
+            .bind(token_hash)
+            .fetch_optional(conn)
+            .await?;
+
+            Ok(user_id.map(i64::from))
+        })
+        .await
+    }
+
+    // Page of the stored password hashes after the user `after_id`, by id
+    pub async fn list_password_hashes(
+        &self,
+        after_id: i32,
+        limit: i64,
+    ) -> Result<Vec<PasswordHashRow>, Error> {
+        observe_query("user.list_password_hashes", async move {
+            sqlx::query_as::<_, PasswordHashRow>(
+                "SELECT id, password FROM users WHERE id > $1 ORDER BY id LIMIT $2",
+            )
+            .bind(after_id)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Require a password reset from the users, returns how many were not flagged yet
+    pub async fn require_password_reset(&self, user_ids: &[i32]) -> Result<u64, Error> {
+        observe_query("user.require_password_reset", async move {
+            let result = sqlx::query(
+                "UPDATE users SET password_reset_required = TRUE
+                 WHERE id = ANY($1) AND NOT password_reset_required",
+            )
+            .bind(user_ids)
+            .execute(&self.pool)
+            .await?;
+            Ok(result.rows_affected())
+        })
+        .await
+    }
 
-        Ok(())
+    // Store the counts of a password hash scan
+    pub async fn record_password_hash_audit(
+        &self,
+        counts: PasswordHashCounts,
+    ) -> Result<(), Error> {
+        observe_query("user.record_password_hash_audit", async move {
+            sqlx::query(
+                "INSERT INTO password_hash_audits (total, current, outdated, malformed)
+                 VALUES ($1, $2, $3, $4)",
+            )
+            .bind(counts.total())
+            .bind(counts.current)
+            .bind(counts.outdated)
+            .bind(counts.malformed)
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    // Whether the username and the email are used by an account, `false` for the ones
+    // not given. The email of a guest is free, signing up with it converts the guest.
+    pub async fn fetch_taken(
+        &self,
+        username: Option<&str>,
+        email: Option<&str>,
+    ) -> Result<(bool, bool), Error> {
+        observe_query("user.fetch_taken", async move {
+            sqlx::query_as::<_, (bool, bool)>(
+                "SELECT EXISTS(SELECT 1 FROM users WHERE username = $1),
+                        EXISTS(SELECT 1 FROM users WHERE email = $2 AND NOT is_guest)",
+            )
+            .bind(username)
+            .bind(email)
+            .fetch_one(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Whether the email of the user is verified, unknown users are reported as verified
+    pub async fn is_email_verified(&self, user_id: i64) -> Result<bool, Error> {
+        observe_query("user.is_email_verified", async move {
+            let verified = sqlx::query_scalar::<_, bool>(
+                "SELECT email_verified_at IS NOT NULL FROM users WHERE id = $1",
+            )
+            .bind(i32::try_from(user_id).map_err(|_| Error::Protocol("Invalid user ID".into()))?)
+            .fetch_optional(&self.pool)
+            .await?;
+
+            Ok(verified.unwrap_or(true))
+        })
+        .await
+    }
+
+    // Check if the user is an administrator of the instance
+    pub async fn is_admin(&self, user_id: i64) -> Result<bool, Error> {
+        observe_query("user.is_admin", async move {
+            let is_admin =
+                sqlx::query_scalar::<_, bool>("SELECT is_admin FROM users WHERE id = $1")
+                    .bind(
+                        i32::try_from(user_id)
+                            .map_err(|_| Error::Protocol("Invalid user ID".into()))?,
+                    )
+                    .fetch_optional(&self.pool)
+                    .await?;
+
+            Ok(is_admin.unwrap_or(false))
+        })
+        .await
     }
 }
 
@@ -148,6 +480,7 @@ impl UserRepository {
 mod tests {
     use super::*;
     use sqlx::{Pool, Postgres};
+    use time::macros::datetime;
 
     // Mock pool for testing - we can't create a real pool without a database
     fn create_mock_pool() -> Result<Pool<Postgres>, sqlx::Error> {
@@ -191,6 +524,7 @@ mod tests {
         let user_login = GetUserForLoginDb {
             password: "hashed_password".to_string(),
             id: 123,
+            password_reset_required: false,
         };
 
         assert_eq!(user_login.password, "hashed_password");
@@ -205,10 +539,10 @@ mod tests {
             surname: Some("User".to_string()),
             email: "test@example.com".to_string(),
             fone: Some("1234567890".to_string()),
-            created_at: Some("2023-01-01T00:00:00Z".to_string()),
-            updated_at: Some("2023-01-01T00:00:00Z".to_string()),
+            created_at: Some(datetime!(2023-01-01 0:00 UTC)),
+            updated_at: Some(datetime!(2023-01-01 0:00 UTC)),
             active: true,
-            activated_at: Some("2023-01-01T00:00:00Z".to_string()),
+            activated_at: Some(datetime!(2023-01-01 0:00 UTC)),
         };
 
         assert_eq!(user_response.username, "testuser");
@@ -372,10 +706,10 @@ mod tests {
             surname: Some("User".to_string()),
             email: "full@example.com".to_string(),
             fone: Some("1111111111".to_string()),
-            created_at: Some("2023-01-01T00:00:00Z".to_string()),
-            updated_at: Some("2023-01-02T00:00:00Z".to_string()),
+            created_at: Some(datetime!(2023-01-01 0:00 UTC)),
+            updated_at: Some(datetime!(2023-01-02 0:00 UTC)),
             active: true,
-            activated_at: Some("2023-01-01T12:00:00Z".to_string()),
+            activated_at: Some(datetime!(2023-01-01 12:00 UTC)),
         };
 
         assert_eq!(complete_response.username, "fulluser");
@@ -394,11 +728,13 @@ mod tests {
         let user1 = GetUserForLoginDb {
             password: "hash1".to_string(),
             id: 1,
+            password_reset_required: false,
         };
 
         let user2 = GetUserForLoginDb {
             password: "hash2".to_string(),
             id: 2,
+            password_reset_required: true,
         };
 
         assert_ne!(user1.password, user2.password);
@@ -475,10 +811,10 @@ mod tests {
             surname: Some("User".to_string()),
             email: "test@example.com".to_string(),
             fone: Some("1234567890".to_string()),
-            created_at: Some("2023-01-01T00:00:00Z".to_string()),
-            updated_at: Some("2023-01-01T00:00:00Z".to_string()),
+            created_at: Some(datetime!(2023-01-01 0:00 UTC)),
+            updated_at: Some(datetime!(2023-01-01 0:00 UTC)),
             active: true,
-            activated_at: Some("2023-01-01T00:00:00Z".to_string()),
+            activated_at: Some(datetime!(2023-01-01 0:00 UTC)),
         };
 
         assert!(!response.username.is_empty());
@@ -2,18 +2,37 @@
 //! This module defines the HTTP routes for users funciionality.
 
 use axum::routing::{delete, get, post, put};
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json, Router};
+use axum::{
+    extract::{Query, State},
+    http::{header::USER_AGENT, HeaderMap, StatusCode},
+    response::IntoResponse,
+    Router,
+};
+use axum_extra::extract::CookieJar;
 use serde::Serialize;
 use utoipa::ToSchema;
 
-use crate::auth::Claims;
-use crate::modules::common::ErrorResponse;
+use crate::auth::{cookie::cookie_login, Claims};
+use crate::modules::audit::repository::AuditRepository;
+use crate::modules::audit::service::AuditService;
+use crate::modules::common::{
+    required_version, respond, respond_cached, respond_versioned, CacheValidators, ErrorResponse,
+};
+use crate::modules::email::mailer::BackgroundMailer;
+use crate::modules::session::interfaces::SessionDevice;
+use crate::modules::session::repository::SessionRepository;
+use crate::modules::session::service::{session_device, SessionService};
+use crate::modules::two_factor::repository::TwoFactorRepository;
+use crate::modules::two_factor::service::TwoFactorService;
 use crate::modules::user::interfaces::{
-    FetchUserResponse, LoginUserRequest, LoginUserResponse, UpdatePasswordRequest,
-    UpdateUserRequest, UpdateUserResponse, UserSignUp,
+    AvailabilityQuery, AvailabilityResponse, FetchUserResponse, LoginUserRequest,
+    LoginUserResponse, ResetPasswordRequest, UpdatePasswordRequest, UpdateUserRequest,
+    UpdateUserResponse, UserSignUp, VerifyEmailQuery,
 };
 use crate::modules::user::repository::UserRepository;
 use crate::modules::user::service::UserService;
+use crate::utils::client_ip::ClientIp;
+use crate::utils::json::Json;
 use crate::AppState;
 
 #[derive(Serialize, ToSchema)]
@@ -25,11 +44,38 @@ struct Response {
 pub fn user_routes() -> Router<AppState> {
     Router::new()
         .route("/user/signup", post(create_user_route))
+        .route("/user/availability", get(availability_route))
         .route("/user/login", post(login_user_route))
         .route("/user", get(fetch_user_route))
         .route("/user", put(update_user_route))
         .route("/user/password", post(update_password_route))
+        .route("/user/password/reset", post(reset_password_route))
         .route("/user", delete(delete_user_route))
+        .route("/user/verify", get(verify_email_route))
+        .route("/user/verify/resend", post(resend_verification_route))
+}
+
+fn user_service(app_state: &AppState) -> UserService {
+    UserService::new(
+        UserRepository::new(app_state.db_pool.clone()),
+        SessionService::new(
+            SessionRepository::new(app_state.db_pool.clone()),
+            AuditService::new(AuditRepository::new(app_state.db_pool.clone())),
+            app_state.login_alerts.clone(),
+        ),
+        TwoFactorService::new(
+            TwoFactorRepository::new(app_state.db_pool.clone()),
+            SessionService::new(
+                SessionRepository::new(app_state.db_pool.clone()),
+                AuditService::new(AuditRepository::new(app_state.db_pool.clone())),
+                app_state.login_alerts.clone(),
+            ),
+        ),
+        AuditService::new(AuditRepository::new(app_state.db_pool.clone())),
+        BackgroundMailer::new(app_state.mailer.clone(), app_state.tasks.clone()),
+        app_state.config.server.public_url.clone(),
+        app_state.signup_mode,
+    )
 }
 
 // Create User Route
@@ -41,26 +87,23 @@ pub fn user_routes() -> Router<AppState> {
     request_body = UserSignUp,
     responses(
         (status = 201, description = "User signed up successfully", body = Response),
-        (status = 400, description = "Invalid user data", body = ErrorResponse)
+        (status = 400, description = "Invalid user data or invite code, every invalid field is listed in `errors`", body = ErrorResponse),
+        (status = 403, description = "Signup is disabled on this server", body = ErrorResponse),
+        (status = 409, description = "Username or email already exists, the taken field is listed in `errors`", body = ErrorResponse),
+        (status = 500, description = "Failed to create user", body = ErrorResponse),
+        (status = 429, description = "Too many attempts", body = ErrorResponse)
     )
 )]
 pub async fn create_user_route(
     State(app_state): State<AppState>,
     Json(user_signup): Json<UserSignUp>,
 ) -> impl IntoResponse {
-    let user_repository = UserRepository::new(app_state.db_pool.clone());
-    let user_service = UserService::new(user_repository);
-
-    match user_service.create_user(user_signup).await {
-        Ok(response) => (StatusCode::CREATED, Json(response)).into_response(),
-        Err(error) => (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                message: error.0.message,
-            }),
-        )
-            .into_response(),
-    }
+    let user_service = user_service(&app_state);
+
+    respond(
+        StatusCode::CREATED,
+        user_service.create_user(user_signup).await,
+    )
 }
 
 /// Handler function for login route
@@ -70,36 +113,49 @@ pub async fn create_user_route(
     tag = "Login",
     //request_body = UserSignUp,
     responses(
-        (status = 201, description = "User logged successfully", body = LoginUserResponse),
+        (status = 200, description = "User logged successfully, or a pre-auth token when a second factor is required. With `cookie`, the session is set in an HttpOnly cookie and the body carries the CSRF token to send in `X-CSRF-Token` with the requests changing state", body = LoginUserResponse),
         (status = 401, description = "Not Authorized", body = ErrorResponse),
-        (status = 400, description = "Invalid user data", body = ErrorResponse)
+        (status = 400, description = "Invalid user data", body = ErrorResponse),
+        (status = 403, description = "The password must be reset with the token emailed to the account, code `password_reset_required`", body = ErrorResponse),
+        (status = 429, description = "Too many attempts", body = ErrorResponse),
+        (status = 500, description = "Failed to log in", body = ErrorResponse)
     )
 )]
 pub async fn login_user_route(
     State(app_state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    headers: HeaderMap,
+    jar: CookieJar,
     Json(user_login): Json<LoginUserRequest>,
 ) -> impl IntoResponse {
-    let user_repository = UserRepository::new(app_state.db_pool.clone());
-    let user_service = UserService::new(user_repository);
+    let user_service = user_service(&app_state);
+    let cookie = user_login.cookie.unwrap_or(false);
 
-    tracing::info!("Login attempt");
+    tracing::info!("Login attempt from {}", client_ip);
 
     match user_service
         .login_user(
             user_login,
-            app_state.encoding_key,
-            app_state.session_duration_minutes,
+            session_device(
+                headers
+                    .get(USER_AGENT)
+                    .and_then(|value| value.to_str().ok()),
+                client_ip,
+            ),
+            app_state.encoding_key.clone(),
+            app_state.config.session.duration_minutes,
         )
         .await
     {
-        Ok(response) => (StatusCode::CREATED, Json(response)).into_response(),
-        Err(error) => (
-            StatusCode::UNAUTHORIZED,
-            Json(ErrorResponse {
-                message: error.0.message,
-            }),
-        )
-            .into_response(),
+        Ok(response) if cookie => {
+            let (jar, response) = cookie_login(jar, response, &app_state);
+            (StatusCode::OK, jar, Json(response)).into_response()
+        }
+        Ok(response) => (StatusCode::OK, Json(response)).into_response(),
+        Err(error) => {
+            tracing::warn!("Login failed from {}", client_ip);
+            error.into_response()
+        }
     }
 }
 
@@ -108,8 +164,20 @@ pub async fn login_user_route(
     get,
     path = "/user",
     tag = "User Management",
+    params(
+        ("If-None-Match" = Option<String>, Header, description = "ETag of the cached user, answered with 304 while current"),
+        ("If-Modified-Since" = Option<String>, Header, description = "Last-Modified of the cached user, ignored with If-None-Match")
+    ),
     responses(
-        (status = 200, description = "User fetched successfully", body = FetchUserResponse),
+        (status = 200, description = "User fetched successfully", body = FetchUserResponse,
+            headers(
+                ("ETag" = String, description = "Version of the user, sent back in `If-Match` to update it"),
+                ("Last-Modified" = String, description = "Time of the last update of the user"),
+                ("Cache-Control" = String, description = "private, no-cache")
+            )),
+        (status = 304, description = "Cached user still current"),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 500, description = "Failed to fetch user", body = ErrorResponse)
     ),
     security(
         ("jwt_auth" = [])
@@ -118,22 +186,15 @@ pub async fn login_user_route(
 pub async fn fetch_user_route(
     State(app_state): State<AppState>,
     claims: Claims,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
-    let user_repository = UserRepository::new(app_state.db_pool.clone());
-    let user_service = UserService::new(user_repository);
+    let user_service = user_service(&app_state);
 
     let user_id = claims.user_id;
 
-    match user_service.fetch_user(user_id).await {
-        Ok(user) => (StatusCode::OK, Json(user)).into_response(),
-        Err(error) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                message: error.0.message,
-            }),
-        )
-            .into_response(),
-    }
+    respond_cached(&headers, user_service.fetch_user(user_id).await, |user| {
+        CacheValidators::version(user.version()).modified_at(user.updated_at.or(user.created_at))
+    })
 }
 
 // Update User Route
@@ -141,10 +202,17 @@ pub async fn fetch_user_route(
     put,
     path = "/user",
     tag = "User Management",
+    params(
+        ("If-Match" = String, Header, description = "ETag of the version being changed, `*` for any version")
+    ),
     request_body = UpdateUserRequest,
     responses(
-        (status = 200, description = "User updated successfully", body = UpdateUserResponse),
-        (status = 400, description = "Invalid data", body = ErrorResponse)
+        (status = 200, description = "User updated, fields omitted from the request are kept and empty strings clear them", body = UpdateUserResponse,
+            headers(("ETag" = String, description = "Version of the updated user"))),
+        (status = 400, description = "Invalid data", body = ErrorResponse),
+        (status = 412, description = "User changed since the version in `If-Match`", body = ErrorResponse),
+        (status = 428, description = "Missing `If-Match`", body = ErrorResponse),
+        (status = 500, description = "Failed to update user", body = ErrorResponse)
     ),
     security(
         ("jwt_auth" = [])
@@ -153,23 +221,23 @@ pub async fn fetch_user_route(
 pub async fn update_user_route(
     State(app_state): State<AppState>,
     claims: Claims,
+    headers: HeaderMap,
     Json(update_request): Json<UpdateUserRequest>,
 ) -> impl IntoResponse {
-    let user_repository = UserRepository::new(app_state.db_pool.clone());
-    let user_service = UserService::new(user_repository);
+    let user_service = user_service(&app_state);
+    let version = match required_version::<i64>(&headers) {
+        Ok(version) => version,
+        Err(error) => return error.into_response(),
+    };
 
     match user_service
-        .update_user(claims.user_id, update_request)
+        .update_user(claims.user_id, version, update_request)
         .await
     {
-        Ok(response) => (StatusCode::OK, Json(response)).into_response(),
-        Err(error) => (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                message: error.0.message,
-            }),
-        )
-            .into_response(),
+        Ok((response, version)) => {
+            respond_versioned(StatusCode::OK, Ok(response), |_| version.to_string())
+        }
+        Err(error) => error.into_response(),
     }
 }
 
@@ -181,7 +249,10 @@ pub async fn update_user_route(
     request_body = UpdatePasswordRequest,
     responses(
         (status = 200, description = "Password updated successfully", body = UpdateUserResponse),
-        (status = 400, description = "Invalid password data", body = ErrorResponse)
+        (status = 400, description = "Invalid password data, every invalid field is listed in `errors`", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 429, description = "Too many attempts", body = ErrorResponse),
+        (status = 500, description = "Failed to update password", body = ErrorResponse)
     ),
     security(
         ("jwt_auth" = [])
@@ -190,24 +261,67 @@ pub async fn update_user_route(
 pub async fn update_password_route(
     State(app_state): State<AppState>,
     claims: Claims,
+    device: SessionDevice,
     Json(password_request): Json<UpdatePasswordRequest>,
 ) -> impl IntoResponse {
-    let user_repository = UserRepository::new(app_state.db_pool.clone());
-    let user_service = UserService::new(user_repository);
+    let user_service = user_service(&app_state);
 
-    match user_service
-        .update_password(claims.user_id, password_request)
-        .await
-    {
-        Ok(response) => (StatusCode::OK, Json(response)).into_response(),
-        Err(error) => (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                message: error.0.message,
-            }),
-        )
-            .into_response(),
-    }
+    respond(
+        StatusCode::OK,
+        user_service
+            .update_password(claims.user_id, claims.session_id, password_request, &device)
+            .await,
+    )
+}
+
+// Reset Password Route
+#[utoipa::path(
+    post,
+    path = "/user/password/reset",
+    tag = "User Management",
+    request_body = ResetPasswordRequest,
+    responses(
+        (status = 200, description = "Password reset and every session signed out", body = UpdateUserResponse),
+        (status = 400, description = "Invalid or expired token or invalid password, every invalid field is listed in `errors`", body = ErrorResponse),
+        (status = 429, description = "Too many attempts", body = ErrorResponse),
+        (status = 500, description = "Failed to reset password", body = ErrorResponse)
+    )
+)]
+pub async fn reset_password_route(
+    State(app_state): State<AppState>,
+    device: SessionDevice,
+    Json(reset_request): Json<ResetPasswordRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        user_service(&app_state)
+            .reset_password(reset_request, &device)
+            .await,
+    )
+}
+
+// Availability Route
+#[utoipa::path(
+    get,
+    path = "/user/availability",
+    tag = "SignUp",
+    params(AvailabilityQuery),
+    responses(
+        (status = 200, description = "Whether the asked username and email are free, `true` when they are", body = AvailabilityResponse),
+        (status = 400, description = "Neither username nor email given, or invalid email", body = ErrorResponse),
+        (status = 403, description = "Signup is disabled", body = ErrorResponse),
+        (status = 429, description = "Too many checks", body = ErrorResponse),
+        (status = 500, description = "Failed to check availability", body = ErrorResponse)
+    )
+)]
+pub async fn availability_route(
+    State(app_state): State<AppState>,
+    Query(query): Query<AvailabilityQuery>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        user_service(&app_state).check_availability(query).await,
+    )
 }
 
 // Delete User Route
@@ -226,21 +340,63 @@ pub async fn update_password_route(
 pub async fn delete_user_route(
     State(app_state): State<AppState>,
     claims: Claims,
+    device: SessionDevice,
 ) -> impl IntoResponse {
-    let user_repository = UserRepository::new(app_state.db_pool.clone());
-    let user_service = UserService::new(user_repository);
+    let user_service = user_service(&app_state);
 
-    match user_service.delete_user(claims.user_id).await {
-        Ok(response) => (StatusCode::OK, Json(response)).into_response(),
-        Err(error) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                message: error.0.message,
-            }),
-        )
-            .into_response(),
-    }
+    respond(
+        StatusCode::OK,
+        user_service.delete_user(claims.user_id, &device).await,
+    )
+}
+// Verify Email Route
+#[utoipa::path(
+    get,
+    path = "/user/verify",
+    tag = "User Management",
+    params(VerifyEmailQuery),
+    responses(
+        (status = 200, description = "Email verified", body = UpdateUserResponse),
+        (status = 400, description = "Invalid or expired token", body = ErrorResponse),
+        (status = 500, description = "Failed to verify email", body = ErrorResponse)
+    )
+)]
+pub async fn verify_email_route(
+    State(app_state): State<AppState>,
+    Query(query): Query<VerifyEmailQuery>,
+) -> impl IntoResponse {
+    let user_service = user_service(&app_state);
+
+    respond(StatusCode::OK, user_service.verify_email(query).await)
+}
+
+// Resend Verification Route
+#[utoipa::path(
+    post,
+    path = "/user/verify/resend",
+    tag = "User Management",
+    responses(
+        (status = 200, description = "Verification email sent", body = UpdateUserResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 409, description = "Email already verified or sent recently", body = ErrorResponse),
+        (status = 500, description = "Failed to send verification email", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn resend_verification_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+) -> impl IntoResponse {
+    let user_service = user_service(&app_state);
+
+    respond(
+        StatusCode::OK,
+        user_service.resend_verification(claims.user_id).await,
+    )
 }
+
 #[cfg(test)]
 #[allow(
     clippy::assertions_on_constants,
@@ -296,9 +452,25 @@ mod tests {
         assert!(true);
     }
 
+    #[test]
+    fn test_availability_route_structure() {
+        let _app = Router::new().route("/user/availability", get(availability_route));
+        assert!(true);
+    }
+
+    #[test]
+    fn test_verify_route_structure() {
+        let _app = Router::new()
+            .route("/user/verify", get(verify_email_route))
+            .route("/user/verify/resend", post(resend_verification_route));
+        assert!(true);
+    }
+
     #[test]
     fn test_password_route_structure() {
-        let _app = Router::new().route("/user/password", post(update_password_route));
+        let _app = Router::new()
+            .route("/user/password", post(update_password_route))
+            .route("/user/password/reset", post(reset_password_route));
         assert!(true);
     }
 
@@ -2,129 +2,368 @@
 //!
 //! This module contains the bussiness logic for user operations.
 
-use axum::Json;
+use argon2::password_hash::rand_core::{OsRng, RngCore};
 use email_address::EmailAddress;
 use jsonwebtoken::EncodingKey;
+use sqlx::PgConnection;
+use time::{Duration, OffsetDateTime};
 
+use crate::telemetry::{record_login, LoginOutcome};
 use crate::{
-    auth::generate_token,
+    auth::AMR_PASSWORD,
     modules::{
-        common::ErrorResponse,
+        audit::{
+            interfaces::{AuditAction, NewAuditEntry},
+            service::AuditService,
+        },
+        common::AppError,
+        email::{
+            mailer::BackgroundMailer,
+            templates::{EMAIL_VERIFICATION, PASSWORD_RESET},
+        },
+        session::{
+            interfaces::SessionDevice, repository::SessionRepository, service::SessionService,
+        },
+        two_factor::service::TwoFactorService,
         user::{
             interfaces::{
+                user_version, user_version_time, AvailabilityQuery, AvailabilityResponse,
                 FetchUserResponse, LoginUserRequest, LoginUserResponse, NewUserResponse,
-                UpdatePasswordRequest, UpdateUserRequest, UpdateUserResponse, UserSignUp,
-                ValidatedLoginUserRequest, ValidatedUserSignUp,
+                ResetPasswordRequest, SignupOutcome, UpdatePasswordRequest, UpdateUserRequest,
+                UpdateUserResponse, UserSignUp, ValidatedLoginUserRequest, ValidatedUserSignUp,
+                VerifyEmailQuery,
             },
             repository::UserRepository,
+            signup::SignupMode,
         },
     },
     utils::{
+        dates::format_timestamp,
         fone_validation::validate_fone,
-        password::{hash_password, password_validation, validate_password},
-        required_fields::validate_required_fields,
+        password::{
+            hash_password, password_hash_status, password_validation, validate_password,
+            PasswordHashStatus,
+        },
+        token::{generate_random_token, hash_token},
+        validation::{validate_required_fields, ValidationErrors},
     },
 };
 
+// Codes of the invalid user fields
+const INVALID_EMAIL: &str = "invalid_email";
+const WEAK_PASSWORD: &str = "weak_password";
+const INVALID_FONE: &str = "invalid_fone";
+const TAKEN: &str = "taken";
+const INVALID_INVITE_CODE: &str = "invalid_invite_code";
+const INVALID_INVITATION_TOKEN: &str = "invalid_invitation_token";
+
+// Lifetime of an email verification link
+const VERIFICATION_HOURS: i64 = 24;
+// Delay before another verification email can be requested
+const VERIFICATION_RESEND_SECONDS: i64 = 60;
+// Lifetime of a password reset token
+const PASSWORD_RESET_HOURS: i64 = 1;
+// Delay before another password reset email is sent
+const PASSWORD_RESET_RESEND_SECONDS: i64 = 60;
+// Shortest answer of the availability check, plus up to `AVAILABILITY_JITTER_MS`, so its
+// timing doesn't tell whether an account was found
+const AVAILABILITY_MIN_MS: u64 = 150;
+const AVAILABILITY_JITTER_MS: u64 = 100;
+
+// Conflict of a field already used by another account
+fn taken(field: &str, message: &str) -> AppError {
+    let mut errors = ValidationErrors::default();
+    errors.add(field, TAKEN, message);
+    AppError::FieldConflict(errors)
+}
+
+// Error of a signup insert, the unique violations are a username or email taken meanwhile
+fn signup_error(error: sqlx::Error) -> AppError {
+    let constraint = match &error {
+        sqlx::Error::Database(e) if e.is_unique_violation() => e.constraint(),
+        _ => return AppError::Database(error),
+    };
+    match constraint {
+        Some("users_email_key") => taken("email", "Email already exists"),
+        Some("users_username_key") => taken("username", "Username already exists"),
+        _ => AppError::Database(error),
+    }
+}
+
+// Time the availability check answers after, drawn from `random`
+const fn availability_delay(random: u64) -> std::time::Duration {
+    std::time::Duration::from_millis(AVAILABILITY_MIN_MS + random % (AVAILABILITY_JITTER_MS + 1))
+}
+
+// Store a new verification token of the user in the transaction of the caller, its link
+// is emailed once the transaction is committed
+async fn store_verification(
+    conn: &mut PgConnection,
+    user_id: i64,
+) -> Result<(String, OffsetDateTime), sqlx::Error> {
+    let token = generate_random_token();
+    let expires_at = OffsetDateTime::now_utc() + Duration::hours(VERIFICATION_HOURS);
+    UserRepository::start_email_verification(conn, user_id, &hash_token(&token), expires_at)
+        .await?;
+    Ok((token, expires_at))
+}
+
 pub struct UserService {
     user_repository: UserRepository,
+    session_service: SessionService,
+    two_factor_service: TwoFactorService,
+    audit_service: AuditService,
+    mailer: BackgroundMailer,
+    // Base URL of the verification links
+    public_url: String,
+    signup_mode: SignupMode,
 }
 
 impl UserService {
-    pub const fn new(user_repository: UserRepository) -> Self {
-        Self { user_repository }
+    pub const fn new(
+        user_repository: UserRepository,
+        session_service: SessionService,
+        two_factor_service: TwoFactorService,
+        audit_service: AuditService,
+        mailer: BackgroundMailer,
+        public_url: String,
+        signup_mode: SignupMode,
+    ) -> Self {
+        Self {
+            user_repository,
+            session_service,
+            two_factor_service,
+            audit_service,
+            mailer,
+            public_url,
+            signup_mode,
+        }
     }
 
     // Function that creates an user in the application
-    pub async fn create_user(
-        &self,
-        user_signup: UserSignUp,
-    ) -> Result<NewUserResponse, Json<ErrorResponse>> {
-        // Validate required fields
-        let required_fields = vec!["username", "email", "password", "fone", "name", "surname"];
+    pub async fn create_user(&self, user_signup: UserSignUp) -> Result<NewUserResponse, AppError> {
+        if self.signup_mode == SignupMode::Closed {
+            return Err(AppError::Forbidden(
+                "Signup is disabled on this server".to_string(),
+            ));
+        }
+
+        // Validate required fields and the formats of the given ones, reported together
+        let mut required_fields = vec!["username", "email", "password", "fone", "name", "surname"];
+        if self.signup_mode == SignupMode::Invite {
+            required_fields.push("invite_code");
+        }
+        let format_errors = signup_format_errors(&user_signup);
         let mut validated_user: ValidatedUserSignUp =
             match validate_required_fields(&user_signup, required_fields) {
-                Err(missing) => {
-                    return Err(Json(ErrorResponse::new(format!(
-                        "Missing required fields: {missing}"
-                    ))))
+                Ok(user) => {
+                    format_errors.check()?;
+                    user
+                }
+                Err(mut errors) => {
+                    errors.extend(format_errors);
+                    return Err(AppError::InvalidFields(errors));
                 }
-                Ok(user) => user,
             };
 
-        // Check if username is already taken
-        match self
-            .user_repository
-            .exists_user_by_username(&validated_user.username)
-            .await
+        // Only the hashes of the invite codes are stored
+        let invite_code_hash = match (self.signup_mode, user_signup.invite_code.as_deref()) {
+            (SignupMode::Invite, Some(code)) => Some(hash_token(code.trim())),
+            _ => None,
+        };
+        let invitation_token_hash = user_signup
+            .invitation_token
+            .as_deref()
+            .map(|token| hash_token(token.trim()));
+
+        validated_user.password = hash_password(&validated_user.password)
+            .map_err(|e| AppError::Internal(format!("Password hashing error: {e}")))?;
+        let username = validated_user.username.clone();
+        let email = validated_user.email.clone();
+
+        // The unique constraints decide whether the username and email are free, so two
+        // concurrent signups can't both take them. The user, the invite code use and the
+        // verification token are stored together or not at all.
+        let mut tx = self.user_repository.begin().await?;
+        let user_id = match UserRepository::create_user(
+            &mut tx,
+            validated_user,
+            invite_code_hash.as_deref(),
+            invitation_token_hash.as_deref(),
+        )
+        .await
         {
-            Ok(Some(true)) => return Err(Json(ErrorResponse::new("Username already exists"))),
-            Err(e) => return Err(Json(ErrorResponse::new(format!("Database error: {e}")))),
-            _ => {}
+            Ok(SignupOutcome::Created(user_id)) => i64::from(user_id),
+            Ok(SignupOutcome::EmailTaken) => return Err(taken("email", "Email already exists")),
+            Ok(SignupOutcome::InvalidInviteCode) => {
+                let mut errors = ValidationErrors::default();
+                errors.add(
+                    "invite_code",
+                    INVALID_INVITE_CODE,
+                    "Invite code is invalid, expired or used up",
+                );
+                return Err(AppError::InvalidFields(errors));
+            }
+            Ok(SignupOutcome::InvitationRequired) => {
+                let mut errors = ValidationErrors::default();
+                errors.add(
+                    "invitation_token",
+                    INVALID_INVITATION_TOKEN,
+                    "Email was invited as a guest, sign up from a valid invitation link",
+                );
+                return Err(AppError::InvalidFields(errors));
+            }
+            Err(e) => return Err(signup_error(e)),
+        };
+        let (token, expires_at) = store_verification(&mut tx, user_id).await?;
+        tx.commit().await.map_err(signup_error)?;
+
+        // Another verification email can be requested when this one is lost
+        self.email_verification(&username, &email, &token, expires_at);
+        Ok(NewUserResponse {
+            id: user_id,
+            message: "User created".to_string(),
+        })
+    }
+
+    // Whether the username and email are free for a signup. The answer is delayed to a
+    // fuzzed duration, the clients are throttled per IP by the rate limit middleware.
+    pub async fn check_availability(
+        &self,
+        query: AvailabilityQuery,
+    ) -> Result<AvailabilityResponse, AppError> {
+        let started = tokio::time::Instant::now();
+        let result = self.lookup_availability(query).await;
+        tokio::time::sleep_until(started + availability_delay(OsRng.next_u64())).await;
+        result
+    }
+
+    async fn lookup_availability(
+        &self,
+        query: AvailabilityQuery,
+    ) -> Result<AvailabilityResponse, AppError> {
+        if self.signup_mode == SignupMode::Closed {
+            return Err(AppError::Forbidden(
+                "Signup is disabled on this server".to_string(),
+            ));
         }
 
-        // Check if email is already taken
-        match self
+        let username = present(query.username.as_deref()).map(str::trim);
+        let email = present(query.email.as_deref()).map(str::trim);
+        if username.is_none() && email.is_none() {
+            return Err(AppError::Validation(
+                "Missing required fields: username or email".to_string(),
+            ));
+        }
+        if let Some(email) = email {
+            if !EmailAddress::is_valid(email) {
+                let mut errors = ValidationErrors::default();
+                errors.add("email", INVALID_EMAIL, "Email is not valid");
+                return Err(AppError::InvalidFields(errors));
+            }
+        }
+
+        let (username_taken, email_taken) =
+            self.user_repository.fetch_taken(username, email).await?;
+        Ok(AvailabilityResponse {
+            username: username.map(|_| !username_taken),
+            email: email.map(|_| !email_taken),
+        })
+    }
+
+    // Email the verification link of a stored token
+    fn email_verification(
+        &self,
+        username: &str,
+        email: &str,
+        token: &str,
+        expires_at: OffsetDateTime,
+    ) {
+        let link = format!(
+            "{}/user/verify?token={token}",
+            self.public_url.trim_end_matches('/')
+        );
+        let expires_at = format_timestamp(expires_at).unwrap_or_default();
+        self.mailer.send(EMAIL_VERIFICATION.render(
+            email,
+            &[
+                ("username", username),
+                ("link", link.as_str()),
+                ("expires_at", expires_at.as_str()),
+            ],
+        ));
+    }
+
+    // Verify the email of a user with the token of a verification link
+    pub async fn verify_email(
+        &self,
+        query: VerifyEmailQuery,
+    ) -> Result<UpdateUserResponse, AppError> {
+        let Some(token) = query.token.filter(|token| !token.trim().is_empty()) else {
+            return Err(AppError::Validation(
+                "Missing required fields: token".to_string(),
+            ));
+        };
+
+        if self
             .user_repository
-            .exists_user_by_email(&validated_user.email)
-            .await
+            .verify_email(&hash_token(token.trim()))
+            .await?
         {
-            Ok(Some(true)) => return Err(Json(ErrorResponse::new("Email already exists"))),
-            Err(e) => return Err(Json(ErrorResponse::new(format!("Database error: {e}")))),
-            _ => {}
+            Ok(UpdateUserResponse {
+                message: "Email verified".to_string(),
+            })
+        } else {
+            Err(AppError::Validation(
+                "Invalid or expired verification token".to_string(),
+            ))
         }
+    }
 
-        // Check if email is valid
-        let email_validation = EmailAddress::is_valid(&validated_user.email);
-        if !email_validation {
-            return Err(Json(ErrorResponse::new("Email is not valid")));
-        }
+    // Send a new verification email, replacing the pending one
+    pub async fn resend_verification(&self, user_id: i64) -> Result<UpdateUserResponse, AppError> {
+        let target = self
+            .user_repository
+            .fetch_verification_target(user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
 
-        // Check if password is valid
-        if !validate_password(&validated_user.password) {
-            return Err(Json(ErrorResponse::new("Password is not valid")));
+        if target.verified {
+            return Err(AppError::Conflict("Email already verified".to_string()));
         }
-
-        // Check if Fone is Valid
-        if !validate_fone(&validated_user.fone.to_string()) {
-            return Err(Json(ErrorResponse::new("Fone is not valid")));
+        if target.last_sent_at.is_some_and(|sent_at| {
+            sent_at + Duration::seconds(VERIFICATION_RESEND_SECONDS) > OffsetDateTime::now_utc()
+        }) {
+            return Err(AppError::Conflict(
+                "Verification email sent recently, try again later".to_string(),
+            ));
         }
 
-        let hashed_password = match hash_password(&validated_user.password) {
-            Ok(hash) => hash,
-            Err(e) => {
-                return Err(Json(ErrorResponse::new(format!(
-                    "Password hashing error: {e}"
-                ))))
-            }
-        };
-        validated_user.password = hashed_password;
-
-        match self.user_repository.create_user(validated_user).await {
-            Ok(user) => Ok(NewUserResponse {
-                id: i64::from(user),
-                message: "User created".to_string(),
-            }),
-            Err(e) => Err(Json(ErrorResponse::new(format!("Database error: {e}")))),
-        }
+        let mut tx = self.user_repository.begin().await?;
+        let (token, expires_at) = store_verification(&mut tx, user_id).await?;
+        tx.commit().await?;
+        self.email_verification(&target.username, &target.email, &token, expires_at);
+        Ok(UpdateUserResponse {
+            message: "Verification email sent".to_string(),
+        })
     }
 
-    // Function that handles user login
+    // Function that handles user login, opening a session of the device
     pub async fn login_user(
         &self,
         user_login: LoginUserRequest,
+        device: SessionDevice,
         enconding_key: EncodingKey,
         session_duration: i64,
-    ) -> Result<LoginUserResponse, Json<ErrorResponse>> {
+    ) -> Result<LoginUserResponse, AppError> {
         // Validate required fields
         let required_fields = vec!["username", "password"];
         let validated_user: ValidatedLoginUserRequest =
             match validate_required_fields(&user_login, required_fields) {
                 Err(missing) => {
-                    tracing::warn!("Missing required fields: {0}", &missing);
-                    return Err(Json(ErrorResponse::new(format!(
-                        "Missing required fields: {missing}"
-                    ))));
+                    tracing::warn!("Invalid login request: {}", &missing);
+                    record_login(LoginOutcome::MissingFields);
+                    return Err(AppError::InvalidFields(missing));
                 }
                 Ok(user) => user,
             };
@@ -132,177 +371,409 @@ impl UserService {
         let user = validated_user.username;
 
         // Find User login and password in repository
-        let Ok(user_info) = self.user_repository.get_user_for_login(&user).await else {
-            tracing::warn!("User {0} not found", &user);
-            return Err(Json(ErrorResponse::new(
-                "Username and Password invalid".to_string(),
-            )));
+        let user_info = match self.user_repository.get_user_for_login(&user).await {
+            Ok(user_info) => user_info,
+            Err(sqlx::Error::RowNotFound) => {
+                tracing::warn!("User {0} not found", &user);
+                record_login(LoginOutcome::UnknownUser);
+                self.session_service
+                    .record_failed_login(None, &device, &format!("unknown username {user}"))
+                    .await;
+                return Err(invalid_credentials());
+            }
+            Err(e) => {
+                record_login(LoginOutcome::Error);
+                return Err(AppError::Database(e));
+            }
         };
 
+        // Hashes no password verifies against can only be replaced through a reset
+        if user_info.password_reset_required
+            || password_hash_status(&user_info.password) == PasswordHashStatus::Malformed
+        {
+            tracing::warn!("Password reset required for username: {0}", &user);
+            record_login(LoginOutcome::PasswordResetRequired);
+            self.start_password_reset(user_info.id).await;
+            return Err(AppError::PasswordResetRequired(
+                "The password of this account must be reset, a reset token was sent to its email"
+                    .to_string(),
+            ));
+        }
+
         // Validate password
         let is_password_correct =
             password_validation(&user_info.password, &validated_user.password);
         if !is_password_correct {
             tracing::warn!("Password validation failed for username: {0}", &user);
-            return Err(Json(ErrorResponse::new(
-                "Username and Password invalid".to_string(),
-            )));
+            record_login(LoginOutcome::InvalidPassword);
+            self.session_service
+                .record_failed_login(Some(user_info.id), &device, "invalid password")
+                .await;
+            return Err(invalid_credentials());
         }
+        self.upgrade_password_hash(user_info.id, &user_info.password, &validated_user.password)
+            .await;
 
-        // Generate JWT token
-        let token = match generate_token(session_duration, user_info.id, &enconding_key) {
+        // Users with a second factor first get a pre-auth token to exchange with a code
+        match self
+            .two_factor_service
+            .start_challenge(user_info.id, AMR_PASSWORD)
+            .await
+        {
+            Ok(None) => {}
+            Ok(Some(token)) => {
+                record_login(LoginOutcome::TwoFactorRequired);
+                return Ok(LoginUserResponse {
+                    token,
+                    message: "Two-factor code required".to_string(),
+                    two_factor_required: true,
+                    csrf_token: None,
+                });
+            }
+            Err(error) => {
+                record_login(LoginOutcome::Error);
+                return Err(error);
+            }
+        }
+
+        // Open the session of the device with its JWT token
+        let token = match self
+            .session_service
+            .open_session(
+                user_info.id,
+                &device,
+                vec![AMR_PASSWORD.to_string()],
+                &enconding_key,
+                session_duration,
+            )
+            .await
+        {
             Ok(token) => token,
-            Err(e) => {
-                tracing::warn!("Error generating JWT token: {0}", e.message);
-                return Err(Json(ErrorResponse::new(
-                    "Username and Password invalid".to_string(),
-                )));
+            Err(error) => {
+                record_login(LoginOutcome::Error);
+                return Err(error);
             }
         };
 
+        record_login(LoginOutcome::Success);
         Ok(LoginUserResponse {
             token,
             message: "User logged in".to_string(),
+            two_factor_required: false,
+            csrf_token: None,
         })
     }
 
-    // Fetch User Data
-    pub async fn fetch_user(&self, id: i64) -> Result<FetchUserResponse, Json<ErrorResponse>> {
-        let user = match self.user_repository.fetch_user(id).await {
-            Ok(user) => user,
-            Err(e) => {
-                tracing::warn!("Error fetching user data: {0}", e);
-                return Err(Json(ErrorResponse::new("User not found".to_string())));
-            }
+    // Replace an outdated hash by a current one while the password is at hand. A failure
+    // keeps the outdated hash, which still verifies.
+    async fn upgrade_password_hash(&self, id: i64, stored_hash: &str, password: &str) {
+        if password_hash_status(stored_hash) != PasswordHashStatus::Outdated {
+            return;
+        }
+        let upgraded = async {
+            let hashed_password = hash_password(password).map_err(AppError::Internal)?;
+            let mut tx = self.user_repository.begin().await?;
+            UserRepository::update_password(&mut tx, id, &hashed_password).await?;
+            tx.commit().await?;
+            Ok::<(), AppError>(())
         };
-
-        Ok(user)
-    }
-
-    // Update User Data
-    pub async fn update_user(
-        &self,
-        id: i64,
-        update_request: UpdateUserRequest,
-    ) -> Result<UpdateUserResponse, Json<ErrorResponse>> {
-        if let Some(ref fone) = update_request.fone {
-            if !validate_fone(fone) {
-                return Err(Json(ErrorResponse::new("Fone is not valid")));
-            }
+        match upgraded.await {
+            Ok(()) => tracing::info!("Password hash of user {} upgraded", id),
+            Err(e) => tracing::warn!("Error upgrading password hash of user {}: {:?}", id, e),
         }
+    }
 
+    // Email a password reset token to the user, at most once per resend delay
+    async fn start_password_reset(&self, id: i64) {
+        let token = generate_random_token();
+        let expires_at = OffsetDateTime::now_utc() + Duration::hours(PASSWORD_RESET_HOURS);
         match self
             .user_repository
-            .update_user(
+            .start_password_reset(
                 id,
-                update_request.name,
-                update_request.surname,
-                update_request.fone,
+                &hash_token(&token),
+                expires_at,
+                PASSWORD_RESET_RESEND_SECONDS,
             )
             .await
         {
-            Ok(()) => Ok(UpdateUserResponse {
-                message: "User updated successfully".to_string(),
-            }),
-            Err(e) => {
-                tracing::warn!("Error updating user: {}", e);
-                Err(Json(ErrorResponse::new("Failed to update user")))
+            Ok(Some(target)) => {
+                let expires_at = format_timestamp(expires_at).unwrap_or_default();
+                self.mailer.send(PASSWORD_RESET.render(
+                    &target.email,
+                    &[
+                        ("username", target.username.as_str()),
+                        ("token", token.as_str()),
+                        ("expires_at", expires_at.as_str()),
+                    ],
+                ));
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Error starting password reset of user {}: {}", id, e),
+        }
+    }
+
+    // Set a new password with an emailed reset token, signing out every session
+    pub async fn reset_password(
+        &self,
+        reset_request: ResetPasswordRequest,
+        device: &SessionDevice,
+    ) -> Result<UpdateUserResponse, AppError> {
+        let required_fields = vec!["token", "new_password"];
+        let mut errors = ValidationErrors::default();
+        if let Some(new_password) = present(reset_request.new_password.as_deref()) {
+            if !validate_password(new_password) {
+                errors.add("new_password", WEAK_PASSWORD, "New password is not valid");
             }
         }
+        let validated_request: ResetPasswordRequest =
+            match validate_required_fields(&reset_request, required_fields) {
+                Ok(request) => {
+                    errors.check()?;
+                    request
+                }
+                Err(mut missing) => {
+                    missing.extend(errors);
+                    return Err(AppError::InvalidFields(missing));
+                }
+            };
+        let (Some(token), Some(new_password)) =
+            (validated_request.token, validated_request.new_password)
+        else {
+            return Err(AppError::Validation(
+                "Token and new password are required".to_string(),
+            ));
+        };
+
+        let hashed_password = hash_password(&new_password)
+            .map_err(|e| AppError::Internal(format!("Password hashing error: {e}")))?;
+
+        let mut tx = self.user_repository.begin().await?;
+        let id = UserRepository::consume_password_reset(&mut tx, &hash_token(token.trim()))
+            .await?
+            .ok_or_else(|| AppError::Validation("Invalid or expired reset token".to_string()))?;
+        UserRepository::update_password(&mut tx, id, &hashed_password).await?;
+        SessionRepository::delete_other_sessions(&mut tx, id, None).await?;
+        tx.commit().await?;
+
+        self.audit_service
+            .record(NewAuditEntry {
+                user_id: Some(id),
+                actor_id: Some(id),
+                action: AuditAction::PasswordChanged,
+                device,
+                details: Some("password reset".to_string()),
+            })
+            .await;
+        Ok(UpdateUserResponse {
+            message: "Password reset, log in with the new password".to_string(),
+        })
+    }
+
+    // Fetch User Data
+    pub async fn fetch_user(&self, id: i64) -> Result<FetchUserResponse, AppError> {
+        self.user_repository
+            .fetch_user(id)
+            .await
+            .map_err(user_error)
+    }
+
+    // Update User Data, only the fields of the request are changed. With
+    // `expected_version`, only while the user is still at that version. Returns the
+    // version the user ends at.
+    pub async fn update_user(
+        &self,
+        id: i64,
+        expected_version: Option<i64>,
+        update_request: UpdateUserRequest,
+    ) -> Result<(UpdateUserResponse, i64), AppError> {
+        let changes = user_changes(update_request)?;
+        let stale =
+            || AppError::PreconditionFailed("User changed since the given version".to_string());
+        let expected = expected_version
+            .map(|version| user_version_time(version).ok_or_else(stale))
+            .transpose()?;
+
+        let version =
+            if changes.name.is_some() || changes.surname.is_some() || changes.fone.is_some() {
+                self.user_repository
+                    .update_user(id, changes.name, changes.surname, changes.fone, expected)
+                    .await?
+                    .map(user_version)
+                    .ok_or_else(stale)?
+            } else {
+                let version = self.fetch_user(id).await?.version();
+                if expected_version.is_some_and(|expected| expected != version) {
+                    return Err(stale());
+                }
+                version
+            };
+        Ok((
+            UpdateUserResponse {
+                message: "User updated successfully".to_string(),
+            },
+            version,
+        ))
     }
 
-    // Update User Password
+    // Update User Password, signing out every other session
     pub async fn update_password(
         &self,
         id: i64,
+        session_id: Option<i64>,
         password_request: UpdatePasswordRequest,
-    ) -> Result<UpdateUserResponse, Json<ErrorResponse>> {
+        device: &SessionDevice,
+    ) -> Result<UpdateUserResponse, AppError> {
         let required_fields = vec!["current_password", "new_password"];
+        let mut errors = ValidationErrors::default();
+        if let Some(new_password) = present(password_request.new_password.as_deref()) {
+            if !validate_password(new_password) {
+                errors.add("new_password", WEAK_PASSWORD, "New password is not valid");
+            }
+        }
         let validated_request: UpdatePasswordRequest =
             match validate_required_fields(&password_request, required_fields) {
-                Err(missing) => {
-                    return Err(Json(ErrorResponse::new(format!(
-                        "Missing required fields: {missing}"
-                    ))))
+                Ok(request) => {
+                    errors.check()?;
+                    request
+                }
+                Err(mut missing) => {
+                    missing.extend(errors);
+                    return Err(AppError::InvalidFields(missing));
                 }
-                Ok(req) => req,
             };
 
-        // Get current user password - need to get by user ID, not username
-        let user_info = match self.user_repository.fetch_user(id).await {
-            Ok(_) => {
-                // Get user login info by fetching username first, then getting login data
-                match self.user_repository.fetch_user(id).await {
-                    Ok(user) => {
-                        match self
-                            .user_repository
-                            .get_user_for_login(&user.username)
-                            .await
-                        {
-                            Ok(info) => info,
-                            Err(_) => return Err(Json(ErrorResponse::new("User not found"))),
-                        }
-                    }
-                    Err(_) => return Err(Json(ErrorResponse::new("User not found"))),
-                }
-            }
-            Err(_) => return Err(Json(ErrorResponse::new("User not found"))),
-        };
+        // The password is read by username
+        let user = self
+            .user_repository
+            .fetch_user(id)
+            .await
+            .map_err(user_error)?;
+        let user_info = self
+            .user_repository
+            .get_user_for_login(&user.username)
+            .await
+            .map_err(user_error)?;
 
         // Validate current password
         let current_password = validated_request
             .current_password
             .as_ref()
-            .ok_or_else(|| Json(ErrorResponse::new("Current password is required")))?;
+            .ok_or_else(|| AppError::Validation("Current password is required".to_string()))?;
 
         if !password_validation(&user_info.password, current_password) {
-            return Err(Json(ErrorResponse::new("Current password is incorrect")));
+            return Err(AppError::Validation(
+                "Current password is incorrect".to_string(),
+            ));
         }
 
         let new_password = validated_request
             .new_password
             .as_ref()
-            .ok_or_else(|| Json(ErrorResponse::new("New password is required")))?;
-        if !validate_password(new_password) {
-            return Err(Json(ErrorResponse::new("New password is not valid")));
-        }
+            .ok_or_else(|| AppError::Validation("New password is required".to_string()))?;
+
+        let hashed_password = hash_password(new_password)
+            .map_err(|e| AppError::Internal(format!("Password hashing error: {e}")))?;
+
+        // The other sessions are signed out along, a stolen session can't outlive the
+        // password change
+        let mut tx = self.user_repository.begin().await?;
+        UserRepository::update_password(&mut tx, id, &hashed_password).await?;
+        SessionRepository::delete_other_sessions(&mut tx, id, session_id).await?;
+        tx.commit().await?;
+
+        self.audit_service
+            .record(NewAuditEntry {
+                user_id: Some(id),
+                actor_id: Some(id),
+                action: AuditAction::PasswordChanged,
+                device,
+                details: None,
+            })
+            .await;
+        Ok(UpdateUserResponse {
+            message: "Password updated successfully".to_string(),
+        })
+    }
 
-        let hashed_password = match hash_password(new_password) {
-            Ok(hash) => hash,
-            Err(e) => {
-                return Err(Json(ErrorResponse::new(format!(
-                    "Password hashing error: {e}"
-                ))))
-            }
-        };
+    // Delete User
+    pub async fn delete_user(
+        &self,
+        id: i64,
+        device: &SessionDevice,
+    ) -> Result<UpdateUserResponse, AppError> {
+        self.user_repository.delete_user(id).await?;
+        self.audit_service
+            .record(NewAuditEntry {
+                user_id: Some(id),
+                actor_id: Some(id),
+                action: AuditAction::AccountDeleted,
+                device,
+                details: None,
+            })
+            .await;
+        Ok(UpdateUserResponse {
+            message: "User deleted successfully".to_string(),
+        })
+    }
+}
 
-        match self
-            .user_repository
-            .update_password(id, &hashed_password)
-            .await
-        {
-            Ok(()) => Ok(UpdateUserResponse {
-                message: "Password updated successfully".to_string(),
-            }),
-            Err(e) => {
-                tracing::warn!("Error updating password: {}", e);
-                Err(Json(ErrorResponse::new("Failed to update password")))
-            }
+// Value of an optional field unless blank, blank fields are reported as missing
+fn present(value: Option<&str>) -> Option<&str> {
+    value.filter(|value| !value.trim().is_empty())
+}
+
+// Problems of the formats of the given signup fields
+fn signup_format_errors(user_signup: &UserSignUp) -> ValidationErrors {
+    let mut errors = ValidationErrors::default();
+    if let Some(email) = present(user_signup.email.as_deref()) {
+        if !EmailAddress::is_valid(email) {
+            errors.add("email", INVALID_EMAIL, "Email is not valid");
+        }
+    }
+    if let Some(password) = present(user_signup.password.as_deref()) {
+        if !validate_password(password) {
+            errors.add("password", WEAK_PASSWORD, "Password is not valid");
+        }
+    }
+    if let Some(fone) = present(user_signup.fone.as_deref()) {
+        if !validate_fone(fone) {
+            errors.add("fone", INVALID_FONE, "Fone is not valid");
         }
     }
+    errors
+}
 
-    // Delete User
-    pub async fn delete_user(&self, id: i64) -> Result<UpdateUserResponse, Json<ErrorResponse>> {
-        match self.user_repository.delete_user(id).await {
-            Ok(()) => Ok(UpdateUserResponse {
-                message: "User deleted successfully".to_string(),
-            }),
-            Err(e) => {
-                tracing::warn!("Error deleting user: {}", e);
-                Err(Json(ErrorResponse::new("Failed to delete user")))
-            }
+// Trimmed fields of an update, `None` keeps the value and an empty string clears it
+fn user_changes(update_request: UpdateUserRequest) -> Result<UpdateUserRequest, ValidationErrors> {
+    let trim = |value: Option<String>| value.map(|value| value.trim().to_string());
+    let changes = UpdateUserRequest {
+        name: trim(update_request.name),
+        surname: trim(update_request.surname),
+        fone: trim(update_request.fone),
+    };
+
+    let mut errors = ValidationErrors::default();
+    if let Some(fone) = present(changes.fone.as_deref()) {
+        if !validate_fone(fone) {
+            errors.add("fone", INVALID_FONE, "Fone is not valid");
         }
     }
+    errors.check()?;
+    Ok(changes)
+}
+
+// Error of a wrong username or password, the same for both so usernames can't be probed
+fn invalid_credentials() -> AppError {
+    AppError::Unauthorized("Username and Password invalid".to_string())
+}
+
+// Missing user rows are not found errors, other failures database errors
+fn user_error(error: sqlx::Error) -> AppError {
+    match error {
+        sqlx::Error::RowNotFound => AppError::NotFound("User not found".to_string()),
+        e => AppError::Database(e),
+    }
 }
 
 #[cfg(test)]
@@ -314,6 +785,7 @@ impl UserService {
 )]
 mod tests {
     use super::*;
+    use crate::modules::common::ErrorResponse;
     use crate::modules::user::interfaces::*;
 
     // Mock repository for testing
@@ -363,19 +835,12 @@ mod tests {
             Self { mock_repo }
         }
 
-        async fn create_user(
-            &self,
-            user_signup: UserSignUp,
-        ) -> Result<NewUserResponse, Json<ErrorResponse>> {
+        async fn create_user(&self, user_signup: UserSignUp) -> Result<NewUserResponse, AppError> {
             // Validate required fields
             let required_fields = vec!["username", "email", "password", "fone", "name", "surname"];
             let validated_user: ValidatedUserSignUp =
                 match validate_required_fields(&user_signup, required_fields) {
-                    Err(missing) => {
-                        return Err(Json(ErrorResponse::new(format!(
-                            "Missing required fields: {missing}"
-                        ))))
-                    }
+                    Err(missing) => return Err(AppError::InvalidFields(missing)),
                     Ok(user) => user,
                 };
 
@@ -385,8 +850,10 @@ mod tests {
                 .exists_user_by_username(&validated_user.username)
                 .await
             {
-                Ok(Some(true)) => return Err(Json(ErrorResponse::new("Username already exists"))),
-                Err(_) => return Err(Json(ErrorResponse::new("Database error"))),
+                Ok(Some(true)) => {
+                    return Err(AppError::Conflict("Username already exists".to_string()))
+                }
+                Err(e) => return Err(AppError::Database(e)),
                 _ => {}
             }
 
@@ -396,25 +863,27 @@ mod tests {
                 .exists_user_by_email(&validated_user.email)
                 .await
             {
-                Ok(Some(true)) => return Err(Json(ErrorResponse::new("Email already exists"))),
-                Err(_) => return Err(Json(ErrorResponse::new("Database error"))),
+                Ok(Some(true)) => {
+                    return Err(AppError::Conflict("Email already exists".to_string()))
+                }
+                Err(e) => return Err(AppError::Database(e)),
                 _ => {}
             }
 
             // Check if email is valid
             let email_validation = EmailAddress::is_valid(&validated_user.email);
             if !email_validation {
-                return Err(Json(ErrorResponse::new("Email is not valid")));
+                return Err(AppError::Validation("Email is not valid".to_string()));
             }
 
             // Check if password is valid
             if !validate_password(&validated_user.password) {
-                return Err(Json(ErrorResponse::new("Password is not valid")));
+                return Err(AppError::Validation("Password is not valid".to_string()));
             }
 
             // Check if Fone is Valid
             if !validate_fone(&validated_user.fone) {
-                return Err(Json(ErrorResponse::new("Fone is not valid")));
+                return Err(AppError::Validation("Fone is not valid".to_string()));
             }
 
             match self.mock_repo.create_user(validated_user).await {
@@ -422,7 +891,7 @@ mod tests {
                     id: i64::from(user),
                     message: "User created".to_string(),
                 }),
-                Err(_) => Err(Json(ErrorResponse::new("Database error"))),
+                Err(e) => Err(AppError::Database(e)),
             }
         }
     }
@@ -439,6 +908,8 @@ mod tests {
             email: Some("test@example.com".to_string()),
             fone: Some("1234567890".to_string()),
             password: Some("Password123!".to_string()),
+            invite_code: None,
+            invitation_token: None,
         };
 
         let result = service.create_user(user_signup).await;
@@ -460,12 +931,17 @@ mod tests {
             email: Some("test@example.com".to_string()),
             fone: Some("1234567890".to_string()),
             password: Some("Password123!".to_string()),
+            invite_code: None,
+            invitation_token: None,
         };
 
         let result = service.create_user(user_signup).await;
         assert!(result.is_err());
         let error = result.unwrap_err();
-        assert!(error.0.message.contains("Missing required fields"));
+        assert!(error
+            .to_error_response()
+            .message
+            .contains("Missing required fields"));
     }
 
     #[tokio::test]
@@ -481,12 +957,14 @@ mod tests {
             email: Some("test@example.com".to_string()),
             fone: Some("1234567890".to_string()),
             password: Some("Password123!".to_string()),
+            invite_code: None,
+            invitation_token: None,
         };
 
         let result = service.create_user(user_signup).await;
         assert!(result.is_err());
         let error = result.unwrap_err();
-        assert_eq!(error.0.message, "Username already exists");
+        assert_eq!(error.to_error_response().message, "Username already exists");
     }
 
     #[tokio::test]
@@ -502,12 +980,14 @@ mod tests {
             email: Some("test@example.com".to_string()),
             fone: Some("1234567890".to_string()),
             password: Some("Password123!".to_string()),
+            invite_code: None,
+            invitation_token: None,
         };
 
         let result = service.create_user(user_signup).await;
         assert!(result.is_err());
         let error = result.unwrap_err();
-        assert_eq!(error.0.message, "Email already exists");
+        assert_eq!(error.to_error_response().message, "Email already exists");
     }
 
     #[tokio::test]
@@ -522,12 +1002,14 @@ mod tests {
             email: Some("invalid-email".to_string()),
             fone: Some("1234567890".to_string()),
             password: Some("Password123!".to_string()),
+            invite_code: None,
+            invitation_token: None,
         };
 
         let result = service.create_user(user_signup).await;
         assert!(result.is_err());
         let error = result.unwrap_err();
-        assert_eq!(error.0.message, "Email is not valid");
+        assert_eq!(error.to_error_response().message, "Email is not valid");
     }
 
     #[tokio::test]
@@ -542,12 +1024,14 @@ mod tests {
             email: Some("test@example.com".to_string()),
             fone: Some("1234567890".to_string()),
             password: Some("weak".to_string()),
+            invite_code: None,
+            invitation_token: None,
         };
 
         let result = service.create_user(user_signup).await;
         assert!(result.is_err());
         let error = result.unwrap_err();
-        assert_eq!(error.0.message, "Password is not valid");
+        assert_eq!(error.to_error_response().message, "Password is not valid");
     }
 
     #[tokio::test]
@@ -562,12 +1046,14 @@ mod tests {
             email: Some("test@example.com".to_string()),
             fone: Some("123".to_string()),
             password: Some("Password123!".to_string()),
+            invite_code: None,
+            invitation_token: None,
         };
 
         let result = service.create_user(user_signup).await;
         assert!(result.is_err());
         let error = result.unwrap_err();
-        assert_eq!(error.0.message, "Fone is not valid");
+        assert_eq!(error.to_error_response().message, "Fone is not valid");
     }
 
     #[tokio::test]
@@ -583,12 +1069,14 @@ mod tests {
             email: Some("test@example.com".to_string()),
             fone: Some("1234567890".to_string()),
             password: Some("Password123!".to_string()),
+            invite_code: None,
+            invitation_token: None,
         };
 
         let result = service.create_user(user_signup).await;
         assert!(result.is_err());
         let error = result.unwrap_err();
-        assert_eq!(error.0.message, "Database error");
+        assert!(matches!(error, AppError::Database(_)));
     }
 
     // Test actual service methods with minimal mocking
@@ -634,6 +1122,7 @@ mod tests {
         let login_request = LoginUserRequest {
             username: Some("testuser".to_string()),
             password: Some("password123".to_string()),
+            cookie: None,
         };
 
         assert_eq!(login_request.username, Some("testuser".to_string()));
@@ -642,6 +1131,7 @@ mod tests {
         let empty_request = LoginUserRequest {
             username: None,
             password: None,
+            cookie: None,
         };
 
         assert_eq!(empty_request.username, None);
@@ -664,6 +1154,8 @@ mod tests {
         let response = LoginUserResponse {
             token: "jwt.token.here".to_string(),
             message: "Login successful".to_string(),
+            two_factor_required: false,
+            csrf_token: None,
         };
 
         assert_eq!(response.token, "jwt.token.here");
@@ -719,6 +1211,143 @@ mod tests {
         assert_eq!(response.message, "Update completed");
     }
 
+    #[test]
+    fn test_availability_delay() {
+        let shortest = std::time::Duration::from_millis(AVAILABILITY_MIN_MS);
+        let longest = shortest + std::time::Duration::from_millis(AVAILABILITY_JITTER_MS);
+        assert_eq!(availability_delay(0), shortest);
+        assert_eq!(availability_delay(AVAILABILITY_JITTER_MS), longest);
+        for random in [1, 7_777, u64::MAX] {
+            let delay = availability_delay(random);
+            assert!(delay >= shortest && delay <= longest);
+        }
+    }
+
+    #[test]
+    fn test_signup_format_errors() {
+        let signup = UserSignUp {
+            username: None,
+            name: None,
+            surname: None,
+            email: Some("not-an-email".to_string()),
+            fone: Some("123".to_string()),
+            password: Some("weak".to_string()),
+            invite_code: None,
+            invitation_token: None,
+        };
+        let errors = signup_format_errors(&signup);
+        assert_eq!(errors.fields(), vec!["email", "password", "fone"]);
+        let codes: Vec<String> = errors.into_errors().into_iter().map(|e| e.code).collect();
+        assert_eq!(codes, vec![INVALID_EMAIL, WEAK_PASSWORD, INVALID_FONE]);
+
+        // Blank fields are left to the required fields check
+        let signup = UserSignUp {
+            email: Some("  ".to_string()),
+            fone: None,
+            password: Some("Str0ng!Pass".to_string()),
+            ..signup
+        };
+        assert!(signup_format_errors(&signup).is_empty());
+    }
+
+    #[test]
+    fn test_signup_taken_fields() {
+        let error = taken("username", "Username already exists");
+        assert_eq!(error.status(), axum::http::StatusCode::CONFLICT);
+        let body = error.to_error_response();
+        assert_eq!(body.message, "Username already exists");
+        let errors = body.errors.unwrap();
+        assert_eq!(errors[0].field, "username");
+        assert_eq!(errors[0].code, TAKEN);
+
+        // Other failures stay database errors
+        assert!(matches!(
+            signup_error(sqlx::Error::RowNotFound),
+            AppError::Database(_)
+        ));
+    }
+
+    fn update_request(
+        name: Option<&str>,
+        surname: Option<&str>,
+        fone: Option<&str>,
+    ) -> UpdateUserRequest {
+        UpdateUserRequest {
+            name: name.map(str::to_string),
+            surname: surname.map(str::to_string),
+            fone: fone.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_user_changes_keep_omitted_fields() {
+        let changes = user_changes(update_request(None, None, None)).unwrap();
+        assert_eq!(changes.name, None);
+        assert_eq!(changes.surname, None);
+        assert_eq!(changes.fone, None);
+
+        let changes = user_changes(update_request(Some(" Ada "), None, None)).unwrap();
+        assert_eq!(changes.name.as_deref(), Some("Ada"));
+        assert_eq!(changes.surname, None);
+        assert_eq!(changes.fone, None);
+
+        let changes = user_changes(update_request(None, Some("Lovelace"), None)).unwrap();
+        assert_eq!(changes.name, None);
+        assert_eq!(changes.surname.as_deref(), Some("Lovelace"));
+        assert_eq!(changes.fone, None);
+
+        let changes = user_changes(update_request(None, None, Some("(11) 99999-9999"))).unwrap();
+        assert_eq!(changes.name, None);
+        assert_eq!(changes.surname, None);
+        assert_eq!(changes.fone.as_deref(), Some("(11) 99999-9999"));
+    }
+
+    #[test]
+    fn test_user_changes_set_every_field() {
+        let changes = user_changes(update_request(
+            Some("Ada"),
+            Some("Lovelace"),
+            Some("1234567890"),
+        ))
+        .unwrap();
+        assert_eq!(changes.name.as_deref(), Some("Ada"));
+        assert_eq!(changes.surname.as_deref(), Some("Lovelace"));
+        assert_eq!(changes.fone.as_deref(), Some("1234567890"));
+    }
+
+    #[test]
+    fn test_user_changes_clear_fields() {
+        // Empty strings clear the fields, an empty fone is not validated
+        let changes = user_changes(update_request(Some(""), Some(" "), Some(""))).unwrap();
+        assert_eq!(changes.name.as_deref(), Some(""));
+        assert_eq!(changes.surname.as_deref(), Some(""));
+        assert_eq!(changes.fone.as_deref(), Some(""));
+
+        let changes = user_changes(update_request(Some("Ada"), Some(""), None)).unwrap();
+        assert_eq!(changes.name.as_deref(), Some("Ada"));
+        assert_eq!(changes.surname.as_deref(), Some(""));
+        assert_eq!(changes.fone, None);
+    }
+
+    #[test]
+    fn test_user_changes_invalid_fone() {
+        let errors = user_changes(update_request(Some("Ada"), None, Some("123"))).unwrap_err();
+        assert_eq!(errors.fields(), vec!["fone"]);
+    }
+
+    #[test]
+    fn test_user_error_mapping() {
+        assert!(matches!(
+            user_error(sqlx::Error::RowNotFound),
+            AppError::NotFound(_)
+        ));
+        assert!(matches!(
+            user_error(sqlx::Error::PoolTimedOut),
+            AppError::Database(_)
+        ));
+        assert!(matches!(invalid_credentials(), AppError::Unauthorized(_)));
+    }
+
     #[test]
     fn test_service_error_handling() {
         let error_msg = "Database connection failed";
@@ -756,6 +1385,8 @@ mod tests {
             email: Some("test@example.com".to_string()),
             password: Some("password123".to_string()),
             fone: Some("1234567890".to_string()),
+            invite_code: None,
+            invitation_token: None,
         };
 
         assert!(signup.username.is_some());
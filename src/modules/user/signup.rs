@@ -0,0 +1,63 @@
+//! # Signup Mode
+//! Who may create an account on the instance, read from `SIGNUP_MODE`. Private and
+//! self-hosted instances close the signup or require an invite code generated by an
+//! administrator.
+
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SignupMode {
+    // Anyone can sign up
+    #[default]
+    Open,
+    // Signing up requires a valid invite code
+    Invite,
+    // No new accounts, administrators only manage the existing ones
+    Closed,
+}
+
+impl SignupMode {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Invite => "invite",
+            Self::Closed => "closed",
+        }
+    }
+}
+
+impl FromStr for SignupMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "open" => Ok(Self::Open),
+            "invite" => Ok(Self::Invite),
+            "closed" => Ok(Self::Closed),
+            _ => Err(format!(
+                "Invalid SIGNUP_MODE {value}, expected open, invite or closed"
+            )),
+        }
+    }
+}
+
+// Signup mode configured in the environment, open by default
+pub fn signup_mode_from_env() -> Result<SignupMode, String> {
+    std::env::var("SIGNUP_MODE")
+        .unwrap_or_default()
+        .parse::<SignupMode>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signup_mode_parse() {
+        assert_eq!("".parse::<SignupMode>(), Ok(SignupMode::Open));
+        assert_eq!(" Invite ".parse::<SignupMode>(), Ok(SignupMode::Invite));
+        assert_eq!("closed".parse::<SignupMode>(), Ok(SignupMode::Closed));
+        assert!("private".parse::<SignupMode>().is_err());
+        assert_eq!(SignupMode::Invite.as_str(), "invite");
+    }
+}
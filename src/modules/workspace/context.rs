@@ -0,0 +1,86 @@
+//! # `Workspace` Context
+//! Active workspace of a request, resolved from the session token.
+
+use axum::{
+    extract::{FromRequestParts, MatchedPath},
+    http::{request::Parts, StatusCode},
+};
+
+use crate::{
+    auth::Claims,
+    modules::workspace::{permissions::WorkspacePermission, repository::WorkspaceRepository},
+    AppState,
+};
+
+// User and workspace a request works in. Membership and the permission needed by the
+// route are checked on every request, so removed or limited members lose access before
+// their token expires.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WorkspaceContext {
+    pub user_id: i64,
+    pub workspace_id: i64,
+}
+
+impl FromRequestParts<AppState> for WorkspaceContext {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let claims = Claims::from_request_parts(parts, state).await?;
+        let route = parts
+            .extensions
+            .get::<MatchedPath>()
+            .map_or_else(|| parts.uri.path(), MatchedPath::as_str);
+        let needed = WorkspacePermission::required_for(&parts.method, route);
+
+        match WorkspaceRepository::new(state.db_pool.clone())
+            .active_workspace(claims.user_id, claims.workspace_id)
+            .await
+        {
+            Ok(Some(workspace)) if needed.granted_by(workspace.permissions.as_deref()) => {
+                Ok(Self {
+                    user_id: claims.user_id,
+                    workspace_id: i64::from(workspace.id),
+                })
+            }
+            Ok(Some(workspace)) => {
+                tracing::warn!(
+                    "User {} lacks the {} permission in workspace {}",
+                    claims.user_id,
+                    needed.as_str(),
+                    workspace.id
+                );
+                Err(StatusCode::FORBIDDEN)
+            }
+            Ok(None) => {
+                tracing::warn!(
+                    "User {} is not a member of workspace {:?}",
+                    claims.user_id,
+                    claims.workspace_id
+                );
+                Err(StatusCode::FORBIDDEN)
+            }
+            Err(e) => {
+                tracing::warn!("Error resolving active workspace: {}", e);
+                Err(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_is_copy() {
+        let context = WorkspaceContext {
+            user_id: 1,
+            workspace_id: 2,
+        };
+        let copy = context;
+        assert_eq!(copy, context);
+    }
+}
@@ -0,0 +1,277 @@
+//! # `Workspaces` Interfaces
+//! This module defines the data structures from Workspaces module
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+use crate::utils::dates::{rfc3339, rfc3339_option};
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct CreateWorkspaceRequest {
+    // Workspace name
+    pub name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct ValidatedCreateWorkspaceRequest {
+    pub name: String,
+}
+
+// Workspace row with the role of the user as stored in database
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct WorkspaceRow {
+    pub id: i32,
+    pub name: String,
+    pub personal: bool,
+    pub role: String,
+    pub default_template_id: Option<i32>,
+    pub created_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct WorkspaceResponse {
+    pub id: i64,
+    pub name: String,
+    // Personal workspaces can't be shared
+    pub personal: bool,
+    // One of `owner` or `member`
+    pub role: String,
+    // Permission template given to the members joining the workspace
+    pub default_template_id: Option<i64>,
+    #[serde(with = "rfc3339_option")]
+    pub created_at: Option<OffsetDateTime>,
+}
+
+impl From<WorkspaceRow> for WorkspaceResponse {
+    fn from(row: WorkspaceRow) -> Self {
+        Self {
+            id: i64::from(row.id),
+            name: row.name,
+            personal: row.personal,
+            role: row.role,
+            default_template_id: row.default_template_id.map(i64::from),
+            created_at: row.created_at,
+        }
+    }
+}
+
+// Workspace a request works in, with the permissions of the member.
+// `permissions` is `None` for owners and members without a template.
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct ActiveWorkspaceRow {
+    pub id: i32,
+    pub permissions: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct WorkspaceTokenResponse {
+    // Session token working in the workspace
+    pub token: String,
+    pub workspace_id: i64,
+}
+
+// Workspace member row as stored in database
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct WorkspaceMemberRow {
+    pub user_id: i32,
+    pub username: String,
+    pub role: String,
+    pub template_id: Option<i32>,
+    pub created_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct WorkspaceMemberResponse {
+    pub user_id: i64,
+    pub username: String,
+    // One of `owner` or `member`
+    pub role: String,
+    // Permission template limiting the member, every permission when not set
+    pub template_id: Option<i64>,
+    #[serde(with = "rfc3339_option")]
+    pub joined_at: Option<OffsetDateTime>,
+}
+
+impl From<WorkspaceMemberRow> for WorkspaceMemberResponse {
+    fn from(row: WorkspaceMemberRow) -> Self {
+        Self {
+            user_id: i64::from(row.user_id),
+            username: row.username,
+            role: row.role,
+            template_id: row.template_id.map(i64::from),
+            joined_at: row.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct InviteWorkspaceMemberRequest {
+    // Email of the user to invite
+    pub email: Option<String>,
+}
+
+// Workspace invitation row as stored in database
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct WorkspaceInvitationRow {
+    pub id: i32,
+    pub workspace_id: i32,
+    pub email: String,
+    pub expires_at: OffsetDateTime,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct WorkspaceInvitationResponse {
+    pub id: i64,
+    pub workspace_id: i64,
+    pub email: String,
+    // Invitation token, only returned once
+    pub token: String,
+    #[serde(with = "rfc3339")]
+    pub expires_at: OffsetDateTime,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct AcceptWorkspaceInvitationRequest {
+    // Invitation token received by email
+    pub token: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct WorkspaceMessageResponse {
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct PermissionTemplateRequest {
+    // Template name, unique in the workspace
+    pub name: Option<String>,
+    // Permissions granted to the members of the template, among `read`, `todos.write`,
+    // `lists.write`, `lists.share` and `todos.assign`
+    pub permissions: Option<Vec<String>>,
+}
+
+// Permission template row as stored in database
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct PermissionTemplateRow {
+    pub id: i32,
+    pub name: String,
+    pub permissions: Vec<String>,
+    pub created_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct PermissionTemplateResponse {
+    pub id: i64,
+    pub name: String,
+    pub permissions: Vec<String>,
+    #[serde(with = "rfc3339_option")]
+    pub created_at: Option<OffsetDateTime>,
+}
+
+impl From<PermissionTemplateRow> for PermissionTemplateResponse {
+    fn from(row: PermissionTemplateRow) -> Self {
+        Self {
+            id: i64::from(row.id),
+            name: row.name,
+            permissions: row.permissions,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct AssignTemplateRequest {
+    // Permission template, `null` grants every permission
+    pub template_id: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct TransferWorkspaceRequest {
+    // Username of the member receiving the workspace
+    pub to_username: Option<String>,
+}
+
+// Workspace ownership transfer row as stored in database
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct WorkspaceTransferRow {
+    pub id: i32,
+    pub workspace_id: i32,
+    pub from_user_id: i32,
+    pub to_user_id: i32,
+    pub status: String,
+    pub created_at: Option<OffsetDateTime>,
+    pub resolved_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct WorkspaceTransferResponse {
+    pub id: i64,
+    pub workspace_id: i64,
+    pub from_user_id: i64,
+    pub to_user_id: i64,
+    // One of `pending`, `accepted` or `declined`
+    pub status: String,
+    #[serde(with = "rfc3339_option")]
+    pub created_at: Option<OffsetDateTime>,
+    #[serde(with = "rfc3339_option")]
+    pub resolved_at: Option<OffsetDateTime>,
+}
+
+impl From<WorkspaceTransferRow> for WorkspaceTransferResponse {
+    fn from(row: WorkspaceTransferRow) -> Self {
+        Self {
+            id: i64::from(row.id),
+            workspace_id: i64::from(row.workspace_id),
+            from_user_id: i64::from(row.from_user_id),
+            to_user_id: i64::from(row.to_user_id),
+            status: row.status,
+            created_at: row.created_at,
+            resolved_at: row.resolved_at,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workspace_response_from_row() {
+        let response = WorkspaceResponse::from(WorkspaceRow {
+            id: 3,
+            name: "Acme".to_string(),
+            personal: false,
+            role: "owner".to_string(),
+            default_template_id: Some(4),
+            created_at: None,
+        });
+        assert_eq!(response.id, 3);
+        assert_eq!(response.role, "owner");
+        assert!(!response.personal);
+        assert_eq!(response.default_template_id, Some(4));
+    }
+
+    #[test]
+    fn test_accept_invitation_request_missing_token() {
+        let request: AcceptWorkspaceInvitationRequest = serde_json::from_str("{}").unwrap();
+        assert_eq!(request.token, None);
+    }
+
+    #[test]
+    fn test_workspace_transfer_response_from_row() {
+        let response = WorkspaceTransferResponse::from(WorkspaceTransferRow {
+            id: 1,
+            workspace_id: 3,
+            from_user_id: 4,
+            to_user_id: 5,
+            status: "pending".to_string(),
+            created_at: None,
+            resolved_at: None,
+        });
+        assert_eq!(response.workspace_id, 3);
+        assert_eq!(response.to_user_id, 5);
+        assert_eq!(response.status, "pending");
+    }
+}
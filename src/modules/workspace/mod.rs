@@ -0,0 +1,11 @@
+//! # `Workspace` Mod
+//! Workspace imports for the workspace module
+
+pub mod context;
+pub mod interfaces;
+pub mod permissions;
+pub mod repository;
+pub mod routes;
+pub mod service;
+
+pub use routes::workspace_routes;
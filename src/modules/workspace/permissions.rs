@@ -0,0 +1,169 @@
+//! # Workspace Permissions
+//! What the members of a workspace may do. Owners, and members without a permission
+//! template, have every permission. A template limits its members to the permissions it
+//! lists, like a "Contractor" template only granting `read`.
+
+use std::str::FromStr;
+
+use axum::http::Method;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+// Routes answering a `POST` without changing anything
+const READ_ONLY_POSTS: [&str; 1] = ["/todos/check-duplicates"];
+
+// Routes sharing a list or opening it to outsiders
+const SHARE_ROUTES: [&str; 3] = [
+    "/lists/{id}/members",
+    "/lists/{id}/guests",
+    "/lists/{id}/inbound",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum WorkspacePermission {
+    // See the lists and todos of the workspace
+    #[serde(rename = "read")]
+    Read,
+    // Create, change and delete todos, with their items, tags, reminders and attachments
+    #[serde(rename = "todos.write")]
+    TodosWrite,
+    // Create, rename, archive, transfer and delete lists
+    #[serde(rename = "lists.write")]
+    ListsWrite,
+    // Share lists with members, guests and inbound email senders
+    #[serde(rename = "lists.share")]
+    ListsShare,
+    // Delegate todos to other members
+    #[serde(rename = "todos.assign")]
+    TodosAssign,
+}
+
+impl WorkspacePermission {
+    pub const ALL: [Self; 5] = [
+        Self::Read,
+        Self::TodosWrite,
+        Self::ListsWrite,
+        Self::ListsShare,
+        Self::TodosAssign,
+    ];
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::TodosWrite => "todos.write",
+            Self::ListsWrite => "lists.write",
+            Self::ListsShare => "lists.share",
+            Self::TodosAssign => "todos.assign",
+        }
+    }
+
+    // Permission needed by a request in a workspace, from its method and matched route
+    pub fn required_for(method: &Method, route: &str) -> Self {
+        if matches!(*method, Method::GET | Method::HEAD) || READ_ONLY_POSTS.contains(&route) {
+            Self::Read
+        } else if route.starts_with("/todos/{id}/assign") {
+            Self::TodosAssign
+        } else if SHARE_ROUTES.iter().any(|prefix| route.starts_with(prefix)) {
+            Self::ListsShare
+        } else if route.starts_with("/lists") {
+            Self::ListsWrite
+        } else {
+            Self::TodosWrite
+        }
+    }
+
+    // Whether a member holds the permission. `None` is full access, unknown names in a
+    // template are ignored.
+    pub fn granted_by(self, permissions: Option<&[String]>) -> bool {
+        permissions.map_or(true, |permissions| {
+            permissions
+                .iter()
+                .any(|permission| permission.parse::<Self>().is_ok_and(|held| held == self))
+        })
+    }
+}
+
+impl FromStr for WorkspacePermission {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|permission| permission.as_str() == value.trim())
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::ALL.into_iter().map(Self::as_str).collect();
+                format!("Permissions must be among {}", names.join(", "))
+            })
+    }
+}
+
+// Validate the permissions of a template, in catalogue order without duplicates
+pub fn parse_permissions(values: &[String]) -> Result<Vec<String>, String> {
+    let permissions = values
+        .iter()
+        .map(|value| value.parse::<WorkspacePermission>())
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(WorkspacePermission::ALL
+        .into_iter()
+        .filter(|permission| permissions.contains(permission))
+        .map(|permission| permission.as_str().to_string())
+        .collect())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_required_for() {
+        let required = WorkspacePermission::required_for;
+        assert_eq!(
+            required(&Method::GET, "/lists/{id}/members"),
+            WorkspacePermission::Read
+        );
+        assert_eq!(
+            required(&Method::POST, "/todos/check-duplicates"),
+            WorkspacePermission::Read
+        );
+        assert_eq!(
+            required(&Method::POST, "/todos/{id}/assign"),
+            WorkspacePermission::TodosAssign
+        );
+        assert_eq!(
+            required(&Method::DELETE, "/lists/{id}/members/{user_id}"),
+            WorkspacePermission::ListsShare
+        );
+        assert_eq!(
+            required(&Method::PUT, "/lists/{id}"),
+            WorkspacePermission::ListsWrite
+        );
+        assert_eq!(
+            required(&Method::POST, "/todos/{id}/items"),
+            WorkspacePermission::TodosWrite
+        );
+    }
+
+    #[test]
+    fn test_granted_by() {
+        let contractor = vec!["read".to_string(), "unknown".to_string()];
+        assert!(WorkspacePermission::Read.granted_by(Some(contractor.as_slice())));
+        assert!(!WorkspacePermission::TodosWrite.granted_by(Some(contractor.as_slice())));
+        assert!(WorkspacePermission::TodosWrite.granted_by(None));
+        assert!(!WorkspacePermission::Read.granted_by(Some(&[][..])));
+    }
+
+    #[test]
+    fn test_parse_permissions() {
+        let values = vec![
+            "todos.write".to_string(),
+            " read".to_string(),
+            "read".to_string(),
+        ];
+        assert_eq!(
+            parse_permissions(&values).unwrap(),
+            vec!["read", "todos.write"]
+        );
+        assert!(parse_permissions(&["admin".to_string()]).is_err());
+    }
+}
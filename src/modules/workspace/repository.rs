@@ -0,0 +1,566 @@
+//! # `Workspace` Repository
+//! This module defines the workspace repository for workspace and membership operations.
+
+use sqlx::{Error, Pool, Postgres};
+use time::OffsetDateTime;
+
+use crate::modules::{
+    common::to_db_id,
+    workspace::interfaces::{
+        ActiveWorkspaceRow, PermissionTemplateRow, WorkspaceInvitationRow, WorkspaceMemberRow,
+        WorkspaceRow, WorkspaceTransferRow,
+    },
+};
+use crate::telemetry::observe_query;
+
+const WORKSPACE_COLUMNS: &str = "w.id, w.name, w.personal_user_id IS NOT NULL AS personal, \
+    m.role, w.default_template_id, w.created_at";
+
+const TEMPLATE_COLUMNS: &str = "t.id, t.name, t.permissions, t.created_at";
+
+const TRANSFER_COLUMNS: &str =
+    "id, workspace_id, from_user_id, to_user_id, status, created_at, resolved_at";
+
+pub struct WorkspaceRepository {
+    pool: Pool<Postgres>,
+}
+
+impl WorkspaceRepository {
+    // Constructor
+    pub const fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    // Workspace the user works in, `workspace_id` or the personal workspace when not set,
+    // with the permissions of the template of the user.
+    // Returns `None` when the user is not a member of it.
+    pub async fn active_workspace(
+        &self,
+        user_id: i64,
+        workspace_id: Option<i64>,
+    ) -> Result<Option<ActiveWorkspaceRow>, Error> {
+        observe_query("workspace.active_workspace", async move {
+            sqlx::query_as::<_, ActiveWorkspaceRow>(
+                "SELECT w.id,
+                    CASE WHEN m.role = 'owner' THEN NULL ELSE t.permissions END AS permissions
+                 FROM workspaces w
+                 JOIN workspace_members m ON m.workspace_id = w.id AND m.user_id = $1
+                 LEFT JOIN workspace_permission_templates t ON t.id = m.template_id
+                 WHERE w.id = $2 OR ($2::INTEGER IS NULL AND w.personal_user_id = $1)",
+            )
+            .bind(to_db_id(user_id)?)
+            .bind(workspace_id.map(to_db_id).transpose()?)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Create a workspace owned by the user
+    pub async fn create_workspace(&self, user_id: i64, name: &str) -> Result<WorkspaceRow, Error> {
+        observe_query("workspace.create_workspace", async move {
+            sqlx::query_as::<_, WorkspaceRow>(
+                "WITH w AS (
+                    INSERT INTO workspaces (name) VALUES ($2)
+                    RETURNING id, name, created_at
+                 ), m AS (
+                    INSERT INTO workspace_members (workspace_id, user_id, role)
+                    SELECT id, $1, 'owner' FROM w
+                 )
+                 SELECT w.id, w.name, FALSE AS personal, 'owner' AS role,
+                    NULL::INTEGER AS default_template_id, w.created_at
+                 FROM w",
+            )
+            .bind(to_db_id(user_id)?)
+            .bind(name)
+            .fetch_one(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // List the workspaces of the user, the personal one first
+    pub async fn list_workspaces(&self, user_id: i64) -> Result<Vec<WorkspaceRow>, Error> {
+        observe_query("workspace.list_workspaces", async move {
+            let query = format!(
+                "SELECT {WORKSPACE_COLUMNS}
+                 FROM workspace_members m JOIN workspaces w ON w.id = m.workspace_id
+                 WHERE m.user_id = $1
+                 ORDER BY w.personal_user_id IS NULL, w.name, w.id"
+            );
+
+            sqlx::query_as::<_, WorkspaceRow>(&query)
+                .bind(to_db_id(user_id)?)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Fetch a workspace the user is a member of
+    pub async fn fetch_workspace(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+    ) -> Result<Option<WorkspaceRow>, Error> {
+        observe_query("workspace.fetch_workspace", async move {
+            let query = format!(
+                "SELECT {WORKSPACE_COLUMNS}
+                 FROM workspace_members m JOIN workspaces w ON w.id = m.workspace_id
+                 WHERE m.user_id = $1 AND w.id = $2"
+            );
+
+            sqlx::query_as::<_, WorkspaceRow>(&query)
+                .bind(to_db_id(user_id)?)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // List the members of a workspace, oldest first
+    pub async fn list_members(&self, workspace_id: i64) -> Result<Vec<WorkspaceMemberRow>, Error> {
+        observe_query("workspace.list_members", async move {
+            sqlx::query_as::<_, WorkspaceMemberRow>(
+                "SELECT m.user_id, u.username, m.role, m.template_id, m.created_at
+                 FROM workspace_members m JOIN users u ON u.id = m.user_id
+                 WHERE m.workspace_id = $1
+                 ORDER BY m.created_at, m.user_id",
+            )
+            .bind(to_db_id(workspace_id)?)
+            .fetch_all(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Remove a member, by a workspace owner or by the member leaving. Owners stay.
+    // The lists of the workspace shared with the member are unshared.
+    pub async fn remove_member(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        member_id: i64,
+    ) -> Result<bool, Error> {
+        observe_query("workspace.remove_member", async move {
+            let mut tx = self.pool.begin().await?;
+
+            let removed = sqlx::query(
+                "DELETE FROM workspace_members m
+                 WHERE m.workspace_id = $1 AND m.user_id = $3 AND m.role <> 'owner'
+                   AND (m.user_id = $2 OR EXISTS(
+                        SELECT 1 FROM workspace_members o
+                        WHERE o.workspace_id = $1 AND o.user_id = $2 AND o.role = 'owner'))",
+            )
+            .bind(to_db_id(workspace_id)?)
+            .bind(to_db_id(user_id)?)
+            .bind(to_db_id(member_id)?)
+            .execute(&mut *tx)
+            .await?;
+            if removed.rows_affected() == 0 {
+                return Ok(false);
+            }
+
+            sqlx::query(
+                "DELETE FROM list_members lm USING lists l
+                 WHERE lm.list_id = l.id AND l.workspace_id = $1 AND lm.user_id = $2",
+            )
+            .bind(to_db_id(workspace_id)?)
+            .bind(to_db_id(member_id)?)
+            .execute(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            Ok(true)
+        })
+        .await
+    }
+
+    // Store an invitation to a shared workspace owned by the user.
+    // Returns `None` when the workspace is not found, personal or not owned by the user.
+    pub async fn create_invitation(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        email: &str,
+        token_hash: &str,
+        expires_at: OffsetDateTime,
+    ) -> Result<Option<WorkspaceInvitationRow>, Error> {
+        observe_query("workspace.create_invitation", async move {
+            sqlx::query_as::<_, WorkspaceInvitationRow>(
+                "INSERT INTO workspace_invitations
+                    (workspace_id, invited_by, email, token_hash, expires_at)
+                 SELECT w.id, m.user_id, $3, $4, $5
+                 FROM workspaces w
+                 JOIN workspace_members m
+                    ON m.workspace_id = w.id AND m.user_id = $2 AND m.role = 'owner'
+                 WHERE w.id = $1 AND w.personal_user_id IS NULL
+                 RETURNING id, workspace_id, email, expires_at",
+            )
+            .bind(to_db_id(workspace_id)?)
+            .bind(to_db_id(user_id)?)
+            .bind(email)
+            .bind(token_hash)
+            .bind(expires_at)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Accept a pending invitation sent to the email of the user and join its workspace.
+    // Returns `None` when the invitation is invalid, expired or for another email.
+    pub async fn accept_invitation(
+        &self,
+        user_id: i64,
+        token_hash: &str,
+    ) -> Result<Option<WorkspaceRow>, Error> {
+        observe_query("workspace.accept_invitation", async move {
+            let mut tx = self.pool.begin().await?;
+
+            let Some(workspace_id) = sqlx::query_scalar::<_, i32>(
+                "UPDATE workspace_invitations i SET accepted_at = NOW()
+                 FROM users u
+                 WHERE i.token_hash = $1 AND i.accepted_at IS NULL AND i.expires_at > NOW()
+                   AND u.id = $2 AND LOWER(u.email) = i.email AND NOT u.is_guest
+                 RETURNING i.workspace_id",
+            )
+            .bind(token_hash)
+            .bind(to_db_id(user_id)?)
+            .fetch_optional(&mut *tx)
+            .await?
+            else {
+                return Ok(None);
+            };
+
+            sqlx::query(
+                "INSERT INTO workspace_members (workspace_id, user_id, role, template_id)
+                 SELECT id, $2, 'member', default_template_id FROM workspaces WHERE id = $1
+                 ON CONFLICT (workspace_id, user_id) DO NOTHING",
+            )
+            .bind(workspace_id)
+            .bind(to_db_id(user_id)?)
+            .execute(&mut *tx)
+            .await?;
+
+            let query = format!(
+                "SELECT {WORKSPACE_COLUMNS}
+                 FROM workspace_members m JOIN workspaces w ON w.id = m.workspace_id
+                 WHERE m.user_id = $1 AND w.id = $2"
+            );
+            let workspace = sqlx::query_as::<_, WorkspaceRow>(&query)
+                .bind(to_db_id(user_id)?)
+                .bind(workspace_id)
+                .fetch_one(&mut *tx)
+                .await?;
+
+            tx.commit().await?;
+            Ok(Some(workspace))
+        })
+        .await
+    }
+
+    // Offer a shared workspace owned by the user to another of its members, replacing
+    // any pending offer for the workspace
+    pub async fn create_transfer(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        to_username: &str,
+    ) -> Result<Option<WorkspaceTransferRow>, Error> {
+        observe_query("workspace.create_transfer", async move {
+            let query = format!(
+                "INSERT INTO workspace_transfers (workspace_id, from_user_id, to_user_id)
+                 SELECT w.id, o.user_id, u.id
+                 FROM workspaces w
+                 JOIN workspace_members o
+                    ON o.workspace_id = w.id AND o.user_id = $2 AND o.role = 'owner'
+                 JOIN users u ON u.username = $3
+                 JOIN workspace_members m ON m.workspace_id = w.id AND m.user_id = u.id
+                 WHERE w.id = $1 AND w.personal_user_id IS NULL AND u.id <> o.user_id
+                 ON CONFLICT (workspace_id) WHERE status = 'pending'
+                 DO UPDATE SET from_user_id = EXCLUDED.from_user_id,
+                    to_user_id = EXCLUDED.to_user_id, created_at = NOW()
+                 RETURNING {TRANSFER_COLUMNS}"
+            );
+
+            sqlx::query_as::<_, WorkspaceTransferRow>(&query)
+                .bind(to_db_id(workspace_id)?)
+                .bind(to_db_id(user_id)?)
+                .bind(to_username)
+                .fetch_optional(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // List pending workspace transfers offered to the user
+    pub async fn list_incoming_transfers(
+        &self,
+        user_id: i64,
+    ) -> Result<Vec<WorkspaceTransferRow>, Error> {
+        observe_query("workspace.list_incoming_transfers", async move {
+            let query = format!(
+                "SELECT {TRANSFER_COLUMNS} FROM workspace_transfers
+                 WHERE to_user_id = $1 AND status = 'pending'
+                 ORDER BY created_at DESC"
+            );
+
+            sqlx::query_as::<_, WorkspaceTransferRow>(&query)
+                .bind(to_db_id(user_id)?)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Accept a pending transfer, the recipient becomes the owner and the previous owner a
+    // member with every permission. Returns `None` when the transfer is not found, or
+    // when the recipient left the workspace or the sender no longer owns it.
+    pub async fn accept_transfer(
+        &self,
+        user_id: i64,
+        transfer_id: i64,
+    ) -> Result<Option<WorkspaceTransferRow>, Error> {
+        observe_query("workspace.accept_transfer", async move {
+            let mut tx = self.pool.begin().await?;
+
+            let query = format!(
+                "UPDATE workspace_transfers SET status = 'accepted', resolved_at = NOW()
+                 WHERE id = $1 AND to_user_id = $2 AND status = 'pending'
+                 RETURNING {TRANSFER_COLUMNS}"
+            );
+            let Some(transfer) = sqlx::query_as::<_, WorkspaceTransferRow>(&query)
+                .bind(to_db_id(transfer_id)?)
+                .bind(to_db_id(user_id)?)
+                .fetch_optional(&mut *tx)
+                .await?
+            else {
+                return Ok(None);
+            };
+
+            let demoted = sqlx::query(
+                "UPDATE workspace_members SET role = 'member', template_id = NULL
+                 WHERE workspace_id = $1 AND user_id = $2 AND role = 'owner'",
+            )
+            .bind(transfer.workspace_id)
+            .bind(transfer.from_user_id)
+            .execute(&mut *tx)
+            .await?;
+            let promoted = sqlx::query(
+                "UPDATE workspace_members SET role = 'owner', template_id = NULL
+                 WHERE workspace_id = $1 AND user_id = $2",
+            )
+            .bind(transfer.workspace_id)
+            .bind(transfer.to_user_id)
+            .execute(&mut *tx)
+            .await?;
+            if demoted.rows_affected() == 0 || promoted.rows_affected() == 0 {
+                return Ok(None);
+            }
+
+            tx.commit().await?;
+            Ok(Some(transfer))
+        })
+        .await
+    }
+
+    // Decline a pending workspace transfer offered to the user
+    pub async fn decline_transfer(&self, user_id: i64, transfer_id: i64) -> Result<bool, Error> {
+        observe_query("workspace.decline_transfer", async move {
+            let result = sqlx::query(
+                "UPDATE workspace_transfers SET status = 'declined', resolved_at = NOW()
+                 WHERE id = $1 AND to_user_id = $2 AND status = 'pending'",
+            )
+            .bind(to_db_id(transfer_id)?)
+            .bind(to_db_id(user_id)?)
+            .execute(&self.pool)
+            .await?;
+
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    // List the permission templates of a workspace, by name
+    pub async fn list_templates(
+        &self,
+        workspace_id: i64,
+    ) -> Result<Vec<PermissionTemplateRow>, Error> {
+        observe_query("workspace.list_templates", async move {
+            let query = format!(
+                "SELECT {TEMPLATE_COLUMNS} FROM workspace_permission_templates t
+                 WHERE t.workspace_id = $1
+                 ORDER BY t.name, t.id"
+            );
+
+            sqlx::query_as::<_, PermissionTemplateRow>(&query)
+                .bind(to_db_id(workspace_id)?)
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await
+    }
+
+    // Store a permission template of a shared workspace owned by the user.
+    // Returns `None` when the workspace is not found, personal or not owned by the user.
+    pub async fn create_template(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        name: &str,
+        permissions: &[String],
+    ) -> Result<Option<PermissionTemplateRow>, Error> {
+        observe_query("workspace.create_template", async move {
+            sqlx::query_as::<_, PermissionTemplateRow>(
+                "INSERT INTO workspace_permission_templates (workspace_id, name, permissions)
+                 SELECT w.id, $3, $4
+                 FROM workspaces w
+                 JOIN workspace_members m
+                    ON m.workspace_id = w.id AND m.user_id = $2 AND m.role = 'owner'
+                 WHERE w.id = $1 AND w.personal_user_id IS NULL
+                 RETURNING id, name, permissions, created_at",
+            )
+            .bind(to_db_id(workspace_id)?)
+            .bind(to_db_id(user_id)?)
+            .bind(name)
+            .bind(permissions)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Rename a template of a workspace owned by the user or change its permissions,
+    // unset values are kept. Its members get the new permissions on their next request.
+    pub async fn update_template(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        template_id: i64,
+        name: Option<&str>,
+        permissions: Option<&[String]>,
+    ) -> Result<Option<PermissionTemplateRow>, Error> {
+        observe_query("workspace.update_template", async move {
+            sqlx::query_as::<_, PermissionTemplateRow>(
+                "UPDATE workspace_permission_templates t
+                 SET name = COALESCE($4, t.name), permissions = COALESCE($5, t.permissions)
+                 FROM workspace_members m
+                 WHERE t.id = $3 AND t.workspace_id = $1
+                   AND m.workspace_id = $1 AND m.user_id = $2 AND m.role = 'owner'
+                 RETURNING t.id, t.name, t.permissions, t.created_at",
+            )
+            .bind(to_db_id(workspace_id)?)
+            .bind(to_db_id(user_id)?)
+            .bind(to_db_id(template_id)?)
+            .bind(name)
+            .bind(permissions)
+            .fetch_optional(&self.pool)
+            .await
+        })
+        .await
+    }
+
+    // Delete a template of a workspace owned by the user, fails while members hold it
+    pub async fn delete_template(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        template_id: i64,
+    ) -> Result<bool, Error> {
+        observe_query("workspace.delete_template", async move {
+            let deleted = sqlx::query(
+                "DELETE FROM workspace_permission_templates t
+                 USING workspace_members m
+                 WHERE t.id = $3 AND t.workspace_id = $1
+                   AND m.workspace_id = $1 AND m.user_id = $2 AND m.role = 'owner'",
+            )
+            .bind(to_db_id(workspace_id)?)
+            .bind(to_db_id(user_id)?)
+            .bind(to_db_id(template_id)?)
+            .execute(&self.pool)
+            .await?;
+            Ok(deleted.rows_affected() > 0)
+        })
+        .await
+    }
+
+    // Set the template of a member, by a workspace owner. Owners can't be limited.
+    // `None` gives the member every permission.
+    pub async fn set_member_template(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        member_id: i64,
+        template_id: Option<i64>,
+    ) -> Result<bool, Error> {
+        observe_query("workspace.set_member_template", async move {
+            let updated = sqlx::query(
+                "UPDATE workspace_members m SET template_id = $4
+                 WHERE m.workspace_id = $1 AND m.user_id = $3 AND m.role <> 'owner'
+                   AND EXISTS(
+                        SELECT 1 FROM workspace_members o
+                        WHERE o.workspace_id = $1 AND o.user_id = $2 AND o.role = 'owner')
+                   AND ($4::INTEGER IS NULL OR EXISTS(
+                        SELECT 1 FROM workspace_permission_templates t
+                        WHERE t.id = $4 AND t.workspace_id = $1))",
+            )
+            .bind(to_db_id(workspace_id)?)
+            .bind(to_db_id(user_id)?)
+            .bind(to_db_id(member_id)?)
+            .bind(template_id.map(to_db_id).transpose()?)
+            .execute(&self.pool)
+            .await?;
+            Ok(updated.rows_affected() > 0)
+        })
+        .await
+    }
+
+    // Set the template of the members joining a shared workspace owned by the user
+    pub async fn set_default_template(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        template_id: Option<i64>,
+    ) -> Result<bool, Error> {
+        observe_query("workspace.set_default_template", async move {
+            let updated = sqlx::query(
+                "UPDATE workspaces w SET default_template_id = $3
+                 WHERE w.id = $1 AND w.personal_user_id IS NULL
+                   AND EXISTS(
+                        SELECT 1 FROM workspace_members o
+                        WHERE o.workspace_id = $1 AND o.user_id = $2 AND o.role = 'owner')
+                   AND ($3::INTEGER IS NULL OR EXISTS(
+                        SELECT 1 FROM workspace_permission_templates t
+                        WHERE t.id = $3 AND t.workspace_id = $1))",
+            )
+            .bind(to_db_id(workspace_id)?)
+            .bind(to_db_id(user_id)?)
+            .bind(template_id.map(to_db_id).transpose()?)
+            .execute(&self.pool)
+            .await?;
+            Ok(updated.rows_affected() > 0)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workspace_columns_match_row() {
+        for column in [
+            "w.id",
+            "w.name",
+            "AS personal",
+            "m.role",
+            "w.default_template_id",
+            "w.created_at",
+        ] {
+            assert!(WORKSPACE_COLUMNS.contains(column));
+        }
+    }
+}
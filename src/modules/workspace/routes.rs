@@ -0,0 +1,596 @@
+//! #`Workspace` Routes
+//! This module defines the HTTP routes for workspaces functionality.
+
+use axum::extract::Path;
+use axum::routing::{delete, get, post, put};
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Router};
+
+use crate::auth::Claims;
+use crate::modules::common::{respond, ErrorResponse};
+use crate::modules::email::mailer::BackgroundMailer;
+use crate::modules::workspace::interfaces::{
+    AcceptWorkspaceInvitationRequest, AssignTemplateRequest, CreateWorkspaceRequest,
+    InviteWorkspaceMemberRequest, PermissionTemplateRequest, PermissionTemplateResponse,
+    TransferWorkspaceRequest, WorkspaceInvitationResponse, WorkspaceMemberResponse,
+    WorkspaceMessageResponse, WorkspaceResponse, WorkspaceTokenResponse, WorkspaceTransferResponse,
+};
+use crate::modules::workspace::repository::WorkspaceRepository;
+use crate::modules::workspace::service::WorkspaceService;
+use crate::utils::json::Json;
+use crate::AppState;
+
+// Creates and returns the workspace routes
+pub fn workspace_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/workspaces",
+            get(list_workspaces_route).post(create_workspace_route),
+        )
+        .route("/workspaces/{id}/switch", post(switch_workspace_route))
+        .route("/workspaces/{id}/members", get(list_members_route))
+        .route(
+            "/workspaces/{id}/members/{user_id}",
+            delete(remove_member_route),
+        )
+        .route("/workspaces/{id}/invitations", post(invite_member_route))
+        .route(
+            "/workspaces/invitations/accept",
+            post(accept_invitation_route),
+        )
+        .route("/workspaces/{id}/transfer", post(transfer_workspace_route))
+        .route("/workspaces/transfers", get(list_incoming_transfers_route))
+        .route(
+            "/workspaces/transfers/{transfer_id}/accept",
+            post(accept_transfer_route),
+        )
+        .route(
+            "/workspaces/transfers/{transfer_id}/decline",
+            post(decline_transfer_route),
+        )
+        .route(
+            "/workspaces/{id}/templates",
+            get(list_templates_route).post(create_template_route),
+        )
+        .route(
+            "/workspaces/{id}/templates/{template_id}",
+            put(update_template_route).delete(delete_template_route),
+        )
+        .route(
+            "/workspaces/{id}/members/{user_id}/template",
+            put(set_member_template_route),
+        )
+        .route(
+            "/workspaces/{id}/default-template",
+            put(set_default_template_route),
+        )
+}
+
+fn workspace_service(app_state: &AppState) -> WorkspaceService {
+    WorkspaceService::new(
+        WorkspaceRepository::new(app_state.db_pool.clone()),
+        BackgroundMailer::new(app_state.mailer.clone(), app_state.tasks.clone()),
+    )
+}
+
+// Create Workspace Route
+#[utoipa::path(
+    post,
+    path = "/workspaces",
+    tag = "Workspaces",
+    request_body = CreateWorkspaceRequest,
+    responses(
+        (status = 201, description = "Workspace created successfully", body = WorkspaceResponse),
+        (status = 400, description = "Invalid workspace data", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn create_workspace_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Json(create_request): Json<CreateWorkspaceRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::CREATED,
+        workspace_service(&app_state)
+            .create_workspace(claims.user_id, create_request)
+            .await,
+    )
+}
+
+// List Workspaces Route
+#[utoipa::path(
+    get,
+    path = "/workspaces",
+    tag = "Workspaces",
+    responses(
+        (status = 200, description = "Workspaces of the user", body = [WorkspaceResponse]),
+        (status = 500, description = "Failed to list workspaces", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn list_workspaces_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        workspace_service(&app_state)
+            .list_workspaces(claims.user_id)
+            .await,
+    )
+}
+
+// Switch Workspace Route
+#[utoipa::path(
+    post,
+    path = "/workspaces/{id}/switch",
+    tag = "Workspaces",
+    params(
+        ("id" = i64, Path, description = "Workspace id")
+    ),
+    responses(
+        (status = 200, description = "Token working in the workspace", body = WorkspaceTokenResponse),
+        (status = 404, description = "Workspace not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn switch_workspace_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        workspace_service(&app_state)
+            .switch_workspace(
+                claims.user_id,
+                claims.session_id,
+                claims.amr,
+                id,
+                &app_state.encoding_key,
+                app_state.config.session.duration_minutes,
+            )
+            .await,
+    )
+}
+
+// List Workspace Members Route
+#[utoipa::path(
+    get,
+    path = "/workspaces/{id}/members",
+    tag = "Workspaces",
+    params(
+        ("id" = i64, Path, description = "Workspace id")
+    ),
+    responses(
+        (status = 200, description = "Members fetched successfully", body = [WorkspaceMemberResponse]),
+        (status = 404, description = "Workspace not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn list_members_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        workspace_service(&app_state)
+            .list_members(claims.user_id, id)
+            .await,
+    )
+}
+
+// Remove Workspace Member Route
+#[utoipa::path(
+    delete,
+    path = "/workspaces/{id}/members/{user_id}",
+    tag = "Workspaces",
+    params(
+        ("id" = i64, Path, description = "Workspace id"),
+        ("user_id" = i64, Path, description = "Member user id")
+    ),
+    responses(
+        (status = 200, description = "Member removed successfully", body = WorkspaceMessageResponse),
+        (status = 404, description = "Member not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn remove_member_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Path((id, user_id)): Path<(i64, i64)>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        workspace_service(&app_state)
+            .remove_member(claims.user_id, id, user_id)
+            .await,
+    )
+}
+
+// Invite Workspace Member Route
+#[utoipa::path(
+    post,
+    path = "/workspaces/{id}/invitations",
+    tag = "Workspaces",
+    params(
+        ("id" = i64, Path, description = "Workspace id")
+    ),
+    request_body = InviteWorkspaceMemberRequest,
+    responses(
+        (status = 201, description = "Invitation created, the token is only returned once", body = WorkspaceInvitationResponse),
+        (status = 400, description = "Invalid email", body = ErrorResponse),
+        (status = 404, description = "Workspace not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn invite_member_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<i64>,
+    Json(invite_request): Json<InviteWorkspaceMemberRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::CREATED,
+        workspace_service(&app_state)
+            .invite_member(claims.user_id, id, invite_request)
+            .await,
+    )
+}
+
+// Accept Workspace Invitation Route
+#[utoipa::path(
+    post,
+    path = "/workspaces/invitations/accept",
+    tag = "Workspaces",
+    request_body = AcceptWorkspaceInvitationRequest,
+    responses(
+        (status = 200, description = "Workspace joined", body = WorkspaceResponse),
+        (status = 400, description = "Invitation is invalid or expired", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn accept_invitation_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Json(accept_request): Json<AcceptWorkspaceInvitationRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        workspace_service(&app_state)
+            .accept_invitation(claims.user_id, accept_request)
+            .await,
+    )
+}
+
+// Transfer Workspace Route
+#[utoipa::path(
+    post,
+    path = "/workspaces/{id}/transfer",
+    tag = "Workspaces",
+    params(
+        ("id" = i64, Path, description = "Workspace id")
+    ),
+    request_body = TransferWorkspaceRequest,
+    responses(
+        (status = 201, description = "Workspace transfer offered", body = WorkspaceTransferResponse),
+        (status = 400, description = "Missing recipient", body = ErrorResponse),
+        (status = 404, description = "Workspace or recipient not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn transfer_workspace_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<i64>,
+    Json(transfer_request): Json<TransferWorkspaceRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::CREATED,
+        workspace_service(&app_state)
+            .transfer_workspace(claims.user_id, id, transfer_request)
+            .await,
+    )
+}
+
+// List Incoming Workspace Transfers Route
+#[utoipa::path(
+    get,
+    path = "/workspaces/transfers",
+    tag = "Workspaces",
+    responses(
+        (status = 200, description = "Pending transfers listed", body = [WorkspaceTransferResponse]),
+        (status = 500, description = "Failed to list transfers", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn list_incoming_transfers_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        workspace_service(&app_state)
+            .list_incoming_transfers(claims.user_id)
+            .await,
+    )
+}
+
+// Accept Workspace Transfer Route
+#[utoipa::path(
+    post,
+    path = "/workspaces/transfers/{transfer_id}/accept",
+    tag = "Workspaces",
+    params(
+        ("transfer_id" = i64, Path, description = "Transfer id")
+    ),
+    responses(
+        (status = 200, description = "Transfer accepted, the previous owner stays a member", body = WorkspaceTransferResponse),
+        (status = 404, description = "Transfer not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn accept_transfer_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Path(transfer_id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        workspace_service(&app_state)
+            .accept_transfer(claims.user_id, transfer_id)
+            .await,
+    )
+}
+
+// Decline Workspace Transfer Route
+#[utoipa::path(
+    post,
+    path = "/workspaces/transfers/{transfer_id}/decline",
+    tag = "Workspaces",
+    params(
+        ("transfer_id" = i64, Path, description = "Transfer id")
+    ),
+    responses(
+        (status = 200, description = "Transfer declined", body = WorkspaceMessageResponse),
+        (status = 404, description = "Transfer not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn decline_transfer_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Path(transfer_id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        workspace_service(&app_state)
+            .decline_transfer(claims.user_id, transfer_id)
+            .await,
+    )
+}
+
+// List Permission Templates Route
+#[utoipa::path(
+    get,
+    path = "/workspaces/{id}/templates",
+    tag = "Workspaces",
+    params(
+        ("id" = i64, Path, description = "Workspace id")
+    ),
+    responses(
+        (status = 200, description = "Templates fetched successfully", body = [PermissionTemplateResponse]),
+        (status = 404, description = "Workspace not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn list_templates_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        workspace_service(&app_state)
+            .list_templates(claims.user_id, id)
+            .await,
+    )
+}
+
+// Create Permission Template Route
+#[utoipa::path(
+    post,
+    path = "/workspaces/{id}/templates",
+    tag = "Workspaces",
+    params(
+        ("id" = i64, Path, description = "Workspace id")
+    ),
+    request_body = PermissionTemplateRequest,
+    responses(
+        (status = 201, description = "Template created", body = PermissionTemplateResponse),
+        (status = 400, description = "Invalid template", body = ErrorResponse),
+        (status = 404, description = "Workspace not found", body = ErrorResponse),
+        (status = 409, description = "Template name taken", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn create_template_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<i64>,
+    Json(template_request): Json<PermissionTemplateRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::CREATED,
+        workspace_service(&app_state)
+            .create_template(claims.user_id, id, template_request)
+            .await,
+    )
+}
+
+// Update Permission Template Route
+#[utoipa::path(
+    put,
+    path = "/workspaces/{id}/templates/{template_id}",
+    tag = "Workspaces",
+    params(
+        ("id" = i64, Path, description = "Workspace id"),
+        ("template_id" = i64, Path, description = "Template id")
+    ),
+    request_body = PermissionTemplateRequest,
+    responses(
+        (status = 200, description = "Template updated, its members get the new permissions", body = PermissionTemplateResponse),
+        (status = 400, description = "Invalid template", body = ErrorResponse),
+        (status = 404, description = "Template not found", body = ErrorResponse),
+        (status = 409, description = "Template name taken", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn update_template_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Path((id, template_id)): Path<(i64, i64)>,
+    Json(template_request): Json<PermissionTemplateRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        workspace_service(&app_state)
+            .update_template(claims.user_id, id, template_id, template_request)
+            .await,
+    )
+}
+
+// Delete Permission Template Route
+#[utoipa::path(
+    delete,
+    path = "/workspaces/{id}/templates/{template_id}",
+    tag = "Workspaces",
+    params(
+        ("id" = i64, Path, description = "Workspace id"),
+        ("template_id" = i64, Path, description = "Template id")
+    ),
+    responses(
+        (status = 200, description = "Template deleted successfully", body = WorkspaceMessageResponse),
+        (status = 404, description = "Template not found", body = ErrorResponse),
+        (status = 409, description = "Template still assigned to members", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn delete_template_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Path((id, template_id)): Path<(i64, i64)>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        workspace_service(&app_state)
+            .delete_template(claims.user_id, id, template_id)
+            .await,
+    )
+}
+
+// Set Member Permission Template Route
+#[utoipa::path(
+    put,
+    path = "/workspaces/{id}/members/{user_id}/template",
+    tag = "Workspaces",
+    params(
+        ("id" = i64, Path, description = "Workspace id"),
+        ("user_id" = i64, Path, description = "Member user id")
+    ),
+    request_body = AssignTemplateRequest,
+    responses(
+        (status = 200, description = "Member permissions updated", body = WorkspaceMessageResponse),
+        (status = 404, description = "Member or template not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn set_member_template_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Path((id, user_id)): Path<(i64, i64)>,
+    Json(assign_request): Json<AssignTemplateRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        workspace_service(&app_state)
+            .set_member_template(claims.user_id, id, user_id, assign_request)
+            .await,
+    )
+}
+
+// Set Default Permission Template Route
+#[utoipa::path(
+    put,
+    path = "/workspaces/{id}/default-template",
+    tag = "Workspaces",
+    params(
+        ("id" = i64, Path, description = "Workspace id")
+    ),
+    request_body = AssignTemplateRequest,
+    responses(
+        (status = 200, description = "Default template of the joining members updated", body = WorkspaceMessageResponse),
+        (status = 404, description = "Workspace or template not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+pub async fn set_default_template_route(
+    State(app_state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<i64>,
+    Json(assign_request): Json<AssignTemplateRequest>,
+) -> impl IntoResponse {
+    respond(
+        StatusCode::OK,
+        workspace_service(&app_state)
+            .set_default_template(claims.user_id, id, assign_request)
+            .await,
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workspace_routes_creation() {
+        let _routes = workspace_routes();
+        assert!(true);
+    }
+}
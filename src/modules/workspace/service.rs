@@ -0,0 +1,595 @@
+//! # `Workspace` Service
+//!
+//! This module contains the bussiness logic for workspaces. Lists and todos belong to a
+//! workspace, members switch between their workspaces with a token for each one.
+
+use email_address::EmailAddress;
+use jsonwebtoken::EncodingKey;
+use time::{Duration, OffsetDateTime};
+
+use crate::{
+    auth::generate_workspace_token,
+    modules::{
+        common::AppError,
+        email::{mailer::BackgroundMailer, templates::WORKSPACE_INVITATION},
+        workspace::{
+            interfaces::{
+                AcceptWorkspaceInvitationRequest, AssignTemplateRequest, CreateWorkspaceRequest,
+                InviteWorkspaceMemberRequest, PermissionTemplateRequest,
+                PermissionTemplateResponse, TransferWorkspaceRequest,
+                ValidatedCreateWorkspaceRequest, WorkspaceInvitationResponse,
+                WorkspaceMemberResponse, WorkspaceMessageResponse, WorkspaceResponse,
+                WorkspaceTokenResponse, WorkspaceTransferResponse,
+            },
+            permissions::parse_permissions,
+            repository::WorkspaceRepository,
+        },
+    },
+    utils::{
+        dates::format_timestamp,
+        token::{generate_random_token, hash_token},
+        validation::validate_required_fields,
+    },
+};
+
+// Lifetime of a workspace invitation
+const INVITATION_DAYS: i64 = 7;
+
+// Longest name of a permission template
+const MAX_TEMPLATE_NAME_CHARS: usize = 64;
+
+// Trimmed name of a permission template
+fn template_name(name: &str) -> Result<String, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::Validation(
+            "Template name cannot be empty".to_string(),
+        ));
+    }
+    if name.chars().count() > MAX_TEMPLATE_NAME_CHARS {
+        return Err(AppError::Validation(format!(
+            "Template name cannot exceed {MAX_TEMPLATE_NAME_CHARS} characters"
+        )));
+    }
+    Ok(name.to_string())
+}
+
+fn is_unique_violation(e: &sqlx::Error) -> bool {
+    matches!(e, sqlx::Error::Database(e) if e.is_unique_violation())
+}
+
+pub struct WorkspaceService {
+    workspace_repository: WorkspaceRepository,
+    mailer: BackgroundMailer,
+}
+
+impl WorkspaceService {
+    pub const fn new(workspace_repository: WorkspaceRepository, mailer: BackgroundMailer) -> Self {
+        Self {
+            workspace_repository,
+            mailer,
+        }
+    }
+
+    // Create a shared workspace owned by the user
+    pub async fn create_workspace(
+        &self,
+        user_id: i64,
+        create_request: CreateWorkspaceRequest,
+    ) -> Result<WorkspaceResponse, AppError> {
+        let validated: ValidatedCreateWorkspaceRequest =
+            validate_required_fields(&create_request, vec!["name"])
+                .map_err(AppError::InvalidFields)?;
+
+        match self
+            .workspace_repository
+            .create_workspace(user_id, validated.name.trim())
+            .await
+        {
+            Ok(workspace) => Ok(WorkspaceResponse::from(workspace)),
+            Err(e) => {
+                tracing::warn!("Error creating workspace: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // List the workspaces of the user
+    pub async fn list_workspaces(&self, user_id: i64) -> Result<Vec<WorkspaceResponse>, AppError> {
+        match self.workspace_repository.list_workspaces(user_id).await {
+            Ok(workspaces) => Ok(workspaces
+                .into_iter()
+                .map(WorkspaceResponse::from)
+                .collect()),
+            Err(e) => {
+                tracing::warn!("Error listing workspaces: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Issue a token of the same session working in one of the user workspaces
+    pub async fn switch_workspace(
+        &self,
+        user_id: i64,
+        session_id: Option<i64>,
+        amr: Vec<String>,
+        workspace_id: i64,
+        encoding_key: &EncodingKey,
+        session_duration: i64,
+    ) -> Result<WorkspaceTokenResponse, AppError> {
+        match self
+            .workspace_repository
+            .fetch_workspace(user_id, workspace_id)
+            .await
+        {
+            Ok(Some(_)) => {}
+            Ok(None) => return Err(AppError::NotFound("Workspace not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error fetching workspace: {}", e);
+                return Err(AppError::Database(e));
+            }
+        }
+
+        let token = generate_workspace_token(
+            session_duration,
+            user_id,
+            session_id,
+            amr,
+            Some(workspace_id),
+            encoding_key,
+        )?;
+
+        Ok(WorkspaceTokenResponse {
+            token,
+            workspace_id,
+        })
+    }
+
+    // List the members of a workspace, visible to its members
+    pub async fn list_members(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+    ) -> Result<Vec<WorkspaceMemberResponse>, AppError> {
+        match self
+            .workspace_repository
+            .fetch_workspace(user_id, workspace_id)
+            .await
+        {
+            Ok(Some(_)) => {}
+            Ok(None) => return Err(AppError::NotFound("Workspace not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error fetching workspace: {}", e);
+                return Err(AppError::Database(e));
+            }
+        }
+
+        match self.workspace_repository.list_members(workspace_id).await {
+            Ok(members) => Ok(members
+                .into_iter()
+                .map(WorkspaceMemberResponse::from)
+                .collect()),
+            Err(e) => {
+                tracing::warn!("Error listing workspace members: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Remove a member from a workspace, members may leave by themselves
+    pub async fn remove_member(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        member_id: i64,
+    ) -> Result<WorkspaceMessageResponse, AppError> {
+        match self
+            .workspace_repository
+            .remove_member(user_id, workspace_id, member_id)
+            .await
+        {
+            Ok(true) => Ok(WorkspaceMessageResponse {
+                message: "Member removed successfully".to_string(),
+            }),
+            Ok(false) => Err(AppError::NotFound("Member not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error removing workspace member: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Invite a user by email to a workspace owned by the user
+    pub async fn invite_member(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        invite_request: InviteWorkspaceMemberRequest,
+    ) -> Result<WorkspaceInvitationResponse, AppError> {
+        let Some(email) = invite_request
+            .email
+            .map(|email| email.trim().to_lowercase())
+        else {
+            return Err(AppError::Validation(
+                "Missing required fields: email".to_string(),
+            ));
+        };
+        if !EmailAddress::is_valid(&email) {
+            return Err(AppError::Validation("Email is not valid".to_string()));
+        }
+
+        let token = generate_random_token();
+        let expires_at = OffsetDateTime::now_utc() + Duration::days(INVITATION_DAYS);
+
+        match self
+            .workspace_repository
+            .create_invitation(
+                user_id,
+                workspace_id,
+                &email,
+                &hash_token(&token),
+                expires_at,
+            )
+            .await
+        {
+            Ok(Some(invitation)) => {
+                let expires_at = format_timestamp(invitation.expires_at).unwrap_or_default();
+                self.mailer.send(WORKSPACE_INVITATION.render(
+                    &invitation.email,
+                    &[
+                        ("token", token.as_str()),
+                        ("expires_at", expires_at.as_str()),
+                    ],
+                ));
+                Ok(WorkspaceInvitationResponse {
+                    id: i64::from(invitation.id),
+                    workspace_id: i64::from(invitation.workspace_id),
+                    email: invitation.email,
+                    token,
+                    expires_at: invitation.expires_at,
+                })
+            }
+            Ok(None) => Err(AppError::NotFound(
+                "Workspace not found or not shareable".to_string(),
+            )),
+            Err(e) => {
+                tracing::warn!("Error creating workspace invitation: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Offer the ownership of a shared workspace to another of its members
+    pub async fn transfer_workspace(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        transfer_request: TransferWorkspaceRequest,
+    ) -> Result<WorkspaceTransferResponse, AppError> {
+        let to_username = match transfer_request.to_username.as_deref().map(str::trim) {
+            Some(username) if !username.is_empty() => username.to_string(),
+            _ => {
+                return Err(AppError::Validation(
+                    "Missing required fields: to_username".to_string(),
+                ))
+            }
+        };
+
+        match self
+            .workspace_repository
+            .create_transfer(user_id, workspace_id, &to_username)
+            .await
+        {
+            Ok(Some(transfer)) => Ok(WorkspaceTransferResponse::from(transfer)),
+            Ok(None) => Err(AppError::NotFound(
+                "Workspace or recipient not found".to_string(),
+            )),
+            Err(e) => {
+                tracing::warn!("Error creating workspace transfer: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // List workspace transfers waiting for the user answer
+    pub async fn list_incoming_transfers(
+        &self,
+        user_id: i64,
+    ) -> Result<Vec<WorkspaceTransferResponse>, AppError> {
+        match self
+            .workspace_repository
+            .list_incoming_transfers(user_id)
+            .await
+        {
+            Ok(transfers) => Ok(transfers
+                .into_iter()
+                .map(WorkspaceTransferResponse::from)
+                .collect()),
+            Err(e) => {
+                tracing::warn!("Error listing workspace transfers: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Accept a transfer, becoming the workspace owner
+    pub async fn accept_transfer(
+        &self,
+        user_id: i64,
+        transfer_id: i64,
+    ) -> Result<WorkspaceTransferResponse, AppError> {
+        match self
+            .workspace_repository
+            .accept_transfer(user_id, transfer_id)
+            .await
+        {
+            Ok(Some(transfer)) => Ok(WorkspaceTransferResponse::from(transfer)),
+            Ok(None) => Err(AppError::NotFound("Transfer not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error accepting workspace transfer: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Decline a workspace transfer
+    pub async fn decline_transfer(
+        &self,
+        user_id: i64,
+        transfer_id: i64,
+    ) -> Result<WorkspaceMessageResponse, AppError> {
+        match self
+            .workspace_repository
+            .decline_transfer(user_id, transfer_id)
+            .await
+        {
+            Ok(true) => Ok(WorkspaceMessageResponse {
+                message: "Transfer declined".to_string(),
+            }),
+            Ok(false) => Err(AppError::NotFound("Transfer not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error declining workspace transfer: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // List the permission templates of a workspace, visible to its members
+    pub async fn list_templates(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+    ) -> Result<Vec<PermissionTemplateResponse>, AppError> {
+        match self
+            .workspace_repository
+            .fetch_workspace(user_id, workspace_id)
+            .await
+        {
+            Ok(Some(_)) => {}
+            Ok(None) => return Err(AppError::NotFound("Workspace not found".to_string())),
+            Err(e) => {
+                tracing::warn!("Error fetching workspace: {}", e);
+                return Err(AppError::Database(e));
+            }
+        }
+
+        match self.workspace_repository.list_templates(workspace_id).await {
+            Ok(templates) => Ok(templates
+                .into_iter()
+                .map(PermissionTemplateResponse::from)
+                .collect()),
+            Err(e) => {
+                tracing::warn!("Error listing permission templates: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Create a permission template in a shared workspace owned by the user
+    pub async fn create_template(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        template_request: PermissionTemplateRequest,
+    ) -> Result<PermissionTemplateResponse, AppError> {
+        let (Some(name), Some(permissions)) = (template_request.name, template_request.permissions)
+        else {
+            return Err(AppError::Validation(
+                "Missing required fields: name, permissions".to_string(),
+            ));
+        };
+        let name = template_name(&name)?;
+        let permissions = parse_permissions(&permissions).map_err(AppError::Validation)?;
+
+        match self
+            .workspace_repository
+            .create_template(user_id, workspace_id, &name, &permissions)
+            .await
+        {
+            Ok(Some(template)) => Ok(PermissionTemplateResponse::from(template)),
+            Ok(None) => Err(AppError::NotFound(
+                "Workspace not found or not shareable".to_string(),
+            )),
+            Err(e) if is_unique_violation(&e) => Err(AppError::Conflict(
+                "A template with this name already exists".to_string(),
+            )),
+            Err(e) => {
+                tracing::warn!("Error creating permission template: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Rename a permission template or change its permissions
+    pub async fn update_template(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        template_id: i64,
+        template_request: PermissionTemplateRequest,
+    ) -> Result<PermissionTemplateResponse, AppError> {
+        let name = template_request
+            .name
+            .as_deref()
+            .map(template_name)
+            .transpose()?;
+        let permissions = template_request
+            .permissions
+            .as_deref()
+            .map(parse_permissions)
+            .transpose()
+            .map_err(AppError::Validation)?;
+
+        match self
+            .workspace_repository
+            .update_template(
+                user_id,
+                workspace_id,
+                template_id,
+                name.as_deref(),
+                permissions.as_deref(),
+            )
+            .await
+        {
+            Ok(Some(template)) => Ok(PermissionTemplateResponse::from(template)),
+            Ok(None) => Err(AppError::NotFound("Template not found".to_string())),
+            Err(e) if is_unique_violation(&e) => Err(AppError::Conflict(
+                "A template with this name already exists".to_string(),
+            )),
+            Err(e) => {
+                tracing::warn!("Error updating permission template: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Delete a permission template no member holds anymore
+    pub async fn delete_template(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        template_id: i64,
+    ) -> Result<WorkspaceMessageResponse, AppError> {
+        match self
+            .workspace_repository
+            .delete_template(user_id, workspace_id, template_id)
+            .await
+        {
+            Ok(true) => Ok(WorkspaceMessageResponse {
+                message: "Template deleted successfully".to_string(),
+            }),
+            Ok(false) => Err(AppError::NotFound("Template not found".to_string())),
+            Err(sqlx::Error::Database(e)) if e.is_foreign_key_violation() => Err(
+                AppError::Conflict("Template is still assigned to members".to_string()),
+            ),
+            Err(e) => {
+                tracing::warn!("Error deleting permission template: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Limit a member to the permissions of a template, or give them every permission
+    pub async fn set_member_template(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        member_id: i64,
+        assign_request: AssignTemplateRequest,
+    ) -> Result<WorkspaceMessageResponse, AppError> {
+        match self
+            .workspace_repository
+            .set_member_template(user_id, workspace_id, member_id, assign_request.template_id)
+            .await
+        {
+            Ok(true) => Ok(WorkspaceMessageResponse {
+                message: "Member permissions updated successfully".to_string(),
+            }),
+            Ok(false) => Err(AppError::NotFound(
+                "Member or template not found".to_string(),
+            )),
+            Err(e) => {
+                tracing::warn!("Error setting member permission template: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Set the template given to the members joining the workspace
+    pub async fn set_default_template(
+        &self,
+        user_id: i64,
+        workspace_id: i64,
+        assign_request: AssignTemplateRequest,
+    ) -> Result<WorkspaceMessageResponse, AppError> {
+        match self
+            .workspace_repository
+            .set_default_template(user_id, workspace_id, assign_request.template_id)
+            .await
+        {
+            Ok(true) => Ok(WorkspaceMessageResponse {
+                message: "Default template updated successfully".to_string(),
+            }),
+            Ok(false) => Err(AppError::NotFound(
+                "Workspace or template not found".to_string(),
+            )),
+            Err(e) => {
+                tracing::warn!("Error setting default permission template: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+
+    // Join the workspace of an invitation sent to the user email
+    pub async fn accept_invitation(
+        &self,
+        user_id: i64,
+        accept_request: AcceptWorkspaceInvitationRequest,
+    ) -> Result<WorkspaceResponse, AppError> {
+        let Some(token) = accept_request.token else {
+            return Err(AppError::Validation(
+                "Missing required fields: token".to_string(),
+            ));
+        };
+
+        match self
+            .workspace_repository
+            .accept_invitation(user_id, &hash_token(token.trim()))
+            .await
+        {
+            Ok(Some(workspace)) => {
+                tracing::info!("User {} joined workspace {}", user_id, workspace.id);
+                Ok(WorkspaceResponse::from(workspace))
+            }
+            Ok(None) => Err(AppError::Validation(
+                "Invitation is invalid or expired".to_string(),
+            )),
+            Err(e) => {
+                tracing::warn!("Error accepting workspace invitation: {}", e);
+                Err(AppError::Database(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invitation_lifetime() {
+        let expires_at = OffsetDateTime::now_utc() + Duration::days(INVITATION_DAYS);
+        assert!(expires_at > OffsetDateTime::now_utc() + Duration::days(6));
+    }
+
+    #[test]
+    fn test_template_name() {
+        assert_eq!(
+            template_name("  Contractor ").ok(),
+            Some("Contractor".to_string())
+        );
+        assert!(template_name("   ").is_err());
+        assert!(template_name(&"x".repeat(MAX_TEMPLATE_NAME_CHARS + 1)).is_err());
+    }
+}
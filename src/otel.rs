@@ -0,0 +1,299 @@
+//! # Trace Export
+//!
+//! Spans of the requests and of the database queries, exported with OTLP over HTTP when
+//! the application is built with the `otel` feature and `OTEL_EXPORTER_OTLP_ENDPOINT` is
+//! set. The W3C `traceparent` header of the requests continues the trace of the caller.
+//! Sampling follows the standard `OTEL_TRACES_SAMPLER` and `OTEL_TRACES_SAMPLER_ARG`.
+
+use std::time::Duration;
+
+use axum::extract::{MatchedPath, Request};
+use tower_http::trace::OnResponse;
+use tracing::{field::Empty, Span};
+use tracing_subscriber::{registry::LookupSpan, Layer};
+
+use crate::utils::request_id::RequestId;
+
+#[cfg(feature = "otel")]
+use opentelemetry::{propagation::Extractor, trace::TracerProvider as _};
+#[cfg(feature = "otel")]
+use opentelemetry_sdk::{
+    propagation::TraceContextPropagator,
+    trace::{Sampler, SdkTracerProvider},
+    Resource,
+};
+#[cfg(feature = "otel")]
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+// Layer exporting the spans, added to the tracing subscriber
+pub type ExportLayer<S> = Box<dyn Layer<S> + Send + Sync + 'static>;
+
+// Sampler used when `OTEL_TRACES_SAMPLER` is not set
+const DEFAULT_SAMPLER: &str = "parentbased_always_on";
+
+fn env_value(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+}
+
+// Share of the traces kept, and whether the decision of the caller is followed
+#[derive(Clone, Copy, Debug, PartialEq)]
+// Only read when built with the `otel` feature
+#[cfg_attr(not(feature = "otel"), allow(dead_code))]
+pub struct TraceSampling {
+    pub parent_based: bool,
+    pub ratio: f64,
+}
+
+impl TraceSampling {
+    // Parse the sampler names of the OpenTelemetry specification, the argument is the
+    // ratio of `traceidratio`, 1 when not set
+    pub fn parse(sampler: &str, arg: Option<&str>) -> Result<Self, String> {
+        let (parent_based, name) = sampler
+            .strip_prefix("parentbased_")
+            .map_or((false, sampler), |name| (true, name));
+        let ratio = match name {
+            "always_on" => 1.0,
+            "always_off" => 0.0,
+            "traceidratio" => match arg {
+                None => 1.0,
+                Some(arg) => arg
+                    .trim()
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|ratio| (0.0..=1.0).contains(ratio))
+                    .ok_or_else(|| {
+                        format!("Invalid OTEL_TRACES_SAMPLER_ARG: {arg} is not between 0 and 1")
+                    })?,
+            },
+            _ => return Err(format!("Unknown OTEL_TRACES_SAMPLER: {sampler}")),
+        };
+        Ok(Self {
+            parent_based,
+            ratio,
+        })
+    }
+
+    pub fn from_env() -> Result<Self, String> {
+        Self::parse(
+            &env_value("OTEL_TRACES_SAMPLER").unwrap_or_else(|| DEFAULT_SAMPLER.to_string()),
+            env_value("OTEL_TRACES_SAMPLER_ARG").as_deref(),
+        )
+    }
+
+    #[cfg(feature = "otel")]
+    fn sampler(self) -> Sampler {
+        let sampler = if self.ratio >= 1.0 {
+            Sampler::AlwaysOn
+        } else if self.ratio <= 0.0 {
+            Sampler::AlwaysOff
+        } else {
+            Sampler::TraceIdRatioBased(self.ratio)
+        };
+        if self.parent_based {
+            Sampler::ParentBased(Box::new(sampler))
+        } else {
+            sampler
+        }
+    }
+}
+
+// Exporter of the spans, flushed at shutdown
+#[derive(Default)]
+pub struct TraceExport {
+    #[cfg(feature = "otel")]
+    provider: Option<SdkTracerProvider>,
+}
+
+impl TraceExport {
+    // Export the spans still buffered, waits for the collector
+    #[cfg_attr(
+        not(feature = "otel"),
+        allow(clippy::missing_const_for_fn, clippy::unused_self)
+    )]
+    pub fn shutdown(self) {
+        #[cfg(feature = "otel")]
+        if let Some(provider) = self.provider {
+            if let Err(e) = provider.shutdown() {
+                tracing::warn!("Failed to flush the exported traces: {}", e);
+            }
+        }
+    }
+}
+
+// Layer exporting the spans to the collector configured in the environment, none when
+// `OTEL_EXPORTER_OTLP_ENDPOINT` is not set
+pub fn trace_export_from_env<S>() -> Result<(Option<ExportLayer<S>>, TraceExport), String>
+where
+    S: tracing::Subscriber + for<'span> LookupSpan<'span> + Send + Sync,
+{
+    if env_value("OTEL_EXPORTER_OTLP_ENDPOINT").is_none()
+        && env_value("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT").is_none()
+    {
+        return Ok((None, TraceExport::default()));
+    }
+    otlp_layer(TraceSampling::from_env()?)
+}
+
+#[cfg(not(feature = "otel"))]
+#[allow(clippy::unnecessary_wraps)]
+fn otlp_layer<S>(_sampling: TraceSampling) -> Result<(Option<ExportLayer<S>>, TraceExport), String>
+where
+    S: tracing::Subscriber + for<'span> LookupSpan<'span> + Send + Sync,
+{
+    Err("OTEL_EXPORTER_OTLP_ENDPOINT needs the application built with the otel feature".to_string())
+}
+
+// The exporter reads the endpoint, headers and timeout from the `OTEL_EXPORTER_OTLP_*`
+// variables itself
+#[cfg(feature = "otel")]
+fn otlp_layer<S>(sampling: TraceSampling) -> Result<(Option<ExportLayer<S>>, TraceExport), String>
+where
+    S: tracing::Subscriber + for<'span> LookupSpan<'span> + Send + Sync,
+{
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .build()
+        .map_err(|e| format!("Failed to create OTLP exporter: {e}"))?;
+    let service_name =
+        env_value("OTEL_SERVICE_NAME").unwrap_or_else(|| env!("CARGO_PKG_NAME").to_string());
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_sampler(sampling.sampler())
+        .with_resource(Resource::builder().with_service_name(service_name).build())
+        .build();
+
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+    opentelemetry::global::set_tracer_provider(provider.clone());
+    let layer = tracing_opentelemetry::layer().with_tracer(provider.tracer(env!("CARGO_PKG_NAME")));
+
+    Ok((
+        Some(Box::new(layer)),
+        TraceExport {
+            provider: Some(provider),
+        },
+    ))
+}
+
+// Headers of a request read by the propagator
+#[cfg(feature = "otel")]
+struct HeaderExtractor<'a>(&'a axum::http::HeaderMap);
+
+#[cfg(feature = "otel")]
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(axum::http::HeaderName::as_str).collect()
+    }
+}
+
+// Server span of a request named after its route, child of the `traceparent` of the
+// caller. The span carries the request id, logged with every line of the request.
+pub fn request_span(request: &Request) -> Span {
+    let method = request.method();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or_else(|| request.uri().path(), MatchedPath::as_str);
+    let span = tracing::info_span!(
+        "request",
+        otel.name = %format_args!("{method} {route}"),
+        otel.kind = "server",
+        otel.status_code = Empty,
+        http.request.method = %method,
+        http.route = %route,
+        http.response.status_code = Empty,
+        request_id = Empty,
+    );
+    if let Some(request_id) = request.extensions().get::<RequestId>() {
+        span.record("request_id", request_id.as_str());
+    }
+
+    #[cfg(feature = "otel")]
+    {
+        let parent = opentelemetry::global::get_text_map_propagator(|propagator| {
+            propagator.extract(&HeaderExtractor(request.headers()))
+        });
+        if let Err(e) = span.set_parent(parent) {
+            tracing::debug!("Trace parent not applied: {}", e);
+        }
+    }
+
+    span
+}
+
+// Records the status of each response on the span of its request, and logs the response
+// at `info` with the access log or at `debug` otherwise
+#[derive(Clone, Copy, Debug)]
+pub struct RecordResponse {
+    access_log: bool,
+}
+
+impl RecordResponse {
+    pub const fn new(access_log: bool) -> Self {
+        Self { access_log }
+    }
+}
+
+impl<B> OnResponse<B> for RecordResponse {
+    fn on_response(self, response: &axum::http::Response<B>, latency: Duration, span: &Span) {
+        let status = response.status().as_u16();
+        span.record("http.response.status_code", status);
+        if response.status().is_server_error() {
+            span.record("otel.status_code", "error");
+        }
+
+        let latency_ms = latency.as_millis();
+        if self.access_log {
+            tracing::info!(status, latency_ms, "Request finished");
+        } else {
+            tracing::debug!(status, latency_ms, "Request finished");
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sampling() {
+        assert_eq!(
+            TraceSampling::parse("parentbased_always_on", None).unwrap(),
+            TraceSampling {
+                parent_based: true,
+                ratio: 1.0
+            }
+        );
+        assert_eq!(
+            TraceSampling::parse("always_off", None).unwrap(),
+            TraceSampling {
+                parent_based: false,
+                ratio: 0.0
+            }
+        );
+        assert_eq!(
+            TraceSampling::parse("parentbased_traceidratio", Some("0.25")).unwrap(),
+            TraceSampling {
+                parent_based: true,
+                ratio: 0.25
+            }
+        );
+        assert_eq!(
+            TraceSampling::parse("traceidratio", None).unwrap(),
+            TraceSampling::parse("always_on", None).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_sampling() {
+        assert!(TraceSampling::parse("sometimes", None).is_err());
+        assert!(TraceSampling::parse("traceidratio", Some("2")).is_err());
+        assert!(TraceSampling::parse("traceidratio", Some("half")).is_err());
+    }
+}
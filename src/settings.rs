@@ -0,0 +1,132 @@
+//! # Runtime Settings
+//!
+//! Settings that are safe to change while the server runs. They are read from
+//! the environment at startup and reloaded, together with the `.env` file, when
+//! the process receives `SIGHUP`.
+
+use tokio::sync::watch;
+use tracing_subscriber::{
+    fmt, layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter, Registry,
+};
+
+use crate::{
+    otel::{trace_export_from_env, TraceExport},
+    utils::client_ip::TrustedProxies,
+};
+
+// Log filter used when `RUST_LOG` is not set
+const DEFAULT_LOG_FILTER: &str = "info";
+
+// Handle used to swap the log filter of the running subscriber
+pub type LogReloadHandle = reload::Handle<EnvFilter, Registry>;
+
+// Settings that can change without a restart
+#[derive(Clone, Debug)]
+pub struct RuntimeSettings {
+    pub log_filter: String,
+    pub trusted_proxies: TrustedProxies,
+}
+
+impl RuntimeSettings {
+    // Validate raw settings values
+    pub fn parse(log_filter: &str, trusted_proxies: &str) -> Result<Self, String> {
+        EnvFilter::try_new(log_filter).map_err(|e| format!("Invalid RUST_LOG: {e}"))?;
+        let trusted_proxies = trusted_proxies
+            .parse::<TrustedProxies>()
+            .map_err(|e| format!("Invalid TRUSTED_PROXIES: {e}"))?;
+
+        Ok(Self {
+            log_filter: log_filter.to_string(),
+            trusted_proxies,
+        })
+    }
+
+    // Read the settings from the environment
+    pub fn from_env() -> Result<Self, String> {
+        Self::parse(
+            &log_filter_from_env(),
+            &std::env::var("TRUSTED_PROXIES").unwrap_or_default(),
+        )
+    }
+}
+
+fn log_filter_from_env() -> String {
+    std::env::var("RUST_LOG").unwrap_or_else(|_| DEFAULT_LOG_FILTER.to_string())
+}
+
+// Install the tracing subscriber with a reloadable filter, exporting the spans when a
+// collector is configured
+pub fn init_tracing() -> Result<(LogReloadHandle, TraceExport), String> {
+    let filter = EnvFilter::try_new(log_filter_from_env())
+        .unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_FILTER));
+    let (filter, handle) = reload::Layer::new(filter);
+    let (export_layer, trace_export) = trace_export_from_env()?;
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(export_layer)
+        .with(fmt::layer())
+        .init();
+
+    Ok((handle, trace_export))
+}
+
+// Re-read `.env` and the environment, then apply and publish the new settings.
+// Invalid settings are logged and the current ones are kept.
+fn reload_settings(log_handle: &LogReloadHandle, settings: &watch::Sender<RuntimeSettings>) {
+    if let Err(e) = dotenvy::dotenv_override() {
+        tracing::warn!("Could not reload .env file: {}", e);
+    }
+
+    let new_settings = match RuntimeSettings::from_env() {
+        Ok(new_settings) => new_settings,
+        Err(e) => {
+            tracing::error!("Settings not reloaded: {}", e);
+            return;
+        }
+    };
+
+    match EnvFilter::try_new(&new_settings.log_filter) {
+        Ok(filter) => {
+            if let Err(e) = log_handle.reload(filter) {
+                tracing::error!("Failed to reload log filter: {}", e);
+            }
+        }
+        Err(e) => tracing::error!("Invalid RUST_LOG: {}", e),
+    }
+
+    tracing::info!("Settings reloaded, log filter: {}", new_settings.log_filter);
+    settings.send_replace(new_settings);
+}
+
+// Reload the settings every time the process receives `SIGHUP`
+#[cfg(unix)]
+pub async fn reload_on_sighup(
+    log_handle: LogReloadHandle,
+    settings: watch::Sender<RuntimeSettings>,
+) -> std::io::Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangups = signal(SignalKind::hangup())?;
+    while hangups.recv().await.is_some() {
+        reload_settings(&log_handle, &settings);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_valid_settings() {
+        let settings = RuntimeSettings::parse("info,rust_todo_app=debug", "10.0.0.0/8");
+        assert!(settings.is_ok());
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_values() {
+        assert!(RuntimeSettings::parse("info", "not-a-cidr").is_err());
+        assert!(RuntimeSettings::parse("rust_todo_app=loud", "").is_err());
+    }
+}
@@ -0,0 +1,225 @@
+//! # Blob Storage
+//!
+//! Files uploaded or generated by the application, such as todo attachments, are kept
+//! in a blob storage selected with `STORAGE_BACKEND`: the local disk, an S3-compatible
+//! bucket or a Google Cloud Storage bucket. Each feature stores its blobs under its own
+//! key prefix, e.g. `todos/{id}/...` for attachments.
+
+use std::{
+    path::{Component, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use axum::{
+    body::{Body, Bytes},
+    http::Method,
+};
+use object_store::{
+    aws::AmazonS3Builder, gcp::GoogleCloudStorageBuilder, path::Path as ObjectPath, signer::Signer,
+    Attribute, Attributes, ObjectStore, PutOptions, PutPayload,
+};
+use tokio_util::io::ReaderStream;
+
+const DEFAULT_LOCAL_PATH: &str = "./data/attachments";
+const DEFAULT_URL_TTL_SECONDS: u64 = 300;
+
+// How the content of a blob is served
+pub enum Download {
+    // Presigned URL the client is redirected to
+    Redirect(String),
+    // Content streamed through the API
+    Stream { body: Body, size: u64 },
+}
+
+#[async_trait]
+pub trait BlobStorage: Send + Sync {
+    // Store the content under the key, replacing any previous content
+    async fn put(&self, key: &str, data: Bytes, content_type: &str) -> Result<(), String>;
+
+    // Serve the content stored under the key
+    async fn download(&self, key: &str) -> Result<Download, String>;
+
+    // Remove the content stored under the key, a missing key is not an error
+    async fn delete(&self, key: &str) -> Result<(), String>;
+}
+
+// Files stored on the local disk, streamed through the API
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    // Keys are generated by the server, anything but plain path segments is rejected
+    fn path(&self, key: &str) -> Result<PathBuf, String> {
+        let relative = std::path::Path::new(key);
+        let is_plain = relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+        if key.is_empty() || !is_plain {
+            return Err(format!("Invalid storage key: {key}"));
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait]
+impl BlobStorage for LocalStorage {
+    async fn put(&self, key: &str, data: Bytes, _content_type: &str) -> Result<(), String> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        tokio::fs::write(&path, &data)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    async fn download(&self, key: &str) -> Result<Download, String> {
+        let file = tokio::fs::File::open(self.path(key)?)
+            .await
+            .map_err(|e| e.to_string())?;
+        let size = file.metadata().await.map_err(|e| e.to_string())?.len();
+        Ok(Download::Stream {
+            body: Body::from_stream(ReaderStream::new(file)),
+            size,
+        })
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), String> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+// Objects stored in a cloud bucket, downloaded with presigned URLs
+pub struct BucketStorage<S> {
+    store: S,
+    url_ttl: Duration,
+}
+
+impl<S> BucketStorage<S> {
+    pub const fn new(store: S, url_ttl: Duration) -> Self {
+        Self { store, url_ttl }
+    }
+}
+
+#[async_trait]
+impl<S: ObjectStore + Signer> BlobStorage for BucketStorage<S> {
+    async fn put(&self, key: &str, data: Bytes, content_type: &str) -> Result<(), String> {
+        let options = PutOptions {
+            attributes: std::iter::once((Attribute::ContentType, content_type.to_string()))
+                .collect::<Attributes>(),
+            ..PutOptions::default()
+        };
+        self.store
+            .put_opts(&ObjectPath::from(key), PutPayload::from(data), options)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    async fn download(&self, key: &str) -> Result<Download, String> {
+        self.store
+            .signed_url(Method::GET, &ObjectPath::from(key), self.url_ttl)
+            .await
+            .map(|url| Download::Redirect(url.to_string()))
+            .map_err(|e| e.to_string())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), String> {
+        match self.store.delete(&ObjectPath::from(key)).await {
+            Ok(()) | Err(object_store::Error::NotFound { .. }) => Ok(()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+// Read the storage configuration from the environment. S3 is configured from the
+// `AWS_*` variables, e.g. `AWS_BUCKET`, `AWS_REGION` and `AWS_ENDPOINT`, and Google
+// Cloud Storage from the `GOOGLE_*` variables, e.g. `GOOGLE_BUCKET`.
+pub fn blob_storage_from_env() -> Result<Arc<dyn BlobStorage>, String> {
+    // `ATTACHMENT_URL_TTL_SECONDS` predates the other blobs and is still honored
+    let url_ttl = Duration::from_secs(
+        std::env::var("STORAGE_URL_TTL_SECONDS")
+            .or_else(|_| std::env::var("ATTACHMENT_URL_TTL_SECONDS"))
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_URL_TTL_SECONDS),
+    );
+
+    match std::env::var("STORAGE_BACKEND")
+        .unwrap_or_else(|_| "local".to_string())
+        .trim()
+        .to_ascii_lowercase()
+        .as_str()
+    {
+        "local" => Ok(Arc::new(LocalStorage::new(
+            std::env::var("STORAGE_LOCAL_PATH").unwrap_or_else(|_| DEFAULT_LOCAL_PATH.to_string()),
+        ))),
+        "s3" => {
+            let store = AmazonS3Builder::from_env()
+                .build()
+                .map_err(|e| format!("Invalid S3 storage configuration: {e}"))?;
+            Ok(Arc::new(BucketStorage::new(store, url_ttl)))
+        }
+        "gcs" => {
+            let store = GoogleCloudStorageBuilder::from_env()
+                .build()
+                .map_err(|e| format!("Invalid GCS storage configuration: {e}"))?;
+            Ok(Arc::new(BucketStorage::new(store, url_ttl)))
+        }
+        other => Err(format!("Unknown STORAGE_BACKEND: {other}")),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use axum::body::to_bytes;
+
+    fn temp_storage() -> LocalStorage {
+        LocalStorage::new(std::env::temp_dir().join(crate::utils::token::generate_random_token()))
+    }
+
+    #[test]
+    fn test_local_storage_rejects_traversal() {
+        let storage = temp_storage();
+        assert!(storage.path("todos/1/abc").is_ok());
+        assert!(storage.path("../etc/passwd").is_err());
+        assert!(storage.path("/etc/passwd").is_err());
+        assert!(storage.path("").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_local_storage_round_trip() {
+        let storage = temp_storage();
+        storage
+            .put("todos/1/file", Bytes::from_static(b"hello"), "text/plain")
+            .await
+            .unwrap();
+
+        match storage.download("todos/1/file").await.unwrap() {
+            Download::Stream { body, size } => {
+                assert_eq!(size, 5);
+                assert_eq!(to_bytes(body, 1024).await.unwrap(), "hello");
+            }
+            Download::Redirect(_) => unreachable!("local storage streams"),
+        }
+
+        storage.delete("todos/1/file").await.unwrap();
+        storage.delete("todos/1/file").await.unwrap();
+        assert!(storage.download("todos/1/file").await.is_err());
+    }
+}
@@ -7,19 +7,183 @@ use utoipa::{
     Modify, OpenApi,
 };
 
+use crate::auth::oauth::routes as oauth_routes;
 use crate::modules::{
     common::ErrorResponse,
-    user::interfaces::{FetchUserResponse, NewUserResponse, UserSignUp},
+    user::interfaces::{AvailabilityResponse, FetchUserResponse, NewUserResponse, UserSignUp},
 };
 use crate::modules::{
     health::{
-        interfaces::health_response::{HealthResponse, PingResponse},
+        interfaces::health_response::{
+            DependencyHealth, HealthResponse, PingResponse, ReadinessResponse,
+        },
         service,
     },
     user::interfaces::{LoginUserRequest, LoginUserResponse},
 };
 
+use crate::modules::admin::{
+    interfaces::{
+        AdminMessageResponse, AdminUserPageResponse, AdminUserResponse, CreateInviteCodeRequest,
+        EmailDiagnosticsResponse, InviteCodeCreatedResponse, InviteCodeResponse,
+        PasswordHashStatsResponse, TestEmailRequest, TestEmailResponse,
+    },
+    routes as admin_routes,
+};
+use crate::modules::api_key::{
+    interfaces::{
+        ApiKeyCreatedResponse, ApiKeyMessageResponse, ApiKeyResponse, CreateApiKeyRequest,
+    },
+    routes as api_key_routes,
+};
+use crate::modules::assignment::{
+    interfaces::{AssignTodoRequest, AssignmentResponse},
+    routes as assignment_routes,
+};
+use crate::modules::attachment::{
+    interfaces::{
+        AttachmentMessageResponse, AttachmentResponse, StorageUsageResponse,
+        UploadAttachmentRequest, WorkspaceStorageUsage,
+    },
+    routes as attachment_routes,
+};
+use crate::modules::audit::{
+    interfaces::{AuditEntryResponse, AuditPageResponse},
+    routes as audit_routes,
+};
+use crate::modules::badge::{interfaces::BadgesResponse, routes as badge_routes};
+use crate::modules::changes::{
+    interfaces::{ChangedLists, ChangedTags, ChangedTodos, ChangesResponse, TombstoneResponse},
+    routes as changes_routes,
+};
+use crate::modules::demo::{interfaces::DemoSessionResponse, routes as demo_routes};
+use crate::modules::email::mailer::EmailSettings;
+use crate::modules::export::{
+    interfaces::{DataExportResponse, ExportFormat, ExportStatus},
+    routes as export_routes,
+};
+use crate::modules::import::{
+    interfaces::{
+        ImportOutcome, ImportReport, ImportRowResponse, ImportSource, UploadImportRequest,
+    },
+    routes as import_routes,
+};
+use crate::modules::inbound::{
+    interfaces::{
+        InboundAddressResponse, InboundEmailRequest, InboundEmailResponse, InboundMessageResponse,
+        UpdateInboundSendersRequest,
+    },
+    routes as inbound_routes,
+};
+use crate::modules::invitation::{
+    interfaces::{
+        AcceptInvitationRequest, GuestInvitationResponse, GuestListResponse, GuestLoginResponse,
+        InviteGuestRequest,
+    },
+    routes as invitation_routes,
+};
+use crate::modules::item::{
+    interfaces::{
+        ChecklistProgress, ChecklistResponse, CreateItemRequest, ItemMessageResponse, ItemResponse,
+        ReorderItemsRequest, UpdateItemRequest,
+    },
+    routes as item_routes,
+};
+use crate::modules::list::{
+    interfaces::{
+        BurndownDayResponse, BurndownResponse, ListMemberResponse, ListMessageResponse,
+        ListRequest, ListResponse, ListRole, ListTransferResponse, ShareListRequest,
+        SharedListResponse, TransferListRequest, UpdateWipLimitsRequest, WipLimit,
+        WipLimitResponse, WipLimitsResponse,
+    },
+    routes as list_routes,
+};
+use crate::modules::meta::{
+    interfaces::{MetaLimits, MetaResponse, VersionResponse},
+    routes as meta_routes,
+};
+use crate::modules::moderation::{
+    interfaces::{BannedTermRequest, BannedTermResponse, FlagResponse, ModerationMessageResponse},
+    routes as moderation_routes,
+};
+use crate::modules::preference::{
+    interfaces::{
+        FormatSettingsResponse, NotificationSettings, PreferencesResponse, QuietHours,
+        SettingsExport, UpdatePreferencesRequest,
+    },
+    routes as preference_routes,
+};
+use crate::modules::presence::{
+    interfaces::{PresenceCommand, PresenceEvent},
+    routes as presence_routes,
+};
+use crate::modules::reminder::{
+    interfaces::{CreateReminderRequest, ReminderMessageResponse, ReminderResponse},
+    routes as reminder_routes,
+};
+use crate::modules::report::{
+    interfaces::{
+        ReportMessageResponse, StaleReportResponse, StaleSubscriptionRequest,
+        StaleSubscriptionResponse, StaleTodoResponse,
+    },
+    routes as report_routes,
+};
+use crate::modules::search::routes as search_routes;
+use crate::modules::security_webhook::{
+    interfaces::{
+        CreateSecurityWebhookRequest, SecurityWebhookCreatedResponse,
+        SecurityWebhookMessageResponse, SecurityWebhookResponse,
+    },
+    routes as security_webhook_routes,
+};
+use crate::modules::session::{
+    interfaces::{SessionMessageResponse, SessionResponse},
+    routes as session_routes,
+};
+use crate::modules::sync::{
+    interfaces::{
+        SyncCommand, SyncConflict, SyncCreated, SyncEvent, SyncRequest, SyncResolution,
+        SyncResponse, SyncTodoChange,
+    },
+    routes as sync_routes,
+};
+use crate::modules::tag::{
+    interfaces::{
+        AttachTagRequest, BulkTagFilter, BulkTagRequest, BulkTagResponse, CreateTagRequest,
+        TagMessageResponse, TagResponse,
+    },
+    routes as tag_routes,
+};
+use crate::modules::todo::{
+    interfaces::{
+        AssignListRequest, CheckDuplicatesRequest, CreateTodoRequest, DuplicateTodoResponse,
+        StatsBreakdownResponse, StatsGroupResponse, StatsInterval, StatsPeriodResponse,
+        TodoExportLine, TodoFieldChange, TodoHistoryEntryResponse, TodoMessageResponse,
+        TodoOccurrencesResponse, TodoPageResponse, TodoResponse, TodoStatsResponse, TodoStatus,
+        UpdateTodoRequest,
+    },
+    routes as todo_routes,
+};
+use crate::modules::two_factor::{
+    interfaces::{
+        EnableTwoFactorResponse, RecoveryCodesResponse, TwoFactorCodeRequest, TwoFactorLoginRequest,
+    },
+    routes as two_factor_routes,
+};
 use crate::modules::user::routes as user_routes;
+use crate::modules::workspace::{
+    interfaces::{
+        AcceptWorkspaceInvitationRequest, AssignTemplateRequest, CreateWorkspaceRequest,
+        InviteWorkspaceMemberRequest, PermissionTemplateRequest, PermissionTemplateResponse,
+        TransferWorkspaceRequest, WorkspaceInvitationResponse, WorkspaceMemberResponse,
+        WorkspaceMessageResponse, WorkspaceResponse, WorkspaceTokenResponse,
+        WorkspaceTransferResponse,
+    },
+    permissions::WorkspacePermission,
+    routes as workspace_routes,
+};
+use crate::utils::validation::FieldError;
+use crate::workers::{WorkerState, WorkerStatus};
 
 /// `OpenAPI` documentation configuration
 ///
@@ -30,21 +194,215 @@ use crate::modules::user::routes as user_routes;
     info(
         title = "Todo App API",
         version = "1.0.0",
-        description = "API documentation for the Todo App built with Axum and Utoipa.",
+        description = "API documentation for the Todo App built with Axum and Utoipa. Timestamps are RFC 3339 strings in UTC, e.g. `2025-03-10T12:00:00Z`.",
     ),
     paths(
         service::health_check,
+        service::liveness,
+        service::dependency_readiness,
         service::ping,
+        service::readiness,
         service::test_login,
         user_routes::create_user_route,
+        user_routes::availability_route,
         user_routes::login_user_route,
         user_routes::fetch_user_route,
         user_routes::update_user_route,
         user_routes::delete_user_route,
         user_routes::update_password_route,
+        user_routes::reset_password_route,
+        user_routes::verify_email_route,
+        user_routes::resend_verification_route,
+        list_routes::create_list_route,
+        list_routes::list_lists_route,
+        list_routes::rename_list_route,
+        list_routes::archive_list_route,
+        list_routes::unarchive_list_route,
+        list_routes::delete_list_route,
+        list_routes::transfer_list_route,
+        list_routes::list_incoming_transfers_route,
+        list_routes::accept_transfer_route,
+        list_routes::decline_transfer_route,
+        list_routes::share_list_route,
+        list_routes::list_members_route,
+        list_routes::remove_member_route,
+        list_routes::list_shared_lists_route,
+        list_routes::burndown_route,
+        list_routes::wip_limits_route,
+        list_routes::update_wip_limits_route,
+        todo_routes::create_todo_route,
+        todo_routes::check_duplicates_route,
+        todo_routes::list_todos_route,
+        search_routes::search_todos_route,
+        todo_routes::today_view_route,
+        todo_routes::upcoming_view_route,
+        todo_routes::todo_stats_route,
+        todo_routes::list_stats_route,
+        todo_routes::tag_stats_route,
+        todo_routes::fetch_todo_route,
+        todo_routes::update_todo_route,
+        todo_routes::assign_list_route,
+        todo_routes::preview_occurrences_route,
+        todo_routes::delete_todo_route,
+        todo_routes::list_trash_route,
+        todo_routes::list_scheduled_route,
+        todo_routes::export_todos_route,
+        todo_routes::restore_todo_route,
+        todo_routes::todo_history_route,
+        todo_routes::revert_todo_route,
+        todo_routes::archive_todo_route,
+        todo_routes::unarchive_todo_route,
+        tag_routes::create_tag_route,
+        tag_routes::list_tags_route,
+        tag_routes::delete_tag_route,
+        tag_routes::bulk_attach_tag_route,
+        tag_routes::bulk_detach_tag_route,
+        tag_routes::list_todo_tags_route,
+        tag_routes::attach_tag_route,
+        tag_routes::detach_tag_route,
+        item_routes::create_item_route,
+        item_routes::list_items_route,
+        item_routes::update_item_route,
+        item_routes::toggle_item_route,
+        item_routes::reorder_items_route,
+        item_routes::delete_item_route,
+        reminder_routes::create_reminder_route,
+        reminder_routes::list_reminders_route,
+        reminder_routes::cancel_reminder_route,
+        assignment_routes::assign_todo_route,
+        assignment_routes::list_assigned_route,
+        assignment_routes::accept_assignment_route,
+        assignment_routes::decline_assignment_route,
+        report_routes::stale_report_route,
+        report_routes::fetch_subscription_route,
+        report_routes::subscribe_route,
+        report_routes::unsubscribe_route,
+        session_routes::list_sessions_route,
+        session_routes::revoke_session_route,
+        session_routes::logout_route,
+        two_factor_routes::enable_two_factor_route,
+        two_factor_routes::verify_two_factor_route,
+        two_factor_routes::two_factor_login_route,
+        oauth_routes::oauth_start_route,
+        oauth_routes::oauth_callback_route,
+        preference_routes::fetch_preferences_route,
+        preference_routes::update_preferences_route,
+        preference_routes::export_settings_route,
+        preference_routes::import_settings_route,
+        preference_routes::format_settings_route,
+        badge_routes::fetch_badges_route,
+        badge_routes::mark_read_route,
+        demo_routes::create_demo_session_route,
+        invitation_routes::invite_guest_route,
+        invitation_routes::accept_invitation_route,
+        invitation_routes::fetch_guest_list_route,
+        presence_routes::list_live_route,
+        presence_routes::guest_list_live_route,
+        sync_routes::sync_delta_route,
+        sync_routes::sync_route,
+        sync_routes::sync_socket_route,
+        changes_routes::list_changes_route,
+        attachment_routes::upload_attachment_route,
+        attachment_routes::list_attachments_route,
+        attachment_routes::download_attachment_route,
+        attachment_routes::delete_attachment_route,
+        attachment_routes::storage_usage_route,
+        export_routes::export_account_route,
+        export_routes::fetch_export_route,
+        export_routes::download_export_route,
+        import_routes::import_route,
+        workspace_routes::create_workspace_route,
+        workspace_routes::list_workspaces_route,
+        workspace_routes::switch_workspace_route,
+        workspace_routes::list_members_route,
+        workspace_routes::remove_member_route,
+        workspace_routes::invite_member_route,
+        workspace_routes::accept_invitation_route,
+        workspace_routes::transfer_workspace_route,
+        workspace_routes::list_incoming_transfers_route,
+        workspace_routes::accept_transfer_route,
+        workspace_routes::decline_transfer_route,
+        workspace_routes::list_templates_route,
+        workspace_routes::create_template_route,
+        workspace_routes::update_template_route,
+        workspace_routes::delete_template_route,
+        workspace_routes::set_member_template_route,
+        workspace_routes::set_default_template_route,
+        meta_routes::meta_route,
+        meta_routes::version_route,
+        admin_routes::list_users_route,
+        admin_routes::disable_user_route,
+        admin_routes::enable_user_route,
+        admin_routes::delete_user_route,
+        admin_routes::email_diagnostics_route,
+        admin_routes::test_email_route,
+        admin_routes::create_invite_code_route,
+        admin_routes::list_invite_codes_route,
+        admin_routes::delete_invite_code_route,
+        admin_routes::password_hash_stats_route,
+        moderation_routes::list_banned_terms_route,
+        moderation_routes::add_banned_term_route,
+        moderation_routes::delete_banned_term_route,
+        moderation_routes::list_flags_route,
+        moderation_routes::dismiss_flag_route,
+        moderation_routes::remove_flag_route,
+        audit_routes::list_user_audit_route,
+        audit_routes::list_admin_audit_route,
+        security_webhook_routes::create_security_webhook_route,
+        security_webhook_routes::list_security_webhooks_route,
+        security_webhook_routes::delete_security_webhook_route,
+        api_key_routes::create_api_key_route,
+        api_key_routes::list_api_keys_route,
+        api_key_routes::revoke_api_key_route,
+        api_key_routes::create_signed_todo_route,
+        inbound_routes::fetch_inbound_address_route,
+        inbound_routes::create_inbound_address_route,
+        inbound_routes::update_inbound_senders_route,
+        inbound_routes::delete_inbound_address_route,
+        inbound_routes::inbound_email_route,
     ),
     components(
-        schemas(HealthResponse, PingResponse, ErrorResponse, NewUserResponse, UserSignUp, LoginUserRequest, LoginUserResponse, FetchUserResponse)
+        schemas(HealthResponse, PingResponse, ErrorResponse, FieldError, NewUserResponse, UserSignUp, AvailabilityResponse, LoginUserRequest, LoginUserResponse, FetchUserResponse),
+        schemas(ListRequest, ListResponse, ListMessageResponse, TransferListRequest, ListTransferResponse),
+        schemas(ShareListRequest, ListMemberResponse, ListRole, SharedListResponse),
+        schemas(BurndownResponse, BurndownDayResponse),
+        schemas(WipLimit, UpdateWipLimitsRequest, WipLimitResponse, WipLimitsResponse),
+        schemas(CreateTodoRequest, UpdateTodoRequest, AssignListRequest, TodoResponse, TodoStatus, TodoPageResponse, TodoMessageResponse, TodoOccurrencesResponse, TodoStatsResponse, TodoHistoryEntryResponse, TodoFieldChange, CheckDuplicatesRequest, DuplicateTodoResponse, TodoExportLine),
+        schemas(StatsBreakdownResponse, StatsGroupResponse, StatsPeriodResponse, StatsInterval),
+        schemas(CreateTagRequest, AttachTagRequest, TagResponse, TagMessageResponse),
+        schemas(BulkTagFilter, BulkTagRequest, BulkTagResponse),
+        schemas(CreateItemRequest, UpdateItemRequest, ReorderItemsRequest, ItemResponse, ItemMessageResponse, ChecklistProgress, ChecklistResponse),
+        schemas(CreateReminderRequest, ReminderResponse, ReminderMessageResponse),
+        schemas(AssignTodoRequest, AssignmentResponse),
+        schemas(StaleReportResponse, StaleTodoResponse, StaleSubscriptionRequest, StaleSubscriptionResponse, ReportMessageResponse),
+        schemas(SessionResponse, SessionMessageResponse),
+        schemas(EnableTwoFactorResponse, TwoFactorCodeRequest, RecoveryCodesResponse, TwoFactorLoginRequest),
+        schemas(PreferencesResponse, UpdatePreferencesRequest, QuietHours),
+        schemas(SettingsExport, NotificationSettings),
+        schemas(FormatSettingsResponse),
+        schemas(BadgesResponse),
+        schemas(DemoSessionResponse),
+        schemas(ReadinessResponse, WorkerStatus, WorkerState),
+        schemas(DependencyHealth),
+        schemas(InviteGuestRequest, GuestInvitationResponse, AcceptInvitationRequest, GuestLoginResponse, GuestListResponse),
+        schemas(PresenceEvent, PresenceCommand),
+        schemas(SyncRequest, SyncTodoChange, SyncResponse, SyncCreated, SyncConflict, SyncResolution),
+        schemas(SyncCommand, SyncEvent),
+        schemas(ChangesResponse, ChangedTodos, ChangedLists, ChangedTags, TombstoneResponse),
+        schemas(UploadAttachmentRequest, AttachmentResponse, AttachmentMessageResponse),
+        schemas(StorageUsageResponse, WorkspaceStorageUsage),
+        schemas(DataExportResponse, ExportFormat, ExportStatus),
+        schemas(UploadImportRequest, ImportReport, ImportRowResponse, ImportOutcome, ImportSource),
+        schemas(CreateWorkspaceRequest, WorkspaceResponse, WorkspaceTokenResponse, WorkspaceMemberResponse, WorkspaceMessageResponse, TransferWorkspaceRequest, WorkspaceTransferResponse),
+        schemas(InviteWorkspaceMemberRequest, WorkspaceInvitationResponse, AcceptWorkspaceInvitationRequest),
+        schemas(PermissionTemplateRequest, PermissionTemplateResponse, AssignTemplateRequest, WorkspacePermission),
+        schemas(CreateApiKeyRequest, ApiKeyResponse, ApiKeyCreatedResponse, ApiKeyMessageResponse),
+        schemas(MetaResponse, MetaLimits, VersionResponse),
+        schemas(AdminUserResponse, AdminUserPageResponse, AdminMessageResponse, EmailSettings, EmailDiagnosticsResponse, TestEmailRequest, TestEmailResponse, CreateInviteCodeRequest, InviteCodeResponse, InviteCodeCreatedResponse, PasswordHashStatsResponse),
+        schemas(BannedTermRequest, BannedTermResponse, FlagResponse, ModerationMessageResponse),
+        schemas(AuditEntryResponse, AuditPageResponse),
+        schemas(CreateSecurityWebhookRequest, SecurityWebhookCreatedResponse, SecurityWebhookResponse, SecurityWebhookMessageResponse),
+        schemas(InboundAddressResponse, UpdateInboundSendersRequest, InboundMessageResponse, InboundEmailRequest, InboundEmailResponse)
     ),
     security(
         ("bearer_auth" = [])
@@ -56,9 +414,63 @@ use crate::modules::user::routes as user_routes;
         (name = "SignUp",
         description = "User registration and signup endpoints."),
         (name = "Login",
-        description = "User login endpoints."),
+        description = "User login endpoints. Browser frontends can log in with `cookie` to keep the session in an HttpOnly cookie, the requests changing state then repeat the returned CSRF token in the X-CSRF-Token header."),
         (name = "User Management",
-        description = "User management endpoints.")
+        description = "User management endpoints."),
+        (name = "Sessions",
+        description = "Devices signed in to the account, each login opens a session that can be revoked."),
+        (name = "Two-Factor Authentication",
+        description = "TOTP second factor, logins then exchange a pre-auth token and a code for their session token."),
+        (name = "OAuth",
+        description = "Login with Google or GitHub, linked to the account with the same verified email."),
+        (name = "Preferences",
+        description = "Timezone and quiet hours of the user, non-critical reminders are held during the quiet hours. The settings can be exported and imported into another account."),
+        (name = "Badges",
+        description = "Unread counters of the user, kept up to date on each write so clients don't fetch the collections to count them"),
+        (name = "Demo",
+        description = "Sandbox sessions to try the API without registering. The throwaway user and its sample todos are deleted once the token expires."),
+        (name = "Lists",
+        description = "Endpoints to organize todos into lists and share them with other users."),
+        (name = "Todos",
+        description = "Todo management endpoints."),
+        (name = "Tags",
+        description = "Endpoints to label todos with tags."),
+        (name = "Checklist",
+        description = "Endpoints to manage checklist items under a todo."),
+        (name = "Reminders",
+        description = "Reminders of todos at a fixed date or relative to their due date, sent by a background dispatcher when due."),
+        (name = "Assignments",
+        description = "Todos delegated to another workspace member, waiting in their review queue until accepted or declined."),
+        (name = "Reports",
+        description = "Stale todos report and its optional monthly review email."),
+        (name = "Invitations",
+        description = "Guest invitations to shared lists."),
+        (name = "Presence",
+        description = "WebSockets broadcasting who is viewing and typing on a list."),
+        (name = "Sync",
+        description = "Offline sync of todos, the changes feed for polling clients and the sync socket pushing changes to every device."),
+        (name = "Attachments",
+        description = "Files attached to todos, stored on local disk or S3, and the storage quota of each user."),
+        (name = "Export",
+        description = "Export of all the data of the account as JSON or CSV. Large accounts are exported in the background, the file is downloaded once ready and kept for 7 days."),
+        (name = "Import",
+        description = "Import of the todos exported from Todoist, Trello or a CSV file into the workspace, with a dry run previewing the outcome of each row."),
+        (name = "Workspaces",
+        description = "Workspaces holding the lists and todos of a team, and their members."),
+        (name = "Meta",
+        description = "Description of the deployment, so a single frontend build adapts to each deployment, and the build of the running binary."),
+        (name = "Admin",
+        description = "User accounts managed by the administrators, disabled accounts can't log in, and the invite codes required to sign up when `SIGNUP_MODE` is `invite`."),
+        (name = "Moderation",
+        description = "Banned terms refused in the list names and review queue of the names flagged by the moderation API, for administrators."),
+        (name = "Audit",
+        description = "Log of the logins, password changes and administrative actions, for the account owner and the administrators."),
+        (name = "Security Webhooks",
+        description = "Webhooks receiving the logins, logins from a new device and password changes of the account, signed with HMAC-SHA256 in the X-Security-Signature header."),
+        (name = "Api Keys",
+        description = "Keys of the server-side integrations. Their requests carry the key id in X-Api-Key, the unix time in X-Api-Timestamp and, in X-Api-Signature, the hex HMAC-SHA256 of `<timestamp>\\n<METHOD>\\n<path and query>\\n<hex SHA-256 of the body>` keyed with the secret. Timestamps more than 5 minutes off and reused signatures are rejected."),
+        (name = "Inbound Email",
+        description = "Email addresses of the lists. Emails sent to them by the list owner or an allowed sender become todos, posted by the email provider to the inbound email webhook.")
     )
 )]
 pub struct ApiDoc;
@@ -72,6 +484,14 @@ impl Modify for SecurityAddon {
                 "jwt_auth",
                 SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("Authorization"))),
             );
+            components.add_security_scheme(
+                "session_cookie",
+                SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new("session"))),
+            );
+            components.add_security_scheme(
+                "api_key_signature",
+                SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-Api-Signature"))),
+            );
         } else {
             tracing::warn!("No components registered in OpenAPI spec when adding security scheme.");
         }
@@ -107,6 +527,10 @@ mod tests {
         assert!(openapi.components.is_some());
         let components = openapi.components.unwrap();
         assert!(components.security_schemes.contains_key("jwt_auth"));
+        assert!(components.security_schemes.contains_key("session_cookie"));
+        assert!(components
+            .security_schemes
+            .contains_key("api_key_signature"));
     }
 
     #[test]
@@ -121,4 +545,13 @@ mod tests {
         addon.modify(&mut openapi);
         assert!(openapi.components.is_none());
     }
+
+    #[test]
+    fn test_timestamps_documented_as_date_time() {
+        let doc = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let user = &doc["components"]["schemas"]["FetchUserResponse"]["properties"];
+        assert_eq!(user["created_at"]["format"], "date-time");
+        let todo = &doc["components"]["schemas"]["TodoResponse"]["properties"];
+        assert_eq!(todo["due_at"]["format"], "date-time");
+    }
 }
@@ -0,0 +1,304 @@
+//! # Postman Export
+//!
+//! Converts the generated `OpenAPI` document into a Postman collection (v2.1) and a
+//! matching environment. Requests use the `baseUrl` and `token` variables, so the
+//! same collection works against any deployment.
+
+use serde_json::{json, Map, Value};
+use utoipa::openapi::OpenApi;
+
+const COLLECTION_SCHEMA: &str =
+    "https://schema.getpostman.com/json/collection/v2.1.0/collection.json";
+
+const BASE_URL_VARIABLE: &str = "baseUrl";
+const TOKEN_VARIABLE: &str = "token";
+
+// HTTP methods of an `OpenAPI` path item, in the order they are exported
+const METHODS: [&str; 7] = ["get", "post", "put", "patch", "delete", "head", "options"];
+
+// Folder of the operations without tag
+const UNTAGGED_FOLDER: &str = "Other";
+
+// Nesting depth followed when building example bodies, guards against recursive schemas
+const MAX_EXAMPLE_DEPTH: usize = 5;
+
+// Build the Postman collection of the API, one folder per tag
+pub fn postman_collection(openapi: &OpenApi) -> Result<Value, serde_json::Error> {
+    let document = serde_json::to_value(openapi)?;
+
+    // Folders follow the tag order of the document
+    let mut folders: Vec<(String, Vec<Value>)> = document["tags"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|tag| tag["name"].as_str())
+        .map(|name| (name.to_string(), Vec::new()))
+        .collect();
+
+    for (path, item) in document["paths"].as_object().into_iter().flatten() {
+        for method in METHODS {
+            let Some(operation) = item.get(method) else {
+                continue;
+            };
+
+            let folder = operation["tags"][0].as_str().unwrap_or(UNTAGGED_FOLDER);
+            let request = request_item(path, method, operation, &document);
+            match folders.iter_mut().find(|(name, _)| name == folder) {
+                Some((_, requests)) => requests.push(request),
+                None => folders.push((folder.to_string(), vec![request])),
+            }
+        }
+    }
+
+    let items: Vec<Value> = folders
+        .into_iter()
+        .filter(|(_, requests)| !requests.is_empty())
+        .map(|(name, requests)| json!({ "name": name, "item": requests }))
+        .collect();
+
+    Ok(json!({
+        "info": {
+            "name": document["info"]["title"],
+            "description": document["info"]["description"],
+            "version": document["info"]["version"],
+            "schema": COLLECTION_SCHEMA,
+        },
+        "auth": bearer_auth(),
+        "variable": [
+            { "key": BASE_URL_VARIABLE, "value": "" },
+            { "key": TOKEN_VARIABLE, "value": "" },
+        ],
+        "item": items,
+    }))
+}
+
+// Build the Postman environment defining the collection variables
+pub fn postman_environment(name: &str, base_url: &str) -> Value {
+    json!({
+        "name": name,
+        "values": [
+            { "key": BASE_URL_VARIABLE, "value": base_url, "type": "default", "enabled": true },
+            { "key": TOKEN_VARIABLE, "value": "", "type": "secret", "enabled": true },
+        ],
+        "_postman_variable_scope": "environment",
+    })
+}
+
+// Bearer authentication with the `token` variable
+fn bearer_auth() -> Value {
+    json!({
+        "type": "bearer",
+        "bearer": [
+            { "key": "token", "value": format!("{{{{{TOKEN_VARIABLE}}}}}"), "type": "string" },
+        ],
+    })
+}
+
+// Postman request of an operation
+fn request_item(path: &str, method: &str, operation: &Value, document: &Value) -> Value {
+    let method = method.to_uppercase();
+    let name = operation["summary"]
+        .as_str()
+        .map_or_else(|| format!("{method} {path}"), str::to_string);
+    let parameters = operation["parameters"]
+        .as_array()
+        .map_or(&[][..], Vec::as_slice);
+
+    let mut request = Map::new();
+    request.insert("method".to_string(), Value::String(method));
+    request.insert("url".to_string(), request_url(path, parameters));
+    if let Some(description) = operation["description"].as_str() {
+        request.insert("description".to_string(), description.into());
+    }
+
+    if let Some(schema) = operation.pointer("/requestBody/content/application~1json/schema") {
+        let body = example_value(schema, document, 0);
+        request.insert(
+            "header".to_string(),
+            json!([{ "key": "Content-Type", "value": "application/json" }]),
+        );
+        request.insert(
+            "body".to_string(),
+            json!({
+                "mode": "raw",
+                "raw": serde_json::to_string_pretty(&body).unwrap_or_default(),
+                "options": { "raw": { "language": "json" } },
+            }),
+        );
+    }
+
+    // Operations without security requirement are public, e.g. signup and login
+    let secured = operation["security"]
+        .as_array()
+        .is_some_and(|requirements| !requirements.is_empty());
+    if !secured {
+        request.insert("auth".to_string(), json!({ "type": "noauth" }));
+    }
+
+    json!({ "name": name, "request": request })
+}
+
+// Postman URL of a path, `{id}` placeholders become `:id` path variables
+fn request_url(path: &str, parameters: &[Value]) -> Value {
+    let segments: Vec<String> = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            segment
+                .strip_prefix('{')
+                .and_then(|name| name.strip_suffix('}'))
+                .map_or_else(|| segment.to_string(), |name| format!(":{name}"))
+        })
+        .collect();
+
+    let parameters_in = |location: &str| -> Vec<Value> {
+        parameters
+            .iter()
+            .filter(|parameter| parameter["in"] == location)
+            .map(|parameter| {
+                json!({
+                    "key": parameter["name"],
+                    "value": "",
+                    "description": parameter["description"].as_str().unwrap_or_default(),
+                    // Optional query parameters are listed but not sent
+                    "disabled": location == "query" && parameter["required"] != true,
+                })
+            })
+            .collect()
+    };
+
+    json!({
+        "raw": format!("{{{{{BASE_URL_VARIABLE}}}}}/{}", segments.join("/")),
+        "host": [format!("{{{{{BASE_URL_VARIABLE}}}}}")],
+        "path": segments,
+        "variable": parameters_in("path"),
+        "query": parameters_in("query"),
+    })
+}
+
+// Placeholder value matching a JSON schema, references are resolved in `document`
+fn example_value(schema: &Value, document: &Value, depth: usize) -> Value {
+    if depth > MAX_EXAMPLE_DEPTH {
+        return Value::Null;
+    }
+    if let Some(reference) = schema["$ref"].as_str() {
+        return document
+            .pointer(reference.trim_start_matches('#'))
+            .map_or(Value::Null, |target| {
+                example_value(target, document, depth + 1)
+            });
+    }
+    if let Some(example) = schema.get("example") {
+        return example.clone();
+    }
+    if let Some(value) = schema["enum"].as_array().and_then(|values| values.first()) {
+        return value.clone();
+    }
+    if let Some(variant) = ["oneOf", "anyOf", "allOf"]
+        .iter()
+        .find_map(|key| schema[key].as_array().and_then(|variants| variants.first()))
+    {
+        return example_value(variant, document, depth + 1);
+    }
+
+    // Nullable fields are typed as `["string", "null"]`
+    let schema_type = match &schema["type"] {
+        Value::String(schema_type) => schema_type.as_str(),
+        Value::Array(types) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|schema_type| *schema_type != "null")
+            .unwrap_or("null"),
+        _ if schema["properties"].is_object() => "object",
+        _ => "null",
+    };
+
+    match schema_type {
+        "object" => Value::Object(
+            schema["properties"]
+                .as_object()
+                .into_iter()
+                .flatten()
+                .map(|(name, property)| {
+                    (name.clone(), example_value(property, document, depth + 1))
+                })
+                .collect(),
+        ),
+        "array" => json!([example_value(&schema["items"], document, depth + 1)]),
+        "string" => Value::String(String::new()),
+        "integer" | "number" => json!(0),
+        "boolean" => Value::Bool(false),
+        _ => Value::Null,
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::swagger::doc_config::ApiDoc;
+    use utoipa::OpenApi;
+
+    fn find_request<'a>(collection: &'a Value, method: &str, raw_url: &str) -> &'a Value {
+        collection["item"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .flat_map(|folder| folder["item"].as_array().unwrap())
+            .find(|item| {
+                item["request"]["method"] == method && item["request"]["url"]["raw"] == raw_url
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_collection_groups_requests_by_tag() {
+        let collection = postman_collection(&ApiDoc::openapi()).unwrap();
+        assert_eq!(collection["info"]["schema"], COLLECTION_SCHEMA);
+        assert_eq!(collection["info"]["name"], "Todo App API");
+
+        let folders: Vec<&str> = collection["item"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|folder| folder["name"].as_str())
+            .collect();
+        assert!(folders.contains(&"Todos"));
+        assert!(folders.contains(&"Login"));
+    }
+
+    #[test]
+    fn test_path_parameters_become_variables() {
+        let collection = postman_collection(&ApiDoc::openapi()).unwrap();
+        let request = find_request(&collection, "GET", "{{baseUrl}}/todos/:id");
+        assert_eq!(request["request"]["url"]["variable"][0]["key"], "id");
+        // Secured operations inherit the collection bearer token
+        assert!(request["request"].get("auth").is_none());
+        assert_eq!(collection["auth"]["bearer"][0]["value"], "{{token}}");
+    }
+
+    #[test]
+    fn test_public_operations_have_no_auth() {
+        let collection = postman_collection(&ApiDoc::openapi()).unwrap();
+        let request = find_request(&collection, "POST", "{{baseUrl}}/user/login");
+        assert_eq!(request["request"]["auth"]["type"], "noauth");
+    }
+
+    #[test]
+    fn test_request_body_example() {
+        let collection = postman_collection(&ApiDoc::openapi()).unwrap();
+        let request = find_request(&collection, "POST", "{{baseUrl}}/todos");
+        let body: Value =
+            serde_json::from_str(request["request"]["body"]["raw"].as_str().unwrap()).unwrap();
+        assert_eq!(body["title"], "");
+        assert!(body.get("list_id").is_some());
+    }
+
+    #[test]
+    fn test_environment_variables() {
+        let environment = postman_environment("Todo App API", "http://localhost:8000");
+        assert_eq!(environment["values"][0]["key"], BASE_URL_VARIABLE);
+        assert_eq!(environment["values"][0]["value"], "http://localhost:8000");
+        assert_eq!(environment["values"][1]["key"], TOKEN_VARIABLE);
+    }
+}
@@ -0,0 +1,69 @@
+//! # Background Tasks
+//!
+//! Work the handlers start without waiting for it, like the emails. The tasks are
+//! tracked so a graceful shutdown waits for them instead of dropping them.
+
+use std::{future::Future, time::Duration};
+
+use tokio_util::task::TaskTracker;
+
+// Tasks spawned by the handlers, shared by the clones
+#[derive(Clone, Default)]
+pub struct BackgroundTasks {
+    tracker: TaskTracker,
+}
+
+impl BackgroundTasks {
+    // Run `task` in the background, the shutdown waits for it
+    pub fn spawn<F>(&self, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.tracker.spawn(task);
+    }
+
+    // Wait for the running tasks, at most `timeout`. Returns how many were still
+    // running when the wait gave up.
+    pub async fn shutdown(&self, timeout: Duration) -> usize {
+        self.tracker.close();
+        if tokio::time::timeout(timeout, self.tracker.wait())
+            .await
+            .is_ok()
+        {
+            return 0;
+        }
+        self.tracker.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_shutdown_waits_for_tasks() {
+        let tasks = BackgroundTasks::default();
+        let done = Arc::new(AtomicBool::new(false));
+        let flag = done.clone();
+        tasks.spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            flag.store(true, Ordering::SeqCst);
+        });
+
+        assert_eq!(tasks.shutdown(Duration::from_secs(5)).await, 0);
+        assert!(done.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_gives_up() {
+        let tasks = BackgroundTasks::default();
+        tasks.spawn(std::future::pending());
+
+        assert_eq!(tasks.shutdown(Duration::from_millis(10)).await, 1);
+    }
+}
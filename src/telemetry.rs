@@ -0,0 +1,232 @@
+//! # Telemetry
+//!
+//! Prometheus metrics for authentication outcomes, security events, database queries and
+//! the connection pool.
+
+use axum::{
+    extract::{MatchedPath, Request},
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+    routing::get,
+    Router,
+};
+use metrics_exporter_prometheus::{BuildError, Matcher, PrometheusBuilder, PrometheusHandle};
+use std::{future::Future, time::Instant};
+use tracing::Instrument;
+
+// Login attempts by outcome
+pub const AUTH_LOGIN_TOTAL: &str = "auth_login_total";
+// Tokens issued by kind
+pub const AUTH_TOKENS_ISSUED_TOTAL: &str = "auth_tokens_issued_total";
+// Requests rejected with 401 or 403, by route and status
+pub const AUTH_REJECTIONS_TOTAL: &str = "auth_rejections_total";
+// Duration of the repository queries, by query name
+pub const DB_QUERY_DURATION_SECONDS: &str = "db_query_duration_seconds";
+// Failed repository queries, by query name
+pub const DB_QUERY_ERRORS_TOTAL: &str = "db_query_errors_total";
+// Queries that found no free connection in time, by query name
+pub const DB_POOL_TIMEOUTS_TOTAL: &str = "db_pool_timeouts_total";
+// Queries run again after a transient error, by query name
+pub const DB_QUERY_RETRIES_TOTAL: &str = "db_query_retries_total";
+// Open, idle and maximum connections of the pool
+pub const DB_POOL_CONNECTIONS: &str = "db_pool_connections";
+pub const DB_POOL_IDLE_CONNECTIONS: &str = "db_pool_idle_connections";
+pub const DB_POOL_MAX_CONNECTIONS: &str = "db_pool_max_connections";
+
+// Buckets of the query durations, from 1ms to 10s
+const DB_QUERY_BUCKETS: &[f64] = &[
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+// Outcome of a login attempt
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoginOutcome {
+    Success,
+    MissingFields,
+    UnknownUser,
+    InvalidPassword,
+    TwoFactorRequired,
+    InvalidSecondFactor,
+    InvalidOAuth,
+    PasswordResetRequired,
+    Error,
+}
+
+impl LoginOutcome {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::MissingFields => "missing_fields",
+            Self::UnknownUser => "unknown_user",
+            Self::InvalidPassword => "invalid_password",
+            Self::TwoFactorRequired => "two_factor_required",
+            Self::InvalidSecondFactor => "invalid_second_factor",
+            Self::InvalidOAuth => "invalid_oauth",
+            Self::PasswordResetRequired => "password_reset_required",
+            Self::Error => "error",
+        }
+    }
+}
+
+// Prometheus recorder, the query durations are exported as histograms
+fn prometheus_builder() -> Result<PrometheusBuilder, BuildError> {
+    PrometheusBuilder::new().set_buckets_for_metric(
+        Matcher::Full(DB_QUERY_DURATION_SECONDS.to_string()),
+        DB_QUERY_BUCKETS,
+    )
+}
+
+// Install the global Prometheus recorder
+pub fn install_recorder() -> Result<PrometheusHandle, BuildError> {
+    prometheus_builder()?.install_recorder()
+}
+
+// Route exposing the metrics in the Prometheus text format
+pub fn metrics_routes<S>(handle: PrometheusHandle) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    Router::new().route("/metrics", get(move || async move { handle.render() }))
+}
+
+// Count a login attempt
+pub fn record_login(outcome: LoginOutcome) {
+    metrics::counter!(AUTH_LOGIN_TOTAL, "outcome" => outcome.as_str()).increment(1);
+}
+
+// Count an issued token, `kind` is `session` or `guest`
+pub fn record_token_issued(kind: &'static str) {
+    metrics::counter!(AUTH_TOKENS_ISSUED_TOTAL, "kind" => kind).increment(1);
+}
+
+// Run a repository query in a client span, recording its duration and failure under
+// `name`, written `<module>.<method>`
+pub async fn observe_query<T>(
+    name: &'static str,
+    query: impl Future<Output = Result<T, sqlx::Error>>,
+) -> Result<T, sqlx::Error> {
+    let span = tracing::info_span!(
+        "db.query",
+        otel.name = name,
+        otel.kind = "client",
+        db.system.name = "postgresql",
+        db.operation.name = name,
+    );
+    let start = Instant::now();
+    let result = query.instrument(span).await;
+
+    metrics::histogram!(DB_QUERY_DURATION_SECONDS, "query" => name)
+        .record(start.elapsed().as_secs_f64());
+    match &result {
+        Err(sqlx::Error::PoolTimedOut) => {
+            metrics::counter!(DB_QUERY_ERRORS_TOTAL, "query" => name).increment(1);
+            metrics::counter!(DB_POOL_TIMEOUTS_TOTAL, "query" => name).increment(1);
+            tracing::warn!("Query {} found no free database connection in time", name);
+        }
+        Err(_) => {
+            metrics::counter!(DB_QUERY_ERRORS_TOTAL, "query" => name).increment(1);
+        }
+        Ok(_) => {}
+    }
+
+    result
+}
+
+// Count a query run again after a transient error
+pub fn record_query_retry(name: &'static str) {
+    metrics::counter!(DB_QUERY_RETRIES_TOTAL, "query" => name).increment(1);
+}
+
+// Record the connections of the pool
+pub fn record_pool(size: u32, idle: usize, max: u32) {
+    metrics::gauge!(DB_POOL_CONNECTIONS).set(f64::from(size));
+    #[allow(clippy::cast_precision_loss)]
+    metrics::gauge!(DB_POOL_IDLE_CONNECTIONS).set(idle as f64);
+    metrics::gauge!(DB_POOL_MAX_CONNECTIONS).set(f64::from(max));
+}
+
+const fn is_auth_rejection(status: StatusCode) -> bool {
+    matches!(status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN)
+}
+
+// Middleware counting authentication rejections per route
+pub async fn track_auth_rejections(request: Request, next: Next) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or_else(|| "unmatched".to_string(), |path| path.as_str().to_string());
+
+    let response = next.run(request).await;
+
+    let status = response.status();
+    if is_auth_rejection(status) {
+        metrics::counter!(
+            AUTH_REJECTIONS_TOTAL,
+            "route" => route,
+            "status" => status.as_str().to_string()
+        )
+        .increment(1);
+    }
+
+    response
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_login_outcomes() {
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+
+        metrics::with_local_recorder(&recorder, || {
+            record_login(LoginOutcome::Success);
+            record_login(LoginOutcome::InvalidPassword);
+            record_login(LoginOutcome::InvalidPassword);
+        });
+
+        let rendered = handle.render();
+        assert!(rendered.contains("auth_login_total{outcome=\"success\"} 1"));
+        assert!(rendered.contains("auth_login_total{outcome=\"invalid_password\"} 2"));
+    }
+
+    #[test]
+    fn test_record_token_issued() {
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+
+        metrics::with_local_recorder(&recorder, || record_token_issued("guest"));
+
+        assert!(handle
+            .render()
+            .contains("auth_tokens_issued_total{kind=\"guest\"} 1"));
+    }
+
+    #[tokio::test]
+    async fn test_observe_query() {
+        let recorder = prometheus_builder().unwrap().build_recorder();
+        let handle = recorder.handle();
+        let _guard = metrics::set_default_local_recorder(&recorder);
+
+        let found = observe_query("todo.fetch_todo", async { Ok(1) }).await;
+        let failed: Result<i32, _> =
+            observe_query("todo.fetch_todo", async { Err(sqlx::Error::RowNotFound) }).await;
+
+        assert_eq!(found.ok(), Some(1));
+        assert!(failed.is_err());
+        let rendered = handle.render();
+        assert!(rendered.contains("db_query_duration_seconds_bucket{"));
+        assert!(rendered.contains("db_query_duration_seconds_count{query=\"todo.fetch_todo\"} 2"));
+        assert!(rendered.contains("db_query_errors_total{query=\"todo.fetch_todo\"} 1"));
+    }
+
+    #[test]
+    fn test_is_auth_rejection() {
+        assert!(is_auth_rejection(StatusCode::UNAUTHORIZED));
+        assert!(is_auth_rejection(StatusCode::FORBIDDEN));
+        assert!(!is_auth_rejection(StatusCode::NOT_FOUND));
+    }
+}
@@ -0,0 +1,277 @@
+use std::{
+    convert::Infallible,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    str::FromStr,
+    sync::Arc,
+};
+
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::{header, request::Parts, HeaderMap},
+};
+
+use crate::AppState;
+
+// Network in CIDR notation, e.g. `10.0.0.0/8` or `fd00::/8`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Cidr {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, ip.to_canonical()) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (address, prefix) = value
+            .split_once('/')
+            .map_or((value, None), |(a, p)| (a, Some(p)));
+        let network = address
+            .parse::<IpAddr>()
+            .map_err(|_| format!("Invalid proxy address: {value}"))?;
+        let max_prefix = if network.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            None => max_prefix,
+            Some(prefix) => prefix
+                .parse::<u8>()
+                .ok()
+                .filter(|prefix| *prefix <= max_prefix)
+                .ok_or_else(|| format!("Invalid proxy prefix: {value}"))?,
+        };
+
+        Ok(Self { network, prefix })
+    }
+}
+
+// Proxies allowed to report the client address through forwarding headers
+#[derive(Clone, Debug, Default)]
+pub struct TrustedProxies {
+    networks: Arc<[Cidr]>,
+}
+
+impl TrustedProxies {
+    fn is_trusted(&self, ip: IpAddr) -> bool {
+        self.networks.iter().any(|network| network.contains(ip))
+    }
+
+    // Resolve the client address of a request received from `peer`.
+    // Forwarding headers are only read when the peer is a trusted proxy, and the
+    // chain is walked from the nearest hop so clients can't spoof their address.
+    pub fn client_ip(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        let peer = peer.to_canonical();
+        if !self.is_trusted(peer) {
+            return peer;
+        }
+
+        let Some(chain) = forwarded_chain(headers) else {
+            return peer;
+        };
+
+        let mut client = peer;
+        for hop in chain.iter().rev() {
+            let Some(ip) = hop.as_ref().map(IpAddr::to_canonical) else {
+                // Obfuscated or malformed hop, the last known address is the best guess
+                break;
+            };
+            client = ip;
+            if !self.is_trusted(ip) {
+                break;
+            }
+        }
+        client
+    }
+}
+
+impl FromStr for TrustedProxies {
+    type Err = String;
+
+    // Comma separated list of CIDRs, bare addresses are single hosts
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let networks = value
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(Cidr::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            networks: networks.into(),
+        })
+    }
+}
+
+// Parse a forwarded node, with an optional port and IPv6 brackets
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim().trim_matches('"');
+    if let Some(rest) = node.strip_prefix('[') {
+        return rest.split_once(']')?.0.parse().ok();
+    }
+    node.parse().ok().or_else(|| {
+        node.split_once(':')
+            .and_then(|(address, _port)| address.parse::<Ipv4Addr>().ok())
+            .map(IpAddr::V4)
+    })
+}
+
+// Hops listed by the `Forwarded` header, or `X-Forwarded-For` as a fallback
+fn forwarded_chain(headers: &HeaderMap) -> Option<Vec<Option<IpAddr>>> {
+    let forwarded = headers
+        .get_all(header::FORWARDED)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|element| {
+            element
+                .split(';')
+                .filter_map(|pair| pair.split_once('='))
+                .find(|(key, _)| key.trim().eq_ignore_ascii_case("for"))
+                .and_then(|(_, node)| parse_node(node))
+        })
+        .collect::<Vec<_>>();
+    if !forwarded.is_empty() {
+        return Some(forwarded);
+    }
+
+    let forwarded_for = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(parse_node)
+        .collect::<Vec<_>>();
+    (!forwarded_for.is_empty()).then_some(forwarded_for)
+}
+
+// Client address of the request, resolved through the trusted proxies
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+impl FromRequestParts<AppState> for ClientIp {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        // Connection info is always set by the server, only missing in tests
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED), |info| info.0.ip());
+
+        Ok(Self(
+            state
+                .settings
+                .borrow()
+                .trusted_proxies
+                .client_ip(peer, &parts.headers),
+        ))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+
+    fn headers(name: &'static str, value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(name, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    fn proxies() -> TrustedProxies {
+        "10.0.0.0/8, 192.168.1.1, fd00::/8".parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_trusted_proxies() {
+        assert!("".parse::<TrustedProxies>().unwrap().networks.is_empty());
+        assert_eq!(proxies().networks.len(), 3);
+        assert!("10.0.0.0/33".parse::<TrustedProxies>().is_err());
+        assert!("not-an-ip".parse::<TrustedProxies>().is_err());
+    }
+
+    #[test]
+    fn test_cidr_contains() {
+        let proxies = proxies();
+        assert!(proxies.is_trusted(ip("10.1.2.3")));
+        assert!(proxies.is_trusted(ip("::ffff:10.1.2.3")));
+        assert!(proxies.is_trusted(ip("192.168.1.1")));
+        assert!(!proxies.is_trusted(ip("192.168.1.2")));
+        assert!(proxies.is_trusted(ip("fd12::1")));
+        assert!("0.0.0.0/0"
+            .parse::<TrustedProxies>()
+            .unwrap()
+            .is_trusted(ip("8.8.8.8")));
+    }
+
+    #[test]
+    fn test_untrusted_peer_ignores_headers() {
+        let headers = headers("x-forwarded-for", "1.2.3.4");
+        assert_eq!(
+            proxies().client_ip(ip("203.0.113.9"), &headers),
+            ip("203.0.113.9")
+        );
+    }
+
+    #[test]
+    fn test_x_forwarded_for_skips_trusted_hops() {
+        let headers = headers("x-forwarded-for", "6.6.6.6, 1.2.3.4, 10.0.0.2");
+        assert_eq!(proxies().client_ip(ip("10.0.0.1"), &headers), ip("1.2.3.4"));
+    }
+
+    #[test]
+    fn test_forwarded_header() {
+        let headers = headers(
+            "forwarded",
+            "for=\"[2001:db8::1]:4711\";proto=https, for=10.0.0.2:8080",
+        );
+        assert_eq!(
+            proxies().client_ip(ip("10.0.0.1"), &headers),
+            ip("2001:db8::1")
+        );
+    }
+
+    #[test]
+    fn test_obfuscated_hop_stops_the_walk() {
+        let headers = headers("forwarded", "for=1.2.3.4, for=_hidden, for=10.0.0.2");
+        assert_eq!(
+            proxies().client_ip(ip("10.0.0.1"), &headers),
+            ip("10.0.0.2")
+        );
+    }
+
+    #[test]
+    fn test_trusted_peer_without_headers() {
+        assert_eq!(
+            proxies().client_ip(ip("10.0.0.1"), &HeaderMap::new()),
+            ip("10.0.0.1")
+        );
+    }
+}
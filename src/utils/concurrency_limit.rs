@@ -0,0 +1,86 @@
+use axum::{
+    error_handling::HandleErrorLayer,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::MethodRouter,
+    BoxError, Json,
+};
+use tower::{load_shed::error::Overloaded, ServiceBuilder};
+
+use crate::modules::common::ErrorResponse;
+
+// Concurrent requests allowed on each expensive endpoint. Kept below the size of the
+// database pool so cheap requests always find a free connection.
+pub const HEAVY_REQUEST_CONCURRENCY: usize = 4;
+
+// Seconds a client is asked to wait before retrying a rejected request
+const RETRY_AFTER_SECONDS: &str = "1";
+
+// Limit the number of in-flight requests of an endpoint. Requests over the limit are
+// rejected right away instead of queueing for a database connection.
+pub fn limit_concurrency<S>(method_router: MethodRouter<S>, max: usize) -> MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    method_router.layer(
+        ServiceBuilder::new()
+            .layer(HandleErrorLayer::new(handle_limit_error))
+            .load_shed()
+            .concurrency_limit(max),
+    )
+}
+
+async fn handle_limit_error(error: BoxError) -> Response {
+    if error.is::<Overloaded>() {
+        tracing::warn!("Rejecting request, endpoint concurrency limit reached");
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            [(header::RETRY_AFTER, RETRY_AFTER_SECONDS)],
+            Json(ErrorResponse::new(
+                "Server is busy with similar requests, please retry shortly",
+            )),
+        )
+            .into_response();
+    }
+
+    tracing::warn!("Unhandled error in concurrency limit: {}", error);
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ErrorResponse::internal("Internal server error")),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn app(max: usize) -> Router {
+        Router::new().route("/", limit_concurrency(get(|| async { "ok" }), max))
+    }
+
+    #[tokio::test]
+    async fn test_request_within_limit_is_served() {
+        let response = app(1)
+            .oneshot(Request::builder().uri("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_request_over_limit_is_rejected() {
+        let response = app(0)
+            .oneshot(Request::builder().uri("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            response.headers().get(header::RETRY_AFTER).unwrap(),
+            RETRY_AFTER_SECONDS
+        );
+    }
+}
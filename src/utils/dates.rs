@@ -0,0 +1,155 @@
+use time::{
+    format_description::{well_known::Rfc3339, FormatItem},
+    macros::format_description,
+    Date, OffsetDateTime, PrimitiveDateTime, UtcOffset,
+};
+
+// Date of the HTTP headers like `Last-Modified`, e.g. `Mon, 10 Mar 2025 12:00:00 GMT`
+const HTTP_DATE: &[FormatItem<'_>] = format_description!(
+    "[weekday repr:short], [day] [month repr:short] [year] [hour]:[minute]:[second] GMT"
+);
+
+// Parse a `YYYY-MM-DD` day given in the `field` query parameter
+pub fn parse_day(value: &str, field: &str) -> Result<Date, String> {
+    Date::parse(value.trim(), format_description!("[year]-[month]-[day]"))
+        .map_err(|_| format!("{field} must be a date as YYYY-MM-DD"))
+}
+
+// Format a timestamp as RFC3339 in UTC, e.g. `2025-03-10T12:00:00.5Z`. Fails for the
+// years RFC3339 can't represent.
+pub fn format_timestamp(value: OffsetDateTime) -> Result<String, time::error::Format> {
+    value.to_offset(UtcOffset::UTC).format(&Rfc3339)
+}
+
+// Format a timestamp as an HTTP date, dropping the fraction of second
+pub fn format_http_date(value: OffsetDateTime) -> Result<String, time::error::Format> {
+    value.to_offset(UtcOffset::UTC).format(HTTP_DATE)
+}
+
+// Parse an HTTP date, `None` for the obsolete formats and invalid dates
+pub fn parse_http_date(value: &str) -> Option<OffsetDateTime> {
+    PrimitiveDateTime::parse(value.trim(), HTTP_DATE)
+        .ok()
+        .map(PrimitiveDateTime::assume_utc)
+}
+
+// Serde format of the timestamps of the responses, `#[serde(with = "rfc3339")]`
+pub mod rfc3339 {
+    use serde::{de::Error as _, ser::Error as _, Deserialize, Deserializer, Serializer};
+    use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+    pub fn serialize<S: Serializer>(
+        value: &OffsetDateTime,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let formatted = super::format_timestamp(*value).map_err(S::Error::custom)?;
+        serializer.serialize_str(&formatted)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<OffsetDateTime, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        OffsetDateTime::parse(&value, &Rfc3339).map_err(D::Error::custom)
+    }
+}
+
+// Serde format of the optional timestamps, `null` when unset
+pub mod rfc3339_option {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+    #[allow(clippy::ref_option)]
+    pub fn serialize<S: Serializer>(
+        value: &Option<OffsetDateTime>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => super::rfc3339::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<OffsetDateTime>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|value| OffsetDateTime::parse(&value, &Rfc3339))
+            .transpose()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use time::macros::{date, datetime};
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Stamped {
+        #[serde(with = "rfc3339")]
+        at: OffsetDateTime,
+        #[serde(with = "rfc3339_option")]
+        seen_at: Option<OffsetDateTime>,
+    }
+
+    #[test]
+    fn test_parse_day() {
+        assert_eq!(parse_day(" 2025-03-01 ", "from"), Ok(date!(2025 - 03 - 01)));
+        assert_eq!(
+            parse_day("03/01/2025", "from"),
+            Err("from must be a date as YYYY-MM-DD".to_string())
+        );
+        assert!(parse_day("2025-02-30", "to").is_err());
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(
+            format_timestamp(datetime!(2025-03-10 09:30:00.25 -03:00)).ok(),
+            Some("2025-03-10T12:30:00.25Z".to_string())
+        );
+        assert_eq!(
+            format_timestamp(datetime!(2025-03-10 12:00 UTC)).ok(),
+            Some("2025-03-10T12:00:00Z".to_string())
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_rfc3339_serde() {
+        let stamped = Stamped {
+            at: datetime!(2025-03-10 12:00 +01:00),
+            seen_at: None,
+        };
+        let json = serde_json::to_string(&stamped).unwrap();
+        assert_eq!(json, r#"{"at":"2025-03-10T11:00:00Z","seen_at":null}"#);
+        assert_eq!(serde_json::from_str::<Stamped>(&json).unwrap(), stamped);
+
+        let parsed: Stamped = serde_json::from_str(
+            r#"{"at":"2025-03-10T11:00:00Z","seen_at":"2025-03-11T08:00:00+02:00"}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.seen_at, Some(datetime!(2025-03-11 06:00 UTC)));
+        assert!(
+            serde_json::from_str::<Stamped>(r#"{"at":"2025-03-10 11:00:00","seen_at":null}"#)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_http_date() {
+        let at = datetime!(2025-03-10 13:30:15.5 +01:00);
+        assert_eq!(
+            format_http_date(at).ok(),
+            Some("Mon, 10 Mar 2025 12:30:15 GMT".to_string())
+        );
+        assert_eq!(
+            parse_http_date("Mon, 10 Mar 2025 12:30:15 GMT"),
+            Some(datetime!(2025-03-10 12:30:15 UTC))
+        );
+        assert_eq!(parse_http_date("Monday, 10-Mar-25 12:30:15 GMT"), None);
+    }
+}
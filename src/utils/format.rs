@@ -0,0 +1,333 @@
+//! # Locale Formatting
+//! Dates, times and numbers written for people, in the locale and 12/24h preference of
+//! the user: emails and the readable fields of the exports. Locales without rules below
+//! fall back to `en-US`. The API itself keeps exchanging RFC3339 timestamps, see `dates`.
+
+use time::{Date, PrimitiveDateTime, Time};
+
+// Locale of the users without preferences
+pub const DEFAULT_LOCALE: &str = "en-US";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DateOrder {
+    DayMonthYear,
+    MonthDayYear,
+}
+
+// Clock of the times, `12h` with AM/PM or `24h`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeFormat {
+    Hours12,
+    Hours24,
+}
+
+impl TimeFormat {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Hours12 => "12h",
+            Self::Hours24 => "24h",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "12h" => Some(Self::Hours12),
+            "24h" => Some(Self::Hours24),
+            _ => None,
+        }
+    }
+}
+
+// First day of the week in calendars
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WeekStart {
+    Saturday,
+    Sunday,
+    Monday,
+}
+
+impl WeekStart {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Saturday => "saturday",
+            Self::Sunday => "sunday",
+            Self::Monday => "monday",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "saturday" => Some(Self::Saturday),
+            "sunday" => Some(Self::Sunday),
+            "monday" => Some(Self::Monday),
+            _ => None,
+        }
+    }
+}
+
+// Formatting conventions of a locale
+#[derive(Debug)]
+pub struct LocaleRules {
+    pub tag: &'static str,
+    date_order: DateOrder,
+    date_separator: char,
+    pub week_start: WeekStart,
+    pub time_format: TimeFormat,
+    pub decimal_separator: char,
+    pub group_separator: char,
+}
+
+// Known locales, the first one is `DEFAULT_LOCALE`
+const LOCALES: [LocaleRules; 7] = [
+    LocaleRules {
+        tag: DEFAULT_LOCALE,
+        date_order: DateOrder::MonthDayYear,
+        date_separator: '/',
+        week_start: WeekStart::Sunday,
+        time_format: TimeFormat::Hours12,
+        decimal_separator: '.',
+        group_separator: ',',
+    },
+    LocaleRules {
+        tag: "en-GB",
+        date_order: DateOrder::DayMonthYear,
+        date_separator: '/',
+        week_start: WeekStart::Monday,
+        time_format: TimeFormat::Hours24,
+        decimal_separator: '.',
+        group_separator: ',',
+    },
+    LocaleRules {
+        tag: "pt-PT",
+        date_order: DateOrder::DayMonthYear,
+        date_separator: '/',
+        week_start: WeekStart::Monday,
+        time_format: TimeFormat::Hours24,
+        decimal_separator: ',',
+        group_separator: ' ',
+    },
+    LocaleRules {
+        tag: "pt-BR",
+        date_order: DateOrder::DayMonthYear,
+        date_separator: '/',
+        week_start: WeekStart::Sunday,
+        time_format: TimeFormat::Hours24,
+        decimal_separator: ',',
+        group_separator: '.',
+    },
+    LocaleRules {
+        tag: "es-ES",
+        date_order: DateOrder::DayMonthYear,
+        date_separator: '/',
+        week_start: WeekStart::Monday,
+        time_format: TimeFormat::Hours24,
+        decimal_separator: ',',
+        group_separator: '.',
+    },
+    LocaleRules {
+        tag: "fr-FR",
+        date_order: DateOrder::DayMonthYear,
+        date_separator: '/',
+        week_start: WeekStart::Monday,
+        time_format: TimeFormat::Hours24,
+        decimal_separator: ',',
+        group_separator: ' ',
+    },
+    LocaleRules {
+        tag: "de-DE",
+        date_order: DateOrder::DayMonthYear,
+        date_separator: '.',
+        week_start: WeekStart::Monday,
+        time_format: TimeFormat::Hours24,
+        decimal_separator: ',',
+        group_separator: '.',
+    },
+];
+
+// Rules of a locale tag, matched case insensitively, e.g. `pt-br`
+pub fn locale_rules(tag: &str) -> Option<&'static LocaleRules> {
+    let tag = tag.trim().replace('_', "-");
+    LOCALES
+        .iter()
+        .find(|rules| rules.tag.eq_ignore_ascii_case(&tag))
+}
+
+// Tags of the known locales, for the error messages
+pub fn locale_tags() -> String {
+    LOCALES
+        .iter()
+        .map(|rules| rules.tag)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl LocaleRules {
+    // Pattern of the dates, e.g. `MM/DD/YYYY`
+    pub fn date_pattern(&self) -> String {
+        let separator = self.date_separator;
+        match self.date_order {
+            DateOrder::DayMonthYear => format!("DD{separator}MM{separator}YYYY"),
+            DateOrder::MonthDayYear => format!("MM{separator}DD{separator}YYYY"),
+        }
+    }
+}
+
+// Formats values in the conventions of a user
+#[derive(Clone, Copy, Debug)]
+pub struct Formatter {
+    rules: &'static LocaleRules,
+    time_format: TimeFormat,
+}
+
+impl Formatter {
+    // Formatter of a locale, unknown locales use `DEFAULT_LOCALE`. A valid
+    // `time_format` overrides the clock of the locale.
+    pub fn new(locale: &str, time_format: Option<&str>) -> Self {
+        let rules = locale_rules(locale).unwrap_or(&LOCALES[0]);
+        Self {
+            rules,
+            time_format: time_format
+                .and_then(TimeFormat::parse)
+                .unwrap_or(rules.time_format),
+        }
+    }
+
+    pub const fn rules(&self) -> &'static LocaleRules {
+        self.rules
+    }
+
+    pub const fn time_format(&self) -> TimeFormat {
+        self.time_format
+    }
+
+    // Pattern of the times, `h:mm A` or `HH:mm`
+    pub const fn time_pattern(&self) -> &'static str {
+        match self.time_format {
+            TimeFormat::Hours12 => "h:mm A",
+            TimeFormat::Hours24 => "HH:mm",
+        }
+    }
+
+    // Date as the locale writes it, e.g. `03/10/2025` or `10.03.2025`
+    pub fn date(&self, date: Date) -> String {
+        let separator = self.rules.date_separator;
+        let (day, month, year) = (date.day(), u8::from(date.month()), date.year());
+        match self.rules.date_order {
+            DateOrder::DayMonthYear => {
+                format!("{day:02}{separator}{month:02}{separator}{year}")
+            }
+            DateOrder::MonthDayYear => {
+                format!("{month:02}{separator}{day:02}{separator}{year}")
+            }
+        }
+    }
+
+    // Time of day, e.g. `2:05 PM` or `14:05`
+    pub fn time(&self, time: Time) -> String {
+        let (hour, minute) = (time.hour(), time.minute());
+        match self.time_format {
+            TimeFormat::Hours12 => {
+                let suffix = if hour < 12 { "AM" } else { "PM" };
+                let hour = match hour % 12 {
+                    0 => 12,
+                    hour => hour,
+                };
+                format!("{hour}:{minute:02} {suffix}")
+            }
+            TimeFormat::Hours24 => format!("{hour:02}:{minute:02}"),
+        }
+    }
+
+    // Wall clock date and time, already in the timezone of the user
+    pub fn datetime(&self, at: PrimitiveDateTime) -> String {
+        format!("{} {}", self.date(at.date()), self.time(at.time()))
+    }
+
+    // Wall clock date and time followed by its timezone, e.g. `03/10/2025 2:05 PM (UTC)`
+    pub fn zoned_datetime(&self, at: PrimitiveDateTime, timezone: &str) -> String {
+        format!("{} ({timezone})", self.datetime(at))
+    }
+
+    // Integer with the digits grouped by thousands, e.g. `1,234` or `1.234`
+    pub fn integer(&self, value: i64) -> String {
+        let digits = value.unsigned_abs().to_string();
+        let mut grouped = String::with_capacity(digits.len() + digits.len() / 3 + 1);
+        if value < 0 {
+            grouped.push('-');
+        }
+        for (index, digit) in digits.chars().enumerate() {
+            if index > 0 && (digits.len() - index) % 3 == 0 {
+                grouped.push(self.rules.group_separator);
+            }
+            grouped.push(digit);
+        }
+        grouped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::{date, datetime, time};
+
+    #[test]
+    fn test_locale_rules() {
+        assert_eq!(locale_rules("pt-br").map(|rules| rules.tag), Some("pt-BR"));
+        assert_eq!(
+            locale_rules(" de_DE ").map(|rules| rules.tag),
+            Some("de-DE")
+        );
+        assert!(locale_rules("xx-XX").is_none());
+        assert!(locale_tags().starts_with(DEFAULT_LOCALE));
+    }
+
+    #[test]
+    fn test_formatter_dates_and_times() {
+        let us = Formatter::new("en-US", None);
+        assert_eq!(
+            us.datetime(datetime!(2025-03-10 14:05)),
+            "03/10/2025 2:05 PM"
+        );
+        assert_eq!(us.time(time!(0:30)), "12:30 AM");
+        assert_eq!(us.time(time!(12:00)), "12:00 PM");
+        assert_eq!(us.time_pattern(), "h:mm A");
+
+        let de = Formatter::new("de-DE", None);
+        assert_eq!(de.date(date!(2025 - 03 - 10)), "10.03.2025");
+        assert_eq!(de.rules().date_pattern(), "DD.MM.YYYY");
+        assert_eq!(de.datetime(datetime!(2025-03-10 14:05)), "10.03.2025 14:05");
+        assert_eq!(
+            de.zoned_datetime(datetime!(2025-03-10 14:05), "Europe/Berlin"),
+            "10.03.2025 14:05 (Europe/Berlin)"
+        );
+
+        let gb_12h = Formatter::new("en-GB", Some("12h"));
+        assert_eq!(
+            gb_12h.datetime(datetime!(2025-03-10 9:00)),
+            "10/03/2025 9:00 AM"
+        );
+
+        let unknown = Formatter::new("xx-XX", Some("nope"));
+        assert_eq!(unknown.rules().tag, DEFAULT_LOCALE);
+        assert_eq!(unknown.time_format(), TimeFormat::Hours12);
+    }
+
+    #[test]
+    fn test_formatter_integer() {
+        assert_eq!(
+            Formatter::new("en-US", None).integer(1_234_567),
+            "1,234,567"
+        );
+        assert_eq!(Formatter::new("de-DE", None).integer(-1234), "-1.234");
+        assert_eq!(Formatter::new("fr-FR", None).integer(999), "999");
+        assert_eq!(Formatter::new("pt-PT", None).integer(0), "0");
+    }
+
+    #[test]
+    fn test_parse_week_start_and_time_format() {
+        assert_eq!(WeekStart::parse(" Monday "), Some(WeekStart::Monday));
+        assert!(WeekStart::parse("tuesday").is_none());
+        assert_eq!(TimeFormat::parse("24H"), Some(TimeFormat::Hours24));
+        assert!(TimeFormat::parse("24").is_none());
+    }
+}
@@ -0,0 +1,220 @@
+//! # JSON Bodies
+//! Extractor of the JSON request bodies, a drop-in for the one of axum. Bodies that can't
+//! be read are answered with the standard error response instead of plain text, and the
+//! fields that don't deserialize are listed in `errors` with their path.
+
+use axum::{
+    extract::{rejection::JsonRejection, FromRequest, Request},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::modules::common::{AppError, ErrorResponse, VALIDATION_ERROR};
+use crate::utils::validation::ValidationErrors;
+
+// Code of the fields holding a value of the wrong type or shape
+const INVALID: &str = "invalid";
+
+// JSON body of a request, or of a response
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Json<T>(pub T);
+
+impl<T, S> FromRequest<S> for Json<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        match axum::Json::<T>::from_request(request, state).await {
+            Ok(axum::Json(value)) => Ok(Self(value)),
+            Err(rejection) => Err(rejection_response(&rejection)),
+        }
+    }
+}
+
+impl<T: Serialize> IntoResponse for Json<T> {
+    fn into_response(self) -> Response {
+        axum::Json(self.0).into_response()
+    }
+}
+
+// Body read by hand, like the one of a signed request, with the errors of the extractor
+pub fn from_slice<T: DeserializeOwned>(body: &[u8]) -> Result<T, AppError> {
+    let malformed =
+        |error: &serde_json::Error| AppError::Validation(format!("Malformed JSON body: {error}"));
+    let mut deserializer = serde_json::Deserializer::from_slice(body);
+    let value = serde_path_to_error::deserialize(&mut deserializer).map_err(|error| {
+        if error.inner().is_data() {
+            AppError::InvalidFields(field_errors(&error))
+        } else {
+            malformed(error.inner())
+        }
+    })?;
+    deserializer.end().map_err(|error| malformed(&error))?;
+    Ok(value)
+}
+
+// Error response of a body that couldn't be extracted. Fields of the wrong type are
+// reported as invalid fields, the other rejections keep the status chosen by axum.
+fn rejection_response(rejection: &JsonRejection) -> Response {
+    if let JsonRejection::JsonDataError(error) = rejection {
+        // The error of serde sits under the axum error wrapping it
+        let mut source = std::error::Error::source(error);
+        while let Some(error) = source {
+            if let Some(error) =
+                error.downcast_ref::<serde_path_to_error::Error<serde_json::Error>>()
+            {
+                return AppError::InvalidFields(field_errors(error)).into_response();
+            }
+            source = error.source();
+        }
+    }
+
+    let status = rejection.status();
+    let code = match status {
+        StatusCode::PAYLOAD_TOO_LARGE => "payload_too_large",
+        StatusCode::UNSUPPORTED_MEDIA_TYPE => "unsupported_media_type",
+        _ => VALIDATION_ERROR,
+    };
+    let status = if status == StatusCode::UNPROCESSABLE_ENTITY {
+        StatusCode::BAD_REQUEST
+    } else {
+        status
+    };
+    (
+        status,
+        axum::Json(ErrorResponse::with_code(code, rejection.body_text())),
+    )
+        .into_response()
+}
+
+// Field of a deserialization error, `title` for a missing title or `items[2].done`
+fn field_errors(error: &serde_path_to_error::Error<serde_json::Error>) -> ValidationErrors {
+    let path = error.path().to_string();
+    let message = error.inner().to_string();
+    // The position in the body is not useful once the field is named
+    let message = message
+        .rsplit_once(" at line ")
+        .map_or(message.as_str(), |(message, _)| message);
+
+    let mut errors = ValidationErrors::default();
+    let missing = message
+        .strip_prefix("missing field `")
+        .and_then(|rest| rest.strip_suffix('`'));
+    match missing {
+        Some(field) if path == "." => errors.missing(field),
+        Some(field) => errors.missing(&format!("{path}.{field}")),
+        None => errors.add(&path, INVALID, message),
+    }
+    errors
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::post, Router};
+    use serde::Deserialize;
+    use tower::ServiceExt;
+
+    #[derive(Deserialize)]
+    struct Item {
+        #[allow(dead_code)]
+        done: bool,
+    }
+
+    #[derive(Deserialize)]
+    struct Todo {
+        title: String,
+        #[allow(dead_code)]
+        #[serde(default)]
+        items: Vec<Item>,
+    }
+
+    async fn post_json(content_type: &str, body: &'static str) -> (StatusCode, serde_json::Value) {
+        let app = Router::new().route(
+            "/",
+            post(|Json(todo): Json<Todo>| async move { todo.title }),
+        );
+        let response = app
+            .oneshot(
+                axum::http::Request::builder()
+                    .method("POST")
+                    .uri("/")
+                    .header("content-type", content_type)
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn test_valid_body() {
+        let (status, _) = post_json("application/json", r#"{"title": "Pay rent"}"#).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_field() {
+        let (status, body) = post_json(
+            "application/json",
+            r#"{"title": "Pay rent", "items": [{"done": true}, {"done": "yes"}]}"#,
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], VALIDATION_ERROR);
+        assert_eq!(body["errors"][0]["field"], "items[1].done");
+        assert_eq!(body["errors"][0]["code"], INVALID);
+        assert!(!body["errors"][0]["message"]
+            .as_str()
+            .unwrap()
+            .contains("line"));
+    }
+
+    #[tokio::test]
+    async fn test_missing_field() {
+        let (status, body) = post_json("application/json", "{}").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["errors"][0]["field"], "title");
+        assert_eq!(body["errors"][0]["code"], "missing");
+    }
+
+    #[tokio::test]
+    async fn test_malformed_body() {
+        let (status, body) = post_json("application/json", r#"{"title": "#).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], VALIDATION_ERROR);
+        assert!(body.get("errors").is_none());
+    }
+
+    #[test]
+    fn test_from_slice() {
+        let todo: Todo = from_slice(br#"{"title": "Pay rent"}"#).unwrap();
+        assert_eq!(todo.title, "Pay rent");
+
+        assert!(matches!(
+            from_slice::<Todo>(br#"{"title": 4}"#),
+            Err(AppError::InvalidFields(errors)) if errors.fields() == vec!["title"]
+        ));
+        assert!(matches!(
+            from_slice::<Todo>(br#"{"title": "Pay rent"} {}"#),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_missing_content_type() {
+        let (status, body) = post_json("text/plain", r#"{"title": "Pay rent"}"#).await;
+        assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(body["code"], "unsupported_media_type");
+    }
+}
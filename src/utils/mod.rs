@@ -1,3 +1,13 @@
+pub mod client_ip;
+pub mod concurrency_limit;
+pub mod dates;
 pub mod fone_validation;
+pub mod format;
+pub mod json;
 pub mod password;
-pub mod required_fields;
+pub mod rate_limit;
+pub mod request_id;
+pub mod static_json;
+pub mod token;
+pub mod totp;
+pub mod validation;
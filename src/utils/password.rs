@@ -2,7 +2,7 @@
 
 use argon2::{
     password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
-    Argon2,
+    Algorithm, Argon2, Params, Version,
 };
 use once_cell::sync::Lazy;
 use regex::Regex;
@@ -54,6 +54,46 @@ pub fn password_validation(stored_password_hash: &str, password_input: &str) ->
         .is_ok()
 }
 
+// State of a stored password hash
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PasswordHashStatus {
+    // Argon2id hash with the current parameters
+    Current,
+    // Argon2 hash of another variant, version or with weaker parameters. It still verifies
+    // and is replaced by a current hash on the next login.
+    Outdated,
+    // Value no password verifies against, like a plain text or bcrypt value left by the
+    // old signup. The user has to reset the password.
+    Malformed,
+}
+
+// Tell whether a stored hash is current, needs to be rehashed or can't be verified
+pub fn password_hash_status(stored_password_hash: &str) -> PasswordHashStatus {
+    let Ok(hash) = PasswordHash::new(stored_password_hash) else {
+        return PasswordHashStatus::Malformed;
+    };
+    let (Ok(algorithm), Ok(params)) =
+        (Algorithm::try_from(hash.algorithm), Params::try_from(&hash))
+    else {
+        return PasswordHashStatus::Malformed;
+    };
+    if hash.salt.is_none() || hash.hash.is_none() {
+        return PasswordHashStatus::Malformed;
+    }
+
+    let current = Params::default();
+    let is_current = algorithm == Algorithm::Argon2id
+        && hash.version == Some(u32::from(Version::V0x13))
+        && params.m_cost() >= current.m_cost()
+        && params.t_cost() >= current.t_cost()
+        && params.p_cost() >= current.p_cost();
+    if is_current {
+        PasswordHashStatus::Current
+    } else {
+        PasswordHashStatus::Outdated
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -127,4 +167,32 @@ mod tests {
         assert!(!password_validation("invalid_hash", "TestPassword123!"));
         assert!(!password_validation("", "TestPassword123!"));
     }
+
+    #[test]
+    fn test_password_hash_status() {
+        let hash = hash_password("TestPassword123!").unwrap();
+        assert_eq!(password_hash_status(&hash), PasswordHashStatus::Current);
+
+        let salt = SaltString::generate(&mut OsRng);
+        let weak = Argon2::new(
+            Algorithm::Argon2i,
+            Version::V0x13,
+            Params::new(4096, 1, 1, None).unwrap(),
+        )
+        .hash_password(b"TestPassword123!", &salt)
+        .unwrap()
+        .to_string();
+        assert_eq!(password_hash_status(&weak), PasswordHashStatus::Outdated);
+        assert!(password_validation(&weak, "TestPassword123!"));
+
+        assert_eq!(
+            password_hash_status("$2b$12$R9h/cIPz0gi.URNNX3kh2OPST9/PgBkqquzi.Ss7KIUgO2t0jWMUW"),
+            PasswordHashStatus::Malformed
+        );
+        assert_eq!(
+            password_hash_status("TestPassword123!"),
+            PasswordHashStatus::Malformed
+        );
+        assert_eq!(password_hash_status(""), PasswordHashStatus::Malformed);
+    }
 }
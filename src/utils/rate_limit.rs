@@ -0,0 +1,434 @@
+//! # Rate Limit
+//! Per client IP and per username limits of the authentication endpoints and of the
+//! signup availability check, counted in fixed windows. Counters are kept in process by
+//! default, or in Redis with the `redis` feature so every replica shares them.
+
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{header, request::Parts, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use jsonwebtoken::{decode, Algorithm, Validation};
+use serde::Deserialize;
+
+use crate::{
+    auth::{request_token, Claims},
+    config::{RateLimitBackend, RateLimitConfig},
+    modules::common::ErrorResponse,
+    utils::client_ip::ClientIp,
+    AppState,
+};
+
+// Endpoints counted against the limits, all of them `POST`
+const RATE_LIMITED_PATHS: [&str; 5] = [
+    "/user/signup",
+    "/user/login",
+    "/user/password",
+    "/user/password/reset",
+    "/auth/2fa/login",
+];
+
+// `GET` endpoint probing for accounts, only limited per client IP
+const AVAILABILITY_PATH: &str = "/user/availability";
+
+// `POST` endpoint creating demo users, only limited per client IP
+const DEMO_PATH: &str = "/demo/session";
+
+// Largest body read to find the username, the credentials are far smaller
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+// Keys tracked in process before the ended windows are dropped
+const MAX_TRACKED_KEYS: usize = 100_000;
+
+// Requests allowed per window, 0 disables the limit
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimit {
+    pub max: u64,
+    pub window: Duration,
+}
+
+impl RateLimit {
+    const fn new(max: u64, window_seconds: u64) -> Self {
+        Self {
+            max,
+            window: Duration::from_secs(window_seconds),
+        }
+    }
+}
+
+// Counters of the rate limits
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    // Name reported in the logs
+    fn name(&self) -> &'static str;
+
+    // Count a hit of `key`, returns the hits of its current window and the seconds
+    // until that window ends. A window starts with its first hit.
+    async fn hit(&self, key: &str, window: Duration) -> Result<(u64, u64), String>;
+}
+
+// Whole seconds until `end`, rounded up so clients don't retry too early
+fn seconds_until(end: Instant, now: Instant) -> u64 {
+    let remaining = end.saturating_duration_since(now);
+    remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0)
+}
+
+// Counters of this instance only, each replica enforces the limits on its own
+#[derive(Default)]
+pub struct MemoryStore {
+    windows: Mutex<HashMap<String, (Instant, u64)>>,
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryStore {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    async fn hit(&self, key: &str, window: Duration) -> Result<(u64, u64), String> {
+        let now = Instant::now();
+        let mut windows = self
+            .windows
+            .lock()
+            .map_err(|_| "Rate limit counters poisoned".to_string())?;
+        if windows.len() >= MAX_TRACKED_KEYS {
+            windows.retain(|_, (end, _)| *end > now);
+        }
+
+        let (end, hits) = windows.entry(key.to_string()).or_insert((now + window, 0));
+        if *end <= now {
+            *end = now + window;
+            *hits = 0;
+        }
+        *hits += 1;
+        let counted = (*hits, seconds_until(*end, now));
+        drop(windows);
+        Ok(counted)
+    }
+}
+
+// Counters shared by the replicas, expiring with their window
+#[cfg(feature = "redis")]
+pub struct RedisStore {
+    connection: redis::aio::ConnectionManager,
+    script: redis::Script,
+}
+
+#[cfg(feature = "redis")]
+impl RedisStore {
+    // Prefix of the counter keys
+    const KEY_PREFIX: &'static str = "todo_app:rate_limit:";
+
+    // Increment and expiry run as one script so a counter can't be left without TTL
+    const HIT_SCRIPT: &'static str = r"
+        local hits = redis.call('INCR', KEYS[1])
+        if hits == 1 then
+            redis.call('EXPIRE', KEYS[1], ARGV[1])
+        end
+        return {hits, redis.call('TTL', KEYS[1])}
+    ";
+
+    pub async fn connect(url: &str) -> Result<Self, String> {
+        let client = redis::Client::open(url).map_err(|e| format!("Invalid REDIS_URL: {e}"))?;
+        let connection = client
+            .get_connection_manager()
+            .await
+            .map_err(|e| format!("Failed to connect to Redis: {e}"))?;
+        Ok(Self {
+            connection,
+            script: redis::Script::new(Self::HIT_SCRIPT),
+        })
+    }
+}
+
+#[cfg(feature = "redis")]
+#[async_trait]
+impl RateLimitStore for RedisStore {
+    fn name(&self) -> &'static str {
+        "redis"
+    }
+
+    async fn hit(&self, key: &str, window: Duration) -> Result<(u64, u64), String> {
+        let mut connection = self.connection.clone();
+        let (hits, ttl): (u64, i64) = self
+            .script
+            .key(format!("{}{key}", Self::KEY_PREFIX))
+            .arg(window.as_secs())
+            .invoke_async(&mut connection)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok((hits, u64::try_from(ttl).unwrap_or(0)))
+    }
+}
+
+// Limits of the authentication endpoints
+#[derive(Clone)]
+pub struct RateLimiter {
+    store: Arc<dyn RateLimitStore>,
+    per_ip: RateLimit,
+    per_username: RateLimit,
+}
+
+impl RateLimiter {
+    pub const fn new(
+        store: Arc<dyn RateLimitStore>,
+        per_ip: RateLimit,
+        per_username: RateLimit,
+    ) -> Self {
+        Self {
+            store,
+            per_ip,
+            per_username,
+        }
+    }
+
+    // Count a request to `path` against the limits of its client and username, returns
+    // the seconds to wait when one is exceeded. Store errors let the request through so
+    // an outage of the store doesn't lock the users out.
+    async fn check(&self, path: &str, ip: IpAddr, username: Option<&str>) -> Option<u64> {
+        let mut keys = vec![(format!("{path}:ip:{ip}"), self.per_ip)];
+        if let Some(username) = username {
+            keys.push((format!("{path}:username:{username}"), self.per_username));
+        }
+
+        let mut retry_after = None;
+        for (key, limit) in keys {
+            if limit.max == 0 {
+                continue;
+            }
+            match self.store.hit(&key, limit.window).await {
+                Ok((hits, reset)) if hits > limit.max => {
+                    retry_after = retry_after.max(Some(reset.max(1)));
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!(
+                    "Error counting rate limit in {} store: {}",
+                    self.store.name(),
+                    e
+                ),
+            }
+        }
+        retry_after
+    }
+}
+
+#[cfg(not(feature = "redis"))]
+#[allow(clippy::unused_async)]
+async fn redis_store(_url: &str) -> Result<Arc<dyn RateLimitStore>, String> {
+    Err("RATE_LIMIT_BACKEND=redis needs the application built with the redis feature".to_string())
+}
+
+#[cfg(feature = "redis")]
+async fn redis_store(url: &str) -> Result<Arc<dyn RateLimitStore>, String> {
+    Ok(Arc::new(RedisStore::connect(url).await?))
+}
+
+// Limiter of the configuration, `None` when its backend is `off`.
+// The `ip_*` settings limit each client IP and the `username_*` ones each username.
+pub async fn rate_limiter_from_config(
+    config: &RateLimitConfig,
+) -> Result<Option<RateLimiter>, String> {
+    let store: Arc<dyn RateLimitStore> = match config.backend {
+        RateLimitBackend::Off => return Ok(None),
+        RateLimitBackend::Memory => Arc::new(MemoryStore::default()),
+        RateLimitBackend::Redis => redis_store(&config.redis_url).await?,
+    };
+
+    Ok(Some(RateLimiter::new(
+        store,
+        RateLimit::new(config.ip_max, config.ip_window_seconds),
+        RateLimit::new(config.username_max, config.username_window_seconds),
+    )))
+}
+
+#[derive(Deserialize)]
+struct UsernameBody {
+    username: Option<String>,
+}
+
+// Username of a signup or login body, case-insensitive so variants share a counter
+fn body_username(body: &[u8]) -> Option<String> {
+    serde_json::from_slice::<UsernameBody>(body)
+        .ok()
+        .and_then(|body| body.username)
+        .map(|username| username.trim().to_lowercase())
+        .filter(|username| !username.is_empty())
+}
+
+// Account of an authenticated request, its token carries no username
+fn token_user(parts: &Parts, state: &AppState) -> Option<String> {
+    let token = request_token(&parts.method, &parts.headers, state).ok()?;
+    decode::<Claims>(
+        &token,
+        &state.decoding_key,
+        &Validation::new(Algorithm::HS256),
+    )
+    .ok()
+    .map(|token_data| format!("#{}", token_data.claims.user_id))
+}
+
+// Whether the request is counted against the limits
+fn is_rate_limited(method: &Method, path: &str) -> bool {
+    (method == Method::POST && RATE_LIMITED_PATHS.contains(&path))
+        || (method == Method::GET && path == AVAILABILITY_PATH)
+        || (method == Method::POST && path == DEMO_PATH)
+}
+
+// Middleware rejecting the authentication requests over their limits with a 429 and the
+// seconds to wait in `Retry-After`. The body is buffered to read the username.
+pub async fn rate_limit_auth(
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path().to_string();
+    let Some(rate_limiter) = state.rate_limiter.as_ref() else {
+        return next.run(request).await;
+    };
+    if !is_rate_limited(request.method(), &path) {
+        return next.run(request).await;
+    }
+
+    let (parts, body) = request.into_parts();
+    let Ok(body) = to_bytes(body, MAX_BODY_BYTES).await else {
+        return (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            Json(ErrorResponse::new("Request body too large")),
+        )
+            .into_response();
+    };
+    // A token only names the account changing its password, a login or signup with any
+    // token still counts against the username it tries
+    let username = match path.as_str() {
+        "/user/password" => token_user(&parts, &state),
+        AVAILABILITY_PATH | DEMO_PATH => None,
+        _ => body_username(&body),
+    };
+
+    if let Some(retry_after) = rate_limiter
+        .check(&path, client_ip, username.as_deref())
+        .await
+    {
+        tracing::warn!("Rate limit of {} reached by {}", path, client_ip);
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::RETRY_AFTER, retry_after.to_string())],
+            Json(ErrorResponse::new("Too many attempts, please retry later")),
+        )
+            .into_response();
+    }
+
+    next.run(Request::from_parts(parts, Body::from(body))).await
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+
+    fn limiter(per_ip: u64, per_username: u64) -> RateLimiter {
+        let window = Duration::from_secs(60);
+        RateLimiter::new(
+            Arc::new(MemoryStore::default()),
+            RateLimit {
+                max: per_ip,
+                window,
+            },
+            RateLimit {
+                max: per_username,
+                window,
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_window() {
+        let store = MemoryStore::default();
+        let window = Duration::from_secs(60);
+        assert_eq!(store.hit("key", window).await.unwrap(), (1, 60));
+        assert_eq!(store.hit("key", window).await.unwrap().0, 2);
+        assert_eq!(store.hit("other", window).await.unwrap().0, 1);
+
+        // An ended window starts over
+        assert_eq!(store.hit("short", Duration::ZERO).await.unwrap().0, 1);
+        assert_eq!(store.hit("short", Duration::ZERO).await.unwrap().0, 1);
+    }
+
+    #[tokio::test]
+    async fn test_ip_limit() {
+        let limiter = limiter(2, 0);
+        let client = ip("203.0.113.9");
+        assert_eq!(limiter.check("/user/login", client, None).await, None);
+        assert_eq!(limiter.check("/user/login", client, None).await, None);
+        assert_eq!(limiter.check("/user/login", client, None).await, Some(60));
+
+        // Other clients and endpoints keep their own counters
+        assert_eq!(
+            limiter.check("/user/login", ip("203.0.113.10"), None).await,
+            None
+        );
+        assert_eq!(limiter.check("/user/signup", client, None).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_username_limit() {
+        let limiter = limiter(0, 1);
+        assert_eq!(
+            limiter
+                .check("/user/login", ip("203.0.113.9"), Some("alice"))
+                .await,
+            None
+        );
+        // Spreading the attempts over addresses doesn't help
+        assert_eq!(
+            limiter
+                .check("/user/login", ip("198.51.100.7"), Some("alice"))
+                .await,
+            Some(60)
+        );
+        assert_eq!(
+            limiter
+                .check("/user/login", ip("198.51.100.7"), Some("bob"))
+                .await,
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_rate_limited() {
+        assert!(is_rate_limited(&Method::POST, "/user/login"));
+        assert!(is_rate_limited(&Method::GET, "/user/availability"));
+        assert!(is_rate_limited(&Method::POST, "/demo/session"));
+        assert!(!is_rate_limited(&Method::GET, "/demo/session"));
+        assert!(!is_rate_limited(&Method::GET, "/user/login"));
+        assert!(!is_rate_limited(&Method::POST, "/user/availability"));
+        assert!(!is_rate_limited(&Method::GET, "/user"));
+    }
+
+    #[test]
+    fn test_body_username() {
+        assert_eq!(
+            body_username(br#"{"username":" Alice ","password":"secret"}"#),
+            Some("alice".to_string())
+        );
+        assert_eq!(body_username(br#"{"username":""}"#), None);
+        assert_eq!(body_username(br#"{"password":"secret"}"#), None);
+        assert_eq!(body_username(b"not json"), None);
+    }
+}
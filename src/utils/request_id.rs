@@ -0,0 +1,177 @@
+//! # Request Id
+//! Id of each request, taken from the `X-Request-Id` header of the caller or generated,
+//! and sent back in the same header. The request span logs it with every line and the
+//! error responses carry it, so a reported id leads to the logs of its request.
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+// Longest id accepted from a caller
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+// Random bytes of the generated ids
+const REQUEST_ID_BYTES: usize = 16;
+
+tokio::task_local! {
+    static CURRENT_REQUEST_ID: RequestId;
+}
+
+// Id of a request, in the extensions of the request
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequestId(String);
+
+impl RequestId {
+    // Id sent by the caller, only kept when short and made of characters safe to log
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let valid = !value.is_empty()
+            && value.len() <= MAX_REQUEST_ID_LENGTH
+            && value
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b':'));
+        valid.then(|| Self(value.to_string()))
+    }
+
+    pub fn generate() -> Self {
+        let mut bytes = [0u8; REQUEST_ID_BYTES];
+        OsRng.fill_bytes(&mut bytes);
+        Self(hex::encode(bytes))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+// Id of the request being handled, `None` outside of a request
+pub fn current_request_id() -> Option<String> {
+    CURRENT_REQUEST_ID
+        .try_with(|request_id| request_id.0.clone())
+        .ok()
+}
+
+// Assign its id to a request, readable by the handlers for the duration of the request
+pub async fn assign_request_id(mut request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(RequestId::parse)
+        .unwrap_or_else(RequestId::generate);
+    request.extensions_mut().insert(request_id.clone());
+
+    let header = HeaderValue::from_str(request_id.as_str()).ok();
+    let mut response = CURRENT_REQUEST_ID
+        .scope(request_id, next.run(request))
+        .await;
+    if let Some(header) = header {
+        response.headers_mut().insert(REQUEST_ID_HEADER, header);
+    }
+    response
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::modules::common::ErrorResponse;
+    use axum::{body::Body, middleware, routing::get, Router};
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_parse_request_id() {
+        assert_eq!(
+            RequestId::parse(" abc-123_x.y:z ").unwrap().as_str(),
+            "abc-123_x.y:z"
+        );
+        assert!(RequestId::parse("").is_none());
+        assert!(RequestId::parse("id with spaces").is_none());
+        assert!(RequestId::parse("line\nbreak").is_none());
+        assert!(RequestId::parse(&"a".repeat(MAX_REQUEST_ID_LENGTH + 1)).is_none());
+    }
+
+    #[test]
+    fn test_generate_request_id() {
+        let request_id = RequestId::generate();
+        assert_eq!(request_id.as_str().len(), REQUEST_ID_BYTES * 2);
+        assert_ne!(request_id, RequestId::generate());
+        assert!(current_request_id().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_assign_request_id() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async { current_request_id().unwrap_or_default() }),
+            )
+            .layer(middleware::from_fn(assign_request_id));
+
+        let response = app
+            .clone()
+            .oneshot(
+                axum::http::Request::builder()
+                    .uri("/")
+                    .header(REQUEST_ID_HEADER, "caller-id")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            response.headers().get(REQUEST_ID_HEADER).unwrap(),
+            "caller-id"
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"caller-id");
+
+        let response = app
+            .oneshot(
+                axum::http::Request::builder()
+                    .uri("/")
+                    .header(REQUEST_ID_HEADER, "not valid")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let generated = response.headers().get(REQUEST_ID_HEADER).unwrap();
+        assert_eq!(generated.len(), REQUEST_ID_BYTES * 2);
+    }
+
+    #[tokio::test]
+    async fn test_error_response_carries_request_id() {
+        let app = Router::new()
+            .route("/", get(|| async { ErrorResponse::new("Invalid todo") }))
+            .layer(middleware::from_fn(assign_request_id));
+
+        let response = app
+            .oneshot(
+                axum::http::Request::builder()
+                    .uri("/")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let request_id = response.headers().get(REQUEST_ID_HEADER).unwrap().clone();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["request_id"], request_id.to_str().unwrap());
+        assert!(serde_json::to_value(ErrorResponse::new("Invalid todo"))
+            .unwrap()
+            .get("request_id")
+            .is_none());
+    }
+}
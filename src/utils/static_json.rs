@@ -0,0 +1,137 @@
+use axum::{
+    body::Bytes,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, MethodRouter},
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+// Clients revalidate on every use, the ETag makes that a cheap 304
+const CACHE_CONTROL: &str = "public, no-cache";
+
+// JSON document serialized once, served with a strong ETag
+#[derive(Clone, Debug)]
+pub struct StaticJson {
+    body: Bytes,
+    etag: HeaderValue,
+}
+
+impl StaticJson {
+    pub fn new<T: Serialize>(value: &T) -> Result<Self, serde_json::Error> {
+        let body = serde_json::to_vec(value)?;
+        let etag = format!("\"{}\"", hex::encode(Sha256::digest(&body)));
+
+        Ok(Self {
+            body: Bytes::from(body),
+            // Quoted hex digests are always valid header values
+            etag: HeaderValue::from_str(&etag).unwrap_or_else(|_| HeaderValue::from_static("\"\"")),
+        })
+    }
+
+    // Whether the `If-None-Match` header of the request matches the document
+    fn is_fresh(&self, headers: &HeaderMap) -> bool {
+        let Some(if_none_match) = headers
+            .get(header::IF_NONE_MATCH)
+            .and_then(|value| value.to_str().ok())
+        else {
+            return false;
+        };
+
+        // `If-None-Match` uses the weak comparison, so `W/` prefixes are ignored
+        if_none_match.split(',').map(str::trim).any(|tag| {
+            tag == "*" || tag.trim_start_matches("W/").as_bytes() == self.etag.as_bytes()
+        })
+    }
+
+    // Full document, or `304 Not Modified` when the client copy is current
+    pub fn respond(&self, headers: &HeaderMap) -> Response {
+        let cache_headers = [
+            (header::ETAG, self.etag.clone()),
+            (
+                header::CACHE_CONTROL,
+                HeaderValue::from_static(CACHE_CONTROL),
+            ),
+        ];
+
+        if self.is_fresh(headers) {
+            return (StatusCode::NOT_MODIFIED, cache_headers).into_response();
+        }
+
+        (
+            StatusCode::OK,
+            cache_headers,
+            [(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            )],
+            self.body.clone(),
+        )
+            .into_response()
+    }
+}
+
+// GET route serving a static JSON document
+pub fn static_json_route<S>(document: StaticJson) -> MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    get(move |headers: HeaderMap| async move { document.respond(&headers) })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn document() -> StaticJson {
+        StaticJson::new(&serde_json::json!({"openapi": "3.1.0"})).unwrap()
+    }
+
+    fn if_none_match(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_etag_is_strong_and_stable() {
+        let etag = document().etag.to_str().unwrap().to_string();
+        assert!(etag.starts_with('"') && etag.ends_with('"'));
+        assert_eq!(etag.len(), 66);
+        assert_eq!(document().etag, HeaderValue::from_str(&etag).unwrap());
+    }
+
+    #[test]
+    fn test_respond_without_validator() {
+        let response = document().respond(&HeaderMap::new());
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().contains_key(header::ETAG));
+        assert_eq!(
+            response.headers().get(header::CACHE_CONTROL).unwrap(),
+            CACHE_CONTROL
+        );
+    }
+
+    #[test]
+    fn test_respond_not_modified() {
+        let document = document();
+        let etag = document.etag.to_str().unwrap().to_string();
+
+        for value in [
+            etag.clone(),
+            format!("W/{etag}"),
+            format!("\"x\", {etag}"),
+            "*".to_string(),
+        ] {
+            let response = document.respond(&if_none_match(&value));
+            assert_eq!(response.status(), StatusCode::NOT_MODIFIED, "{value}");
+        }
+    }
+
+    #[test]
+    fn test_respond_with_stale_validator() {
+        let response = document().respond(&if_none_match("\"stale\""));
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
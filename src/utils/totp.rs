@@ -0,0 +1,163 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
+// Length of a time step, a code is valid for this many seconds
+const STEP_SECONDS: i64 = 30;
+// Digits of a code
+const DIGITS: u32 = 6;
+// Random bytes of a secret, the size of a SHA-1 digest as advised by RFC 4226
+const SECRET_BYTES: usize = 20;
+// Steps before and after the current one accepted, to allow for clock drift
+const ALLOWED_DRIFT: i64 = 1;
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+// Generate a random TOTP secret
+pub fn generate_secret() -> Vec<u8> {
+    let mut secret = vec![0u8; SECRET_BYTES];
+    OsRng.fill_bytes(&mut secret);
+    secret
+}
+
+// Encode bytes as unpadded RFC 4648 base32, the form authenticator apps expect secrets in
+pub fn base32_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for byte in bytes {
+        buffer = (buffer << 8) | u32::from(*byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(char::from(
+                BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize],
+            ));
+        }
+        buffer &= (1 << bits) - 1;
+    }
+    if bits > 0 {
+        encoded.push(char::from(
+            BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize],
+        ));
+    }
+    encoded
+}
+
+// Percent encode a label or parameter of an otpauth URI
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| {
+            if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+                char::from(byte).to_string()
+            } else {
+                format!("%{byte:02X}")
+            }
+        })
+        .collect()
+}
+
+// URI registering a secret in an authenticator app, usually shown as a QR code
+pub fn otpauth_uri(issuer: &str, account: &str, secret: &[u8]) -> String {
+    let issuer = percent_encode(issuer);
+    format!(
+        "otpauth://totp/{issuer}:{}?secret={}&issuer={issuer}&algorithm=SHA1&digits={DIGITS}&period={STEP_SECONDS}",
+        percent_encode(account),
+        base32_encode(secret),
+    )
+}
+
+// HOTP value of a counter, RFC 4226
+fn hotp(secret: &[u8], counter: u64) -> Option<u32> {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).ok()?;
+    mac.update(&counter.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    let offset = usize::from(digest[digest.len() - 1] & 0x0f);
+    let value = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+    Some(value % 10u32.pow(DIGITS))
+}
+
+// Check a TOTP code at `unix_time`, RFC 6238. Returns the time step it matched, which
+// callers record to refuse the code being used twice.
+pub fn verify_code(secret: &[u8], code: &str, unix_time: i64) -> Option<i64> {
+    let code = code.trim();
+    if code.len() != DIGITS as usize || !code.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    let code: u32 = code.parse().ok()?;
+
+    let current = unix_time.div_euclid(STEP_SECONDS);
+    (current - ALLOWED_DRIFT..=current + ALLOWED_DRIFT).find(|step| {
+        u64::try_from(*step)
+            .ok()
+            .and_then(|counter| hotp(secret, counter))
+            == Some(code)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Secret of the RFC 6238 SHA-1 test vectors
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    #[test]
+    fn test_base32_encode() {
+        assert_eq!(base32_encode(b""), "");
+        assert_eq!(base32_encode(b"f"), "MY");
+        assert_eq!(base32_encode(b"foobar"), "MZXW6YTBOI");
+        assert_eq!(
+            base32_encode(RFC_SECRET),
+            "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ"
+        );
+    }
+
+    #[test]
+    fn test_verify_code_matches_rfc_vectors() {
+        assert_eq!(verify_code(RFC_SECRET, "287082", 59), Some(1));
+        assert_eq!(
+            verify_code(RFC_SECRET, "081804", 1_111_111_109),
+            Some(37_037_036)
+        );
+        assert_eq!(
+            verify_code(RFC_SECRET, " 005924 ", 1_234_567_890),
+            Some(41_152_263)
+        );
+    }
+
+    #[test]
+    fn test_verify_code_allows_clock_drift() {
+        assert_eq!(verify_code(RFC_SECRET, "287082", 59 + 30), Some(1));
+        assert_eq!(verify_code(RFC_SECRET, "287082", 59 + 60), None);
+    }
+
+    #[test]
+    fn test_verify_code_rejects_malformed_codes() {
+        assert_eq!(verify_code(RFC_SECRET, "28708", 59), None);
+        assert_eq!(verify_code(RFC_SECRET, "+87082", 59), None);
+        assert_eq!(verify_code(RFC_SECRET, "abcdef", 59), None);
+    }
+
+    #[test]
+    fn test_otpauth_uri() {
+        assert_eq!(
+            otpauth_uri("Todo App", "ana@example.com", RFC_SECRET),
+            "otpauth://totp/Todo%20App:ana%40example.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ\
+             &issuer=Todo%20App&algorithm=SHA1&digits=6&period=30"
+        );
+    }
+
+    #[test]
+    fn test_generate_secret_is_random() {
+        assert_eq!(generate_secret().len(), SECRET_BYTES);
+        assert_ne!(generate_secret(), generate_secret());
+    }
+}
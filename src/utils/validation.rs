@@ -0,0 +1,260 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+// Code of the fields left empty
+pub const MISSING: &str = "missing";
+
+/// Problem of a field of a request
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug, PartialEq, Eq)]
+pub struct FieldError {
+    pub field: String,
+    /// Machine readable code, e.g. `missing` or `invalid_email`
+    pub code: String,
+    pub message: String,
+}
+
+// Every problem of a request, collected so they are all reported at once
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValidationErrors(Vec<FieldError>);
+
+impl ValidationErrors {
+    // Record a problem of a field
+    pub fn add(&mut self, field: &str, code: &str, message: impl Into<String>) {
+        self.0.push(FieldError {
+            field: field.to_string(),
+            code: code.to_string(),
+            message: message.into(),
+        });
+    }
+
+    // Record a field left empty
+    pub fn missing(&mut self, field: &str) {
+        self.add(field, MISSING, format!("{field} is required"));
+    }
+
+    // Record the problems of another validation
+    pub fn extend(&mut self, other: Self) {
+        self.0.extend(other.0);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    // Fields with a problem, in the order they were recorded
+    pub fn fields(&self) -> Vec<&str> {
+        self.0.iter().map(|error| error.field.as_str()).collect()
+    }
+
+    pub fn into_errors(self) -> Vec<FieldError> {
+        self.0
+    }
+
+    // `Err` with the problems when there are any
+    pub fn check(self) -> Result<(), Self> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+// "Missing required fields: a, b" when fields are only missing, the messages otherwise
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.iter().all(|error| error.code == MISSING) {
+            write!(f, "Missing required fields: {}", self.fields().join(", "))
+        } else {
+            let messages: Vec<&str> = self.0.iter().map(|error| error.message.as_str()).collect();
+            f.write_str(&messages.join("; "))
+        }
+    }
+}
+
+// Check that every required field is set, a blank string or a zero counts as missing.
+// Every missing field is reported.
+pub fn validate_required_fields<T, R>(
+    data: &T,
+    required_fields: Vec<&str>,
+) -> Result<R, ValidationErrors>
+where
+    T: Serialize,
+    R: for<'de> Deserialize<'de>,
+{
+    let mut errors = ValidationErrors::default();
+    let json_value = match serde_json::to_value(data) {
+        Ok(value) => value,
+        Err(e) => {
+            errors.add("body", "invalid", format!("Failed to serialize data: {e}"));
+            return Err(errors);
+        }
+    };
+
+    for field in required_fields {
+        match json_value.get(field) {
+            Some(Value::String(s)) if s.trim().is_empty() => errors.missing(field),
+            Some(Value::Null) | None => errors.missing(field),
+            Some(Value::Number(n)) if n.as_i64() == Some(0) => errors.missing(field),
+            _ => {}
+        }
+    }
+    errors.check()?;
+
+    serde_json::from_value(json_value).map_err(|e| {
+        let mut errors = ValidationErrors::default();
+        errors.add(
+            "body",
+            "invalid",
+            format!("Failed to deserialize data: {e}"),
+        );
+        errors
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::manual_string_new)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    struct TestData {
+        name: Option<String>,
+        email: Option<String>,
+        age: Option<i32>,
+        active: Option<bool>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug)]
+    struct ValidatedTestData {
+        name: String,
+        email: String,
+        age: i32,
+        active: bool,
+    }
+
+    #[test]
+    fn test_validate_required_fields_success() {
+        let data = TestData {
+            name: Some("John".to_string()),
+            email: Some("john@example.com".to_string()),
+            age: Some(25),
+            active: Some(true),
+        };
+
+        let result: Result<ValidatedTestData, ValidationErrors> =
+            validate_required_fields(&data, vec!["name", "email"]);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_validate_required_fields_missing_field() {
+        let data = TestData {
+            name: Some("John".to_string()),
+            email: None,
+            age: Some(25),
+            active: Some(true),
+        };
+
+        let result: Result<ValidatedTestData, ValidationErrors> =
+            validate_required_fields(&data, vec!["name", "email"]);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().fields(), vec!["email"]);
+    }
+
+    #[test]
+    fn test_validate_required_fields_empty_string() {
+        let data = TestData {
+            name: Some("".to_string()),
+            email: Some("john@example.com".to_string()),
+            age: Some(25),
+            active: Some(true),
+        };
+
+        let result: Result<ValidatedTestData, ValidationErrors> =
+            validate_required_fields(&data, vec!["name", "email"]);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().fields(), vec!["name"]);
+    }
+
+    #[test]
+    fn test_validate_required_fields_whitespace_string() {
+        let data = TestData {
+            name: Some("   ".to_string()),
+            email: Some("john@example.com".to_string()),
+            age: Some(25),
+            active: Some(true),
+        };
+
+        let result: Result<ValidatedTestData, ValidationErrors> =
+            validate_required_fields(&data, vec!["name", "email"]);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().fields(), vec!["name"]);
+    }
+
+    #[test]
+    fn test_validate_required_fields_zero_number() {
+        let data = TestData {
+            name: Some("John".to_string()),
+            email: Some("john@example.com".to_string()),
+            age: Some(0),
+            active: Some(true),
+        };
+
+        let result: Result<ValidatedTestData, ValidationErrors> =
+            validate_required_fields(&data, vec!["name", "email", "age"]);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().fields(), vec!["age"]);
+    }
+
+    #[test]
+    fn test_validate_required_fields_no_required_fields() {
+        let data = TestData {
+            name: Some("John".to_string()),
+            email: Some("john@example.com".to_string()),
+            age: Some(25),
+            active: Some(true),
+        };
+
+        let result: Result<ValidatedTestData, ValidationErrors> =
+            validate_required_fields(&data, vec![]);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_validate_required_fields_reports_every_missing_field() {
+        let data = TestData {
+            name: None,
+            email: Some(" ".to_string()),
+            age: Some(0),
+            active: Some(true),
+        };
+
+        let errors =
+            validate_required_fields::<_, ValidatedTestData>(&data, vec!["name", "email", "age"])
+                .unwrap_err();
+        assert_eq!(errors.fields(), vec!["name", "email", "age"]);
+        assert_eq!(
+            errors.to_string(),
+            "Missing required fields: name, email, age"
+        );
+        assert_eq!(errors.into_errors()[0].code, MISSING);
+    }
+
+    #[test]
+    fn test_validation_errors_display() {
+        let mut errors = ValidationErrors::default();
+        assert!(errors.clone().check().is_ok());
+
+        errors.missing("username");
+        errors.add("email", "invalid_email", "Email is not valid");
+        assert_eq!(
+            errors.to_string(),
+            "username is required; Email is not valid"
+        );
+        assert!(errors.check().is_err());
+    }
+}
@@ -0,0 +1,291 @@
+//! # Background Workers
+//!
+//! Background workers only start once the database is reachable and its schema
+//! is in place. Each worker is tracked in a registry surfaced by `/ready`.
+//!
+//! Every instance runs the same workers. Jobs that must not run on several instances
+//! at once go through `run_exclusive`, backed by a Postgres advisory lock.
+
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, PoisonError, RwLock,
+    },
+    time::Duration,
+};
+
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+use utoipa::ToSchema;
+
+// Tables created by `db/init.sql`, workers wait until all of them exist
+const REQUIRED_TABLES: &[&str] = &[
+    "users",
+    "lists",
+    "todos",
+    "tags",
+    "todo_tags",
+    "list_transfers",
+    "guest_invitations",
+    "todo_items",
+    "outbox_events",
+    "reminders",
+    "email_verifications",
+    "stale_report_subscriptions",
+    "todo_list_events",
+    "sessions",
+    "user_preferences",
+    "user_two_factor",
+    "two_factor_recovery_codes",
+    "two_factor_challenges",
+    "todo_assignments",
+    "oauth_states",
+    "oauth_accounts",
+    "list_wip_limits",
+    "banned_terms",
+    "moderation_flags",
+    "audit_log",
+    "security_webhooks",
+    "known_devices",
+    "list_inbound_addresses",
+    "invite_codes",
+    "password_resets",
+    "password_hash_audits",
+    "workspace_permission_templates",
+    "api_keys",
+    "api_key_signatures",
+    "data_exports",
+];
+
+// Delay between two startup checks while the database is not ready
+const STARTUP_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+// Long running task of a worker, an error stops the worker
+pub type WorkerTask = Pin<Box<dyn Future<Output = Result<(), String>> + Send>>;
+
+/// State of a background worker
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkerState {
+    /// Waiting for the database to be ready
+    Waiting,
+    /// Running
+    Running,
+    /// Finished on its own
+    Stopped,
+    /// Stopped with an error or a panic
+    Failed,
+}
+
+/// Status of a background worker
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct WorkerStatus {
+    /// Worker name
+    pub name: String,
+    /// Current state
+    pub state: WorkerState,
+    /// Error of a failed worker
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+// Registry of the background workers, shared with the readiness endpoint
+#[derive(Clone, Default)]
+pub struct WorkerRegistry {
+    workers: Arc<RwLock<BTreeMap<&'static str, WorkerStatus>>>,
+    started: Arc<AtomicBool>,
+}
+
+impl WorkerRegistry {
+    fn set(&self, name: &'static str, state: WorkerState, detail: Option<String>) {
+        self.workers
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(
+                name,
+                WorkerStatus {
+                    name: name.to_string(),
+                    state,
+                    detail,
+                },
+            );
+    }
+
+    // Status of every registered worker, ordered by name
+    pub fn snapshot(&self) -> Vec<WorkerStatus> {
+        self.workers
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    // Whether the startup checks passed
+    pub fn is_started(&self) -> bool {
+        self.started.load(Ordering::Acquire)
+    }
+
+    // Ready once started with every worker running
+    pub fn is_ready(&self) -> bool {
+        self.is_started()
+            && self
+                .snapshot()
+                .iter()
+                .all(|worker| worker.state == WorkerState::Running)
+    }
+
+    // Spawn a worker and track how it ends
+    fn spawn(&self, name: &'static str, task: WorkerTask) {
+        self.set(name, WorkerState::Running, None);
+        tracing::info!("Worker {} started", name);
+
+        let registry = self.clone();
+        let handle = tokio::spawn(task);
+        tokio::spawn(async move {
+            match handle.await {
+                Ok(Ok(())) => {
+                    tracing::info!("Worker {} stopped", name);
+                    registry.set(name, WorkerState::Stopped, None);
+                }
+                Ok(Err(e)) => {
+                    tracing::error!("Worker {} failed: {}", name, e);
+                    registry.set(name, WorkerState::Failed, Some(e));
+                }
+                Err(e) => {
+                    tracing::error!("Worker {} panicked: {}", name, e);
+                    registry.set(
+                        name,
+                        WorkerState::Failed,
+                        Some("Worker panicked".to_string()),
+                    );
+                }
+            }
+        });
+    }
+}
+
+// Tables of `REQUIRED_TABLES` missing from the database
+async fn missing_tables(pool: &Pool<Postgres>) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar::<_, String>(
+        "SELECT t.name FROM UNNEST($1::TEXT[]) AS t(name)
+         WHERE to_regclass('public.' || t.name) IS NULL",
+    )
+    .bind(REQUIRED_TABLES)
+    .fetch_all(pool)
+    .await
+}
+
+// Wait until the database answers and its schema is in place
+async fn wait_for_database(pool: &Pool<Postgres>) {
+    loop {
+        match missing_tables(pool).await {
+            Ok(missing) if missing.is_empty() => return,
+            Ok(missing) => tracing::warn!(
+                "Database schema incomplete, missing tables: {}",
+                missing.join(", ")
+            ),
+            Err(e) => tracing::warn!("Database not ready: {}", e),
+        }
+        tokio::time::sleep(STARTUP_RETRY_DELAY).await;
+    }
+}
+
+// Run `job` on a single instance at a time. The job runs while this instance holds the
+// transaction scoped advisory lock named `name`, other instances skip their run instead
+// of waiting for it. Returns `None` when another instance holds the lock.
+// The lock is released with the transaction, also when the job is cancelled or panics.
+pub async fn run_exclusive<F, T>(
+    pool: &Pool<Postgres>,
+    name: &str,
+    job: F,
+) -> Result<Option<T>, sqlx::Error>
+where
+    F: Future<Output = T>,
+{
+    let mut tx = pool.begin().await?;
+    let acquired = sqlx::query_scalar::<_, bool>("SELECT pg_try_advisory_xact_lock(hashtext($1))")
+        .bind(name)
+        .fetch_one(&mut *tx)
+        .await?;
+    if !acquired {
+        return Ok(None);
+    }
+
+    let output = job.await;
+    tx.commit().await?;
+    Ok(Some(output))
+}
+
+// Register the workers, then start them once the database is ready
+pub async fn start_workers(
+    pool: Pool<Postgres>,
+    registry: WorkerRegistry,
+    workers: Vec<(&'static str, WorkerTask)>,
+) {
+    for (name, _) in &workers {
+        registry.set(name, WorkerState::Waiting, None);
+    }
+
+    wait_for_database(&pool).await;
+
+    for (name, task) in workers {
+        registry.spawn(name, task);
+    }
+    registry.started.store(true, Ordering::Release);
+    tracing::info!("Background workers started");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_registry_tracks_worker_outcomes() {
+        let registry = WorkerRegistry::default();
+        assert!(!registry.is_ready());
+
+        registry.spawn("failing", Box::pin(async { Err("boom".to_string()) }));
+        registry.spawn("finished", Box::pin(async { Ok(()) }));
+        registry.started.store(true, Ordering::Release);
+
+        for _ in 0..100 {
+            if registry
+                .snapshot()
+                .iter()
+                .all(|worker| worker.state != WorkerState::Running)
+            {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let workers = registry.snapshot();
+        assert_eq!(workers[0].name, "failing");
+        assert_eq!(workers[0].state, WorkerState::Failed);
+        assert_eq!(workers[0].detail.as_deref(), Some("boom"));
+        assert_eq!(workers[1].state, WorkerState::Stopped);
+        assert!(!registry.is_ready());
+    }
+
+    #[tokio::test]
+    async fn test_registry_ready_with_running_workers() {
+        let registry = WorkerRegistry::default();
+        registry.spawn("idle", Box::pin(std::future::pending()));
+        assert!(!registry.is_ready());
+
+        registry.started.store(true, Ordering::Release);
+        assert!(registry.is_ready());
+    }
+
+    #[test]
+    fn test_required_tables_are_unique() {
+        let mut tables = REQUIRED_TABLES.to_vec();
+        tables.sort_unstable();
+        tables.dedup();
+        assert_eq!(tables.len(), REQUIRED_TABLES.len());
+    }
+}